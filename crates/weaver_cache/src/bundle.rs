@@ -0,0 +1,344 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Self-contained registry bundles for air-gapped environments.
+//!
+//! A bundle is a tar archive (`.tar`, `.tar.gz`, `.tgz`, or `.tar.zst`)
+//! containing the registries loaded by a command, fetched at their exact
+//! revision, and optionally the templates and the policies to use with them:
+//!
+//! ```text
+//! weaver-bundle/
+//!   weaver_bundle.yaml   # The manifest of the bundle
+//!   registries/main/     # The first registry
+//!   registries/main.1/   # The next registries, if any
+//!   templates/           # Optional
+//!   policies/            # Optional
+//! ```
+//!
+//! A bundle is a local archive, so it can be used as a registry path without
+//! any network access: the bundle itself resolves to its first registry, and
+//! the other registries are selected with the sub-folder syntax (e.g.
+//! `bundle.tar.gz[registries/main.1]`).
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Error::InvalidBundle;
+use crate::{ArchiveFormat, Error, RegistryRepo};
+
+/// The manifest of a bundle, at the root of the bundle.
+pub const BUNDLE_MANIFEST: &str = "weaver_bundle.yaml";
+/// The top-level directory of a bundle (skipped when the bundle is unpacked).
+const BUNDLE_ROOT: &str = "weaver-bundle";
+/// The version of the bundle format.
+const BUNDLE_VERSION: u32 = 1;
+/// The directory of the templates in a bundle.
+const TEMPLATES_DIR: &str = "templates";
+/// The directory of the policies in a bundle.
+const POLICIES_DIR: &str = "policies";
+
+/// The manifest of a bundle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BundleManifest {
+    /// The version of the bundle format.
+    pub version: u32,
+    /// The bundled registries, the first one being the default registry of
+    /// the bundle.
+    pub registries: Vec<BundledRegistry>,
+    /// The directory of the templates in the bundle, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub templates: Option<String>,
+    /// The directory of the policies in the bundle, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policies: Option<String>,
+}
+
+/// A registry recorded in the manifest of a bundle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BundledRegistry {
+    /// The directory of the registry in the bundle.
+    pub dir: String,
+    /// The registry path the registry was fetched from.
+    pub registry_path: String,
+    /// The exact revision of the registry, if any (see
+    /// [`RegistryRepo::revision`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+}
+
+impl BundleManifest {
+    /// Reads the manifest of an unpacked bundle, if the directory is a bundle.
+    pub fn load(dir: &Path) -> Result<Option<Self>, Error> {
+        let path = dir.join(BUNDLE_MANIFEST);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let invalid = |error: String| InvalidBundle {
+            path: path.display().to_string(),
+            error,
+        };
+        let content = std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+        let manifest: Self = serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        if manifest.version > BUNDLE_VERSION {
+            return Err(invalid(format!(
+                "Unsupported version {}, the latest supported version is {}",
+                manifest.version, BUNDLE_VERSION
+            )));
+        }
+        if manifest.registries.is_empty() {
+            return Err(invalid(
+                "The bundle doesn't contain any registry".to_owned(),
+            ));
+        }
+        Ok(Some(manifest))
+    }
+}
+
+/// Returns the directory of the default registry of an unpacked bundle, or
+/// `None` if the directory is not a bundle.
+pub(crate) fn default_registry_dir(dir: &Path) -> Result<Option<PathBuf>, Error> {
+    Ok(BundleManifest::load(dir)?.map(|manifest| dir.join(&manifest.registries[0].dir)))
+}
+
+/// Writes a bundle of registries, templates, and policies. The format of the
+/// bundle is selected by the extension of the output file. The `.git`
+/// directories are skipped.
+///
+/// Arguments:
+/// - `output`: The path of the bundle.
+/// - `registries`: The registries to bundle, the first one being the default
+///   registry of the bundle.
+/// - `templates`: The templates directory to bundle, if any.
+/// - `policies`: The policy files or directories to bundle.
+pub fn write_bundle(
+    output: &Path,
+    registries: &[RegistryRepo],
+    templates: Option<&Path>,
+    policies: &[PathBuf],
+) -> Result<BundleManifest, Error> {
+    let invalid = |error: String| InvalidBundle {
+        path: output.display().to_string(),
+        error,
+    };
+    if registries.is_empty() {
+        return Err(invalid("No registry to bundle".to_owned()));
+    }
+    let manifest = BundleManifest {
+        version: BUNDLE_VERSION,
+        registries: registries
+            .iter()
+            .map(|repo| BundledRegistry {
+                dir: format!("registries/{}", repo.id()),
+                registry_path: repo.registry_path_repr().to_owned(),
+                revision: repo.revision().map(str::to_owned),
+            })
+            .collect(),
+        templates: templates.map(|_| TEMPLATES_DIR.to_owned()),
+        policies: (!policies.is_empty()).then(|| POLICIES_DIR.to_owned()),
+    };
+
+    let file = File::create(output).map_err(|e| invalid(e.to_string()))?;
+    let result = match ArchiveFormat::from_file_name(&output.to_string_lossy()) {
+        Some(ArchiveFormat::Tar) => {
+            write_tar(file, &manifest, registries, templates, policies).map(|_| ())
+        }
+        Some(ArchiveFormat::TarGz) => write_tar(
+            flate2::write::GzEncoder::new(file, flate2::Compression::default()),
+            &manifest,
+            registries,
+            templates,
+            policies,
+        )
+        .and_then(|encoder| encoder.finish().map(|_| ())),
+        Some(ArchiveFormat::TarZst) => zstd::Encoder::new(file, 0)
+            .and_then(|encoder| write_tar(encoder, &manifest, registries, templates, policies))
+            .and_then(|encoder| encoder.finish().map(|_| ())),
+        Some(ArchiveFormat::Zip) | None => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Supported bundle formats are: .tar, .tar.gz, .tgz, .tar.zst",
+        )),
+    };
+    if let Err(e) = result {
+        // Don't leave a partial bundle behind.
+        _ = std::fs::remove_file(output);
+        return Err(invalid(e.to_string()));
+    }
+    Ok(manifest)
+}
+
+/// Writes the entries of a bundle into a tar stream, and returns the stream.
+fn write_tar<W: Write>(
+    writer: W,
+    manifest: &BundleManifest,
+    registries: &[RegistryRepo],
+    templates: Option<&Path>,
+    policies: &[PathBuf],
+) -> io::Result<W> {
+    let root = Path::new(BUNDLE_ROOT);
+    let mut builder = tar::Builder::new(writer);
+    builder.mode(tar::HeaderMode::Deterministic);
+
+    let content = serde_yaml::to_string(manifest).map_err(io::Error::other)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    builder.append_data(&mut header, root.join(BUNDLE_MANIFEST), content.as_bytes())?;
+
+    for (repo, bundled) in registries.iter().zip(&manifest.registries) {
+        append_dir(&mut builder, repo.path(), &root.join(&bundled.dir))?;
+    }
+    if let Some(templates) = templates {
+        append_dir(&mut builder, templates, &root.join(TEMPLATES_DIR))?;
+    }
+    for policy in policies {
+        let name = policy.file_name().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid policy path `{}`", policy.display()),
+            )
+        })?;
+        append_dir(&mut builder, policy, &root.join(POLICIES_DIR).join(name))?;
+    }
+    builder.into_inner()
+}
+
+/// Appends a file, or the files of a directory sorted by name, to a tar
+/// stream under the given name. The `.git` directories are skipped.
+fn append_dir<W: Write>(builder: &mut tar::Builder<W>, src: &Path, name: &Path) -> io::Result<()> {
+    let entries = walkdir::WalkDir::new(src)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git");
+    for entry in entries {
+        let entry = entry.map_err(io::Error::from)?;
+        let relative_path = entry.path().strip_prefix(src).map_err(io::Error::other)?;
+        let entry_name = if relative_path.as_os_str().is_empty() {
+            name.to_path_buf()
+        } else {
+            name.join(relative_path)
+        };
+        builder.append_path_with_name(entry.path(), entry_name)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use crate::registry_path::RegistryPath;
+    use crate::RegistryCache;
+
+    use super::*;
+
+    fn cache() -> RegistryCache {
+        RegistryCache::try_default().expect("Failed to create the registry cache")
+    }
+
+    #[test]
+    fn test_bundle() {
+        let dir = TempDir::new("bundle").unwrap();
+        let main: RegistryPath = "../../test_data/semantic-conventions-1.26.0.tar.gz[model]"
+            .parse()
+            .unwrap();
+        let other = RegistryPath::LocalFolder {
+            path: "src".to_owned(),
+        };
+        let registries = vec![
+            RegistryRepo::try_new("main", &main, &cache()).unwrap(),
+            RegistryRepo::try_new("main.1", &other, &cache()).unwrap(),
+        ];
+        let templates = dir.path().join("templates");
+        std::fs::create_dir_all(templates.join("markdown")).unwrap();
+        std::fs::write(templates.join("markdown/weaver.yaml"), "templates: []\n").unwrap();
+        let policies = vec![PathBuf::from("../../test_data/registry.rego")];
+
+        let output = dir.path().join("bundle.tar");
+        let manifest = write_bundle(&output, &registries, Some(&templates), &policies).unwrap();
+        assert_eq!(manifest.registries[0].dir, "registries/main");
+        assert_eq!(manifest.registries[0].registry_path, main.to_string());
+        assert_eq!(manifest.registries[1].dir, "registries/main.1");
+        assert_eq!(manifest.templates.as_deref(), Some("templates"));
+        assert_eq!(manifest.policies.as_deref(), Some("policies"));
+        let mut archive = tar::Archive::new(File::open(&output).unwrap());
+        let entries: Vec<_> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().display().to_string())
+            .collect();
+        for entry in [
+            "weaver-bundle/weaver_bundle.yaml",
+            "weaver-bundle/registries/main/logs/events.yaml",
+            "weaver-bundle/registries/main.1/bundle.rs",
+            "weaver-bundle/templates/markdown/weaver.yaml",
+            "weaver-bundle/policies/registry.rego",
+        ] {
+            assert!(entries.iter().any(|e| e == entry), "{}", entry);
+        }
+
+        for file_name in ["bundle.tar", "bundle.tar.gz", "bundle.tar.zst"] {
+            let output = dir.path().join(file_name);
+            _ = write_bundle(&output, &registries, None, &[]).unwrap();
+
+            // The bundle resolves to its first registry.
+            let bundle = RegistryRepo::try_new(
+                "main",
+                &RegistryPath::LocalArchive {
+                    path: output.display().to_string(),
+                    sub_folder: None,
+                },
+                &cache(),
+            )
+            .unwrap();
+            assert!(
+                bundle.path().join("logs/events.yaml").exists(),
+                "{}",
+                file_name
+            );
+
+            // The other registries are selected with a sub-folder.
+            let bundle = RegistryRepo::try_new(
+                "main",
+                &RegistryPath::LocalArchive {
+                    path: output.display().to_string(),
+                    sub_folder: Some("registries/main.1".to_owned()),
+                },
+                &cache(),
+            )
+            .unwrap();
+            assert!(bundle.path().join("bundle.rs").exists(), "{}", file_name);
+        }
+
+        assert!(matches!(
+            write_bundle(&dir.path().join("bundle.zip"), &registries, None, &[]),
+            Err(InvalidBundle { .. })
+        ));
+        assert!(!dir.path().join("bundle.zip").exists());
+        assert!(matches!(
+            write_bundle(&dir.path().join("empty.tar"), &[], None, &[]),
+            Err(InvalidBundle { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_bundle_manifest() {
+        let dir = TempDir::new("bundle").unwrap();
+        assert_eq!(BundleManifest::load(dir.path()).unwrap(), None);
+        let path = dir.path().join(BUNDLE_MANIFEST);
+        std::fs::write(&path, "version: 2\nregistries: []\n").unwrap();
+        assert!(matches!(
+            BundleManifest::load(dir.path()),
+            Err(InvalidBundle { .. })
+        ));
+        std::fs::write(&path, "version: 1\nregistries: []\n").unwrap();
+        assert!(matches!(
+            BundleManifest::load(dir.path()),
+            Err(InvalidBundle { .. })
+        ));
+    }
+}
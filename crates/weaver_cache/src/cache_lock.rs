@@ -0,0 +1,379 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Locking of the entries of the registry cache.
+//!
+//! Concurrent Weaver processes (e.g. the jobs of a CI matrix sharing a cache)
+//! serialize the download and the replacement of a cache entry with a lock
+//! file next to the entry (`<entry>.lock`), created atomically. A process
+//! waiting for a lock polls it until it is released. A lock older than a
+//! maximum duration is considered abandoned by an interrupted process and is
+//! taken over, so the holder of a lock refreshes its modification time while
+//! it is held (e.g. during a long download).
+//!
+//! The processes using a cache entry hold a read lock on it: a reader file
+//! next to the entry (`<entry>.reader-<pid>-<n>`), created while holding the
+//! lock of the entry and also refreshed while it is held. An entry with
+//! readers is never replaced nor removed. The temporary directories of the
+//! cache (e.g. a registry being downloaded) are read locked the same way by
+//! the process creating them, so the `cache` commands don't remove them.
+
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use tempdir::TempDir;
+
+use crate::Error;
+use crate::Error::CacheDirNotCreated;
+
+/// The duration after which a lock is considered abandoned.
+pub(crate) const DEFAULT_STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+/// The maximum delay between two attempts to take a lock.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The infix of the reader files of an entry.
+const READER_INFIX: &str = ".reader-";
+
+/// The number of read locks taken by the process, to name their files.
+static READ_LOCK_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// A lock on a cache entry, released when dropped.
+#[derive(Debug)]
+pub(crate) struct CacheLock {
+    path: PathBuf,
+    _heartbeat: Heartbeat,
+}
+
+impl CacheLock {
+    /// Takes the lock of a cache entry, waiting until it is released by
+    /// another process or becomes older than `stale_age`.
+    pub(crate) fn acquire(entry_dir: &Path, stale_age: Duration) -> Result<Self, Error> {
+        let path = lock_path(entry_dir);
+        let lock_error = |e: std::io::Error| CacheDirNotCreated {
+            message: format!("Failed to lock `{}`: {}", path.display(), e),
+        };
+        let mut poll_interval = Duration::from_millis(10);
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    // The process id only helps to diagnose a stuck lock.
+                    _ = write!(file, "{}", std::process::id());
+                    let heartbeat = Heartbeat::start(path.clone(), stale_age);
+                    return Ok(Self {
+                        path,
+                        _heartbeat: heartbeat,
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    if is_stale(&path, stale_age) {
+                        // The lock may have been released in the meantime.
+                        match std::fs::remove_file(&path) {
+                            Err(e) if e.kind() != ErrorKind::NotFound => return Err(lock_error(e)),
+                            _ => continue,
+                        }
+                    }
+                    std::thread::sleep(poll_interval);
+                    poll_interval = (poll_interval * 2).min(MAX_POLL_INTERVAL);
+                }
+                Err(e) => return Err(lock_error(e)),
+            }
+        }
+    }
+
+    /// Returns true if the lock of a cache entry is held by a process, i.e.
+    /// if it exists and is not older than `stale_age`.
+    pub(crate) fn is_locked(entry_dir: &Path, stale_age: Duration) -> bool {
+        let path = lock_path(entry_dir);
+        path.exists() && !is_stale(&path, stale_age)
+    }
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        // A lock that can't be removed is eventually taken over.
+        _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// A read lock on a cache entry, released when dropped.
+#[derive(Debug)]
+pub(crate) struct ReadLock {
+    path: PathBuf,
+    _heartbeat: Heartbeat,
+}
+
+impl ReadLock {
+    /// Takes a read lock on a cache entry. The caller must hold the lock of
+    /// the entry, so the entry can't be replaced in the meantime.
+    pub(crate) fn acquire(
+        entry_dir: &Path,
+        _lock: &CacheLock,
+        stale_age: Duration,
+    ) -> Result<Self, Error> {
+        Self::create(entry_dir, stale_age)
+    }
+
+    /// Creates a reader file for the given directory.
+    fn create(entry_dir: &Path, stale_age: Duration) -> Result<Self, Error> {
+        let mut path = entry_dir.as_os_str().to_owned();
+        path.push(format!(
+            "{}{}-{}",
+            READER_INFIX,
+            std::process::id(),
+            READ_LOCK_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let path = PathBuf::from(path);
+        _ = File::create(&path).map_err(|e| CacheDirNotCreated {
+            message: format!("Failed to lock `{}`: {}", path.display(), e),
+        })?;
+        let heartbeat = Heartbeat::start(path.clone(), stale_age);
+        Ok(Self {
+            path,
+            _heartbeat: heartbeat,
+        })
+    }
+
+    /// Returns true if a process (including this one) holds a read lock on
+    /// the entry. The reader files older than `stale_age`, left by
+    /// interrupted processes, are removed.
+    pub(crate) fn has_readers(entry_dir: &Path, stale_age: Duration) -> bool {
+        let (Some(dir), Some(name)) = (entry_dir.parent(), entry_dir.file_name()) else {
+            return false;
+        };
+        let mut prefix = name.to_owned();
+        prefix.push(READER_INFIX);
+        let prefix = prefix.to_string_lossy().into_owned();
+        let Ok(dir_entries) = std::fs::read_dir(dir) else {
+            return false;
+        };
+        let mut has_readers = false;
+        for dir_entry in dir_entries.filter_map(Result::ok) {
+            if !dir_entry.file_name().to_string_lossy().starts_with(&prefix) {
+                continue;
+            }
+            let path = dir_entry.path();
+            if is_stale(&path, stale_age) {
+                _ = std::fs::remove_file(&path);
+            } else {
+                has_readers = true;
+            }
+        }
+        has_readers
+    }
+}
+
+impl Drop for ReadLock {
+    fn drop(&mut self) {
+        // A reader file that can't be removed eventually becomes stale.
+        _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// A temporary directory of the cache, read locked while it exists. The
+/// directory is deleted when dropped.
+#[derive(Debug)]
+pub(crate) struct CacheTempDir {
+    // Dropped before the read lock, so the directory is deleted first.
+    dir: TempDir,
+    _read_lock: ReadLock,
+}
+
+impl CacheTempDir {
+    /// Creates a read locked temporary directory in the cache directory.
+    pub(crate) fn create(cache_dir: &Path, prefix: &str) -> Result<Self, Error> {
+        let dir = TempDir::new_in(cache_dir, prefix).map_err(|e| CacheDirNotCreated {
+            message: e.to_string(),
+        })?;
+        // The directory is not visible to other processes yet.
+        let read_lock = ReadLock::create(dir.path(), DEFAULT_STALE_LOCK_AGE)?;
+        Ok(Self {
+            dir,
+            _read_lock: read_lock,
+        })
+    }
+
+    /// Returns the path of the directory.
+    pub(crate) fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Keeps the directory (e.g. once it has been renamed) and releases its
+    /// read lock.
+    pub(crate) fn into_path(self) -> PathBuf {
+        self.dir.into_path()
+    }
+}
+
+/// A thread refreshing the modification time of a lock file while the lock
+/// is held, so the lock is never considered abandoned. The thread is stopped
+/// when dropped.
+#[derive(Debug)]
+struct Heartbeat {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+    /// Starts refreshing the lock file 4 times per `stale_age`.
+    fn start(path: PathBuf, stale_age: Duration) -> Self {
+        let (stop, stopped) = channel::<()>();
+        let interval = (stale_age / 4).max(Duration::from_millis(10));
+        let thread = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                // A lock file that can't be refreshed is eventually taken
+                // over, like a lock file left by an interrupted process.
+                _ = OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|file| file.set_modified(SystemTime::now()));
+            }
+        });
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        // Disconnecting the channel stops the thread.
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+/// Returns the path of the lock file of a cache entry.
+fn lock_path(entry_dir: &Path) -> PathBuf {
+    let mut path = entry_dir.as_os_str().to_owned();
+    path.push(".lock");
+    PathBuf::from(path)
+}
+
+/// Returns true if the lock file is older than `stale_age`. A lock file
+/// removed in the meantime is not stale.
+fn is_stale(path: &Path, stale_age: Duration) -> bool {
+    File::open(path)
+        .and_then(|file| file.metadata())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= stale_age)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let dir = TempDir::new("lock").unwrap();
+        let entry_dir = dir.path().join("entry");
+        let lock = CacheLock::acquire(&entry_dir, DEFAULT_STALE_LOCK_AGE).unwrap();
+        assert!(dir.path().join("entry.lock").exists());
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let waiter = {
+            let events = events.clone();
+            let entry_dir = entry_dir.clone();
+            std::thread::spawn(move || {
+                let _lock = CacheLock::acquire(&entry_dir, DEFAULT_STALE_LOCK_AGE).unwrap();
+                events.lock().unwrap().push("acquired by waiter");
+            })
+        };
+        std::thread::sleep(Duration::from_millis(200));
+        events.lock().unwrap().push("released");
+        drop(lock);
+        waiter.join().unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["released", "acquired by waiter"]
+        );
+        assert!(!dir.path().join("entry.lock").exists());
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let dir = TempDir::new("lock").unwrap();
+        let entry_dir = dir.path().join("entry");
+        // A lock left by an interrupted process.
+        std::fs::write(dir.path().join("entry.lock"), "12345").unwrap();
+        let lock = CacheLock::acquire(&entry_dir, Duration::ZERO).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("entry.lock")).unwrap(),
+            std::process::id().to_string()
+        );
+        drop(lock);
+    }
+
+    #[test]
+    fn test_held_lock_is_refreshed() {
+        let dir = TempDir::new("lock").unwrap();
+        let entry_dir = dir.path().join("entry");
+        let stale_age = Duration::from_millis(400);
+        let lock = CacheLock::acquire(&entry_dir, stale_age).unwrap();
+        // The lock is held longer than the stale age, but it is refreshed.
+        std::thread::sleep(stale_age * 2);
+        assert!(!is_stale(&lock_path(&entry_dir), stale_age));
+        assert!(CacheLock::is_locked(&entry_dir, stale_age));
+        drop(lock);
+        assert!(!lock_path(&entry_dir).exists());
+        assert!(!CacheLock::is_locked(&entry_dir, stale_age));
+    }
+
+    #[test]
+    fn test_read_locks() {
+        let dir = TempDir::new("lock").unwrap();
+        let entry_dir = dir.path().join("entry");
+        assert!(!ReadLock::has_readers(&entry_dir, DEFAULT_STALE_LOCK_AGE));
+
+        let lock = CacheLock::acquire(&entry_dir, DEFAULT_STALE_LOCK_AGE).unwrap();
+        let reader = ReadLock::acquire(&entry_dir, &lock, DEFAULT_STALE_LOCK_AGE).unwrap();
+        let other_reader = ReadLock::acquire(&entry_dir, &lock, DEFAULT_STALE_LOCK_AGE).unwrap();
+        drop(lock);
+        // The readers of another entry are ignored.
+        assert!(!ReadLock::has_readers(
+            &dir.path().join("ent"),
+            DEFAULT_STALE_LOCK_AGE
+        ));
+        assert!(ReadLock::has_readers(&entry_dir, DEFAULT_STALE_LOCK_AGE));
+        drop(reader);
+        assert!(ReadLock::has_readers(&entry_dir, DEFAULT_STALE_LOCK_AGE));
+        drop(other_reader);
+        assert!(!ReadLock::has_readers(&entry_dir, DEFAULT_STALE_LOCK_AGE));
+
+        // A reader file left by an interrupted process is removed.
+        let reader_file = dir.path().join("entry.reader-12345-0");
+        std::fs::write(&reader_file, "").unwrap();
+        assert!(!ReadLock::has_readers(&entry_dir, Duration::ZERO));
+        assert!(!reader_file.exists());
+    }
+
+    #[test]
+    fn test_temp_dir_is_read_locked() {
+        let dir = TempDir::new("lock").unwrap();
+        let tmp_dir = CacheTempDir::create(dir.path(), "repo").unwrap();
+        let tmp_path = tmp_dir.path().to_path_buf();
+        assert!(ReadLock::has_readers(&tmp_path, DEFAULT_STALE_LOCK_AGE));
+        drop(tmp_dir);
+        assert!(!tmp_path.exists());
+        assert!(!ReadLock::has_readers(&tmp_path, DEFAULT_STALE_LOCK_AGE));
+
+        // A kept directory is no longer read locked.
+        let tmp_path = CacheTempDir::create(dir.path(), "repo")
+            .unwrap()
+            .into_path();
+        assert!(tmp_path.exists());
+        assert!(!ReadLock::has_readers(&tmp_path, DEFAULT_STALE_LOCK_AGE));
+    }
+}
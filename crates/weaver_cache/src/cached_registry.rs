@@ -0,0 +1,418 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inspection and cleanup of the persistent cache of the remote registries.
+//!
+//! The cache directory contains one entry per remote registry (a directory
+//! named after the hash of the registry path, with a metadata file) and the
+//! temporary directories of the registries being downloaded or unpacked from
+//! a local archive. The temporary directories left by interrupted commands
+//! are reported and removed like the other entries. The files of the
+//! registries are stored in the `objects` directory (see the `store` module),
+//! whose unreferenced objects are removed when the cache is pruned or cleared.
+//!
+//! The entries in use by a process (a registry being downloaded or read, or
+//! a temporary directory being written, see the `cache_lock` module) are
+//! never removed.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use walkdir::WalkDir;
+
+use crate::cache_lock::{CacheLock, ReadLock, DEFAULT_STALE_LOCK_AGE};
+use crate::Error::CacheDirNotAccessible;
+use crate::{store, CacheEntry, Error, CACHE_DIR, REMOVED_INFIX};
+
+/// An entry of the registry cache.
+#[derive(Debug, Clone)]
+pub struct CachedRegistry {
+    /// The directory of the entry.
+    pub path: PathBuf,
+    /// The registry path, or `None` for a temporary directory.
+    pub registry_path: Option<String>,
+    /// The download time of the registry, or the last modification time of
+    /// a temporary directory.
+    pub fetched_at: SystemTime,
+    /// The total size of the files of the entry, in bytes.
+    pub size: u64,
+    /// True if the entry is in use by a process.
+    pub in_use: bool,
+}
+
+impl CachedRegistry {
+    /// Returns the age of the entry (zero if the clock went backwards).
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.fetched_at)
+            .unwrap_or_default()
+    }
+
+    /// Removes the entry from the cache, unless it is in use by a process.
+    /// Returns false if the entry is in use.
+    ///
+    /// A registry entry is locked while it is removed, so a process
+    /// downloading or starting to read the same registry is not disturbed.
+    pub fn remove(&self) -> Result<bool, Error> {
+        if self.in_use || is_in_use(&self.path) {
+            return Ok(false);
+        }
+        let not_accessible = |e: std::io::Error| CacheDirNotAccessible {
+            path: self.path.display().to_string(),
+            error: e.to_string(),
+        };
+        if self.registry_path.is_some() {
+            let _lock = CacheLock::acquire(&self.path, DEFAULT_STALE_LOCK_AGE)?;
+            if ReadLock::has_readers(&self.path, DEFAULT_STALE_LOCK_AGE) {
+                return Ok(false);
+            }
+            CacheEntry::remove(&self.path).map_err(not_accessible)?;
+        } else {
+            match fs::remove_dir_all(&self.path) {
+                // Another process may be removing it concurrently.
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(not_accessible(e))
+                }
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Returns true if a cache entry or a temporary directory is in use by a
+/// process, i.e. if it is locked or read locked.
+fn is_in_use(path: &Path) -> bool {
+    CacheLock::is_locked(path, DEFAULT_STALE_LOCK_AGE)
+        || ReadLock::has_readers(path, DEFAULT_STALE_LOCK_AGE)
+}
+
+/// Returns the default directory of the registry cache
+/// (`~/.weaver/semconv_registry_cache`).
+pub fn default_cache_dir() -> Result<PathBuf, Error> {
+    dirs::home_dir()
+        .map(|home| home.join(CACHE_DIR))
+        .ok_or(Error::HomeDirNotFound)
+}
+
+/// Returns the entries of the registry cache, the oldest first. A missing
+/// cache directory is an empty cache.
+///
+/// The size of an entry includes a share of the objects of its files, the
+/// objects referenced by several entries being split between them, so the
+/// total size of the entries is the size of the cache.
+pub fn cached_registries(cache_dir: &Path) -> Result<Vec<CachedRegistry>, Error> {
+    let entries = cache_entries(cache_dir)?;
+    let mut ref_counts: HashMap<&str, u64> = HashMap::new();
+    for (_, entry) in &entries {
+        for digest in entry_digests(entry.as_ref()) {
+            *ref_counts.entry(digest).or_default() += 1;
+        }
+    }
+
+    let mut registries = vec![];
+    for (path, entry) in &entries {
+        let fetched_at = match entry {
+            Some(entry) => UNIX_EPOCH + Duration::from_secs(entry.fetched_at),
+            None => fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .map_err(|e| CacheDirNotAccessible {
+                    path: path.display().to_string(),
+                    error: e.to_string(),
+                })?,
+        };
+        // The files stored as objects are counted below.
+        let registry_dir = entry.as_ref().map(|entry| path.join(&entry.path));
+        let is_stored = |file: &Path| match (entry, &registry_dir) {
+            (Some(entry), Some(registry_dir)) => file
+                .strip_prefix(registry_dir)
+                .ok()
+                .and_then(|relative_path| entry.objects.get(&store::manifest_key(relative_path)))
+                .is_some_and(|digest| store::object_path(cache_dir, digest).exists()),
+            _ => false,
+        };
+        let files_size: u64 = WalkDir::new(path)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|dir_entry| !is_stored(dir_entry.path()))
+            .filter_map(|dir_entry| dir_entry.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum();
+        let objects_size: u64 = entry_digests(entry.as_ref())
+            .into_iter()
+            .map(|digest| store::object_size(cache_dir, digest) / ref_counts[digest])
+            .sum();
+        registries.push(CachedRegistry {
+            path: path.clone(),
+            registry_path: entry.as_ref().map(|entry| entry.registry_path.clone()),
+            fetched_at,
+            size: files_size + objects_size,
+            in_use: is_in_use(path),
+        });
+    }
+    registries.sort_by_key(|registry| registry.fetched_at);
+    Ok(registries)
+}
+
+/// Returns the directories of the registry cache with their metadata (`None`
+/// for a temporary directory, including the entries being removed). The
+/// objects directory is skipped.
+fn cache_entries(cache_dir: &Path) -> Result<Vec<(PathBuf, Option<CacheEntry>)>, Error> {
+    if !cache_dir.exists() {
+        return Ok(vec![]);
+    }
+    let not_accessible = |error: std::io::Error| CacheDirNotAccessible {
+        path: cache_dir.display().to_string(),
+        error: error.to_string(),
+    };
+    let mut entries = vec![];
+    for dir_entry in fs::read_dir(cache_dir).map_err(not_accessible)? {
+        let path = dir_entry.map_err(not_accessible)?.path();
+        if !path.is_dir() || path.file_name() == Some(OsStr::new(store::OBJECTS_DIR)) {
+            continue;
+        }
+        let is_removed = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().contains(REMOVED_INFIX));
+        let entry = if is_removed {
+            None
+        } else {
+            CacheEntry::load(&path)
+        };
+        entries.push((path, entry));
+    }
+    Ok(entries)
+}
+
+/// Returns the distinct digests of the files of a cache entry.
+fn entry_digests(entry: Option<&CacheEntry>) -> HashSet<&str> {
+    entry
+        .map(|entry| entry.objects.values().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Removes the objects no longer referenced by a cache entry.
+fn remove_unreferenced_objects(cache_dir: &Path) -> Result<(), Error> {
+    let entries = cache_entries(cache_dir)?;
+    let referenced = entries
+        .iter()
+        .flat_map(|(_, entry)| entry_digests(entry.as_ref()))
+        .collect();
+    _ = store::remove_unreferenced_objects(cache_dir, &referenced).map_err(|e| {
+        CacheDirNotAccessible {
+            path: cache_dir.join(store::OBJECTS_DIR).display().to_string(),
+            error: e.to_string(),
+        }
+    })?;
+    Ok(())
+}
+
+/// Removes the entries older than `older_than`, then the oldest entries until
+/// the total size of the cache is at most `max_size` bytes, and the objects
+/// no longer referenced. The entries in use are kept. Returns the removed
+/// entries.
+pub fn prune(
+    cache_dir: &Path,
+    older_than: Option<Duration>,
+    max_size: Option<u64>,
+) -> Result<Vec<CachedRegistry>, Error> {
+    let registries = cached_registries(cache_dir)?;
+    let mut total_size: u64 = registries.iter().map(|registry| registry.size).sum();
+    let mut removed = vec![];
+    for registry in registries {
+        let too_old = older_than.is_some_and(|older_than| registry.age() > older_than);
+        let too_big = max_size.is_some_and(|max_size| total_size > max_size);
+        if (too_old || too_big) && registry.remove()? {
+            total_size -= registry.size;
+            removed.push(registry);
+        }
+    }
+    remove_unreferenced_objects(cache_dir)?;
+    Ok(removed)
+}
+
+/// Removes all the entries of the registry cache not in use, and their
+/// objects. Returns the removed entries.
+pub fn clear(cache_dir: &Path) -> Result<Vec<CachedRegistry>, Error> {
+    let mut removed = vec![];
+    for registry in cached_registries(cache_dir)? {
+        if registry.remove()? {
+            removed.push(registry);
+        }
+    }
+    remove_unreferenced_objects(cache_dir)?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+    use crate::cache_lock::CacheTempDir;
+    use crate::test::write_cache_entry;
+
+    #[test]
+    fn test_cached_registries() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        assert!(cached_registries(&cache_dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+
+        write_cache_entry(cache_dir.path(), "a", "https://example.com/a.zip", 10, 1000);
+        write_cache_entry(
+            cache_dir.path(),
+            "b",
+            "https://example.com/b.zip",
+            3600,
+            2000,
+        );
+        // A temporary directory left by an interrupted download.
+        fs::create_dir_all(cache_dir.path().join("repo.1234/registry")).unwrap();
+
+        let registries = cached_registries(cache_dir.path()).unwrap();
+        let registry_paths: Vec<_> = registries
+            .iter()
+            .map(|registry| registry.registry_path.as_deref())
+            .collect();
+        assert_eq!(
+            registry_paths,
+            vec![
+                Some("https://example.com/b.zip"),
+                Some("https://example.com/a.zip"),
+                None
+            ]
+        );
+        assert!(registries[0].size > 2000);
+        assert!(registries[0].age() >= Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_prune() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        write_cache_entry(cache_dir.path(), "a", "a", 10, 1000);
+        write_cache_entry(cache_dir.path(), "b", "b", 3600, 1000);
+        write_cache_entry(cache_dir.path(), "c", "c", 7200, 1000);
+
+        // Nothing to prune.
+        assert!(prune(cache_dir.path(), None, None).unwrap().is_empty());
+
+        let removed = prune(cache_dir.path(), Some(Duration::from_secs(5000)), None).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].registry_path.as_deref(), Some("c"));
+        assert!(!cache_dir.path().join("c").exists());
+
+        // The oldest entries are removed first.
+        let removed = prune(cache_dir.path(), None, Some(1500)).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].registry_path.as_deref(), Some("b"));
+        assert!(cache_dir.path().join("a").exists());
+
+        fs::create_dir_all(cache_dir.path().join("repo.1234")).unwrap();
+        assert_eq!(clear(cache_dir.path()).unwrap().len(), 2);
+        assert!(cached_registries(cache_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_entries_in_use_are_kept() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        write_cache_entry(cache_dir.path(), "read", "read", 7200, 1000);
+        write_cache_entry(cache_dir.path(), "locked", "locked", 7200, 1000);
+        write_cache_entry(cache_dir.path(), "unused", "unused", 7200, 1000);
+        // A registry being read, a registry being downloaded, and a registry
+        // being unpacked.
+        let lock =
+            CacheLock::acquire(&cache_dir.path().join("read"), DEFAULT_STALE_LOCK_AGE).unwrap();
+        let reader = ReadLock::acquire(
+            &cache_dir.path().join("read"),
+            &lock,
+            DEFAULT_STALE_LOCK_AGE,
+        )
+        .unwrap();
+        drop(lock);
+        let lock =
+            CacheLock::acquire(&cache_dir.path().join("locked"), DEFAULT_STALE_LOCK_AGE).unwrap();
+        let tmp_dir = CacheTempDir::create(cache_dir.path(), "repo").unwrap();
+        // A temporary directory and an entry left by interrupted processes.
+        fs::create_dir_all(cache_dir.path().join("repo.1234/registry")).unwrap();
+        write_cache_entry(cache_dir.path(), "old.removed-1234-5678", "old", 7200, 1000);
+
+        let in_use: Vec<_> = cached_registries(cache_dir.path())
+            .unwrap()
+            .into_iter()
+            .filter(|registry| registry.in_use)
+            .map(|registry| registry.path)
+            .collect();
+        assert_eq!(in_use.len(), 3);
+        assert!(in_use.contains(&tmp_dir.path().to_path_buf()));
+
+        let removed = prune(cache_dir.path(), Some(Duration::from_secs(60)), None).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].registry_path.as_deref(), Some("unused"));
+        assert_eq!(clear(cache_dir.path()).unwrap().len(), 2);
+        assert!(cache_dir.path().join("read").exists());
+        assert!(cache_dir.path().join("locked").exists());
+        assert!(tmp_dir.path().exists());
+
+        drop(reader);
+        drop(lock);
+        drop(tmp_dir);
+        assert_eq!(clear(cache_dir.path()).unwrap().len(), 2);
+        assert!(cached_registries(cache_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shared_objects() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let write_stored_entry = |name: &str, files: &[(&str, usize)], age: u64| {
+            let entry_dir = cache_dir.path().join(name);
+            for (file, size) in files {
+                let path = entry_dir.join("registry").join(file);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, file.repeat(*size)).unwrap();
+            }
+            CacheEntry {
+                registry_path: name.to_owned(),
+                refspec: None,
+                revision: None,
+                path: PathBuf::from("registry"),
+                fetched_at: crate::now_secs() - age,
+                validators: Default::default(),
+                objects: store::import(cache_dir.path(), &entry_dir.join("registry")).unwrap(),
+                signer: None,
+            }
+            .store(&entry_dir)
+            .unwrap();
+        };
+        // The `a` file (1000 bytes) is shared by both entries.
+        write_stored_entry("v1", &[("a", 1000), ("b", 300)], 3600);
+        write_stored_entry("v2", &[("a", 1000), ("c", 500)], 10);
+
+        let registries = cached_registries(cache_dir.path()).unwrap();
+        assert_eq!(registries.len(), 2);
+        let metadata_size = |name: &str| {
+            fs::metadata(cache_dir.path().join(name).join(crate::CACHE_METADATA_FILE))
+                .unwrap()
+                .len()
+        };
+        assert_eq!(registries[0].size, 500 + 300 + metadata_size("v1"));
+        assert_eq!(registries[1].size, 500 + 500 + metadata_size("v2"));
+
+        // The objects of the removed entry are removed, except the shared one.
+        let removed = prune(cache_dir.path(), Some(Duration::from_secs(60)), None).unwrap();
+        assert_eq!(removed.len(), 1);
+        let registries = cached_registries(cache_dir.path()).unwrap();
+        assert_eq!(registries[0].size, 1000 + 500 + metadata_size("v2"));
+        assert_eq!(
+            fs::read_to_string(cache_dir.path().join("v2/registry/a")).unwrap(),
+            "a".repeat(1000)
+        );
+
+        _ = clear(cache_dir.path()).unwrap();
+        assert_eq!(fs::read_dir(cache_dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Download of the remote archives.
+//!
+//! A failed download (network error, server error, or interrupted transfer) is
+//! retried with an exponential backoff. The bytes already downloaded are kept,
+//! and the next attempt requests the rest of the file with a `Range` header. A
+//! server that doesn't support ranges sends the whole file again.
+//!
+//! A file downloaded before can be downloaded again with a conditional request
+//! (`If-None-Match` and `If-Modified-Since` headers) using the validators
+//! (`ETag` and `Last-Modified` headers) of the previous download. A file that
+//! hasn't changed is not sent again by the server.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The retry policy of the downloads.
+#[derive(Debug, Clone)]
+pub(crate) struct RetryPolicy {
+    /// The maximum number of attempts (including the first one).
+    pub(crate) max_attempts: u32,
+    /// The delay before the first retry, doubled after each retry.
+    pub(crate) initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// The validators of a downloaded file (`ETag` and `Last-Modified` headers).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Validators {
+    /// The entity tag of the file.
+    pub(crate) etag: Option<String>,
+    /// The last modification date of the file.
+    pub(crate) last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Returns the validators of a response.
+    fn from_response(response: &ureq::Response) -> Self {
+        Self {
+            etag: response.header("ETag").map(str::to_owned),
+            last_modified: response.header("Last-Modified").map(str::to_owned),
+        }
+    }
+
+    /// Returns true if there is no validator.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// The outcome of a download.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Downloaded {
+    /// The file was downloaded, with its validators.
+    File(Validators),
+    /// The file hasn't changed since the download with the given validators.
+    NotModified,
+}
+
+/// The error of a download attempt.
+enum AttemptError {
+    /// A transient error, the download can be retried.
+    Transient(String),
+    /// A permanent error (e.g. a 404 response).
+    Permanent(String),
+}
+
+/// Downloads a file into the given path with the given HTTP agent and
+/// `Authorization` header (if any), retrying the failed attempts according to
+/// the retry policy.
+pub(crate) fn download(
+    agent: &ureq::Agent,
+    url: &str,
+    authorization: Option<&str>,
+    path: &Path,
+    policy: &RetryPolicy,
+) -> Result<(), String> {
+    download_if_modified(agent, url, authorization, None, path, policy).map(|_| ())
+}
+
+/// Downloads a file like [`download`], unless it hasn't changed since a
+/// previous download with the given validators (if any).
+pub(crate) fn download_if_modified(
+    agent: &ureq::Agent,
+    url: &str,
+    authorization: Option<&str>,
+    validators: Option<&Validators>,
+    path: &Path,
+    policy: &RetryPolicy,
+) -> Result<Downloaded, String> {
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match download_attempt(agent, url, authorization, validators, path) {
+            Ok(downloaded) => return Ok(downloaded),
+            Err(AttemptError::Transient(_)) if attempt < policy.max_attempts => {
+                std::thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(AttemptError::Transient(error)) => {
+                return Err(format!("{} (after {} attempts)", error, attempt))
+            }
+            Err(AttemptError::Permanent(error)) => return Err(error),
+        }
+    }
+}
+
+/// Downloads a file, or the rest of it if it is partially downloaded. The
+/// request is conditional if validators are given and nothing is downloaded
+/// yet.
+fn download_attempt(
+    agent: &ureq::Agent,
+    url: &str,
+    authorization: Option<&str>,
+    validators: Option<&Validators>,
+    path: &Path,
+) -> Result<Downloaded, AttemptError> {
+    let permanent = |e: io::Error| AttemptError::Permanent(e.to_string());
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(permanent)?;
+    let downloaded = file.metadata().map_err(permanent)?.len();
+
+    let mut request = agent.get(url);
+    if let Some(authorization) = authorization {
+        request = request.set("Authorization", authorization);
+    }
+    if downloaded > 0 {
+        request = request.set("Range", &format!("bytes={}-", downloaded));
+    } else if let Some(validators) = validators {
+        if let Some(etag) = &validators.etag {
+            request = request.set("If-None-Match", etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.set("If-Modified-Since", last_modified);
+        }
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(416, _)) => {
+            // The partial file doesn't match the remote file anymore, the
+            // next attempt starts from scratch.
+            file.set_len(0).map_err(permanent)?;
+            return Err(AttemptError::Transient("HTTP status code: 416".to_owned()));
+        }
+        Err(ureq::Error::Status(status, _)) if status == 429 || status >= 500 => {
+            return Err(AttemptError::Transient(format!(
+                "HTTP status code: {}",
+                status
+            )));
+        }
+        Err(ureq::Error::Status(status, _)) => {
+            return Err(AttemptError::Permanent(format!(
+                "HTTP status code: {}",
+                status
+            )));
+        }
+        Err(e @ ureq::Error::Transport(_)) => return Err(AttemptError::Transient(e.to_string())),
+    };
+    if response.status() == 304 && downloaded == 0 && validators.is_some() {
+        return Ok(Downloaded::NotModified);
+    }
+    let response_validators = Validators::from_response(&response);
+
+    let resumed = response.status() == 206
+        && response
+            .header("Content-Range")
+            .is_some_and(|range| range.starts_with(&format!("bytes {}-", downloaded)));
+    if !resumed {
+        if response.status() != 200 {
+            return Err(AttemptError::Permanent(format!(
+                "HTTP status code: {}",
+                response.status()
+            )));
+        }
+        // The whole file is sent again.
+        file.set_len(0).map_err(permanent)?;
+    }
+
+    // The number of bytes written is ignored, a truncated response is
+    // reported as an error by the reader.
+    _ = io::copy(&mut response.into_reader(), &mut file)
+        .map_err(|e| AttemptError::Transient(e.to_string()))?;
+    Ok(Downloaded::File(response_validators))
+}
+
+/// Returns the SHA-256 digest of a file (lowercase hexadecimal).
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    _ = io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    const CONTENT: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Serves one scripted response per connection, and returns the headers
+    /// of the requests (with lowercase names) once all the responses are sent.
+    fn serve(responses: Vec<Vec<u8>>) -> (String, JoinHandle<Vec<HashMap<String, String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/registry.zip", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = vec![];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut headers = HashMap::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    _ = reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        _ = headers.insert(name.to_lowercase(), value.trim().to_owned());
+                    }
+                }
+                stream.write_all(&response).unwrap();
+                requests.push(headers);
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    /// Returns the `Range` headers of the requests.
+    fn ranges(requests: Vec<HashMap<String, String>>) -> Vec<Option<String>> {
+        requests
+            .iter()
+            .map(|headers| headers.get("range").cloned())
+            .collect()
+    }
+
+    fn response(status: &str, headers: &str, body: &[u8], content_length: usize) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n",
+            status, content_length, headers
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[test]
+    fn test_download_retry_and_resume() {
+        let (url, server) = serve(vec![
+            // A server error.
+            response("503 Service Unavailable", "", b"", 0),
+            // An interrupted transfer.
+            response("200 OK", "", &CONTENT[..10], CONTENT.len()),
+            // The rest of the file.
+            response(
+                "206 Partial Content",
+                &format!(
+                    "Content-Range: bytes 10-{}/{}\r\n",
+                    CONTENT.len() - 1,
+                    CONTENT.len()
+                ),
+                &CONTENT[10..],
+                CONTENT.len() - 10,
+            ),
+        ]);
+        let dir = TempDir::new("download").unwrap();
+        let path = dir.path().join("registry.zip");
+        download(&ureq::agent(), &url, None, &path, &policy()).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), CONTENT);
+        assert_eq!(
+            ranges(server.join().unwrap()),
+            vec![None, None, Some("bytes=10-".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_download_without_range_support() {
+        let (url, server) = serve(vec![
+            response("200 OK", "", &CONTENT[..10], CONTENT.len()),
+            // The range is ignored, the whole file is sent again.
+            response("200 OK", "", CONTENT, CONTENT.len()),
+        ]);
+        let dir = TempDir::new("download").unwrap();
+        let path = dir.path().join("registry.zip");
+        download(&ureq::agent(), &url, None, &path, &policy()).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), CONTENT);
+        _ = server.join().unwrap();
+    }
+
+    #[test]
+    fn test_download_errors() {
+        // A permanent error is not retried.
+        let (url, server) = serve(vec![response("404 Not Found", "", b"", 0)]);
+        let dir = TempDir::new("download").unwrap();
+        let error = download(
+            &ureq::agent(),
+            &url,
+            None,
+            &dir.path().join("a.zip"),
+            &policy(),
+        )
+        .unwrap_err();
+        assert_eq!(error, "HTTP status code: 404");
+        assert_eq!(server.join().unwrap().len(), 1);
+
+        // The transient errors are retried up to the maximum number of attempts.
+        let (url, server) = serve(vec![
+            response("500 Internal Server Error", "", b"", 0),
+            response("502 Bad Gateway", "", b"", 0),
+            response("503 Service Unavailable", "", b"", 0),
+        ]);
+        let error = download(
+            &ureq::agent(),
+            &url,
+            None,
+            &dir.path().join("b.zip"),
+            &policy(),
+        )
+        .unwrap_err();
+        assert_eq!(error, "HTTP status code: 503 (after 3 attempts)");
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_conditional_download() {
+        let validators = Validators {
+            etag: Some("\"v1\"".to_owned()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_owned()),
+        };
+        let (url, server) = serve(vec![
+            response(
+                "200 OK",
+                &format!(
+                    "ETag: {}\r\nLast-Modified: {}\r\n",
+                    validators.etag.as_deref().unwrap(),
+                    validators.last_modified.as_deref().unwrap()
+                ),
+                CONTENT,
+                CONTENT.len(),
+            ),
+            response("304 Not Modified", "", b"", 0),
+        ]);
+        let dir = TempDir::new("download").unwrap();
+        let path = dir.path().join("registry.zip");
+        let agent = ureq::agent();
+        assert_eq!(
+            download_if_modified(&agent, &url, None, None, &path, &policy()).unwrap(),
+            Downloaded::File(validators.clone())
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), CONTENT);
+
+        let path = dir.path().join("registry-2.zip");
+        assert_eq!(
+            download_if_modified(&agent, &url, None, Some(&validators), &path, &policy()).unwrap(),
+            Downloaded::NotModified
+        );
+        let requests = server.join().unwrap();
+        assert!(!requests[0].contains_key("if-none-match"));
+        assert_eq!(requests[1]["if-none-match"], "\"v1\"");
+        assert_eq!(
+            requests[1]["if-modified-since"],
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+    }
+
+    #[test]
+    fn test_sha256_file() {
+        let dir = TempDir::new("download").unwrap();
+        let path = dir.path().join("file");
+        std::fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The registries fetched through a registry cache.
+//!
+//! A registry path loaded several times through the same cache (e.g. the same
+//! registry used as the current and the baseline registry of a command) is
+//! only fetched once: the next loads reuse the registry fetched first, without
+//! accessing the network or the persistent cache again, whatever the TTL of
+//! the cache. The clones of a cache share its fetched registries.
+//!
+//! The temporary directory of a registry unpacked from a local archive (or
+//! layered with an overlay) is shared by the repos loaded from it and deleted
+//! with the last of them, so such a registry is only reused while one of its
+//! repos is alive. The read lock of a registry loaded from the persistent
+//! cache is kept with the fetched registry, so its cache entry is not replaced
+//! while the cache may still use it, and is released with the last clone of
+//! the cache and the last repo loaded from it.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use crate::cache_lock::{CacheTempDir, ReadLock};
+use crate::registry_path::RegistryPath;
+use crate::RegistryRepo;
+
+/// The registries fetched through a registry cache, keyed by registry path.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FetchedRegistries {
+    registries: Arc<Mutex<HashMap<String, FetchedRegistry>>>,
+}
+
+/// A registry fetched through a registry cache.
+#[derive(Debug)]
+struct FetchedRegistry {
+    registry_path: String,
+    path: PathBuf,
+    refspec: Option<String>,
+    revision: Option<String>,
+    /// The temporary directory of the registry, if any, owned by its repos.
+    tmp_dir: Option<Weak<CacheTempDir>>,
+    /// The read lock of the cache entry of the registry, if any.
+    read_lock: Option<Arc<ReadLock>>,
+}
+
+impl FetchedRegistries {
+    /// Returns a repo of the registry already fetched for this registry path, if
+    /// any.
+    pub(crate) fn get(&self, registry_path: &RegistryPath, id: &str) -> Option<RegistryRepo> {
+        let registries = self
+            .registries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let registry = registries.get(&registry_path.to_string())?;
+        let tmp_dir = match &registry.tmp_dir {
+            Some(tmp_dir) => Some(tmp_dir.upgrade()?),
+            None => None,
+        };
+        Some(RegistryRepo {
+            id: id.to_owned(),
+            registry_path: registry.registry_path.clone(),
+            path: registry.path.clone(),
+            refspec: registry.refspec.clone(),
+            revision: registry.revision.clone(),
+            tmp_dir,
+            read_lock: registry.read_lock.clone(),
+            // The warnings were reported when the registry was fetched.
+            warnings: vec![],
+        })
+    }
+
+    /// Records a fetched registry. The local folders are not recorded, as they
+    /// are never fetched.
+    pub(crate) fn insert(&self, registry_path: &RegistryPath, repo: &RegistryRepo) {
+        if matches!(registry_path, RegistryPath::LocalFolder { .. }) {
+            return;
+        }
+        _ = self
+            .registries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                registry_path.to_string(),
+                FetchedRegistry {
+                    registry_path: repo.registry_path.clone(),
+                    path: repo.path.clone(),
+                    refspec: repo.refspec.clone(),
+                    revision: repo.revision.clone(),
+                    tmp_dir: repo.tmp_dir.as_ref().map(Arc::downgrade),
+                    read_lock: repo.read_lock.clone(),
+                },
+            );
+    }
+}
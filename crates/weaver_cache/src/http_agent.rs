@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The HTTP settings of the archive downloads.
+//!
+//! The settings are read from the environment:
+//! - `HTTPS_PROXY` (resp. `HTTP_PROXY`): the proxy of the HTTPS (resp. HTTP)
+//!   URLs, `ALL_PROXY` otherwise. The lowercase variables are also supported.
+//! - `NO_PROXY`: a comma-separated list of hosts, domains (e.g. `example.com`
+//!   or `.example.com`, matching the sub-domains), or `*` for all hosts,
+//!   accessed without proxy.
+//! - `WEAVER_CA_CERTS`: the path of a PEM file containing root certificates
+//!   trusted in addition to the built-in ones (e.g. the certificate of a
+//!   corporate TLS inspection proxy).
+//!
+//! The Git repositories are fetched by the HTTP client of the Git transport,
+//! which reads the same proxy variables by itself (the Git configuration of
+//! the repositories, including `http.proxy`, is isolated from the user's
+//! one and not used). The Git transport can't be given additional root
+//! certificates: it only trusts its built-in ones.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::CertificateDer;
+use rustls::RootCertStore;
+use url::Url;
+
+use crate::Error;
+use crate::Error::{InvalidCaCertificates, InvalidProxy};
+
+/// The environment variable containing the path of the additional root
+/// certificates.
+const CA_CERTS_ENV_VAR: &str = "WEAVER_CA_CERTS";
+
+/// The proxy and TLS settings of the HTTP downloads.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpSettings {
+    https_proxy: Option<String>,
+    http_proxy: Option<String>,
+    all_proxy: Option<String>,
+    no_proxy: Vec<String>,
+    ca_certs: Option<PathBuf>,
+}
+
+impl HttpSettings {
+    /// Reads the HTTP settings from the environment.
+    pub(crate) fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+    }
+
+    /// Reads the HTTP settings from the given variables. The uppercase
+    /// variables take precedence over the lowercase ones.
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let proxy_var = |name: &str| var(name).or_else(|| var(&name.to_lowercase()));
+        Self {
+            https_proxy: proxy_var("HTTPS_PROXY"),
+            http_proxy: proxy_var("HTTP_PROXY"),
+            all_proxy: proxy_var("ALL_PROXY"),
+            no_proxy: proxy_var("NO_PROXY")
+                .map(|no_proxy| {
+                    no_proxy
+                        .split(',')
+                        .map(|host| host.trim().trim_start_matches('.').to_lowercase())
+                        .filter(|host| !host.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ca_certs: var(CA_CERTS_ENV_VAR).map(PathBuf::from),
+        }
+    }
+
+    /// Returns the proxy of a URL, if any.
+    fn proxy(&self, url: &Url) -> Option<&str> {
+        let host = url.host_str()?.to_lowercase();
+        let bypassed = self.no_proxy.iter().any(|no_proxy| {
+            no_proxy == "*"
+                || host == *no_proxy
+                || host
+                    .strip_suffix(no_proxy.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        });
+        if bypassed {
+            return None;
+        }
+        match url.scheme() {
+            "https" => self.https_proxy.as_deref(),
+            "http" => self.http_proxy.as_deref(),
+            _ => None,
+        }
+        .or(self.all_proxy.as_deref())
+    }
+
+    /// Returns an HTTP agent to download the given URL.
+    pub(crate) fn agent(&self, url: &str) -> Result<ureq::Agent, Error> {
+        let mut builder = ureq::AgentBuilder::new();
+        if let Some(proxy) = Url::parse(url).ok().and_then(|url| self.proxy(&url)) {
+            builder = builder.proxy(ureq::Proxy::new(proxy).map_err(|e| InvalidProxy {
+                proxy: proxy.to_owned(),
+                error: e.to_string(),
+            })?);
+        }
+        if let Some(ca_certs) = &self.ca_certs {
+            builder = builder.tls_config(tls_config(ca_certs).map_err(|error| {
+                InvalidCaCertificates {
+                    path: ca_certs.display().to_string(),
+                    error,
+                }
+            })?);
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Returns a TLS configuration trusting the built-in root certificates and
+/// the root certificates of the given PEM file.
+fn tls_config(ca_certs: &Path) -> Result<Arc<rustls::ClientConfig>, String> {
+    let mut roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let mut count = 0;
+    for cert in CertificateDer::pem_file_iter(ca_certs).map_err(|e| e.to_string())? {
+        roots
+            .add(cert.map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    if count == 0 {
+        return Err("No certificate found".to_owned());
+    }
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|e| e.to_string())?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    use super::*;
+
+    fn settings(vars: &[(&str, &str)]) -> HttpSettings {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        HttpSettings::from_vars(|name| vars.get(name).map(|value| (*value).to_owned()))
+    }
+
+    fn proxy<'a>(settings: &'a HttpSettings, url: &str) -> Option<&'a str> {
+        settings.proxy(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_proxy() {
+        let http_settings = settings(&[]);
+        assert_eq!(proxy(&http_settings, "https://example.com/a.zip"), None);
+
+        let http_settings = settings(&[
+            ("HTTPS_PROXY", "http://secure-proxy:3128"),
+            ("http_proxy", "http://proxy:3128"),
+            ("NO_PROXY", "localhost, .internal.example.com,example.org"),
+        ]);
+        assert_eq!(
+            proxy(&http_settings, "https://example.com/a.zip"),
+            Some("http://secure-proxy:3128")
+        );
+        assert_eq!(
+            proxy(&http_settings, "http://example.com/a.zip"),
+            Some("http://proxy:3128")
+        );
+        for url in [
+            "http://localhost:8080/a.zip",
+            "https://internal.example.com/a.zip",
+            "https://git.internal.example.com/a.zip",
+            "https://EXAMPLE.org/a.zip",
+        ] {
+            assert_eq!(proxy(&http_settings, url), None, "{}", url);
+        }
+        assert_eq!(
+            proxy(&http_settings, "https://notexample.org/a.zip"),
+            Some("http://secure-proxy:3128")
+        );
+
+        let http_settings = settings(&[("ALL_PROXY", "http://proxy:3128"), ("no_proxy", "*")]);
+        assert_eq!(proxy(&http_settings, "https://example.com/a.zip"), None);
+        let http_settings = settings(&[("all_proxy", "http://proxy:3128")]);
+        assert_eq!(
+            proxy(&http_settings, "https://example.com/a.zip"),
+            Some("http://proxy:3128")
+        );
+    }
+
+    #[test]
+    fn test_agent_with_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request_line = String::new();
+            _ = BufReader::new(stream.try_clone().unwrap())
+                .read_line(&mut request_line)
+                .unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                .unwrap();
+            request_line
+        });
+
+        let http_settings = settings(&[("HTTP_PROXY", &proxy_url)]);
+        let url = "http://registry.example.com/registry.zip";
+        let response = http_settings.agent(url).unwrap().get(url).call().unwrap();
+        assert_eq!(response.into_string().unwrap(), "ok");
+        // The request is sent to the proxy with the absolute URL.
+        assert!(server
+            .join()
+            .unwrap()
+            .starts_with("GET http://registry.example.com/registry.zip "));
+
+        let http_settings = settings(&[("HTTPS_PROXY", "ftp://proxy")]);
+        assert!(matches!(
+            http_settings.agent("https://example.com/a.zip"),
+            Err(InvalidProxy { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ca_certs() {
+        assert!(tls_config(Path::new("tests/test_data/test_ca.pem")).is_ok());
+
+        let http_settings = settings(&[(CA_CERTS_ENV_VAR, "tests/test_data/test_ca.pem")]);
+        assert!(http_settings.agent("https://example.com/a.zip").is_ok());
+
+        for ca_certs in [
+            "tests/test_data/does_not_exist.pem",
+            "tests/test_data/semconv_registry_v1.26.0.zip",
+        ] {
+            let http_settings = settings(&[(CA_CERTS_ENV_VAR, ca_certs)]);
+            assert!(matches!(
+                http_settings.agent("https://example.com/a.zip"),
+                Err(InvalidCaCertificates { .. })
+            ));
+        }
+    }
+}
@@ -8,27 +8,113 @@ use std::io;
 use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use gix::bstr::ByteSlice;
 use gix::clone::PrepareFetch;
 use gix::create::Kind;
 use gix::remote::fetch::Shallow;
-use gix::{create, open, progress};
+use gix::remote::Direction;
+use gix::{create, open, progress, ThreadSafeRepository};
 use miette::Diagnostic;
-use serde::Serialize;
-use tempdir::TempDir;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use url::Url;
 
 use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages};
 
+use crate::cache_lock::{CacheLock, CacheTempDir, ReadLock, DEFAULT_STALE_LOCK_AGE};
+use crate::download::{Downloaded, RetryPolicy, Validators};
+use crate::fetched::FetchedRegistries;
+use crate::git_auth::GitAuth;
+use crate::http_agent::HttpSettings;
+use crate::lockfile::LockMode;
+use crate::mirrors::Mirrors;
 use crate::registry_path::RegistryPath;
-use crate::Error::{GitError, InvalidRegistryArchive, UnsupportedRegistryArchive};
+use crate::signature::{Signer, TrustPolicy};
+use crate::store::Manifest;
+use crate::Error::{
+    ArchiveChecksumMismatch, CacheDirNotCreated, GitError, InvalidRegistryArchive,
+    InvalidRegistryOverlay, InvalidRegistryPath, NoMatchingGitTag, OciError, RegistryNotCached,
+    RegistrySignatureInvalid, UnsupportedRegistryArchive,
+};
 
+pub mod bundle;
+mod cache_lock;
+pub mod cached_registry;
+mod download;
+mod fetched;
+mod git_auth;
+mod http_agent;
+pub mod lockfile;
+mod mirrors;
+mod oci;
 pub mod registry_path;
+mod signature;
+mod store;
+pub mod test;
 
-/// The extension for a tar gz archive.
-const TAR_GZ_EXT: &str = ".tar.gz";
-/// The extension for a zip archive.
-const ZIP_EXT: &str = ".zip";
+/// The directory of the registry cache, relative to the home directory.
+const CACHE_DIR: &str = ".weaver/semconv_registry_cache";
+/// The file containing the metadata of a cache entry.
+const CACHE_METADATA_FILE: &str = "metadata.json";
+/// The directory of a cache entry containing the registry.
+const CACHE_REGISTRY_DIR: &str = "registry";
+/// The infix of the names of the cache entries being removed.
+const REMOVED_INFIX: &str = ".removed-";
+
+/// The default freshness of the cached registries (1 day).
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The supported archive formats of the registries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ArchiveFormat {
+    /// A plain tar archive (`.tar`).
+    Tar,
+    /// A gzip-compressed tar archive (`.tar.gz` or `.tgz`).
+    TarGz,
+    /// A zstd-compressed tar archive (`.tar.zst`).
+    TarZst,
+    /// A zip archive (`.zip`).
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Returns the file extensions of the format, the usual one first.
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            ArchiveFormat::Tar => &[".tar"],
+            ArchiveFormat::TarGz => &[".tar.gz", ".tgz"],
+            ArchiveFormat::TarZst => &[".tar.zst"],
+            ArchiveFormat::Zip => &[".zip"],
+        }
+    }
+
+    /// Returns the usual file extension of the format.
+    pub(crate) fn extension(self) -> &'static str {
+        self.extensions()[0]
+    }
+
+    /// Returns the archive format of a file name or URL, based on its
+    /// extension.
+    pub(crate) fn from_file_name(file_name: &str) -> Option<Self> {
+        [
+            ArchiveFormat::Tar,
+            ArchiveFormat::TarGz,
+            ArchiveFormat::TarZst,
+            ArchiveFormat::Zip,
+        ]
+        .into_iter()
+        .find(|format| {
+            format
+                .extensions()
+                .iter()
+                .any(|extension| file_name.ends_with(extension))
+        })
+    }
+}
 
 /// An error that can occur while creating or using a cache.
 #[derive(thiserror::Error, Debug, Clone, Serialize, Diagnostic)]
@@ -45,6 +131,15 @@ pub enum Error {
         message: String,
     },
 
+    /// An entry of the cache directory can't be read or removed.
+    #[error("The cache entry `{path}` can't be accessed: {error}")]
+    CacheDirNotAccessible {
+        /// The path of the cache entry
+        path: String,
+        /// The error message
+        error: String,
+    },
+
     /// Git repo not created.
     #[error("Git repo `{repo_url}` not created: {message}")]
     GitRepoNotCreated {
@@ -63,6 +158,51 @@ pub enum Error {
         message: String,
     },
 
+    /// An invalid Git credentials file.
+    #[error("The Git credentials file `{path}` is invalid: {error}")]
+    InvalidGitCredentials {
+        /// The credentials file path
+        path: String,
+        /// The error message
+        error: String,
+    },
+
+    /// An invalid proxy URL.
+    #[error("The proxy `{proxy}` is invalid: {error}")]
+    InvalidProxy {
+        /// The proxy URL
+        proxy: String,
+        /// The error message
+        error: String,
+    },
+
+    /// An invalid file of additional root certificates.
+    #[error("The root certificates file `{path}` is invalid: {error}")]
+    InvalidCaCertificates {
+        /// The root certificates file path
+        path: String,
+        /// The error message
+        error: String,
+    },
+
+    /// An invalid mirrors file.
+    #[error("The registry mirrors file `{path}` is invalid: {error}")]
+    InvalidMirrors {
+        /// The mirrors file path
+        path: String,
+        /// The error message
+        error: String,
+    },
+
+    /// An invalid trust file.
+    #[error("The registry trust file `{path}` is invalid: {error}")]
+    InvalidTrustFile {
+        /// The trust file path
+        path: String,
+        /// The error message
+        error: String,
+    },
+
     /// An invalid registry path.
     #[error("The registry path `{path}` is invalid: {error}")]
     InvalidRegistryPath {
@@ -72,8 +212,17 @@ pub enum Error {
         error: String,
     },
 
+    /// No tag of the Git repository matches the requested version range.
+    #[error("No tag of the Git repository `{repo_url}` matches the version range `{version_req}`")]
+    NoMatchingGitTag {
+        /// The git repo URL
+        repo_url: String,
+        /// The requested version range
+        version_req: String,
+    },
+
     /// An invalid registry archive.
-    #[error("This archive `{archive}` is not supported. Supported formats are: .tar.gz, .zip")]
+    #[error("This archive `{archive}` is not supported. Supported formats are: .tar, .tar.gz, .tgz, .tar.zst, .zip")]
     UnsupportedRegistryArchive {
         /// The registry archive path
         archive: String,
@@ -87,6 +236,99 @@ pub enum Error {
         /// The error message
         error: String,
     },
+
+    /// An invalid registry bundle.
+    #[error("The registry bundle `{path}` is invalid: {error}")]
+    InvalidBundle {
+        /// The bundle path
+        path: String,
+        /// The error message
+        error: String,
+    },
+
+    /// An overlay directory can't be applied to a registry.
+    #[error("The overlay `{overlay}` can't be applied to the registry: {error}")]
+    InvalidRegistryOverlay {
+        /// The overlay directory path
+        overlay: String,
+        /// The error message
+        error: String,
+    },
+
+    /// The checksum of a downloaded archive doesn't match the expected one.
+    #[error("The SHA-256 checksum of the registry archive `{archive}` is `{actual}`, expected `{expected}`")]
+    ArchiveChecksumMismatch {
+        /// The registry archive URL
+        archive: String,
+        /// The expected SHA-256 digest
+        expected: String,
+        /// The SHA-256 digest of the downloaded archive
+        actual: String,
+    },
+
+    /// The signature of a remote registry can't be verified against its
+    /// expected signer.
+    #[error("The signature of the registry `{registry_path}` can't be verified: {error}")]
+    #[diagnostic(help("The expected signers of the remote registries are declared in the registry trust file (`WEAVER_REGISTRY_TRUST`)."))]
+    RegistrySignatureInvalid {
+        /// The registry path
+        registry_path: String,
+        /// The error message
+        error: String,
+    },
+
+    /// An OCI artifact can't be pulled.
+    #[error("Failed to pull the OCI artifact `{reference}`: {message}")]
+    OciError {
+        /// The OCI artifact reference
+        reference: String,
+        /// The error message
+        message: String,
+    },
+
+    /// An invalid lockfile.
+    #[error("The lockfile `{path}` is invalid: {error}")]
+    InvalidLockfile {
+        /// The lockfile path
+        path: String,
+        /// The error message
+        error: String,
+    },
+
+    /// A remote registry is not in the lockfile in locked mode.
+    #[error("The registry `{registry_path}` is not in the lockfile `{lockfile}`")]
+    #[diagnostic(help(
+        "Run the command once without `--locked` or `--frozen` to update the lockfile."
+    ))]
+    RegistryNotLocked {
+        /// The registry path
+        registry_path: String,
+        /// The lockfile path
+        lockfile: String,
+    },
+
+    /// A remote registry doesn't match its revision in the lockfile in locked
+    /// mode.
+    #[error("The registry `{registry_path}` resolved to the revision `{resolved}`, but the lockfile `{lockfile}` records `{locked}`")]
+    #[diagnostic(help("Run the command without `--locked` or `--frozen` to update the lockfile if this change is expected."))]
+    LockedRegistryChanged {
+        /// The registry path
+        registry_path: String,
+        /// The lockfile path
+        lockfile: String,
+        /// The revision recorded in the lockfile
+        locked: String,
+        /// The revision of the registry
+        resolved: String,
+    },
+
+    /// A remote registry is not in the cache in offline mode.
+    #[error("The registry `{registry_path}` is not in the cache and can't be downloaded in offline mode")]
+    #[diagnostic(help("Run the command once without `--offline` to download the registry."))]
+    RegistryNotCached {
+        /// The registry path
+        registry_path: String,
+    },
 }
 
 impl From<Error> for DiagnosticMessages {
@@ -95,84 +337,755 @@ impl From<Error> for DiagnosticMessages {
     }
 }
 
+/// The settings of the persistent cache of the remote registries.
+#[derive(Debug, Clone)]
+struct CacheOptions {
+    /// The directory of the cache.
+    dir: PathBuf,
+    /// The duration during which a cached registry is used without being
+    /// downloaded again.
+    ttl: Duration,
+    /// Only use the cached registries, never access the network.
+    offline: bool,
+    /// The mirrors of the remote registries.
+    mirrors: Mirrors,
+    /// The expected signers of the remote registries.
+    trust: TrustPolicy,
+}
+
+/// The persistent cache of the remote registries and its settings, given to
+/// [`RegistryRepo::try_new`].
+#[derive(Debug, Clone)]
+pub struct RegistryCache {
+    options: CacheOptions,
+    /// The lockfile of the remote registries and how it is used, if any.
+    lockfile: Option<(PathBuf, LockMode)>,
+    /// The registries already fetched through the cache (see the `fetched`
+    /// module).
+    fetched: FetchedRegistries,
+}
+
+impl RegistryCache {
+    /// Returns the cache stored in the home directory of the user
+    /// (`~/.weaver/semconv_registry_cache`), with the default freshness TTL
+    /// (1 day). The mirrors and the expected signers of the remote registries
+    /// are read from the environment (see the `mirrors` and `signature`
+    /// modules).
+    pub fn try_default() -> Result<Self, Error> {
+        Ok(Self {
+            options: CacheOptions {
+                dir: cached_registry::default_cache_dir()?,
+                ttl: DEFAULT_CACHE_TTL,
+                offline: false,
+                mirrors: Mirrors::from_env()?,
+                trust: TrustPolicy::from_env()?,
+            },
+            lockfile: None,
+            fetched: FetchedRegistries::default(),
+        })
+    }
+
+    /// Stores the cache in the given directory.
+    #[must_use]
+    pub fn with_dir(mut self, dir: PathBuf) -> Self {
+        self.options.dir = dir;
+        // The registries fetched through the previous directory are not shared.
+        self.fetched = FetchedRegistries::default();
+        self
+    }
+
+    /// Sets the duration during which a cached registry is used without
+    /// being downloaded again.
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.options.ttl = ttl;
+        self
+    }
+
+    /// Enables or disables the offline mode. In offline mode, the remote
+    /// registries are only read from the cache, and a registry missing from
+    /// the cache is an error.
+    #[must_use]
+    pub fn with_offline_mode(mut self, offline: bool) -> Self {
+        self.options.offline = offline;
+        self
+    }
+
+    /// Checks or records the revisions of the remote registries in the given
+    /// lockfile (see the `lockfile` module).
+    #[must_use]
+    pub fn with_lockfile(mut self, path: PathBuf, mode: LockMode) -> Self {
+        self.lockfile = Some((path, mode));
+        self
+    }
+
+    /// Returns the directory of the cache.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.options.dir
+    }
+}
+
+/// The metadata of a remote registry stored in the cache.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// The registry path representation (with the tag selected by a version
+    /// range, if any).
+    registry_path: String,
+    /// The Git reference checked out.
+    refspec: Option<String>,
+    /// The exact revision of the registry (see [`RegistryRepo::revision`]).
+    #[serde(default)]
+    revision: Option<String>,
+    /// The path of the registry, relative to the cache entry directory.
+    path: PathBuf,
+    /// The download time, in seconds since the Unix epoch.
+    fetched_at: u64,
+    /// The HTTP validators of a remote archive, sent back to the server to
+    /// check whether the archive changed.
+    #[serde(default, skip_serializing_if = "Validators::is_empty")]
+    validators: Validators,
+    /// The manifest of the registry, whose files are hard links to the
+    /// objects of the content-addressable store (see the `store` module).
+    #[serde(default, skip_serializing_if = "Manifest::is_empty")]
+    objects: Manifest,
+    /// The signer the registry was verified against, if any (see the
+    /// `signature` module).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signer: Option<String>,
+}
+
+/// The result of the fetch of a remote registry.
+enum Fetched {
+    /// The registry was fetched, with the HTTP validators of its archive (if
+    /// any).
+    Registry(Box<RegistryRepo>, Validators),
+    /// The cached registry hasn't changed.
+    NotModified,
+}
+
+impl CacheEntry {
+    /// Reads the cache entry stored in the given directory, or returns `None`
+    /// if there is no such entry or if it can't be read.
+    fn load(entry_dir: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(entry_dir.join(CACHE_METADATA_FILE)).ok()?;
+        let entry: Self = serde_json::from_str(&content).ok()?;
+        entry_dir.join(&entry.path).is_dir().then_some(entry)
+    }
+
+    /// Writes the metadata of the cache entry into the given directory.
+    fn store(&self, entry_dir: &Path) -> Result<(), Error> {
+        let content = serde_json::to_string(self).map_err(|e| CacheDirNotCreated {
+            message: e.to_string(),
+        })?;
+        std::fs::write(entry_dir.join(CACHE_METADATA_FILE), content).map_err(|e| {
+            CacheDirNotCreated {
+                message: e.to_string(),
+            }
+        })
+    }
+
+    /// Removes the cache entry stored in the given directory. The entry is
+    /// first renamed, then deleted, so an interrupted removal never leaves a
+    /// partially deleted entry in place. A renamed entry that can't be
+    /// deleted is removed like a temporary directory by the `cache` commands.
+    fn remove(entry_dir: &Path) -> io::Result<()> {
+        let mut trash_dir = entry_dir.as_os_str().to_owned();
+        trash_dir.push(format!(
+            "{}{}-{}",
+            REMOVED_INFIX,
+            std::process::id(),
+            now_nanos()
+        ));
+        let trash_dir = PathBuf::from(trash_dir);
+        std::fs::rename(entry_dir, &trash_dir)?;
+        _ = std::fs::remove_dir_all(trash_dir);
+        Ok(())
+    }
+
+    /// Returns true if the entry was downloaded less than `ttl` ago.
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        now_secs() < self.fetched_at.saturating_add(ttl.as_secs())
+    }
+}
+
+/// Returns the current time, in seconds since the Unix epoch.
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns the current time, in nanoseconds since the Unix epoch.
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default()
+}
+
+/// Removes the content of a directory.
+fn clear_dir(dir: &Path) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the files of a directory into another one, replacing the existing
+/// files. The copies are writable, even if the files are read-only objects of
+/// the store. The `.git` directories are skipped.
+fn copy_dir(src: &Path, dst: &Path) -> io::Result<()> {
+    let entries = walkdir::WalkDir::new(src)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git");
+    for entry in entries {
+        let entry = entry.map_err(io::Error::from)?;
+        let relative_path = entry.path().strip_prefix(src).map_err(io::Error::other)?;
+        let target = dst.join(relative_path);
+        if entry.file_type().is_dir() {
+            create_dir_all(&target)?;
+        } else {
+            store::copy_file(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
 /// A semantic convention registry repository that can be:
 /// - A simple wrapper around a local directory
 /// - Initialized from a Git repository
 /// - Initialized from a Git archive
-#[derive(Default)]
+/// - Initialized from an OCI artifact
+///
+/// The Git repositories, the remote archives, and the OCI artifacts are stored in a persistent
+/// cache (see [`RegistryCache`]) keyed by their registry path (URL, refspec,
+/// and sub-folder). A cached registry is reused until its freshness TTL
+/// expires, and in offline mode the network is never accessed. The files of
+/// the cached registries are stored once in a content-addressable store
+/// shared by all the entries (see the `store` module). A cache entry is read
+/// locked while a repo loaded from it is alive, so it is neither replaced nor
+/// removed by another process in the meantime.
+///
+/// A registry path loaded several times through the same cache is only
+/// fetched once (see [`RegistryRepo::try_new`]). The clones of a repo share its temporary
+/// directory, if any, which is deleted with the last of them.
+#[derive(Default, Clone)]
 pub struct RegistryRepo {
     // A unique identifier for the registry (e.g. main, baseline, etc.)
     id: String,
     registry_path: String,
     path: PathBuf,
+    // The Git reference checked out (e.g. the tag selected by a version range).
+    refspec: Option<String>,
+    // The exact revision of a remote registry (commit or digest).
+    revision: Option<String>,
     // Need to keep the tempdir live for the lifetime of the RegistryRepo.
     #[allow(dead_code)]
-    tmp_dir: Option<TempDir>,
+    tmp_dir: Option<Arc<CacheTempDir>>,
+    // The read lock of the cache entry of the registry, if any, released with
+    // the last clone of the RegistryRepo and of the cache it was fetched
+    // through (see the `fetched` module).
+    #[allow(dead_code)]
+    read_lock: Option<Arc<ReadLock>>,
+    // The warnings raised while fetching the registry.
+    warnings: Vec<String>,
 }
 
 impl RegistryRepo {
     /// Creates a new `RegistryRepo` from a `RegistryPath` object that
     /// specifies the location of the registry.
-    pub fn try_new(id: &str, registry_path: &RegistryPath) -> Result<Self, Error> {
+    ///
+    /// A registry path already loaded through the same cache reuses the
+    /// registry fetched the first time (see the `fetched` module), so each
+    /// registry is fetched once per command.
+    ///
+    /// The remote registries are fetched through the given cache. The
+    /// revision of a remote registry is checked or recorded in the lockfile
+    /// of the cache, if any (see [`RegistryCache::with_lockfile`]).
+    pub fn try_new(
+        id: &str,
+        registry_path: &RegistryPath,
+        cache: &RegistryCache,
+    ) -> Result<Self, Error> {
+        let repo = Self::try_new_shared(id, registry_path, cache)?;
+        if let Some((path, mode)) = &cache.lockfile {
+            lockfile::lock(path, *mode, registry_path, &repo)?;
+        }
+        Ok(repo)
+    }
+
+    /// Creates a new `RegistryRepo`, reusing the registry already fetched
+    /// through the cache for the same registry path, if any.
+    fn try_new_shared(
+        id: &str,
+        registry_path: &RegistryPath,
+        cache: &RegistryCache,
+    ) -> Result<Self, Error> {
+        if let Some(repo) = cache.fetched.get(registry_path, id) {
+            return Ok(repo);
+        }
+        let repo = Self::try_new_with_options(id, registry_path, &cache.options)?;
+        cache.fetched.insert(registry_path, &repo);
+        Ok(repo)
+    }
+
+    /// Creates a new `RegistryRepo` with the given cache settings.
+    fn try_new_with_options(
+        id: &str,
+        registry_path: &RegistryPath,
+        options: &CacheOptions,
+    ) -> Result<Self, Error> {
         let registry_path_repr = registry_path.to_string();
         match registry_path {
             RegistryPath::LocalFolder { path } => Ok(Self {
                 id: id.to_owned(),
                 registry_path: registry_path_repr,
                 path: path.into(),
+                refspec: None,
+                revision: None,
                 tmp_dir: None,
+                read_lock: None,
+                warnings: vec![],
             }),
             RegistryPath::GitRepo {
-                url, sub_folder, ..
-            } => Self::try_from_git_url(id, url, sub_folder, registry_path_repr),
+                url,
+                refspec,
+                sub_folder,
+            } => {
+                if options.trust.signer(url).is_some() {
+                    return Err(RegistrySignatureInvalid {
+                        registry_path: registry_path_repr,
+                        error: "Only the remote archives and the OCI artifacts can be verified"
+                            .to_owned(),
+                    });
+                }
+                Self::try_from_cache(id, registry_path, options, None, |target_dir, _| {
+                    Self::fetch_with_mirrors(url, &options.mirrors, target_dir, |url| {
+                        Self::try_from_git_url(
+                            id,
+                            url,
+                            refspec,
+                            sub_folder,
+                            registry_path_repr.clone(),
+                            &options.dir,
+                            target_dir,
+                        )
+                        .map(|repo| Fetched::Registry(Box::new(repo), Validators::default()))
+                    })
+                })
+            }
             RegistryPath::LocalArchive { path, sub_folder } => {
                 // Create a temporary directory for the repo that will be deleted
                 // when the RegistryRepo goes out of scope.
-                let tmp_dir = Self::create_tmp_repo()?;
-                Self::try_from_local_archive(
+                let tmp_dir = Self::create_tmp_repo(&options.dir)?;
+                let repo = Self::try_from_local_archive(
                     id,
                     path,
                     sub_folder.as_ref(),
-                    tmp_dir,
+                    tmp_dir.path(),
                     registry_path_repr,
-                )
+                )?;
+                Ok(Self {
+                    tmp_dir: Some(Arc::new(tmp_dir)),
+                    read_lock: None,
+                    warnings: vec![],
+                    ..repo
+                })
             }
-            RegistryPath::RemoteArchive { url, sub_folder } => {
-                // Create a temporary directory for the repo that will be deleted
-                // when the RegistryRepo goes out of scope.
-                let tmp_dir = Self::create_tmp_repo()?;
-                Self::try_from_remote_archive(
+            RegistryPath::RemoteArchive {
+                url,
+                sha256,
+                sub_folder,
+            } => {
+                let signer = options.trust.signer(url);
+                Self::try_from_cache(
                     id,
-                    url,
-                    sub_folder.as_ref(),
-                    tmp_dir,
-                    registry_path_repr,
+                    registry_path,
+                    options,
+                    signer.as_ref(),
+                    |target_dir, validators| {
+                        Self::fetch_with_mirrors(url, &options.mirrors, target_dir, |url| {
+                            Self::try_from_remote_archive(
+                                id,
+                                url,
+                                sha256.as_ref(),
+                                sub_folder.as_ref(),
+                                signer.as_ref(),
+                                validators,
+                                target_dir,
+                                registry_path_repr.clone(),
+                            )
+                        })
+                    },
                 )
             }
+            RegistryPath::OciArtifact {
+                registry,
+                repository,
+                tag,
+                digest,
+                sub_folder,
+            } => {
+                let url = format!("oci://{}/{}", registry, repository);
+                let signer = options.trust.signer(&url);
+                let fetch = |target_dir: &Path, _: Option<&Validators>| {
+                    Self::fetch_with_mirrors(&url, &options.mirrors, target_dir, |url| {
+                        let (registry, repository) = url
+                            .strip_prefix("oci://")
+                            .and_then(|reference| reference.split_once('/'))
+                            .ok_or_else(|| InvalidRegistryPath {
+                                path: url.to_owned(),
+                                error:
+                                    "Invalid OCI mirror, expected `oci://<registry>/<repository>`"
+                                        .to_owned(),
+                            })?;
+                        Self::try_from_oci_artifact(
+                            id,
+                            registry,
+                            repository,
+                            tag.as_ref(),
+                            digest.as_ref(),
+                            sub_folder.as_ref(),
+                            signer.as_ref(),
+                            target_dir,
+                        )
+                        .map(|repo| Fetched::Registry(Box::new(repo), Validators::default()))
+                    })
+                };
+                Self::try_from_cache(id, registry_path, options, signer.as_ref(), fetch)
+            }
+            RegistryPath::Overlay { base, overlay } => {
+                let base_repo = Self::try_new_with_options(id, base, options)?;
+                let tmp_dir = Self::create_tmp_repo(&options.dir)?;
+                Self::apply_overlay(&base_repo, Path::new(overlay), tmp_dir.path())?;
+                Ok(Self {
+                    id: id.to_owned(),
+                    registry_path: format!(
+                        "{}+overlay={}",
+                        base_repo.registry_path_repr(),
+                        overlay
+                    ),
+                    path: tmp_dir.path().to_path_buf(),
+                    refspec: base_repo.refspec.clone(),
+                    revision: base_repo.revision.clone(),
+                    tmp_dir: Some(Arc::new(tmp_dir)),
+                    read_lock: None,
+                    warnings: vec![],
+                })
+            }
+        }
+    }
+
+    /// Fetches a remote registry into the target directory from its URL and
+    /// the URLs of its mirrors, in the order of the mirrors (see the `mirrors`
+    /// module), until a fetch succeeds. The parent directory of the target
+    /// (the temporary directory of the cache entry) is emptied after a failed
+    /// fetch. The error of the last fetch is returned if all of them fail.
+    ///
+    /// The registry path representation of a registry fetched from a mirror
+    /// refers to its own URL, so the cache entry and the lockfile don't
+    /// depend on the mirror used.
+    fn fetch_with_mirrors(
+        url: &str,
+        mirrors: &Mirrors,
+        target_dir: &Path,
+        mut fetch: impl FnMut(&str) -> Result<Fetched, Error>,
+    ) -> Result<Fetched, Error> {
+        let mut result = None;
+        for fetch_url in mirrors.urls(url) {
+            match result {
+                Some(Ok(_)) => break,
+                Some(Err(_)) => {
+                    let tmp_dir = target_dir.parent().unwrap_or(target_dir);
+                    clear_dir(tmp_dir)
+                        .and_then(|()| create_dir_all(target_dir))
+                        .map_err(|e| CacheDirNotCreated {
+                            message: e.to_string(),
+                        })?;
+                }
+                None => {}
+            }
+            result = Some(fetch(&fetch_url).map(|fetched| match fetched {
+                Fetched::Registry(repo, validators) if fetch_url != url => Fetched::Registry(
+                    Box::new(Self {
+                        registry_path: match repo.registry_path.strip_prefix(fetch_url.as_str()) {
+                            Some(rest) => format!("{}{}", url, rest),
+                            None => repo.registry_path.clone(),
+                        },
+                        ..*repo
+                    }),
+                    validators,
+                ),
+                fetched => fetched,
+            }));
+        }
+        // The URL of the registry is always one of the URLs to fetch.
+        result.expect("no URL to fetch the registry from")
+    }
+
+    /// Copies a registry into the target directory, then the files of the
+    /// overlay directory over it (adding or replacing files). The registry
+    /// itself (e.g. a cache entry or a local folder) is left untouched.
+    fn apply_overlay(
+        base_repo: &RegistryRepo,
+        overlay: &Path,
+        target_dir: &Path,
+    ) -> Result<(), Error> {
+        let overlay_error = |error: String| InvalidRegistryOverlay {
+            overlay: overlay.display().to_string(),
+            error,
+        };
+        if !overlay.is_dir() {
+            return Err(overlay_error("Not a directory".to_owned()));
+        }
+        copy_dir(base_repo.path(), target_dir)
+            .map_err(|e| overlay_error(format!("Failed to copy the registry: {}", e)))?;
+        copy_dir(overlay, target_dir)
+            .map_err(|e| overlay_error(format!("Failed to copy the overlay: {}", e)))
+    }
+
+    /// Creates a new `RegistryRepo` from a remote registry stored in the
+    /// persistent cache.
+    ///
+    /// A fresh cache entry is reused as is. A missing, stale, or incomplete
+    /// entry (e.g. left by an interrupted process) is downloaded again with
+    /// the `fetch` function into a temporary directory which then replaces
+    /// the previous entry. In offline mode, any complete cached entry is
+    /// reused and a missing entry is an error.
+    ///
+    /// The HTTP validators of a stale entry, if any, are given to the `fetch`
+    /// function to make a conditional request. A stale entry that hasn't
+    /// changed is kept as is and becomes fresh again.
+    ///
+    /// The entry is locked while it is checked and replaced (see the
+    /// `cache_lock` module), so concurrent processes fetching the same
+    /// registry wait for the first one and reuse its entry. The previous
+    /// entry is renamed before being deleted, and an entry read by another
+    /// repo (of any process) is never replaced: the registry downloaded again
+    /// is then used from its temporary directory, deleted with the repo.
+    ///
+    /// When the registry has an expected signer, the `fetch` function
+    /// verifies its signature, and an entry verified against another signer
+    /// (or not verified) is treated as missing.
+    fn try_from_cache(
+        id: &str,
+        registry_path: &RegistryPath,
+        options: &CacheOptions,
+        signer: Option<&Signer>,
+        fetch: impl FnOnce(&Path, Option<&Validators>) -> Result<Fetched, Error>,
+    ) -> Result<Self, Error> {
+        let signer = signer.map(Signer::to_string);
+        let entry_dir = options.dir.join(Self::cache_key(registry_path));
+        create_dir_all(&options.dir).map_err(|e| CacheDirNotCreated {
+            message: e.to_string(),
+        })?;
+        let lock = CacheLock::acquire(&entry_dir, DEFAULT_STALE_LOCK_AGE)?;
+        let cached = match CacheEntry::load(&entry_dir).filter(|entry| entry.signer == signer) {
+            Some(entry) if options.offline || entry.is_fresh(options.ttl) => {
+                return Self::from_cache_entry(id, entry, &entry_dir, &lock);
+            }
+            None if options.offline => {
+                return Err(RegistryNotCached {
+                    registry_path: registry_path.to_string(),
+                });
+            }
+            cached => cached,
+        };
+
+        let tmp_dir = Self::create_tmp_repo(&options.dir)?;
+        let target_dir = tmp_dir.path().join(CACHE_REGISTRY_DIR);
+        create_dir_all(&target_dir).map_err(|e| CacheDirNotCreated {
+            message: e.to_string(),
+        })?;
+        let validators = cached
+            .as_ref()
+            .map(|entry| &entry.validators)
+            .filter(|validators| !validators.is_empty());
+        let (repo, validators) = match (fetch(&target_dir, validators)?, cached) {
+            (Fetched::Registry(repo, validators), _) => (*repo, validators),
+            (Fetched::NotModified, Some(entry)) => {
+                let entry = CacheEntry {
+                    fetched_at: now_secs(),
+                    ..entry
+                };
+                entry.store(&entry_dir)?;
+                return Self::from_cache_entry(id, entry, &entry_dir, &lock);
+            }
+            // Only a conditional fetch, i.e. of a cached entry, can be
+            // unchanged.
+            (Fetched::NotModified, None) => {
+                return Err(CacheDirNotCreated {
+                    message: format!("The registry `{}` was not downloaded", registry_path),
+                })
+            }
+        };
+        // The files of the registry are deduplicated with those of the other
+        // cached registries.
+        let objects = store::import(&options.dir, &repo.path).map_err(|e| CacheDirNotCreated {
+            message: format!("Failed to store the registry files: {}", e),
+        })?;
+        let warnings = repo.warnings;
+        let entry = CacheEntry {
+            registry_path: repo.registry_path,
+            refspec: repo.refspec,
+            revision: repo.revision,
+            path: repo
+                .path
+                .strip_prefix(tmp_dir.path())
+                .unwrap_or(Path::new(CACHE_REGISTRY_DIR))
+                .to_path_buf(),
+            fetched_at: now_secs(),
+            validators,
+            objects,
+            signer,
+        };
+        entry.store(tmp_dir.path())?;
+
+        // Replaces the stale or incomplete entry, if any.
+        if entry_dir.exists() {
+            if ReadLock::has_readers(&entry_dir, DEFAULT_STALE_LOCK_AGE) {
+                return Ok(Self {
+                    id: id.to_owned(),
+                    registry_path: entry.registry_path,
+                    path: tmp_dir.path().join(entry.path),
+                    refspec: entry.refspec,
+                    revision: entry.revision,
+                    tmp_dir: Some(Arc::new(tmp_dir)),
+                    read_lock: None,
+                    warnings,
+                });
+            }
+            CacheEntry::remove(&entry_dir).map_err(|e| CacheDirNotCreated {
+                message: e.to_string(),
+            })?;
+        }
+        if let Err(e) = std::fs::rename(tmp_dir.path(), &entry_dir) {
+            // A process not honoring the lock (e.g. an older version) may
+            // have stored the same entry concurrently.
+            if CacheEntry::load(&entry_dir).is_none() {
+                return Err(CacheDirNotCreated {
+                    message: e.to_string(),
+                });
+            }
+        } else {
+            _ = tmp_dir.into_path();
         }
+        Ok(Self {
+            warnings,
+            ..Self::from_cache_entry(id, entry, &entry_dir, &lock)?
+        })
+    }
+
+    /// Creates a new `RegistryRepo` from a cache entry, read locked until the
+    /// last clone of the repo is dropped. The caller holds the lock of the
+    /// entry.
+    fn from_cache_entry(
+        id: &str,
+        entry: CacheEntry,
+        entry_dir: &Path,
+        lock: &CacheLock,
+    ) -> Result<Self, Error> {
+        let read_lock = ReadLock::acquire(entry_dir, lock, DEFAULT_STALE_LOCK_AGE)?;
+        Ok(Self {
+            id: id.to_owned(),
+            registry_path: entry.registry_path,
+            path: entry_dir.join(entry.path),
+            refspec: entry.refspec,
+            revision: entry.revision,
+            tmp_dir: None,
+            read_lock: Some(Arc::new(read_lock)),
+            warnings: vec![],
+        })
+    }
+
+    /// Returns the cache key of a registry path, i.e. the hash of its
+    /// representation (URL, refspec, and sub-folder).
+    fn cache_key(registry_path: &RegistryPath) -> String {
+        format!("{:x}", Sha256::digest(registry_path.to_string()))
     }
 
     /// Creates a new `RegistryRepo` from a Git URL.
+    ///
+    /// The credentials of the remote (token, credentials file, or SSH key)
+    /// are read from the environment, see the `git_auth` module.
+    ///
+    /// The clone is shallow and is written into the target directory. If a
+    /// sub-folder is specified, only the files of this sub-folder are written
+    /// to the disk. gix doesn't support partial clones (e.g. `filter=blob:none`)
+    /// nor sparse checkouts, so all the objects of the selected commit are
+    /// still downloaded. The temporary repositories (e.g. to list the tags)
+    /// are created in the cache directory.
+    ///
+    /// If the refspec is a semver range, the tags of the repository are listed
+    /// and the highest matching version is checked out. The selected tag
+    /// replaces the range in the registry path representation, so the exact
+    /// version used is recorded.
     fn try_from_git_url(
         id: &str,
         url: &str,
+        refspec: &Option<String>,
         sub_folder: &Option<String>,
         registry_path: String,
+        cache_dir: &Path,
+        target_dir: &Path,
     ) -> Result<Self, Error> {
-        let tmp_dir = Self::create_tmp_repo()?;
-        let tmp_path = tmp_dir.path().to_path_buf();
+        let auth = GitAuth::from_env()?;
+        let warnings = auth.warnings().to_vec();
+        let (refspec, ref_name, registry_path) = match refspec {
+            Some(refspec) => match Self::version_req(refspec) {
+                Some(version_req) => {
+                    let version_req = version_req.map_err(|e| InvalidRegistryPath {
+                        path: registry_path.clone(),
+                        error: format!("Invalid version range `{}`: {}", refspec, e),
+                    })?;
+                    let tags = Self::list_git_tags(url, &auth, cache_dir)?;
+                    let tag =
+                        Self::highest_matching_tag(&version_req, tags.iter().map(String::as_str))
+                            .ok_or_else(|| NoMatchingGitTag {
+                                repo_url: url.to_owned(),
+                                version_req: refspec.clone(),
+                            })?
+                            .to_owned();
+                    let pinned_registry_path = RegistryPath::GitRepo {
+                        url: url.to_owned(),
+                        refspec: Some(tag.clone()),
+                        sub_folder: sub_folder.clone(),
+                    }
+                    .to_string();
+                    let ref_name = format!("refs/tags/{}", tag);
+                    (Some(tag), Some(ref_name), pinned_registry_path)
+                }
+                None => (Some(refspec.clone()), Some(refspec.clone()), registry_path),
+            },
+            None => (None, None, registry_path),
+        };
+
+        let target_path = target_dir.to_path_buf();
 
-        // Clones the repo into the temporary directory.
-        // Use shallow clone to save time and space.
+        // Clones the repo into the target directory.
+        // Use shallow clone to save time and space. When a sub-folder is
+        // specified, the repo is bare and only the files of the sub-folder are
+        // written, but the objects of the whole commit are fetched.
+        let (git_dir, kind) = match sub_folder {
+            Some(_) => (target_path.join(".git"), Kind::Bare),
+            None => (target_path.clone(), Kind::WithWorktree),
+        };
         let mut fetch = PrepareFetch::new(
             url,
-            tmp_path.clone(),
-            Kind::WithWorktree,
+            git_dir,
+            kind,
             create::Options {
                 destination_must_be_empty: true,
                 fs_capabilities: None,
             },
-            open::Options::isolated(),
+            open::Options::isolated().config_overrides(auth.config_overrides()),
         )
         .map_err(|e| GitError {
             repo_url: url.to_owned(),
@@ -180,65 +1093,255 @@ impl RegistryRepo {
         })?
         .with_shallow(Shallow::DepthAtRemote(
             NonZeroU32::new(1).expect("1 is not zero"),
-        ));
-
-        let (mut prepare, _outcome) = fetch
-            .fetch_then_checkout(progress::Discard, &AtomicBool::new(false))
-            .map_err(|e| GitError {
-                repo_url: url.to_owned(),
-                message: e.to_string(),
-            })?;
+        ))
+        .with_ref_name(ref_name.as_deref())
+        .map_err(|e| GitError {
+            repo_url: url.to_owned(),
+            message: e.to_string(),
+        })?;
 
-        let (_repo, _outcome) = prepare
-            .main_worktree(progress::Discard, &AtomicBool::new(false))
-            .map_err(|e| GitError {
-                repo_url: url.to_owned(),
-                message: e.to_string(),
-            })?;
+        if auth.has_credentials() {
+            fetch = fetch.configure_connection(move |connection| {
+                _ = connection.set_credentials(auth.credentials_fn());
+                Ok(())
+            });
+        }
 
         // Determines the final path to the repo taking into account the sub_folder.
-        let path = if let Some(sub_folder) = sub_folder {
-            let path_to_repo = tmp_path.join(sub_folder);
-
-            // Checks the existence of the path in the repo.
-            // If the path doesn't exist, returns an error.
-            if !path_to_repo.exists() {
-                return Err(GitError {
+        let head_commit = |repo: &gix::Repository| {
+            repo.head_id()
+                .map(|id| id.detach().to_string())
+                .map_err(|e| GitError {
                     repo_url: url.to_owned(),
-                    message: format!("Path `{}` not found in repo", sub_folder),
-                });
-            }
-
-            path_to_repo
-        } else {
-            tmp_path
+                    message: e.to_string(),
+                })
         };
+        let (path, revision) = if let Some(sub_folder) = sub_folder {
+            let (repo, _outcome) = fetch
+                .fetch_only(progress::Discard, &AtomicBool::new(false))
+                .map_err(|e| GitError {
+                    repo_url: url.to_owned(),
+                    message: e.to_string(),
+                })?;
 
-        Ok(Self {
-            id: id.to_owned(),
-            registry_path,
+            let path_to_repo = target_path.join(sub_folder);
+            Self::checkout_sub_folder(&repo, sub_folder, &path_to_repo).map_err(|message| {
+                GitError {
+                    repo_url: url.to_owned(),
+                    message,
+                }
+            })?;
+            (path_to_repo, head_commit(&repo)?)
+        } else {
+            let (mut prepare, _outcome) = fetch
+                .fetch_then_checkout(progress::Discard, &AtomicBool::new(false))
+                .map_err(|e| GitError {
+                    repo_url: url.to_owned(),
+                    message: e.to_string(),
+                })?;
+
+            let (repo, _outcome) = prepare
+                .main_worktree(progress::Discard, &AtomicBool::new(false))
+                .map_err(|e| GitError {
+                    repo_url: url.to_owned(),
+                    message: e.to_string(),
+                })?;
+            let revision = head_commit(&repo)?;
+            (target_path, revision)
+        };
+
+        Ok(Self {
+            id: id.to_owned(),
+            registry_path,
             path,
-            tmp_dir: Some(tmp_dir),
+            refspec,
+            revision: Some(revision),
+            tmp_dir: None,
+            read_lock: None,
+            warnings,
+        })
+    }
+
+    /// Writes the files of a sub-folder of the `HEAD` commit of a repository
+    /// into the target directory. The objects of the commit must have been
+    /// fetched, this is not a Git sparse checkout.
+    fn checkout_sub_folder(
+        repo: &gix::Repository,
+        sub_folder: &str,
+        target_dir: &Path,
+    ) -> Result<(), String> {
+        let tree = repo.head_tree().map_err(|e| e.to_string())?;
+        let sub_tree = tree
+            .lookup_entry_by_path(sub_folder)
+            .map_err(|e| e.to_string())?
+            .filter(|entry| entry.mode().is_tree())
+            .ok_or_else(|| format!("Path `{}` not found in repo", sub_folder))?
+            .object()
+            .map_err(|e| e.to_string())?
+            .into_tree();
+        Self::write_tree(&sub_tree, target_dir)
+    }
+
+    /// Writes the files of a Git tree into a directory, recursively.
+    /// Symbolic links and submodules are skipped.
+    fn write_tree(tree: &gix::Tree<'_>, dir: &Path) -> Result<(), String> {
+        create_dir_all(dir).map_err(|e| e.to_string())?;
+        for entry in tree.iter() {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let filename = entry.filename().to_str().map_err(|e| e.to_string())?;
+            if matches!(filename, "" | "." | "..") || filename.contains(['/', '\\']) {
+                return Err(format!("Invalid file name `{}` in repo", filename));
+            }
+            let path = dir.join(filename);
+            if entry.mode().is_tree() {
+                let object = entry.object().map_err(|e| e.to_string())?;
+                Self::write_tree(&object.into_tree(), &path)?;
+            } else if entry.mode().is_blob() {
+                let object = entry.object().map_err(|e| e.to_string())?;
+                std::fs::write(&path, &object.data).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the version range of a refspec, or `None` if the refspec is a
+    /// plain tag or branch name. A refspec is a version range if it starts
+    /// with a comparison operator (`^`, `~`, `=`, `<`, `>`) or contains a
+    /// wildcard (`*`).
+    fn version_req(refspec: &str) -> Option<Result<VersionReq, semver::Error>> {
+        (refspec.starts_with(['^', '~', '=', '<', '>']) || refspec.contains('*'))
+            .then(|| VersionReq::parse(refspec))
+    }
+
+    /// Lists the tags of a remote Git repository without cloning it.
+    fn list_git_tags(url: &str, auth: &GitAuth, cache_dir: &Path) -> Result<Vec<String>, Error> {
+        let git_error = |message: String| GitError {
+            repo_url: url.to_owned(),
+            message,
+        };
+        // A bare repository is required to connect to the remote, it is
+        // deleted once the tags are listed.
+        let tmp_dir = Self::create_tmp_repo(cache_dir)?;
+        let repo = ThreadSafeRepository::init_opts(
+            tmp_dir.path(),
+            Kind::Bare,
+            create::Options::default(),
+            open::Options::isolated().config_overrides(auth.config_overrides()),
+        )
+        .map_err(|e| git_error(e.to_string()))?
+        .to_thread_local();
+        let remote = repo
+            .remote_at(url)
+            .map_err(|e| git_error(e.to_string()))?
+            .with_refspecs(Some("+refs/tags/*:refs/tags/*"), Direction::Fetch)
+            .map_err(|e| git_error(e.to_string()))?;
+        let mut connection = remote
+            .connect(Direction::Fetch)
+            .map_err(|e| git_error(e.to_string()))?;
+        if auth.has_credentials() {
+            _ = connection.set_credentials(auth.credentials_fn());
+        }
+        let (ref_map, _) = connection
+            .ref_map(progress::Discard, Default::default())
+            .map_err(|e| git_error(e.to_string()))?;
+
+        Ok(ref_map
+            .remote_refs
+            .iter()
+            .filter_map(|remote_ref| {
+                let (name, _, _) = remote_ref.unpack();
+                name.to_str()
+                    .ok()?
+                    .strip_prefix("refs/tags/")
+                    .map(str::to_owned)
+            })
+            .collect())
+    }
+
+    /// Returns the tag with the highest version matching the version range.
+    /// The tags that are not versions (optionally prefixed with `v`) are
+    /// ignored.
+    fn highest_matching_tag<'a>(
+        version_req: &VersionReq,
+        tags: impl Iterator<Item = &'a str>,
+    ) -> Option<&'a str> {
+        tags.filter_map(|tag| {
+            let version = Self::tag_version(tag)?;
+            version_req.matches(&version).then_some((version, tag))
         })
+        .max_by(|(v1, _), (v2, _)| v1.cmp(v2))
+        .map(|(_, tag)| tag)
+    }
+
+    /// Returns the version of a version tag (e.g. `v1.26.0` or `1.26.0`).
+    fn tag_version(tag: &str) -> Option<Version> {
+        Version::parse(tag.strip_prefix('v').unwrap_or(tag)).ok()
+    }
+
+    /// Returns the version tags, sorted by version (the oldest first). The
+    /// other tags are ignored.
+    fn sorted_version_tags(tags: Vec<String>) -> Vec<String> {
+        let mut versions: Vec<_> = tags
+            .into_iter()
+            .filter_map(|tag| Some((Self::tag_version(&tag)?, tag)))
+            .collect();
+        versions.sort();
+        versions.into_iter().map(|(_, tag)| tag).collect()
+    }
+
+    /// Lists the versions of a remote registry, the oldest first: the version
+    /// tags (e.g. `v1.26.0` or `1.26.0`) of a Git repository or of an OCI
+    /// artifact repository. These versions can be selected with a semver
+    /// range in the registry path (e.g. `@^1.26` for a Git repository).
+    ///
+    /// The remote is always accessed, whatever the cache settings.
+    pub fn list_versions(
+        registry_path: &RegistryPath,
+        cache: &RegistryCache,
+    ) -> Result<Vec<String>, Error> {
+        let tags = match registry_path {
+            RegistryPath::GitRepo { url, .. } => {
+                Self::list_git_tags(url, &GitAuth::from_env()?, cache.dir())?
+            }
+            RegistryPath::OciArtifact {
+                registry,
+                repository,
+                ..
+            } => oci::OciClient::new(registry, repository)?
+                .list_tags()
+                .map_err(|message| OciError {
+                    reference: registry_path.to_string(),
+                    message,
+                })?,
+            RegistryPath::Overlay { base, .. } => return Self::list_versions(base, cache),
+            RegistryPath::LocalFolder { .. }
+            | RegistryPath::LocalArchive { .. }
+            | RegistryPath::RemoteArchive { .. } => {
+                return Err(InvalidRegistryPath {
+                    path: registry_path.to_string(),
+                    error: "Only the Git repositories and the OCI artifacts have versions"
+                        .to_owned(),
+                })
+            }
+        };
+        Ok(Self::sorted_version_tags(tags))
     }
 
     /// Create a new `RegistryRepo` from a local archive.
-    /// The archive can be in `.tar.gz` or `.zip` format.
+    /// The archive can be in `.tar`, `.tar.gz` (or `.tgz`), `.tar.zst`, or `.zip` format.
     /// The sub_folder is used to filter the entries inside the archive to unpack.
-    /// The temporary directory is created in the `.weaver/semconv_registry_cache`.
-    /// The temporary directory is deleted when the `RegistryRepo` goes out of scope.
     ///
     /// Arguments:
     /// - `id`: The unique identifier for the registry.
     /// - `archive_filename`: The path to the archive file.
     /// - `sub_folder`: The sub-folder to unpack inside the archive.
-    /// - `target_dir`: The temporary target directory where the archive will be unpacked.
+    /// - `target_dir`: The target directory where the archive will be unpacked.
     /// - `registry_path`: The registry path representation (for debug purposes).
     fn try_from_local_archive(
         id: &str,
         archive_filename: &str,
         sub_folder: Option<&String>,
-        target_dir: TempDir,
+        target_dir: &Path,
         registry_path: String,
     ) -> Result<Self, Error> {
         let archive_path = Path::new(archive_filename);
@@ -252,24 +1355,52 @@ impl RegistryRepo {
             archive: archive_filename.to_owned(),
             error: e.to_string(),
         })?;
-        let target_path_buf = target_dir.path().to_path_buf();
+        let target_path_buf = target_dir.to_path_buf();
 
-        // Process the supported formats (i.e.: `.tar.gz`, and `.zip`)
-        if archive_filename.ends_with(TAR_GZ_EXT) {
-            Self::unpack_tar_gz(archive_filename, archive_file, &target_path_buf, sub_folder)?;
-        } else if archive_filename.ends_with(ZIP_EXT) {
-            Self::unpack_zip(archive_filename, archive_file, &target_path_buf, sub_folder)?;
-        } else {
-            return Err(UnsupportedRegistryArchive {
-                archive: archive_filename.to_owned(),
-            });
+        // Process the supported formats (see `ArchiveFormat`)
+        match ArchiveFormat::from_file_name(archive_filename) {
+            Some(ArchiveFormat::Tar) => {
+                Self::unpack_tar(archive_filename, archive_file, &target_path_buf, sub_folder)?;
+            }
+            Some(ArchiveFormat::TarGz) => Self::unpack_tar(
+                archive_filename,
+                flate2::read::GzDecoder::new(archive_file),
+                &target_path_buf,
+                sub_folder,
+            )?,
+            Some(ArchiveFormat::TarZst) => {
+                let decoder =
+                    zstd::Decoder::new(archive_file).map_err(|e| InvalidRegistryArchive {
+                        archive: archive_filename.to_owned(),
+                        error: e.to_string(),
+                    })?;
+                Self::unpack_tar(archive_filename, decoder, &target_path_buf, sub_folder)?;
+            }
+            Some(ArchiveFormat::Zip) => {
+                Self::unpack_zip(archive_filename, archive_file, &target_path_buf, sub_folder)?;
+            }
+            None => {
+                return Err(UnsupportedRegistryArchive {
+                    archive: archive_filename.to_owned(),
+                });
+            }
+        }
+
+        // A bundle without sub-folder resolves to its default registry.
+        let path = match sub_folder {
+            None => bundle::default_registry_dir(&target_path_buf)?.unwrap_or(target_path_buf),
+            Some(_) => target_path_buf,
         };
 
         Ok(Self {
             id: id.to_owned(),
             registry_path,
-            path: target_path_buf,
-            tmp_dir: Some(target_dir),
+            path,
+            refspec: None,
+            revision: None,
+            tmp_dir: None,
+            read_lock: None,
+            warnings: vec![],
         })
     }
 
@@ -279,20 +1410,20 @@ impl RegistryRepo {
         &self.id
     }
 
-    /// Unpacks a tar.gz archive into the specified target directory.
+    /// Unpacks a tar archive (read from a possibly decompressed stream) into
+    /// the specified target directory.
     ///
     /// This first directory in the archive is skipped as it is the directory corresponding to the
     /// archive itself. The sub_folder is used to filter the entries to unpack. The sub_folder
     /// directory is also skipped in the folder hierarchy to only unpack the content of the
     /// sub-folder.
-    fn unpack_tar_gz(
+    fn unpack_tar(
         archive_filename: &str,
-        archive_file: File,
+        tar_stream: impl io::Read,
         target_path: &Path,
         sub_folder: Option<&String>,
     ) -> Result<(), Error> {
-        let tar_file = flate2::read::GzDecoder::new(archive_file);
-        let mut archive = tar::Archive::new(tar_file);
+        let mut archive = tar::Archive::new(tar_stream);
 
         for entry in archive.entries().map_err(|e| InvalidRegistryArchive {
             archive: archive_filename.to_owned(),
@@ -394,17 +1525,10 @@ impl RegistryRepo {
         _ = components.next();
 
         // If a sub-folder is specified, skip entries not in the sub-folder.
-        if let Some(sub_folder) = sub_folder {
-            if !sub_folder.trim().is_empty() {
-                // Skip any entry that is not in the sub-folder.
-                // If the entry is in the sub-folder, the sub-folder component is skipped.
-                let component = components.next();
-                if let Some(component) = component {
-                    if component.as_os_str() != sub_folder.as_str() {
-                        return None; // Skip entries not in the sub-folder
-                    }
-                }
-            }
+        // If the entry is in the sub-folder, the sub-folder components are skipped.
+        if let Some(sub_folder) = sub_folder.filter(|sub_folder| !sub_folder.trim().is_empty()) {
+            let relative_path = components.as_path().strip_prefix(sub_folder).ok()?;
+            return Some(target_path.join(relative_path));
         }
         Some(target_path.join(components.collect::<PathBuf>()))
     }
@@ -422,37 +1546,35 @@ impl RegistryRepo {
 
     /// Create a new `RegistryRepo` from a remote archive.
     ///
-    /// The archive can be in `.tar.gz` or `.zip` format.
+    /// The archive can be in `.tar`, `.tar.gz` (or `.tgz`), `.tar.zst`, or `.zip` format.
     /// The sub_folder is used to filter the entries inside the archive to unpack.
-    /// The temporary directory is created in the `.weaver/semconv_registry_cache`.
-    /// The temporary directory is deleted when the `RegistryRepo` goes out of scope.
+    /// The archive is downloaded next to the target directory and deleted once
+    /// unpacked. The failed downloads are retried with an exponential backoff
+    /// and the interrupted transfers are resumed (see the `download` module).
+    /// With the validators of a cached archive, the archive is only downloaded
+    /// and unpacked if it changed. The signature of the archive is verified
+    /// before it is unpacked (see the `signature` module).
     ///
     /// Arguments:
     /// - `id`: The unique identifier for the registry.
     /// - `url`: The URL of the archive.
+    /// - `sha256`: The expected SHA-256 digest of the archive, if any.
     /// - `sub_folder`: The sub-folder to unpack inside the archive.
-    /// - `target_dir`: The temporary target directory where the archive will be unpacked.
+    /// - `signer`: The expected signer of the archive, if any.
+    /// - `validators`: The HTTP validators of the cached archive, if any.
+    /// - `target_dir`: The target directory where the archive will be unpacked.
     /// - `registry_path`: The registry path representation (for debug purposes).
     fn try_from_remote_archive(
         id: &str,
         url: &str,
+        sha256: Option<&String>,
         sub_folder: Option<&String>,
-        target_dir: TempDir,
+        signer: Option<&Signer>,
+        validators: Option<&Validators>,
+        target_dir: &Path,
         registry_path: String,
-    ) -> Result<Self, Error> {
-        let tmp_path = target_dir.path().to_path_buf();
-
-        // Download the archive from the URL
-        let response = ureq::get(url).call().map_err(|e| InvalidRegistryArchive {
-            archive: url.to_owned(),
-            error: e.to_string(),
-        })?;
-        if response.status() != 200 {
-            return Err(InvalidRegistryArchive {
-                archive: url.to_owned(),
-                error: format!("HTTP status code: {}", response.status()),
-            });
-        }
+    ) -> Result<Fetched, Error> {
+        let download_path = target_dir.parent().unwrap_or(target_dir).to_path_buf();
 
         // Parse the URL to get the file name
         let parsed_url = Url::parse(url).map_err(|e| InvalidRegistryArchive {
@@ -461,7 +1583,7 @@ impl RegistryRepo {
         })?;
         let file_name = parsed_url
             .path_segments()
-            .and_then(|segments| segments.last())
+            .and_then(|mut segments| segments.next_back())
             .and_then(|name| if name.is_empty() { None } else { Some(name) })
             .ok_or("Failed to extract file name from URL")
             .map_err(|e| InvalidRegistryArchive {
@@ -470,31 +1592,140 @@ impl RegistryRepo {
             })?;
 
         // Create the full path to the save file
-        let save_path = tmp_path.join(file_name);
+        let save_path = download_path.join(file_name);
 
-        // Open a file in write mode
-        let mut file = File::create(save_path.clone()).map_err(|e| InvalidRegistryArchive {
+        // Download the archive from the URL, the failed attempts are retried
+        // and the interrupted transfers are resumed.
+        let agent = HttpSettings::from_env().agent(url)?;
+        let downloaded = download::download_if_modified(
+            &agent,
+            url,
+            None,
+            validators,
+            &save_path,
+            &RetryPolicy::default(),
+        )
+        .map_err(|error| InvalidRegistryArchive {
             archive: url.to_owned(),
-            error: e.to_string(),
+            error,
         })?;
+        let Downloaded::File(validators) = downloaded else {
+            return Ok(Fetched::NotModified);
+        };
 
-        // Write the response body to the file.
-        // The number of bytes written is ignored as the `try_from_local_archive` function
-        // will handle the archive extraction and return an error if the archive is invalid.
-        _ = io::copy(&mut response.into_reader(), &mut file).map_err(|e| {
-            InvalidRegistryArchive {
-                archive: url.to_owned(),
-                error: e.to_string(),
-            }
+        let actual = download::sha256_file(&save_path).map_err(|e| InvalidRegistryArchive {
+            archive: url.to_owned(),
+            error: e.to_string(),
         })?;
+        if let Some(expected) = sha256 {
+            if actual != *expected {
+                return Err(ArchiveChecksumMismatch {
+                    archive: url.to_owned(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        if let Some(signer) = signer {
+            signer
+                .verify_archive(url, &save_path, &actual)
+                .map_err(|error| RegistrySignatureInvalid {
+                    registry_path: url.to_owned(),
+                    error,
+                })?;
+        }
 
-        Self::try_from_local_archive(
+        let repo = Self::try_from_local_archive(
             id,
             save_path.to_str().unwrap_or_default(),
             sub_folder,
             target_dir,
             registry_path,
-        )
+        )?;
+        // The archive is no longer needed once unpacked.
+        _ = std::fs::remove_file(&save_path);
+        Ok(Fetched::Registry(
+            Box::new(Self {
+                revision: Some(format!("sha256:{}", actual)),
+                ..repo
+            }),
+            validators,
+        ))
+    }
+
+    /// Create a new `RegistryRepo` from an OCI artifact.
+    ///
+    /// The archive layer of the artifact (see [`ArchiveFormat`]) is downloaded
+    /// next to the target directory, verified against its digest, unpacked
+    /// like a remote archive, and deleted (see the `oci` module). When the
+    /// artifact is pulled by tag, the digest of its manifest is added to the
+    /// registry path representation, so the exact artifact used is recorded.
+    /// The signature of the manifest is verified before the archive is
+    /// pulled (see the `signature` module).
+    ///
+    /// Arguments:
+    /// - `id`: The unique identifier for the registry.
+    /// - `registry`: The host (and port) of the OCI registry.
+    /// - `repository`: The repository of the artifact.
+    /// - `tag`: The tag of the artifact, if any (`latest` by default).
+    /// - `digest`: The expected digest of the artifact manifest, if any.
+    /// - `sub_folder`: The sub-folder to unpack inside the archive.
+    /// - `signer`: The expected signer of the artifact, if any.
+    /// - `target_dir`: The target directory where the archive will be unpacked.
+    fn try_from_oci_artifact(
+        id: &str,
+        registry: &str,
+        repository: &str,
+        tag: Option<&String>,
+        digest: Option<&String>,
+        sub_folder: Option<&String>,
+        signer: Option<&Signer>,
+        target_dir: &Path,
+    ) -> Result<Self, Error> {
+        let reference = digest.or(tag).map_or("latest", String::as_str);
+        let oci_error = |message: String| OciError {
+            reference: format!("oci://{}/{}@{}", registry, repository, reference),
+            message,
+        };
+        let mut client = oci::OciClient::new(registry, repository)?;
+        let (manifest, manifest_digest) = client.pull_manifest(reference).map_err(oci_error)?;
+        if let Some(signer) = signer {
+            signer
+                .verify_oci_artifact(&format!("{}/{}@{}", registry, repository, manifest_digest))
+                .map_err(|error| RegistrySignatureInvalid {
+                    registry_path: format!("oci://{}/{}@{}", registry, repository, reference),
+                    error,
+                })?;
+        }
+        let (layer, format) = manifest.registry_layer().map_err(oci_error)?;
+        let save_path = target_dir
+            .parent()
+            .unwrap_or(target_dir)
+            .join(format!("artifact{}", format.extension()));
+        client
+            .pull_blob(&layer.digest, &save_path)
+            .map_err(oci_error)?;
+
+        let registry_path = RegistryPath::OciArtifact {
+            registry: registry.to_owned(),
+            repository: repository.to_owned(),
+            tag: tag.cloned(),
+            digest: Some(manifest_digest.clone()),
+            sub_folder: sub_folder.cloned(),
+        };
+        let repo = Self::try_from_local_archive(
+            id,
+            save_path.to_str().unwrap_or_default(),
+            sub_folder,
+            target_dir,
+            registry_path.to_string(),
+        )?;
+        // The archive is no longer needed once unpacked.
+        _ = std::fs::remove_file(&save_path);
+        Ok(Self {
+            revision: Some(manifest_digest),
+            ..repo
+        })
     }
 
     /// Returns the local path to the semconv registry.
@@ -503,6 +1734,30 @@ impl RegistryRepo {
         self.path.as_path()
     }
 
+    /// Returns the Git reference checked out, if any. When the registry path
+    /// specifies a version range, this is the selected tag.
+    #[must_use]
+    pub fn refspec(&self) -> Option<&str> {
+        self.refspec.as_deref()
+    }
+
+    /// Returns the exact revision of a remote registry: the commit of a Git
+    /// repository, or the digest (`sha256:<hex digest>`) of a remote archive
+    /// or of the manifest of an OCI artifact. The local registries have no
+    /// revision.
+    #[must_use]
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    /// Returns the warnings raised while fetching the registry (e.g. the
+    /// malformed lines of the Git credentials file). A registry loaded from
+    /// the cache, or already fetched through the cache, has no warnings.
+    #[must_use]
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     /// Returns the registry path textual representation.
     #[must_use]
     pub fn registry_path_repr(&self) -> &str {
@@ -510,26 +1765,20 @@ impl RegistryRepo {
     }
 
     /// Creates a temporary directory for the registry repository and returns the path.
-    /// The temporary directory is created in the `.weaver/semconv_registry_cache`.
-    fn create_tmp_repo() -> Result<TempDir, Error> {
-        let home = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
-        let cache_path = home.join(".weaver/semconv_registry_cache");
-
-        create_dir_all(cache_path.as_path()).map_err(|e| Error::CacheDirNotCreated {
+    /// The temporary directory is created in the cache directory, and read
+    /// locked until it is dropped (see the `cache_lock` module).
+    fn create_tmp_repo(cache_path: &Path) -> Result<CacheTempDir, Error> {
+        create_dir_all(cache_path).map_err(|e| CacheDirNotCreated {
             message: e.to_string(),
         })?;
-
-        let tmp_dir = TempDir::new_in(cache_path.as_path(), "repo").map_err(|e| {
-            Error::CacheDirNotCreated {
-                message: e.to_string(),
-            }
-        })?;
-        Ok(tmp_dir)
+        CacheTempDir::create(cache_path, "repo")
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use tempdir::TempDir;
+
     use super::*;
     use weaver_common::test::ServeStaticFiles;
 
@@ -542,13 +1791,71 @@ mod tests {
         count
     }
 
+    #[test]
+    fn test_highest_matching_tag() {
+        let tags = [
+            "v1.25.0",
+            "v1.26.0",
+            "v1.27.0-rc.1",
+            "v1.27.0",
+            "v1.27.1",
+            "1.28.0",
+            "v2.0.0",
+            "latest",
+        ];
+        let select = |range: &str| {
+            let version_req = RegistryRepo::version_req(range)
+                .expect("Should be a version range")
+                .expect("Invalid version range");
+            RegistryRepo::highest_matching_tag(&version_req, tags.iter().copied())
+        };
+        assert_eq!(select("^1.26"), Some("1.28.0"));
+        assert_eq!(select("~1.27.0"), Some("v1.27.1"));
+        assert_eq!(select("=1.26.0"), Some("v1.26.0"));
+        assert_eq!(select(">=1.0"), Some("v2.0.0"));
+        assert_eq!(select("1.27.*"), Some("v1.27.1"));
+        assert_eq!(select("^3"), None);
+
+        // Plain tags and branches are not version ranges.
+        assert!(RegistryRepo::version_req("v1.26.0").is_none());
+        assert!(RegistryRepo::version_req("main").is_none());
+        assert!(RegistryRepo::version_req("^one").is_some_and(|r| r.is_err()));
+    }
+
+    #[test]
+    fn test_list_versions() {
+        let tags = [
+            "latest",
+            "v1.27.0",
+            "1.26.0",
+            "v1.27.0-rc.1",
+            "v1.9.0",
+            "main",
+        ];
+        assert_eq!(
+            RegistryRepo::sorted_version_tags(tags.iter().map(|tag| (*tag).to_owned()).collect()),
+            vec!["v1.9.0", "1.26.0", "v1.27.0-rc.1", "v1.27.0"]
+        );
+
+        for registry_path in [
+            "registry",
+            "registry.zip",
+            "https://example.com/registry.zip",
+        ] {
+            assert!(matches!(
+                RegistryRepo::list_versions(&registry_path.parse().unwrap(), &cache()),
+                Err(InvalidRegistryPath { .. })
+            ));
+        }
+    }
+
     #[test]
     fn test_semconv_registry_local_repo() {
         // A RegistryRepo created from a local folder.
         let registry_path = RegistryPath::LocalFolder {
             path: "../../crates/weaver_codegen_test/semconv_registry".to_owned(),
         };
-        let repo = RegistryRepo::try_new("main", &registry_path).unwrap();
+        let repo = RegistryRepo::try_new("main", &registry_path, &cache()).unwrap();
         let repo_path = repo.path().to_path_buf();
         assert!(repo_path.exists());
         assert!(
@@ -562,7 +1869,7 @@ mod tests {
     }
 
     fn check_archive(registry_path: RegistryPath, file_to_check: Option<&str>) {
-        let repo = RegistryRepo::try_new("main", &registry_path).unwrap();
+        let repo = RegistryRepo::try_new("main", &registry_path, &cache()).unwrap();
         let repo_path = repo.path().to_path_buf();
         // At this point, the repo should be cloned into a temporary directory.
         assert!(repo_path.exists());
@@ -580,6 +1887,40 @@ mod tests {
         assert!(!repo_path.exists());
     }
 
+    /// Checks a remote registry fetched into an empty cache. Unlike the local
+    /// archives, the registry is kept in the cache once the repo is dropped.
+    fn check_cached_registry(registry_path: RegistryPath, file_to_check: Option<&str>) {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let repo =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false))
+                .unwrap();
+        let repo_path = repo.path().to_path_buf();
+        assert!(repo_path.starts_with(cache_dir.path()));
+        assert!(
+            count_yaml_files(&repo_path) > 0,
+            "There should be at least one `.yaml` file in the repo"
+        );
+        if let Some(file_to_check) = file_to_check {
+            assert!(repo_path.join(file_to_check).exists());
+        }
+        drop(repo);
+        assert!(repo_path.exists());
+    }
+
+    fn cache() -> RegistryCache {
+        RegistryCache::try_default().expect("Failed to create the registry cache")
+    }
+
+    fn options(cache_dir: &TempDir, offline: bool) -> CacheOptions {
+        CacheOptions {
+            dir: cache_dir.path().to_path_buf(),
+            ttl: DEFAULT_CACHE_TTL,
+            offline,
+            mirrors: Mirrors::default(),
+            trust: TrustPolicy::default(),
+        }
+    }
+
     #[test]
     fn test_semconv_registry_git_repo() {
         let registry_path = RegistryPath::GitRepo {
@@ -588,7 +1929,7 @@ mod tests {
             sub_folder: Some("model".to_owned()),
             refspec: None,
         };
-        check_archive(registry_path, None);
+        check_cached_registry(registry_path, None);
     }
 
     #[test]
@@ -607,6 +1948,97 @@ mod tests {
         check_archive(registry_path, Some("general.yaml"));
     }
 
+    #[test]
+    fn test_semconv_registry_local_tar_archives() {
+        // The `.tar`, `.tgz`, and `.tar.zst` variants of the `.tar.gz` archive.
+        let dir = TempDir::new("archives").unwrap();
+        let tar_gz = std::fs::read("../../test_data/semantic-conventions-1.26.0.tar.gz").unwrap();
+        let mut tar = vec![];
+        _ = io::copy(
+            &mut flate2::read::GzDecoder::new(tar_gz.as_slice()),
+            &mut tar,
+        )
+        .unwrap();
+        let archives = [
+            ("semconv.tar", tar.clone()),
+            ("semconv.tgz", tar_gz),
+            (
+                "semconv.tar.zst",
+                zstd::encode_all(tar.as_slice(), 0).unwrap(),
+            ),
+        ];
+        for (file_name, content) in archives {
+            let path = dir.path().join(file_name);
+            std::fs::write(&path, content).unwrap();
+            let registry_path = format!("{}[model]", path.display())
+                .parse::<RegistryPath>()
+                .unwrap();
+            assert!(
+                matches!(registry_path, RegistryPath::LocalArchive { .. }),
+                "{}",
+                file_name
+            );
+            check_archive(registry_path, Some("general.yaml"));
+        }
+    }
+
+    #[test]
+    fn test_archive_format() {
+        for (file_name, format) in [
+            ("registry.tar", Some(ArchiveFormat::Tar)),
+            ("registry.tar.gz", Some(ArchiveFormat::TarGz)),
+            ("registry.tgz", Some(ArchiveFormat::TarGz)),
+            ("registry.tar.zst", Some(ArchiveFormat::TarZst)),
+            ("registry.zip", Some(ArchiveFormat::Zip)),
+            ("registry.gz", None),
+            ("registry.zst", None),
+            ("registry", None),
+        ] {
+            assert_eq!(
+                ArchiveFormat::from_file_name(file_name),
+                format,
+                "{}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_semconv_registry_overlay() {
+        let overlay_dir = TempDir::new("overlay").unwrap();
+        std::fs::write(overlay_dir.path().join("general.yaml"), "groups: []\n").unwrap();
+        create_dir_all(overlay_dir.path().join("patches")).unwrap();
+        std::fs::write(overlay_dir.path().join("patches/new.yaml"), "groups: []\n").unwrap();
+
+        let registry_path = format!(
+            "../../test_data/semantic-conventions-1.26.0.tar.gz[model]+overlay={}",
+            overlay_dir.path().display()
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+        let repo = RegistryRepo::try_new("main", &registry_path, &cache()).unwrap();
+        let repo_path = repo.path().to_path_buf();
+        assert_eq!(repo.registry_path_repr(), registry_path.to_string());
+        // The files of the overlay are added or replace those of the registry.
+        assert_eq!(
+            std::fs::read_to_string(repo_path.join("general.yaml")).unwrap(),
+            "groups: []\n"
+        );
+        assert!(repo_path.join("patches/new.yaml").exists());
+        assert!(repo_path.join("logs/events.yaml").exists());
+        drop(repo);
+        assert!(!repo_path.exists());
+
+        let registry_path =
+            "../../test_data/semantic-conventions-1.26.0.tar.gz[model]+overlay=does_not_exist"
+                .parse::<RegistryPath>()
+                .unwrap();
+        assert!(matches!(
+            RegistryRepo::try_new("main", &registry_path, &cache()),
+            Err(InvalidRegistryOverlay { .. })
+        ));
+    }
+
     #[test]
     fn test_semconv_registry_remote_tar_gz_archive() {
         let server = ServeStaticFiles::from("tests/test_data").unwrap();
@@ -616,7 +2048,7 @@ mod tests {
         )
         .parse::<RegistryPath>()
         .unwrap();
-        check_archive(registry_path, Some("general.yaml"));
+        check_cached_registry(registry_path, Some("general.yaml"));
     }
 
     #[test]
@@ -628,6 +2060,635 @@ mod tests {
         )
         .parse::<RegistryPath>()
         .unwrap();
-        check_archive(registry_path, Some("general.yaml"));
+        check_cached_registry(registry_path, Some("general.yaml"));
+    }
+
+    #[test]
+    fn test_semconv_registry_remote_archive_not_modified() {
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("semconv_registry_v1.26.0.zip")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+        let cache_dir = TempDir::new("cache").unwrap();
+        // The cached registries are always stale.
+        let options = CacheOptions {
+            ttl: Duration::ZERO,
+            ..options(&cache_dir, false)
+        };
+        let repo = RegistryRepo::try_new_with_options("main", &registry_path, &options).unwrap();
+        let entry_dir = cache_dir
+            .path()
+            .join(RegistryRepo::cache_key(&registry_path));
+        let entry = CacheEntry::load(&entry_dir).unwrap();
+        assert!(!entry.validators.is_empty());
+        CacheEntry {
+            fetched_at: 0,
+            ..entry
+        }
+        .store(&entry_dir)
+        .unwrap();
+        std::fs::write(repo.path().join("marker.txt"), "").unwrap();
+
+        // The archive hasn't changed, the cached registry is kept and becomes
+        // fresh again.
+        let new_repo =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options).unwrap();
+        assert_eq!(new_repo.path(), repo.path());
+        assert_eq!(new_repo.revision(), repo.revision());
+        assert!(new_repo.path().join("marker.txt").exists());
+        assert!(CacheEntry::load(&entry_dir).unwrap().fetched_at > 0);
+    }
+
+    #[test]
+    fn test_registry_fetched_once() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        // The cached registries are always fetched again, except through the
+        // same cache.
+        let cache = RegistryCache {
+            options: CacheOptions {
+                ttl: Duration::ZERO,
+                ..options(&cache_dir, false)
+            },
+            lockfile: None,
+            fetched: FetchedRegistries::default(),
+        };
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("semconv_registry_v1.26.0.zip")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+        let repo = RegistryRepo::try_new_shared("main", &registry_path, &cache).unwrap();
+        drop(server);
+        let baseline_repo =
+            RegistryRepo::try_new_shared("baseline", &registry_path, &cache).unwrap();
+        assert_eq!(baseline_repo.id, "baseline");
+        assert_eq!(baseline_repo.path(), repo.path());
+        assert_eq!(baseline_repo.revision(), repo.revision());
+        drop(repo);
+        drop(baseline_repo);
+        // The registry is still reused once all its repos are dropped.
+        assert!(RegistryRepo::try_new_shared("main", &registry_path, &cache).is_ok());
+
+        // The temporary directory of a local archive is shared by its repos.
+        let archive_dir = TempDir::new("archive").unwrap();
+        let archive = archive_dir.path().join("registry.zip");
+        _ = std::fs::copy("tests/test_data/semconv_registry_v1.26.0.zip", &archive).unwrap();
+        let registry_path = format!("{}[model]", archive.display())
+            .parse::<RegistryPath>()
+            .unwrap();
+        let repo = RegistryRepo::try_new_shared("main", &registry_path, &cache).unwrap();
+        let baseline_repo =
+            RegistryRepo::try_new_shared("baseline", &registry_path, &cache).unwrap();
+        let repo_path = repo.path().to_path_buf();
+        assert_eq!(baseline_repo.path(), repo_path);
+        // A clone of the cache shares its fetched registries, unlike another
+        // cache.
+        let cloned_cache_repo =
+            RegistryRepo::try_new_shared("main", &registry_path, &cache.clone()).unwrap();
+        assert_eq!(cloned_cache_repo.path(), repo_path);
+        drop(cloned_cache_repo);
+        let other_cache = RegistryCache {
+            fetched: FetchedRegistries::default(),
+            ..cache.clone()
+        };
+        let other_cache_repo =
+            RegistryRepo::try_new_shared("main", &registry_path, &other_cache).unwrap();
+        assert_ne!(other_cache_repo.path(), repo_path);
+        drop(other_cache_repo);
+        drop(repo);
+        assert!(repo_path.exists());
+        drop(baseline_repo);
+        assert!(!repo_path.exists());
+        // The archive is unpacked again once all its repos are dropped.
+        let repo = RegistryRepo::try_new_shared("main", &registry_path, &cache).unwrap();
+        assert_ne!(repo.path(), repo_path);
+        assert!(repo.path().join("general.yaml").exists());
+    }
+
+    #[test]
+    fn test_semconv_registry_mirror() {
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let mirrors_dir = TempDir::new("mirrors").unwrap();
+        let mirrors_file = mirrors_dir.path().join("mirrors.yaml");
+        std::fs::write(
+            &mirrors_file,
+            format!(
+                "mirrors:\n- prefix: {}\n  urls: [{}, {}]\n",
+                server.relative_path_to_url("upstream/"),
+                server.relative_path_to_url("unavailable/"),
+                server.relative_path_to_url("")
+            ),
+        )
+        .unwrap();
+        let cache_dir = TempDir::new("cache").unwrap();
+        let options = CacheOptions {
+            mirrors: Mirrors::load(&mirrors_file).unwrap(),
+            ..options(&cache_dir, false)
+        };
+        // The upstream URL and the first mirror don't serve the archive.
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("upstream/semconv_registry_v1.26.0.zip")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+        let repo = RegistryRepo::try_new_with_options("main", &registry_path, &options).unwrap();
+        assert!(repo.path().join("general.yaml").exists());
+        assert_eq!(repo.registry_path_repr(), registry_path.to_string());
+
+        // All the URLs fail.
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("upstream/does_not_exist.zip")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+        assert!(matches!(
+            RegistryRepo::try_new_with_options("main", &registry_path, &options),
+            Err(InvalidRegistryArchive { .. })
+        ));
+
+        // The mirrors are tried before the upstream URL, which isn't fetched
+        // once a mirror succeeds.
+        std::fs::write(
+            &mirrors_file,
+            "mirrors:\n\
+            - prefix: https://upstream.example.com/\n  \
+              mirrors_first: true\n  \
+              urls: [https://mirror-1.example.com/, https://mirror-2.example.com/]\n",
+        )
+        .unwrap();
+        let target_dir = cache_dir.path().join("fetch").join(CACHE_REGISTRY_DIR);
+        create_dir_all(&target_dir).unwrap();
+        let mut fetched_urls = vec![];
+        let fetched = RegistryRepo::fetch_with_mirrors(
+            "https://upstream.example.com/registry.zip",
+            &Mirrors::load(&mirrors_file).unwrap(),
+            &target_dir,
+            |url| {
+                fetched_urls.push(url.to_owned());
+                if url.starts_with("https://mirror-1.") {
+                    return Err(InvalidRegistryArchive {
+                        archive: url.to_owned(),
+                        error: "unavailable".to_owned(),
+                    });
+                }
+                Ok(Fetched::Registry(
+                    Box::new(RegistryRepo {
+                        registry_path: format!("{}[model]", url),
+                        ..RegistryRepo::default()
+                    }),
+                    Validators::default(),
+                ))
+            },
+        );
+        assert_eq!(
+            fetched_urls,
+            vec![
+                "https://mirror-1.example.com/registry.zip",
+                "https://mirror-2.example.com/registry.zip"
+            ]
+        );
+        let Ok(Fetched::Registry(repo, _)) = fetched else {
+            panic!("Expected a registry fetched from a mirror");
+        };
+        assert_eq!(
+            repo.registry_path,
+            "https://upstream.example.com/registry.zip[model]"
+        );
+    }
+
+    #[test]
+    fn test_semconv_registry_signer() {
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let trust_dir = TempDir::new("trust").unwrap();
+        let trust_file = trust_dir.path().join("trust.yaml");
+        std::fs::write(
+            &trust_file,
+            format!(
+                "registries:\n\
+                - prefix: {}\n  gpg: {{ keyring: keyring.gpg }}\n\
+                - prefix: https://github.com/open-telemetry/\n  cosign: {{ key: cosign.pub }}\n",
+                server.relative_path_to_url("")
+            ),
+        )
+        .unwrap();
+        let cache_dir = TempDir::new("cache").unwrap();
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("semconv_registry_v1.26.0.zip")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+
+        // An unsigned registry is cached without signer.
+        let repo =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false))
+                .unwrap();
+        let entry_dir = cache_dir
+            .path()
+            .join(RegistryRepo::cache_key(&registry_path));
+        assert_eq!(CacheEntry::load(&entry_dir).unwrap().signer, None);
+        drop(repo);
+
+        // The unverified cached registry is not reused, and the server
+        // doesn't serve the checksum file of the archive.
+        let options = CacheOptions {
+            trust: TrustPolicy::load(&trust_file).unwrap(),
+            ..options(&cache_dir, false)
+        };
+        assert!(matches!(
+            RegistryRepo::try_new_with_options("main", &registry_path, &options),
+            Err(RegistrySignatureInvalid { .. })
+        ));
+
+        // The Git repositories can't be verified.
+        let registry_path = "https://github.com/open-telemetry/semantic-conventions.git[model]"
+            .parse::<RegistryPath>()
+            .unwrap();
+        assert!(matches!(
+            RegistryRepo::try_new_with_options("main", &registry_path, &options),
+            Err(RegistrySignatureInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_semconv_registry_remote_archive_checksum() {
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let url = server.relative_path_to_url("semconv_registry_v1.26.0.zip");
+        let digest =
+            download::sha256_file(Path::new("tests/test_data/semconv_registry_v1.26.0.zip"))
+                .unwrap();
+        let registry_path = format!("{}@sha256:{}[model]", url, digest)
+            .parse::<RegistryPath>()
+            .unwrap();
+        check_cached_registry(registry_path, Some("general.yaml"));
+
+        let cache_dir = TempDir::new("cache").unwrap();
+        let registry_path = format!("{}@sha256:{}[model]", url, "0".repeat(64))
+            .parse::<RegistryPath>()
+            .unwrap();
+        let result =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false));
+        assert!(
+            matches!(result, Err(ArchiveChecksumMismatch { ref actual, .. }) if *actual == digest)
+        );
+        // Nothing is cached.
+        assert!(cached_registry::cached_registries(cache_dir.path())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_registry_cache_lockfile() {
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let registry_path = |file: &str| {
+            format!("{}[model]", server.relative_path_to_url(file))
+                .parse::<RegistryPath>()
+                .unwrap()
+        };
+        let cache_dir = TempDir::new("cache").unwrap();
+        let lockfile_path = cache_dir.path().join(lockfile::DEFAULT_LOCKFILE);
+        let registry_cache = cache().with_dir(cache_dir.path().join("registries"));
+
+        // No lockfile by default.
+        let zip_path = registry_path("semconv_registry_v1.26.0.zip");
+        _ = RegistryRepo::try_new("main", &zip_path, &registry_cache).unwrap();
+        assert!(!lockfile_path.exists());
+
+        let updating_cache = registry_cache
+            .clone()
+            .with_lockfile(lockfile_path.clone(), LockMode::Update);
+        _ = RegistryRepo::try_new("main", &zip_path, &updating_cache).unwrap();
+        let lockfile = lockfile::Lockfile::load(&lockfile_path).unwrap();
+        assert_eq!(lockfile.registries.len(), 1);
+        assert_eq!(lockfile.registries[0].registry_path, zip_path.to_string());
+
+        let locked_cache = registry_cache.with_lockfile(lockfile_path, LockMode::Locked);
+        _ = RegistryRepo::try_new("main", &zip_path, &locked_cache).unwrap();
+        assert!(matches!(
+            RegistryRepo::try_new(
+                "main",
+                &registry_path("semconv_registry_v1.26.0.tar.gz"),
+                &locked_cache
+            ),
+            Err(Error::RegistryNotLocked { .. })
+        ));
+    }
+
+    #[test]
+    fn test_semconv_registry_oci_artifact() {
+        // A registry serving the manifest and the layer of an artifact.
+        let registry_dir = TempDir::new("oci").unwrap();
+        let repository_dir = registry_dir.path().join("v2/acme/semconv");
+        create_dir_all(repository_dir.join("manifests")).unwrap();
+        create_dir_all(repository_dir.join("blobs")).unwrap();
+        let archive = Path::new("tests/test_data/semconv_registry_v1.26.0.tar.gz");
+        let layer_digest = format!("sha256:{}", download::sha256_file(archive).unwrap());
+        _ = std::fs::copy(archive, repository_dir.join("blobs").join(&layer_digest)).unwrap();
+        let manifest = format!(
+            r#"{{"schemaVersion": 2, "mediaType": "application/vnd.oci.image.manifest.v1+json", "layers": [{{"mediaType": "application/vnd.oci.image.layer.v1.tar+gzip", "digest": "{}", "size": 1}}]}}"#,
+            layer_digest
+        );
+        let manifest_digest = format!("sha256:{:x}", Sha256::digest(&manifest));
+        std::fs::write(repository_dir.join("manifests/1.26.0"), &manifest).unwrap();
+        std::fs::write(
+            repository_dir.join("manifests").join(&manifest_digest),
+            &manifest,
+        )
+        .unwrap();
+        let server = ServeStaticFiles::from(registry_dir.path()).unwrap();
+        let registry = format!("127.0.0.1:{}", server.port());
+
+        // Pulled by tag, the manifest digest is recorded.
+        let cache_dir = TempDir::new("cache").unwrap();
+        let registry_path = format!("oci://{}/acme/semconv:1.26.0[model]", registry)
+            .parse::<RegistryPath>()
+            .unwrap();
+        let repo =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false))
+                .unwrap();
+        assert!(repo.path().join("general.yaml").exists());
+        assert_eq!(repo.revision(), Some(manifest_digest.as_str()));
+        assert_eq!(
+            repo.registry_path_repr(),
+            format!(
+                "oci://{}/acme/semconv:1.26.0@{}[model]",
+                registry, manifest_digest
+            )
+        );
+
+        // Pulled by digest.
+        let registry_path = format!("oci://{}/acme/semconv@{}[model]", registry, manifest_digest)
+            .parse::<RegistryPath>()
+            .unwrap();
+        check_cached_registry(registry_path, Some("general.yaml"));
+
+        // A digest that doesn't match the manifest.
+        _ = std::fs::copy(
+            repository_dir.join("manifests/1.26.0"),
+            repository_dir
+                .join("manifests")
+                .join(format!("sha256:{}", "0".repeat(64))),
+        )
+        .unwrap();
+        let registry_path = format!("oci://{}/acme/semconv@sha256:{}", registry, "0".repeat(64))
+            .parse::<RegistryPath>()
+            .unwrap();
+        let result =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false));
+        assert!(matches!(result, Err(OciError { .. })));
+
+        // A missing tag.
+        let registry_path = format!("oci://{}/acme/semconv:2.0.0", registry)
+            .parse::<RegistryPath>()
+            .unwrap();
+        let result =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false));
+        assert!(matches!(result, Err(OciError { .. })));
+    }
+
+    #[test]
+    fn test_registry_cache_offline() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("semconv_registry_v1.26.0.zip")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+
+        // Nothing is cached yet.
+        let result =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, true));
+        assert!(matches!(result, Err(RegistryNotCached { .. })));
+
+        let repo =
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false))
+                .unwrap();
+        let repo_path = repo.path().to_path_buf();
+        let entry_dir = cache_dir
+            .path()
+            .join(RegistryRepo::cache_key(&registry_path));
+        assert!(repo_path.starts_with(&entry_dir));
+        // The downloaded archive is deleted once unpacked.
+        assert!(!entry_dir.join("semconv_registry_v1.26.0.zip").exists());
+        drop(repo);
+
+        // The cached registry is used without accessing the server.
+        drop(server);
+        let digest =
+            download::sha256_file(Path::new("tests/test_data/semconv_registry_v1.26.0.zip"))
+                .unwrap();
+        for offline in [true, false] {
+            let repo = RegistryRepo::try_new_with_options(
+                "main",
+                &registry_path,
+                &options(&cache_dir, offline),
+            )
+            .unwrap();
+            assert_eq!(repo.path(), repo_path);
+            assert_eq!(repo.registry_path_repr(), registry_path.to_string());
+            assert_eq!(repo.revision(), Some(format!("sha256:{}", digest).as_str()));
+            assert!(repo.path().join("general.yaml").exists());
+        }
+    }
+
+    #[test]
+    fn test_registry_cache_concurrency() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("semconv_registry_v1.26.0.tar.gz")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+
+        // An incomplete entry, e.g. left by an interrupted removal.
+        let entry_dir = cache_dir
+            .path()
+            .join(RegistryRepo::cache_key(&registry_path));
+        create_dir_all(entry_dir.join(CACHE_REGISTRY_DIR)).unwrap();
+        assert!(CacheEntry::load(&entry_dir).is_none());
+
+        // Concurrent fetches of the same registry share the same entry.
+        let paths: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let repo = RegistryRepo::try_new_with_options(
+                            "main",
+                            &registry_path,
+                            &options(&cache_dir, false),
+                        )
+                        .unwrap();
+                        assert!(repo.path().join("general.yaml").exists());
+                        repo.path().to_path_buf()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(paths.iter().all(|path| path.starts_with(&entry_dir)));
+        assert!(CacheEntry::load(&entry_dir).is_some());
+        // Neither temporary directories nor locks are left behind.
+        let registries = cached_registry::cached_registries(cache_dir.path()).unwrap();
+        assert_eq!(registries.len(), 1);
+        // The cache only contains the entry and the objects of its files.
+        assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_registry_cache_readers() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        let registry_path = format!(
+            "{}[model]",
+            server.relative_path_to_url("semconv_registry_v1.26.0.tar.gz")
+        )
+        .parse::<RegistryPath>()
+        .unwrap();
+        let entry_dir = cache_dir
+            .path()
+            .join(RegistryRepo::cache_key(&registry_path));
+        // The cached registries are always downloaded again.
+        let options = CacheOptions {
+            ttl: Duration::ZERO,
+            ..options(&cache_dir, false)
+        };
+        let unconditional_refresh = || {
+            CacheEntry {
+                validators: Validators::default(),
+                ..CacheEntry::load(&entry_dir).unwrap()
+            }
+            .store(&entry_dir)
+            .unwrap();
+        };
+
+        let reader = RegistryRepo::try_new_with_options("main", &registry_path, &options).unwrap();
+        assert!(ReadLock::has_readers(&entry_dir, DEFAULT_STALE_LOCK_AGE));
+        std::fs::write(reader.path().join("marker.txt"), "").unwrap();
+
+        // The stale entry is read, so it is not replaced: the registry
+        // downloaded again is used from a temporary directory.
+        unconditional_refresh();
+        let repo = RegistryRepo::try_new_with_options("main", &registry_path, &options).unwrap();
+        let tmp_path = repo.path().to_path_buf();
+        assert!(!tmp_path.starts_with(&entry_dir));
+        assert!(tmp_path.join("general.yaml").exists());
+        assert!(reader.path().join("marker.txt").exists());
+        drop(repo);
+        assert!(!tmp_path.exists());
+
+        // Once the reader is dropped, the entry is replaced.
+        drop(reader);
+        assert!(!ReadLock::has_readers(&entry_dir, DEFAULT_STALE_LOCK_AGE));
+        unconditional_refresh();
+        let repo = RegistryRepo::try_new_with_options("main", &registry_path, &options).unwrap();
+        assert!(repo.path().starts_with(&entry_dir));
+        assert!(!repo.path().join("marker.txt").exists());
+        drop(repo);
+        // The previous entry was renamed, then deleted.
+        let registries = cached_registry::cached_registries(cache_dir.path()).unwrap();
+        assert_eq!(registries.len(), 1);
+        assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_registry_cache_deduplication() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let server = ServeStaticFiles::from("tests/test_data").unwrap();
+        // Two versions of the registry, cached as two entries.
+        let repos: Vec<_> = [
+            "semconv_registry_v1.26.0.tar.gz",
+            "semconv_registry_v1.26.0.zip",
+        ]
+        .iter()
+        .map(|archive| {
+            let registry_path = format!("{}[model]", server.relative_path_to_url(archive))
+                .parse::<RegistryPath>()
+                .unwrap();
+            RegistryRepo::try_new_with_options("main", &registry_path, &options(&cache_dir, false))
+                .unwrap()
+        })
+        .collect();
+        assert_ne!(repos[0].path(), repos[1].path());
+
+        // Their identical files are stored once.
+        let entries: Vec<_> = repos
+            .iter()
+            .map(|repo| CacheEntry::load(repo.path().parent().unwrap()).unwrap())
+            .collect();
+        assert!(!entries[0].objects.is_empty());
+        assert_eq!(
+            entries[0].objects["general.yaml"],
+            entries[1].objects["general.yaml"]
+        );
+        assert_ne!(
+            entries[0].objects["registry/http.yaml"],
+            entries[1].objects["registry/http.yaml"]
+        );
+        let objects_count = walkdir::WalkDir::new(cache_dir.path().join(store::OBJECTS_DIR))
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .count();
+        let digests: std::collections::HashSet<_> = entries
+            .iter()
+            .flat_map(|entry| entry.objects.values())
+            .collect();
+        assert_eq!(objects_count, digests.len());
+        assert!(objects_count < entries[0].objects.len() + entries[1].objects.len());
+        let general = std::fs::read_to_string(repos[1].path().join("general.yaml")).unwrap();
+        assert_eq!(
+            general,
+            std::fs::read_to_string(store::object_path(
+                cache_dir.path(),
+                &entries[0].objects["general.yaml"]
+            ))
+            .unwrap()
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let inode = |path: &Path| std::fs::metadata(path).unwrap().ino();
+            assert_eq!(
+                inode(&repos[0].path().join("general.yaml")),
+                inode(&repos[1].path().join("general.yaml"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_cache_entry_freshness() {
+        let ttl = Duration::from_secs(3600);
+        let mut entry = CacheEntry {
+            registry_path: "https://example.com/registry.zip".to_owned(),
+            refspec: None,
+            revision: None,
+            path: PathBuf::from(CACHE_REGISTRY_DIR),
+            fetched_at: now_secs(),
+            validators: Validators::default(),
+            objects: Manifest::new(),
+            signer: None,
+        };
+        assert!(entry.is_fresh(ttl));
+        assert!(!entry.is_fresh(Duration::ZERO));
+        entry.fetched_at -= 2 * ttl.as_secs();
+        assert!(!entry.is_fresh(ttl));
+
+        // The cache keys depend on the URL, the refspec, and the sub-folder.
+        let key = |path: &str| RegistryRepo::cache_key(&path.parse::<RegistryPath>().unwrap());
+        let url = "https://github.com/open-telemetry/semantic-conventions.git";
+        assert_eq!(key(url), key(url));
+        assert_ne!(key(url), key(&format!("{}@v1.26.0", url)));
+        assert_ne!(key(url), key(&format!("{}[model]", url)));
     }
 }
@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The lockfile of the remote registries (`weaver.lock`).
+//!
+//! The lockfile records the exact revision of each remote registry loaded by
+//! a command: the commit of a Git repository, or the digest of a remote
+//! archive or of the manifest of an OCI artifact. The registries are keyed by
+//! their registry path as specified (e.g. with a version range), so a
+//! lockfile can be shared by several commands using different registries.
+//!
+//! In update mode, the new or changed revisions are written into the
+//! lockfile. In locked mode, the lockfile is never written, and a registry
+//! missing from the lockfile or resolved to another revision is an error. The
+//! local folders and archives are not locked, and an overlay of a remote
+//! registry is locked with the revision of the remote registry.
+//!
+//! The lockfile is enabled on the registry cache used to create the
+//! registries (see [`crate::RegistryCache::with_lockfile`]).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::registry_path::RegistryPath;
+use crate::Error::{InvalidLockfile, LockedRegistryChanged, RegistryNotLocked};
+use crate::{Error, RegistryRepo};
+
+/// The default lockfile, relative to the current directory.
+pub const DEFAULT_LOCKFILE: &str = "weaver.lock";
+/// The version of the lockfile format.
+const LOCKFILE_VERSION: u32 = 1;
+/// The comment written at the top of the lockfile.
+const LOCKFILE_HEADER: &str =
+    "# This file is generated by Weaver to record the exact revision of the\n\
+    # remote registries. It is not intended to be edited manually.\n";
+
+/// How the lockfile is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// The revisions of the remote registries are recorded in the lockfile.
+    Update,
+    /// The revisions of the remote registries must match the lockfile, which
+    /// is never written.
+    Locked,
+}
+
+/// The content of a lockfile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lockfile {
+    /// The version of the lockfile format.
+    pub version: u32,
+    /// The locked registries, sorted by registry path.
+    #[serde(default)]
+    pub registries: Vec<LockedRegistry>,
+}
+
+/// A registry recorded in the lockfile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockedRegistry {
+    /// The registry path as specified.
+    pub registry_path: String,
+    /// The registry path as resolved (e.g. with the tag selected by a version
+    /// range, or the digest of an OCI artifact pulled by tag).
+    pub resolved: String,
+    /// The exact revision of the registry (see [`RegistryRepo::revision`]).
+    pub revision: String,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            registries: vec![],
+        }
+    }
+}
+
+impl Lockfile {
+    /// Reads a lockfile. A missing lockfile is an empty lockfile.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let invalid = |error: String| InvalidLockfile {
+            path: path.display().to_string(),
+            error,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let lockfile: Self = serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        if lockfile.version > LOCKFILE_VERSION {
+            return Err(invalid(format!(
+                "Unsupported version {}, the latest supported version is {}",
+                lockfile.version, LOCKFILE_VERSION
+            )));
+        }
+        Ok(lockfile)
+    }
+
+    /// Writes the lockfile.
+    pub fn store(&self, path: &Path) -> Result<(), Error> {
+        let invalid = |error: String| InvalidLockfile {
+            path: path.display().to_string(),
+            error,
+        };
+        let content = serde_yaml::to_string(self).map_err(|e| invalid(e.to_string()))?;
+        std::fs::write(path, format!("{}{}", LOCKFILE_HEADER, content))
+            .map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Returns the locked registry of a registry path, if any.
+    #[must_use]
+    pub fn get(&self, registry_path: &str) -> Option<&LockedRegistry> {
+        self.registries
+            .iter()
+            .find(|locked| locked.registry_path == registry_path)
+    }
+
+    /// Adds or replaces a locked registry. Returns true if the lockfile
+    /// changed.
+    fn insert(&mut self, locked: LockedRegistry) -> bool {
+        match self
+            .registries
+            .binary_search_by(|registry| registry.registry_path.cmp(&locked.registry_path))
+        {
+            Ok(index) if self.registries[index] == locked => false,
+            Ok(index) => {
+                self.registries[index] = locked;
+                true
+            }
+            Err(index) => {
+                self.registries.insert(index, locked);
+                true
+            }
+        }
+    }
+}
+
+/// Returns true if the registry is remote, or is an overlay of a remote
+/// registry.
+fn is_remote(registry_path: &RegistryPath) -> bool {
+    match registry_path {
+        RegistryPath::GitRepo { .. }
+        | RegistryPath::RemoteArchive { .. }
+        | RegistryPath::OciArtifact { .. } => true,
+        RegistryPath::Overlay { base, .. } => is_remote(base),
+        RegistryPath::LocalFolder { .. } | RegistryPath::LocalArchive { .. } => false,
+    }
+}
+
+/// Checks or records the revision of a remote registry in a lockfile.
+pub(crate) fn lock(
+    path: &Path,
+    mode: LockMode,
+    registry_path: &RegistryPath,
+    repo: &RegistryRepo,
+) -> Result<(), Error> {
+    let Some(revision) = repo.revision().filter(|_| is_remote(registry_path)) else {
+        return Ok(());
+    };
+    let registry_path = registry_path.to_string();
+    let mut lockfile = Lockfile::load(path)?;
+    match mode {
+        LockMode::Locked => match lockfile.get(&registry_path) {
+            Some(locked) if locked.revision == revision => Ok(()),
+            Some(locked) => Err(LockedRegistryChanged {
+                registry_path,
+                lockfile: path.display().to_string(),
+                locked: locked.revision.clone(),
+                resolved: revision.to_owned(),
+            }),
+            None => Err(RegistryNotLocked {
+                registry_path,
+                lockfile: path.display().to_string(),
+            }),
+        },
+        LockMode::Update => {
+            let changed = lockfile.insert(LockedRegistry {
+                registry_path,
+                resolved: repo.registry_path_repr().to_owned(),
+                revision: revision.to_owned(),
+            });
+            if changed {
+                lockfile.store(path)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn repo(registry_path: &str, revision: Option<&str>) -> RegistryRepo {
+        RegistryRepo {
+            id: "main".to_owned(),
+            registry_path: registry_path.to_owned(),
+            revision: revision.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lockfile() {
+        let dir = TempDir::new("lockfile").unwrap();
+        let path = dir.path().join(DEFAULT_LOCKFILE);
+        let git_path: RegistryPath = "https://example.com/semconv.git@^1.26[model]"
+            .parse()
+            .unwrap();
+        let git_repo = repo(
+            "https://example.com/semconv.git@v1.27.0[model]",
+            Some("3b0a4e5c"),
+        );
+        let archive_path: RegistryPath = "https://example.com/registry.zip".parse().unwrap();
+        let archive_repo = repo("https://example.com/registry.zip", Some("sha256:abcd"));
+
+        // Nothing is locked yet.
+        assert!(matches!(
+            lock(&path, LockMode::Locked, &git_path, &git_repo),
+            Err(RegistryNotLocked { .. })
+        ));
+        assert!(!path.exists());
+
+        lock(&path, LockMode::Update, &git_path, &git_repo).unwrap();
+        lock(&path, LockMode::Update, &archive_path, &archive_repo).unwrap();
+        let lockfile = Lockfile::load(&path).unwrap();
+        assert_eq!(lockfile.registries.len(), 2);
+        // The registries are sorted by registry path.
+        assert_eq!(lockfile.registries[1].registry_path, git_path.to_string());
+        assert_eq!(
+            lockfile.registries[1].resolved,
+            "https://example.com/semconv.git@v1.27.0[model]"
+        );
+        assert_eq!(lockfile.registries[1].revision, "3b0a4e5c");
+
+        lock(&path, LockMode::Locked, &git_path, &git_repo).unwrap();
+        lock(&path, LockMode::Locked, &archive_path, &archive_repo).unwrap();
+
+        // The registry drifted.
+        let new_git_repo = repo(
+            "https://example.com/semconv.git@v1.28.0[model]",
+            Some("9f2c61d7"),
+        );
+        assert!(matches!(
+            lock(&path, LockMode::Locked, &git_path, &new_git_repo),
+            Err(LockedRegistryChanged { ref locked, ref resolved, .. }) if locked == "3b0a4e5c" && resolved == "9f2c61d7"
+        ));
+        lock(&path, LockMode::Update, &git_path, &new_git_repo).unwrap();
+        lock(&path, LockMode::Locked, &git_path, &new_git_repo).unwrap();
+        assert_eq!(Lockfile::load(&path).unwrap().registries.len(), 2);
+
+        // The local registries are not locked.
+        let local_path: RegistryPath = "registry.zip".parse().unwrap();
+        lock(
+            &path,
+            LockMode::Locked,
+            &local_path,
+            &repo("registry.zip", None),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_invalid_lockfile() {
+        let dir = TempDir::new("lockfile").unwrap();
+        let path = dir.path().join(DEFAULT_LOCKFILE);
+        std::fs::write(&path, "version: 2\nregistries: []\n").unwrap();
+        assert!(matches!(Lockfile::load(&path), Err(InvalidLockfile { .. })));
+        std::fs::write(&path, "registries: 3\n").unwrap();
+        assert!(matches!(Lockfile::load(&path), Err(InvalidLockfile { .. })));
+    }
+}
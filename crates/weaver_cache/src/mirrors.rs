@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The mirrors of the remote registries.
+//!
+//! The mirrors are declared in a YAML file, whose path is read from the
+//! `WEAVER_REGISTRY_MIRRORS` environment variable (`~/.weaver/mirrors.yaml`
+//! by default, if it exists):
+//!
+//! ```yaml
+//! mirrors:
+//!   - prefix: https://github.com/open-telemetry/
+//!     urls:
+//!       - https://git.internal.example.com/otel/
+//!   - prefix: oci://ghcr.io/acme/
+//!     mirrors_first: true
+//!     urls:
+//!       - oci://registry.internal.example.com/acme/
+//! ```
+//!
+//! A remote registry whose URL (or `oci://` reference) starts with a prefix
+//! is fetched from its own URL first, then from each mirror URL (the prefix
+//! replaced by the mirror URL) in order, until a fetch succeeds. Each fetch
+//! is retried with backoff before moving to the next URL, so a prefix whose
+//! upstream host is known to be unreachable (e.g. in an air-gapped
+//! environment) should set `mirrors_first`: its mirrors are then tried
+//! before its own URL. The registries are cached and locked under their own
+//! registry path, whatever the mirror used.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::Error;
+use crate::Error::InvalidMirrors;
+
+/// The environment variable containing the path of the mirrors file.
+const MIRRORS_ENV_VAR: &str = "WEAVER_REGISTRY_MIRRORS";
+/// The default mirrors file (relative to the home directory).
+const DEFAULT_MIRRORS_FILE: &str = ".weaver/mirrors.yaml";
+
+/// The mirrors of the remote registries.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Mirrors {
+    #[serde(default)]
+    mirrors: Vec<Mirror>,
+}
+
+/// The mirrors of the registries whose URL starts with a prefix.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Mirror {
+    /// The prefix of the URLs of the mirrored registries.
+    prefix: String,
+    /// The URLs replacing the prefix, in order of preference.
+    urls: Vec<String>,
+    /// Whether the mirrors are tried before the URL of the registry.
+    #[serde(default)]
+    mirrors_first: bool,
+}
+
+impl Mirrors {
+    /// Reads the mirrors file from the environment.
+    pub(crate) fn from_env() -> Result<Self, Error> {
+        Self::from_vars(
+            |name| std::env::var(name).ok().filter(|value| !value.is_empty()),
+            dirs::home_dir(),
+        )
+    }
+
+    /// Reads the mirrors file from the given variables. The default mirrors
+    /// file is only read if it exists.
+    fn from_vars(
+        var: impl Fn(&str) -> Option<String>,
+        home_dir: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        match var(MIRRORS_ENV_VAR) {
+            Some(path) => Self::load(Path::new(&path)),
+            None => match home_dir.map(|home_dir| home_dir.join(DEFAULT_MIRRORS_FILE)) {
+                Some(path) if path.is_file() => Self::load(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// Reads a mirrors file.
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        let invalid = |error: String| InvalidMirrors {
+            path: path.display().to_string(),
+            error,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Returns the URLs to fetch a registry from, in order: its own URL, then
+    /// the URLs of its mirrors, or its own URL last if one of the matching
+    /// prefixes sets `mirrors_first`.
+    pub(crate) fn urls(&self, url: &str) -> Vec<String> {
+        let mut urls = vec![];
+        let mut mirrors_first = false;
+        for mirror in &self.mirrors {
+            if let Some(rest) = url.strip_prefix(&mirror.prefix) {
+                mirrors_first |= mirror.mirrors_first;
+                urls.extend(
+                    mirror
+                        .urls
+                        .iter()
+                        .map(|mirror_url| format!("{}{}", mirror_url, rest)),
+                );
+            }
+        }
+        if mirrors_first {
+            urls.push(url.to_owned());
+        } else {
+            urls.insert(0, url.to_owned());
+        }
+        urls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_mirror_urls() {
+        let dir = TempDir::new("mirrors").unwrap();
+        let path = dir.path().join("mirrors.yaml");
+        std::fs::write(
+            &path,
+            "mirrors:\n\
+            - prefix: https://github.com/open-telemetry/\n  \
+              urls: [https://mirror-1.example.com/otel/, https://mirror-2.example.com/]\n\
+            - prefix: oci://ghcr.io/\n  \
+              urls: [oci://localhost:5000/]\n\
+            - prefix: https://example.com/air-gapped/\n  \
+              mirrors_first: true\n  \
+              urls: [https://mirror-1.example.com/]\n",
+        )
+        .unwrap();
+        let vars: HashMap<_, _> = [(MIRRORS_ENV_VAR, path.display().to_string())]
+            .into_iter()
+            .collect();
+        let mirrors = Mirrors::from_vars(|name| vars.get(name).cloned(), None).unwrap();
+
+        assert_eq!(
+            mirrors.urls("https://github.com/open-telemetry/semantic-conventions.git"),
+            vec![
+                "https://github.com/open-telemetry/semantic-conventions.git",
+                "https://mirror-1.example.com/otel/semantic-conventions.git",
+                "https://mirror-2.example.com/semantic-conventions.git",
+            ]
+        );
+        assert_eq!(
+            mirrors.urls("oci://ghcr.io/acme/semconv"),
+            vec![
+                "oci://ghcr.io/acme/semconv",
+                "oci://localhost:5000/acme/semconv"
+            ]
+        );
+        assert_eq!(
+            mirrors.urls("https://example.com/registry.zip"),
+            vec!["https://example.com/registry.zip"]
+        );
+        assert_eq!(
+            mirrors.urls("https://example.com/air-gapped/registry.zip"),
+            vec![
+                "https://mirror-1.example.com/registry.zip",
+                "https://example.com/air-gapped/registry.zip"
+            ]
+        );
+
+        // Without mirrors file.
+        let mirrors = Mirrors::from_vars(|_| None, Some(dir.path().join("home"))).unwrap();
+        assert_eq!(mirrors.urls("oci://ghcr.io/a"), vec!["oci://ghcr.io/a"]);
+
+        std::fs::write(&path, "mirrors:\n- prefix: https://github.com/\n").unwrap();
+        assert!(matches!(
+            Mirrors::from_vars(|name| vars.get(name).cloned(), None),
+            Err(InvalidMirrors { .. })
+        ));
+    }
+}
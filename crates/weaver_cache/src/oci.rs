@@ -0,0 +1,583 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pull of the registries published as OCI artifacts.
+//!
+//! The artifacts are pulled with the HTTP API of the OCI distribution
+//! specification. The manifest of an artifact must contain a layer with an
+//! archive of the registry (`.tar`, `.tar.gz`, `.tgz`, `.tar.zst`, or `.zip`),
+//! identified by its file name annotation (`org.opencontainers.image.title`,
+//! set by `oras push`) or by its media type (e.g.
+//! `application/vnd.oci.image.layer.v1.tar+gzip` or `application/zip`). The
+//! digests of the manifest and of the layer are verified.
+//!
+//! The registries requiring authentication are accessed with the token flow
+//! of the distribution specification (including the anonymous tokens of the
+//! public repositories) or with basic authentication. The credentials are
+//! read from the environment:
+//! - `WEAVER_OCI_TOKEN`: the password or the access token,
+//! - `WEAVER_OCI_USERNAME`: the username (`oauth2` by default).
+//!
+//! The registries on the loopback interface (e.g. `localhost:5000`) are
+//! accessed over HTTP, the others over HTTPS.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::download::RetryPolicy;
+use crate::http_agent::HttpSettings;
+use crate::{download, ArchiveFormat, Error};
+
+/// The environment variable containing the username of the OCI registries.
+const USERNAME_ENV_VAR: &str = "WEAVER_OCI_USERNAME";
+/// The environment variable containing the password or the token of the OCI
+/// registries.
+const TOKEN_ENV_VAR: &str = "WEAVER_OCI_TOKEN";
+/// The username used with the token if none is specified.
+const DEFAULT_USERNAME: &str = "oauth2";
+/// The annotation containing the file name of a layer.
+const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+/// The media type of an OCI image index.
+const INDEX_MEDIA_TYPE: &str = "application/vnd.oci.image.index.v1+json";
+/// The media types of the manifests accepted from the registries.
+const MANIFEST_MEDIA_TYPES: &str = "application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json, \
+    application/vnd.oci.image.index.v1+json";
+/// The maximum size of a manifest (4 MiB, as recommended by the distribution
+/// specification).
+const MAX_MANIFEST_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Regex matching the parameters of an authentication challenge
+/// (e.g. `realm="https://ghcr.io/token"`).
+static CHALLENGE_PARAM_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"([A-Za-z]+)="([^"]*)""#).expect("Invalid regex"));
+
+/// Regex matching the query of the next page of a tag list in a `Link`
+/// header (e.g. `</v2/acme/semconv/tags/list?n=100&last=1.2.0>; rel="next"`).
+static LINK_NEXT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<[^>]*/tags/list(?P<query>\?[^>]*)>\s*;\s*rel="?next"?"#).expect("Invalid regex")
+});
+
+/// The manifest of an artifact (or an image index, which is not supported).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Manifest {
+    media_type: Option<String>,
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+}
+
+/// The descriptor of a layer.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Descriptor {
+    media_type: String,
+    /// The digest of the layer (`<algorithm>:<hex digest>`).
+    pub(crate) digest: String,
+    #[serde(default)]
+    annotations: HashMap<String, String>,
+}
+
+impl Manifest {
+    /// Returns the first layer containing a registry archive, and its format.
+    pub(crate) fn registry_layer(&self) -> Result<(&Descriptor, ArchiveFormat), String> {
+        if !self.manifests.is_empty() || self.media_type.as_deref() == Some(INDEX_MEDIA_TYPE) {
+            return Err(
+                "The reference points to an image index, not to a registry artifact".to_owned(),
+            );
+        }
+        self.layers
+            .iter()
+            .find_map(|layer| layer.archive_format().map(|format| (layer, format)))
+            .ok_or_else(|| "No layer of the artifact is a registry archive".to_owned())
+    }
+}
+
+impl Descriptor {
+    /// Returns the format of the layer if it is a registry archive. The file
+    /// name takes precedence over the media type, as the files pushed by
+    /// `oras` without a media type are `application/vnd.oci.image.layer.v1.tar`.
+    fn archive_format(&self) -> Option<ArchiveFormat> {
+        if let Some(format) = self
+            .annotations
+            .get(TITLE_ANNOTATION)
+            .and_then(|title| ArchiveFormat::from_file_name(title))
+        {
+            return Some(format);
+        }
+        match self.media_type.as_str() {
+            "application/vnd.oci.image.layer.v1.tar" => Some(ArchiveFormat::Tar),
+            "application/vnd.oci.image.layer.v1.tar+gzip"
+            | "application/vnd.docker.image.rootfs.diff.tar.gzip"
+            | "application/gzip"
+            | "application/x-gzip" => Some(ArchiveFormat::TarGz),
+            "application/vnd.oci.image.layer.v1.tar+zstd" | "application/zstd" => {
+                Some(ArchiveFormat::TarZst)
+            }
+            "application/zip" => Some(ArchiveFormat::Zip),
+            _ => None,
+        }
+    }
+}
+
+/// The response of a token service.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// The list of the tags of a repository.
+#[derive(Debug, Deserialize)]
+struct TagList {
+    /// The tags, `null` for a repository without tags.
+    tags: Option<Vec<String>>,
+}
+
+/// A client pulling the artifacts of an OCI repository.
+pub(crate) struct OciClient {
+    agent: ureq::Agent,
+    /// The repository name, e.g. `acme/semconv`.
+    repository: String,
+    /// The URL of the repository API, e.g. `https://ghcr.io/v2/acme/semconv`.
+    repository_url: String,
+    /// The username and the password (or token), if any.
+    credentials: Option<(String, String)>,
+    /// The `Authorization` header obtained from the first authentication
+    /// challenge of the registry, if any.
+    authorization: Option<String>,
+}
+
+impl OciClient {
+    /// Creates a client of a repository, with the credentials read from the
+    /// environment.
+    pub(crate) fn new(registry: &str, repository: &str) -> Result<Self, Error> {
+        let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let credentials = var(TOKEN_ENV_VAR).map(|token| {
+            (
+                var(USERNAME_ENV_VAR).unwrap_or_else(|| DEFAULT_USERNAME.to_owned()),
+                token,
+            )
+        });
+        Self::with_credentials(registry, repository, credentials)
+    }
+
+    /// Creates a client of a repository with the given credentials.
+    fn with_credentials(
+        registry: &str,
+        repository: &str,
+        credentials: Option<(String, String)>,
+    ) -> Result<Self, Error> {
+        let scheme = if is_loopback(registry) {
+            "http"
+        } else {
+            "https"
+        };
+        let repository_url = format!("{}://{}/v2/{}", scheme, registry, repository);
+        Ok(Self {
+            agent: HttpSettings::from_env().agent(&repository_url)?,
+            repository: repository.to_owned(),
+            repository_url,
+            credentials,
+            authorization: None,
+        })
+    }
+
+    /// Pulls the manifest of a tag or a digest, and returns it with its
+    /// digest. When pulled by digest, the digest of the manifest is verified.
+    pub(crate) fn pull_manifest(&mut self, reference: &str) -> Result<(Manifest, String), String> {
+        let response = self.get(&format!("manifests/{}", reference), MANIFEST_MEDIA_TYPES)?;
+        let mut content = vec![];
+        _ = response
+            .into_reader()
+            .take(MAX_MANIFEST_SIZE)
+            .read_to_end(&mut content)
+            .map_err(|e| e.to_string())?;
+        let digest = format!("sha256:{:x}", Sha256::digest(&content));
+        if reference.starts_with("sha256:") && reference != digest {
+            return Err(format!(
+                "The digest of the manifest is `{}`, expected `{}`",
+                digest, reference
+            ));
+        }
+        let manifest =
+            serde_json::from_slice(&content).map_err(|e| format!("Invalid manifest: {}", e))?;
+        Ok((manifest, digest))
+    }
+
+    /// Downloads a blob into the given path and verifies its digest.
+    pub(crate) fn pull_blob(&self, digest: &str, path: &Path) -> Result<(), String> {
+        let expected = digest
+            .strip_prefix("sha256:")
+            .ok_or_else(|| format!("Unsupported digest `{}`", digest))?;
+        download::download(
+            &self.agent,
+            &format!("{}/blobs/{}", self.repository_url, digest),
+            self.authorization.as_deref(),
+            path,
+            &RetryPolicy::default(),
+        )?;
+        let actual = download::sha256_file(path).map_err(|e| e.to_string())?;
+        if actual != expected {
+            return Err(format!(
+                "The digest of the layer is `sha256:{}`, expected `{}`",
+                actual, digest
+            ));
+        }
+        Ok(())
+    }
+
+    /// Lists the tags of the repository, following the pages of the list
+    /// (`Link` header).
+    pub(crate) fn list_tags(&mut self) -> Result<Vec<String>, String> {
+        let mut tags = vec![];
+        let mut path = Some("tags/list".to_owned());
+        while let Some(page) = path {
+            let response = self.get(&page, "application/json")?;
+            path = response
+                .header("Link")
+                .and_then(|link| LINK_NEXT_REGEX.captures(link))
+                .and_then(|captures| captures.name("query"))
+                .map(|query| format!("tags/list{}", query.as_str()));
+            let tag_list: TagList =
+                serde_json::from_reader(response.into_reader().take(MAX_MANIFEST_SIZE))
+                    .map_err(|e| format!("Invalid tag list: {}", e))?;
+            tags.extend(tag_list.tags.unwrap_or_default());
+        }
+        Ok(tags)
+    }
+
+    /// Sends a GET request to the repository API. The first authentication
+    /// challenge of the registry is answered, and the request is sent again.
+    fn get(&mut self, path: &str, accept: &str) -> Result<ureq::Response, String> {
+        let url = format!("{}/{}", self.repository_url, path);
+        loop {
+            let mut request = self.agent.get(&url).set("Accept", accept);
+            if let Some(authorization) = &self.authorization {
+                request = request.set("Authorization", authorization);
+            }
+            match request.call() {
+                Ok(response) => return Ok(response),
+                Err(ureq::Error::Status(401, response)) if self.authorization.is_none() => {
+                    let challenge = response
+                        .header("WWW-Authenticate")
+                        .unwrap_or_default()
+                        .to_owned();
+                    self.authorization = Some(self.authorize(&challenge)?);
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+
+    /// Returns the `Authorization` header answering an authentication
+    /// challenge (`WWW-Authenticate` header) of the registry.
+    fn authorize(&self, challenge: &str) -> Result<String, String> {
+        let (scheme, params) = parse_challenge(challenge);
+        if scheme.eq_ignore_ascii_case("basic") {
+            return self.basic_authorization().ok_or_else(|| {
+                format!(
+                    "The registry requires credentials, set the `{}` and `{}` environment variables",
+                    USERNAME_ENV_VAR, TOKEN_ENV_VAR
+                )
+            });
+        }
+        if !scheme.eq_ignore_ascii_case("bearer") {
+            return Err(format!(
+                "Unsupported authentication challenge `{}`",
+                challenge
+            ));
+        }
+
+        // Token flow: the token service returns a token for the scope of the
+        // challenge (pull access to the repository by default).
+        let realm = params
+            .get("realm")
+            .ok_or_else(|| format!("No realm in the authentication challenge `{}`", challenge))?;
+        let mut token_url =
+            Url::parse(realm).map_err(|e| format!("Invalid token realm `{}`: {}", realm, e))?;
+        let scope = params
+            .get("scope")
+            .cloned()
+            .unwrap_or_else(|| format!("repository:{}:pull", self.repository));
+        {
+            let mut query = token_url.query_pairs_mut();
+            if let Some(service) = params.get("service") {
+                _ = query.append_pair("service", service);
+            }
+            _ = query.append_pair("scope", &scope);
+        }
+        let agent = HttpSettings::from_env()
+            .agent(token_url.as_str())
+            .map_err(|e| e.to_string())?;
+        let mut request = agent.request_url("GET", &token_url);
+        if let Some(authorization) = self.basic_authorization() {
+            request = request.set("Authorization", &authorization);
+        }
+        let response = request.call().map_err(|e| match e {
+            ureq::Error::Status(401 | 403, _) => format!(
+                "Access denied by the token service `{}`, check the `{}` and `{}` environment variables",
+                realm, USERNAME_ENV_VAR, TOKEN_ENV_VAR
+            ),
+            e => e.to_string(),
+        })?;
+        let token: TokenResponse = serde_json::from_reader(response.into_reader())
+            .map_err(|e| format!("Invalid response of the token service: {}", e))?;
+        token
+            .token
+            .or(token.access_token)
+            .map(|token| format!("Bearer {}", token))
+            .ok_or_else(|| "No token in the response of the token service".to_owned())
+    }
+
+    /// Returns the basic `Authorization` header of the credentials, if any.
+    fn basic_authorization(&self) -> Option<String> {
+        self.credentials.as_ref().map(|(username, password)| {
+            format!(
+                "Basic {}",
+                STANDARD.encode(format!("{}:{}", username, password))
+            )
+        })
+    }
+}
+
+/// Parses an authentication challenge into its scheme and its parameters
+/// (with lowercase names).
+fn parse_challenge(challenge: &str) -> (&str, HashMap<String, String>) {
+    let challenge = challenge.trim();
+    let (scheme, params) = challenge.split_once(' ').unwrap_or((challenge, ""));
+    let params = CHALLENGE_PARAM_REGEX
+        .captures_iter(params)
+        .map(|captures| (captures[1].to_lowercase(), captures[2].to_owned()))
+        .collect();
+    (scheme, params)
+}
+
+/// Returns true if the registry (`host[:port]`) is on the loopback interface.
+fn is_loopback(registry: &str) -> bool {
+    let host = registry
+        .rsplit_once(':')
+        .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+        .map_or(registry, |(host, _)| host);
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use super::*;
+
+    /// Serves one scripted response per connection, and returns the request
+    /// line and the `Authorization` header of the requests.
+    fn serve(
+        listener: TcpListener,
+        responses: Vec<String>,
+    ) -> JoinHandle<Vec<(String, Option<String>)>> {
+        std::thread::spawn(move || {
+            let mut requests = vec![];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                _ = reader.read_line(&mut request_line).unwrap();
+                let mut authorization = None;
+                loop {
+                    let mut line = String::new();
+                    _ = reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("authorization") {
+                            authorization = Some(value.trim().to_owned());
+                        }
+                    }
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push((request_line.trim().to_owned(), authorization));
+            }
+            requests
+        })
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        let (scheme, params) = parse_challenge(
+            r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:acme/semconv:pull""#,
+        );
+        assert_eq!(scheme, "Bearer");
+        assert_eq!(params["realm"], "https://ghcr.io/token");
+        assert_eq!(params["service"], "ghcr.io");
+        assert_eq!(params["scope"], "repository:acme/semconv:pull");
+
+        let (scheme, params) = parse_challenge(r#"Basic Realm="registry""#);
+        assert_eq!(scheme, "Basic");
+        assert_eq!(params["realm"], "registry");
+    }
+
+    #[test]
+    fn test_is_loopback() {
+        for registry in [
+            "localhost",
+            "localhost:5000",
+            "127.0.0.1:5000",
+            "[::1]:5000",
+            "[::1]",
+        ] {
+            assert!(is_loopback(registry), "{}", registry);
+        }
+        for registry in [
+            "ghcr.io",
+            "registry.example.com:5000",
+            "localhost.example.com",
+        ] {
+            assert!(!is_loopback(registry), "{}", registry);
+        }
+    }
+
+    #[test]
+    fn test_registry_layer() {
+        let manifest = |json: &str| serde_json::from_str::<Manifest>(json).unwrap();
+        let layer = |media_type: &str, title: &str| {
+            format!(
+                r#"{{"mediaType": "{}", "digest": "sha256:{}", "size": 1, "annotations": {{"{}": "{}"}}}}"#,
+                media_type,
+                "0".repeat(64),
+                TITLE_ANNOTATION,
+                title
+            )
+        };
+
+        let oras_manifest = manifest(&format!(
+            r#"{{"schemaVersion": 2, "layers": [{}, {}]}}"#,
+            layer("application/vnd.oci.image.config.v1+json", "config.json"),
+            layer("application/vnd.oci.image.layer.v1.tar", "registry.zip"),
+        ));
+        assert_eq!(
+            oras_manifest.registry_layer().unwrap().1,
+            ArchiveFormat::Zip
+        );
+        let manifest_tar_gz = manifest(&format!(
+            r#"{{"schemaVersion": 2, "layers": [{}]}}"#,
+            layer("application/vnd.oci.image.layer.v1.tar+gzip", "")
+        ));
+        assert_eq!(
+            manifest_tar_gz.registry_layer().unwrap().1,
+            ArchiveFormat::TarGz
+        );
+        let manifest_tar_zst = manifest(&format!(
+            r#"{{"schemaVersion": 2, "layers": [{}]}}"#,
+            layer("application/vnd.oci.image.layer.v1.tar+zstd", "")
+        ));
+        assert_eq!(
+            manifest_tar_zst.registry_layer().unwrap().1,
+            ArchiveFormat::TarZst
+        );
+
+        let no_archive = manifest(&format!(
+            r#"{{"schemaVersion": 2, "layers": [{}]}}"#,
+            layer("application/json", "registry.json")
+        ));
+        assert!(no_archive.registry_layer().is_err());
+        let index = manifest(&format!(
+            r#"{{"schemaVersion": 2, "mediaType": "{}", "manifests": []}}"#,
+            INDEX_MEDIA_TYPE
+        ));
+        assert!(index.registry_layer().is_err());
+    }
+
+    #[test]
+    fn test_bearer_token_flow() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let registry = listener.local_addr().unwrap().to_string();
+        let manifest = r#"{"schemaVersion": 2, "layers": []}"#;
+        let responses = vec![
+            format!(
+                "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Bearer realm=\"http://{}/token\",service=\"test\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                registry
+            ),
+            "HTTP/1.1 200 OK\r\nContent-Length: 18\r\nConnection: close\r\n\r\n{\"token\":\"s3cr3t\"}".to_owned(),
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                manifest.len(),
+                manifest
+            ),
+        ];
+        let server = serve(listener, responses);
+
+        let mut client = OciClient::with_credentials(
+            &registry,
+            "acme/semconv",
+            Some(("user".to_owned(), "password".to_owned())),
+        )
+        .unwrap();
+        let (_, digest) = client.pull_manifest("1.2.0").unwrap();
+        assert_eq!(digest, format!("sha256:{:x}", Sha256::digest(manifest)));
+        assert_eq!(client.authorization.as_deref(), Some("Bearer s3cr3t"));
+
+        let requests = server.join().unwrap();
+        assert_eq!(
+            requests[0],
+            (
+                "GET /v2/acme/semconv/manifests/1.2.0 HTTP/1.1".to_owned(),
+                None
+            )
+        );
+        assert_eq!(
+            requests[1],
+            (
+                "GET /token?service=test&scope=repository%3Aacme%2Fsemconv%3Apull HTTP/1.1"
+                    .to_owned(),
+                Some("Basic dXNlcjpwYXNzd29yZA==".to_owned())
+            )
+        );
+        assert_eq!(requests[2].1.as_deref(), Some("Bearer s3cr3t"));
+    }
+
+    #[test]
+    fn test_list_tags() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let registry = listener.local_addr().unwrap().to_string();
+        let response = |headers: &str, body: &str| {
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+                body.len(),
+                headers,
+                body
+            )
+        };
+        let responses = vec![
+            response(
+                "Link: </v2/acme/semconv/tags/list?n=2&last=1.1.0>; rel=\"next\"\r\n",
+                r#"{"name": "acme/semconv", "tags": ["1.0.0", "1.1.0"]}"#,
+            ),
+            response("", r#"{"name": "acme/semconv", "tags": ["latest"]}"#),
+        ];
+        let server = serve(listener, responses);
+
+        let mut client = OciClient::with_credentials(&registry, "acme/semconv", None).unwrap();
+        assert_eq!(
+            client.list_tags().unwrap(),
+            vec!["1.0.0", "1.1.0", "latest"]
+        );
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].0, "GET /v2/acme/semconv/tags/list HTTP/1.1");
+        assert_eq!(
+            requests[1].0,
+            "GET /v2/acme/semconv/tags/list?n=2&last=1.1.0 HTTP/1.1"
+        );
+    }
+}
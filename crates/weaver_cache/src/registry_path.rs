@@ -8,20 +8,62 @@ use std::str::FromStr;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::Error;
+use crate::{ArchiveFormat, Error};
 
 /// Regex to parse a registry path supporting the following formats:
 /// - source
 /// - source@tag
 /// - source\[sub_folder\]
 /// - source@tag\[sub_folder\]
+/// - source\[sub_folder\]@tag
+///
+/// For a Git repository, the tag can be a semver range (e.g. `^1.30`). For a
+/// remote archive, the tag is the expected checksum of the archive
+/// (`sha256:<hex digest>`).
 static REGISTRY_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^(?P<source>.+?)(?:@(?P<refspec>.+?))?(?:\[(?P<sub_folder>.+?)])?$")
-        .expect("Invalid regex")
+    Regex::new(concat!(
+        r"^(?P<source>.+?)(?:",
+        r"(?:@(?P<refspec>.+?))?(?:\[(?P<sub_folder>.+?)])?",
+        r"|\[(?P<sub_folder_first>.+?)]@(?P<refspec_last>.+?)",
+        r")$"
+    ))
+    .expect("Invalid regex")
 });
 
+/// Regex to parse an OCI artifact reference supporting the following formats:
+/// - oci://registry/repository
+/// - oci://registry/repository:tag
+/// - oci://registry/repository@sha256:digest
+/// - oci://registry/repository:tag@sha256:digest
+///
+/// optionally followed by a sub-folder (`[sub_folder]`).
+static OCI_REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"^oci://(?P<registry>[^/@\[\]]+)",
+        r"/(?P<repository>[a-z0-9]+(?:[._/-]+[a-z0-9]+)*)",
+        r"(?::(?P<tag>[A-Za-z0-9_][A-Za-z0-9._-]{0,127}))?",
+        r"(?:@(?P<digest>sha256:[0-9a-fA-F]{64}))?",
+        r"(?:\[(?P<sub_folder>.+?)])?$"
+    ))
+    .expect("Invalid regex")
+});
+
+/// The separator of a registry path and the overlay directory layered over it
+/// (e.g. `https://github.com/org/repo.git@v1.26.0[model]+overlay=./patches`).
+const OVERLAY_SEPARATOR: &str = "+overlay=";
+
+/// Regex matching the user info of a URL (e.g. `git@` in
+/// `ssh://git@github.com/org/repo.git`).
+static URL_USER_INFO_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[a-z][a-z0-9+.-]*://[^@/]+@").expect("Invalid regex"));
+
+/// Regex matching a scp-like Git URL (e.g. `git@github.com:org/repo.git`).
+static SCP_LIKE_URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<user_info>[^@/:\[\]]+@)[^@/:\[\]]+:").expect("Invalid regex"));
+
 /// Path to a semantic convention registry.
-/// The path can be a local directory or a Git URL.
+/// The path can be a local directory, a Git URL (HTTPS, SSH, or scp-like
+/// `user@host:path`), or an OCI artifact reference (`oci://...`).
 #[derive(Debug, Clone)]
 pub enum RegistryPath {
     /// Local folder path pointing to a semantic convention registry.
@@ -40,6 +82,8 @@ pub enum RegistryPath {
     RemoteArchive {
         /// URL of the remote archive
         url: String,
+        /// Expected SHA-256 digest of the archive (lowercase hexadecimal)
+        sha256: Option<String>,
         /// Sub-folder within the archive containing the semantic convention registry
         sub_folder: Option<String>,
     },
@@ -47,11 +91,34 @@ pub enum RegistryPath {
     GitRepo {
         /// URL of the Git repository
         url: String,
-        /// Specific Tag, Branch, or Commit of the Git repository (NOT YET SUPPORTED)
+        /// Specific Tag or Branch of the Git repository, or a semver range
+        /// (e.g. `^1.26`, `~1.27.0`, `>=1.26`, `1.*`) selecting the highest
+        /// matching version tag.
         refspec: Option<String>,
         /// Sub-folder within the repository containing the semantic convention registry
         sub_folder: Option<String>,
     },
+    /// OCI artifact containing an archive of a semantic convention registry.
+    OciArtifact {
+        /// Host (and port) of the OCI registry, e.g. `ghcr.io`
+        registry: String,
+        /// Repository of the artifact within the OCI registry, e.g. `acme/semconv`
+        repository: String,
+        /// Tag of the artifact (`latest` if neither a tag nor a digest is specified)
+        tag: Option<String>,
+        /// Digest of the artifact manifest (`sha256:<lowercase hex digest>`)
+        digest: Option<String>,
+        /// Sub-folder within the archive containing the semantic convention registry
+        sub_folder: Option<String>,
+    },
+    /// A registry with the files of a local overlay directory layered over
+    /// it, adding or replacing the files of the registry.
+    Overlay {
+        /// The registry the overlay is applied to
+        base: Box<RegistryPath>,
+        /// Path to the local overlay directory
+        overlay: String,
+    },
 }
 
 /// Implement the `FromStr` trait for `RegistryPath`, so that it can be used as
@@ -61,8 +128,50 @@ impl FromStr for RegistryPath {
 
     /// Parse a string into a `RegistryPath`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((base, overlay)) = s.rsplit_once(OVERLAY_SEPARATOR) {
+            if overlay.is_empty() {
+                return Err(Error::InvalidRegistryPath {
+                    path: s.to_owned(),
+                    error: "No overlay directory found".to_owned(),
+                });
+            }
+            return Ok(Self::Overlay {
+                base: Box::new(base.parse()?),
+                overlay: overlay.to_owned(),
+            });
+        }
+
+        if s.starts_with("oci://") {
+            let captures = OCI_REFERENCE_REGEX
+                .captures(s)
+                .ok_or(Error::InvalidRegistryPath {
+                    path: s.to_owned(),
+                    error: "Invalid OCI artifact reference, expected `oci://<registry>/<repository>[:<tag>][@sha256:<digest>]`".to_owned(),
+                })?;
+            let capture = |name: &str| captures.name(name).map(|m| m.as_str().to_owned());
+            return Ok(Self::OciArtifact {
+                registry: capture("registry").unwrap_or_default(),
+                repository: capture("repository").unwrap_or_default(),
+                tag: capture("tag"),
+                digest: capture("digest").map(|digest| digest.to_ascii_lowercase()),
+                sub_folder: capture("sub_folder"),
+            });
+        }
+
+        // The `@` of the user info of a URL doesn't introduce a refspec.
+        let user_info_len = URL_USER_INFO_REGEX
+            .find(s)
+            .map(|m| m.end())
+            .or_else(|| {
+                SCP_LIKE_URL_REGEX
+                    .captures(s)
+                    .and_then(|captures| captures.name("user_info"))
+                    .map(|m| m.end())
+            })
+            .unwrap_or(0);
+        let (user_info, path) = s.split_at(user_info_len);
         let captures = REGISTRY_REGEX
-            .captures(s)
+            .captures(path)
             .ok_or(Error::InvalidRegistryPath {
                 path: s.to_owned(),
                 error: "Invalid registry path".to_owned(),
@@ -74,13 +183,38 @@ impl FromStr for RegistryPath {
                 error: "Invalid registry path. No local path or URL found".to_owned(),
             })?
             .as_str();
-        let refspec = captures.name("refspec").map(|m| m.as_str().to_owned());
-        let sub_folder = captures.name("sub_folder").map(|m| m.as_str().to_owned());
+        let source = &format!("{}{}", user_info, source);
+        let refspec = captures
+            .name("refspec")
+            .or_else(|| captures.name("refspec_last"))
+            .map(|m| m.as_str().to_owned());
+        let sub_folder = captures
+            .name("sub_folder")
+            .or_else(|| captures.name("sub_folder_first"))
+            .map(|m| m.as_str().to_owned());
 
         if source.starts_with("http://") || source.starts_with("https://") {
-            if source.ends_with(".zip") || source.ends_with(".tar.gz") {
+            if ArchiveFormat::from_file_name(source).is_some() {
+                let sha256 = refspec
+                    .map(|refspec| {
+                        refspec
+                            .strip_prefix("sha256:")
+                            .filter(|digest| {
+                                digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())
+                            })
+                            .map(str::to_ascii_lowercase)
+                            .ok_or_else(|| Error::InvalidRegistryPath {
+                                path: s.to_owned(),
+                                error: format!(
+                                    "Invalid checksum `{}`, expected `sha256:<hex digest>`",
+                                    refspec
+                                ),
+                            })
+                    })
+                    .transpose()?;
                 Ok(Self::RemoteArchive {
                     url: source.to_owned(),
+                    sha256,
                     sub_folder,
                 })
             } else {
@@ -90,7 +224,13 @@ impl FromStr for RegistryPath {
                     sub_folder,
                 })
             }
-        } else if source.ends_with(".zip") || source.ends_with(".tar.gz") {
+        } else if source.starts_with("ssh://") || SCP_LIKE_URL_REGEX.is_match(source) {
+            Ok(Self::GitRepo {
+                url: source.to_owned(),
+                refspec,
+                sub_folder,
+            })
+        } else if ArchiveFormat::from_file_name(source).is_some() {
             Ok(Self::LocalArchive {
                 path: source.to_owned(),
                 sub_folder,
@@ -117,12 +257,19 @@ impl Display for RegistryPath {
                     write!(f, "{}", path)
                 }
             }
-            RegistryPath::RemoteArchive { url, sub_folder } => {
+            RegistryPath::RemoteArchive {
+                url,
+                sha256,
+                sub_folder,
+            } => {
+                write!(f, "{}", url)?;
+                if let Some(sha256) = sha256 {
+                    write!(f, "@sha256:{}", sha256)?;
+                }
                 if let Some(sub_folder) = sub_folder {
-                    write!(f, "{}[{}]", url, sub_folder)
-                } else {
-                    write!(f, "{}", url)
+                    write!(f, "[{}]", sub_folder)?;
                 }
+                Ok(())
             }
             RegistryPath::GitRepo {
                 url,
@@ -134,6 +281,28 @@ impl Display for RegistryPath {
                 (None, Some(folder)) => write!(f, "{}[{}]", url, folder),
                 (None, None) => write!(f, "{}", url),
             },
+            RegistryPath::OciArtifact {
+                registry,
+                repository,
+                tag,
+                digest,
+                sub_folder,
+            } => {
+                write!(f, "oci://{}/{}", registry, repository)?;
+                if let Some(tag) = tag {
+                    write!(f, ":{}", tag)?;
+                }
+                if let Some(digest) = digest {
+                    write!(f, "@{}", digest)?;
+                }
+                if let Some(sub_folder) = sub_folder {
+                    write!(f, "[{}]", sub_folder)?;
+                }
+                Ok(())
+            }
+            RegistryPath::Overlay { base, overlay } => {
+                write!(f, "{}{}{}", base, OVERLAY_SEPARATOR, overlay)
+            }
         }
     }
 }
@@ -157,7 +326,10 @@ mod tests {
         // Local archive (zip)
         let registry_path_str = "http://example.com/registry.zip";
         let registry_path: RegistryPath = registry_path_str.parse().unwrap();
-        if let RegistryPath::RemoteArchive { url, sub_folder } = &registry_path {
+        if let RegistryPath::RemoteArchive {
+            url, sub_folder, ..
+        } = &registry_path
+        {
             assert_eq!(url, registry_path_str);
             assert_eq!(*sub_folder, None);
         } else {
@@ -168,7 +340,10 @@ mod tests {
         // Local archive with sub-folder (zip)
         let registry_path_str = "http://example.com/registry.zip[model]";
         let registry_path: RegistryPath = registry_path_str.parse().unwrap();
-        if let RegistryPath::RemoteArchive { url, sub_folder } = &registry_path {
+        if let RegistryPath::RemoteArchive {
+            url, sub_folder, ..
+        } = &registry_path
+        {
             assert_eq!(url, "http://example.com/registry.zip");
             assert_eq!(*sub_folder, Some("model".to_owned()));
         } else {
@@ -179,7 +354,10 @@ mod tests {
         // Local archive (tar.gz)
         let registry_path_str = "http://example.com/registry.tar.gz";
         let registry_path: RegistryPath = registry_path_str.parse().unwrap();
-        if let RegistryPath::RemoteArchive { url, sub_folder } = &registry_path {
+        if let RegistryPath::RemoteArchive {
+            url, sub_folder, ..
+        } = &registry_path
+        {
             assert_eq!(url, registry_path_str);
             assert_eq!(*sub_folder, None);
         } else {
@@ -190,7 +368,10 @@ mod tests {
         // Local archive with sub-folder (tar.gz)
         let registry_path_str = "http://example.com/registry.tar.gz[model]";
         let registry_path: RegistryPath = registry_path_str.parse().unwrap();
-        if let RegistryPath::RemoteArchive { url, sub_folder } = &registry_path {
+        if let RegistryPath::RemoteArchive {
+            url, sub_folder, ..
+        } = &registry_path
+        {
             assert_eq!(url, "http://example.com/registry.tar.gz");
             assert_eq!(*sub_folder, Some("model".to_owned()));
         } else {
@@ -198,6 +379,30 @@ mod tests {
         }
         assert_eq!(registry_path.to_string(), registry_path_str);
 
+        // Remote archive with a checksum and a sub-folder
+        let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let registry_path_str = format!("http://example.com/registry.zip@sha256:{}[model]", digest);
+        let registry_path: RegistryPath = registry_path_str.parse().unwrap();
+        if let RegistryPath::RemoteArchive {
+            url,
+            sha256,
+            sub_folder,
+        } = &registry_path
+        {
+            assert_eq!(url, "http://example.com/registry.zip");
+            assert_eq!(sha256.as_deref(), Some(digest));
+            assert_eq!(*sub_folder, Some("model".to_owned()));
+        } else {
+            panic!("Expected RemoteArchive, got something else");
+        }
+        assert_eq!(registry_path.to_string(), registry_path_str);
+        assert!("http://example.com/registry.zip@v1.0.0"
+            .parse::<RegistryPath>()
+            .is_err());
+        assert!("http://example.com/registry.zip@sha256:abc"
+            .parse::<RegistryPath>()
+            .is_err());
+
         // Git repository
         let registry_path_str = "http://example.com/registry.git";
         let registry_path: RegistryPath = registry_path_str.parse().unwrap();
@@ -265,5 +470,156 @@ mod tests {
             panic!("Expected GitRepo, got something else");
         }
         assert_eq!(registry_path.to_string(), registry_path_str);
+
+        // Git repository with the sub-folder before the version range
+        let registry_path: RegistryPath =
+            "https://github.com/open-telemetry/semantic-conventions.git[model]@^1.30"
+                .parse()
+                .unwrap();
+        if let RegistryPath::GitRepo {
+            url,
+            refspec,
+            sub_folder,
+        } = &registry_path
+        {
+            assert_eq!(
+                url,
+                "https://github.com/open-telemetry/semantic-conventions.git"
+            );
+            assert_eq!(refspec.as_deref(), Some("^1.30"));
+            assert_eq!(sub_folder.as_deref(), Some("model"));
+        } else {
+            panic!("Expected GitRepo, got something else");
+        }
+        assert_eq!(
+            registry_path.to_string(),
+            "https://github.com/open-telemetry/semantic-conventions.git@^1.30[model]"
+        );
+
+        // Git repositories with a user info
+        for (registry_path_str, expected_url, expected_refspec) in [
+            (
+                "git@github.com:org/registry.git@v1.0.0[model]",
+                "git@github.com:org/registry.git",
+                Some("v1.0.0"),
+            ),
+            (
+                "ssh://git@github.com/org/registry.git@^1.26",
+                "ssh://git@github.com/org/registry.git",
+                Some("^1.26"),
+            ),
+            (
+                "https://user@example.com/registry.git",
+                "https://user@example.com/registry.git",
+                None,
+            ),
+        ] {
+            let registry_path: RegistryPath = registry_path_str.parse().unwrap();
+            if let RegistryPath::GitRepo { url, refspec, .. } = &registry_path {
+                assert_eq!(url, expected_url);
+                assert_eq!(refspec.as_deref(), expected_refspec);
+            } else {
+                panic!("Expected GitRepo, got something else");
+            }
+            assert_eq!(registry_path.to_string(), registry_path_str);
+        }
+
+        // OCI artifacts
+        let digest = format!("sha256:{}", "ab".repeat(32));
+        for (
+            registry_path_str,
+            expected_registry,
+            expected_tag,
+            expected_digest,
+            expected_sub_folder,
+        ) in [
+            (
+                "oci://ghcr.io/acme/semconv:1.2.0".to_owned(),
+                "ghcr.io",
+                Some("1.2.0"),
+                None,
+                None,
+            ),
+            (
+                format!("oci://localhost:5000/acme/semconv@{}[model]", digest),
+                "localhost:5000",
+                None,
+                Some(digest.as_str()),
+                Some("model"),
+            ),
+            (
+                format!("oci://ghcr.io/acme/semconv:v1.2.0@{}", digest),
+                "ghcr.io",
+                Some("v1.2.0"),
+                Some(digest.as_str()),
+                None,
+            ),
+            (
+                "oci://ghcr.io/acme/semconv".to_owned(),
+                "ghcr.io",
+                None,
+                None,
+                None,
+            ),
+        ] {
+            let registry_path: RegistryPath = registry_path_str.parse().unwrap();
+            if let RegistryPath::OciArtifact {
+                registry,
+                repository,
+                tag,
+                digest,
+                sub_folder,
+            } = &registry_path
+            {
+                assert_eq!(registry, expected_registry);
+                assert_eq!(repository, "acme/semconv");
+                assert_eq!(tag.as_deref(), expected_tag);
+                assert_eq!(digest.as_deref(), expected_digest);
+                assert_eq!(sub_folder.as_deref(), expected_sub_folder);
+            } else {
+                panic!("Expected OciArtifact, got something else");
+            }
+            assert_eq!(registry_path.to_string(), registry_path_str);
+        }
+        for invalid in [
+            "oci://ghcr.io",
+            "oci://ghcr.io/Acme/semconv",
+            "oci://ghcr.io/acme/semconv:1.2.0@sha256:abc",
+            "oci://ghcr.io/acme/semconv@v1.2.0",
+        ] {
+            assert!(invalid.parse::<RegistryPath>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_overlay_registry_path() {
+        let registry_path_str =
+            "https://github.com/open-telemetry/semantic-conventions.git@v1.26.0[model]+overlay=./patches";
+        let registry_path: RegistryPath = registry_path_str.parse().unwrap();
+        if let RegistryPath::Overlay { base, overlay } = &registry_path {
+            assert!(matches!(
+                base.as_ref(),
+                RegistryPath::GitRepo { refspec: Some(refspec), sub_folder: Some(sub_folder), .. }
+                    if refspec == "v1.26.0" && sub_folder == "model"
+            ));
+            assert_eq!(overlay, "./patches");
+        } else {
+            panic!("Expected Overlay, got something else");
+        }
+        assert_eq!(registry_path.to_string(), registry_path_str);
+
+        // The overlays are applied in order.
+        let registry_path: RegistryPath =
+            "registry.zip[model]+overlay=a+overlay=b".parse().unwrap();
+        if let RegistryPath::Overlay { base, overlay } = &registry_path {
+            assert!(
+                matches!(base.as_ref(), RegistryPath::Overlay { overlay, .. } if overlay == "a")
+            );
+            assert_eq!(overlay, "b");
+        } else {
+            panic!("Expected Overlay, got something else");
+        }
+
+        assert!("registry+overlay=".parse::<RegistryPath>().is_err());
     }
 }
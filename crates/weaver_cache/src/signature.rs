@@ -0,0 +1,485 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The signature verification of the remote registries.
+//!
+//! The expected signers of the remote registries are declared in a YAML
+//! trust file, whose path is read from the `WEAVER_REGISTRY_TRUST`
+//! environment variable (`~/.weaver/trust.yaml` by default, if it exists):
+//!
+//! ```yaml
+//! registries:
+//!   - prefix: https://github.com/acme/semconv/releases/download/
+//!     cosign:
+//!       identity: https://github.com/acme/semconv/.github/workflows/release.yml@refs/heads/main
+//!       issuer: https://token.actions.githubusercontent.com
+//!   - prefix: oci://ghcr.io/acme/
+//!     cosign:
+//!       key: /etc/weaver/cosign.pub
+//!   - prefix: https://artifacts.example.com/semconv/
+//!     gpg:
+//!       keyring: /etc/weaver/semconv.gpg
+//!       fingerprint: 4AEE18F83AFDEB23D1CA4F2C9D4B1A9F3A8E2B7C
+//! ```
+//!
+//! A remote registry whose URL (or `oci://` reference) starts with a prefix
+//! must be signed by the signer of the longest matching prefix:
+//! - A remote archive signed with cosign is verified with its Sigstore bundle,
+//!   downloaded from `<archive URL>.sigstore.json`
+//!   (`cosign verify-blob --bundle`).
+//! - A remote archive signed with GPG is verified with a checksum file,
+//!   downloaded from `<archive URL>.sha256`, and its detached signature,
+//!   downloaded from `<archive URL>.sha256.asc` (`gpgv`). The checksum file
+//!   must contain the SHA-256 digest of the archive.
+//! - An OCI artifact is verified with the cosign signature of its manifest
+//!   (`cosign verify`).
+//!
+//! The `cosign` and `gpgv` commands must be installed. The signatures are
+//! verified before the registry is cached, and a cached registry verified
+//! with another signer (or not verified) is fetched again.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::download::{self, RetryPolicy};
+use crate::http_agent::HttpSettings;
+use crate::Error;
+use crate::Error::InvalidTrustFile;
+
+/// The environment variable containing the path of the trust file.
+const TRUST_ENV_VAR: &str = "WEAVER_REGISTRY_TRUST";
+/// The default trust file (relative to the home directory).
+const DEFAULT_TRUST_FILE: &str = ".weaver/trust.yaml";
+/// The suffix of the URL of the Sigstore bundle of an archive.
+const SIGSTORE_BUNDLE_SUFFIX: &str = ".sigstore.json";
+/// The suffix of the URL of the checksum file of an archive.
+const CHECKSUM_SUFFIX: &str = ".sha256";
+/// The suffix of the URL of the detached GPG signature of the checksum file.
+const GPG_SIGNATURE_SUFFIX: &str = ".sha256.asc";
+
+/// The expected signers of the remote registries.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TrustPolicy {
+    #[serde(default)]
+    registries: Vec<TrustedRegistry>,
+}
+
+/// The expected signer of the registries whose URL starts with a prefix.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrustedRegistry {
+    /// The prefix of the URLs of the signed registries.
+    prefix: String,
+    /// The cosign identity of the signer, if signed with cosign.
+    #[serde(default)]
+    cosign: Option<CosignIdentity>,
+    /// The GPG key of the signer, if signed with GPG.
+    #[serde(default)]
+    gpg: Option<GpgIdentity>,
+}
+
+/// The identity of a cosign signer: a public key, or the identity and the
+/// OIDC issuer of the certificate of a keyless signature.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CosignIdentity {
+    /// The path of the public key.
+    #[serde(default)]
+    key: Option<String>,
+    /// The identity (e.g. a workflow URL or an email) of the certificate.
+    #[serde(default)]
+    identity: Option<String>,
+    /// The OIDC issuer of the certificate.
+    #[serde(default)]
+    issuer: Option<String>,
+}
+
+/// The key of a GPG signer.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct GpgIdentity {
+    /// The path of the keyring containing the public key.
+    keyring: String,
+    /// The fingerprint of the key, if the keyring contains other keys.
+    #[serde(default)]
+    fingerprint: Option<String>,
+}
+
+/// The expected signer of a registry.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Signer {
+    /// A cosign signer.
+    Cosign(CosignIdentity),
+    /// A GPG signer.
+    Gpg(GpgIdentity),
+}
+
+impl TrustPolicy {
+    /// Reads the trust file from the environment.
+    pub(crate) fn from_env() -> Result<Self, Error> {
+        Self::from_vars(
+            |name| std::env::var(name).ok().filter(|value| !value.is_empty()),
+            dirs::home_dir(),
+        )
+    }
+
+    /// Reads the trust file from the given variables. The default trust file
+    /// is only read if it exists.
+    fn from_vars(
+        var: impl Fn(&str) -> Option<String>,
+        home_dir: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        match var(TRUST_ENV_VAR) {
+            Some(path) => Self::load(Path::new(&path)),
+            None => match home_dir.map(|home_dir| home_dir.join(DEFAULT_TRUST_FILE)) {
+                Some(path) if path.is_file() => Self::load(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// Reads a trust file. Each registry must declare exactly one signer, and
+    /// a cosign signer either a key or an identity and an issuer.
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        let invalid = |error: String| InvalidTrustFile {
+            path: path.display().to_string(),
+            error,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let policy: Self = serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        for registry in &policy.registries {
+            _ = registry.signer().map_err(|error| {
+                invalid(format!(
+                    "Invalid signer of `{}`: {}",
+                    registry.prefix, error
+                ))
+            })?;
+        }
+        Ok(policy)
+    }
+
+    /// Returns the expected signer of a registry URL (or `oci://` reference),
+    /// i.e. the signer of the longest prefix of the URL, if any.
+    pub(crate) fn signer(&self, url: &str) -> Option<Signer> {
+        self.registries
+            .iter()
+            .filter(|registry| url.starts_with(&registry.prefix))
+            .max_by_key(|registry| registry.prefix.len())
+            .and_then(|registry| registry.signer().ok())
+    }
+}
+
+impl TrustedRegistry {
+    /// Returns the signer of the registry.
+    fn signer(&self) -> Result<Signer, String> {
+        match (&self.cosign, &self.gpg) {
+            (Some(cosign), None) => match (&cosign.key, &cosign.identity, &cosign.issuer) {
+                (Some(_), None, None) | (None, Some(_), Some(_)) => {
+                    Ok(Signer::Cosign(cosign.clone()))
+                }
+                _ => Err("Expected either a `key` or an `identity` and an `issuer`".to_owned()),
+            },
+            (None, Some(gpg)) => Ok(Signer::Gpg(gpg.clone())),
+            _ => Err("Expected either a `cosign` or a `gpg` signer".to_owned()),
+        }
+    }
+}
+
+impl Display for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signer::Cosign(CosignIdentity { key: Some(key), .. }) => {
+                write!(f, "cosign:key={}", key)
+            }
+            Signer::Cosign(CosignIdentity {
+                identity, issuer, ..
+            }) => write!(
+                f,
+                "cosign:identity={},issuer={}",
+                identity.as_deref().unwrap_or_default(),
+                issuer.as_deref().unwrap_or_default()
+            ),
+            Signer::Gpg(GpgIdentity {
+                keyring,
+                fingerprint,
+            }) => match fingerprint {
+                Some(fingerprint) => write!(f, "gpg:{}", fingerprint),
+                None => write!(f, "gpg:keyring={}", keyring),
+            },
+        }
+    }
+}
+
+impl Signer {
+    /// Verifies the signature of a downloaded archive, with the signature
+    /// files downloaded next to it.
+    ///
+    /// Arguments:
+    /// - `url`: The URL the archive was downloaded from.
+    /// - `archive`: The path of the downloaded archive.
+    /// - `sha256`: The SHA-256 digest of the archive (lowercase hexadecimal).
+    pub(crate) fn verify_archive(
+        &self,
+        url: &str,
+        archive: &Path,
+        sha256: &str,
+    ) -> Result<(), String> {
+        let download_next_to_archive = |suffix: &str| {
+            let mut path = archive.as_os_str().to_owned();
+            path.push(suffix);
+            let path = PathBuf::from(path);
+            let url = format!("{}{}", url, suffix);
+            let agent = HttpSettings::from_env()
+                .agent(&url)
+                .map_err(|e| e.to_string())?;
+            download::download(&agent, &url, None, &path, &RetryPolicy::default())
+                .map_err(|e| format!("Failed to download `{}`: {}", url, e))?;
+            Ok::<_, String>(path)
+        };
+        match self {
+            Signer::Cosign(identity) => {
+                let bundle = download_next_to_archive(SIGSTORE_BUNDLE_SUFFIX)?;
+                let mut command = Command::new("cosign");
+                _ = command.arg("verify-blob").arg("--bundle").arg(&bundle);
+                identity.add_args(&mut command);
+                _ = command.arg(archive);
+                let result = run(command).map(|_| ());
+                _ = std::fs::remove_file(bundle);
+                result
+            }
+            Signer::Gpg(gpg) => {
+                let checksum_file = download_next_to_archive(CHECKSUM_SUFFIX)?;
+                let signature = download_next_to_archive(GPG_SIGNATURE_SUFFIX)?;
+                let result = gpg.verify(&signature, &checksum_file).and_then(|()| {
+                    let content =
+                        std::fs::read_to_string(&checksum_file).map_err(|e| e.to_string())?;
+                    match checksum_file_digest(&content) {
+                        Some(digest) if digest.eq_ignore_ascii_case(sha256) => Ok(()),
+                        Some(digest) => Err(format!(
+                            "The signed SHA-256 digest is `{}`, the archive digest is `{}`",
+                            digest, sha256
+                        )),
+                        None => Err("The checksum file contains no SHA-256 digest".to_owned()),
+                    }
+                });
+                _ = std::fs::remove_file(checksum_file);
+                _ = std::fs::remove_file(signature);
+                result
+            }
+        }
+    }
+
+    /// Verifies the cosign signature of an OCI artifact, referenced by the
+    /// digest of its manifest (e.g. `ghcr.io/acme/semconv@sha256:<digest>`).
+    pub(crate) fn verify_oci_artifact(&self, reference: &str) -> Result<(), String> {
+        match self {
+            Signer::Cosign(identity) => {
+                let mut command = Command::new("cosign");
+                _ = command.arg("verify");
+                identity.add_args(&mut command);
+                _ = command.arg(reference);
+                run(command).map(|_| ())
+            }
+            Signer::Gpg(_) => {
+                Err("The OCI artifacts can only be verified with a cosign signer".to_owned())
+            }
+        }
+    }
+}
+
+impl CosignIdentity {
+    /// Adds the options of the expected identity to a cosign command.
+    fn add_args(&self, command: &mut Command) {
+        if let Some(key) = &self.key {
+            _ = command.arg("--key").arg(key);
+        }
+        if let Some(identity) = &self.identity {
+            _ = command.arg("--certificate-identity").arg(identity);
+        }
+        if let Some(issuer) = &self.issuer {
+            _ = command.arg("--certificate-oidc-issuer").arg(issuer);
+        }
+    }
+}
+
+impl GpgIdentity {
+    /// Verifies the detached signature of a file with `gpgv`, and the
+    /// fingerprint of the signing key, if any.
+    fn verify(&self, signature: &Path, file: &Path) -> Result<(), String> {
+        let mut command = Command::new("gpgv");
+        _ = command
+            .arg("--status-fd")
+            .arg("1")
+            .arg("--keyring")
+            .arg(&self.keyring)
+            .arg(signature)
+            .arg(file);
+        let status = run(command)?;
+        let Some(expected) = &self.fingerprint else {
+            return Ok(());
+        };
+        let expected = expected.replace(' ', "");
+        let signed_by_expected_key = valid_signature_fingerprints(&status)
+            .any(|fingerprint| fingerprint.eq_ignore_ascii_case(&expected));
+        if signed_by_expected_key {
+            Ok(())
+        } else {
+            Err(format!(
+                "The signature was not made by the key `{}`",
+                expected
+            ))
+        }
+    }
+}
+
+/// Runs a verification command, and returns its standard output.
+fn run(mut command: Command) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run `{}` (is it installed?): {}", program, e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "`{}` failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Returns the fingerprints of the keys of the valid signatures reported in
+/// the status output of `gpgv` (`[GNUPG:] VALIDSIG <fingerprint> ...` lines).
+fn valid_signature_fingerprints(status: &str) -> impl Iterator<Item = &str> {
+    status.lines().filter_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next() == Some("[GNUPG:]") && fields.next() == Some("VALIDSIG"))
+            .then(|| fields.next())
+            .flatten()
+    })
+}
+
+/// Returns the digest of a checksum file, i.e. the first word of the file
+/// (`<hex digest>` or `<hex digest>  <file name>`), if it is a SHA-256
+/// digest.
+fn checksum_file_digest(content: &str) -> Option<&str> {
+    content
+        .split_whitespace()
+        .next()
+        .filter(|digest| digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_trust_policy() {
+        let dir = TempDir::new("trust").unwrap();
+        let path = dir.path().join("trust.yaml");
+        std::fs::write(
+            &path,
+            "registries:\n\
+            - prefix: https://example.com/\n  \
+              gpg: { keyring: keyring.gpg }\n\
+            - prefix: https://example.com/acme/\n  \
+              cosign: { identity: release@acme.com, issuer: https://accounts.google.com }\n\
+            - prefix: oci://ghcr.io/acme/\n  \
+              cosign: { key: cosign.pub }\n",
+        )
+        .unwrap();
+        let vars: HashMap<_, _> = [(TRUST_ENV_VAR, path.display().to_string())]
+            .into_iter()
+            .collect();
+        let policy = TrustPolicy::from_vars(|name| vars.get(name).cloned(), None).unwrap();
+
+        // The longest prefix wins.
+        assert_eq!(
+            policy
+                .signer("https://example.com/acme/registry.zip")
+                .map(|signer| signer.to_string()),
+            Some("cosign:identity=release@acme.com,issuer=https://accounts.google.com".to_owned())
+        );
+        assert_eq!(
+            policy
+                .signer("https://example.com/registry.zip")
+                .map(|signer| signer.to_string()),
+            Some("gpg:keyring=keyring.gpg".to_owned())
+        );
+        assert_eq!(
+            policy
+                .signer("oci://ghcr.io/acme/semconv")
+                .map(|signer| signer.to_string()),
+            Some("cosign:key=cosign.pub".to_owned())
+        );
+        assert_eq!(policy.signer("oci://ghcr.io/other/semconv"), None);
+
+        // Without trust file.
+        let policy = TrustPolicy::from_vars(|_| None, Some(dir.path().join("home"))).unwrap();
+        assert_eq!(policy.signer("https://example.com/registry.zip"), None);
+
+        // Invalid signers.
+        for registry in [
+            "{ prefix: https://example.com/ }",
+            "{ prefix: https://example.com/, cosign: { key: cosign.pub }, gpg: { keyring: keyring.gpg } }",
+            "{ prefix: https://example.com/, cosign: { identity: release@acme.com } }",
+            "{ prefix: https://example.com/, cosign: { key: cosign.pub, issuer: https://accounts.google.com } }",
+        ] {
+            std::fs::write(&path, format!("registries:\n- {}\n", registry)).unwrap();
+            assert!(
+                matches!(
+                    TrustPolicy::from_vars(|name| vars.get(name).cloned(), None),
+                    Err(InvalidTrustFile { .. })
+                ),
+                "{}",
+                registry
+            );
+        }
+    }
+
+    #[test]
+    fn test_gpg_status() {
+        let status = "[GNUPG:] NEWSIG\n\
+            [GNUPG:] GOODSIG 9D4B1A9F3A8E2B7C Acme <release@acme.com>\n\
+            [GNUPG:] VALIDSIG 4AEE18F83AFDEB23D1CA4F2C9D4B1A9F3A8E2B7C 2025-01-01 1735689600 0 4 0 1 10 00 4AEE18F83AFDEB23D1CA4F2C9D4B1A9F3A8E2B7C\n";
+        assert_eq!(
+            valid_signature_fingerprints(status).collect::<Vec<_>>(),
+            vec!["4AEE18F83AFDEB23D1CA4F2C9D4B1A9F3A8E2B7C"]
+        );
+        assert_eq!(
+            valid_signature_fingerprints("[GNUPG:] BADSIG 9D4B").count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_checksum_file_digest() {
+        let digest = "a".repeat(64);
+        assert_eq!(checksum_file_digest(&digest), Some(digest.as_str()));
+        assert_eq!(
+            checksum_file_digest(&format!("{}  registry.zip\n", digest)),
+            Some(digest.as_str())
+        );
+        assert_eq!(checksum_file_digest("registry.zip"), None);
+        assert_eq!(checksum_file_digest(""), None);
+    }
+
+    #[test]
+    fn test_oci_artifact_gpg_signer() {
+        let signer = Signer::Gpg(GpgIdentity {
+            keyring: "keyring.gpg".to_owned(),
+            fingerprint: None,
+        });
+        assert!(signer
+            .verify_oci_artifact("ghcr.io/acme/semconv@sha256:0000")
+            .is_err());
+    }
+}
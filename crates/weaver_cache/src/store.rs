@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The content-addressable store of the registry cache.
+//!
+//! The files of the cached registries are stored once in the `objects`
+//! directory of the cache, keyed by the SHA-256 digest of their content
+//! (`objects/<first 2 hex digits>/<next 62 hex digits>`). Each cache entry
+//! records the manifest of its registry, i.e. the digest of each file, and
+//! its registry directory is made of hard links to the objects. The files
+//! shared by several registry versions, or by the registries of several
+//! projects, are thus stored once on disk.
+//!
+//! All the links to an object share its content, so a write through one of
+//! them would change the files of every registry sharing the object. The
+//! objects are thus made read-only (on Unix, where a read-only file can still
+//! be removed), and the registry files copied out of the cache (e.g. for an
+//! overlay) are written as new, writable files.
+//!
+//! On file systems without hard links, the files are kept as is in the cache
+//! entries. The objects are never modified; those no longer referenced by a
+//! cache entry are removed by [`remove_unreferenced_objects`].
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::download::sha256_file;
+
+/// The directory of the objects, relative to the cache directory.
+pub(crate) const OBJECTS_DIR: &str = "objects";
+/// The suffix of the temporary links replacing a file.
+const LINK_SUFFIX: &str = ".weaver-link";
+
+/// The manifest of a registry: the digest of each file, keyed by its path
+/// relative to the registry directory (with `/` separators).
+pub(crate) type Manifest = BTreeMap<String, String>;
+
+/// Returns the path of the object of a digest.
+pub(crate) fn object_path(cache_dir: &Path, digest: &str) -> PathBuf {
+    let (prefix, rest) = digest.split_at(digest.len().min(2));
+    cache_dir.join(OBJECTS_DIR).join(prefix).join(rest)
+}
+
+/// Returns the key of a file in a manifest, from its path relative to the
+/// registry directory.
+pub(crate) fn manifest_key(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Moves the files of a registry directory into the store and returns the
+/// manifest of the registry. Each file becomes a hard link to the read-only
+/// object of its content, which is created from the file if it isn't already
+/// stored. The `.git` directories are skipped.
+pub(crate) fn import(cache_dir: &Path, registry_dir: &Path) -> io::Result<Manifest> {
+    let mut manifest = Manifest::new();
+    let entries = WalkDir::new(registry_dir)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git");
+    for entry in entries {
+        let entry = entry.map_err(io::Error::from)?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let digest = sha256_file(path)?;
+        let object = object_path(cache_dir, &digest);
+        if let Some(parent) = object.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match fs::hard_link(path, &object) {
+            // The file is now the object of its content.
+            Ok(()) => set_read_only(&object)?,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                // The file is replaced by a link to the stored object. If the
+                // object was removed in the meantime, the file is kept. The
+                // objects stored by previous versions may still be writable.
+                let mut link = path.as_os_str().to_owned();
+                link.push(LINK_SUFFIX);
+                if fs::hard_link(&object, &link).is_ok() {
+                    set_read_only(&object)?;
+                    fs::rename(&link, path)?;
+                }
+            }
+            // Hard links are not supported, the file is kept.
+            Err(_) => {}
+        }
+        let relative_path = path.strip_prefix(registry_dir).map_err(io::Error::other)?;
+        _ = manifest.insert(manifest_key(relative_path), digest);
+    }
+    Ok(manifest)
+}
+
+/// Removes the write permissions of a stored object, shared by all its links.
+#[cfg(unix)]
+fn set_read_only(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() & !0o222);
+    fs::set_permissions(path, permissions)
+}
+
+/// The read-only files can't be removed on Windows, so the objects are kept
+/// writable.
+#[cfg(not(unix))]
+fn set_read_only(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Copies a file out of the store. The copy is a new file, writable whatever
+/// the permissions of the object, unlike with [`fs::copy`].
+pub(crate) fn copy_file(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::write(dst, fs::read(src)?)
+}
+
+/// Returns the size of a stored object, or 0 if it doesn't exist.
+pub(crate) fn object_size(cache_dir: &Path, digest: &str) -> u64 {
+    fs::metadata(object_path(cache_dir, digest))
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+/// Removes the objects whose digest is not referenced, and returns their
+/// total size in bytes.
+pub(crate) fn remove_unreferenced_objects(
+    cache_dir: &Path,
+    referenced: &HashSet<&str>,
+) -> io::Result<u64> {
+    let objects_dir = cache_dir.join(OBJECTS_DIR);
+    if !objects_dir.exists() {
+        return Ok(0);
+    }
+    let mut removed_size = 0;
+    for prefix_entry in fs::read_dir(&objects_dir)? {
+        let prefix_dir = prefix_entry?.path();
+        if !prefix_dir.is_dir() {
+            continue;
+        }
+        let prefix = prefix_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for object_entry in fs::read_dir(&prefix_dir)? {
+            let object_entry = object_entry?;
+            let digest = format!("{}{}", prefix, object_entry.file_name().to_string_lossy());
+            if !referenced.contains(digest.as_str()) {
+                let size = object_entry.metadata()?.len();
+                fs::remove_file(object_entry.path())?;
+                removed_size += size;
+            }
+        }
+        // Only succeeds if the directory is empty.
+        _ = fs::remove_dir(&prefix_dir);
+    }
+    _ = fs::remove_dir(&objects_dir);
+    Ok(removed_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn write_registry(dir: &Path, files: &[(&str, &str)]) {
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_import() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let v1 = cache_dir.path().join("v1");
+        let v2 = cache_dir.path().join("v2");
+        write_registry(
+            &v1,
+            &[
+                ("http/registry.yaml", "http v1"),
+                ("dns/registry.yaml", "dns"),
+                (".git/HEAD", "ref"),
+            ],
+        );
+        write_registry(
+            &v2,
+            &[
+                ("http/registry.yaml", "http v2"),
+                ("dns/registry.yaml", "dns"),
+            ],
+        );
+
+        let manifest_v1 = import(cache_dir.path(), &v1).unwrap();
+        let manifest_v2 = import(cache_dir.path(), &v2).unwrap();
+        assert_eq!(
+            manifest_v1.keys().collect::<Vec<_>>(),
+            vec!["dns/registry.yaml", "http/registry.yaml"]
+        );
+        assert_eq!(
+            manifest_v1["dns/registry.yaml"],
+            manifest_v2["dns/registry.yaml"]
+        );
+        assert_ne!(
+            manifest_v1["http/registry.yaml"],
+            manifest_v2["http/registry.yaml"]
+        );
+
+        // The registries are unchanged, and the identical file is stored once.
+        assert_eq!(
+            fs::read_to_string(v2.join("dns/registry.yaml")).unwrap(),
+            "dns"
+        );
+        assert_eq!(
+            fs::read_to_string(v2.join("http/registry.yaml")).unwrap(),
+            "http v2"
+        );
+        let objects: Vec<_> = WalkDir::new(cache_dir.path().join(OBJECTS_DIR))
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .collect();
+        assert_eq!(objects.len(), 3);
+        let dns = object_path(cache_dir.path(), &manifest_v1["dns/registry.yaml"]);
+        assert_eq!(fs::read_to_string(&dns).unwrap(), "dns");
+        assert_eq!(
+            object_size(cache_dir.path(), &manifest_v1["dns/registry.yaml"]),
+            3
+        );
+
+        // Only the objects of v2 are kept.
+        fs::remove_dir_all(&v1).unwrap();
+        let referenced: HashSet<&str> = manifest_v2.values().map(String::as_str).collect();
+        assert_eq!(
+            remove_unreferenced_objects(cache_dir.path(), &referenced).unwrap(),
+            7
+        );
+        assert!(dns.exists());
+        assert!(!object_path(cache_dir.path(), &manifest_v1["http/registry.yaml"]).exists());
+
+        assert_eq!(
+            remove_unreferenced_objects(cache_dir.path(), &HashSet::new()).unwrap(),
+            10
+        );
+        assert!(!cache_dir.path().join(OBJECTS_DIR).exists());
+        // The registry files are still there.
+        assert_eq!(
+            fs::read_to_string(v2.join("dns/registry.yaml")).unwrap(),
+            "dns"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_does_not_spread() {
+        let cache_dir = TempDir::new("cache").unwrap();
+        let v1 = cache_dir.path().join("v1");
+        let v2 = cache_dir.path().join("v2");
+        write_registry(&v1, &[("dns/registry.yaml", "dns")]);
+        write_registry(&v2, &[("dns/registry.yaml", "dns")]);
+        _ = import(cache_dir.path(), &v1).unwrap();
+        _ = import(cache_dir.path(), &v2).unwrap();
+
+        // The shared object is read-only through all its links.
+        for registry in [&v1, &v2] {
+            let path = registry.join("dns/registry.yaml");
+            assert!(fs::metadata(&path).unwrap().permissions().readonly());
+        }
+
+        // A write through one registry is refused, unless the process ignores
+        // the file permissions (e.g. root), and never reaches the other one.
+        let privileged = {
+            let probe = cache_dir.path().join("probe");
+            fs::write(&probe, "").unwrap();
+            set_read_only(&probe).unwrap();
+            fs::write(&probe, "").is_ok()
+        };
+        let written = fs::write(v2.join("dns/registry.yaml"), "changed");
+        if !privileged {
+            assert!(written.is_err());
+            assert_eq!(
+                fs::read_to_string(v1.join("dns/registry.yaml")).unwrap(),
+                "dns"
+            );
+        }
+
+        // A copy out of the store is writable and independent of the object.
+        let copy = cache_dir.path().join("copy.yaml");
+        copy_file(&v1.join("dns/registry.yaml"), &copy).unwrap();
+        assert!(!fs::metadata(&copy).unwrap().permissions().readonly());
+        fs::write(&copy, "copy").unwrap();
+        assert_ne!(
+            fs::read_to_string(v1.join("dns/registry.yaml")).unwrap(),
+            "copy"
+        );
+    }
+}
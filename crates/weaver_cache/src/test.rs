@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Registry cache entries for testing purposes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::CacheEntry;
+
+/// Writes a cache entry named `name` in the given cache directory, for the
+/// given registry path, downloaded `age` seconds ago, with a single registry
+/// file of `size` bytes.
+pub fn write_cache_entry(cache_dir: &Path, name: &str, registry_path: &str, age: u64, size: usize) {
+    let entry_dir = cache_dir.join(name);
+    fs::create_dir_all(entry_dir.join("registry")).expect("Failed to create the entry");
+    fs::write(entry_dir.join("registry/registry.yaml"), "a".repeat(size))
+        .expect("Failed to write the registry file");
+    CacheEntry {
+        registry_path: registry_path.to_owned(),
+        refspec: None,
+        revision: None,
+        path: PathBuf::from("registry"),
+        fetched_at: crate::now_secs() - age,
+        validators: Default::default(),
+        objects: Default::default(),
+        signer: None,
+    }
+    .store(&entry_dir)
+    .expect("Failed to store the entry metadata");
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks that the Git repositories are fetched through the proxy set in the
+//! environment. This test runs in its own process, as it sets the proxy
+//! variables of the process.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+
+use tempdir::TempDir;
+use weaver_cache::registry_path::RegistryPath;
+use weaver_cache::{RegistryCache, RegistryRepo};
+
+#[test]
+fn test_git_repo_through_proxy() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind the proxy");
+    let proxy_url = format!(
+        "http://{}",
+        listener.local_addr().expect("No proxy address")
+    );
+    let proxy = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("No connection to the proxy");
+        let mut request_line = String::new();
+        _ = BufReader::new(stream.try_clone().expect("Failed to clone the stream"))
+            .read_line(&mut request_line)
+            .expect("Failed to read the request");
+        _ = stream.write_all(
+            b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+        request_line
+    });
+    for var in [
+        "NO_PROXY",
+        "no_proxy",
+        "ALL_PROXY",
+        "all_proxy",
+        "http_proxy",
+    ] {
+        std::env::remove_var(var);
+    }
+    std::env::set_var("HTTP_PROXY", &proxy_url);
+
+    let cache_dir = TempDir::new("cache").expect("Failed to create the cache dir");
+    let registry_cache = RegistryCache::try_default()
+        .expect("Failed to create the registry cache")
+        .with_dir(cache_dir.path().to_path_buf());
+    let registry_path = RegistryPath::GitRepo {
+        url: "http://git.example.invalid/registry.git".to_owned(),
+        sub_folder: None,
+        refspec: None,
+    };
+    // The proxy doesn't serve the repository.
+    assert!(RegistryRepo::try_new("main", &registry_path, &registry_cache).is_err());
+
+    // The request is sent to the proxy with the absolute URL.
+    let request_line = proxy.join().expect("The proxy failed");
+    assert!(
+        request_line.starts_with("GET http://git.example.invalid/registry.git/info/refs"),
+        "{}",
+        request_line
+    );
+}
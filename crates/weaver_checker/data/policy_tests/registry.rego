@@ -0,0 +1,17 @@
+package before_resolution
+
+# A registry `attribute_group` containing at least one `ref` attribute is
+# considered invalid.
+deny[violation] {
+    group := input.groups[_]
+    startswith(group.id, "registry.")
+    attr := group.attributes[_]
+    attr.ref != null
+    violation := {
+        "id": "registry_with_ref_attr",
+        "type": "semconv_attribute",
+        "category": "attribute_registry",
+        "group": group.id,
+        "attr": attr.ref,
+    }
+}
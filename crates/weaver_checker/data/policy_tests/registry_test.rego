@@ -0,0 +1,14 @@
+package before_resolution
+
+test_ref_attr_denied {
+    count(deny) == 1 with input as {"groups": [{"id": "registry.a", "attributes": [{"ref": "a.b"}]}]}
+}
+
+test_defined_attr_allowed {
+    count(deny) == 0 with input as {"groups": [{"id": "registry.a", "attributes": [{"id": "a.b"}]}]}
+}
+
+# This test fails on purpose.
+test_wrong_expectation {
+    count(deny) == 2 with input as {"groups": [{"id": "registry.a", "attributes": [{"ref": "a.b"}]}]}
+}
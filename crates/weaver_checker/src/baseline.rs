@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Baselines of known policy violations.
+//!
+//! A baseline file lists the known violations of a registry, each identified
+//! by the id of the violated rule and a hash of its target (the group and the
+//! attribute of the violation):
+//!
+//! ```yaml
+//! violations:
+//!   - id: attr_stability_deprecated
+//!     target: 7b0b2c53f2e6a3d1
+//! ```
+//!
+//! The violations of the baseline are not reported by [`crate::Engine::check`],
+//! so that strict policies can be adopted incrementally on legacy registries:
+//! only the new violations are reported. A baseline created with
+//! [`Baseline::recording`] suppresses all the violations, and records them to
+//! write a new baseline file.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::violation::Violation;
+use crate::Error;
+
+/// The number of hexadecimal digits of the target hashes.
+const TARGET_HASH_LEN: usize = 16;
+
+/// A known violation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineEntry {
+    /// The id of the violated rule.
+    pub id: String,
+    /// The hash of the target of the violation.
+    pub target: String,
+}
+
+impl BaselineEntry {
+    /// Returns the baseline entry of a violation.
+    #[must_use]
+    pub fn new(violation: &Violation) -> Self {
+        match violation {
+            Violation::SemconvAttribute {
+                id, group, attr, ..
+            } => {
+                let digest = Sha256::digest(format!("{}\n{}", group, attr));
+                let mut target = format!("{:x}", digest);
+                target.truncate(TARGET_HASH_LEN);
+                Self {
+                    id: id.clone(),
+                    target,
+                }
+            }
+        }
+    }
+}
+
+/// The content of a baseline file.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BaselineFile {
+    /// The known violations.
+    violations: Vec<BaselineEntry>,
+}
+
+/// A baseline of known violations.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    /// The known violations.
+    entries: BTreeSet<BaselineEntry>,
+    /// True if all the violations are suppressed, i.e. the baseline is being
+    /// regenerated.
+    suppress_all: bool,
+    /// The violations checked against the baseline, shared by the clones of the
+    /// engine.
+    checked: Mutex<BTreeSet<BaselineEntry>>,
+}
+
+impl Baseline {
+    /// Loads a baseline file.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let invalid = |error: String| Error::InvalidBaselineFile {
+            file: path.display().to_string(),
+            error,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let file: BaselineFile =
+            serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        Ok(Self {
+            entries: file.violations.into_iter().collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Returns a baseline suppressing and recording all the violations.
+    #[must_use]
+    pub fn recording() -> Self {
+        Self {
+            suppress_all: true,
+            ..Default::default()
+        }
+    }
+
+    /// Records the violations and returns those that are not in the baseline.
+    pub(crate) fn filter(&self, violations: Vec<Violation>) -> Vec<Violation> {
+        let mut checked = self.checked.lock().expect("Baseline lock poisoned");
+        violations
+            .into_iter()
+            .filter(|violation| {
+                let entry = BaselineEntry::new(violation);
+                let known = self.suppress_all || self.entries.contains(&entry);
+                _ = checked.insert(entry);
+                !known
+            })
+            .collect()
+    }
+
+    /// Returns the number of violations suppressed so far.
+    #[must_use]
+    pub fn suppressed_count(&self) -> usize {
+        let checked = self.checked.lock().expect("Baseline lock poisoned");
+        if self.suppress_all {
+            checked.len()
+        } else {
+            checked.intersection(&self.entries).count()
+        }
+    }
+
+    /// Returns the number of entries of the baseline that didn't match any
+    /// violation so far, i.e. the violations that have been fixed.
+    #[must_use]
+    pub fn stale_count(&self) -> usize {
+        let checked = self.checked.lock().expect("Baseline lock poisoned");
+        self.entries.difference(&checked).count()
+    }
+
+    /// Writes a baseline file with all the violations checked so far, and
+    /// returns their number.
+    pub fn save(&self, path: &Path) -> Result<usize, Error> {
+        let invalid = |error: String| Error::InvalidBaselineFile {
+            file: path.display().to_string(),
+            error,
+        };
+        let checked = self.checked.lock().expect("Baseline lock poisoned");
+        let file = BaselineFile {
+            violations: checked.iter().cloned().collect(),
+        };
+        let content = serde_yaml::to_string(&file).map_err(|e| invalid(e.to_string()))?;
+        std::fs::write(path, content).map_err(|e| invalid(e.to_string()))?;
+        Ok(file.violations.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use crate::violation::Severity;
+
+    use super::*;
+
+    fn violation(id: &str, attr: &str) -> Violation {
+        Violation::SemconvAttribute {
+            id: id.to_owned(),
+            category: "attribute".to_owned(),
+            group: "registry.a".to_owned(),
+            attr: attr.to_owned(),
+            severity: Severity::Violation,
+            fix: None,
+        }
+    }
+
+    #[test]
+    fn test_baseline() {
+        let dir = TempDir::new("baseline").unwrap();
+        let path = dir.path().join("baseline.yaml");
+        let violations = || {
+            vec![
+                violation("attr_removed", "a.b"),
+                violation("attr_removed", "a.c"),
+                violation("attr_stability_deprecated", "a.b"),
+            ]
+        };
+
+        // The recording baseline suppresses and records all the violations.
+        let baseline = Baseline::recording();
+        assert!(baseline.filter(violations()).is_empty());
+        assert_eq!(baseline.suppressed_count(), 3);
+        assert_eq!(baseline.save(&path).unwrap(), 3);
+
+        // Only the new violations are reported.
+        let baseline = Baseline::load(&path).unwrap();
+        let mut new_violations = violations();
+        _ = new_violations.remove(1);
+        new_violations.push(violation("attr_removed", "a.d"));
+        assert_eq!(
+            baseline.filter(new_violations),
+            vec![violation("attr_removed", "a.d")]
+        );
+        assert_eq!(baseline.suppressed_count(), 2);
+        // The violation of `a.c` has been fixed.
+        assert_eq!(baseline.stale_count(), 1);
+
+        // The targets are hashed.
+        let entry = BaselineEntry::new(&violation("attr_removed", "a.b"));
+        assert_eq!(entry.target.len(), TARGET_HASH_LEN);
+        assert_ne!(
+            entry.target,
+            BaselineEntry::new(&violation("attr_removed", "a.c")).target
+        );
+    }
+
+    #[test]
+    fn test_invalid_baseline() {
+        let result = Baseline::load(Path::new("data/baselines/missing.yaml"));
+        assert!(matches!(result, Err(Error::InvalidBaselineFile { .. })));
+    }
+}
@@ -0,0 +1,1505 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small interpreter of the [Common Expression Language](https://github.com/google/cel-spec)
+//! (CEL), used to evaluate the CEL policies.
+//!
+//! The values are JSON values. The supported subset of CEL covers the needs of
+//! the semconv checks:
+//! - literals: `null`, booleans, integers, doubles, strings (with `'` or `"`,
+//!   and `r'...'` raw strings), lists `[a, b]`, and maps `{'k': v}`,
+//! - operators: `!`, `-`, `*`, `/`, `%`, `+`, `-`, `<`, `<=`, `>`, `>=`, `==`,
+//!   `!=`, `in`, `&&`, `||`, and `? :`,
+//! - field selection `a.b`, indexing `a[0]` or `a['b']`,
+//! - the `has(a.b)` macro and the `all`, `exists`, `exists_one`, `map`, and
+//!   `filter` macros on lists and maps,
+//! - the functions `size`, `int`, `double`, `string`, and `matches`, and the
+//!   methods `size`, `contains`, `startsWith`, `endsWith`, `matches`,
+//!   `lowerAscii`, `upperAscii`, and `split`.
+//!
+//! Anything else is rejected when the expression is compiled, in particular
+//! the unsigned integers (`1u`), the bytes literals (`b'...'`), the
+//! triple-quoted strings, the octal, hexadecimal, and Unicode escape sequences,
+//! the message construction (`T{f: v}`), the reserved words, and the other
+//! functions (e.g. `uint`, `bytes`, `type`, `dyn`, `timestamp`, `duration`) or
+//! arities. The following restrictions are only detected during the
+//! evaluation: the map keys must be strings, and as in CEL, the arithmetic
+//! operators don't mix integers and doubles (`1 + 2.0` is an error, unlike the
+//! comparisons `1 < 2.0` and `1 == 1.0`).
+//!
+//! As in CEL, `&&` and `||` absorb the errors of one of their operands when the
+//! other one determines the result. A field set to `null` is considered
+//! absent by `has`.
+
+use std::borrow::Cow;
+
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+
+/// A compiled CEL expression.
+#[derive(Debug, Clone)]
+pub(crate) struct Program {
+    expr: Expr,
+}
+
+/// The variables of an evaluation, as a chain of bindings.
+pub(crate) struct Activation<'a> {
+    name: &'a str,
+    value: &'a Value,
+    parent: Option<&'a Activation<'a>>,
+}
+
+impl<'a> Activation<'a> {
+    /// Creates an activation binding a single variable.
+    pub(crate) fn new(name: &'a str, value: &'a Value) -> Self {
+        Self {
+            name,
+            value,
+            parent: None,
+        }
+    }
+
+    /// Returns a new activation binding a variable on top of this one.
+    pub(crate) fn bind<'b>(&'b self, name: &'b str, value: &'b Value) -> Activation<'b> {
+        Activation {
+            name,
+            value,
+            parent: Some(self),
+        }
+    }
+
+    /// Returns the value of a variable.
+    fn lookup(&self, name: &str) -> Option<&'a Value> {
+        let mut activation = Some(self);
+        while let Some(current) = activation {
+            if current.name == name {
+                return Some(current.value);
+            }
+            activation = current.parent;
+        }
+        None
+    }
+}
+
+impl Program {
+    /// Compiles a CEL expression.
+    pub(crate) fn compile(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            Token::Eof => Ok(Self { expr }),
+            token => Err(format!(
+                "Unexpected {} at position {}",
+                token.describe(),
+                parser.offset()
+            )),
+        }
+    }
+
+    /// Evaluates the expression with the given variables.
+    pub(crate) fn eval(&self, activation: &Activation<'_>) -> Result<Value, String> {
+        eval(&self.expr, activation).map(Cow::into_owned)
+    }
+}
+
+// ========= Lexer =========
+
+/// A token of a CEL expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Double(f64),
+    Str(String),
+    Ident(String),
+    Sym(&'static str),
+    Eof,
+}
+
+impl Token {
+    /// Returns a description of the token for the error messages.
+    fn describe(&self) -> String {
+        match self {
+            Token::Int(value) => format!("integer `{}`", value),
+            Token::Double(value) => format!("number `{}`", value),
+            Token::Str(value) => format!("string {:?}", value),
+            Token::Ident(name) => format!("identifier `{}`", name),
+            Token::Sym(sym) => format!("`{}`", sym),
+            Token::Eof => "end of expression".to_owned(),
+        }
+    }
+}
+
+/// The reserved words of CEL, which can't be used as identifiers.
+const RESERVED_WORDS: [&str; 16] = [
+    "as",
+    "break",
+    "const",
+    "continue",
+    "else",
+    "for",
+    "function",
+    "if",
+    "import",
+    "let",
+    "loop",
+    "package",
+    "namespace",
+    "return",
+    "var",
+    "void",
+];
+
+/// The symbols, the longest first.
+const SYMBOLS: [&str; 24] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "+", "-", "*", "/", "%", "!", "?", ":", ".", ",",
+    "[", "]", "(", ")", "{", "}",
+];
+
+/// Splits an expression into tokens, with their offset in the expression.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let mut pos = 0;
+    while pos < chars.len() {
+        let c = chars[pos];
+        let start = pos;
+        if c.is_whitespace() {
+            pos += 1;
+        } else if c.is_ascii_digit() {
+            let (token, end) = number(&chars, pos)?;
+            tokens.push((token, start));
+            pos = end;
+        } else if (c == '\'' || c == '"')
+            && chars.get(pos + 1) == Some(&c)
+            && chars.get(pos + 2) == Some(&c)
+        {
+            return Err(format!(
+                "Triple-quoted strings are not supported at position {}",
+                pos
+            ));
+        } else if c == '\'' || c == '"' {
+            let (value, end) = string(&chars, pos + 1, c, false)?;
+            tokens.push((Token::Str(value), start));
+            pos = end;
+        } else if (c == 'r' || c == 'R')
+            && matches!(chars.get(pos + 1), Some(&quote) if quote == '\'' || quote == '"')
+        {
+            let (value, end) = string(&chars, pos + 2, chars[pos + 1], true)?;
+            tokens.push((Token::Str(value), start));
+            pos = end;
+        } else if is_bytes_literal(&chars, pos) {
+            return Err(format!(
+                "Bytes literals are not supported at position {}",
+                pos
+            ));
+        } else if c.is_alphabetic() || c == '_' {
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            tokens.push((Token::Ident(chars[start..pos].iter().collect()), start));
+        } else {
+            let sym = SYMBOLS
+                .iter()
+                .find(|sym| {
+                    sym.chars()
+                        .enumerate()
+                        .all(|(i, sym_char)| chars.get(pos + i) == Some(&sym_char))
+                })
+                .ok_or_else(|| format!("Unexpected character `{}` at position {}", c, pos))?;
+            tokens.push((Token::Sym(sym), start));
+            pos += sym.len();
+        }
+    }
+    tokens.push((Token::Eof, chars.len()));
+    Ok(tokens)
+}
+
+/// Returns true if a bytes literal (`b'...'`, or `br'...'` and `rb'...'` for
+/// the raw ones) starts at `pos`.
+fn is_bytes_literal(chars: &[char], pos: usize) -> bool {
+    let prefix: String = chars[pos..]
+        .iter()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    matches!(prefix.as_str(), "b" | "br" | "rb")
+        && matches!(chars.get(pos + prefix.len()), Some('\'' | '"'))
+}
+
+/// Reads a number starting at `pos`, and returns it with the position after
+/// it.
+fn number(chars: &[char], mut pos: usize) -> Result<(Token, usize), String> {
+    let start = pos;
+    if chars[pos] == '0' && matches!(chars.get(pos + 1), Some('x' | 'X')) {
+        pos += 2;
+        while pos < chars.len() && chars[pos].is_ascii_hexdigit() {
+            pos += 1;
+        }
+        let digits: String = chars[start + 2..pos].iter().collect();
+        let value = i64::from_str_radix(&digits, 16)
+            .map_err(|e| format!("Invalid number at position {}: {}", start, e))?;
+        unsigned_unsupported(chars, pos)?;
+        return Ok((Token::Int(value), pos));
+    }
+    let mut is_double = false;
+    while pos < chars.len() && chars[pos].is_ascii_digit() {
+        pos += 1;
+    }
+    if chars.get(pos) == Some(&'.') && chars.get(pos + 1).is_some_and(char::is_ascii_digit) {
+        is_double = true;
+        pos += 1;
+        while pos < chars.len() && chars[pos].is_ascii_digit() {
+            pos += 1;
+        }
+    }
+    if matches!(chars.get(pos), Some('e' | 'E')) {
+        is_double = true;
+        pos += 1;
+        if matches!(chars.get(pos), Some('+' | '-')) {
+            pos += 1;
+        }
+        while pos < chars.len() && chars[pos].is_ascii_digit() {
+            pos += 1;
+        }
+    }
+    let literal: String = chars[start..pos].iter().collect();
+    let invalid = |e: String| format!("Invalid number `{}` at position {}: {}", literal, start, e);
+    if is_double {
+        let value = literal.parse().map_err(|e| invalid(format!("{}", e)))?;
+        Ok((Token::Double(value), pos))
+    } else {
+        let value = literal.parse().map_err(|e| invalid(format!("{}", e)))?;
+        unsigned_unsupported(chars, pos)?;
+        Ok((Token::Int(value), pos))
+    }
+}
+
+/// Returns an error if the integer literal ending at `pos` is unsigned.
+fn unsigned_unsupported(chars: &[char], pos: usize) -> Result<(), String> {
+    if matches!(chars.get(pos), Some('u' | 'U')) {
+        return Err(format!(
+            "Unsigned integers are not supported at position {}",
+            pos
+        ));
+    }
+    Ok(())
+}
+
+/// Reads a string literal whose content starts at `pos`, and returns it with
+/// the position after the closing quote.
+fn string(
+    chars: &[char],
+    mut pos: usize,
+    quote: char,
+    raw: bool,
+) -> Result<(String, usize), String> {
+    let start = pos - 1;
+    let mut value = String::new();
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c == quote {
+            return Ok((value, pos + 1));
+        }
+        if c == '\\' && !raw {
+            let escaped = chars
+                .get(pos + 1)
+                .ok_or_else(|| format!("Unterminated string at position {}", start))?;
+            value.push(match escaped {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '\\' | '\'' | '"' | '`' | '?' => *escaped,
+                _ => {
+                    return Err(format!(
+                        "Unsupported escape sequence `\\{}` at position {}",
+                        escaped, pos
+                    ))
+                }
+            });
+            pos += 2;
+        } else {
+            value.push(c);
+            pos += 1;
+        }
+    }
+    Err(format!("Unterminated string at position {}", start))
+}
+
+// ========= Parser =========
+
+/// The comprehension macros.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Macro {
+    All,
+    Exists,
+    ExistsOne,
+    Map,
+    Filter,
+}
+
+/// A CEL expression.
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Ident(String),
+    Select(Box<Expr>, String),
+    Has(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Call {
+        function: String,
+        target: Option<Box<Expr>>,
+        args: Vec<Expr>,
+    },
+    /// A `matches` call with a literal regular expression, compiled once.
+    Matches(Box<Expr>, Regex),
+    Comprehension {
+        kind: Macro,
+        range: Box<Expr>,
+        var: String,
+        args: Vec<Expr>,
+    },
+    List(Vec<Expr>),
+    Map(Vec<(Expr, Expr)>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// A recursive descent parser of CEL expressions.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Consumes the given symbol if it is the next token.
+    fn accept(&mut self, sym: &str) -> bool {
+        if matches!(self.peek(), Token::Sym(next) if *next == sym) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, sym: &str) -> Result<(), String> {
+        if self.accept(sym) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected `{}` but found {} at position {}",
+                sym,
+                self.peek().describe(),
+                self.offset()
+            ))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let condition = self.or()?;
+        if self.accept("?") {
+            let then = self.or()?;
+            self.expect(":")?;
+            let otherwise = self.expr()?;
+            return Ok(Expr::Ternary(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+        Ok(condition)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.accept("||") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.relation()?;
+        while self.accept("&&") {
+            left = Expr::And(Box::new(left), Box::new(self.relation()?));
+        }
+        Ok(left)
+    }
+
+    fn relation(&mut self) -> Result<Expr, String> {
+        let mut left = self.addition()?;
+        loop {
+            let op = match self.peek() {
+                Token::Sym(op @ ("<" | "<=" | ">" | ">=" | "==" | "!=")) => *op,
+                Token::Ident(name) if name == "in" => "in",
+                _ => return Ok(left),
+            };
+            _ = self.next();
+            left = Expr::Binary(op, Box::new(left), Box::new(self.addition()?));
+        }
+    }
+
+    fn addition(&mut self) -> Result<Expr, String> {
+        let mut left = self.multiplication()?;
+        while let Token::Sym(op @ ("+" | "-")) = self.peek() {
+            let op = *op;
+            _ = self.next();
+            left = Expr::Binary(op, Box::new(left), Box::new(self.multiplication()?));
+        }
+        Ok(left)
+    }
+
+    fn multiplication(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Token::Sym(op @ ("*" | "/" | "%")) = self.peek() {
+            let op = *op;
+            _ = self.next();
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.accept("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.accept("-") {
+            return Ok(match self.unary()? {
+                Expr::Literal(Value::Number(number)) => {
+                    Expr::Literal(negate(&number).map_err(|e| e.to_owned())?)
+                }
+                operand => Expr::Neg(Box::new(operand)),
+            });
+        }
+        self.member()
+    }
+
+    fn member(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.accept(".") {
+                let offset = self.offset();
+                let name = match self.next() {
+                    Token::Ident(name) => name,
+                    token => {
+                        return Err(format!(
+                            "Expected a field name but found {} at position {}",
+                            token.describe(),
+                            offset
+                        ))
+                    }
+                };
+                if self.accept("(") {
+                    let args = self.args(")")?;
+                    expr = method_call(expr, name, args, offset)?;
+                } else {
+                    expr = Expr::Select(Box::new(expr), name);
+                }
+            } else if self.accept("[") {
+                let index = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let offset = self.offset();
+        match self.next() {
+            Token::Int(value) => Ok(Expr::Literal(Value::from(value))),
+            Token::Double(value) => Ok(Expr::Literal(Value::from(value))),
+            Token::Str(value) => Ok(Expr::Literal(Value::String(value))),
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if RESERVED_WORDS.contains(&name.as_str()) || name == "in" => Err(format!(
+                    "The reserved word `{}` can't be used at position {}",
+                    name, offset
+                )),
+                _ if self.accept("(") => {
+                    let args = self.args(")")?;
+                    global_call(name, args, offset)
+                }
+                _ => Ok(Expr::Ident(name)),
+            },
+            Token::Sym("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Sym("[") => Ok(Expr::List(self.args("]")?)),
+            Token::Sym("{") => {
+                let mut entries = vec![];
+                if !self.accept("}") {
+                    loop {
+                        let key = self.expr()?;
+                        self.expect(":")?;
+                        entries.push((key, self.expr()?));
+                        if self.accept("}") {
+                            break;
+                        }
+                        self.expect(",")?;
+                        // A trailing comma is allowed.
+                        if self.accept("}") {
+                            break;
+                        }
+                    }
+                }
+                Ok(Expr::Map(entries))
+            }
+            token => Err(format!(
+                "Unexpected {} at position {}",
+                token.describe(),
+                offset
+            )),
+        }
+    }
+
+    /// Parses a comma-separated list of expressions up to the closing symbol.
+    fn args(&mut self, close: &str) -> Result<Vec<Expr>, String> {
+        let mut args = vec![];
+        if self.accept(close) {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expr()?);
+            if self.accept(close) {
+                return Ok(args);
+            }
+            self.expect(",")?;
+            // A trailing comma is allowed.
+            if self.accept(close) {
+                return Ok(args);
+            }
+        }
+    }
+}
+
+/// Builds a global function call, or a `has` macro.
+fn global_call(function: String, mut args: Vec<Expr>, offset: usize) -> Result<Expr, String> {
+    match function.as_str() {
+        "has" => match (args.pop(), args.is_empty()) {
+            (Some(Expr::Select(operand, field)), true) => Ok(Expr::Has(operand, field)),
+            _ => Err(format!(
+                "`has` expects a single field selection (e.g. `has(a.b)`) at position {}",
+                offset
+            )),
+        },
+        "matches" if args.len() == 2 => {
+            let pattern = args.pop().unwrap_or(Expr::Literal(Value::Null));
+            let text = args.pop().unwrap_or(Expr::Literal(Value::Null));
+            method_call(text, function, vec![pattern], offset)
+        }
+        _ => {
+            check_function(&function, false, args.len(), offset)?;
+            Ok(Expr::Call {
+                function,
+                target: None,
+                args,
+            })
+        }
+    }
+}
+
+/// Builds a method call, a comprehension macro, or a `matches` call with a
+/// precompiled regular expression.
+fn method_call(
+    target: Expr,
+    function: String,
+    mut args: Vec<Expr>,
+    offset: usize,
+) -> Result<Expr, String> {
+    let kind = match function.as_str() {
+        "all" => Some(Macro::All),
+        "exists" => Some(Macro::Exists),
+        "exists_one" => Some(Macro::ExistsOne),
+        "map" => Some(Macro::Map),
+        "filter" => Some(Macro::Filter),
+        _ => None,
+    };
+    if let Some(kind) = kind {
+        let arity_ok = match kind {
+            Macro::Map => args.len() == 2 || args.len() == 3,
+            _ => args.len() == 2,
+        };
+        return match (arity_ok, args.first()) {
+            (true, Some(Expr::Ident(var))) => {
+                let var = var.clone();
+                _ = args.remove(0);
+                Ok(Expr::Comprehension {
+                    kind,
+                    range: Box::new(target),
+                    var,
+                    args,
+                })
+            }
+            _ => Err(format!(
+                "`{}` expects a variable name and an expression (e.g. `l.{}(x, x > 0)`) at position {}",
+                function, function, offset
+            )),
+        };
+    }
+    check_function(&function, true, args.len(), offset)?;
+    if function == "matches" {
+        if let [Expr::Literal(Value::String(pattern))] = args.as_slice() {
+            let regex = Regex::new(pattern)
+                .map_err(|e| format!("Invalid regular expression at position {}: {}", offset, e))?;
+            return Ok(Expr::Matches(Box::new(target), regex));
+        }
+    }
+    Ok(Expr::Call {
+        function,
+        target: Some(Box::new(target)),
+        args,
+    })
+}
+
+/// Returns an error if a function (or a method, with a target) isn't
+/// supported with this number of arguments.
+fn check_function(
+    function: &str,
+    method: bool,
+    arg_count: usize,
+    offset: usize,
+) -> Result<(), String> {
+    let arity = match (function, method) {
+        ("size" | "int" | "double" | "string", false) => 1,
+        ("matches", false) => 2,
+        ("size" | "lowerAscii" | "upperAscii", true) => 0,
+        ("contains" | "startsWith" | "endsWith" | "matches" | "split", true) => 1,
+        _ => {
+            return Err(format!(
+                "Unsupported {} `{}` at position {}",
+                if method { "method" } else { "function" },
+                function,
+                offset
+            ))
+        }
+    };
+    check_arity(function, arity, arg_count, offset)
+}
+
+/// Returns an error if a function doesn't have the expected number of
+/// arguments.
+fn check_arity(
+    function: &str,
+    expected: usize,
+    arg_count: usize,
+    offset: usize,
+) -> Result<(), String> {
+    if arg_count != expected {
+        return Err(format!(
+            "`{}` expects {} argument(s) but found {} at position {}",
+            function, expected, arg_count, offset
+        ));
+    }
+    Ok(())
+}
+
+// ========= Evaluation =========
+
+/// Evaluates an expression. The values of the variables are borrowed as long
+/// as possible.
+fn eval<'a>(expr: &Expr, activation: &Activation<'a>) -> Result<Cow<'a, Value>, String> {
+    match expr {
+        Expr::Literal(value) => Ok(Cow::Owned(value.clone())),
+        Expr::Ident(name) => activation
+            .lookup(name)
+            .map(Cow::Borrowed)
+            .ok_or_else(|| format!("Undeclared reference to `{}`", name)),
+        Expr::Select(operand, field) => match eval(operand, activation)? {
+            Cow::Borrowed(value) => select(value, field).map(Cow::Borrowed),
+            Cow::Owned(value) => select(&value, field).map(|value| Cow::Owned(value.clone())),
+        },
+        Expr::Has(operand, field) => match eval(operand, activation)?.as_ref() {
+            Value::Object(map) => Ok(Cow::Owned(Value::Bool(
+                map.get(field).is_some_and(|value| !value.is_null()),
+            ))),
+            value => Err(format!(
+                "`has` expects a map, found {} (`{}`)",
+                type_name(value),
+                field
+            )),
+        },
+        Expr::Index(operand, index) => {
+            let index = eval(index, activation)?;
+            match eval(operand, activation)? {
+                Cow::Borrowed(value) => at(value, &index).map(Cow::Borrowed),
+                Cow::Owned(value) => at(&value, &index).map(|value| Cow::Owned(value.clone())),
+            }
+        }
+        Expr::Call {
+            function,
+            target,
+            args,
+        } => {
+            let target = match target {
+                Some(target) => Some(eval(target, activation)?),
+                None => None,
+            };
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, activation))
+                .collect::<Result<Vec<_>, _>>()?;
+            call(function, target.as_deref(), &args).map(Cow::Owned)
+        }
+        Expr::Matches(target, regex) => match eval(target, activation)?.as_ref() {
+            Value::String(text) => Ok(Cow::Owned(Value::Bool(regex.is_match(text)))),
+            value => Err(no_overload("matches", &[value])),
+        },
+        Expr::Comprehension {
+            kind,
+            range,
+            var,
+            args,
+        } => match eval(range, activation)? {
+            Cow::Borrowed(range) => comprehension(*kind, range, var, args, activation),
+            Cow::Owned(range) => comprehension(*kind, &range, var, args, activation),
+        }
+        .map(Cow::Owned),
+        Expr::List(items) => Ok(Cow::Owned(Value::Array(
+            items
+                .iter()
+                .map(|item| eval(item, activation).map(Cow::into_owned))
+                .collect::<Result<_, _>>()?,
+        ))),
+        Expr::Map(entries) => {
+            let mut map = Map::new();
+            for (key, value) in entries {
+                let key = match eval(key, activation)?.into_owned() {
+                    Value::String(key) => key,
+                    key => {
+                        return Err(format!(
+                            "The map keys must be strings, found {}",
+                            type_name(&key)
+                        ))
+                    }
+                };
+                _ = map.insert(key, eval(value, activation)?.into_owned());
+            }
+            Ok(Cow::Owned(Value::Object(map)))
+        }
+        Expr::Not(operand) => match eval(operand, activation)?.as_ref() {
+            Value::Bool(value) => Ok(Cow::Owned(Value::Bool(!value))),
+            value => Err(no_overload("!", &[value])),
+        },
+        Expr::Neg(operand) => match eval(operand, activation)?.as_ref() {
+            Value::Number(number) => negate(number).map(Cow::Owned).map_err(str::to_owned),
+            value => Err(no_overload("-", &[value])),
+        },
+        Expr::Binary(op, left, right) => {
+            let left = eval(left, activation)?;
+            let right = eval(right, activation)?;
+            binary(op, &left, &right).map(Cow::Owned)
+        }
+        Expr::And(left, right) => logical(false, left, right, activation),
+        Expr::Or(left, right) => logical(true, left, right, activation),
+        Expr::Ternary(condition, then, otherwise) => match eval(condition, activation)?.as_ref() {
+            Value::Bool(true) => eval(then, activation),
+            Value::Bool(false) => eval(otherwise, activation),
+            value => Err(format!(
+                "The condition of `? :` must be a bool, found {}",
+                type_name(value)
+            )),
+        },
+    }
+}
+
+/// Evaluates `&&` (`short_circuit` = false) or `||` (`short_circuit` =
+/// true). An error of one operand is absorbed if the other one is
+/// `short_circuit`.
+fn logical<'a>(
+    short_circuit: bool,
+    left: &Expr,
+    right: &Expr,
+    activation: &Activation<'a>,
+) -> Result<Cow<'a, Value>, String> {
+    let op = if short_circuit { "||" } else { "&&" };
+    let as_bool = |value: Result<Cow<'_, Value>, String>| match value {
+        Ok(value) => match value.as_ref() {
+            Value::Bool(value) => Ok(*value),
+            value => Err(no_overload(op, &[value])),
+        },
+        Err(e) => Err(e),
+    };
+    match as_bool(eval(left, activation)) {
+        Ok(value) if value == short_circuit => Ok(Cow::Owned(Value::Bool(value))),
+        Ok(_) => as_bool(eval(right, activation)).map(|value| Cow::Owned(Value::Bool(value))),
+        Err(e) => match as_bool(eval(right, activation)) {
+            Ok(value) if value == short_circuit => Ok(Cow::Owned(Value::Bool(value))),
+            _ => Err(e),
+        },
+    }
+}
+
+/// Evaluates a comprehension macro over the items of a list or the keys of a
+/// map.
+fn comprehension(
+    kind: Macro,
+    range: &Value,
+    var: &str,
+    args: &[Expr],
+    activation: &Activation<'_>,
+) -> Result<Value, String> {
+    let keys: Vec<Value>;
+    let items: &[Value] = match range {
+        Value::Array(items) => items,
+        Value::Object(map) => {
+            keys = map.keys().cloned().map(Value::String).collect();
+            &keys
+        }
+        value => {
+            return Err(format!(
+                "Comprehensions expect a list or a map, found {}",
+                type_name(value)
+            ))
+        }
+    };
+    let predicate = |item: &Value| -> Result<bool, String> {
+        match eval(&args[0], &activation.bind(var, item))?.as_ref() {
+            Value::Bool(value) => Ok(*value),
+            value => Err(format!(
+                "The predicate of a comprehension must be a bool, found {}",
+                type_name(value)
+            )),
+        }
+    };
+    match kind {
+        Macro::All | Macro::Exists => {
+            let expected = kind == Macro::Exists;
+            // As for `&&` and `||`, an error is absorbed if the result is
+            // determined by another item.
+            let mut error = None;
+            for item in items {
+                match predicate(item) {
+                    Ok(value) if value == expected => return Ok(Value::Bool(expected)),
+                    Ok(_) => {}
+                    Err(e) => error = error.or(Some(e)),
+                }
+            }
+            match error {
+                Some(e) => Err(e),
+                None => Ok(Value::Bool(!expected)),
+            }
+        }
+        Macro::ExistsOne => {
+            let mut count = 0;
+            for item in items {
+                if predicate(item)? {
+                    count += 1;
+                }
+            }
+            Ok(Value::Bool(count == 1))
+        }
+        Macro::Filter => {
+            let mut filtered = vec![];
+            for item in items {
+                if predicate(item)? {
+                    filtered.push(item.clone());
+                }
+            }
+            Ok(Value::Array(filtered))
+        }
+        Macro::Map => {
+            let mut mapped = vec![];
+            for item in items {
+                if args.len() == 2 && !predicate(item)? {
+                    continue;
+                }
+                let transform = &args[args.len() - 1];
+                mapped.push(eval(transform, &activation.bind(var, item))?.into_owned());
+            }
+            Ok(Value::Array(mapped))
+        }
+    }
+}
+
+/// Returns the field of a map.
+fn select<'v>(value: &'v Value, field: &str) -> Result<&'v Value, String> {
+    match value {
+        Value::Object(map) => map
+            .get(field)
+            .ok_or_else(|| format!("No such key: `{}`", field)),
+        value => Err(format!(
+            "Can't select the field `{}` of {}",
+            field,
+            type_name(value)
+        )),
+    }
+}
+
+/// Returns the item of a list or a map at the given index or key.
+fn at<'v>(value: &'v Value, index: &Value) -> Result<&'v Value, String> {
+    match (value, index) {
+        (Value::Array(items), Value::Number(number)) => number
+            .as_i64()
+            .and_then(|index| usize::try_from(index).ok())
+            .and_then(|index| items.get(index))
+            .ok_or_else(|| format!("Index out of range: {}", number)),
+        (Value::Object(map), Value::String(key)) => map
+            .get(key)
+            .ok_or_else(|| format!("No such key: `{}`", key)),
+        (value, index) => Err(no_overload("_[_]", &[value, index])),
+    }
+}
+
+/// Calls a function (with a target for the method calls).
+fn call(function: &str, target: Option<&Value>, args: &[Cow<'_, Value>]) -> Result<Value, String> {
+    let mut all_args: Vec<&Value> = target.into_iter().collect();
+    all_args.extend(args.iter().map(AsRef::as_ref));
+    match (function, all_args.as_slice()) {
+        ("size", [value]) => match value {
+            Value::String(text) => Ok(Value::from(text.chars().count())),
+            Value::Array(items) => Ok(Value::from(items.len())),
+            Value::Object(map) => Ok(Value::from(map.len())),
+            value => Err(no_overload(function, &[value])),
+        },
+        ("contains", [Value::String(text), Value::String(sub)]) => {
+            Ok(Value::Bool(text.contains(sub.as_str())))
+        }
+        ("startsWith", [Value::String(text), Value::String(prefix)]) => {
+            Ok(Value::Bool(text.starts_with(prefix.as_str())))
+        }
+        ("endsWith", [Value::String(text), Value::String(suffix)]) => {
+            Ok(Value::Bool(text.ends_with(suffix.as_str())))
+        }
+        ("matches", [Value::String(text), Value::String(pattern)]) => Regex::new(pattern)
+            .map(|regex| Value::Bool(regex.is_match(text)))
+            .map_err(|e| format!("Invalid regular expression: {}", e)),
+        ("lowerAscii", [Value::String(text)]) => Ok(Value::String(text.to_ascii_lowercase())),
+        ("upperAscii", [Value::String(text)]) => Ok(Value::String(text.to_ascii_uppercase())),
+        ("split", [Value::String(text), Value::String(separator)]) => Ok(Value::Array(
+            text.split(separator.as_str())
+                .map(|part| Value::String(part.to_owned()))
+                .collect(),
+        )),
+        ("string", [value]) => match value {
+            Value::String(_) => Ok((*value).clone()),
+            Value::Number(number) => Ok(Value::String(number.to_string())),
+            Value::Bool(value) => Ok(Value::String(value.to_string())),
+            value => Err(no_overload(function, &[value])),
+        },
+        ("int", [value]) => match value {
+            Value::Number(number) => match number.as_i64() {
+                Some(value) => Ok(Value::from(value)),
+                None => number
+                    .as_f64()
+                    .filter(|value| value.is_finite() && value.abs() < 9.2e18)
+                    .map(|value| Value::from(value.trunc() as i64))
+                    .ok_or_else(|| format!("Integer overflow: {}", number)),
+            },
+            Value::String(text) => text
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|e| format!("Can't convert `{}` to int: {}", text, e)),
+            value => Err(no_overload(function, &[value])),
+        },
+        ("double", [value]) => match value {
+            Value::Number(number) => Ok(double(number.as_f64().unwrap_or_default())),
+            Value::String(text) => text
+                .parse::<f64>()
+                .map(double)
+                .map_err(|e| format!("Can't convert `{}` to double: {}", text, e)),
+            value => Err(no_overload(function, &[value])),
+        },
+        (
+            "size" | "contains" | "startsWith" | "endsWith" | "matches" | "lowerAscii"
+            | "upperAscii" | "split" | "string" | "int" | "double",
+            args,
+        ) => Err(no_overload(function, args)),
+        _ => Err(format!("Unknown function `{}`", function)),
+    }
+}
+
+/// Evaluates a binary operator.
+fn binary(op: &str, left: &Value, right: &Value) -> Result<Value, String> {
+    match op {
+        "==" => Ok(Value::Bool(equals(left, right))),
+        "!=" => Ok(Value::Bool(!equals(left, right))),
+        "in" => match right {
+            Value::Array(items) => Ok(Value::Bool(items.iter().any(|item| equals(left, item)))),
+            Value::Object(map) => match left {
+                Value::String(key) => Ok(Value::Bool(map.contains_key(key))),
+                _ => Ok(Value::Bool(false)),
+            },
+            _ => Err(no_overload(op, &[left, right])),
+        },
+        "<" | "<=" | ">" | ">=" => {
+            let ordering = match (left, right) {
+                (Value::Number(left), Value::Number(right)) => compare_numbers(left, right),
+                (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+                (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+                _ => return Err(no_overload(op, &[left, right])),
+            };
+            let Some(ordering) = ordering else {
+                // Comparisons with NaN are false.
+                return Ok(Value::Bool(false));
+            };
+            Ok(Value::Bool(match op {
+                "<" => ordering.is_lt(),
+                "<=" => ordering.is_le(),
+                ">" => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        "+" => match (left, right) {
+            (Value::String(left), Value::String(right)) => {
+                Ok(Value::String(format!("{}{}", left, right)))
+            }
+            (Value::Array(left), Value::Array(right)) => {
+                Ok(Value::Array(left.iter().chain(right).cloned().collect()))
+            }
+            (Value::Number(l), Value::Number(r)) => arithmetic(op, l, r),
+            _ => Err(no_overload(op, &[left, right])),
+        },
+        "-" | "*" | "/" | "%" => match (left, right) {
+            (Value::Number(l), Value::Number(r)) => arithmetic(op, l, r),
+            _ => Err(no_overload(op, &[left, right])),
+        },
+        _ => Err(format!("Unknown operator `{}`", op)),
+    }
+}
+
+/// Evaluates an arithmetic operator. The operations on two integers are
+/// checked, those on two doubles are computed as is, and as in CEL, the
+/// integers and doubles can't be mixed.
+fn arithmetic(op: &str, left: &Number, right: &Number) -> Result<Value, String> {
+    if left.is_f64() != right.is_f64() {
+        return Err(no_overload(
+            op,
+            &[&Value::Number(left.clone()), &Value::Number(right.clone())],
+        ));
+    }
+    if let (Some(l), Some(r)) = (left.as_i64(), right.as_i64()) {
+        let result = match op {
+            "+" => l.checked_add(r),
+            "-" => l.checked_sub(r),
+            "*" => l.checked_mul(r),
+            "/" if r == 0 => return Err("Division by zero".to_owned()),
+            "/" => l.checked_div(r),
+            "%" if r == 0 => return Err("Modulus by zero".to_owned()),
+            _ => l.checked_rem(r),
+        };
+        return result
+            .map(Value::from)
+            .ok_or_else(|| format!("Integer overflow: {} {} {}", l, op, r));
+    }
+    let (l, r) = (
+        left.as_f64().unwrap_or_default(),
+        right.as_f64().unwrap_or_default(),
+    );
+    match op {
+        "+" => Ok(double(l + r)),
+        "-" => Ok(double(l - r)),
+        "*" => Ok(double(l * r)),
+        "/" => Ok(double(l / r)),
+        _ => Err(no_overload(op, &[&double(l), &double(r)])),
+    }
+}
+
+/// Negates a number.
+fn negate(number: &Number) -> Result<Value, &'static str> {
+    match number.as_i64() {
+        Some(value) => value
+            .checked_neg()
+            .map(Value::from)
+            .ok_or("Integer overflow"),
+        None => Ok(double(-number.as_f64().unwrap_or_default())),
+    }
+}
+
+/// Returns a double value (`null` for the non-finite numbers, which JSON
+/// doesn't support).
+fn double(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+/// Compares two numbers, integers or doubles.
+fn compare_numbers(left: &Number, right: &Number) -> Option<std::cmp::Ordering> {
+    match (left.as_i64(), right.as_i64()) {
+        (Some(left), Some(right)) => Some(left.cmp(&right)),
+        _ => left
+            .as_f64()
+            .unwrap_or_default()
+            .partial_cmp(&right.as_f64().unwrap_or_default()),
+    }
+}
+
+/// Returns true if two values are equal, the numbers being compared by value
+/// whatever their type.
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => {
+            compare_numbers(left, right) == Some(std::cmp::Ordering::Equal)
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| equals(l, r))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, l)| right.get(key).is_some_and(|r| equals(l, r)))
+        }
+        _ => left == right,
+    }
+}
+
+/// Returns the CEL type name of a value.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(number) if number.is_f64() => "double",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "map",
+    }
+}
+
+/// Returns the error of a function or operator applied to unsupported types.
+fn no_overload(function: &str, args: &[&Value]) -> String {
+    format!(
+        "No matching overload for `{}` applied to ({})",
+        function,
+        args.iter()
+            .map(|arg| type_name(arg))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn eval_with(source: &str, input: &Value) -> Result<Value, String> {
+        Program::compile(source)?.eval(&Activation::new("input", input))
+    }
+
+    fn eval_str(source: &str) -> Result<Value, String> {
+        eval_with(source, &Value::Null)
+    }
+
+    #[test]
+    fn test_literals_and_operators() {
+        for (source, expected) in [
+            ("1 + 2 * 3", json!(7)),
+            ("(1 + 2) * 3", json!(9)),
+            ("7 / 2", json!(3)),
+            ("7 % 4", json!(3)),
+            ("1.5 * 2.0", json!(3.0)),
+            ("-3 + 1", json!(-2)),
+            ("0x10", json!(16)),
+            ("1e3", json!(1000.0)),
+            ("'a' + \"b\"", json!("ab")),
+            (r#""a\tb""#, json!("a\tb")),
+            (r"r'a\.b'", json!("a\\.b")),
+            ("[1, 2] + [3]", json!([1, 2, 3])),
+            ("{'a': 1, 'b': [true]}", json!({"a": 1, "b": [true]})),
+            ("1 == 1.0", json!(true)),
+            ("'a' < 'b' && 2 >= 2", json!(true)),
+            ("!(1 > 2) || false", json!(true)),
+            ("2 in [1, 2]", json!(true)),
+            ("'c' in {'a': 1}", json!(false)),
+            ("1 > 2 ? 'x' : 'y'", json!("y")),
+            ("null == null", json!(true)),
+            ("[1, [2]] == [1, [2.0]]", json!(true)),
+        ] {
+            assert_eq!(eval_str(source).unwrap(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_functions_and_macros() {
+        let input = json!({
+            "groups": [
+                {"id": "registry.http", "attributes": [{"id": "http.method"}, {"id": "http.Route"}]},
+                {"id": "registry.dns", "attributes": [{"id": "dns.question.name", "deprecated": null}]},
+            ]
+        });
+        for (source, expected) in [
+            ("size(input.groups)", json!(2)),
+            ("input.groups[0].id.size()", json!(13)),
+            ("input.groups[1]['id']", json!("registry.dns")),
+            (
+                "input.groups.map(g, g.id)",
+                json!(["registry.http", "registry.dns"]),
+            ),
+            (
+                "input.groups.map(g, size(g.attributes) > 1, g.id)",
+                json!(["registry.http"]),
+            ),
+            (
+                "input.groups.filter(g, g.id.endsWith('dns')).size()",
+                json!(1),
+            ),
+            (
+                "input.groups.all(g, g.id.startsWith('registry.'))",
+                json!(true),
+            ),
+            ("input.groups.exists(g, g.id.contains('dns'))", json!(true)),
+            (
+                "input.groups.exists_one(g, g.id.contains('registry'))",
+                json!(false),
+            ),
+            (
+                "input.groups[0].attributes.all(a, a.id.matches('^[a-z]+(\\\\.[a-z_]+)*$'))",
+                json!(false),
+            ),
+            ("matches('http.route', r'^[a-z.]+$')", json!(true)),
+            (
+                "has(input.groups[1].attributes[0].deprecated)",
+                json!(false),
+            ),
+            ("has(input.groups[0].id)", json!(true)),
+            ("{'a': 1, 'b': 2}.all(k, k in ['a', 'b'])", json!(true)),
+            ("'A.b'.lowerAscii() + 'c'.upperAscii()", json!("a.bC")),
+            ("'a.b.c'.split('.')", json!(["a", "b", "c"])),
+            ("int('42') + int(2.7)", json!(44)),
+            ("string(42) + string(true)", json!("42true")),
+            ("double(1) / 2.0", json!(0.5)),
+        ] {
+            assert_eq!(eval_with(source, &input).unwrap(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let input = json!({"groups": [{"id": "a"}]});
+        // Errors absorbed by `&&`, `||`, and the `exists`/`all` macros.
+        for (source, expected) in [
+            ("input.groups[0].brief == 'x' || true", json!(true)),
+            ("false && input.unknown", json!(false)),
+            ("input.groups[0].brief == 'x' && false", json!(false)),
+            ("[1, 'a'].exists(x, x > 0)", json!(true)),
+        ] {
+            assert_eq!(eval_with(source, &input).unwrap(), expected, "{}", source);
+        }
+
+        for (source, error) in [
+            ("input.groups[0].brief", "No such key: `brief`"),
+            ("input.groups[3]", "Index out of range: 3"),
+            ("unknown", "Undeclared reference to `unknown`"),
+            ("1 / 0", "Division by zero"),
+            (
+                "1 + 'a'",
+                "No matching overload for `+` applied to (int, string)",
+            ),
+            (
+                "size(1)",
+                "No matching overload for `size` applied to (int)",
+            ),
+            (
+                "1 ? 2 : 3",
+                "The condition of `? :` must be a bool, found int",
+            ),
+            (
+                "9223372036854775807 + 1",
+                "Integer overflow: 9223372036854775807 + 1",
+            ),
+        ] {
+            let observed = eval_with(source, &input).unwrap_err();
+            assert!(observed.starts_with(error), "{}: {}", source, observed);
+        }
+
+        for (source, error) in [
+            ("1 +", "Unexpected end of expression at position 3"),
+            (
+                "(1",
+                "Expected `)` but found end of expression at position 2",
+            ),
+            ("'abc", "Unterminated string at position 0"),
+            ("a # b", "Unexpected character `#` at position 2"),
+            ("has(a)", "`has` expects a single field selection"),
+            (
+                "l.all(1, true)",
+                "`all` expects a variable name and an expression",
+            ),
+            ("a.matches('(')", "Invalid regular expression at position 2"),
+            ("foo(1)", "Unsupported function `foo` at position 0"),
+            ("1 2", "Unexpected integer `2` at position 2"),
+        ] {
+            let observed = Program::compile(source).unwrap_err();
+            assert!(observed.starts_with(error), "{}: {}", source, observed);
+        }
+    }
+
+    /// Cases of the supported subset taken from the conformance tests of the
+    /// CEL specification (`tests/simple/testdata`), with the results expected
+    /// by the specification.
+    #[test]
+    fn test_conformance() {
+        for (source, expected) in [
+            // basic.textproto
+            ("-(-42)", json!(42)),
+            ("1.0e-2", json!(0.01)),
+            (r#""\"""#, json!("\"")),
+            (r#"r"\n""#, json!("\\n")),
+            ("{}", json!({})),
+            ("[]", json!([])),
+            // comparisons.textproto
+            ("1 == 1", json!(true)),
+            ("1 != 2", json!(true)),
+            ("1.0 == 1", json!(true)),
+            ("1 < 2.5", json!(true)),
+            ("-1 < 0", json!(true)),
+            ("'a' < 'b'", json!(true)),
+            ("'abc' < 'abd'", json!(true)),
+            ("'' < 'a'", json!(true)),
+            ("false < true", json!(true)),
+            ("[1, 2] == [1, 2]", json!(true)),
+            ("[1, 2] != [2, 1]", json!(true)),
+            ("{'k': 'v'} == {'k': 'v'}", json!(true)),
+            ("{'k': 1} == {'k': 1.0}", json!(true)),
+            ("1 == 'a'", json!(false)),
+            ("null != 1", json!(true)),
+            ("2 in {'a': 1}", json!(false)),
+            ("'a' in {'a': 1}", json!(true)),
+            ("[] == []", json!(true)),
+            // integer_math.textproto
+            ("-7 / 2", json!(-3)),
+            ("-7 % 2", json!(-1)),
+            ("7 % -2", json!(1)),
+            ("2 * 3 + 4", json!(10)),
+            // fp_math.textproto
+            ("2.5 + 1.5", json!(4.0)),
+            ("-2.5 * 2.0", json!(-5.0)),
+            ("1.0 / 4.0", json!(0.25)),
+            // logic.textproto
+            ("true && false", json!(false)),
+            ("false || true", json!(true)),
+            ("!false", json!(true)),
+            ("true ? 1 : 2", json!(1)),
+            ("false ? 1 : true ? 2 : 3", json!(2)),
+            ("1 / 0 == 1 || true", json!(true)),
+            ("true || 1 / 0 == 1", json!(true)),
+            ("false && 1 / 0 == 1", json!(false)),
+            ("1 / 0 == 1 && false", json!(false)),
+            // macros.textproto
+            ("[1, 2, 3].all(x, x > 0)", json!(true)),
+            ("[].all(x, x > 0)", json!(true)),
+            ("[].exists(x, x > 0)", json!(false)),
+            ("[1, 2, 3].exists(x, x == 2)", json!(true)),
+            ("[1, 2, 3].exists_one(x, x > 2)", json!(true)),
+            ("[1, 2, 3].exists_one(x, x > 1)", json!(false)),
+            ("[1, 2, 3].map(x, x * 2)", json!([2, 4, 6])),
+            ("[1, 2, 3].map(x, x > 1, x)", json!([2, 3])),
+            ("[1, 2, 3].filter(x, x > 1)", json!([2, 3])),
+            ("{'a': 1, 'b': 2}.exists(k, k == 'b')", json!(true)),
+            ("[1, 0].exists(x, 1 / x == 1)", json!(true)),
+            ("has({'a': 1}.a)", json!(true)),
+            ("has({'a': 1}.b)", json!(false)),
+            // string.textproto
+            ("size('')", json!(0)),
+            ("size('hello')", json!(5)),
+            ("'héllo'.size()", json!(5)),
+            ("'hello'.contains('')", json!(true)),
+            ("'hello'.contains('ell')", json!(true)),
+            ("'hello'.startsWith('he')", json!(true)),
+            ("'hello'.endsWith('lo')", json!(true)),
+            ("'hubba'.matches('ubb')", json!(true)),
+            ("matches('hubba', '^ubb')", json!(false)),
+            ("'a' + 'b' == 'ab'", json!(true)),
+            // lists.textproto
+            ("[1, 2] + [3, 4]", json!([1, 2, 3, 4])),
+            ("[1, 2, 3][1]", json!(2)),
+            ("size([1, 2, 3])", json!(3)),
+            ("3 in [1, 2, 3]", json!(true)),
+            ("{'a': [1]}['a'][0]", json!(1)),
+            // conversions.textproto
+            ("int('-42')", json!(-42)),
+            ("int(-2.9)", json!(-2)),
+            ("double('1e3')", json!(1000.0)),
+            ("double(-1)", json!(-1.0)),
+            ("string(-42)", json!("-42")),
+            ("string('abc')", json!("abc")),
+            ("string(false)", json!("false")),
+        ] {
+            assert_eq!(eval_str(source), Ok(expected), "{}", source);
+        }
+
+        for source in [
+            // The mixed integer and double arithmetic has no overload.
+            "1 + 1.0",
+            "2.0 * 3",
+            "5.0 % 2.0",
+            // The strings can't be indexed.
+            "'abc'[0]",
+            "[1, 2][-1]",
+            "[1, 2][2]",
+            "{'a': 1}.b",
+            "null < 1",
+            "-9223372036854775807 - 2",
+            "int('1.5')",
+            "1 / 0 == 1 && true",
+            "[1, 2].exists_one(x, 1 / (x - 1) > 0)",
+        ] {
+            assert!(eval_str(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_unsupported() {
+        for (source, error) in [
+            ("1u", "Unsigned integers are not supported"),
+            ("0x1U + 1", "Unsigned integers are not supported"),
+            ("b'abc'", "Bytes literals are not supported"),
+            ("rb'abc'", "Bytes literals are not supported"),
+            ("'''abc'''", "Triple-quoted strings are not supported"),
+            (r#""\101""#, r"Unsupported escape sequence `\1`"),
+            (r"'\x41'", r"Unsupported escape sequence `\x`"),
+            (r"'\u0041'", r"Unsupported escape sequence `\u`"),
+            ("uint(1)", "Unsupported function `uint`"),
+            ("bytes('a')", "Unsupported function `bytes`"),
+            ("type(1) == int", "Unsupported function `type`"),
+            ("dyn(1)", "Unsupported function `dyn`"),
+            (
+                "timestamp('2024-01-01T00:00:00Z')",
+                "Unsupported function `timestamp`",
+            ),
+            ("duration('1s')", "Unsupported function `duration`"),
+            ("contains('abc', 'b')", "Unsupported function `contains`"),
+            ("'a'.int()", "Unsupported method `int`"),
+            ("[1].sort()", "Unsupported method `sort`"),
+            ("size('a', 'b')", "`size` expects 1 argument(s) but found 2"),
+            ("'a'.size(1)", "`size` expects 0 argument(s) but found 1"),
+            (
+                "'a'.startsWith()",
+                "`startsWith` expects 1 argument(s) but found 0",
+            ),
+            ("google.protobuf.Int64Value{value: 1}", "Unexpected `{`"),
+            ("var", "The reserved word `var` can't be used"),
+            ("[1].all(if, true)", "The reserved word `if` can't be used"),
+            ("in", "The reserved word `in` can't be used"),
+        ] {
+            let observed = Program::compile(source).unwrap_err();
+            assert!(observed.starts_with(error), "{}: {}", source, observed);
+        }
+    }
+}
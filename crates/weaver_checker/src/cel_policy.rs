@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! CEL policies, an alternative to the Rego policies.
+//!
+//! A CEL policy file (`*.cel.yaml` or `*.cel.yml`) defines the rules of a
+//! policy stage. Each rule is a CEL expression returning `true` when the
+//! checked element violates the rule:
+//!
+//! ```yaml
+//! stage: before_resolution
+//! rules:
+//!   - id: attr_stability_deprecated
+//!     category: attribute
+//!     scope: attribute
+//!     deny: >
+//!       has(attribute.deprecated) && has(attribute.stability)
+//!       && attribute.stability != 'deprecated'
+//! ```
+//!
+//! The `scope` of a rule defines the elements it is evaluated on:
+//! - `registry`: the rule is evaluated once,
+//! - `group` (default): the rule is evaluated on each group (bound to the
+//!   `group` variable),
+//! - `attribute`: the rule is evaluated on each attribute of each group
+//!   (bound to the `group` and `attribute` variables).
+//!
+//! The groups are `input.groups` by default, the optional `groups` field of a
+//! rule defines another CEL expression returning the groups to check (e.g.
+//! `data.groups`). The `input` and `data` variables are always defined.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::cel::{Activation, Program};
+use crate::violation::{Severity, Violation};
+use crate::{Error, PolicyStage};
+
+/// The groups checked by default.
+const DEFAULT_GROUPS: &str = "has(input.groups) ? input.groups : []";
+
+/// Returns true if the path is a CEL policy file.
+pub(crate) fn is_cel_policy_file(path: &str) -> bool {
+    path.ends_with(".cel.yaml") || path.ends_with(".cel.yml")
+}
+
+/// The content of a CEL policy file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CelPolicyFile {
+    /// The stage of the rules.
+    stage: PolicyStage,
+    /// The rules of the policy.
+    rules: Vec<CelRuleSpec>,
+}
+
+/// A rule of a CEL policy file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CelRuleSpec {
+    /// The id of the violations.
+    id: String,
+    /// The category of the violations.
+    category: String,
+    /// The severity of the violations.
+    #[serde(default)]
+    severity: Severity,
+    /// The elements the rule is evaluated on.
+    #[serde(default)]
+    scope: RuleScope,
+    /// A CEL expression returning the groups to check.
+    groups: Option<String>,
+    /// A CEL expression returning true if the rule is violated.
+    deny: String,
+}
+
+/// The elements a rule is evaluated on.
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum RuleScope {
+    /// The rule is evaluated once.
+    Registry,
+    /// The rule is evaluated on each group.
+    #[default]
+    Group,
+    /// The rule is evaluated on each attribute of each group.
+    Attribute,
+}
+
+/// A compiled rule.
+#[derive(Debug, Clone)]
+struct CelRule {
+    id: String,
+    category: String,
+    severity: Severity,
+    scope: RuleScope,
+    groups: Program,
+    deny: Program,
+}
+
+/// A compiled CEL policy.
+#[derive(Debug, Clone)]
+pub(crate) struct CelPolicy {
+    /// The path of the policy (used for error messages).
+    path: String,
+    /// The stage of the rules.
+    pub(crate) stage: PolicyStage,
+    rules: Vec<CelRule>,
+}
+
+impl CelPolicy {
+    /// Parses and compiles a CEL policy.
+    pub(crate) fn parse(path: &str, content: &str) -> Result<Self, Error> {
+        let invalid = |error: String| Error::InvalidPolicyFile {
+            file: path.to_owned(),
+            error,
+        };
+        let file: CelPolicyFile =
+            serde_yaml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+        let compile = |rule: &str, field: &str, source: &str| {
+            Program::compile(source)
+                .map_err(|e| invalid(format!("rule `{}`, `{}` expression: {}", rule, field, e)))
+        };
+        let rules = file
+            .rules
+            .into_iter()
+            .map(|rule| {
+                if rule.scope == RuleScope::Registry && rule.groups.is_some() {
+                    return Err(invalid(format!(
+                        "rule `{}`: the `groups` field is not supported by the `registry` scope",
+                        rule.id
+                    )));
+                }
+                Ok(CelRule {
+                    groups: compile(
+                        &rule.id,
+                        "groups",
+                        rule.groups.as_deref().unwrap_or(DEFAULT_GROUPS),
+                    )?,
+                    deny: compile(&rule.id, "deny", &rule.deny)?,
+                    id: rule.id,
+                    category: rule.category,
+                    severity: rule.severity,
+                    scope: rule.scope,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            path: path.to_owned(),
+            stage: file.stage,
+            rules,
+        })
+    }
+
+    /// Evaluates the rules and returns the violations.
+    pub(crate) fn check(&self, input: &Value, data: &Value) -> Result<Vec<Violation>, Error> {
+        let root = Activation::new("input", input);
+        let root = root.bind("data", data);
+        let mut violations = vec![];
+        for rule in &self.rules {
+            let failed = |error: String| Error::ViolationEvaluationError {
+                error: format!("{} (rule `{}` of `{}`)", error, rule.id, self.path),
+            };
+            let denied = |activation: &Activation<'_>| match rule.deny.eval(activation) {
+                Ok(Value::Bool(denied)) => Ok(denied),
+                Ok(value) => Err(failed(format!(
+                    "the `deny` expression returned `{}` instead of a bool",
+                    value
+                ))),
+                Err(e) => Err(failed(e)),
+            };
+            let violation = |group: &Value, attr: &Value| Violation::SemconvAttribute {
+                id: rule.id.clone(),
+                category: rule.category.clone(),
+                group: string_field(group, &["id"]),
+                attr: string_field(attr, &["id", "name", "ref"]),
+                severity: rule.severity,
+                fix: None,
+            };
+
+            if rule.scope == RuleScope::Registry {
+                if denied(&root)? {
+                    violations.push(violation(&Value::Null, &Value::Null));
+                }
+                continue;
+            }
+            let groups = match rule.groups.eval(&root).map_err(failed)? {
+                Value::Array(groups) => groups,
+                value => {
+                    return Err(failed(format!(
+                        "the `groups` expression returned `{}` instead of a list",
+                        value
+                    )))
+                }
+            };
+            for group in &groups {
+                let activation = root.bind("group", group);
+                if rule.scope == RuleScope::Group {
+                    if denied(&activation)? {
+                        violations.push(violation(group, &Value::Null));
+                    }
+                    continue;
+                }
+                let Some(Value::Array(attributes)) = group.get("attributes") else {
+                    continue;
+                };
+                for attribute in attributes {
+                    if denied(&activation.bind("attribute", attribute))? {
+                        violations.push(violation(group, attribute));
+                    }
+                }
+            }
+        }
+        Ok(violations)
+    }
+}
+
+/// Returns the first string field of a value among the given names, or an
+/// empty string.
+pub(crate) fn string_field(value: &Value, names: &[&str]) -> String {
+    names
+        .iter()
+        .find_map(|name| value.get(name).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_cel_policy() {
+        let policy = CelPolicy::parse(
+            "test.cel.yaml",
+            r#"
+stage: after_resolution
+rules:
+  - id: too_many_groups
+    category: registry
+    scope: registry
+    deny: size(input.groups) > 1
+  - id: missing_brief
+    category: group
+    deny: "!has(group.brief)"
+  - id: uppercase_attr
+    category: attribute
+    scope: attribute
+    deny: attribute.name.lowerAscii() != attribute.name
+  - id: group_removed
+    category: schema_evolution
+    groups: data.groups
+    deny: "!input.groups.exists(g, g.id == group.id)"
+"#,
+        )
+        .unwrap();
+        assert_eq!(policy.stage, PolicyStage::AfterResolution);
+
+        let input = json!({"groups": [
+            {"id": "a", "brief": "A", "attributes": [{"name": "a.X"}, {"name": "a.y"}]},
+            {"id": "b", "attributes": []},
+        ]});
+        let data = json!({"groups": [{"id": "b"}, {"id": "c"}]});
+        let violations: Vec<_> = policy
+            .check(&input, &data)
+            .unwrap()
+            .into_iter()
+            .map(
+                |Violation::SemconvAttribute {
+                     id, group, attr, ..
+                 }| (id, group, attr),
+            )
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                ("too_many_groups".to_owned(), "".to_owned(), "".to_owned()),
+                ("missing_brief".to_owned(), "b".to_owned(), "".to_owned()),
+                (
+                    "uppercase_attr".to_owned(),
+                    "a".to_owned(),
+                    "a.X".to_owned()
+                ),
+                ("group_removed".to_owned(), "c".to_owned(), "".to_owned()),
+            ]
+        );
+
+        // Without groups, the default scope checks nothing.
+        assert!(policy.rules[1..3].iter().all(|rule| rule
+            .groups
+            .eval(&Activation::new("input", &json!({})))
+            .unwrap()
+            == json!([])));
+    }
+
+    #[test]
+    fn test_invalid_cel_policy() {
+        for (content, error) in [
+            ("stage: unknown\nrules: []", "stage: unknown variant `unknown`"),
+            (
+                "stage: before_resolution\nrules:\n- {id: a, category: b, deny: '1 +'}",
+                "rule `a`, `deny` expression: Unexpected end of expression at position 3",
+            ),
+            (
+                "stage: before_resolution\nrules:\n- {id: a, category: b, scope: registry, groups: '[]', deny: 'true'}",
+                "rule `a`: the `groups` field is not supported by the `registry` scope",
+            ),
+        ] {
+            match CelPolicy::parse("test.cel.yaml", content) {
+                Err(Error::InvalidPolicyFile { file, error: observed }) => {
+                    assert_eq!(file, "test.cel.yaml");
+                    assert!(observed.starts_with(error), "{}", observed);
+                }
+                result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+            }
+        }
+
+        let policy = CelPolicy::parse(
+            "test.cel.yaml",
+            "stage: before_resolution\nrules:\n- {id: a, category: b, deny: 'group.id'}",
+        )
+        .unwrap();
+        match policy.check(&json!({"groups": [{"id": "g"}]}), &Value::Null) {
+            Err(Error::ViolationEvaluationError { error }) => assert_eq!(
+                error,
+                "the `deny` expression returned `\"g\"` instead of a bool (rule `a` of `test.cel.yaml`)"
+            ),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+}
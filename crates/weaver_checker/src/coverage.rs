@@ -0,0 +1,296 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Coverage reports of the Rego policies.
+//!
+//! When the coverage is enabled, the lines of the Rego policies covered by
+//! each evaluation of a policy stage are accumulated by the engine (and its
+//! clones), so that each line gets a hit count, i.e. the number of evaluations
+//! covering it. The hit count of a rule is the highest hit count of its lines.
+//! The report can be exported in JSON, or in the LCOV format supported by most
+//! of the coverage tools and CI dashboards.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use regex::Regex;
+use serde::Serialize;
+
+/// The coverage of the Rego policies accumulated over the evaluations.
+#[derive(Debug, Default)]
+pub(crate) struct CoverageData {
+    /// The source code and the line hit counts of each policy file.
+    files: BTreeMap<String, (String, BTreeMap<u32, u64>)>,
+}
+
+impl CoverageData {
+    /// Adds the coverage of an evaluation.
+    pub(crate) fn add(&mut self, report: &regorus::coverage::Report) {
+        for file in &report.files {
+            let (_, lines) = self
+                .files
+                .entry(file.path.clone())
+                .or_insert_with(|| (file.code.clone(), BTreeMap::new()));
+            for line in &file.covered {
+                *lines.entry(*line).or_default() += 1;
+            }
+            for line in &file.not_covered {
+                _ = lines.entry(*line).or_default();
+            }
+        }
+    }
+
+    /// Returns the coverage report.
+    pub(crate) fn report(&self) -> CoverageReport {
+        CoverageReport {
+            files: self
+                .files
+                .iter()
+                .map(|(path, (code, lines))| FileCoverage {
+                    path: path.clone(),
+                    rules: rule_coverage(code, lines),
+                    lines: lines.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A coverage report of the Rego policies.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CoverageReport {
+    /// The coverage of each policy file.
+    pub files: Vec<FileCoverage>,
+}
+
+/// The coverage of a policy file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCoverage {
+    /// The path of the policy file.
+    pub path: String,
+    /// The hit counts of the executable lines (1-based).
+    pub lines: BTreeMap<u32, u64>,
+    /// The coverage of the rules, in the order of the file.
+    pub rules: Vec<RuleCoverage>,
+}
+
+/// The coverage of a rule definition.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RuleCoverage {
+    /// The name of the rule.
+    pub name: String,
+    /// The first line of the rule definition.
+    pub line: u32,
+    /// The last line of the rule definition.
+    pub end_line: u32,
+    /// The number of evaluations covering the rule.
+    pub hits: u64,
+}
+
+impl FileCoverage {
+    /// Returns the number of covered lines.
+    #[must_use]
+    pub fn covered_lines(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+}
+
+impl CoverageReport {
+    /// Returns the report in JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Returns the report in the LCOV format. The rules are reported as
+    /// functions, named `<rule>:<line>` as a rule can be defined several times.
+    #[must_use]
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for file in &self.files {
+            lcov.push_str(&format!("TN:\nSF:{}\n", file.path));
+            for rule in &file.rules {
+                lcov.push_str(&format!("FN:{},{}:{}\n", rule.line, rule.name, rule.line));
+            }
+            for rule in &file.rules {
+                lcov.push_str(&format!("FNDA:{},{}:{}\n", rule.hits, rule.name, rule.line));
+            }
+            let covered_rules = file.rules.iter().filter(|rule| rule.hits > 0).count();
+            lcov.push_str(&format!(
+                "FNF:{}\nFNH:{}\n",
+                file.rules.len(),
+                covered_rules
+            ));
+            for (line, hits) in &file.lines {
+                lcov.push_str(&format!("DA:{},{}\n", line, hits));
+            }
+            lcov.push_str(&format!(
+                "LF:{}\nLH:{}\nend_of_record\n",
+                file.lines.len(),
+                file.covered_lines()
+            ));
+        }
+        lcov
+    }
+}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for file in &self.files {
+            writeln!(
+                f,
+                "{}: {}/{} lines covered",
+                file.path,
+                file.covered_lines(),
+                file.lines.len()
+            )?;
+            for rule in &file.rules {
+                writeln!(
+                    f,
+                    "  {} (line {}): {} hits",
+                    rule.name, rule.line, rule.hits
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the coverage of the rules defined in the source code of a policy.
+///
+/// A rule definition starts with a non-indented line starting with the rule
+/// name (optionally preceded by `default`), and ends before the next
+/// non-indented statement.
+fn rule_coverage(code: &str, lines: &BTreeMap<u32, u64>) -> Vec<RuleCoverage> {
+    let head_regex =
+        Regex::new(r"^(?:default\s+)?([A-Za-z_][A-Za-z0-9_]*)").expect("Invalid rule head regex");
+    let mut rules: Vec<RuleCoverage> = vec![];
+    let mut last_line = 0;
+    for (index, text) in code.lines().enumerate() {
+        let line = u32::try_from(index + 1).unwrap_or(u32::MAX);
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let is_statement =
+            !trimmed.starts_with(char::is_whitespace) && !trimmed.starts_with(['}', ']', ')']);
+        if is_statement {
+            if let Some(rule) = rules.last_mut() {
+                if rule.end_line == 0 {
+                    rule.end_line = last_line;
+                }
+            }
+            if let Some(captures) = head_regex.captures(trimmed) {
+                let name = &captures[1];
+                if name != "package" && name != "import" {
+                    rules.push(RuleCoverage {
+                        name: name.to_owned(),
+                        line,
+                        end_line: 0,
+                        hits: 0,
+                    });
+                }
+            }
+        }
+        last_line = line;
+    }
+    if let Some(rule) = rules.last_mut() {
+        if rule.end_line == 0 {
+            rule.end_line = last_line;
+        }
+    }
+    for rule in &mut rules {
+        rule.hits = lines
+            .range(rule.line..=rule.end_line)
+            .map(|(_, hits)| *hits)
+            .max()
+            .unwrap_or(0);
+    }
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGO: &str = r#"package before_resolution
+
+import rego.v1
+
+# A comment
+default allow := false
+
+deny contains violation if {
+    group := input.groups[_]
+    group.prefix == ""
+    violation := {"id": "empty_prefix"}
+}
+
+deny contains violation if {
+    group := input.groups[_]
+    violation := {"id": "unused"}
+}
+"#;
+
+    fn report(evaluations: &[&[u32]]) -> CoverageReport {
+        let mut data = CoverageData::default();
+        for covered in evaluations {
+            data.add(&regorus::coverage::Report {
+                files: vec![regorus::coverage::File {
+                    path: "test.rego".to_owned(),
+                    code: REGO.to_owned(),
+                    covered: covered.iter().copied().collect(),
+                    not_covered: [6, 9, 10, 11, 15, 16]
+                        .into_iter()
+                        .filter(|line| !covered.contains(line))
+                        .collect(),
+                }],
+            });
+        }
+        data.report()
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        let report = report(&[&[6, 9, 10], &[6, 9, 10, 11]]);
+        let file = &report.files[0];
+        assert_eq!(file.covered_lines(), 4);
+        assert_eq!(file.lines.get(&6), Some(&2));
+        assert_eq!(file.lines.get(&11), Some(&1));
+        assert_eq!(
+            file.rules,
+            vec![
+                RuleCoverage {
+                    name: "allow".to_owned(),
+                    line: 6,
+                    end_line: 6,
+                    hits: 2
+                },
+                RuleCoverage {
+                    name: "deny".to_owned(),
+                    line: 8,
+                    end_line: 12,
+                    hits: 2
+                },
+                RuleCoverage {
+                    name: "deny".to_owned(),
+                    line: 14,
+                    end_line: 17,
+                    hits: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lcov() {
+        let lcov = report(&[&[6, 9]]).to_lcov();
+        assert_eq!(
+            lcov,
+            "TN:\nSF:test.rego\n\
+             FN:6,allow:6\nFN:8,deny:8\nFN:14,deny:14\n\
+             FNDA:1,allow:6\nFNDA:1,deny:8\nFNDA:0,deny:14\n\
+             FNF:3\nFNH:2\n\
+             DA:6,1\nDA:9,1\nDA:10,0\nDA:11,0\nDA:15,0\nDA:16,0\n\
+             LF:6\nLH:2\nend_of_record\n"
+        );
+    }
+}
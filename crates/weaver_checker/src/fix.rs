@@ -0,0 +1,406 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Application of the fixes suggested by the policies.
+//!
+//! A policy can attach a machine-applicable fix to a violation, setting a
+//! field of the attribute of the violation (or of its group when `attr` is
+//! empty):
+//!
+//! ```rego
+//! deny contains violation if {
+//!     group := input.groups[_]
+//!     attr := group.attributes[_]
+//!     not attr.stability
+//!     violation := {
+//!         "type": "semconv_attribute",
+//!         "id": "attr_stability_missing",
+//!         "category": "attribute",
+//!         "group": group.id,
+//!         "attr": attr.id,
+//!         "fix": {"path": "stability", "value": "development"},
+//!     }
+//! }
+//! ```
+//!
+//! Only the safe fixes are applied: the path must be a single field name and
+//! the value a scalar. The spec files are edited line by line to preserve
+//! their layout and comments: the line of the field is replaced if its value
+//! holds on a single line (keeping its trailing comment), or a new line is
+//! inserted after the `id` line of the element if the field is missing. The
+//! attribute references (`ref`), the flow-style elements (e.g. `- {id: ...}`)
+//! and the fields with a multi-line value are not fixed.
+
+use regex::Regex;
+
+use crate::violation::{Fix, Violation};
+
+/// Applies the fixes of the violations to the content of a semconv YAML file,
+/// and returns the new content and the indexes of the violations whose fix
+/// has been applied.
+#[must_use]
+pub fn apply_fixes(content: &str, violations: &[Violation]) -> (String, Vec<usize>) {
+    let mut lines: Vec<String> = content.lines().map(str::to_owned).collect();
+    let mut applied = vec![];
+    for (index, violation) in violations.iter().enumerate() {
+        let Violation::SemconvAttribute {
+            group,
+            attr,
+            fix: Some(fix),
+            ..
+        } = violation
+        else {
+            continue;
+        };
+        if apply_fix(&mut lines, group, attr, fix) {
+            applied.push(index);
+        }
+    }
+    let mut new_content = lines.join("\n");
+    if content.ends_with('\n') {
+        new_content.push('\n');
+    }
+    (new_content, applied)
+}
+
+/// Applies a fix to the lines of a file, and returns true if the fix has been
+/// applied.
+fn apply_fix(lines: &mut Vec<String>, group: &str, attr: &str, fix: &Fix) -> bool {
+    let Some(value) = scalar_value(&fix.value) else {
+        return false;
+    };
+    let is_field_name = fix
+        .path
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && fix
+            .path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_field_name {
+        return false;
+    }
+
+    let Some(group_element) = find_element(lines, 0, lines.len(), group) else {
+        return false;
+    };
+    let element = if attr.is_empty() {
+        group_element
+    } else {
+        match find_element(lines, group_element.start + 1, group_element.end, attr) {
+            Some(element) => element,
+            None => return false,
+        }
+    };
+
+    let indent = " ".repeat(element.key_indent);
+    let field_prefix = format!("{}{}:", indent, fix.path);
+    let fixed_line = format!("{}{}: {}", indent, fix.path, value);
+    let Some(index) =
+        (element.start + 1..element.end).find(|index| lines[*index].starts_with(&field_prefix))
+    else {
+        lines.insert(element.start + 1, fixed_line);
+        return true;
+    };
+    let current_value = lines[index][field_prefix.len()..].trim();
+    let is_multi_line = current_value.is_empty()
+        || current_value.starts_with(['|', '>'])
+        || lines[index + 1..element.end]
+            .iter()
+            .find(|line| !is_blank(line))
+            .is_some_and(|line| indentation(line) > element.key_indent);
+    if is_multi_line {
+        return false;
+    }
+    lines[index] = match trailing_comment(current_value) {
+        Some(comment) => format!("{} {}", fixed_line, comment),
+        None => fixed_line,
+    };
+    true
+}
+
+/// The lines of a YAML sequence element.
+struct Element {
+    /// The line of the element key (e.g. `- id: ...`).
+    start: usize,
+    /// The line after the last line of the element.
+    end: usize,
+    /// The indentation of the keys of the element.
+    key_indent: usize,
+}
+
+/// Finds the block sequence element whose `id` is the given name between the
+/// lines `from` and `to`.
+fn find_element(lines: &[String], from: usize, to: usize, name: &str) -> Option<Element> {
+    let element_regex = Regex::new(&format!(
+        r#"^(\s*-\s+)id:\s*(['"]?){}(['"]?)\s*(#.*)?$"#,
+        regex::escape(name)
+    ))
+    .expect("Invalid element regex");
+    (from..to).find_map(|start| {
+        let captures = element_regex.captures(&lines[start])?;
+        if captures[2] != captures[3] {
+            return None;
+        }
+        let dash_indent = indentation(&lines[start]);
+        let end = (start + 1..to)
+            .find(|index| !is_blank(&lines[*index]) && indentation(&lines[*index]) <= dash_indent)
+            .unwrap_or(to);
+        Some(Element {
+            start,
+            end,
+            key_indent: captures[1].len(),
+        })
+    })
+}
+
+/// Returns the YAML representation of a scalar value, or `None` if the value
+/// is not a scalar or doesn't hold on a single line.
+fn scalar_value(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Bool(_) | serde_json::Value::Number(_) => Some(value.to_string()),
+        serde_json::Value::String(_) => {
+            let yaml = serde_yaml::to_string(value).ok()?;
+            let yaml = yaml.trim_end();
+            (!yaml.contains('\n')).then(|| yaml.to_owned())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the trailing comment (e.g. `# TODO`) of a single-line YAML value.
+fn trailing_comment(value: &str) -> Option<&str> {
+    let mut chars = value.char_indices().peekable();
+    // The `#` of a quoted value doesn't start a comment.
+    match chars.peek() {
+        Some((_, '"')) => {
+            _ = chars.next();
+            while let Some((_, c)) = chars.next() {
+                match c {
+                    '\\' => _ = chars.next(),
+                    '"' => break,
+                    _ => {}
+                }
+            }
+        }
+        Some((_, '\'')) => {
+            _ = chars.next();
+            while let Some((_, c)) = chars.next() {
+                // A single quote is escaped by doubling it.
+                if c == '\'' && chars.next_if(|(_, c)| *c == '\'').is_none() {
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+    let mut previous = ' ';
+    for (index, c) in chars {
+        if c == '#' && previous.is_whitespace() {
+            return Some(&value[index..]);
+        }
+        previous = c;
+    }
+    None
+}
+
+/// Returns true if the line is empty or a comment.
+fn is_blank(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty() || line.starts_with('#')
+}
+
+/// Returns the number of leading spaces of the line.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::violation::Severity;
+
+    use super::*;
+
+    const SPEC: &str = r#"groups:
+  - id: registry.http
+    type: attribute_group
+    brief: HTTP attributes.
+    attributes:
+      # The request method.
+      - id: http.request.method
+        type: string
+        brief: >
+          HTTP request method.
+      - id: http.route
+        type: string
+        stability: experimental
+        brief: The matched route.
+  - id: registry.url
+    type: attribute_group
+    brief: URL attributes.
+"#;
+
+    fn violation(group: &str, attr: &str, path: &str, value: serde_json::Value) -> Violation {
+        Violation::SemconvAttribute {
+            id: "fix".to_owned(),
+            category: "attribute".to_owned(),
+            group: group.to_owned(),
+            attr: attr.to_owned(),
+            severity: Severity::Violation,
+            fix: Some(Box::new(Fix {
+                path: path.to_owned(),
+                value,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let violations = vec![
+            // Missing field inserted.
+            violation(
+                "registry.http",
+                "http.request.method",
+                "stability",
+                json!("development"),
+            ),
+            // Single-line field replaced.
+            violation("registry.http", "http.route", "stability", json!("stable")),
+            // Group field.
+            violation("registry.url", "", "brief", json!("Fixed URL attributes.")),
+            // Multi-line field.
+            violation(
+                "registry.http",
+                "http.request.method",
+                "brief",
+                json!("Method."),
+            ),
+            // Nested path.
+            violation("registry.http", "http.route", "type.members", json!("x")),
+            // Non scalar value.
+            violation("registry.http", "http.route", "examples", json!(["a"])),
+            // Unknown attribute.
+            violation("registry.http", "url.path", "stability", json!("stable")),
+        ];
+
+        let (content, applied) = apply_fixes(SPEC, &violations);
+        assert_eq!(applied, vec![0, 1, 2]);
+        assert_eq!(
+            content,
+            r#"groups:
+  - id: registry.http
+    type: attribute_group
+    brief: HTTP attributes.
+    attributes:
+      # The request method.
+      - id: http.request.method
+        stability: development
+        type: string
+        brief: >
+          HTTP request method.
+      - id: http.route
+        type: string
+        stability: stable
+        brief: The matched route.
+  - id: registry.url
+    type: attribute_group
+    brief: Fixed URL attributes.
+"#
+        );
+
+        // The fixes are idempotent.
+        let (fixed_again, applied) = apply_fixes(&content, &violations);
+        assert_eq!(applied, vec![0, 1, 2]);
+        assert_eq!(fixed_again, content);
+    }
+
+    #[test]
+    fn test_apply_fixes_ref_and_flow_style() {
+        let spec = r#"groups:
+  - id: registry.http
+    type: attribute_group
+    brief: HTTP attributes.
+    attributes: [{id: http.route, type: string}]
+  - {id: registry.url, type: attribute_group, brief: URL attributes.}
+  - id: http.server
+    type: span
+    brief: HTTP server span.
+    attributes:
+      - ref: http.route
+        requirement_level: required
+"#;
+        let violations = vec![
+            // Flow-style attribute.
+            violation("registry.http", "http.route", "stability", json!("stable")),
+            // Flow-style group.
+            violation("registry.url", "", "stability", json!("stable")),
+            // Attribute reference.
+            violation("http.server", "http.route", "stability", json!("stable")),
+        ];
+
+        let (content, applied) = apply_fixes(spec, &violations);
+        assert!(applied.is_empty());
+        assert_eq!(content, spec);
+    }
+
+    #[test]
+    fn test_apply_fixes_with_comments() {
+        let spec = r#"groups:
+  # - id: registry.http
+  #   brief: Old HTTP attributes.
+  - id: registry.http # The HTTP attributes.
+    type: attribute_group
+    # brief: Commented brief.
+    brief: HTTP attributes. # TODO
+    attributes:
+      # - id: http.route
+      #   stability: development
+      - id: "http.route"  # The route.
+        type: string
+        # stability: development
+        stability: experimental # Not stable yet.
+      - id: http.request.method
+        stability: 'experimental #1' # It's ' quoted.
+      - id: url.path
+        brief: "The \" # path" # Escaped quote.
+"#;
+        let violations = vec![
+            violation("registry.http", "", "brief", json!("HTTP.")),
+            violation("registry.http", "http.route", "stability", json!("stable")),
+            violation(
+                "registry.http",
+                "http.request.method",
+                "stability",
+                json!("stable"),
+            ),
+            violation("registry.http", "url.path", "brief", json!("Path.")),
+        ];
+
+        let (content, applied) = apply_fixes(spec, &violations);
+        assert_eq!(applied, vec![0, 1, 2, 3]);
+        assert_eq!(
+            content,
+            r#"groups:
+  # - id: registry.http
+  #   brief: Old HTTP attributes.
+  - id: registry.http # The HTTP attributes.
+    type: attribute_group
+    # brief: Commented brief.
+    brief: HTTP. # TODO
+    attributes:
+      # - id: http.route
+      #   stability: development
+      - id: "http.route"  # The route.
+        type: string
+        # stability: development
+        stability: stable # Not stable yet.
+      - id: http.request.method
+        stability: stable # It's ' quoted.
+      - id: url.path
+        brief: Path. # Escaped quote.
+"#
+        );
+    }
+}
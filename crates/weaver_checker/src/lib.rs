@@ -7,24 +7,55 @@ use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 use std::fs::metadata;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
 use globset::Glob;
 use miette::Diagnostic;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::to_value;
 use walkdir::DirEntry;
 
 use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages};
 use weaver_common::error::{format_errors, handle_errors, WeaverError};
 
-use crate::violation::Violation;
+use crate::baseline::Baseline;
+use crate::cel_policy::{is_cel_policy_file, CelPolicy};
+use crate::coverage::{CoverageData, CoverageReport};
+use crate::lint_policy::{is_lint_policy_file, LintPolicy};
+use crate::policy_test::{rego_tests, PolicyTest};
+use crate::sandbox::{eval_rule_within_limits, Sandbox};
+use crate::violation::{ReportedViolation, Severity, Violation};
+use crate::wasm_policy::{is_wasm_policy_file, WasmPolicy};
 use crate::Error::CompoundError;
 
+pub mod baseline;
+mod cel;
+mod cel_policy;
+pub mod coverage;
+pub mod fix;
+mod lint_policy;
+pub mod policy_test;
+mod rego_extensions;
+pub mod sandbox;
 pub mod violation;
+mod wasm_policy;
 
 /// Default semconv rules/functions for the semantic convention registry.
 pub const SEMCONV_REGO: &str = include_str!("../../../defaults/rego/semconv.rego");
 
+/// The glob pattern of the CEL policy files.
+pub const CEL_POLICY_GLOB: &str = "*.cel.{yaml,yml}";
+
+/// The glob pattern of the lint policy files.
+pub const LINT_POLICY_GLOB: &str = "*.lint.{yaml,yml}";
+
+/// The glob pattern of the WASM policy files.
+pub const WASM_POLICY_GLOB: &str = "*.wasm";
+
+/// The reserved key of the data document holding the weaver parameters, i.e.
+/// `data.weaver.params` in the Rego policies.
+pub const PARAMS_DATA_KEY: &str = "weaver";
+
 /// An error that can occur while evaluating policies.
 #[derive(thiserror::Error, Debug, Serialize, Diagnostic, Clone)]
 #[must_use]
@@ -102,11 +133,72 @@ pub enum Error {
 
     /// A policy violation error.
     #[error("Policy violation: {violation}, provenance: {provenance}")]
+    #[diagnostic(forward(violation))]
     PolicyViolation {
         /// The provenance of the violation (URL or path).
         provenance: String,
         /// The violation.
-        violation: Violation,
+        violation: Box<ReportedViolation>,
+    },
+
+    /// An invalid policy test file.
+    #[error("Invalid policy test file '{file}', error: {error}")]
+    #[diagnostic(help("Check the test fixtures of the file."))]
+    InvalidPolicyTestFile {
+        /// The file that caused the error.
+        file: String,
+        /// The error that occurred.
+        error: String,
+    },
+
+    /// A failed policy test.
+    #[error("Policy test `{test}` of '{file}' failed:\n{error}")]
+    #[diagnostic(help(
+        "`-` lines are expected violations that were not reported, `+` lines are reported violations that were not expected."
+    ))]
+    PolicyTestFailed {
+        /// The file defining the test.
+        file: String,
+        /// The name of the test.
+        test: String,
+        /// The differences between the expected and the actual results.
+        error: String,
+    },
+
+    /// An invalid baseline file.
+    #[error("Invalid baseline file '{file}', error: {error}")]
+    #[diagnostic(help(
+        "Regenerate the baseline file of the known violations with `--update-baseline`."
+    ))]
+    InvalidBaselineFile {
+        /// The baseline file.
+        file: String,
+        /// The error that occurred.
+        error: String,
+    },
+
+    /// A policy calling a built-in function denied by the sandbox.
+    #[error("The policy file '{file}' calls the denied built-in function `{builtin}`")]
+    #[diagnostic(help(
+        "The built-in functions accessing the network or the environment, or returning non-deterministic values, are denied by the policy sandbox."
+    ))]
+    DeniedBuiltin {
+        /// The policy file.
+        file: String,
+        /// The denied built-in function.
+        builtin: String,
+    },
+
+    /// A policy evaluation exceeding a limit of the sandbox.
+    #[error("The evaluation of the policy package '{package}' exceeded {limit}")]
+    #[diagnostic(help(
+        "Check the policies of the package for expensive rules, or raise the evaluation limits of the policy sandbox."
+    ))]
+    PolicyLimitExceeded {
+        /// The policy package.
+        package: String,
+        /// The exceeded limit.
+        limit: String,
     },
 
     /// A container for multiple errors.
@@ -145,6 +237,8 @@ impl From<Error> for DiagnosticMessages {
 }
 
 /// A list of supported policy stages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PolicyStage {
     /// Policies that are evaluated before resolution.
     BeforeResolution,
@@ -152,6 +246,14 @@ pub enum PolicyStage {
     AfterResolution,
     /// Policies that are evaluated between two registries the resolution phase.
     ComparisonAfterResolution,
+    /// Policies that are evaluated before the generation of the artifacts of a
+    /// target, with the resolved registry, the target name, and the template
+    /// params as input.
+    BeforeGeneration,
+    /// Policies that are evaluated after resolution, with the resolved
+    /// registry and the aggregated statistics of the observed telemetry (e.g.
+    /// collected by a live check in staging) as input.
+    ObservationAfterResolution,
 }
 
 impl Display for PolicyStage {
@@ -167,6 +269,12 @@ impl Display for PolicyStage {
             PolicyStage::ComparisonAfterResolution => {
                 write!(f, "comparison_after_resolution")
             }
+            PolicyStage::BeforeGeneration => {
+                write!(f, "before_generation")
+            }
+            PolicyStage::ObservationAfterResolution => {
+                write!(f, "observation_after_resolution")
+            }
         }
     }
 }
@@ -178,18 +286,59 @@ pub struct Engine {
     engine: regorus::Engine,
     // Flag to enable the coverage report.
     coverage_enabled: bool,
+    // The coverage of the Rego policies, shared by the clones of the engine.
+    coverage: Arc<Mutex<CoverageData>>,
     // Number of policy packages added.
     policy_package_count: usize,
     // Policy packages loaded. This is used to check if a policy package has been imported
     // before evaluating it.
     policy_packages: HashSet<String>,
+    // The CEL policies.
+    cel_policies: Vec<CelPolicy>,
+    // The lint policies.
+    lint_policies: Vec<LintPolicy>,
+    // The WASM policies.
+    wasm_policies: Vec<WasmPolicy>,
+    // The test rules of the Rego policies.
+    rego_tests: Vec<PolicyTest>,
+    // The baseline of the known violations, shared by the clones of the engine.
+    baseline: Option<Arc<Baseline>>,
+    // The reported violations with a suggested fix, shared by the clones of the engine.
+    fixes: Arc<Mutex<Vec<Violation>>>,
+    // The sandbox of the Rego policies.
+    sandbox: Sandbox,
+    // The error of an abandoned evaluation exceeding its limits. The Rego
+    // policies can't be evaluated anymore, as the abandoned evaluation owns
+    // the `regorus` engine.
+    abandoned_evaluation: Option<Error>,
+    // The input and data documents, as seen by the CEL policies.
+    input: serde_json::Value,
+    data: serde_json::Value,
+    // The minimum severity of the violations failing the commands.
+    fail_on: Severity,
 }
 
 impl Engine {
-    /// Creates a new policy engine.
+    /// Creates a new policy engine, with the built-in `weaver.*` Rego
+    /// functions.
     #[must_use]
     pub fn new() -> Self {
-        Default::default()
+        let mut engine = Self::default();
+        rego_extensions::add_weaver_extensions(&mut engine.engine)
+            .expect("Failed to add the built-in Rego functions");
+        engine
+    }
+
+    /// Sets the minimum severity of the violations failing the commands
+    /// (`violation` by default), see [`ReportedViolation`].
+    pub fn set_fail_on(&mut self, severity: Severity) {
+        self.fail_on = severity;
+    }
+
+    /// Returns the minimum severity of the violations failing the commands.
+    #[must_use]
+    pub fn fail_on(&self) -> Severity {
+        self.fail_on
     }
 
     /// Enables the coverage report.
@@ -198,6 +347,44 @@ impl Engine {
         self.coverage_enabled = true;
     }
 
+    /// Returns the coverage report of the Rego policies evaluated so far (by
+    /// this engine and its clones), or `None` if the coverage is not enabled.
+    #[must_use]
+    pub fn coverage_report(&self) -> Option<CoverageReport> {
+        self.coverage_enabled.then(|| {
+            self.coverage
+                .lock()
+                .expect("Coverage lock poisoned")
+                .report()
+        })
+    }
+
+    /// Returns the violations reported so far (by this engine and its clones)
+    /// with a fix suggested by the policies (see [`fix::apply_fixes`]).
+    #[must_use]
+    pub fn fixes(&self) -> Vec<Violation> {
+        self.fixes.lock().expect("Fixes lock poisoned").clone()
+    }
+
+    /// Sets the sandbox of the Rego policies. The capability controls apply to
+    /// the policies added afterwards, and the evaluation limits to the
+    /// evaluations started afterwards.
+    pub fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.sandbox = sandbox;
+    }
+
+    /// Sets the baseline of the known violations, which are not reported by
+    /// [`Engine::check`].
+    pub fn set_baseline(&mut self, baseline: Baseline) {
+        self.baseline = Some(Arc::new(baseline));
+    }
+
+    /// Returns the baseline of the known violations, if any.
+    #[must_use]
+    pub fn baseline(&self) -> Option<&Baseline> {
+        self.baseline.as_deref()
+    }
+
     /// Adds a rego policy (content) to the policy engine.
     ///
     /// # Arguments
@@ -209,6 +396,7 @@ impl Engine {
     ///
     /// The policy package name.
     pub fn add_policy(&mut self, path: &str, rego: &str) -> Result<String, Error> {
+        self.sandbox.check_capabilities(path, rego)?;
         let policy_package = self
             .engine
             .add_policy(path.to_owned(), rego.to_owned())
@@ -223,11 +411,73 @@ impl Engine {
         // Nothing prevent multiple policy files to import the same policy package.
         // All the rules will be combined and evaluated together.
         _ = self.policy_packages.insert(policy_package.clone());
+        // The module added last is the one parsed from this policy file.
+        if let Some(module) = self.engine.get_modules().last() {
+            self.rego_tests
+                .extend(rego_tests(path, &policy_package, module));
+        }
+        Ok(policy_package)
+    }
+
+    /// Adds a CEL policy (content) to the policy engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the policy (used for error messages).
+    /// * `content` - The content of the CEL policy file.
+    ///
+    /// # Returns
+    ///
+    /// The policy package name corresponding to the stage of the policy.
+    pub fn add_cel_policy(&mut self, path: &str, content: &str) -> Result<String, Error> {
+        let policy = CelPolicy::parse(path, content)?;
+        let policy_package = format!("data.{}", policy.stage);
+        self.cel_policies.push(policy);
+        self.policy_package_count += 1;
         Ok(policy_package)
     }
 
-    /// Adds a policy files to the policy engine. If path is a directory it will add any file matching *.rego
-    /// A policy file is a `rego` file that contains the policies to be evaluated.
+    /// Adds a lint policy (content) to the policy engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the policy (used for error messages).
+    /// * `content` - The content of the lint policy file.
+    ///
+    /// # Returns
+    ///
+    /// The policy package name corresponding to the stage of the policy.
+    pub fn add_lint_policy(&mut self, path: &str, content: &str) -> Result<String, Error> {
+        let policy = LintPolicy::parse(path, content)?;
+        let policy_package = format!("data.{}", policy.stage);
+        self.lint_policies.push(policy);
+        self.policy_package_count += 1;
+        Ok(policy_package)
+    }
+
+    /// Adds a WASM policy (compiled module) to the policy engine. The module
+    /// must implement the interface described in the README.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the policy (used for error messages).
+    /// * `wasm` - The binary content of the WASM module.
+    ///
+    /// # Returns
+    ///
+    /// The policy package name corresponding to the stage of the policy.
+    pub fn add_wasm_policy(&mut self, path: &str, wasm: &[u8]) -> Result<String, Error> {
+        let policy = WasmPolicy::parse(path, wasm)?;
+        let policy_package = format!("data.{}", policy.stage);
+        self.wasm_policies.push(policy);
+        self.policy_package_count += 1;
+        Ok(policy_package)
+    }
+
+    /// Adds a policy files to the policy engine. If path is a directory it will add any file matching
+    /// *.rego, *.cel.yaml, *.cel.yml, *.lint.yaml, *.lint.yml, or *.wasm
+    /// A policy file is a `rego` file, a CEL policy file, a lint policy file, or a WASM policy
+    /// module that contains the policies to be evaluated.
     ///
     /// # Arguments
     ///
@@ -252,6 +502,9 @@ impl Engine {
             }
             (false, true) => {
                 _ = self.add_policies(path, "*.rego")?;
+                _ = self.add_policies(path, CEL_POLICY_GLOB)?;
+                _ = self.add_policies(path, LINT_POLICY_GLOB)?;
+                _ = self.add_policies(path, WASM_POLICY_GLOB)?;
             }
             _ => {
                 return Err(Error::UnsupportedPolicyPath {
@@ -262,7 +515,10 @@ impl Engine {
         Ok(())
     }
     /// Adds a policy file to the policy engine.
-    /// A policy file is a `rego` file that contains the policies to be evaluated.
+    /// A policy file is a `rego` file, a CEL policy file (`*.cel.yaml` or
+    /// `*.cel.yml`), a lint policy file (`*.lint.yaml` or `*.lint.yml`), or a
+    /// WASM policy module (`*.wasm`) that contains the policies to be
+    /// evaluated.
     ///
     /// # Arguments
     ///
@@ -277,27 +533,33 @@ impl Engine {
     ) -> Result<String, Error> {
         let policy_path_str = policy_path.as_ref().to_string_lossy().to_string();
 
-        let policy_package = self
-            .engine
-            .add_policy_from_file(policy_path)
-            .map_err(|e| Error::InvalidPolicyFile {
+        if is_wasm_policy_file(&policy_path_str) {
+            let wasm =
+                std::fs::read(policy_path.as_ref()).map_err(|e| Error::InvalidPolicyFile {
+                    file: policy_path_str.clone(),
+                    error: e.to_string(),
+                })?;
+            return self.add_wasm_policy(&policy_path_str, &wasm);
+        }
+        let content = std::fs::read_to_string(policy_path.as_ref()).map_err(|e| {
+            Error::InvalidPolicyFile {
                 file: policy_path_str.clone(),
                 error: e.to_string(),
-            })
-            .inspect(|_| {
-                self.policy_package_count += 1;
-            })?;
-        // Add the policy package defined in the imported policy file.
-        // Nothing prevent multiple policy files to import the same policy package.
-        // All the rules will be combined and evaluated together.
-        _ = self.policy_packages.insert(policy_package.clone());
-        Ok(policy_package)
+            }
+        })?;
+        if is_lint_policy_file(&policy_path_str) {
+            self.add_lint_policy(&policy_path_str, &content)
+        } else if is_cel_policy_file(&policy_path_str) {
+            self.add_cel_policy(&policy_path_str, &content)
+        } else {
+            self.add_policy(&policy_path_str, &content)
+        }
     }
 
     /// Adds all the policy files present in the given directory that match the
     /// given glob pattern (Unix-style glob syntax).
     ///
-    /// Example of pattern: `*.rego`
+    /// Example of pattern: `*.rego` or `*.cel.yaml`
     ///
     /// # Returns
     ///
@@ -349,6 +611,24 @@ impl Engine {
         Ok(added_policy_count)
     }
 
+    /// Returns the test rules (`test_*`) of the Rego policies added to the
+    /// policy engine.
+    #[must_use]
+    pub fn policy_tests(&self) -> &[PolicyTest] {
+        &self.rego_tests
+    }
+
+    /// Evaluates a Rego test rule (e.g. `data.before_resolution.test_ref`)
+    /// and returns true if it evaluates to `true`.
+    pub(crate) fn eval_rego_test(&mut self, rule: &str) -> Result<bool, Error> {
+        let package = rule
+            .trim_start_matches("data.")
+            .rsplit_once('.')
+            .map_or(rule, |(package, _)| package);
+        let value = self.eval_rule(package, rule.to_owned())?;
+        Ok(matches!(value, regorus::Value::Bool(true)))
+    }
+
     /// Returns the number of policy packages added to the policy engine.
     #[must_use]
     pub fn policy_package_count(&self) -> usize {
@@ -370,17 +650,36 @@ impl Engine {
             error: e.to_string(),
         })?;
         let value: regorus::Value =
-            serde_json::from_value(json_data).map_err(|e| Error::InvalidInput {
+            serde_json::from_value(json_data.clone()).map_err(|e| Error::InvalidInput {
                 error: e.to_string(),
             })?;
-        self.engine.add_data(value).map_err(|e| Error::InvalidData {
-            error: e.to_string(),
-        })
+        self.engine
+            .add_data(value)
+            .map_err(|e| Error::InvalidData {
+                error: e.to_string(),
+            })?;
+        // The data documents are merged for the CEL policies.
+        match (&mut self.data, json_data) {
+            (serde_json::Value::Object(data), serde_json::Value::Object(new_data)) => {
+                data.extend(new_data);
+            }
+            (data, new_data) => *data = new_data,
+        }
+        Ok(())
+    }
+
+    /// Adds the weaver parameters (e.g. the `--param` values and the `params`
+    /// of `weaver.yaml`) to the data document, under the reserved
+    /// [`PARAMS_DATA_KEY`] key, so that the policies can be parameterized,
+    /// e.g. `data.weaver.params.max_enum_members`.
+    pub fn add_params<T: Serialize>(&mut self, params: &T) -> Result<(), Error> {
+        self.add_data(&serde_json::json!({ PARAMS_DATA_KEY: { "params": params } }))
     }
 
     /// Clears the data from the policy engine.
     pub fn clear_data(&mut self) {
         self.engine.clear_data();
+        self.data = serde_json::Value::Null;
     }
 
     /// Sets an input document for the policy engine.
@@ -399,32 +698,81 @@ impl Engine {
         })?;
 
         let value: regorus::Value =
-            serde_json::from_value(json_input).map_err(|e| Error::InvalidInput {
+            serde_json::from_value(json_input.clone()).map_err(|e| Error::InvalidInput {
                 error: e.to_string(),
             })?;
         self.engine.set_input(value);
+        self.input = json_input;
         Ok(())
     }
 
     /// Returns a list of violations based on the policies, the data, the
     /// input, and the given policy stage.
-    #[allow(clippy::print_stdout)] // Used to display the coverage (debugging purposes only)
+    ///
+    /// The violations of the Rego policies come first, followed by the
+    /// violations of the CEL policies, of the lint policies, and of the WASM
+    /// policies. The violations
+    /// of the baseline, if any, are not reported.
     pub fn check(&mut self, stage: PolicyStage) -> Result<Vec<Violation>, Error> {
+        let mut violations = self.check_rego(stage)?;
+        for policy in self.cel_policies.iter().filter(|p| p.stage == stage) {
+            violations.extend(policy.check(&self.input, &self.data)?);
+        }
+        for policy in self.lint_policies.iter().filter(|p| p.stage == stage) {
+            violations.extend(policy.check(&self.input));
+        }
+        for policy in self.wasm_policies.iter().filter(|p| p.stage == stage) {
+            violations.extend(policy.check(&self.input, &self.data)?);
+        }
+        if let Some(baseline) = &self.baseline {
+            violations = baseline.filter(violations);
+        }
+        self.fixes
+            .lock()
+            .expect("Fixes lock poisoned")
+            .extend(violations.iter().filter(|v| v.fix().is_some()).cloned());
+        Ok(violations)
+    }
+
+    /// Evaluates a rule of a Rego policy package, within the evaluation limits
+    /// of the package if any (see [`Sandbox`]).
+    fn eval_rule(&mut self, package: &str, rule: String) -> Result<regorus::Value, Error> {
+        if let Some(error) = &self.abandoned_evaluation {
+            return Err(error.clone());
+        }
+        let limits = self.sandbox.limits(package);
+        let result = if limits.is_unlimited() {
+            self.engine.eval_rule(rule)
+        } else {
+            let engine = std::mem::take(&mut self.engine);
+            match eval_rule_within_limits(engine, package, rule, limits) {
+                Ok((engine, result)) => {
+                    // The evaluated engine holds the coverage of the evaluation.
+                    self.engine = engine;
+                    result
+                }
+                Err(error) => {
+                    self.abandoned_evaluation = Some(error.clone());
+                    return Err(error);
+                }
+            }
+        };
+        result.map_err(|e| Error::ViolationEvaluationError {
+            error: e.to_string(),
+        })
+    }
+
+    /// Returns the violations of the Rego policies for the given stage.
+    fn check_rego(&mut self, stage: PolicyStage) -> Result<Vec<Violation>, Error> {
         // If we don't have any policy package that matches the stage,
         // return an empty list of violations.
         if !self.policy_packages.contains(&format!("data.{}", stage)) {
             return Ok(vec![]);
         }
 
-        let value = self
-            .engine
-            .eval_rule(format!("data.{}.deny", stage))
-            .map_err(|e| Error::ViolationEvaluationError {
-                error: e.to_string(),
-            })?;
+        let value = self.eval_rule(&stage.to_string(), format!("data.{}.deny", stage))?;
 
-        // Print the coverage report if enabled
-        // This is useful for debugging purposes
+        // Accumulate the coverage of the evaluation if enabled
         if self.coverage_enabled {
             let report =
                 self.engine
@@ -432,13 +780,11 @@ impl Engine {
                     .map_err(|e| Error::ViolationEvaluationError {
                         error: e.to_string(),
                     })?;
-            let pretty_report =
-                report
-                    .to_string_pretty()
-                    .map_err(|e| Error::ViolationEvaluationError {
-                        error: e.to_string(),
-                    })?;
-            println!("{}", pretty_report);
+            self.engine.clear_coverage_data();
+            self.coverage
+                .lock()
+                .expect("Coverage lock poisoned")
+                .add(&report);
         }
 
         // convert `regorus` value to `serde_json` value
@@ -459,12 +805,16 @@ impl Engine {
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::path::Path;
+    use std::time::Duration;
 
     use serde_yaml::Value;
 
     use weaver_common::error::format_errors;
 
-    use crate::violation::Violation;
+    use crate::baseline::Baseline;
+    use crate::sandbox::{EvalLimits, Sandbox};
+    use crate::violation::{Fix, Severity, Violation};
     use crate::{Engine, Error, PolicyStage};
 
     #[test]
@@ -487,18 +837,24 @@ mod tests {
                 category: "attribute".to_owned(),
                 group: "registry.network1".to_owned(),
                 attr: "protocol.name".to_owned(),
+                severity: Severity::Violation,
+                fix: None,
             },
             Violation::SemconvAttribute {
                 id: "attr_removed".to_owned(),
                 category: "schema_evolution".to_owned(),
                 group: "registry.network1".to_owned(),
                 attr: "protocol.name.3".to_owned(),
+                severity: Severity::Violation,
+                fix: None,
             },
             Violation::SemconvAttribute {
                 id: "registry_with_ref_attr".to_owned(),
                 category: "attribute_registry".to_owned(),
                 group: "registry.network1".to_owned(),
                 attr: "protocol.port".to_owned(),
+                severity: Severity::Violation,
+                fix: None,
             },
         ]
         .into_iter()
@@ -515,6 +871,335 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_cel_policy() -> Result<(), Box<dyn std::error::Error>> {
+        let mut engine = Engine::new();
+        let policy_package = engine.add_policy_from_file("data/policies/otel_policies.cel.yaml")?;
+        assert_eq!(policy_package, "data.before_resolution");
+
+        let old_semconv = std::fs::read_to_string("data/registries/registry.network.old.yaml")?;
+        let old_semconv: Value = serde_yaml::from_str(&old_semconv)?;
+        engine.add_data(&old_semconv)?;
+
+        let new_semconv = std::fs::read_to_string("data/registries/registry.network.new.yaml")?;
+        let new_semconv: Value = serde_yaml::from_str(&new_semconv)?;
+        engine.set_input(&new_semconv)?;
+
+        // The CEL policy reports the same violations as `otel_policies.rego`.
+        let mut ids: Vec<_> = engine
+            .check(PolicyStage::BeforeResolution)?
+            .iter()
+            .map(|violation| violation.id().to_owned())
+            .collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![
+                "attr_removed",
+                "attr_stability_deprecated",
+                "registry_with_ref_attr"
+            ]
+        );
+        assert!(engine.check(PolicyStage::AfterResolution)?.is_empty());
+
+        // Both backends are evaluated for the same stage.
+        _ = engine.add_policy_from_file("data/policies/otel_policies.rego")?;
+        assert_eq!(engine.check(PolicyStage::BeforeResolution)?.len(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_policy() -> Result<(), Box<dyn std::error::Error>> {
+        let mut engine = Engine::new();
+        let policy_package = engine.add_policy_from_file("data/policies/otel_naming.lint.yaml")?;
+        assert_eq!(policy_package, "data.before_resolution");
+
+        let new_semconv = std::fs::read_to_string("data/registries/registry.network.new.yaml")?;
+        let new_semconv: Value = serde_yaml::from_str(&new_semconv)?;
+        engine.set_input(&new_semconv)?;
+
+        assert_eq!(
+            engine.check(PolicyStage::BeforeResolution)?,
+            vec![
+                Violation::SemconvAttribute {
+                    id: "attr_id_snake_case".to_owned(),
+                    category: "naming".to_owned(),
+                    group: "registry.network1".to_owned(),
+                    attr: "protocol.name.2".to_owned(),
+                    severity: Severity::Warning,
+                    fix: None,
+                },
+                Violation::SemconvAttribute {
+                    id: "registry_with_ref_attr".to_owned(),
+                    category: "attribute_registry".to_owned(),
+                    group: "registry.network1".to_owned(),
+                    attr: "protocol.port".to_owned(),
+                    severity: Severity::Violation,
+                    fix: None,
+                },
+            ]
+        );
+        assert!(engine.check(PolicyStage::AfterResolution)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_coverage() -> Result<(), Box<dyn std::error::Error>> {
+        let mut engine = Engine::new();
+        assert!(engine.coverage_report().is_none());
+        engine.enable_coverage();
+        _ = engine.add_policy_from_file("data/policies/otel_policies.rego")?;
+
+        let old_semconv = std::fs::read_to_string("data/registries/registry.network.old.yaml")?;
+        let old_semconv: Value = serde_yaml::from_str(&old_semconv)?;
+        engine.add_data(&old_semconv)?;
+        let new_semconv = std::fs::read_to_string("data/registries/registry.network.new.yaml")?;
+        let new_semconv: Value = serde_yaml::from_str(&new_semconv)?;
+        engine.set_input(&new_semconv)?;
+
+        // The coverage of the clones is accumulated.
+        _ = engine.check(PolicyStage::BeforeResolution)?;
+        _ = engine.clone().check(PolicyStage::BeforeResolution)?;
+
+        let report = engine.coverage_report().expect("coverage not enabled");
+        assert_eq!(report.files.len(), 1);
+        let file = &report.files[0];
+        assert_eq!(file.path, "data/policies/otel_policies.rego");
+        assert!(file.covered_lines() > 0);
+        assert!(file
+            .rules
+            .iter()
+            .any(|rule| rule.name == "deny" && rule.hits == 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_baseline() -> Result<(), Box<dyn std::error::Error>> {
+        let mut engine = Engine::new();
+        _ = engine.add_policy_from_file("data/policies/otel_naming.lint.yaml")?;
+        engine.set_baseline(Baseline::load(Path::new(
+            "data/baselines/otel_naming.yaml",
+        ))?);
+
+        let new_semconv = std::fs::read_to_string("data/registries/registry.network.new.yaml")?;
+        let new_semconv: Value = serde_yaml::from_str(&new_semconv)?;
+        engine.set_input(&new_semconv)?;
+
+        // The known violation is suppressed, also by the clones of the engine.
+        let expected = vec![Violation::SemconvAttribute {
+            id: "registry_with_ref_attr".to_owned(),
+            category: "attribute_registry".to_owned(),
+            group: "registry.network1".to_owned(),
+            attr: "protocol.port".to_owned(),
+            severity: Severity::Violation,
+            fix: None,
+        }];
+        assert_eq!(engine.check(PolicyStage::BeforeResolution)?, expected);
+        assert_eq!(
+            engine.clone().check(PolicyStage::BeforeResolution)?,
+            expected
+        );
+
+        let baseline = engine.baseline().expect("baseline not set");
+        assert_eq!(baseline.suppressed_count(), 1);
+        assert_eq!(baseline.stale_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixes() -> Result<(), Box<dyn std::error::Error>> {
+        let mut engine = Engine::new();
+        _ = engine.add_policy(
+            "fixes.rego",
+            r#"
+package before_resolution
+
+import rego.v1
+
+deny contains violation if {
+    group := input.groups[_]
+    attr := group.attributes[_]
+    attr.deprecated
+    attr.stability != "deprecated"
+    violation := {
+        "id": "attr_stability_deprecated",
+        "type": "semconv_attribute",
+        "category": "attribute",
+        "group": group.id,
+        "attr": attr.id,
+        "fix": {"path": "stability", "value": "deprecated"},
+    }
+}
+"#,
+        )?;
+
+        let new_semconv = std::fs::read_to_string("data/registries/registry.network.new.yaml")?;
+        let new_semconv: Value = serde_yaml::from_str(&new_semconv)?;
+        engine.set_input(&new_semconv)?;
+
+        // The violations with a fix are recorded, also by the clones of the engine.
+        let expected = vec![Violation::SemconvAttribute {
+            id: "attr_stability_deprecated".to_owned(),
+            category: "attribute".to_owned(),
+            group: "registry.network1".to_owned(),
+            attr: "protocol.name".to_owned(),
+            severity: Severity::Violation,
+            fix: Some(Box::new(Fix {
+                path: "stability".to_owned(),
+                value: serde_json::json!("deprecated"),
+            })),
+        }];
+        assert_eq!(
+            engine.clone().check(PolicyStage::BeforeResolution)?,
+            expected
+        );
+        assert_eq!(engine.fixes(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let mut engine = Engine::new();
+        engine.set_sandbox(Sandbox::restricted());
+
+        // The policies calling a denied built-in function are rejected, and
+        // not added to the engine.
+        for (builtin, rego) in [
+            (
+                "time.now_ns",
+                "package before_resolution\n\nnow := time.now_ns()\n",
+            ),
+            (
+                "http.send",
+                "package before_resolution\n\nresp := http.send({\"method\": \"get\", \"url\": \"https://example.com\"})\n",
+            ),
+        ] {
+            let result = engine.add_policy("denied.rego", rego);
+            assert!(
+                matches!(result, Err(Error::DeniedBuiltin { builtin: ref denied, .. }) if denied == builtin),
+                "{:?}",
+                result
+            );
+        }
+        assert_eq!(engine.policy_package_count(), 0);
+
+        // The policies that can't be checked are rejected.
+        let result = engine.add_policy("invalid.rego", "package before_resolution\n\nallow := {\n");
+        assert!(
+            matches!(result, Err(Error::InvalidPolicyFile { .. })),
+            "{:?}",
+            result
+        );
+
+        // `opa.runtime` is not available.
+        _ = engine
+            .add_policy(
+                "runtime.rego",
+                "package before_resolution\n\nenv := opa.runtime().env\n",
+            )
+            .unwrap();
+        assert!(engine
+            .engine
+            .eval_rule("data.before_resolution.env".to_owned())
+            .is_err());
+    }
+
+    #[test]
+    fn test_sandbox_limits() {
+        let slow_policy = r#"
+package before_resolution
+
+import rego.v1
+
+deny contains violation if {
+    some i in numbers.range(1, 100000)
+    i < 0
+    violation := {"id": "never", "type": "semconv_attribute", "category": "", "group": "", "attr": ""}
+}
+"#;
+        let large_policy = r#"
+package after_resolution
+
+import rego.v1
+
+deny contains violation if {
+    items := [{"id": sprintf("item%d", [i])} | some i in numbers.range(1, 200000)]
+    count(items) < 0
+    violation := {"id": "never", "type": "semconv_attribute", "category": "", "group": "", "attr": ""}
+}
+"#;
+        let new_engine = |sandbox: Sandbox| {
+            let mut engine = Engine::new();
+            engine.set_sandbox(sandbox);
+            _ = engine.add_policy("slow.rego", slow_policy).unwrap();
+            _ = engine.add_policy("large.rego", large_policy).unwrap();
+            engine
+                .set_input(&serde_json::json!({"groups": []}))
+                .unwrap();
+            engine
+        };
+
+        // The evaluations within their limits succeed.
+        let mut engine = new_engine(Sandbox {
+            limits: EvalLimits {
+                timeout: Some(Duration::from_secs(600)),
+                max_memory: None,
+            },
+            ..Sandbox::default()
+        });
+        assert!(engine
+            .check(PolicyStage::BeforeResolution)
+            .unwrap()
+            .is_empty());
+
+        // The evaluations exceeding their time limit are abandoned, and the
+        // Rego policies can't be evaluated anymore.
+        let mut engine = new_engine(Sandbox {
+            package_limits: [(
+                "before_resolution".to_owned(),
+                EvalLimits {
+                    timeout: Some(Duration::from_millis(1)),
+                    max_memory: None,
+                },
+            )]
+            .into(),
+            ..Sandbox::default()
+        });
+        for stage in [PolicyStage::BeforeResolution, PolicyStage::AfterResolution] {
+            let result = engine.check(stage);
+            assert!(
+                matches!(result, Err(Error::PolicyLimitExceeded { ref package, ref limit }) if package == "before_resolution" && limit == "the time limit of 1 ms"),
+                "{:?}",
+                result
+            );
+        }
+
+        // The memory used by the evaluations is only measured on Linux.
+        if cfg!(target_os = "linux") {
+            let mut engine = new_engine(Sandbox {
+                package_limits: [(
+                    "after_resolution".to_owned(),
+                    EvalLimits {
+                        timeout: None,
+                        max_memory: Some(1024 * 1024),
+                    },
+                )]
+                .into(),
+                ..Sandbox::default()
+            });
+            assert!(engine
+                .check(PolicyStage::BeforeResolution)
+                .unwrap()
+                .is_empty());
+            let result = engine.check(PolicyStage::AfterResolution);
+            assert!(
+                matches!(result, Err(Error::PolicyLimitExceeded { ref package, .. }) if package == "after_resolution"),
+                "{:?}",
+                result
+            );
+        }
+    }
+
     #[test]
     fn test_invalid_policy() {
         let mut engine = Engine::new();
@@ -522,6 +1207,58 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_params() {
+        let mut engine = Engine::new();
+        _ = engine
+            .add_policy(
+                "params.rego",
+                r#"
+package before_resolution
+
+import rego.v1
+
+deny contains attr_violation("attr_namespace_not_allowed", group.id, attr.id) if {
+    group := input.groups[_]
+    attr := group.attributes[_]
+    namespace := weaver.namespace_of(attr.id)
+    not namespace in data.weaver.params.allowed_namespaces
+}
+
+attr_violation(violation_id, group_id, attr_id) := {
+    "id": violation_id,
+    "type": "semconv_attribute",
+    "category": "attribute",
+    "group": group_id,
+    "attr": attr_id,
+}
+"#,
+            )
+            .unwrap();
+        engine
+            .add_params(&serde_json::json!({ "allowed_namespaces": ["protocol"] }))
+            .unwrap();
+
+        let new_semconv =
+            std::fs::read_to_string("data/registries/registry.network.new.yaml").unwrap();
+        let new_semconv: Value = serde_yaml::from_str(&new_semconv).unwrap();
+        engine.set_input(&new_semconv).unwrap();
+
+        // Only `protocol.name.2` is not in an allowed namespace.
+        let violations = engine.check(PolicyStage::BeforeResolution).unwrap();
+        assert_eq!(
+            violations,
+            vec![Violation::SemconvAttribute {
+                id: "attr_namespace_not_allowed".to_owned(),
+                category: "attribute".to_owned(),
+                group: "registry.network1".to_owned(),
+                attr: "protocol.name.2".to_owned(),
+                severity: Severity::Violation,
+                fix: None,
+            }]
+        );
+    }
+
     #[test]
     fn test_invalid_data() {
         let mut engine = Engine::new();
@@ -572,18 +1309,24 @@ mod tests {
                 category: "attribute".to_owned(),
                 group: "registry.network1".to_owned(),
                 attr: "protocol.name".to_owned(),
+                severity: Severity::Violation,
+                fix: None,
             },
             Violation::SemconvAttribute {
                 id: "attr_removed".to_owned(),
                 category: "schema_evolution".to_owned(),
                 group: "registry.network1".to_owned(),
                 attr: "protocol.name.3".to_owned(),
+                severity: Severity::Violation,
+                fix: None,
             },
             Violation::SemconvAttribute {
                 id: "registry_with_ref_attr".to_owned(),
                 category: "attribute_registry".to_owned(),
                 group: "registry.network1".to_owned(),
                 attr: "protocol.port".to_owned(),
+                severity: Severity::Violation,
+                fix: None,
             },
         ]
         .into_iter()
@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint policies, declarative checks of the fields of the groups and the
+//! attributes that don't require any Rego or CEL.
+//!
+//! A lint policy file (`*.lint.yaml` or `*.lint.yml`) defines the rules of a
+//! policy stage. Each rule checks a field of each group, or of each
+//! attribute of each group:
+//!
+//! ```yaml
+//! stage: before_resolution
+//! rules:
+//!   - id: attr_id_snake_case
+//!     category: naming
+//!     severity: warning
+//!     scope: attribute
+//!     field: id
+//!     pattern: '^[a-z][a-z0-9_]*(\.[a-z][a-z0-9_]*)*$'
+//!   - id: metric_unit_required
+//!     category: metric
+//!     when:
+//!       - field: type
+//!         enum: [metric]
+//!     field: unit
+//!     required: true
+//! ```
+//!
+//! A rule is violated when its field is missing while `required`, or when
+//! its value (or one of the items of a list) doesn't match the `pattern`
+//! regex or isn't one of the `enum` values. A missing optional field is not
+//! checked. The rule only applies to the elements matching all its `when`
+//! conditions, i.e. whose field is present and matches the `pattern` and the
+//! `enum` of the condition.
+//!
+//! The fields are selected with a dot-separated path (e.g. `stability` or
+//! `annotations.code_generation.exclude`). In the `attribute` scope, the
+//! paths starting with `group.` select the fields of the group of the
+//! attribute. The groups are `input.groups`.
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::cel_policy::string_field;
+use crate::violation::{Severity, Violation};
+use crate::{Error, PolicyStage};
+
+/// The prefix of the selectors of the group fields in the `attribute` scope.
+const GROUP_SELECTOR_PREFIX: &str = "group.";
+
+/// Returns true if the path is a lint policy file.
+pub(crate) fn is_lint_policy_file(path: &str) -> bool {
+    path.ends_with(".lint.yaml") || path.ends_with(".lint.yml")
+}
+
+/// The content of a lint policy file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintPolicyFile {
+    /// The stage of the rules.
+    stage: PolicyStage,
+    /// The rules of the policy.
+    rules: Vec<LintRuleSpec>,
+}
+
+/// A rule of a lint policy file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintRuleSpec {
+    /// The id of the violations.
+    id: String,
+    /// The category of the violations.
+    category: String,
+    /// The severity of the violations.
+    #[serde(default)]
+    severity: Severity,
+    /// The elements the rule is evaluated on.
+    #[serde(default)]
+    scope: RuleScope,
+    /// The conditions of the elements the rule applies to.
+    #[serde(default)]
+    when: Vec<ConditionSpec>,
+    /// The selector of the checked field.
+    field: String,
+    /// True if the field must be present.
+    #[serde(default)]
+    required: bool,
+    /// The regex the value must match.
+    pattern: Option<String>,
+    /// The allowed values.
+    #[serde(rename = "enum")]
+    allowed: Option<Vec<Value>>,
+}
+
+/// A condition of a rule.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConditionSpec {
+    /// The selector of the field.
+    field: String,
+    /// The regex the value must match.
+    pattern: Option<String>,
+    /// The allowed values.
+    #[serde(rename = "enum")]
+    allowed: Option<Vec<Value>>,
+}
+
+/// The elements a rule is evaluated on.
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum RuleScope {
+    /// The rule is evaluated on each group.
+    #[default]
+    Group,
+    /// The rule is evaluated on each attribute of each group.
+    Attribute,
+}
+
+/// A compiled check of the value of a field.
+#[derive(Debug, Clone)]
+struct FieldCheck {
+    /// The selector of the field.
+    field: String,
+    pattern: Option<Regex>,
+    allowed: Option<Vec<Value>>,
+}
+
+/// A compiled rule.
+#[derive(Debug, Clone)]
+struct LintRule {
+    id: String,
+    category: String,
+    severity: Severity,
+    scope: RuleScope,
+    when: Vec<FieldCheck>,
+    check: FieldCheck,
+    required: bool,
+}
+
+/// A compiled lint policy.
+#[derive(Debug, Clone)]
+pub(crate) struct LintPolicy {
+    /// The stage of the rules.
+    pub(crate) stage: PolicyStage,
+    rules: Vec<LintRule>,
+}
+
+impl LintPolicy {
+    /// Parses and compiles a lint policy.
+    pub(crate) fn parse(path: &str, content: &str) -> Result<Self, Error> {
+        let invalid = |error: String| Error::InvalidPolicyFile {
+            file: path.to_owned(),
+            error,
+        };
+        let file: LintPolicyFile =
+            serde_yaml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+        let rules = file
+            .rules
+            .into_iter()
+            .map(|rule| -> Result<LintRule, Error> {
+                let compile = |field: String,
+                               pattern: Option<String>,
+                               allowed: Option<Vec<Value>>|
+                 -> Result<FieldCheck, Error> {
+                    let pattern = pattern
+                        .map(|pattern| Regex::new(&pattern))
+                        .transpose()
+                        .map_err(|e| {
+                            invalid(format!("rule `{}`, `{}` pattern: {}", rule.id, field, e))
+                        })?;
+                    Ok(FieldCheck {
+                        field,
+                        pattern,
+                        allowed,
+                    })
+                };
+                let when = rule
+                    .when
+                    .into_iter()
+                    .map(|condition| compile(condition.field, condition.pattern, condition.allowed))
+                    .collect::<Result<_, Error>>()?;
+                let check = compile(rule.field, rule.pattern, rule.allowed)?;
+                Ok(LintRule {
+                    id: rule.id,
+                    category: rule.category,
+                    severity: rule.severity,
+                    scope: rule.scope,
+                    when,
+                    check,
+                    required: rule.required,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self {
+            stage: file.stage,
+            rules,
+        })
+    }
+
+    /// Evaluates the rules on the groups of the input and returns the
+    /// violations.
+    pub(crate) fn check(&self, input: &Value) -> Vec<Violation> {
+        let groups = match input.get("groups") {
+            Some(Value::Array(groups)) => groups.as_slice(),
+            _ => &[],
+        };
+        let mut violations = vec![];
+        for rule in &self.rules {
+            for group in groups {
+                if rule.scope == RuleScope::Group {
+                    if rule.is_violated(group, group) {
+                        violations.push(rule.violation(group, &Value::Null));
+                    }
+                    continue;
+                }
+                let Some(Value::Array(attributes)) = group.get("attributes") else {
+                    continue;
+                };
+                for attribute in attributes {
+                    if rule.is_violated(attribute, group) {
+                        violations.push(rule.violation(group, attribute));
+                    }
+                }
+            }
+        }
+        violations
+    }
+}
+
+impl LintRule {
+    /// Returns true if the rule applies to the element and is violated.
+    fn is_violated(&self, element: &Value, group: &Value) -> bool {
+        let field = |selector: &str| match self.scope {
+            RuleScope::Attribute => match selector.strip_prefix(GROUP_SELECTOR_PREFIX) {
+                Some(selector) => select(group, selector),
+                None => select(element, selector),
+            },
+            RuleScope::Group => select(element, selector),
+        };
+        let applies = self
+            .when
+            .iter()
+            .all(|condition| field(&condition.field).is_some_and(|value| condition.accepts(value)));
+        applies
+            && match field(&self.check.field) {
+                Some(value) => !self.check.accepts(value),
+                None => self.required,
+            }
+    }
+
+    /// Returns the violation of the rule by a group or an attribute.
+    fn violation(&self, group: &Value, attribute: &Value) -> Violation {
+        Violation::SemconvAttribute {
+            id: self.id.clone(),
+            category: self.category.clone(),
+            group: string_field(group, &["id"]),
+            attr: string_field(attribute, &["id", "name", "ref"]),
+            severity: self.severity,
+            fix: None,
+        }
+    }
+}
+
+impl FieldCheck {
+    /// Returns true if the value, or each item of a list, matches the pattern
+    /// and the allowed values of the check.
+    fn accepts(&self, value: &Value) -> bool {
+        let accepts_item = |item: &Value| {
+            let matches_pattern = self.pattern.as_ref().map_or(true, |pattern| match item {
+                Value::String(text) => pattern.is_match(text),
+                Value::Bool(_) | Value::Number(_) => pattern.is_match(&item.to_string()),
+                _ => false,
+            });
+            let is_allowed = self
+                .allowed
+                .as_ref()
+                .map_or(true, |allowed| allowed.contains(item));
+            matches_pattern && is_allowed
+        };
+        match value {
+            Value::Array(items) => items.iter().all(accepts_item),
+            value => accepts_item(value),
+        }
+    }
+}
+
+/// Returns the field of a value selected by a dot-separated path, or `None`
+/// if the field is missing or `null`.
+fn select<'v>(value: &'v Value, selector: &str) -> Option<&'v Value> {
+    selector
+        .split('.')
+        .try_fold(value, |value, name| value.get(name))
+        .filter(|value| !value.is_null())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_lint_policy() {
+        let policy = LintPolicy::parse(
+            "test.lint.yaml",
+            r#"
+stage: before_resolution
+rules:
+  - id: attr_id_snake_case
+    category: naming
+    severity: warning
+    scope: attribute
+    field: id
+    pattern: '^[a-z][a-z0-9_]*(\.[a-z][a-z0-9_]*)*$'
+  - id: metric_unit_required
+    category: metric
+    when:
+      - field: type
+        enum: [metric]
+    field: unit
+    required: true
+  - id: attr_stability
+    category: stability
+    scope: attribute
+    when:
+      - field: group.id
+        pattern: '^registry\.'
+    field: stability
+    enum: [stable, experimental]
+  - id: lowercase_examples
+    category: attribute
+    severity: info
+    scope: attribute
+    field: examples
+    pattern: '^[a-z]+$'
+"#,
+        )
+        .unwrap();
+        assert_eq!(policy.stage, PolicyStage::BeforeResolution);
+
+        let input = json!({"groups": [
+            {"id": "registry.a", "type": "attribute_group", "attributes": [
+                {"id": "a.Upper", "stability": "stable"},
+                {"id": "a.b", "stability": "deprecated", "examples": ["x", "Y"]},
+                {"ref": "c.d", "examples": "z"},
+            ]},
+            {"id": "metric.a", "type": "metric", "unit": "s"},
+            {"id": "metric.b", "type": "metric", "unit": null},
+            {"id": "span.a", "type": "span", "attributes": [{"ref": "a.Upper"}]},
+        ]});
+        let violations: Vec<_> = policy
+            .check(&input)
+            .into_iter()
+            .map(|violation| {
+                let severity = violation.level();
+                let Violation::SemconvAttribute {
+                    id, group, attr, ..
+                } = violation;
+                (id, group, attr, severity)
+            })
+            .collect();
+        let violation = |id: &str, group: &str, attr: &str, severity| {
+            (id.to_owned(), group.to_owned(), attr.to_owned(), severity)
+        };
+        assert_eq!(
+            violations,
+            vec![
+                violation(
+                    "attr_id_snake_case",
+                    "registry.a",
+                    "a.Upper",
+                    Severity::Warning
+                ),
+                violation("metric_unit_required", "metric.b", "", Severity::Violation),
+                violation("attr_stability", "registry.a", "a.b", Severity::Violation),
+                violation("lowercase_examples", "registry.a", "a.b", Severity::Info),
+            ]
+        );
+
+        // Without groups, nothing is checked.
+        assert!(policy.check(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_lint_policy() {
+        for (content, error) in [
+            ("stage: unknown\nrules: []", "stage: unknown variant `unknown`"),
+            (
+                "stage: before_resolution\nrules:\n- {id: a, category: b}",
+                "rules[0]: missing field `field`",
+            ),
+            (
+                "stage: before_resolution\nrules:\n- {id: a, category: b, field: id, pattern: '('}",
+                "rule `a`, `id` pattern: regex parse error",
+            ),
+            (
+                "stage: before_resolution\nrules:\n- {id: a, category: b, field: id, severity: fatal}",
+                "rules[0].severity: unknown variant `fatal`",
+            ),
+        ] {
+            match LintPolicy::parse("test.lint.yaml", content) {
+                Err(Error::InvalidPolicyFile {
+                    file,
+                    error: observed,
+                }) => {
+                    assert_eq!(file, "test.lint.yaml");
+                    assert!(observed.starts_with(error), "{}", observed);
+                }
+                result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+            }
+        }
+    }
+}
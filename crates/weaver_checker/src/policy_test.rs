@@ -0,0 +1,393 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Policy tests, evaluated by the same [`Engine`] and stages as the checks.
+//!
+//! Two kinds of tests are supported:
+//! - The Rego test rules, i.e. the rules named `test_*` of the Rego policy
+//!   files (usually `*_test.rego`), which pass when they evaluate to `true`.
+//!   They are discovered when the policies are added to the engine and
+//!   typically set their input with `with input as ...`.
+//! - The test fixtures of the `*.test.yaml` (or `*.test.yml`) files, which
+//!   evaluate a policy stage on an input and compare the reported violations
+//!   to the expected ones. They apply to all the policies of the engine (Rego,
+//!   CEL, and lint policies):
+//!
+//! ```yaml
+//! tests:
+//!   - name: registry groups don't reference attributes
+//!     stage: before_resolution
+//!     input:
+//!       groups:
+//!         - id: registry.network
+//!           type: attribute_group
+//!           attributes:
+//!             - ref: network.protocol.name
+//!     expect:
+//!       - id: registry_with_ref_attr
+//!         attr: network.protocol.name
+//! ```
+//!
+//! The input can also be loaded from a YAML or JSON file with `input_file`,
+//! relative to the fixture file, and an optional `data` document is added to
+//! the engine (e.g. the baseline registry of the `comparison_after_resolution`
+//! stage). Each expected violation lists the fields that must match a reported
+//! violation. The test fails when an expected violation is not reported or
+//! when a reported violation is not expected.
+
+use std::path::{Path, PathBuf};
+
+use globset::Glob;
+use regorus::unstable::{Expr, Module, Rule, RuleHead};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use weaver_common::error::handle_errors;
+
+use crate::violation::Violation;
+use crate::{Engine, Error, PolicyStage};
+
+/// The glob pattern of the policy test fixture files.
+pub const POLICY_TEST_GLOB: &str = "*.test.{yaml,yml}";
+
+/// A policy test.
+#[derive(Debug, Clone)]
+pub struct PolicyTest {
+    /// The file defining the test.
+    pub file: String,
+    /// The name of the test.
+    pub name: String,
+    kind: PolicyTestKind,
+}
+
+/// The kinds of policy tests.
+#[derive(Debug, Clone)]
+enum PolicyTestKind {
+    /// A Rego test rule, identified by its package (e.g. `data.after_resolution`).
+    Rego { package: String },
+    /// A test fixture.
+    Fixture(Box<Fixture>),
+}
+
+/// The content of a test fixture file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FixtureFile {
+    /// The test fixtures.
+    tests: Vec<FixtureSpec>,
+}
+
+/// A test fixture of a test fixture file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FixtureSpec {
+    /// The name of the test.
+    name: String,
+    /// The evaluated policy stage.
+    stage: PolicyStage,
+    /// The input document.
+    input: Option<Value>,
+    /// The file of the input document, relative to the fixture file.
+    input_file: Option<PathBuf>,
+    /// The data document.
+    data: Option<Value>,
+    /// The expected violations.
+    #[serde(default)]
+    expect: Vec<Map<String, Value>>,
+}
+
+/// A loaded test fixture.
+#[derive(Debug, Clone)]
+struct Fixture {
+    stage: PolicyStage,
+    input: Value,
+    data: Option<Value>,
+    expect: Vec<Map<String, Value>>,
+}
+
+/// Returns the Rego test rules defined by a parsed Rego policy module.
+///
+/// The test rules are the non-function rules whose name starts with `test_`,
+/// including the `default` rules and the rules with an indented head.
+pub(crate) fn rego_tests(file: &str, package: &str, module: &Module) -> Vec<PolicyTest> {
+    let mut tests: Vec<PolicyTest> = vec![];
+    for rule in &module.policy {
+        let refr = match rule.as_ref() {
+            Rule::Spec {
+                head: RuleHead::Compr { refr, .. },
+                ..
+            } => refr,
+            Rule::Default { refr, args, .. } if args.is_empty() => refr,
+            _ => continue,
+        };
+        let Expr::Var((span, _)) = refr.as_ref() else {
+            continue;
+        };
+        let name = span.text();
+        // A rule can be defined incrementally by several rule heads.
+        if name.starts_with("test_") && tests.iter().all(|test| test.name != name) {
+            tests.push(PolicyTest {
+                file: file.to_owned(),
+                name: name.to_owned(),
+                kind: PolicyTestKind::Rego {
+                    package: package.to_owned(),
+                },
+            });
+        }
+    }
+    tests
+}
+
+/// Loads the test fixtures of a fixture file, or of all the fixture files of
+/// a directory (recursively).
+pub fn load_policy_tests<P: AsRef<Path>>(path: P) -> Result<Vec<PolicyTest>, Error> {
+    let path = path.as_ref();
+    if path.is_file() {
+        return load_fixture_file(path);
+    }
+    let test_glob = Glob::new(POLICY_TEST_GLOB)
+        .map_err(|e| Error::InvalidPolicyGlobPattern {
+            pattern: POLICY_TEST_GLOB.to_owned(),
+            error: e.to_string(),
+        })?
+        .compile_matcher();
+    let mut tests = vec![];
+    let mut errors = vec![];
+    for entry in walkdir::WalkDir::new(path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .flatten()
+    {
+        if entry.file_type().is_file() && test_glob.is_match(entry.path()) {
+            match load_fixture_file(entry.path()) {
+                Ok(file_tests) => tests.extend(file_tests),
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+    handle_errors(errors)?;
+    Ok(tests)
+}
+
+/// Loads the test fixtures of a fixture file.
+fn load_fixture_file(path: &Path) -> Result<Vec<PolicyTest>, Error> {
+    let file = path.to_string_lossy().to_string();
+    let invalid = |error: String| Error::InvalidPolicyTestFile {
+        file: file.clone(),
+        error,
+    };
+    let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    let fixture_file: FixtureFile =
+        serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+    fixture_file
+        .tests
+        .into_iter()
+        .map(|spec| {
+            let input = match (spec.input, spec.input_file) {
+                (Some(input), None) => input,
+                (None, Some(input_file)) => {
+                    let input_path = path.parent().unwrap_or(Path::new("")).join(input_file);
+                    let content = std::fs::read_to_string(&input_path).map_err(|e| {
+                        invalid(format!(
+                            "test `{}`, input file {:?}: {}",
+                            spec.name, input_path, e
+                        ))
+                    })?;
+                    serde_yaml::from_str(&content).map_err(|e| {
+                        invalid(format!(
+                            "test `{}`, input file {:?}: {}",
+                            spec.name, input_path, e
+                        ))
+                    })?
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "test `{}` must define either `input` or `input_file`",
+                        spec.name
+                    )))
+                }
+            };
+            Ok(PolicyTest {
+                file: file.clone(),
+                name: spec.name,
+                kind: PolicyTestKind::Fixture(Box::new(Fixture {
+                    stage: spec.stage,
+                    input,
+                    data: spec.data,
+                    expect: spec.expect,
+                })),
+            })
+        })
+        .collect()
+}
+
+impl PolicyTest {
+    /// Runs the test with a copy of the given engine, and returns a
+    /// [`Error::PolicyTestFailed`] error describing the differences if the
+    /// test fails.
+    pub fn run(&self, engine: &Engine) -> Result<(), Error> {
+        let mut engine = engine.clone();
+        let failed = |error: String| Error::PolicyTestFailed {
+            file: self.file.clone(),
+            test: self.name.clone(),
+            error,
+        };
+        match &self.kind {
+            PolicyTestKind::Rego { package } => {
+                if !engine.eval_rego_test(&format!("{}.{}", package, self.name))? {
+                    return Err(failed("the test rule didn't evaluate to `true`".to_owned()));
+                }
+            }
+            PolicyTestKind::Fixture(fixture) => {
+                if let Some(data) = &fixture.data {
+                    engine.add_data(data)?;
+                }
+                engine.set_input(&fixture.input)?;
+                let violations = engine.check(fixture.stage)?;
+                let diff = diff_violations(&fixture.expect, &violations);
+                if !diff.is_empty() {
+                    return Err(failed(diff));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the differences between the expected and the reported violations,
+/// one line per difference: `- <expected>` for the expected violations that
+/// are not reported, and `+ <violation>` for the reported violations that are
+/// not expected. Returns an empty string if there are no differences.
+fn diff_violations(expect: &[Map<String, Value>], violations: &[Violation]) -> String {
+    let mut unexpected: Vec<Value> = violations
+        .iter()
+        .map(|violation| {
+            let mut value = serde_json::to_value(violation).unwrap_or_default();
+            // The default severity is not serialized.
+            if let Value::Object(fields) = &mut value {
+                _ = fields.insert(
+                    "severity".to_owned(),
+                    Value::String(violation.level().to_string()),
+                );
+            }
+            value
+        })
+        .collect();
+    let mut lines = vec![];
+    for expected in expect {
+        let position = unexpected.iter().position(|violation| {
+            expected
+                .iter()
+                .all(|(name, value)| violation.get(name) == Some(value))
+        });
+        match position {
+            Some(position) => _ = unexpected.remove(position),
+            None => lines.push(format!("- {}", Value::Object(expected.clone()))),
+        }
+    }
+    lines.extend(
+        unexpected
+            .iter()
+            .map(|violation| format!("+ {}", violation)),
+    );
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rego_tests() {
+        let rego = r#"
+package after_resolution
+
+test_metric_unit {
+    count(deny) == 0 with input as {"groups": []}
+}
+
+test_metric_unit {
+    true
+}
+
+test_span_kind := true
+
+  test_indented {
+      true
+  }
+
+default test_default := true
+
+test_helper(x) := x
+
+deny[x] { false }
+"#;
+        let mut engine = regorus::Engine::new();
+        let package = engine
+            .add_policy("test.rego".to_owned(), rego.to_owned())
+            .expect("Invalid policy");
+        let module = engine.get_modules().last().expect("No module");
+        let tests = rego_tests("test.rego", &package, module);
+        let names: Vec<_> = tests.iter().map(|test| test.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "test_metric_unit",
+                "test_span_kind",
+                "test_indented",
+                "test_default"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_policy_tests() -> Result<(), Box<dyn std::error::Error>> {
+        let mut engine = Engine::new();
+        engine.add_policy_from_file_or_dir("data/policy_tests")?;
+        let mut tests = engine.policy_tests().to_vec();
+        tests.extend(load_policy_tests("data/policy_tests")?);
+
+        let results: Vec<_> = tests
+            .iter()
+            .map(|test| {
+                let result = match test.run(&engine) {
+                    Ok(()) => "passed".to_owned(),
+                    Err(Error::PolicyTestFailed { error, .. }) => error,
+                    Err(e) => panic!("Unexpected error: {}", e),
+                };
+                (test.name.as_str(), result)
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("test_ref_attr_denied", "passed".to_owned()),
+                ("test_defined_attr_allowed", "passed".to_owned()),
+                (
+                    "test_wrong_expectation",
+                    "the test rule didn't evaluate to `true`".to_owned()
+                ),
+                ("ref attribute in a registry group", "passed".to_owned()),
+                ("attribute ids are snake case", "passed".to_owned()),
+                (
+                    "missing and unexpected violations",
+                    "- {\"attr\":\"a.missing\",\"id\":\"registry_with_ref_attr\"}\n\
+                     + {\"attr\":\"a.Upper\",\"category\":\"naming\",\"group\":\"registry.a\",\"id\":\"attr_id_snake_case\",\"severity\":\"warning\",\"type\":\"semconv_attribute\"}"
+                        .to_owned()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_policy_tests() {
+        let result = load_policy_tests("data/policies/invalid_fixture.test.yaml");
+        assert!(
+            matches!(result, Err(Error::InvalidPolicyTestFile { ref error, .. }) if error.contains("either `input` or `input_file`")),
+            "{:?}",
+            result
+        );
+    }
+}
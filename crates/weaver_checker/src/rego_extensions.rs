@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Built-in Rego functions available to all the Rego policies, so that the
+//! policy bundles don't have to re-implement the same string logic:
+//!
+//! - `weaver.valid_attribute_name(name)`: true if the name is made of
+//!   dot-separated snake case segments (e.g. `http.request.method`).
+//! - `weaver.namespace_of(name)`: the namespace of a name, i.e. the part
+//!   before the last dot (e.g. `http.request` for `http.request.method`), or an
+//!   empty string for a name without namespace.
+//! - `weaver.is_stable(x)`: true if the stability (or the `stability` field of
+//!   an attribute or a group) is `stable`.
+//! - `weaver.is_snake_case(text)`, `weaver.is_screaming_snake_case(text)`,
+//!   `weaver.is_kebab_case(text)`, `weaver.is_camel_case(text)` and
+//!   `weaver.is_pascal_case(text)`: case-convention checks.
+
+use regex::Regex;
+use regorus::Value;
+
+/// The case conventions checked by the `weaver.is_<case>` functions.
+const CASE_CONVENTIONS: [(&str, &str); 5] = [
+    ("snake_case", r"^[a-z][a-z0-9]*(_[a-z0-9]+)*$"),
+    ("screaming_snake_case", r"^[A-Z][A-Z0-9]*(_[A-Z0-9]+)*$"),
+    ("kebab_case", r"^[a-z][a-z0-9]*(-[a-z0-9]+)*$"),
+    ("camel_case", r"^[a-z][a-z0-9]*([A-Z][a-z0-9]*)*$"),
+    ("pascal_case", r"^([A-Z][a-z0-9]*)+$"),
+];
+
+/// The regex of the valid attribute names.
+const ATTRIBUTE_NAME_REGEX: &str = r"^[a-z][a-z0-9]*(_[a-z0-9]+)*(\.[a-z][a-z0-9]*(_[a-z0-9]+)*)*$";
+
+/// Adds the built-in `weaver.*` functions to a `regorus` engine.
+pub(crate) fn add_weaver_extensions(engine: &mut regorus::Engine) -> anyhow::Result<()> {
+    let name_regex = Regex::new(ATTRIBUTE_NAME_REGEX).expect("Invalid attribute name regex");
+    engine.add_extension(
+        "weaver.valid_attribute_name".to_owned(),
+        1,
+        Box::new(move |args: Vec<Value>| {
+            let name = string_arg("weaver.valid_attribute_name", &args)?;
+            Ok(Value::from(name_regex.is_match(name)))
+        }),
+    )?;
+    engine.add_extension(
+        "weaver.namespace_of".to_owned(),
+        1,
+        Box::new(|args: Vec<Value>| {
+            let name = string_arg("weaver.namespace_of", &args)?;
+            let namespace = name.rsplit_once('.').map_or("", |(namespace, _)| namespace);
+            Ok(Value::from(namespace.to_owned()))
+        }),
+    )?;
+    engine.add_extension(
+        "weaver.is_stable".to_owned(),
+        1,
+        Box::new(|args: Vec<Value>| {
+            let stability = match &args[0] {
+                Value::Object(fields) => fields
+                    .get(&Value::from("stability"))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+                value => value.clone(),
+            };
+            Ok(Value::from(
+                matches!(&stability, Value::String(text) if text.as_ref() == "stable"),
+            ))
+        }),
+    )?;
+    for (case, pattern) in CASE_CONVENTIONS {
+        let function = format!("weaver.is_{}", case);
+        let case_regex = Regex::new(pattern).expect("Invalid case convention regex");
+        engine.add_extension(
+            function.clone(),
+            1,
+            Box::new(move |args: Vec<Value>| {
+                let text = string_arg(&function, &args)?;
+                Ok(Value::from(case_regex.is_match(text)))
+            }),
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the string argument of a function.
+fn string_arg<'a>(function: &str, args: &'a [Value]) -> anyhow::Result<&'a str> {
+    match args.first() {
+        Some(Value::String(text)) => Ok(text.as_ref()),
+        _ => anyhow::bail!("`{}` expects a string argument", function),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Engine;
+
+    #[test]
+    fn test_weaver_extensions() {
+        let mut engine = Engine::new();
+        _ = engine
+            .add_policy(
+                "extensions_test.rego",
+                r#"
+package extensions_test
+
+import rego.v1
+
+test_valid_attribute_name if {
+    weaver.valid_attribute_name("http.request.method")
+    weaver.valid_attribute_name("url")
+    not weaver.valid_attribute_name("http.Request")
+    not weaver.valid_attribute_name("http..method")
+    not weaver.valid_attribute_name("http.request_")
+}
+
+test_namespace_of if {
+    weaver.namespace_of("http.request.method") == "http.request"
+    weaver.namespace_of("url") == ""
+}
+
+test_is_stable if {
+    weaver.is_stable("stable")
+    weaver.is_stable({"id": "url.path", "stability": "stable"})
+    not weaver.is_stable({"id": "url.path", "stability": "development"})
+    not weaver.is_stable({"id": "url.path"})
+}
+
+test_case_conventions if {
+    weaver.is_snake_case("request_method")
+    not weaver.is_snake_case("requestMethod")
+    weaver.is_screaming_snake_case("REQUEST_METHOD")
+    weaver.is_kebab_case("request-method")
+    weaver.is_camel_case("requestMethod")
+    not weaver.is_camel_case("RequestMethod")
+    weaver.is_pascal_case("RequestMethod")
+}
+"#,
+            )
+            .unwrap();
+        for test in engine.policy_tests().to_vec() {
+            assert!(
+                engine
+                    .eval_rego_test(&format!("data.extensions_test.{}", test.name))
+                    .unwrap(),
+                "{}",
+                test.name
+            );
+        }
+        assert_eq!(engine.policy_tests().len(), 4);
+    }
+}
@@ -0,0 +1,388 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sandbox of the Rego policies, to safely evaluate third-party policy
+//! bundles (e.g. in CI):
+//!
+//! - The Rego policies calling a denied built-in function are rejected when
+//!   they are added to the engine. [`Sandbox::restricted`] denies the built-in
+//!   functions accessing the network or returning non-deterministic values.
+//!   `opa.runtime` is never available, as the `opa-runtime` feature of
+//!   `regorus` is disabled.
+//! - The evaluation of a policy package exceeding its time or memory limit
+//!   is reported as an error.
+//!
+//! Note: `regorus` can't interrupt an evaluation, so the limited evaluations
+//! run on a worker thread watched by the calling thread. An evaluation
+//! exceeding its limits is abandoned: it runs until completion (or until the
+//! process exits) and the engine can't evaluate the Rego policies anymore.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regorus::unstable::{Expr, Literal, Parser, Query, Rule, RuleHead, Source};
+
+use crate::Error;
+
+/// The built-in functions accessing the network or returning
+/// non-deterministic values.
+pub const RESTRICTED_BUILTINS: [&str; 4] =
+    ["http.send", "rand.intn", "time.now_ns", "uuid.rfc4122"];
+
+/// The interval at which the limits of an evaluation are checked.
+const LIMITS_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The evaluation limits of a Rego policy package.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvalLimits {
+    /// The maximum duration of an evaluation.
+    pub timeout: Option<Duration>,
+    /// The maximum growth, in bytes, of the resident memory of the process
+    /// during an evaluation. Only measured on Linux.
+    pub max_memory: Option<u64>,
+}
+
+impl EvalLimits {
+    /// Returns true if no limit is set.
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        self.timeout.is_none() && self.max_memory.is_none()
+    }
+}
+
+/// The sandbox of the Rego policies.
+#[derive(Debug, Clone, Default)]
+pub struct Sandbox {
+    /// The built-in functions the policies can't call.
+    pub denied_builtins: BTreeSet<String>,
+    /// The evaluation limits of the policy packages without specific limits.
+    pub limits: EvalLimits,
+    /// The evaluation limits of specific policy packages (e.g.
+    /// `after_resolution`).
+    pub package_limits: BTreeMap<String, EvalLimits>,
+}
+
+impl Sandbox {
+    /// Returns a sandbox denying the [`RESTRICTED_BUILTINS`], without
+    /// evaluation limits.
+    #[must_use]
+    pub fn restricted() -> Self {
+        Self {
+            denied_builtins: RESTRICTED_BUILTINS
+                .iter()
+                .map(|builtin| (*builtin).to_owned())
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the evaluation limits of a policy package.
+    #[must_use]
+    pub fn limits(&self, package: &str) -> EvalLimits {
+        self.package_limits
+            .get(package)
+            .copied()
+            .unwrap_or(self.limits)
+    }
+
+    /// Returns an error if a Rego policy calls a denied built-in function, or
+    /// if the policy can't be parsed to check it.
+    pub(crate) fn check_capabilities(&self, file: &str, rego: &str) -> Result<(), Error> {
+        if self.denied_builtins.is_empty() {
+            return Ok(());
+        }
+        let module = Source::from_contents(file.to_owned(), rego.to_owned())
+            .and_then(|source| Parser::new(&source)?.parse())
+            .map_err(|e| Error::InvalidPolicyFile {
+                file: file.to_owned(),
+                error: e.to_string(),
+            })?;
+        let mut calls = BTreeSet::new();
+        for rule in &module.policy {
+            rule_calls(rule, &mut calls);
+        }
+        match self
+            .denied_builtins
+            .iter()
+            .find(|builtin| calls.contains(*builtin))
+        {
+            Some(builtin) => Err(Error::DeniedBuiltin {
+                file: file.to_owned(),
+                builtin: builtin.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Evaluates a rule of a policy package on a worker thread, within the
+/// given limits. The engine is moved to the worker thread, and returned with
+/// the result of the evaluation. If the evaluation exceeds its limits, it's
+/// abandoned with the engine.
+pub(crate) fn eval_rule_within_limits(
+    mut engine: regorus::Engine,
+    package: &str,
+    rule: String,
+    limits: EvalLimits,
+) -> Result<(regorus::Engine, anyhow::Result<regorus::Value>), Error> {
+    let limit_exceeded = |limit: String| Error::PolicyLimitExceeded {
+        package: package.to_owned(),
+        limit,
+    };
+    let evaluation_error = |error: String| Error::ViolationEvaluationError { error };
+    let (sender, receiver) = mpsc::channel();
+    _ = thread::Builder::new()
+        .name("rego-evaluation".to_owned())
+        .spawn(move || {
+            let result = engine.eval_rule(rule);
+            // The receiver is gone if the evaluation was abandoned.
+            _ = sender.send((engine, result));
+        })
+        .map_err(|e| evaluation_error(e.to_string()))?;
+
+    let start = Instant::now();
+    let initial_memory = limits.max_memory.and(resident_memory());
+    loop {
+        match receiver.recv_timeout(LIMITS_CHECK_INTERVAL) {
+            Ok(evaluation) => return Ok(evaluation),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(evaluation_error(format!(
+                    "The evaluation of the policy package '{package}' panicked"
+                )))
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        if let Some(timeout) = limits.timeout.filter(|timeout| start.elapsed() > *timeout) {
+            return Err(limit_exceeded(format!(
+                "the time limit of {} ms",
+                timeout.as_millis()
+            )));
+        }
+        if let (Some(max_memory), Some(initial_memory), Some(memory)) =
+            (limits.max_memory, initial_memory, resident_memory())
+        {
+            if memory.saturating_sub(initial_memory) > max_memory {
+                return Err(limit_exceeded(format!(
+                    "the memory limit of {} bytes",
+                    max_memory
+                )));
+            }
+        }
+    }
+}
+
+/// Returns the resident memory of the process in bytes, or None if it can't
+/// be measured (i.e. outside of Linux).
+fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Collects the functions called by a rule.
+fn rule_calls(rule: &Rule, calls: &mut BTreeSet<String>) {
+    match rule {
+        Rule::Spec { head, bodies, .. } => {
+            match head {
+                RuleHead::Compr { refr, assign, .. } => {
+                    expr_calls(refr, calls);
+                    if let Some(assign) = assign {
+                        expr_calls(&assign.value, calls);
+                    }
+                }
+                RuleHead::Set { refr, key, .. } => {
+                    expr_calls(refr, calls);
+                    if let Some(key) = key {
+                        expr_calls(key, calls);
+                    }
+                }
+                RuleHead::Func {
+                    refr, args, assign, ..
+                } => {
+                    expr_calls(refr, calls);
+                    for arg in args {
+                        expr_calls(arg, calls);
+                    }
+                    if let Some(assign) = assign {
+                        expr_calls(&assign.value, calls);
+                    }
+                }
+            }
+            for body in bodies {
+                if let Some(assign) = &body.assign {
+                    expr_calls(&assign.value, calls);
+                }
+                query_calls(&body.query, calls);
+            }
+        }
+        Rule::Default { args, value, .. } => {
+            for arg in args {
+                expr_calls(arg, calls);
+            }
+            expr_calls(value, calls);
+        }
+    }
+}
+
+/// Collects the functions called by a query.
+fn query_calls(query: &Query, calls: &mut BTreeSet<String>) {
+    for stmt in &query.stmts {
+        match &stmt.literal {
+            Literal::SomeVars { .. } => {}
+            Literal::SomeIn {
+                key,
+                value,
+                collection,
+                ..
+            } => {
+                if let Some(key) = key {
+                    expr_calls(key, calls);
+                }
+                expr_calls(value, calls);
+                expr_calls(collection, calls);
+            }
+            Literal::Expr { expr, .. } | Literal::NotExpr { expr, .. } => expr_calls(expr, calls),
+            Literal::Every { domain, query, .. } => {
+                expr_calls(domain, calls);
+                query_calls(query, calls);
+            }
+        }
+        // A `with` modifier replacing a function doesn't call it.
+        for with_mod in &stmt.with_mods {
+            expr_calls(&with_mod.r#as, calls);
+        }
+    }
+}
+
+/// Collects the functions called by an expression.
+fn expr_calls(expr: &Expr, calls: &mut BTreeSet<String>) {
+    match expr {
+        Expr::String(_)
+        | Expr::RawString(_)
+        | Expr::Number(_)
+        | Expr::True(_)
+        | Expr::False(_)
+        | Expr::Null(_)
+        | Expr::Var(_) => {}
+        Expr::Array { items, .. } | Expr::Set { items, .. } => {
+            for item in items {
+                expr_calls(item, calls);
+            }
+        }
+        Expr::Object { fields, .. } => {
+            for (_, key, value) in fields {
+                expr_calls(key, calls);
+                expr_calls(value, calls);
+            }
+        }
+        Expr::ArrayCompr { term, query, .. } | Expr::SetCompr { term, query, .. } => {
+            expr_calls(term, calls);
+            query_calls(query, calls);
+        }
+        Expr::ObjectCompr {
+            key, value, query, ..
+        } => {
+            expr_calls(key, calls);
+            expr_calls(value, calls);
+            query_calls(query, calls);
+        }
+        Expr::Call { fcn, params, .. } => {
+            if let Some(path) = function_path(fcn) {
+                _ = calls.insert(path);
+            }
+            for param in params {
+                expr_calls(param, calls);
+            }
+        }
+        Expr::UnaryExpr { expr, .. } => expr_calls(expr, calls),
+        Expr::RefDot { refr, .. } => expr_calls(refr, calls),
+        Expr::RefBrack { refr, index, .. } => {
+            expr_calls(refr, calls);
+            expr_calls(index, calls);
+        }
+        Expr::BinExpr { lhs, rhs, .. }
+        | Expr::BoolExpr { lhs, rhs, .. }
+        | Expr::ArithExpr { lhs, rhs, .. }
+        | Expr::AssignExpr { lhs, rhs, .. } => {
+            expr_calls(lhs, calls);
+            expr_calls(rhs, calls);
+        }
+        Expr::Membership {
+            key,
+            value,
+            collection,
+            ..
+        } => {
+            if let Some(key) = key {
+                expr_calls(key, calls);
+            }
+            expr_calls(value, calls);
+            expr_calls(collection, calls);
+        }
+    }
+}
+
+/// Returns the dotted path of a called function (e.g. `time.now_ns`).
+fn function_path(fcn: &Expr) -> Option<String> {
+    match fcn {
+        Expr::Var((span, _)) => Some(span.text().to_owned()),
+        Expr::RefDot {
+            refr,
+            field: (span, _),
+            ..
+        } => Some(format!("{}.{}", function_path(refr)?, span.text())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_capabilities() {
+        let sandbox = Sandbox::restricted();
+        assert!(sandbox
+            .check_capabilities(
+                "ok.rego",
+                "package test\n\nimport rego.v1\n\nallow if { count(input.groups) > 0 }"
+            )
+            .is_ok());
+        // The denied built-in functions can be mentioned in the comments and
+        // the strings.
+        assert!(sandbox
+            .check_capabilities(
+                "comment.rego",
+                "package test\n\n# Don't call time.now_ns()\nallow := \"time.now_ns()\"\n"
+            )
+            .is_ok());
+        // A `#` in a string doesn't hide the rest of the line.
+        assert!(matches!(
+            sandbox.check_capabilities(
+                "hash.rego",
+                "package test\n\nnow := [\"#\", time.now_ns()]\n"
+            ),
+            Err(Error::DeniedBuiltin { builtin, .. }) if builtin == "time.now_ns"
+        ));
+        // The calls are found in the nested expressions and queries.
+        assert!(matches!(
+            sandbox.check_capabilities(
+                "nested.rego",
+                "package test\n\nimport rego.v1\n\nids := [id | some x in input.groups; id := concat(\"-\", [x.id, sprintf(\"%d\", [rand.intn(\"seed\", 10)])])]\n"
+            ),
+            Err(Error::DeniedBuiltin { builtin, .. }) if builtin == "rand.intn"
+        ));
+        // No built-in function is denied by default.
+        assert!(Sandbox::default()
+            .check_capabilities("time.rego", "package test\n\nnow := time.now_ns()\n")
+            .is_ok());
+    }
+}
@@ -2,8 +2,10 @@
 
 //! Definition of a policy violation.
 
+use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 /// Enum representing the different types of violations.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,9 +22,74 @@ pub enum Violation {
         group: String,
         /// The semconv attribute where the violation occurred.
         attr: String,
+        /// The severity of the policy violation.
+        #[serde(default, skip_serializing_if = "Severity::is_violation")]
+        severity: Severity,
+        /// The machine-applicable fix suggested by the policy, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fix: Option<Box<Fix>>,
     },
 }
 
+/// A fix suggested by a policy, setting a field of the semconv attribute of
+/// the violation (or of its group when `attr` is empty) in the spec file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Fix {
+    /// The YAML path of the fixed field, relative to the attribute or the
+    /// group of the violation (e.g. `stability`).
+    pub path: String,
+    /// The replacement value of the field.
+    pub value: serde_json::Value,
+}
+
+/// The severity of a policy violation.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// An informational finding.
+    Info,
+    /// A finding that should be fixed.
+    Warning,
+    /// A violation of the policy (the default).
+    #[default]
+    Violation,
+}
+
+impl Severity {
+    /// Returns true if the severity is the default one, i.e. `violation`.
+    #[must_use]
+    pub fn is_violation(&self) -> bool {
+        *self == Severity::Violation
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Violation => write!(f, "violation"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "violation" => Ok(Severity::Violation),
+            other => Err(format!(
+                "Invalid severity `{}`, expected `info`, `warning` or `violation`",
+                other
+            )),
+        }
+    }
+}
+
 impl Display for Violation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -31,6 +98,7 @@ impl Display for Violation {
                 category,
                 group,
                 attr,
+                ..
             } => {
                 write!(
                     f,
@@ -50,4 +118,120 @@ impl Violation {
             Violation::SemconvAttribute { id, .. } => id,
         }
     }
+
+    /// Returns the violation severity level.
+    #[must_use]
+    pub fn level(&self) -> Severity {
+        match self {
+            Violation::SemconvAttribute { severity, .. } => *severity,
+        }
+    }
+
+    /// Returns the fix suggested by the policy, if any.
+    #[must_use]
+    pub fn fix(&self) -> Option<&Fix> {
+        match self {
+            Violation::SemconvAttribute { fix, .. } => fix.as_deref(),
+        }
+    }
+}
+
+/// A violation reported as a diagnostic, with the minimum severity of the
+/// violations failing the command (see [`crate::Engine::set_fail_on`]).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct ReportedViolation {
+    violation: Violation,
+    /// The minimum severity of the violations reported as errors.
+    #[serde(skip)]
+    fail_on: Severity,
+}
+
+impl ReportedViolation {
+    /// Creates a reported violation. The violations with a lower severity
+    /// than `fail_on` are reported as warnings (`warning`) or advices
+    /// (`info`), and don't fail the commands.
+    #[must_use]
+    pub fn new(violation: Violation, fail_on: Severity) -> Self {
+        Self { violation, fail_on }
+    }
+
+    /// Returns the violation.
+    #[must_use]
+    pub fn violation(&self) -> &Violation {
+        &self.violation
+    }
+}
+
+impl Display for ReportedViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.violation.fmt(f)
+    }
+}
+
+impl std::error::Error for ReportedViolation {}
+
+impl Diagnostic for ReportedViolation {
+    /// Returns `error` for the violations whose severity reaches the
+    /// `fail_on` threshold, and `warning` or `advice` for the others.
+    fn severity(&self) -> Option<miette::Severity> {
+        let level = self.violation.level();
+        Some(if level >= self.fail_on {
+            miette::Severity::Error
+        } else if level == Severity::Warning {
+            miette::Severity::Warning
+        } else {
+            miette::Severity::Advice
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violation_diagnostic_severity() {
+        let violation = |severity| Violation::SemconvAttribute {
+            id: "id".to_owned(),
+            category: "category".to_owned(),
+            group: "group".to_owned(),
+            attr: "attr".to_owned(),
+            severity,
+            fix: None,
+        };
+        let diagnostic_severities = |fail_on| {
+            [Severity::Info, Severity::Warning, Severity::Violation].map(|severity| {
+                Diagnostic::severity(&ReportedViolation::new(violation(severity), fail_on))
+            })
+        };
+
+        assert_eq!(
+            diagnostic_severities(Severity::Violation),
+            [
+                Some(miette::Severity::Advice),
+                Some(miette::Severity::Warning),
+                Some(miette::Severity::Error)
+            ]
+        );
+        assert_eq!(
+            diagnostic_severities(Severity::Warning),
+            [
+                Some(miette::Severity::Advice),
+                Some(miette::Severity::Error),
+                Some(miette::Severity::Error)
+            ]
+        );
+
+        // The threshold is not serialized.
+        let reported = ReportedViolation::new(violation(Severity::Warning), Severity::Info);
+        assert_eq!(
+            serde_json::to_value(&reported).unwrap(),
+            serde_json::to_value(violation(Severity::Warning)).unwrap()
+        );
+
+        assert_eq!("warning".parse(), Ok(Severity::Warning));
+        assert_eq!(Severity::Info.to_string(), "info");
+        assert!("error".parse::<Severity>().is_err());
+    }
 }
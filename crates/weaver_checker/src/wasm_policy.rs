@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! WASM policies, i.e. policies compiled to WebAssembly modules (`*.wasm`)
+//! from a language targeting `wasm32-unknown-unknown` without host imports
+//! (e.g. Rust), for the checks that are hard to express in Rego and that are
+//! distributed as binaries.
+//!
+//! A WASM policy is evaluated at a single stage and implements the following
+//! interface (the strings are UTF-8 and the `(ptr, len)` pairs are packed in
+//! an `i64` as `ptr << 32 | len`):
+//!
+//! - `memory`: the exported linear memory of the module.
+//! - `alloc(len: i32) -> i32`: allocates `len` bytes in the memory and returns
+//!   their pointer.
+//! - `stage() -> i64`: returns the stage of the policy (e.g.
+//!   `before_resolution`).
+//! - `check(ptr: i32, len: i32) -> i64`: checks the JSON document
+//!   `{"input": ..., "data": ...}` written at `ptr` by the engine, and returns
+//!   the JSON array of the violations (same format as the Rego violations).
+//!
+//! The modules can't import any host function, so they have no access to the
+//! file system, the network, or the environment. A new instance of the module
+//! is created for each evaluation, with a budget of [`FUEL_BUDGET`] units of
+//! fuel (roughly one per executed instruction) and a linear memory limited to
+//! [`MEMORY_LIMIT`] bytes: the evaluation exceeding them is stopped and
+//! reported as an error.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use wasmi::{Config, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::violation::Violation;
+use crate::{Error, PolicyStage};
+
+/// The fuel budget of an evaluation of a WASM policy.
+pub(crate) const FUEL_BUDGET: u64 = 1_000_000_000;
+
+/// The maximum size in bytes of the linear memory of a WASM policy instance.
+pub(crate) const MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Returns true if the path is a WASM policy file.
+pub(crate) fn is_wasm_policy_file(path: &str) -> bool {
+    path.ends_with(".wasm")
+}
+
+/// A compiled WASM policy.
+#[derive(Debug, Clone)]
+pub(crate) struct WasmPolicy {
+    /// The path of the policy (used for error messages).
+    path: String,
+    /// The stage of the policy.
+    pub(crate) stage: PolicyStage,
+    /// The fuel budget of an evaluation.
+    fuel: u64,
+    /// The maximum size in bytes of the linear memory of an instance.
+    memory_limit: usize,
+    engine: wasmi::Engine,
+    module: Arc<Module>,
+}
+
+impl WasmPolicy {
+    /// Compiles a WASM policy and reads its stage.
+    pub(crate) fn parse(path: &str, wasm: &[u8]) -> Result<Self, Error> {
+        let invalid = |error: String| Error::InvalidPolicyFile {
+            file: path.to_owned(),
+            error,
+        };
+        let mut config = Config::default();
+        _ = config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(|e| invalid(e.to_string()))?;
+        let mut policy = Self {
+            path: path.to_owned(),
+            stage: PolicyStage::BeforeResolution,
+            fuel: FUEL_BUDGET,
+            memory_limit: MEMORY_LIMIT,
+            engine,
+            module: Arc::new(module),
+        };
+        let (mut store, instance) = policy.instantiate().map_err(invalid)?;
+        let stage = call_stage(&mut store, &instance).map_err(invalid)?;
+        policy.stage = serde_json::from_value(Value::String(stage.clone()))
+            .map_err(|_| invalid(format!("unknown policy stage `{}`", stage)))?;
+        Ok(policy)
+    }
+
+    /// Evaluates the policy and returns the violations.
+    pub(crate) fn check(&self, input: &Value, data: &Value) -> Result<Vec<Violation>, Error> {
+        let failed = |error: String| Error::ViolationEvaluationError {
+            error: format!("{} (WASM policy `{}`)", error, self.path),
+        };
+        let document = format!(r#"{{"input":{},"data":{}}}"#, input, data);
+        let (mut store, instance) = self.instantiate().map_err(failed)?;
+        let violations = call_check(&mut store, &instance, document.as_bytes()).map_err(failed)?;
+        serde_json::from_slice(&violations)
+            .map_err(|e| failed(format!("invalid violations returned by `check`: {}", e)))
+    }
+
+    /// Creates a new instance of the module.
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance), String> {
+        // A `memory.grow` exceeding the limit traps instead of returning -1.
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.memory_limit)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.add_fuel(self.fuel).map_err(|e| e.to_string())?;
+        let linker = Linker::<StoreLimits>::new(&self.engine);
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| e.to_string())?;
+        Ok((store, instance))
+    }
+}
+
+/// Calls the `stage` function of an instance and returns the stage name.
+fn call_stage(store: &mut Store<StoreLimits>, instance: &Instance) -> Result<String, String> {
+    let stage = instance
+        .get_typed_func::<(), i64>(&*store, "stage")
+        .map_err(|e| format!("invalid `stage` export: {}", e))?;
+    let packed = stage.call(&mut *store, ()).map_err(|e| e.to_string())?;
+    let bytes = read_memory(store, instance, packed)?;
+    String::from_utf8(bytes).map_err(|e| format!("invalid stage name: {}", e))
+}
+
+/// Writes a document in the memory of an instance, calls its `check` function,
+/// and returns the result.
+fn call_check(
+    store: &mut Store<StoreLimits>,
+    instance: &Instance,
+    document: &[u8],
+) -> Result<Vec<u8>, String> {
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&*store, "alloc")
+        .map_err(|e| format!("invalid `alloc` export: {}", e))?;
+    let check = instance
+        .get_typed_func::<(i32, i32), i64>(&*store, "check")
+        .map_err(|e| format!("invalid `check` export: {}", e))?;
+    let len = i32::try_from(document.len()).map_err(|_| "input too large".to_owned())?;
+    let ptr = alloc.call(&mut *store, len).map_err(|e| e.to_string())?;
+    memory(store, instance)?
+        .write(&mut *store, ptr as u32 as usize, document)
+        .map_err(|e| e.to_string())?;
+    let packed = check
+        .call(&mut *store, (ptr, len))
+        .map_err(|e| e.to_string())?;
+    read_memory(store, instance, packed)
+}
+
+/// Reads the bytes of a packed `(ptr, len)` pair from the memory of an instance.
+fn read_memory(
+    store: &Store<StoreLimits>,
+    instance: &Instance,
+    packed: i64,
+) -> Result<Vec<u8>, String> {
+    let packed = packed as u64;
+    let ptr = (packed >> 32) as usize;
+    let len = (packed & 0xFFFF_FFFF) as usize;
+    // The bounds are checked before allocating the bytes, as the length is
+    // controlled by the module.
+    let data = memory(store, instance)?.data(store);
+    data.get(ptr..ptr.saturating_add(len))
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| {
+            format!(
+                "out of bounds memory access ({} bytes at {}, memory of {} bytes)",
+                len,
+                ptr,
+                data.len()
+            )
+        })
+}
+
+/// Returns the exported memory of an instance.
+fn memory(store: &Store<StoreLimits>, instance: &Instance) -> Result<wasmi::Memory, String> {
+    instance
+        .get_memory(store, "memory")
+        .ok_or_else(|| "missing `memory` export".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::violation::Severity;
+
+    use super::*;
+
+    /// A policy reporting a violation when the input is not `{}`.
+    const POLICY: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "before_resolution")
+  (data (i32.const 32) "[]")
+  (data (i32.const 64) "[{\"type\":\"semconv_attribute\",\"id\":\"not_empty\",\"category\":\"wasm\",\"group\":\"\",\"attr\":\"\"}]")
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "stage") (result i64) (i64.const 17))
+  ;; `{"input":{},"data":...}` has a `}` at offset 10.
+  (func (export "check") (param $ptr i32) (param $len i32) (result i64)
+    (if (result i64)
+      (i32.eq (i32.load8_u (i32.add (local.get $ptr) (i32.const 10))) (i32.const 125))
+      (then (i64.or (i64.shl (i64.const 32) (i64.const 32)) (i64.const 2)))
+      (else (i64.or (i64.shl (i64.const 64) (i64.const 32)) (i64.const 86))))))
+"#;
+
+    #[test]
+    fn test_wasm_policy() {
+        let wasm = wat::parse_str(POLICY).unwrap();
+        let policy = WasmPolicy::parse("test.wasm", &wasm).unwrap();
+        assert_eq!(policy.stage, PolicyStage::BeforeResolution);
+
+        assert!(policy.check(&json!({}), &json!({})).unwrap().is_empty());
+        assert_eq!(
+            policy.check(&json!({"groups": []}), &json!({})).unwrap(),
+            vec![Violation::SemconvAttribute {
+                id: "not_empty".to_owned(),
+                category: "wasm".to_owned(),
+                group: String::new(),
+                attr: String::new(),
+                severity: Severity::Violation,
+                fix: None,
+            }]
+        );
+
+        assert!(matches!(
+            WasmPolicy::parse("invalid.wasm", b"not a wasm module"),
+            Err(Error::InvalidPolicyFile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_wasm_policy_limits() {
+        // A `stage` function returning a length larger than the memory.
+        let wasm = wat::parse_str(
+            r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "stage") (result i64) (i64.const 0xFFFFFFFF)))
+"#,
+        )
+        .unwrap();
+        let result = WasmPolicy::parse("out_of_bounds.wasm", &wasm);
+        assert!(
+            matches!(result, Err(Error::InvalidPolicyFile { ref error, .. }) if error.contains("out of bounds")),
+            "{:?}",
+            result
+        );
+
+        // A `check` function running forever.
+        let wasm = wat::parse_str(
+            r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "before_resolution")
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "stage") (result i64) (i64.const 17))
+  (func (export "check") (param i32) (param i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
+"#,
+        )
+        .unwrap();
+        let mut policy = WasmPolicy::parse("loop.wasm", &wasm).unwrap();
+        policy.fuel = 1_000_000;
+        let result = policy.check(&json!({}), &json!({}));
+        assert!(
+            matches!(result, Err(Error::ViolationEvaluationError { ref error }) if error.contains("fuel")),
+            "{:?}",
+            result
+        );
+
+        // A `check` function growing the memory past the limit (16 pages of
+        // 64 KiB, i.e. 1 MiB).
+        let wasm = wat::parse_str(
+            r#"
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "before_resolution")
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "stage") (result i64) (i64.const 17))
+  (func (export "check") (param i32) (param i32) (result i64)
+    (drop (memory.grow (i32.const 16)))
+    (i64.const 0)))
+"#,
+        )
+        .unwrap();
+        let mut policy = WasmPolicy::parse("grow.wasm", &wasm).unwrap();
+        assert!(policy.check(&json!({}), &json!({})).is_err_and(
+            |e| !matches!(e, Error::ViolationEvaluationError { ref error } if error.contains("limit"))
+        ));
+        policy.memory_limit = 1024 * 1024;
+        let result = policy.check(&json!({}), &json!({}));
+        assert!(
+            matches!(result, Err(Error::ViolationEvaluationError { ref error }) if error.contains("limit")),
+            "{:?}",
+            result
+        );
+    }
+}
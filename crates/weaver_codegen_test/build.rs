@@ -12,7 +12,7 @@ use std::io::Write;
 use std::path::{Component, Path, PathBuf};
 use std::process::exit;
 use weaver_cache::registry_path::RegistryPath;
-use weaver_cache::RegistryRepo;
+use weaver_cache::{RegistryCache, RegistryRepo};
 use weaver_common::in_memory::LogMessage;
 use weaver_common::{in_memory, Logger};
 use weaver_forge::config::{Params, WeaverConfig};
@@ -44,8 +44,9 @@ fn main() {
     let registry_path = RegistryPath::LocalFolder {
         path: SEMCONV_REGISTRY_PATH.into(),
     };
-    let registry_repo =
-        RegistryRepo::try_new("main", &registry_path).unwrap_or_else(|e| process_error(&logger, e));
+    let registry_cache = RegistryCache::try_default().unwrap_or_else(|e| process_error(&logger, e));
+    let registry_repo = RegistryRepo::try_new("main", &registry_path, &registry_cache)
+        .unwrap_or_else(|e| process_error(&logger, e));
     let semconv_specs = SchemaResolver::load_semconv_specs(&registry_repo, FOLLOW_SYMLINKS)
         .ignore(|e| matches!(e.severity(), Some(miette::Severity::Warning)))
         .into_result_failing_non_fatal()
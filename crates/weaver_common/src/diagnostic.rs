@@ -5,6 +5,7 @@
 use crate::Logger;
 use miette::{Diagnostic, LabeledSpan, MietteDiagnostic, Report, Severity};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt::Debug;
 use std::sync::atomic::AtomicBool;
@@ -30,6 +31,141 @@ pub fn is_future_mode_enabled() -> bool {
     FUTURE_MODE.load(std::sync::atomic::Ordering::Relaxed)
 }
 
+/// The severity a diagnostic is promoted or demoted to, regardless of the
+/// severity defined by its error type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeverityOverride {
+    /// The diagnostic is reported as an error (i.e. it fails the operation).
+    Error,
+    /// The diagnostic is reported as a warning.
+    Warning,
+    /// The diagnostic is discarded.
+    Ignore,
+}
+
+impl SeverityOverride {
+    /// Returns the miette severity of the override, or `None` if the
+    /// diagnostic is ignored.
+    #[must_use]
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            SeverityOverride::Error => Some(Severity::Error),
+            SeverityOverride::Warning => Some(Severity::Warning),
+            SeverityOverride::Ignore => None,
+        }
+    }
+}
+
+/// The severity overrides of the diagnostics, indexed by normalized
+/// diagnostic id.
+///
+/// The id of a diagnostic is the name of its error variant, matched
+/// case-insensitively and ignoring `_` and `-` (e.g. `DuplicateGroupName`
+/// and `duplicate_group_name` designate the same diagnostic).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct SeverityOverrides(BTreeMap<String, SeverityOverride>);
+
+impl SeverityOverrides {
+    /// Overrides the severity of the diagnostics with the given id.
+    pub fn insert(&mut self, diagnostic_id: &str, severity: SeverityOverride) {
+        _ = self
+            .0
+            .insert(normalize_diagnostic_id(diagnostic_id), severity);
+    }
+
+    /// Returns true if no severity is overridden.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the severity override of the given error, if any.
+    pub fn get<E: Serialize>(&self, error: &E) -> Option<SeverityOverride> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let json_error = serde_json::to_value(error).ok()?;
+        self.get_by_json(&json_error)
+    }
+
+    /// Returns the severity of the given error, taking the overrides into
+    /// account, or `None` if the error is ignored.
+    pub fn severity<E: Diagnostic + Serialize>(&self, error: &E) -> Option<Severity> {
+        match self.get(error) {
+            Some(severity_override) => severity_override.severity(),
+            None => Some(error.severity().unwrap_or(Severity::Error)),
+        }
+    }
+
+    /// Returns true if the given error is discarded by an override.
+    pub fn is_ignored<E: Serialize>(&self, error: &E) -> bool {
+        self.get(error) == Some(SeverityOverride::Ignore)
+    }
+
+    /// Applies the overrides to the given diagnostic messages: the ignored
+    /// messages are discarded, and the others are reported with their
+    /// overridden severity.
+    #[must_use]
+    pub fn apply(&self, diag_msgs: DiagnosticMessages) -> DiagnosticMessages {
+        if self.0.is_empty() {
+            return diag_msgs;
+        }
+        DiagnosticMessages(
+            diag_msgs
+                .0
+                .into_iter()
+                .filter_map(|msg| match self.get_by_json(&msg.error) {
+                    Some(severity_override) => severity_override
+                        .severity()
+                        .map(|severity| msg.with_severity(severity)),
+                    None => Some(msg),
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the severity override of a serialized error, if any.
+    fn get_by_json(&self, json_error: &serde_json::Value) -> Option<SeverityOverride> {
+        diagnostic_id(json_error).and_then(|id| self.0.get(&normalize_diagnostic_id(id)).copied())
+    }
+}
+
+impl FromIterator<(String, SeverityOverride)> for SeverityOverrides {
+    fn from_iter<I: IntoIterator<Item = (String, SeverityOverride)>>(iter: I) -> Self {
+        let mut overrides = Self::default();
+        for (diagnostic_id, severity) in iter {
+            overrides.insert(&diagnostic_id, severity);
+        }
+        overrides
+    }
+}
+
+/// Returns the id of a serialized error, i.e. the value of its `type` tag,
+/// the name of its variant (externally tagged enums), or its value (unit
+/// variants).
+fn diagnostic_id(json_error: &serde_json::Value) -> Option<&str> {
+    match json_error {
+        serde_json::Value::Object(fields) => match fields.get("type") {
+            Some(serde_json::Value::String(id)) => Some(id),
+            _ if fields.len() == 1 => fields.keys().next().map(String::as_str),
+            _ => None,
+        },
+        serde_json::Value::String(id) => Some(id),
+        _ => None,
+    }
+}
+
+/// Normalizes a diagnostic id so that the different casings of the same
+/// variant name are equal.
+fn normalize_diagnostic_id(id: &str) -> String {
+    id.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// An extension to the [`miette::Diagnostic`] struct that adds an ansi message
 /// representation of the diagnostic message.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -117,6 +253,24 @@ impl DiagnosticMessage {
         }
     }
 
+    /// Returns the diagnostic message reported with the given severity.
+    fn with_severity(mut self, severity: Severity) -> Self {
+        let diagnostic = &mut self.diagnostic;
+        diagnostic.severity = Some(severity);
+        diagnostic.ansi_message = format!(
+            "{:?}",
+            Report::new(MietteDiagnostic {
+                message: diagnostic.message.clone(),
+                code: diagnostic.code.clone(),
+                severity: diagnostic.severity,
+                help: diagnostic.help.clone(),
+                url: diagnostic.url.clone(),
+                labels: diagnostic.labels.clone(),
+            })
+        );
+        self
+    }
+
     /// Returns true if the diagnostic message is a warning
     #[must_use]
     pub fn is_warning(&self) -> bool {
@@ -172,12 +326,12 @@ impl DiagnosticMessages {
     pub fn from_errors<M: Error + Diagnostic + Serialize + Send + Sync + 'static>(
         errors: Vec<M>,
     ) -> Self {
-        Self(errors.into_iter().map(DiagnosticMessage::new).collect())
+        Self::new(errors.into_iter().map(DiagnosticMessage::new).collect())
     }
 
     /// Creates a new list of diagnostic messages from a single error
     pub fn from_error<M: Error + Diagnostic + Serialize + Send + Sync + 'static>(error: M) -> Self {
-        Self(vec![DiagnosticMessage::new(error)])
+        Self::new(vec![DiagnosticMessage::new(error)])
     }
 
     /// Returns true if all the diagnostic messages are explicitly marked as
@@ -242,6 +396,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::result::WResult;
     use miette::{diagnostic, Diagnostic};
 
     #[derive(thiserror::Error, Debug, Clone, Diagnostic, Serialize)]
@@ -335,4 +490,91 @@ mod tests {
             Some("https://example.com".to_owned())
         );
     }
+
+    #[derive(thiserror::Error, Debug, Clone, Diagnostic, Serialize)]
+    enum OverriddenError {
+        #[error("Promoted warning")]
+        #[diagnostic(severity(Warning))]
+        PromotedWarning,
+        #[error("Demoted error")]
+        DemotedError { group_id: String },
+        #[error("Ignored warning")]
+        #[diagnostic(severity(Warning))]
+        IgnoredWarning,
+        #[error("Compound error")]
+        Compound(Vec<OverriddenError>),
+    }
+
+    impl crate::error::WeaverError<OverriddenError> for OverriddenError {
+        fn compound(errors: Vec<OverriddenError>) -> Self {
+            OverriddenError::Compound(errors)
+        }
+    }
+
+    #[test]
+    fn test_severity_overrides() {
+        let overrides: SeverityOverrides = [
+            ("promoted_warning".to_owned(), SeverityOverride::Error),
+            ("DemotedError".to_owned(), SeverityOverride::Warning),
+            ("ignored-warning".to_owned(), SeverityOverride::Ignore),
+        ]
+        .into_iter()
+        .collect();
+        assert!(overrides.is_ignored(&OverriddenError::IgnoredWarning));
+        assert_eq!(
+            overrides.get(&OverriddenError::PromotedWarning),
+            Some(SeverityOverride::Error)
+        );
+        assert_eq!(overrides.get(&OverriddenError::Compound(vec![])), None);
+        assert_eq!(
+            overrides.severity(&OverriddenError::DemotedError {
+                group_id: "test".to_owned(),
+            }),
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            overrides.severity(&OverriddenError::Compound(vec![])),
+            Some(Severity::Error)
+        );
+
+        let messages = overrides.apply(DiagnosticMessages::from_errors(vec![
+            OverriddenError::IgnoredWarning,
+            OverriddenError::DemotedError {
+                group_id: "test".to_owned(),
+            },
+        ]));
+        assert_eq!(messages.len(), 1);
+        assert!(messages.0[0].is_warning());
+        assert!(!messages.has_error());
+
+        let messages = overrides.apply(DiagnosticMessages::from_error(
+            OverriddenError::PromotedWarning,
+        ));
+        assert_eq!(messages.0[0].diagnostic.severity, Some(Severity::Error));
+        assert!(messages.0[0]
+            .diagnostic
+            .ansi_message
+            .contains("Promoted warning"));
+        assert!(messages.has_error());
+
+        // Without overrides, the messages are unchanged.
+        let messages = SeverityOverrides::default().apply(DiagnosticMessages::from_error(
+            OverriddenError::IgnoredWarning,
+        ));
+        assert_eq!(messages.len(), 1);
+        assert!(messages.0[0].is_warning());
+
+        let result = WResult::with_non_fatal_errors(
+            42,
+            vec![
+                OverriddenError::IgnoredWarning,
+                OverriddenError::PromotedWarning,
+            ],
+        )
+        .ignore(|e| overrides.is_ignored(e));
+        assert!(matches!(
+            result,
+            WResult::OkWithNFEs(42, ref nfes) if nfes.len() == 1
+        ));
+    }
 }
@@ -7,6 +7,7 @@ pub mod error;
 pub mod in_memory;
 pub mod quiet;
 pub mod result;
+#[cfg(feature = "test-server")]
 pub mod test;
 
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
@@ -0,0 +1,10 @@
+package io.opentelemetry.semconv;
+
+/** Hand-written class, only the marked region is generated. */
+public final class UserAgentAttributes {
+  // BEGIN GENERATED ATTRIBUTES
+  public static final String OBSOLETE = "obsolete";
+  // END GENERATED ATTRIBUTES
+
+  private UserAgentAttributes() {}
+}
@@ -0,0 +1,12 @@
+package io.opentelemetry.semconv;
+
+/** Hand-written class, only the marked region is generated. */
+public final class UserAgentAttributes {
+  // BEGIN GENERATED ATTRIBUTES
+
+  /** Value of the [HTTP User-Agent](https://www.rfc-editor.org/rfc/rfc9110.html#field.user-agent) header sent by the client. */
+  public static final String USER_AGENT_ORIGINAL = "user_agent.original";
+  // END GENERATED ATTRIBUTES
+
+  private UserAgentAttributes() {}
+}
@@ -23,7 +23,8 @@
 
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 use convert_case::Boundary::{
@@ -46,6 +47,12 @@ use crate::WEAVER_YAML;
 /// Weaver configuration.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WeaverConfig {
+    /// List of configuration files to include (e.g. shared text maps, comment
+    /// formats, or params). The paths are relative to the directory of the
+    /// current configuration file. The included files are loaded first, in
+    /// order, and then overridden by the current configuration file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) include: Option<Vec<String>>,
     /// Type mapping for target specific types (OTel types -> Target language types).
     /// Deprecated: Use `text_maps` instead.
     pub(crate) type_mapping: Option<HashMap<String, String>>,
@@ -79,6 +86,25 @@ pub struct WeaverConfig {
     /// List of acronyms to be considered as unmodifiable words in the case
     /// conversion.
     pub(crate) acronyms: Option<Vec<String>>,
+
+    /// Configuration of the case converter filters (word splitting and
+    /// regex-based exceptions).
+    pub(crate) case_conversion: Option<CaseConversionConfig>,
+
+    /// When enabled, the template engine fails on undefined variables or
+    /// attributes instead of silently rendering them as empty strings.
+    pub(crate) strict_undefined: Option<bool>,
+
+    /// When enabled, a generated file is only written if its content differs
+    /// from the existing file. This preserves the modification time of the
+    /// unchanged files and avoids invalidating incremental builds downstream.
+    pub(crate) write_only_changed_files: Option<bool>,
+
+    /// Glob patterns identifying the static assets (e.g. CSS, images, license
+    /// headers) of the target directory that are copied as-is into the output
+    /// directory. Templates (`*.j2` files or files matched by a template
+    /// configuration) and `weaver.yaml` are never copied.
+    pub(crate) static_files: Option<Vec<Glob>>,
 }
 
 /// Case convention for naming of functions and structs.
@@ -114,6 +140,55 @@ pub enum CaseConvention {
     ScreamingKebabCase,
 }
 
+/// Configuration of the case converter filters (e.g. `snake_case`, `pascal_case`).
+///
+/// The input of a case filter is first split into segments with the
+/// `word_separator` regex. A segment matching one of the `exceptions` is kept as
+/// a single word and rendered with the exception replacement regardless of the
+/// target case convention (e.g. `iOS`, `gRPC`, `HTTP2`). The other segments are
+/// split into words with the default rules and converted as usual.
+///
+/// The word splitting can be overridden per filter in the `filters` section.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CaseConversionConfig {
+    /// A regex matching the separators used to split the input into segments.
+    /// By default, whitespaces, dots, underscores, and hyphens are separators.
+    pub(crate) word_separator: Option<String>,
+    /// When enabled, the segments are also split between letters and digits
+    /// (e.g. `http2` is split into `http` and `2`). Disabled by default.
+    pub(crate) split_digits: Option<bool>,
+    /// A list of exceptions evaluated in order. The first matching exception wins.
+    #[serde(default)]
+    pub(crate) exceptions: Vec<CaseException>,
+    /// The word splitting settings of specific case filters (e.g. `snake_case`),
+    /// overriding the settings above. The `*_const` filters use the settings of
+    /// their base filter.
+    #[serde(default)]
+    pub(crate) filters: BTreeMap<String, CaseFilterConfig>,
+}
+
+/// The word splitting settings of a case filter.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CaseFilterConfig {
+    /// A regex matching the separators used to split the input into segments.
+    pub(crate) word_separator: Option<String>,
+    /// Whether the segments are also split between letters and digits.
+    pub(crate) split_digits: Option<bool>,
+}
+
+/// A case conversion exception.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CaseException {
+    /// A regex that must match an entire segment (e.g. `(?i)ios`).
+    pub(crate) pattern: String,
+    /// The text replacing the matched segment. Capture groups can be referenced
+    /// with `$1`, `$name`, etc. By default, the segment is kept unchanged.
+    pub(crate) replacement: Option<String>,
+}
+
 /// Parameters defined in the command line via the `--params` argument.
 #[derive(Deserialize, Debug, Clone, Default)]
 pub struct Params {
@@ -176,6 +251,23 @@ pub(crate) struct TemplateConfig {
     /// The default value of this path is the same as the input file path.
     /// This file path can be a Jinja expression referencing the parameters.
     pub(crate) file_name: Option<String>,
+    /// An optional injection configuration. When defined, the output of the
+    /// template is injected into the marked regions of the existing output file
+    /// instead of overwriting the whole file.
+    pub(crate) injection: Option<InjectionConfig>,
+}
+
+/// Configuration of the injection of generated code into an existing file.
+/// Only the regions delimited by the begin and end markers are rewritten, the
+/// rest of the file is preserved. The markers are usually placed in comments
+/// of the target language.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct InjectionConfig {
+    /// Text identifying the line that starts a region to rewrite.
+    pub(crate) begin_marker: String,
+    /// Text identifying the line that ends a region to rewrite.
+    pub(crate) end_marker: String,
 }
 
 fn default_filter() -> String {
@@ -356,6 +448,22 @@ impl Default for CaseConvention {
 }
 
 impl CaseConvention {
+    /// Returns the pattern applied to the words and the delimiter used to join
+    /// them for this case convention.
+    pub(crate) fn pattern_and_delim(&self) -> (Pattern, &'static str) {
+        match self {
+            CaseConvention::LowerCase => (Pattern::Lowercase, " "),
+            CaseConvention::UpperCase => (Pattern::Uppercase, " "),
+            CaseConvention::TitleCase => (Pattern::Capital, " "),
+            CaseConvention::PascalCase => (Pattern::Capital, ""),
+            CaseConvention::CamelCase => (Pattern::Camel, ""),
+            CaseConvention::SnakeCase => (Pattern::Lowercase, "_"),
+            CaseConvention::ScreamingSnakeCase => (Pattern::Uppercase, "_"),
+            CaseConvention::KebabCase => (Pattern::Lowercase, "-"),
+            CaseConvention::ScreamingKebabCase => (Pattern::Uppercase, "-"),
+        }
+    }
+
     pub(crate) fn convert(&self, text: &str) -> String {
         // The converters are cached to avoid re-creating them for each conversion.
         // We use a `OnceLock` to ensure that the converters are created only once and
@@ -418,6 +526,7 @@ impl CaseConvention {
 impl Default for WeaverConfig {
     fn default() -> Self {
         Self {
+            include: None,
             type_mapping: None,
             text_maps: None,
             template_syntax: TemplateSyntax {
@@ -434,6 +543,10 @@ impl Default for WeaverConfig {
             params: None,
             templates: None,
             acronyms: None,
+            case_conversion: None,
+            strict_undefined: None,
+            write_only_changed_files: None,
+            static_files: None,
         }
     }
 }
@@ -492,17 +605,56 @@ impl WeaverConfig {
 
         // Each configuration is loaded and merged into the current configuration.
         for conf in configs {
-            let conf: WeaverConfig =
-                serde_yaml::from_str(&conf.content).map_err(|e| InvalidConfigFile {
-                    config_file: conf.path.clone(),
-                    error: e.to_string(),
-                })?;
-            config.override_with(conf);
+            Self::merge_from(&mut config, conf, &mut Vec::new())?;
         }
 
         Ok(config)
     }
 
+    /// Loads a configuration file and merges it into the given configuration.
+    /// The files included by this configuration file are recursively merged first,
+    /// so the content of the configuration file overrides the included files.
+    ///
+    /// `include_stack` contains the files currently being included and is used to
+    /// detect circular includes.
+    fn merge_from(
+        config: &mut WeaverConfig,
+        conf: &FileContent,
+        include_stack: &mut Vec<PathBuf>,
+    ) -> Result<(), Error> {
+        let mut conf_content: WeaverConfig =
+            serde_yaml::from_str(&conf.content).map_err(|e| InvalidConfigFile {
+                config_file: conf.path.clone(),
+                error: e.to_string(),
+            })?;
+
+        if let Some(includes) = conf_content.include.take() {
+            include_stack.push(fs::canonicalize(&conf.path).unwrap_or_else(|_| conf.path.clone()));
+            let base_dir = conf.path.parent().unwrap_or_else(|| Path::new(""));
+            for include in includes {
+                let include_path = base_dir.join(&include);
+                let canonical_path =
+                    fs::canonicalize(&include_path).unwrap_or_else(|_| include_path.clone());
+                if include_stack.contains(&canonical_path) {
+                    return Err(InvalidConfigFile {
+                        config_file: conf.path.clone(),
+                        error: format!("Circular include detected for `{}`", include),
+                    });
+                }
+                let included =
+                    FileContent::try_from_path(&include_path).map_err(|e| InvalidConfigFile {
+                        config_file: conf.path.clone(),
+                        error: format!("Failed to include `{}`: {}", include, e),
+                    })?;
+                Self::merge_from(config, &included, include_stack)?;
+            }
+            _ = include_stack.pop();
+        }
+
+        config.override_with(conf_content);
+        Ok(())
+    }
+
     fn collect_from_path<P: AsRef<Path>>(path: P) -> Vec<FileContent> {
         let mut file_contents = Vec::new();
 
@@ -559,6 +711,17 @@ impl WeaverConfig {
         }
     }
 
+    /// Builds a glob set matching the static files to copy into the output directory.
+    pub fn static_file_matcher(&self) -> Result<GlobSet, Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in self.static_files.iter().flatten() {
+            _ = builder.add(pattern.clone());
+        }
+        builder.build().map_err(|e| Error::InvalidTemplatePattern {
+            error: e.to_string(),
+        })
+    }
+
     /// Override the current `WeaverConfig` with the `WeaverConfig` passed as argument.
     /// The merge is done in place. The `WeaverConfig` passed as argument will be consumed and used
     /// to override the current `WeaverConfig`.
@@ -613,12 +776,27 @@ impl WeaverConfig {
         if child.acronyms.is_some() {
             self.acronyms = child.acronyms;
         }
+        if child.case_conversion.is_some() {
+            self.case_conversion = child.case_conversion;
+        }
+        if child.strict_undefined.is_some() {
+            self.strict_undefined = child.strict_undefined;
+        }
+        if child.write_only_changed_files.is_some() {
+            self.write_only_changed_files = child.write_only_changed_files;
+        }
+        if child.static_files.is_some() {
+            self.static_files = child.static_files;
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_yaml::Value;
+
     use crate::config::{ApplicationMode, WeaverConfig};
+    use crate::error::Error;
     use crate::file_loader::FileContent;
 
     #[test]
@@ -905,6 +1083,110 @@ mod tests {
         assert_eq!(parent.acronyms, Some(vec![]));
     }
 
+    #[test]
+    fn test_write_only_changed_files_override_with() {
+        // If defined in both, the local configuration should override the parent configuration.
+        let mut parent: WeaverConfig =
+            serde_yaml::from_str("write_only_changed_files: true").unwrap();
+        let local: WeaverConfig = serde_yaml::from_str("write_only_changed_files: false").unwrap();
+        parent.override_with(local);
+        assert_eq!(parent.write_only_changed_files, Some(false));
+        let mut parent: WeaverConfig =
+            serde_yaml::from_str("write_only_changed_files: true").unwrap();
+        let local = WeaverConfig::default();
+        parent.override_with(local);
+        assert_eq!(parent.write_only_changed_files, Some(true));
+    }
+
+    #[test]
+    fn test_static_files_override_with() {
+        // If defined in both, the local configuration should override the parent configuration.
+        let mut parent: WeaverConfig = serde_yaml::from_str("static_files: ['*.css']").unwrap();
+        let local: WeaverConfig = serde_yaml::from_str("static_files: ['**/*.png']").unwrap();
+        parent.override_with(local);
+        let matcher = parent.static_file_matcher().unwrap();
+        assert!(matcher.is_match("images/logo.png"));
+        assert!(!matcher.is_match("style.css"));
+
+        let mut parent: WeaverConfig = serde_yaml::from_str("static_files: ['*.css']").unwrap();
+        parent.override_with(WeaverConfig::default());
+        assert!(parent.static_file_matcher().unwrap().is_match("style.css"));
+    }
+
+    #[test]
+    fn test_strict_undefined_override_with() {
+        // If defined in both, the local configuration should override the parent configuration.
+        let mut parent: WeaverConfig = serde_yaml::from_str("strict_undefined: true").unwrap();
+        let local: WeaverConfig = serde_yaml::from_str("strict_undefined: false").unwrap();
+        parent.override_with(local);
+        assert_eq!(parent.strict_undefined, Some(false));
+        let mut parent: WeaverConfig = serde_yaml::from_str("strict_undefined: true").unwrap();
+        parent.override_with(WeaverConfig::default());
+        assert_eq!(parent.strict_undefined, Some(true));
+    }
+
+    #[test]
+    fn test_case_conversion_override_with() {
+        // If defined in both, the local configuration should override the parent configuration.
+        let mut parent: WeaverConfig = serde_yaml::from_str(
+            "case_conversion: {exceptions: [{pattern: 'ios', replacement: 'iOS'}]}",
+        )
+        .unwrap();
+        let local: WeaverConfig =
+            serde_yaml::from_str("case_conversion: {word_separator: '[.]'}").unwrap();
+        parent.override_with(local);
+        let case_conversion = parent.case_conversion.unwrap();
+        assert_eq!(case_conversion.word_separator, Some("[.]".to_owned()));
+        assert!(case_conversion.exceptions.is_empty());
+
+        // If not defined in the local configuration, the parent configuration is kept.
+        let mut parent: WeaverConfig = serde_yaml::from_str(
+            "case_conversion: {exceptions: [{pattern: 'ios', replacement: 'iOS'}]}",
+        )
+        .unwrap();
+        parent.override_with(WeaverConfig::default());
+        assert_eq!(parent.case_conversion.unwrap().exceptions.len(), 1);
+    }
+
+    #[test]
+    fn test_include() {
+        let config = WeaverConfig::try_from_config_files(&["data/config_include/weaver.yaml"])
+            .expect("Failed to load the Weaver configuration");
+
+        // Inherited from `shared/base.yaml` (included by `shared/comments.yaml`).
+        let text_maps = config.text_maps.unwrap();
+        assert_eq!(text_maps["java_types"]["string"], "String");
+        // Inherited from `shared/comments.yaml`.
+        assert_eq!(config.default_comment_format, Some("java".to_owned()));
+        assert!(config.comment_formats.unwrap().contains_key("java"));
+        // Defined locally.
+        assert_eq!(config.acronyms, Some(vec!["iOS".to_owned()]));
+        assert!(config.include.is_none());
+
+        // The params are merged, the local values override the included ones.
+        let params = config.params.unwrap();
+        assert_eq!(params["shared_param"], Value::String("base".to_owned()));
+        assert_eq!(
+            params["overridden_param"],
+            Value::String("local".to_owned())
+        );
+        assert_eq!(params["local_param"], Value::String("local".to_owned()));
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let result = WeaverConfig::try_from_config_files(&["data/config_include/cycle/a.yaml"]);
+        assert!(
+            matches!(result, Err(Error::InvalidConfigFile { error, .. }) if error.contains("Circular"))
+        );
+
+        let configs = vec![FileContent {
+            path: "data/config_include/weaver.yaml".into(),
+            content: "include: ['does_not_exist.yaml']".to_owned(),
+        }];
+        assert!(WeaverConfig::resolve_from(&configs).is_err());
+    }
+
     #[test]
     fn test_try_new() -> Result<(), Box<dyn std::error::Error>> {
         let configs = vec![
@@ -930,6 +1212,7 @@ mod tests {
         assert!(config.params.is_none());
         assert!(config.templates.is_none());
         assert!(config.acronyms.is_none());
+        assert!(config.write_only_changed_files.is_none());
 
         Ok(())
     }
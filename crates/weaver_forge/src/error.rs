@@ -6,6 +6,8 @@ use std::{path::PathBuf, str::FromStr};
 
 use miette::Diagnostic;
 use serde::Serialize;
+#[cfg(feature = "policies")]
+use weaver_checker::violation::ReportedViolation;
 use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages};
 
 use weaver_common::error::WeaverError;
@@ -151,6 +153,15 @@ pub enum Error {
         error: String,
     },
 
+    /// Injection of the generated code into an existing file failed.
+    #[error("Injection of the generated code into {file} failed: {error}")]
+    InjectionFailed {
+        /// File path.
+        file: PathBuf,
+        /// Error message.
+        error: String,
+    },
+
     /// Attribute reference not found in the catalog.
     #[error("Attribute reference {attr_ref} (group: {group_id}) not found in the catalog")]
     AttributeNotFound {
@@ -208,6 +219,17 @@ pub enum Error {
         case: String,
     },
 
+    /// Invalid case conversion configuration.
+    #[error("Invalid case conversion configuration: {error}")]
+    #[diagnostic(
+        help("Please check the `case_conversion` section of the weaver.yaml file."),
+        url("https://github.com/open-telemetry/weaver/blob/main/docs/weaver-config.md")
+    )]
+    InvalidCaseConversion {
+        /// Error message.
+        error: String,
+    },
+
     /// Invalid Markdown text.
     #[error("Invalid Markdown content: {error}")]
     InvalidMarkdown {
@@ -215,6 +237,28 @@ pub enum Error {
         error: String,
     },
 
+    /// A `before_generation` policy violation.
+    #[cfg(feature = "policies")]
+    #[error("Policy violation for the target `{target}`: {violation}")]
+    #[diagnostic(forward(violation))]
+    PolicyViolation {
+        /// The target name.
+        target: String,
+        /// The violation.
+        violation: Box<ReportedViolation>,
+    },
+
+    /// The `before_generation` policies can't be evaluated.
+    #[error(
+        "Failed to evaluate the `before_generation` policies of the target `{target}`: {error}"
+    )]
+    PolicyEvaluationFailed {
+        /// The target name.
+        target: String,
+        /// Error message.
+        error: String,
+    },
+
     /// A generic container for multiple errors.
     #[error("Errors:\n{0:#?}")]
     CompoundError(Vec<Error>),
@@ -2,28 +2,201 @@
 
 //! Case converter filters used by the template engine.
 
+use std::sync::Arc;
+
+use convert_case::Boundary::{DigitLower, DigitUpper, LowerDigit, UpperDigit};
+use convert_case::Converter;
 use minijinja::Environment;
+use regex::Regex;
+
+use crate::config::{CaseConvention, CaseConversionConfig, WeaverConfig};
+use crate::error::Error;
+
+/// Default separators used to split the input of the case filters into segments.
+const DEFAULT_WORD_SEPARATOR: &str = r"[\s._-]+";
 
-use crate::config::CaseConvention;
+/// The `*_const` filters of the `otel` module and their base case filter.
+const CONST_FILTERS: [(&str, &str); 5] = [
+    ("kebab_case_const", "kebab_case"),
+    ("pascal_case_const", "pascal_case"),
+    ("camel_case_const", "camel_case"),
+    ("snake_case_const", "snake_case"),
+    ("screaming_snake_case_const", "screaming_snake_case"),
+];
 
 /// Add case converter filters to the environment.
-pub(crate) fn add_filters(env: &mut Environment<'_>) {
-    env.add_filter("lower_case", case_converter(CaseConvention::LowerCase));
-    env.add_filter("upper_case", case_converter(CaseConvention::UpperCase));
-    env.add_filter("title_case", case_converter(CaseConvention::TitleCase));
-    env.add_filter("pascal_case", case_converter(CaseConvention::PascalCase));
-    env.add_filter("camel_case", case_converter(CaseConvention::CamelCase));
-    env.add_filter("snake_case", case_converter(CaseConvention::SnakeCase));
-    env.add_filter(
-        "screaming_snake_case",
-        case_converter(CaseConvention::ScreamingSnakeCase),
-    );
-    env.add_filter("kebab_case", case_converter(CaseConvention::KebabCase));
-    env.add_filter(
-        "screaming_kebab_case",
-        case_converter(CaseConvention::ScreamingKebabCase),
-    );
+///
+/// If the `case_conversion` section is defined in the configuration, the case
+/// filters honor the word splitting settings (globally or per filter) and the
+/// exceptions defined in this section, and the `*_const` filters of the `otel`
+/// module are replaced by variants honoring them too.
+pub(crate) fn add_filters(env: &mut Environment<'_>, config: &WeaverConfig) -> Result<(), Error> {
+    let filters = [
+        ("lower_case", CaseConvention::LowerCase),
+        ("upper_case", CaseConvention::UpperCase),
+        ("title_case", CaseConvention::TitleCase),
+        ("pascal_case", CaseConvention::PascalCase),
+        ("camel_case", CaseConvention::CamelCase),
+        ("snake_case", CaseConvention::SnakeCase),
+        ("screaming_snake_case", CaseConvention::ScreamingSnakeCase),
+        ("kebab_case", CaseConvention::KebabCase),
+        ("screaming_kebab_case", CaseConvention::ScreamingKebabCase),
+    ];
     env.add_filter("capitalize_first", capitalize_first);
+    let Some(case_conversion) = &config.case_conversion else {
+        for (name, case) in filters {
+            env.add_filter(name, case_converter(case));
+        }
+        return Ok(());
+    };
+
+    if let Some(unknown) = case_conversion
+        .filters
+        .keys()
+        .find(|name| !filters.iter().any(|(filter, _)| filter == name))
+    {
+        return Err(Error::InvalidCaseConversion {
+            error: format!("unknown case filter `{}`", unknown),
+        });
+    }
+    let exceptions: Arc<[_]> = parse_exceptions(case_conversion)?.into();
+    for (name, case) in filters {
+        let converter = Arc::new(CaseConverter::try_new(
+            case_conversion,
+            name,
+            exceptions.clone(),
+        )?);
+        if let Some((const_name, _)) = CONST_FILTERS.iter().find(|(_, base)| *base == name) {
+            let converter = converter.clone();
+            let case = case.clone();
+            env.add_filter(*const_name, move |input: &str| {
+                // Underscores are ignored, but dots are meaningful.
+                converter.convert(&case, &input.replace('_', ""))
+            });
+        }
+        env.add_filter(name, move |input: &str| converter.convert(&case, input));
+    }
+    Ok(())
+}
+
+/// Parses the exceptions of the `case_conversion` section of the configuration.
+fn parse_exceptions(config: &CaseConversionConfig) -> Result<Vec<(Regex, Option<String>)>, Error> {
+    config
+        .exceptions
+        .iter()
+        .map(|exception| {
+            // The pattern must match an entire segment.
+            Regex::new(&format!("^(?:{})$", exception.pattern))
+                .map(|regex| (regex, exception.replacement.clone()))
+                .map_err(|e| Error::InvalidCaseConversion {
+                    error: format!("invalid exception pattern `{}`: {}", exception.pattern, e),
+                })
+        })
+        .collect()
+}
+
+/// A case converter honoring the `case_conversion` section of the configuration.
+pub(crate) struct CaseConverter {
+    word_separator: Regex,
+    split_digits: bool,
+    exceptions: Arc<[(Regex, Option<String>)]>,
+}
+
+impl CaseConverter {
+    /// Creates the case converter of a case filter from the given configuration
+    /// and exceptions. The settings of the filter, if any, override the global
+    /// settings.
+    ///
+    /// An error is returned if the word separator is not a valid regex.
+    pub(crate) fn try_new(
+        config: &CaseConversionConfig,
+        filter: &str,
+        exceptions: Arc<[(Regex, Option<String>)]>,
+    ) -> Result<Self, Error> {
+        let filter_config = config.filters.get(filter);
+        let word_separator = filter_config
+            .and_then(|filter_config| filter_config.word_separator.as_deref())
+            .or(config.word_separator.as_deref())
+            .unwrap_or(DEFAULT_WORD_SEPARATOR);
+        let word_separator =
+            Regex::new(word_separator).map_err(|e| Error::InvalidCaseConversion {
+                error: format!("invalid word separator `{}`: {}", word_separator, e),
+            })?;
+        let split_digits = filter_config
+            .and_then(|filter_config| filter_config.split_digits)
+            .or(config.split_digits)
+            .unwrap_or(false);
+        Ok(Self {
+            word_separator,
+            split_digits,
+            exceptions,
+        })
+    }
+
+    /// Converts the input to the given case convention.
+    pub(crate) fn convert(&self, case: &CaseConvention, input: &str) -> String {
+        let mut words = Vec::new();
+        let mut replacements = Vec::new();
+
+        for segment in self
+            .word_separator
+            .split(input)
+            .filter(|segment| !segment.is_empty())
+        {
+            match self.exception(segment) {
+                Some(replacement) => {
+                    words.push(segment.to_owned());
+                    replacements.push(Some(replacement));
+                }
+                None => {
+                    for word in split_words(segment, self.split_digits) {
+                        words.push(word);
+                        replacements.push(None);
+                    }
+                }
+            }
+        }
+
+        let (pattern, delim) = case.pattern_and_delim();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        pattern
+            .mutate(&words)
+            .into_iter()
+            .zip(replacements)
+            .map(|(word, replacement)| replacement.unwrap_or(word))
+            .collect::<Vec<_>>()
+            .join(delim)
+    }
+
+    /// Returns the replacement of the first exception matching the segment.
+    fn exception(&self, segment: &str) -> Option<String> {
+        self.exceptions
+            .iter()
+            .find(|(regex, _)| regex.is_match(segment))
+            .map(|(regex, replacement)| match replacement {
+                Some(replacement) => regex.replace(segment, replacement.as_str()).into_owned(),
+                None => segment.to_owned(),
+            })
+    }
+}
+
+/// Splits a segment into words with the default boundaries of the case
+/// converters. The digits are only considered as boundaries if `split_digits`
+/// is enabled.
+fn split_words(segment: &str, split_digits: bool) -> Vec<String> {
+    const DELIM: &str = "\u{0}";
+    let mut converter = Converter::new();
+    if !split_digits {
+        converter = converter.remove_boundaries(&[DigitLower, DigitUpper, UpperDigit, LowerDigit]);
+    }
+    converter
+        .remove_pattern()
+        .set_delim(DELIM)
+        .convert(segment)
+        .split(DELIM)
+        .filter(|word| !word.is_empty())
+        .map(str::to_owned)
+        .collect()
 }
 
 /// Converts a `CaseConvention` to a function that converts a string to the specified case
@@ -108,14 +281,16 @@ pub(crate) fn capitalize_first(input: &str) -> String {
 mod tests {
     use minijinja::Environment;
 
+    use crate::config::WeaverConfig;
     use crate::extensions::case::add_filters;
+    use crate::extensions::otel;
 
     #[test]
     fn test_kebab_case() {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'Hello World' | kebab_case }}", ctx)
@@ -129,7 +304,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'Hello World' | lower_case }}", ctx)
@@ -143,7 +318,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'v8js.heap.space.name' | upper_case }}", &ctx)
@@ -187,7 +362,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'Hello World' | title_case }}", ctx)
@@ -201,7 +376,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'v8js.heap.space.name' | camel_case }}", &ctx)
@@ -245,7 +420,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'v8js.heap.space.name' | pascal_case }}", &ctx)
@@ -295,7 +470,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'hello world' | capitalize_first }}", &ctx)
@@ -314,7 +489,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'v8js.heap.space.name' | screaming_snake_case }}", &ctx)
@@ -367,7 +542,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'v8js.heap.space.name' | screaming_kebab_case }}", &ctx)
@@ -420,7 +595,7 @@ mod tests {
         let mut env = Environment::new();
         let ctx = serde_json::Value::Null;
 
-        add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
 
         assert_eq!(
             env.render_str("{{ 'v8js.heap.space.name' | snake_case }}", &ctx)
@@ -467,4 +642,171 @@ mod tests {
             "this_is_an_ios_device_with_a_nice_api!"
         );
     }
+
+    #[test]
+    fn test_case_conversion_exceptions() {
+        let mut env = Environment::new();
+        let ctx = serde_json::Value::Null;
+        let config: WeaverConfig = serde_yaml::from_str(
+            r#"
+case_conversion:
+  exceptions:
+    - pattern: "(?i)ios"
+      replacement: "iOS"
+    - pattern: "(?i)grpc"
+      replacement: "gRPC"
+    - pattern: "HTTP2"
+    - pattern: "(?i)v(\\d+)"
+      replacement: "V$1"
+"#,
+        )
+        .unwrap();
+
+        add_filters(&mut env, &config).unwrap();
+
+        let render = |template: &str| env.render_str(template, &ctx).unwrap();
+        assert_eq!(render("{{ 'ios.version' | pascal_case }}"), "iOSVersion");
+        assert_eq!(render("{{ 'ios.version' | camel_case }}"), "iOSVersion");
+        assert_eq!(render("{{ 'device.ios' | camel_case }}"), "deviceiOS");
+        assert_eq!(
+            render("{{ 'rpc.grpc.status_code' | snake_case }}"),
+            "rpc_gRPC_status_code"
+        );
+        assert_eq!(
+            render("{{ 'rpc.grpc.status_code' | screaming_snake_case }}"),
+            "RPC_gRPC_STATUS_CODE"
+        );
+        assert_eq!(render("{{ 'HTTP2 server' | title_case }}"), "HTTP2 Server");
+        assert_eq!(render("{{ 'http2 server' | title_case }}"), "Http2 Server");
+        assert_eq!(
+            render("{{ 'api.v2.requestCount' | kebab_case }}"),
+            "api-V2-request-count"
+        );
+    }
+
+    #[test]
+    fn test_case_conversion_word_separator() {
+        let mut env = Environment::new();
+        let ctx = serde_json::Value::Null;
+        let config: WeaverConfig = serde_yaml::from_str(
+            r#"
+case_conversion:
+  word_separator: "[/]+"
+"#,
+        )
+        .unwrap();
+
+        add_filters(&mut env, &config).unwrap();
+
+        assert_eq!(
+            env.render_str("{{ 'foo/bar.baz' | snake_case }}", &ctx)
+                .unwrap(),
+            "foo_bar.baz"
+        );
+    }
+
+    #[test]
+    fn test_case_conversion_split_digits() {
+        let mut env = Environment::new();
+        let ctx = serde_json::Value::Null;
+        let config: WeaverConfig = serde_yaml::from_str(
+            r#"
+case_conversion:
+  exceptions:
+    - pattern: "(?i)ios"
+      replacement: "iOS"
+  filters:
+    snake_case:
+      split_digits: true
+    kebab_case:
+      word_separator: "[/.]+"
+"#,
+        )
+        .unwrap();
+
+        add_filters(&mut env, &config).unwrap();
+
+        let render = |template: &str| env.render_str(template, &ctx).unwrap();
+        // Only the `snake_case` filter splits the digits.
+        assert_eq!(render("{{ 'http2.server' | snake_case }}"), "http_2_server");
+        assert_eq!(render("{{ 'http2.server' | pascal_case }}"), "Http2Server");
+        assert_eq!(
+            render("{{ 'k8s.pod.ios' | screaming_snake_case }}"),
+            "K8S_POD_iOS"
+        );
+        // Only the `kebab_case` filter splits the input at slashes.
+        assert_eq!(render("{{ 'ios/client' | kebab_case }}"), "iOS-client");
+        assert_eq!(render("{{ 'ios/client' | snake_case }}"), "ios/client");
+    }
+
+    #[test]
+    fn test_case_conversion_const_filters() {
+        let mut env = Environment::new();
+        let ctx = serde_json::Value::Null;
+        let config: WeaverConfig = serde_yaml::from_str(
+            r#"
+case_conversion:
+  exceptions:
+    - pattern: "(?i)grpc"
+      replacement: "gRPC"
+"#,
+        )
+        .unwrap();
+
+        otel::add_filters(&mut env);
+        add_filters(&mut env, &config).unwrap();
+
+        let render = |template: &str| env.render_str(template, &ctx).unwrap();
+        assert_eq!(
+            render("{{ 'rpc.grpc.status_code' | screaming_snake_case_const }}"),
+            "RPC_gRPC_STATUSCODE"
+        );
+        assert_eq!(
+            render("{{ 'rpc.grpc.status_code' | pascal_case_const }}"),
+            "RpcgRPCStatuscode"
+        );
+        assert_eq!(
+            render("{{ 'messaging.client_id' | kebab_case_const }}"),
+            "messaging-clientid"
+        );
+
+        // Without case conversion, the `otel` filters are kept.
+        let mut env = Environment::new();
+        otel::add_filters(&mut env);
+        add_filters(&mut env, &WeaverConfig::default()).unwrap();
+        assert_eq!(
+            env.render_str(
+                "{{ 'rpc.grpc.status_code' | screaming_snake_case_const }}",
+                &ctx
+            )
+            .unwrap(),
+            "RPC_GRPC_STATUSCODE"
+        );
+    }
+
+    #[test]
+    fn test_invalid_case_conversion() {
+        let mut env = Environment::new();
+        let config: WeaverConfig = serde_yaml::from_str(
+            r#"
+case_conversion:
+  exceptions:
+    - pattern: "(unclosed"
+"#,
+        )
+        .unwrap();
+
+        assert!(add_filters(&mut env, &config).is_err());
+
+        let config: WeaverConfig = serde_yaml::from_str(
+            r#"
+case_conversion:
+  filters:
+    unknown_case:
+      split_digits: true
+"#,
+        )
+        .unwrap();
+        assert!(add_filters(&mut env, &config).is_err());
+    }
 }
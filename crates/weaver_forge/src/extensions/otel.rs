@@ -23,6 +23,8 @@ pub(crate) fn add_filters(env: &mut minijinja::Environment<'_>) {
     env.add_filter("attribute_registry_file", attribute_registry_file);
     env.add_filter("attribute_sort", attribute_sort);
     env.add_filter("metric_namespace", metric_namespace);
+    env.add_filter("entity_namespace", entity_namespace);
+    env.add_filter("event_namespace", event_namespace);
     env.add_filter("required", required);
     env.add_filter("not_required", not_required);
     env.add_filter("instantiated_type", instantiated_type);
@@ -139,6 +141,33 @@ pub(crate) fn metric_namespace(input: &str) -> Result<String, minijinja::Error>
     Ok(parts[1].to_owned())
 }
 
+/// Converts entity.{namespace}.{other}.{components} or resource.{namespace}.{other}.{components}
+/// to {namespace}.
+///
+/// A [`minijinja::Error`] is returned if the input does not start with "entity" or "resource" or
+/// does not have at least two parts. Otherwise, it returns the namespace (second part of the input).
+pub(crate) fn entity_namespace(input: &str) -> Result<String, minijinja::Error> {
+    let parts: Vec<&str> = input.split('.').collect();
+    if parts.len() < 2 || (parts[0] != "entity" && parts[0] != "resource") {
+        return Err(minijinja::Error::new(
+            ErrorKind::InvalidOperation,
+            format!("This entity id `{}` is invalid", input),
+        ));
+    }
+    Ok(parts[1].to_owned())
+}
+
+/// Converts {namespace}.{event_name} to {namespace}.
+///
+/// Event names without namespace are mapped to "other", consistent with the
+/// `semconv_grouped_events` JQ function.
+pub(crate) fn event_namespace(input: &str) -> String {
+    match input.split_once('.') {
+        Some((namespace, _)) if !namespace.is_empty() => namespace.to_owned(),
+        _ => "other".to_owned(),
+    }
+}
+
 /// Converts {namespace}.{attribute_id} to {namespace}.
 ///
 /// A [`minijinja::Error`] is returned if the input does not have
@@ -530,8 +559,8 @@ mod tests {
     use crate::extensions::otel;
     use crate::extensions::otel::{
         attribute_registry_file, attribute_registry_namespace, attribute_registry_title,
-        attribute_sort, is_deprecated, is_experimental, is_stable, metric_namespace,
-        print_member_value,
+        attribute_sort, entity_namespace, event_namespace, is_deprecated, is_experimental,
+        is_stable, metric_namespace, print_member_value,
     };
     use weaver_resolved_schema::attribute::Attribute;
     use weaver_semconv::any_value::{AnyValueCommonSpec, AnyValueSpec};
@@ -655,6 +684,28 @@ mod tests {
         assert!(metric_namespace(input).is_err());
     }
 
+    #[test]
+    fn test_entity_namespace() {
+        assert!(entity_namespace("test").is_err());
+        assert!(entity_namespace("entity").is_err());
+        assert!(entity_namespace("metric.namespace.other").is_err());
+        assert!(entity_namespace("").is_err());
+
+        assert_eq!(
+            entity_namespace("entity.namespace.other.components").unwrap(),
+            "namespace"
+        );
+        assert_eq!(entity_namespace("resource.host").unwrap(), "host");
+    }
+
+    #[test]
+    fn test_event_namespace() {
+        assert_eq!(event_namespace("device.app.lifecycle"), "device");
+        assert_eq!(event_namespace("exception"), "other");
+        assert_eq!(event_namespace(".invalid"), "other");
+        assert_eq!(event_namespace(""), "other");
+    }
+
     #[test]
     fn test_is_stable() {
         // An attribute with stability "stable"
@@ -810,6 +861,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "rec.b".into(),
@@ -825,6 +878,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "crec.a".into(),
@@ -840,6 +895,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "crec.b".into(),
@@ -855,6 +912,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "rec.c".into(),
@@ -870,6 +929,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "rec.d".into(),
@@ -885,6 +946,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "opt.a".into(),
@@ -900,6 +963,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "opt.b".into(),
@@ -915,6 +980,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "req.a".into(),
@@ -930,6 +997,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "req.b".into(),
@@ -945,6 +1014,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
         ];
         let json =
@@ -1003,6 +1074,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "attr2".to_owned(),
@@ -1018,6 +1091,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
             Attribute {
                 name: "attr3".to_owned(),
@@ -1033,6 +1108,8 @@ mod tests {
                 tags: None,
                 value: None,
                 prefix: false,
+                value_constraints: None,
+                aliases: None,
             },
         ];
 
@@ -1323,6 +1400,8 @@ mod tests {
             tags: None,
             value: None,
             prefix: false,
+            value_constraints: None,
+            aliases: None,
         };
 
         otel::add_filters(&mut env);
@@ -1351,6 +1430,8 @@ mod tests {
             tags: None,
             value: None,
             prefix: false,
+            value_constraints: None,
+            aliases: None,
         };
 
         otel::add_filters(&mut env);
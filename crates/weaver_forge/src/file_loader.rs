@@ -22,6 +22,17 @@ pub trait FileLoader {
 
     /// Returns the content of a file from a given name.
     fn load_file(&self, file: &str) -> Result<Option<FileContent>, Error>;
+
+    /// Returns the raw content of a file from a given name (e.g. for binary
+    /// static assets).
+    ///
+    /// The default implementation relies on `load_file` and therefore only
+    /// supports UTF-8 files. Loaders supporting binary files should override it.
+    fn load_binary_file(&self, file: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self
+            .load_file(file)?
+            .map(|file_content| file_content.content.into_bytes()))
+    }
 }
 
 /// A struct that represents the content of a file.
@@ -142,6 +153,19 @@ impl FileLoader for EmbeddedFileLoader {
             None => Ok(None),
         }
     }
+
+    /// Returns the raw content of a file from a given name.
+    fn load_binary_file(&self, file: &str) -> Result<Option<Vec<u8>>, Error> {
+        if let Some(fs_loader) = &self.fs_loader {
+            return fs_loader.load_binary_file(file);
+        }
+
+        let name = format!("{}/{}", self.target, file);
+        Ok(self
+            .embedded_dir
+            .get_file(name)
+            .map(|file| file.contents().to_vec()))
+    }
 }
 
 /// A loader that loads files from the file system.
@@ -208,6 +232,23 @@ impl FileLoader for FileSystemFileLoader {
             }),
         }
     }
+
+    /// Returns the raw content of a file from a given name.
+    fn load_binary_file(&self, file: &str) -> Result<Option<Vec<u8>>, Error> {
+        let path = if let Ok(path) = safe_join(&self.dir, file) {
+            path
+        } else {
+            return Ok(None);
+        };
+        match fs::read(&path) {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::FileLoaderError {
+                file: path,
+                error: err.to_string(),
+            }),
+        }
+    }
 }
 
 // Combine a root path and a template name, ensuring that the combined path is
@@ -294,6 +335,11 @@ mod tests {
 
         // Test content equality between embedded and file system loaders
         assert_eq!(embedded_content.content, fs_content.content);
+        assert_eq!(
+            embedded_loader.load_binary_file("group.md").unwrap(),
+            fs_loader.load_binary_file("group.md").unwrap()
+        );
+        assert!(fs_loader.load_binary_file("missing.md").unwrap().is_none());
 
         // Test root path
         assert_eq!(
@@ -353,6 +399,40 @@ mod tests {
         assert!(fs_content.is_none());
     }
 
+    /// A loader only implementing the required methods of `FileLoader`.
+    struct TextFileLoader(FileSystemFileLoader);
+
+    impl FileLoader for TextFileLoader {
+        fn root(&self) -> &Path {
+            self.0.root()
+        }
+
+        fn all_files(&self) -> Vec<PathBuf> {
+            self.0.all_files()
+        }
+
+        fn load_file(&self, file: &str) -> Result<Option<FileContent>, Error> {
+            self.0.load_file(file)
+        }
+    }
+
+    #[test]
+    fn test_default_load_binary_file() {
+        let fs_loader =
+            FileSystemFileLoader::try_new(PathBuf::from("./templates"), "test").unwrap();
+        let text_loader = TextFileLoader(
+            FileSystemFileLoader::try_new(PathBuf::from("./templates"), "test").unwrap(),
+        );
+        assert_eq!(
+            text_loader.load_binary_file("group.md").unwrap(),
+            fs_loader.load_binary_file("group.md").unwrap()
+        );
+        assert!(text_loader
+            .load_binary_file("missing.md")
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn test_embedded_loader_error() {
         let embedded_loader = EmbeddedFileLoader::try_new(
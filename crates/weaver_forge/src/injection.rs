@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Injection of generated code into the marked regions of existing files.
+//!
+//! A region starts with a line containing the begin marker and ends with a line
+//! containing the end marker. The marker lines are preserved and everything in
+//! between is replaced by the generated code. This works for any language as
+//! long as the markers are placed in comments.
+
+use crate::config::InjectionConfig;
+
+/// Replaces the content of every region of `existing` delimited by the markers
+/// defined in `config` with `generated`.
+///
+/// An error message is returned if no region is found, if a region is not
+/// closed, or if a begin marker is found inside another region.
+pub(crate) fn inject(
+    existing: &str,
+    generated: &str,
+    config: &InjectionConfig,
+) -> Result<String, String> {
+    let mut result = String::with_capacity(existing.len() + generated.len());
+    let mut in_region = false;
+    let mut region_count = 0;
+
+    for (line_number, line) in existing.split_inclusive('\n').enumerate() {
+        if in_region {
+            if line.contains(&config.begin_marker) {
+                return Err(format!(
+                    "nested begin marker `{}` found at line {}",
+                    config.begin_marker,
+                    line_number + 1
+                ));
+            }
+            if line.contains(&config.end_marker) {
+                result.push_str(generated);
+                if !generated.is_empty() && !generated.ends_with('\n') {
+                    result.push('\n');
+                }
+                result.push_str(line);
+                in_region = false;
+            }
+        } else {
+            result.push_str(line);
+            if line.contains(&config.begin_marker) {
+                if !line.ends_with('\n') {
+                    return Err(format!(
+                        "begin marker `{}` found at line {} is never closed",
+                        config.begin_marker,
+                        line_number + 1
+                    ));
+                }
+                in_region = true;
+                region_count += 1;
+            }
+        }
+    }
+
+    if in_region {
+        return Err(format!(
+            "end marker `{}` not found, the last region is never closed",
+            config.end_marker
+        ));
+    }
+    if region_count == 0 {
+        return Err(format!(
+            "no region delimited by `{}` and `{}` found",
+            config.begin_marker, config.end_marker
+        ));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> InjectionConfig {
+        InjectionConfig {
+            begin_marker: "BEGIN GENERATED".to_owned(),
+            end_marker: "END GENERATED".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_inject() {
+        let existing = "package foo;\n\
+            // BEGIN GENERATED\n\
+            old line 1\n\
+            old line 2\n\
+            // END GENERATED\n\
+            hand written\n\
+            # BEGIN GENERATED\n\
+            # END GENERATED";
+        let result = inject(existing, "new line", &config()).unwrap();
+        assert_eq!(
+            result,
+            "package foo;\n\
+            // BEGIN GENERATED\n\
+            new line\n\
+            // END GENERATED\n\
+            hand written\n\
+            # BEGIN GENERATED\n\
+            new line\n\
+            # END GENERATED"
+        );
+
+        // Injecting the same content again is idempotent.
+        assert_eq!(inject(&result, "new line", &config()).unwrap(), result);
+    }
+
+    #[test]
+    fn test_inject_errors() {
+        assert!(inject("no markers\n", "new", &config()).is_err());
+        assert!(inject("// BEGIN GENERATED\nold\n", "new", &config()).is_err());
+        assert!(inject("// BEGIN GENERATED", "new", &config()).is_err());
+        assert!(inject(
+            "// BEGIN GENERATED\n// BEGIN GENERATED\n// END GENERATED\n",
+            "new",
+            &config()
+        )
+        .is_err());
+    }
+}
@@ -10,9 +10,10 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::{fmt, fs};
 
+use itertools::Itertools;
 use minijinja::syntax::SyntaxConfig;
-use minijinja::value::{from_args, Enumerator, Object};
-use minijinja::{Environment, ErrorKind, State, Value};
+use minijinja::value::{from_args, Enumerator, Object, ObjectRepr};
+use minijinja::{Environment, ErrorKind, State, UndefinedBehavior, Value};
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 use serde::Serialize;
@@ -25,12 +26,13 @@ use error::Error::{
 use weaver_common::error::handle_errors;
 use weaver_common::Logger;
 
-use crate::config::{ApplicationMode, Params, TemplateConfig, WeaverConfig};
+use crate::config::{ApplicationMode, InjectionConfig, Params, TemplateConfig, WeaverConfig};
 use crate::debug::error_summary;
-use crate::error::Error::{InvalidConfigFile, InvalidFilePath};
+use crate::error::Error::{InjectionFailed, InvalidConfigFile, InvalidFilePath};
 use crate::extensions::{ansi, case, code, otel, util};
 use crate::file_loader::FileLoader;
 use crate::filter::Filter;
+use crate::profiler::Profiler;
 use crate::registry::{ResolvedGroup, ResolvedRegistry};
 
 pub mod config;
@@ -40,7 +42,9 @@ pub mod extensions;
 pub mod file_loader;
 mod filter;
 mod formats;
+mod injection;
 mod jq;
+pub mod profiler;
 pub mod registry;
 
 /// Name of the Weaver configuration file.
@@ -84,6 +88,8 @@ pub enum OutputDirective {
 #[derive(Debug, Clone)]
 struct TemplateObject {
     file_name: Arc<Mutex<String>>,
+    file_permissions: Arc<Mutex<Option<u32>>>,
+    executable: Arc<Mutex<bool>>,
 }
 
 impl TemplateObject {
@@ -91,6 +97,16 @@ impl TemplateObject {
     fn file_name(&self) -> PathBuf {
         PathBuf::from(self.file_name.lock().expect("Lock poisoned").clone())
     }
+
+    /// Get the permissions of the generated file (if defined by the template).
+    fn file_permissions(&self) -> Option<u32> {
+        *self.file_permissions.lock().expect("Lock poisoned")
+    }
+
+    /// Returns true if the generated file must be executable.
+    fn executable(&self) -> bool {
+        *self.executable.lock().expect("Lock poisoned")
+    }
 }
 
 impl Object for TemplateObject {
@@ -100,15 +116,36 @@ impl Object for TemplateObject {
         name: &str,
         args: &[Value],
     ) -> Result<Value, minijinja::Error> {
-        if name == "set_file_name" {
-            let (file_name,): (&str,) = from_args(args)?;
-            file_name.clone_into(&mut self.file_name.lock().expect("Lock poisoned"));
-            Ok(Value::from(""))
-        } else {
-            Err(minijinja::Error::new(
+        match name {
+            "set_file_name" => {
+                let (file_name,): (&str,) = from_args(args)?;
+                file_name.clone_into(&mut self.file_name.lock().expect("Lock poisoned"));
+                Ok(Value::from(""))
+            }
+            "set_file_permissions" => {
+                // The permissions are expressed in octal (e.g. "755" or "0o644").
+                let (mode,): (&str,) = from_args(args)?;
+                let mode = u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+                    .ok()
+                    .filter(|mode| *mode <= 0o7777)
+                    .ok_or_else(|| {
+                        minijinja::Error::new(
+                            ErrorKind::InvalidOperation,
+                            format!("Invalid file permissions `{mode}`, expected an octal mode (e.g. \"755\")"),
+                        )
+                    })?;
+                *self.file_permissions.lock().expect("Lock poisoned") = Some(mode);
+                Ok(Value::from(""))
+            }
+            "set_executable" => {
+                let (executable,): (Option<bool>,) = from_args(args)?;
+                *self.executable.lock().expect("Lock poisoned") = executable.unwrap_or(true);
+                Ok(Value::from(""))
+            }
+            _ => Err(minijinja::Error::new(
                 ErrorKind::UnknownMethod,
                 format!("template has no method named {name}"),
-            ))
+            )),
         }
     }
 }
@@ -150,6 +187,89 @@ impl Object for ParamsObject {
     }
 }
 
+/// A JSON value accessible from the templates without being converted upfront.
+/// The fields of the objects and the items of the arrays are only converted
+/// into MiniJinja values when they are accessed, so the per-item contexts of the
+/// `each` mode share the filtered result instead of copying it.
+#[derive(Debug)]
+struct JsonObject {
+    /// The JSON document shared by all the views into it.
+    root: Arc<serde_json::Value>,
+    /// The JSON pointer of the object or array in the document.
+    pointer: String,
+}
+
+impl JsonObject {
+    /// Returns the MiniJinja value of the JSON value at the given pointer:
+    /// a lazy view for the objects and arrays, or a converted primitive.
+    fn value(root: &Arc<serde_json::Value>, pointer: String) -> Value {
+        match root.pointer(&pointer) {
+            Some(serde_json::Value::Object(_) | serde_json::Value::Array(_)) => {
+                Value::from_object(JsonObject {
+                    root: root.clone(),
+                    pointer,
+                })
+            }
+            Some(value) => Value::from_serialize(value),
+            None => Value::UNDEFINED,
+        }
+    }
+
+    /// Returns the JSON object or array viewed by this object.
+    fn json(&self) -> &serde_json::Value {
+        self.root
+            .pointer(&self.pointer)
+            .expect("the pointer is checked when the view is created")
+    }
+
+    /// Returns the JSON pointer of a field or an item of this object.
+    fn child_pointer(&self, token: &str) -> String {
+        format!(
+            "{}/{}",
+            self.pointer,
+            token.replace('~', "~0").replace('/', "~1")
+        )
+    }
+}
+
+impl Object for JsonObject {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        match self.json() {
+            serde_json::Value::Array(_) => ObjectRepr::Seq,
+            _ => ObjectRepr::Map,
+        }
+    }
+
+    /// Given a key (or an index for the arrays), converts the associated value.
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        let token = match self.json() {
+            serde_json::Value::Array(_) => key.as_usize()?.to_string(),
+            _ => key.as_str()?.to_owned(),
+        };
+        let value = Self::value(&self.root, self.child_pointer(&token));
+        (!value.is_undefined()).then_some(value)
+    }
+
+    /// Enumerates the keys of the objects or the indexes of the arrays.
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        match self.json() {
+            serde_json::Value::Array(items) => Enumerator::Seq(items.len()),
+            serde_json::Value::Object(fields) => {
+                Enumerator::Values(fields.keys().map(|key| Value::from(key.as_str())).collect())
+            }
+            _ => Enumerator::Empty,
+        }
+    }
+
+    fn enumerator_len(self: &Arc<Self>) -> Option<usize> {
+        match self.json() {
+            serde_json::Value::Array(items) => Some(items.len()),
+            serde_json::Value::Object(fields) => Some(fields.len()),
+            _ => Some(0),
+        }
+    }
+}
+
 /// Template engine for generating artifacts from a semantic convention
 /// registry and telemetry schema.
 pub struct TemplateEngine {
@@ -158,6 +278,18 @@ pub struct TemplateEngine {
 
     /// Target configuration
     target_config: WeaverConfig,
+
+    /// Profiler recording the time spent in the Jinja and JQ filters (if enabled)
+    /// and the names of the Jinja filters to instrument.
+    profiling: Option<Arc<Profiler>>,
+
+    /// Policy engine evaluating the `before_generation` policies (if any).
+    #[cfg(feature = "policies")]
+    policy_engine: Option<weaver_checker::Engine>,
+
+    /// Params passed to the `before_generation` policies, i.e. the CLI-level
+    /// params and the top-level params of the `weaver.yaml` file.
+    policy_params: BTreeMap<String, serde_yaml::Value>,
 }
 
 /// Global context for the template engine.
@@ -178,6 +310,13 @@ pub struct NewContext<'a> {
     pub ctx: &'a serde_json::Value,
 }
 
+/// Returns the template context `{ctx: <value>}` of the JSON value at the
+/// given pointer of the filtered result. The value is converted lazily, see
+/// [`JsonObject`].
+fn template_context(filtered_result: &Arc<serde_json::Value>, pointer: String) -> Value {
+    minijinja::context! { ctx => JsonObject::value(filtered_result, pointer) }
+}
+
 /// Convert a context into a serde_json::Value.
 impl TryInto<serde_json::Value> for NewContext<'_> {
     type Error = Error;
@@ -196,6 +335,11 @@ impl TemplateEngine {
         loader: impl FileLoader + Send + Sync + 'static,
         params: Params,
     ) -> Self {
+        // The params of the `before_generation` policies. The CLI-level params
+        // override the top-level params of the `weaver.yaml` file.
+        let mut policy_params = config.params.clone().unwrap_or_default();
+        policy_params.extend(params.params.clone());
+
         // Compute the params for each template based on:
         // - CLI-level params
         // - Top-level params in the `weaver.yaml` file
@@ -228,9 +372,39 @@ impl TemplateEngine {
         Self {
             file_loader: Arc::new(loader),
             target_config: config,
+            profiling: None,
+            #[cfg(feature = "policies")]
+            policy_engine: None,
+            policy_params,
         }
     }
 
+    /// Returns the params passed to the policies, i.e. the CLI-level params
+    /// and the top-level params of the `weaver.yaml` file.
+    #[must_use]
+    pub fn policy_params(&self) -> &BTreeMap<String, serde_yaml::Value> {
+        &self.policy_params
+    }
+
+    /// Sets the policy engine evaluating the `before_generation` policies
+    /// before rendering the templates.
+    #[cfg(feature = "policies")]
+    pub fn set_policy_engine(&mut self, policy_engine: weaver_checker::Engine) {
+        self.policy_engine = Some(policy_engine);
+    }
+
+    /// Enable the profiling of the Jinja filters used by the templates and of
+    /// the JQ filters defined in the configuration.
+    pub fn enable_profiling(&mut self) {
+        self.profiling = Some(Arc::new(Profiler::default()));
+    }
+
+    /// Returns the profiler if the profiling is enabled.
+    #[must_use]
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiling.as_deref()
+    }
+
     /// Generate a template snippet from serializable context and a snippet identifier.
     ///
     /// # Arguments
@@ -286,6 +460,8 @@ impl TemplateEngine {
             error: e.to_string(),
         })?;
 
+        self.check_policies(&log, &context)?;
+
         // Process each file and collect any errors.
         // The files are processed in parallel.
         let errs = files
@@ -310,8 +486,109 @@ impl TemplateEngine {
                     .collect::<Vec<Error>>()
             })
             .collect::<Vec<Error>>();
+        handle_errors(errs)?;
 
-        handle_errors(errs)
+        if let OutputDirective::File = output_directive {
+            self.copy_static_files(log, output_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Evaluates the `before_generation` policies (if any) on the context, the
+    /// target name, and the params. The violations failing the check (see
+    /// [`weaver_checker::Engine::set_fail_on`]) prevent the generation, the
+    /// others are logged as warnings.
+    #[cfg(feature = "policies")]
+    fn check_policies(&self, log: &impl Logger, context: &serde_json::Value) -> Result<(), Error> {
+        let Some(policy_engine) = &self.policy_engine else {
+            return Ok(());
+        };
+        let target = self
+            .file_loader
+            .root()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let evaluation_failed = |error: String| Error::PolicyEvaluationFailed {
+            target: target.clone(),
+            error,
+        };
+        let input = serde_json::json!({
+            "registry": context,
+            "target": target,
+            "params": self.policy_params,
+        });
+
+        // A local policy engine inheriting the policies of the global one.
+        let mut policy_engine = policy_engine.clone();
+        policy_engine
+            .set_input(&input)
+            .map_err(|e| evaluation_failed(e.to_string()))?;
+        let violations = policy_engine
+            .check(weaver_checker::PolicyStage::BeforeGeneration)
+            .map_err(|e| evaluation_failed(e.to_string()))?;
+
+        let mut errors = vec![];
+        let fail_on = policy_engine.fail_on();
+        for violation in violations {
+            if violation.level() >= fail_on {
+                errors.push(Error::PolicyViolation {
+                    target: target.clone(),
+                    violation: Box::new(weaver_checker::violation::ReportedViolation::new(
+                        violation, fail_on,
+                    )),
+                });
+            } else {
+                log.warn(&format!(
+                    "Policy violation for the target `{}`: {}",
+                    target, violation
+                ));
+            }
+        }
+        handle_errors(errors)
+    }
+
+    /// Evaluates the `before_generation` policies.
+    /// Policies are not supported without the `policies` feature, so this is a no-op.
+    #[cfg(not(feature = "policies"))]
+    fn check_policies(
+        &self,
+        _log: &impl Logger,
+        _context: &serde_json::Value,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Copy the static files of the target directory (as defined by the
+    /// `static_files` section of the configuration) into the output directory.
+    fn copy_static_files(&self, log: impl Logger, output_dir: &Path) -> Result<(), Error> {
+        let static_file_matcher = self.target_config.static_file_matcher()?;
+        if static_file_matcher.is_empty() {
+            return Ok(());
+        }
+        let tmpl_matcher = self.target_config.template_matcher()?;
+        let write_only_changed_files = self
+            .target_config
+            .write_only_changed_files
+            .unwrap_or_default();
+
+        for file in self.file_loader.all_files() {
+            let is_template = file.extension().is_some_and(|ext| ext == "j2")
+                || file == Path::new(WEAVER_YAML)
+                || !tmpl_matcher.matches(&file).is_empty();
+            if is_template || !static_file_matcher.is_match(&file) {
+                continue;
+            }
+            let Some(content) = self.file_loader.load_binary_file(&file.to_string_lossy())? else {
+                continue;
+            };
+            let (copied_file, written) =
+                Self::save_generated_code(output_dir, file, content, write_only_changed_files)?;
+            if written {
+                log.success(&format!("Copied static file {:?}", copied_file));
+            }
+        }
+        Ok(())
     }
 
     /// Process a single template file with the given template configuration,
@@ -328,12 +605,24 @@ impl TemplateEngine {
         let yaml_params = Self::init_params(template.params.clone())?;
         let params = Self::prepare_jq_context(&yaml_params)?;
         let filter = Filter::new(template.filter.as_str());
-        let filtered_result = filter.apply(context.clone(), &params)?;
+        let filtered_result = match self.profiler() {
+            Some(profiler) => {
+                // Multi-line filters are displayed on a single line in the report.
+                let filter_expr = template.filter.split_whitespace().join(" ");
+                profiler.time(&format!("jq filter `{}`", filter_expr), || {
+                    filter.apply(context.clone(), &params)
+                })?
+            }
+            None => filter.apply(context.clone(), &params)?,
+        };
+        // Shared by the lazy template contexts (see `JsonObject`).
+        let filtered_result = Arc::new(filtered_result);
 
         match template.application_mode {
             ApplicationMode::Single => self.process_single_mode(
                 &filtered_result,
                 template.file_name.as_ref(),
+                template.injection.as_ref(),
                 &yaml_params,
                 template_file,
                 output_dir,
@@ -343,6 +632,7 @@ impl TemplateEngine {
             ApplicationMode::Each => self.process_each_mode(
                 &filtered_result,
                 template.file_name.as_ref(),
+                template.injection.as_ref(),
                 &yaml_params,
                 template_file,
                 output_dir,
@@ -357,24 +647,28 @@ impl TemplateEngine {
     /// The evaluation is done in parallel.
     fn process_each_mode(
         &self,
-        ctx: &serde_json::Value,
+        ctx: &Arc<serde_json::Value>,
         file_path: Option<&String>,
+        injection: Option<&InjectionConfig>,
         params: &BTreeMap<String, serde_yaml::Value>,
         template_file: &Path,
         output_dir: &Path,
         output_directive: &OutputDirective,
         log: impl Logger + Sync + Clone,
     ) -> Result<(), Error> {
-        match ctx {
+        match ctx.as_ref() {
             serde_json::Value::Array(values) => {
                 // Evaluate the template for each object in the array context in parallel
-                let errs = values
+                let errs = (0..values.len())
                     .into_par_iter()
-                    .filter_map(|result| {
+                    .filter_map(|index| {
+                        // The context of each item is a view into the filtered
+                        // result, converted as the template accesses it.
                         self.evaluate_template(
                             log.clone(),
-                            NewContext { ctx: result }.try_into().ok()?,
+                            template_context(ctx, format!("/{index}")),
                             file_path,
+                            injection,
                             params,
                             template_file,
                             output_directive,
@@ -387,8 +681,9 @@ impl TemplateEngine {
             }
             _ => self.evaluate_template(
                 log.clone(),
-                NewContext { ctx }.try_into()?,
+                template_context(ctx, String::new()),
                 file_path,
+                injection,
                 params,
                 template_file,
                 output_directive,
@@ -400,8 +695,9 @@ impl TemplateEngine {
     /// Evaluate the template for the entire context.
     fn process_single_mode(
         &self,
-        ctx: &serde_json::Value,
+        ctx: &Arc<serde_json::Value>,
         file_path: Option<&String>,
+        injection: Option<&InjectionConfig>,
         params: &BTreeMap<String, serde_yaml::Value>,
         template_file: &Path,
         output_dir: &Path,
@@ -414,8 +710,9 @@ impl TemplateEngine {
         }
         self.evaluate_template(
             log.clone(),
-            NewContext { ctx }.try_into()?,
+            template_context(ctx, String::new()),
             file_path,
+            injection,
             params,
             template_file,
             output_directive,
@@ -482,8 +779,9 @@ impl TemplateEngine {
     fn evaluate_template(
         &self,
         log: impl Logger + Clone + Sync,
-        ctx: serde_json::Value,
+        ctx: Value,
         file_path: Option<&String>,
+        injection: Option<&InjectionConfig>,
         params: &BTreeMap<String, serde_yaml::Value>,
         template_path: &Path,
         output_directive: &OutputDirective,
@@ -500,14 +798,12 @@ impl TemplateEngine {
         // Pre-determine the file path for the generated file based on the template file path
         // if defined, otherwise use the default file path based on the template file name.
         let file_path = match file_path {
-            Some(file_path) => {
-                engine
-                    .render_str(file_path, ctx.clone())
-                    .map_err(|e| InvalidFilePath {
-                        file_path: file_path.clone(),
-                        error: e.to_string(),
-                    })?
-            }
+            Some(file_path) => engine
+                .render_str(file_path, &ctx)
+                .map_err(|e| InvalidFilePath {
+                    file_path: file_path.clone(),
+                    error: e.to_string(),
+                })?,
             None => {
                 // By default, the file name is the template file name without
                 // the extension ".j2"
@@ -521,6 +817,8 @@ impl TemplateEngine {
         };
         let template_object = TemplateObject {
             file_name: Arc::new(Mutex::new(file_path)),
+            file_permissions: Arc::new(Mutex::new(None)),
+            executable: Arc::new(Mutex::new(false)),
         };
         let template_file = template_path.to_str().ok_or(InvalidTemplateFile {
             template: template_path.to_path_buf(),
@@ -544,7 +842,7 @@ impl TemplateEngine {
         })?;
 
         let output = template
-            .render(ctx.clone())
+            .render(&ctx)
             .map_err(|e| TemplateEvaluationFailed {
                 template: template_path.to_path_buf(),
                 error_id: e.to_string(),
@@ -558,16 +856,42 @@ impl TemplateEngine {
                 eprintln!("{}", output);
             }
             OutputDirective::File => {
-                let generated_file =
-                    Self::save_generated_code(output_dir, template_object.file_name(), output)?;
-                log.success(&format!("Generated file {:?}", generated_file));
+                let write_only_changed_files = self
+                    .target_config
+                    .write_only_changed_files
+                    .unwrap_or_default();
+                let output = match injection {
+                    Some(injection) => Self::inject_generated_code(
+                        output_dir,
+                        &template_object.file_name(),
+                        &output,
+                        injection,
+                    )?,
+                    None => output,
+                };
+                let (generated_file, written) = Self::save_generated_code(
+                    output_dir,
+                    template_object.file_name(),
+                    output,
+                    write_only_changed_files,
+                )?;
+                Self::set_file_permissions(
+                    &generated_file,
+                    template_object.file_permissions(),
+                    template_object.executable(),
+                )?;
+                if written {
+                    log.success(&format!("Generated file {:?}", generated_file));
+                } else {
+                    log.info(&format!("Unchanged file {:?} (skipped)", generated_file));
+                }
             }
         }
         Ok(())
     }
 
     /// Create a new template engine based on the target configuration.
-    fn template_engine(&self) -> Result<Environment<'_>, Error> {
+    fn template_engine(&self) -> Result<Environment<'static>, Error> {
         let mut env = Environment::new();
         let template_syntax = self.target_config.template_syntax.clone();
 
@@ -621,11 +945,19 @@ impl TemplateEngine {
         env.set_unknown_method_callback(minijinja_contrib::pycompat::unknown_method_callback);
 
         let file_loader = self.file_loader.clone();
+        // In profiling mode, the templates are rewritten to apply their filters
+        // through the profiled filter.
+        let profiled_syntax = self.profiling.as_ref().map(|_| syntax.clone());
         env.set_loader(move |name| {
             file_loader
                 .load_file(name)
                 .map_err(|e| minijinja::Error::new(ErrorKind::InvalidOperation, e.to_string()))
-                .map(|opt_file_content| opt_file_content.map(|file_content| file_content.content))
+                .map(|opt_file_content| {
+                    opt_file_content.map(|file_content| match &profiled_syntax {
+                        Some(syntax) => profiler::instrument_source(&file_content.content, syntax),
+                        None => file_content.content,
+                    })
+                })
         });
         env.set_syntax(syntax);
 
@@ -636,19 +968,109 @@ impl TemplateEngine {
         env.set_lstrip_blocks(whitespace_control.lstrip_blocks.unwrap_or_default());
         env.set_keep_trailing_newline(whitespace_control.keep_trailing_newline.unwrap_or_default());
 
+        // Fail on undefined variables or attributes if the strict mode is enabled.
+        if self.target_config.strict_undefined.unwrap_or_default() {
+            env.set_undefined_behavior(UndefinedBehavior::Strict);
+        }
+
         install_weaver_extensions(&mut env, &self.target_config, true)?;
 
+        if let Some(profiler) = &self.profiling {
+            profiler::add_profiled_filter(&mut env, profiler);
+        }
+
         Ok(env)
     }
 
+    /// Injects the generated code into the marked regions of the existing file
+    /// and returns the updated content of this file.
+    fn inject_generated_code(
+        output_dir: &Path,
+        relative_path: &Path,
+        generated_code: &str,
+        injection: &InjectionConfig,
+    ) -> Result<String, Error> {
+        let output_file_path = output_dir.join(relative_path);
+        let existing_code = fs::read_to_string(&output_file_path).map_err(|e| InjectionFailed {
+            file: output_file_path.clone(),
+            error: format!("{}", e),
+        })?;
+        injection::inject(&existing_code, generated_code, injection).map_err(|error| {
+            InjectionFailed {
+                file: output_file_path,
+                error,
+            }
+        })
+    }
+
+    /// Set the permissions of a generated file.
+    ///
+    /// `file_permissions` replaces the permissions of the file, and `executable` adds the
+    /// execute permission wherever the read permission is granted. This is a no-op on
+    /// non-Unix platforms.
+    #[cfg(unix)]
+    fn set_file_permissions(
+        file: &Path,
+        file_permissions: Option<u32>,
+        executable: bool,
+    ) -> Result<(), Error> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if file_permissions.is_none() && !executable {
+            return Ok(());
+        }
+        let to_error = |e: std::io::Error| WriteGeneratedCodeFailed {
+            template: file.to_path_buf(),
+            error: format!("Failed to set the file permissions: {}", e),
+        };
+        let mut mode = match file_permissions {
+            Some(mode) => mode,
+            None => fs::metadata(file).map_err(to_error)?.permissions().mode() & 0o7777,
+        };
+        if executable {
+            // Grant the execute permission to whoever can read the file.
+            mode |= (mode & 0o444) >> 2;
+        }
+        fs::set_permissions(file, fs::Permissions::from_mode(mode)).map_err(to_error)
+    }
+
+    /// Set the permissions of a generated file.
+    /// File permissions are not supported on non-Unix platforms, so this is a no-op.
+    #[cfg(not(unix))]
+    fn set_file_permissions(
+        _file: &Path,
+        _file_permissions: Option<u32>,
+        _executable: bool,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Save the generated code to the output directory.
+    ///
+    /// If `write_only_changed_files` is true and the file already exists with
+    /// the same content, the file is not rewritten so its modification time is
+    /// preserved.
+    ///
+    /// Returns the path of the generated file and a flag indicating whether the
+    /// file has been written.
     fn save_generated_code(
         output_dir: &Path,
         relative_path: PathBuf,
-        generated_code: String,
-    ) -> Result<PathBuf, Error> {
-        // Create all intermediary directories if they don't exist
+        generated_code: impl AsRef<[u8]>,
+        write_only_changed_files: bool,
+    ) -> Result<(PathBuf, bool), Error> {
         let output_file_path = output_dir.join(relative_path);
+
+        // Skip the write if the existing file already contains the generated code.
+        if write_only_changed_files {
+            if let Ok(existing_code) = fs::read(&output_file_path) {
+                if existing_code == generated_code.as_ref() {
+                    return Ok((output_file_path, false));
+                }
+            }
+        }
+
+        // Create all intermediary directories if they don't exist
         if let Some(parent_dir) = output_file_path.parent() {
             if let Err(e) = fs::create_dir_all(parent_dir) {
                 return Err(WriteGeneratedCodeFailed {
@@ -666,7 +1088,7 @@ impl TemplateEngine {
             }
         })?;
 
-        Ok(output_file_path)
+        Ok((output_file_path, true))
     }
 }
 
@@ -679,8 +1101,10 @@ pub(crate) fn install_weaver_extensions(
 ) -> Result<(), Error> {
     code::add_filters(env, config, comment_flag)?;
     ansi::add_filters(env);
-    case::add_filters(env);
     otel::add_filters(env);
+    // Must be called after `otel::add_filters` to override the `*_const`
+    // filters when the case conversion is configured.
+    case::add_filters(env, config)?;
     util::add_filters(env, config);
     util::add_functions(env);
     otel::add_tests(env);
@@ -691,9 +1115,12 @@ pub(crate) fn install_weaver_extensions(
 mod tests {
     use std::fs;
     use std::path::{Path, PathBuf};
+    use std::sync::Arc;
 
     use globset::Glob;
+    use minijinja::Environment;
     use serde::Serialize;
+    use tempdir::TempDir;
 
     use weaver_common::TestLogger;
     use weaver_diff::diff_dir;
@@ -705,7 +1132,7 @@ mod tests {
     use crate::extensions::case::case_converter;
     use crate::file_loader::FileSystemFileLoader;
     use crate::registry::ResolvedRegistry;
-    use crate::{OutputDirective, TemplateEngine};
+    use crate::{template_context, JsonObject, OutputDirective, TemplateEngine};
 
     fn prepare_test(
         target: &str,
@@ -912,6 +1339,7 @@ mod tests {
             application_mode: ApplicationMode::Single,
             params: None,
             file_name: None,
+            injection: None,
         });
         engine.target_config.templates = Some(templates);
 
@@ -948,6 +1376,54 @@ mod tests {
         assert!(diff_dir("expected_output/test", "observed_output/test").unwrap());
     }
 
+    #[test]
+    fn test_save_generated_code_write_only_changed_files() {
+        let output_dir = TempDir::new("save_generated_code")
+            .expect("Failed to create temporary directory")
+            .into_path();
+        let relative_path = PathBuf::from("sub_dir/generated.txt");
+
+        // The file doesn't exist yet, so it must be written.
+        let (file, written) = TemplateEngine::save_generated_code(
+            &output_dir,
+            relative_path.clone(),
+            "content",
+            true,
+        )
+        .unwrap();
+        assert!(written);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "content");
+
+        // Same content, the write is skipped.
+        let (_, written) = TemplateEngine::save_generated_code(
+            &output_dir,
+            relative_path.clone(),
+            "content",
+            true,
+        )
+        .unwrap();
+        assert!(!written);
+
+        // Same content but the option is disabled, the file is rewritten.
+        let (_, written) = TemplateEngine::save_generated_code(
+            &output_dir,
+            relative_path.clone(),
+            "content",
+            false,
+        )
+        .unwrap();
+        assert!(written);
+
+        // Different content, the file is rewritten.
+        let (file, written) =
+            TemplateEngine::save_generated_code(&output_dir, relative_path, "new content", true)
+                .unwrap();
+        assert!(written);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new content");
+
+        fs::remove_dir_all(output_dir).unwrap_or_default();
+    }
+
     #[test]
     fn test_whitespace_control() {
         let (logger, engine, template_registry, observed_output, expected_output) =
@@ -999,6 +1475,68 @@ mod tests {
         assert!(diff_dir("expected_output/py_compat", "observed_output/py_compat").unwrap());
     }
 
+    #[test]
+    #[cfg(feature = "policies")]
+    fn test_before_generation_policies() {
+        #[derive(Serialize)]
+        struct Context {
+            text: String,
+        }
+
+        let generate = |mode: &str| {
+            let loader = FileSystemFileLoader::try_new("templates".into(), "py_compat")
+                .expect("Failed to create file system loader");
+            let config = WeaverConfig::try_from_loader(&loader).unwrap();
+            let params = Params::from_key_value_pairs(&[(
+                "mode",
+                serde_yaml::Value::String(mode.to_owned()),
+            )]);
+            let mut engine = TemplateEngine::new(config, loader, params);
+            let mut policy_engine = weaver_checker::Engine::new();
+            _ = policy_engine
+                .add_policy(
+                    "before_generation.rego",
+                    r#"
+package before_generation
+
+import rego.v1
+
+deny contains violation if {
+    input.params.mode == "strict"
+    input.registry.text != lower(input.registry.text)
+    violation := {
+        "type": "semconv_attribute",
+        "id": "lowercase_text",
+        "category": "generation",
+        "group": input.target,
+        "attr": "text",
+    }
+}
+"#,
+                )
+                .unwrap();
+            engine.set_policy_engine(policy_engine);
+            engine.generate(
+                TestLogger::default(),
+                &Context {
+                    text: "Hello, World!".to_owned(),
+                },
+                Path::new("observed_output/before_generation_policies"),
+                &OutputDirective::File,
+            )
+        };
+
+        // The target, the params, and the registry are the input of the policies.
+        match generate("strict") {
+            Err(crate::error::Error::PolicyViolation { target, violation }) => {
+                assert_eq!(target, "py_compat");
+                assert_eq!(violation.violation().id(), "lowercase_text");
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+        assert!(generate("lenient").is_ok());
+    }
+
     #[test]
     fn test_semconv_jq_functions() {
         let (logger, engine, template_registry, observed_output, expected_output) =
@@ -1046,6 +1584,160 @@ mod tests {
         assert!(diff_dir(expected_output, observed_output).unwrap());
     }
 
+    #[test]
+    fn test_injection() {
+        let (logger, engine, template_registry, observed_output, expected_output) =
+            prepare_test("injection", Params::default());
+
+        // The injection only rewrites the marked regions of an existing file.
+        fs::create_dir_all(&observed_output).unwrap();
+        _ = fs::copy(
+            "data/injection/UserAgentAttributes.java",
+            observed_output.join("UserAgentAttributes.java"),
+        )
+        .unwrap();
+
+        engine
+            .generate(
+                logger.clone(),
+                &template_registry,
+                observed_output.as_path(),
+                &OutputDirective::File,
+            )
+            .inspect_err(|e| {
+                print_dedup_errors(logger.clone(), e.clone());
+            })
+            .expect("Failed to generate registry assets");
+
+        assert!(diff_dir(&expected_output, &observed_output).unwrap());
+
+        // A missing output file is reported as an injection error.
+        fs::remove_file(observed_output.join("UserAgentAttributes.java")).unwrap();
+        let result = engine.generate(
+            logger,
+            &template_registry,
+            observed_output.as_path(),
+            &OutputDirective::File,
+        );
+        assert!(matches!(
+            result,
+            Err(crate::error::Error::InjectionFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_strict_undefined() {
+        let (logger, mut engine, template_registry, observed_output, _) =
+            prepare_test("strict_undefined", Params::default());
+
+        // The strict mode is enabled in `templates/strict_undefined/weaver.yaml`.
+        let result = engine.generate(
+            logger.clone(),
+            &template_registry,
+            observed_output.as_path(),
+            &OutputDirective::File,
+        );
+        assert!(matches!(
+            result,
+            Err(crate::error::Error::TemplateEvaluationFailed { .. })
+        ));
+
+        // Without the strict mode, undefined attributes are rendered as empty strings.
+        engine.target_config.strict_undefined = Some(false);
+        engine
+            .generate(
+                logger.clone(),
+                &template_registry,
+                observed_output.as_path(),
+                &OutputDirective::File,
+            )
+            .expect("Failed to generate registry assets");
+        assert_eq!(
+            fs::read_to_string(observed_output.join("undefined.md")).unwrap(),
+            "Undefined: ``"
+        );
+    }
+
+    #[test]
+    fn test_static_files() {
+        let (logger, engine, template_registry, observed_output, expected_output) =
+            prepare_test("static_files", Params::default());
+
+        engine
+            .generate(
+                logger.clone(),
+                &template_registry,
+                observed_output.as_path(),
+                &OutputDirective::File,
+            )
+            .inspect_err(|e| {
+                print_dedup_errors(logger.clone(), e.clone());
+            })
+            .expect("Failed to generate registry assets");
+
+        assert!(diff_dir(&expected_output, &observed_output).unwrap());
+    }
+
+    #[test]
+    fn test_profiling() {
+        let (logger, mut engine, template_registry, observed_output, expected_output) =
+            prepare_test("static_files", Params::default());
+        engine.enable_profiling();
+
+        engine
+            .generate(
+                logger.clone(),
+                &template_registry,
+                observed_output.as_path(),
+                &OutputDirective::File,
+            )
+            .inspect_err(|e| {
+                print_dedup_errors(logger.clone(), e.clone());
+            })
+            .expect("Failed to generate registry assets");
+
+        // The instrumented filters must not change the generated output.
+        assert!(diff_dir(&expected_output, &observed_output).unwrap());
+
+        let top = engine.profiler().expect("Profiling is enabled").top(10);
+        let names: Vec<_> = top.iter().map(|(name, _)| name.as_str()).collect();
+        assert!(names.contains(&"jinja filter `length`"));
+        assert!(names.contains(&"jq filter `.`"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (logger, engine, template_registry, observed_output, _) =
+            prepare_test("file_permissions", Params::default());
+
+        engine
+            .generate(
+                logger.clone(),
+                &template_registry,
+                observed_output.as_path(),
+                &OutputDirective::File,
+            )
+            .inspect_err(|e| {
+                print_dedup_errors(logger.clone(), e.clone());
+            })
+            .expect("Failed to generate registry assets");
+
+        let mode = |file: &str| {
+            fs::metadata(observed_output.join(file))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        // The execute permission is granted wherever the read permission is.
+        assert_eq!(mode("run.sh") & 0o111, (mode("run.sh") & 0o444) >> 2);
+        assert_ne!(mode("run.sh") & 0o100, 0);
+        assert_eq!(mode("secret.txt"), 0o600);
+    }
+
     #[test]
     fn test_comment_format() {
         let registry_id = "default";
@@ -1072,4 +1764,47 @@ mod tests {
 
         assert!(diff_dir(expected_output, observed_output).unwrap());
     }
+
+    #[test]
+    fn test_lazy_template_context() {
+        let filtered_result = Arc::new(serde_json::json!([
+            {"id": "http", "attributes": [{"name": "http.method"}, {"name": "a/b~c"}]},
+            {"id": "db", "attributes": [], "stable": true, "count": 2}
+        ]));
+
+        // The context of an item is a view into the shared filtered result,
+        // nothing is copied until the template accesses it.
+        let ctx = template_context(&filtered_result, "/0".to_owned());
+        let item = ctx.get_attr("ctx").expect("Missing ctx");
+        let view = item
+            .downcast_object_ref::<JsonObject>()
+            .expect("The item should not be converted");
+        assert!(Arc::ptr_eq(&view.root, &filtered_result));
+        let attributes = item.get_attr("attributes").expect("Missing attributes");
+        assert!(attributes.downcast_object_ref::<JsonObject>().is_some());
+
+        let env = Environment::new();
+        let render = |template: &str, pointer: &str| {
+            env.render_str(
+                template,
+                template_context(&filtered_result, pointer.to_owned()),
+            )
+            .expect("Failed to render the template")
+        };
+        assert_eq!(
+            render(
+                "{{ ctx.id }}:{% for attr in ctx.attributes %} {{ attr.name }}{% endfor %} ({{ ctx.attributes | length }}, {{ ctx.attributes[-1].name }})",
+                "/0"
+            ),
+            "http: http.method a/b~c (2, a/b~c)"
+        );
+        assert_eq!(
+            render(
+                "{{ ctx.stable }} {{ ctx.count + 1 }} {{ ctx.missing is undefined }} {{ ctx | tojson }}",
+                "/1"
+            ),
+            r#"true 3 true {"attributes":[],"count":2,"id":"db","stable":true}"#
+        );
+        assert_eq!(render("{{ ctx | length }}", ""), "2");
+    }
 }
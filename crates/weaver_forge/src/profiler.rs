@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Profiling of the template evaluation.
+//!
+//! When profiling is enabled, the filter applications of the templates are
+//! rewritten to go through an instrumented filter, and the JQ filters of the
+//! template configurations are timed. The time spent in each of them is
+//! aggregated to help target authors identify the slow parts of their templates.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use minijinja::machinery::{tokenize, Token, WhitespaceConfig};
+use minijinja::syntax::SyntaxConfig;
+use minijinja::value::Rest;
+use minijinja::{Environment, State, Value};
+
+/// Aggregated measurements for a profiled Jinja filter or JQ filter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileEntry {
+    /// Number of calls.
+    pub calls: u64,
+    /// Total time spent in the calls.
+    pub total: Duration,
+}
+
+/// Collects the time spent in the Jinja filters and the JQ filters.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    entries: Mutex<HashMap<String, ProfileEntry>>,
+}
+
+impl Profiler {
+    /// Record a call and its duration.
+    pub(crate) fn record(&self, name: &str, elapsed: Duration) {
+        let mut entries = self.entries.lock().expect("Lock poisoned");
+        let entry = entries.entry(name.to_owned()).or_default();
+        entry.calls += 1;
+        entry.total += elapsed;
+    }
+
+    /// Returns the `count` entries with the highest total time, in descending order.
+    #[must_use]
+    pub fn top(&self, count: usize) -> Vec<(String, ProfileEntry)> {
+        let entries = self.entries.lock().expect("Lock poisoned");
+        let mut entries: Vec<_> = entries
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.clone()))
+            .collect();
+        entries.sort_by(|(n1, e1), (n2, e2)| e2.total.cmp(&e1.total).then_with(|| n1.cmp(n2)));
+        entries.truncate(count);
+        entries
+    }
+
+    /// Returns a human-readable report of the `count` entries with the highest
+    /// total time.
+    #[must_use]
+    pub fn report(&self, count: usize) -> String {
+        let top = self.top(count);
+        let width = top.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        let mut report = format!(
+            "{:<width$}  {:>10}  {:>12}  {:>12}\n",
+            "Filter", "Calls", "Total (ms)", "Mean (µs)"
+        );
+        for (name, entry) in top {
+            let mean = entry.total.as_secs_f64() * 1_000_000.0 / entry.calls.max(1) as f64;
+            report.push_str(&format!(
+                "{:<width$}  {:>10}  {:>12.3}  {:>12.3}\n",
+                name,
+                entry.calls,
+                entry.total.as_secs_f64() * 1_000.0,
+                mean
+            ));
+        }
+        report
+    }
+
+    /// Runs `f` and records its duration under `name`.
+    pub(crate) fn time<T>(&self, name: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+}
+
+/// Name of the filter wrapping, in profiling mode, the filters applied by the
+/// templates.
+const PROFILED_FILTER: &str = "__profiled";
+
+/// Rewrites a template source so that each filter application goes through the
+/// profiled filter, e.g. `x | join(", ")` becomes `x | __profiled("join", ", ")`.
+///
+/// Only the filter names and their opening parenthesis are rewritten, so the
+/// line numbers reported by the template errors are preserved. The source is
+/// returned unchanged if it can't be tokenized, the template compilation
+/// then reports the syntax error.
+pub(crate) fn instrument_source(source: &str, syntax: &SyntaxConfig) -> String {
+    let Ok(tokens) = tokenize(source, false, syntax.clone(), WhitespaceConfig::default())
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return source.to_owned();
+    };
+
+    let mut instrumented = String::with_capacity(source.len());
+    let mut copied = 0;
+    for (i, (token, span)) in tokens.iter().enumerate() {
+        let Token::Ident(name) = token else {
+            continue;
+        };
+        // Filters follow a pipe, or start the filter chain of a `filter` block.
+        let is_filter = match i.checked_sub(1).map(|i| &tokens[i].0) {
+            Some(Token::Pipe) => true,
+            Some(Token::Ident("filter")) => {
+                matches!(
+                    i.checked_sub(2).map(|i| &tokens[i].0),
+                    Some(Token::BlockStart)
+                )
+            }
+            _ => false,
+        };
+        if !is_filter {
+            continue;
+        }
+
+        let start = span.start_offset as usize;
+        let (end, replacement) = match (tokens.get(i + 1), tokens.get(i + 2)) {
+            (Some((Token::ParenOpen, _)), Some((Token::ParenClose, paren))) => (
+                paren.start_offset as usize,
+                format!("{PROFILED_FILTER}(\"{name}\""),
+            ),
+            (Some((Token::ParenOpen, paren)), _) => (
+                paren.end_offset as usize,
+                format!("{PROFILED_FILTER}(\"{name}\", "),
+            ),
+            _ => (
+                span.end_offset as usize,
+                format!("{PROFILED_FILTER}(\"{name}\")"),
+            ),
+        };
+        instrumented.push_str(&source[copied..start]);
+        instrumented.push_str(&replacement);
+        copied = end;
+    }
+    instrumented.push_str(&source[copied..]);
+    instrumented
+}
+
+/// Adds the profiled filter to the environment. This filter applies the filter
+/// named by its first argument to the value with the remaining arguments, and
+/// records the time spent in it.
+///
+/// The filters of the environment are left unchanged, and the filters are
+/// applied with the state of the template being rendered.
+pub(crate) fn add_profiled_filter(env: &mut Environment<'static>, profiler: &Arc<Profiler>) {
+    let profiler = profiler.clone();
+    env.add_filter(
+        PROFILED_FILTER,
+        move |state: &State<'_, '_>,
+              value: Value,
+              name: &str,
+              args: Rest<Value>|
+              -> Result<Value, minijinja::Error> {
+            let args: Vec<Value> = std::iter::once(value).chain(args.0).collect();
+            profiler.time(&format!("jinja filter `{name}`"), || {
+                state.apply_filter(name, &args)
+            })
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instrument_source() {
+        let syntax = SyntaxConfig::default();
+        assert_eq!(
+            instrument_source(
+                "| Name | {{ ctx.name | snake_case }} {{ x|upper|join(', ') }}",
+                &syntax
+            ),
+            r#"| Name | {{ ctx.name | __profiled("snake_case") }} {{ x|__profiled("upper")|__profiled("join", ', ') }}"#
+        );
+        assert_eq!(
+            instrument_source(
+                "{% filter upper | trim() %}a{% endfilter %}{% set y | lower %}b{% endset %}",
+                &syntax
+            ),
+            r#"{% filter __profiled("upper") | __profiled("trim") %}a{% endfilter %}{% set y | __profiled("lower") %}b{% endset %}"#
+        );
+        // Invalid templates are not rewritten.
+        assert_eq!(instrument_source("{{ x | }", &syntax), "{{ x | }");
+    }
+
+    #[test]
+    fn test_add_profiled_filter() {
+        let profiler = Arc::new(Profiler::default());
+        let template = "{{ 3 | double }} {{ x | map(attribute='a') | join('-') }} \
+                        {{ [2, 1] | sort(reverse=true) | join }} {{ 'b' | prefixed }}";
+        let ctx = minijinja::context! {
+            x => vec![minijinja::context! { a => 1 }, minijinja::context! { a => 2 }],
+            prefix => "a"
+        };
+        let mut env = Environment::new();
+        env.add_filter("double", |v: i64| v * 2);
+        // A filter relying on the state of the template being rendered.
+        env.add_filter("prefixed", |state: &State<'_, '_>, v: &str| {
+            format!("{}{}", state.lookup("prefix").unwrap_or_default(), v)
+        });
+        add_profiled_filter(&mut env, &profiler);
+
+        let result = env
+            .render_str(&instrument_source(template, &SyntaxConfig::default()), ctx)
+            .unwrap();
+        assert_eq!(result, "6 1-2 21 ab");
+
+        let top = profiler.top(10);
+        assert_eq!(top.len(), 5);
+        assert!(top
+            .iter()
+            .all(|(_, entry)| entry.calls == 2 || entry.calls == 1));
+        assert!(profiler.report(1).contains("jinja filter"));
+        assert_eq!(profiler.report(10).lines().count(), 6);
+    }
+}
@@ -4,6 +4,8 @@
 //! catalog are resolved to the actual catalog entries to ease the template
 //! evaluation.
 
+use std::collections::HashMap;
+
 use crate::error::Error;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -12,7 +14,7 @@ use weaver_resolved_schema::catalog::Catalog;
 use weaver_resolved_schema::lineage::GroupLineage;
 use weaver_resolved_schema::registry::{Constraint, Group, Registry};
 use weaver_semconv::any_value::AnyValueSpec;
-use weaver_semconv::group::{GroupType, InstrumentSpec, SpanKindSpec};
+use weaver_semconv::group::{ExtendsSpec, GroupType, InstrumentSpec, SpanKindSpec};
 use weaver_semconv::stability::Stability;
 
 /// A resolved semantic convention registry used in the context of the template and policy
@@ -47,11 +49,11 @@ pub struct ResolvedGroup {
     #[serde(default)]
     #[serde(skip_serializing_if = "String::is_empty")]
     pub prefix: String,
-    /// Reference another semantic convention id. It inherits the prefix,
-    /// constraints, and all attributes defined in the specified semantic
-    /// convention.
+    /// Reference another semantic convention id, or a list of semantic
+    /// convention ids. It inherits the prefix, constraints, and all attributes
+    /// defined in the specified semantic conventions.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub extends: Option<String>,
+    pub extends: Option<ExtendsSpec>,
     /// Specifies the stability of the semantic convention.
     /// Note that, if stability is missing but deprecated is present, it will
     /// automatically set the stability to deprecated. If deprecated is
@@ -105,9 +107,18 @@ pub struct ResolvedGroup {
     /// The readable name for attribute groups used when generating registry tables.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
+    /// The URL of the telemetry schema the group is defined under.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_url: Option<String>,
     /// The body specification used for event semantic conventions.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub body: Option<AnyValueSpec>,
+    /// The content hash of the resolved group (hex-encoded SHA-256), covering
+    /// all its resolved fields except the lineage. It only changes when the
+    /// resolved definition of the group changes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub content_hash: String,
 }
 
 impl ResolvedGroup {
@@ -161,7 +172,9 @@ impl ResolvedGroup {
             name: group.name.clone(),
             lineage,
             display_name: group.display_name.clone(),
+            schema_url: group.schema_url.clone(),
             body: group.body.clone(),
+            content_hash: group.content_hash(catalog),
         })
     }
 }
@@ -222,7 +235,9 @@ impl ResolvedRegistry {
                     name: group.name.clone(),
                     lineage,
                     display_name: group.display_name.clone(),
+                    schema_url: group.schema_url.clone(),
                     body: group.body.clone(),
+                    content_hash: group.content_hash(catalog),
                 }
             })
             .collect();
@@ -236,14 +251,74 @@ impl ResolvedRegistry {
             groups,
         })
     }
+
+    /// Returns a registry containing only the groups added or changed since a
+    /// baseline registry, i.e. the groups without a baseline group with the
+    /// same id and the same content hash (the lineage is ignored).
+    #[must_use]
+    pub fn changed_groups(&self, baseline: &ResolvedRegistry) -> ResolvedRegistry {
+        let baseline_hashes: HashMap<&str, &str> = baseline
+            .groups
+            .iter()
+            .map(|group| (group.id.as_str(), group.content_hash.as_str()))
+            .collect();
+        ResolvedRegistry {
+            registry_url: self.registry_url.clone(),
+            groups: self
+                .groups
+                .iter()
+                .filter(|group| {
+                    group.content_hash.is_empty()
+                        || baseline_hashes.get(group.id.as_str())
+                            != Some(&group.content_hash.as_str())
+                })
+                .cloned()
+                .collect(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use weaver_resolver::SchemaResolver;
+    use weaver_semconv::registry::SemConvRegistry;
+
     use crate::ResolvedRegistry;
     use schemars::schema_for;
     use serde_json::to_string_pretty;
 
+    #[test]
+    fn test_changed_groups() {
+        let mut registry = SemConvRegistry::try_from_path_pattern("default", "data/*.yaml")
+            .into_result_failing_non_fatal()
+            .expect("Failed to load registry");
+        let schema = SchemaResolver::resolve_semantic_convention_registry(&mut registry)
+            .expect("Failed to resolve registry");
+        let registry = ResolvedRegistry::try_from_resolved_registry(
+            schema.registry("default").expect("registry not found"),
+            schema.catalog(),
+        )
+        .expect("Failed to create the resolved registry");
+        assert!(registry.groups.len() > 2);
+
+        // Compared to itself, no group is changed.
+        assert!(registry.changed_groups(&registry).groups.is_empty());
+
+        // A group is removed from the baseline (i.e. added), and another one
+        // is changed. The lineage is ignored.
+        let mut baseline = registry.clone();
+        let added = baseline.groups.remove(0);
+        baseline.groups[0].content_hash = "changed".to_owned();
+        baseline.groups[1].lineage = None;
+        let changed: Vec<_> = registry
+            .changed_groups(&baseline)
+            .groups
+            .into_iter()
+            .map(|group| group.id)
+            .collect();
+        assert_eq!(changed, vec![added.id, registry.groups[1].id.clone()]);
+    }
+
     #[test]
     fn test_json_schema_gen() {
         // Ensure the JSON schema can be generated for the TemplateRegistry
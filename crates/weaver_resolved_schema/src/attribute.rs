@@ -10,7 +10,9 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::ops::Not;
-use weaver_semconv::attribute::{AttributeSpec, AttributeType, Examples, RequirementLevel};
+use weaver_semconv::attribute::{
+    AttributeSpec, AttributeType, Examples, RequirementLevel, ValueConstraintsSpec,
+};
 use weaver_semconv::stability::Stability;
 
 /// An attribute definition.
@@ -24,6 +26,7 @@ pub struct Attribute {
     pub r#type: AttributeType,
     /// A brief description of the attribute.
     #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
     pub brief: String,
     /// Sequence of example values for the attribute or single example
     /// value. They are required only for string and string array
@@ -80,6 +83,14 @@ pub struct Attribute {
     /// Note: This is only used in a telemetry schema specification.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<Value>,
+
+    /// Validation constraints on the values of the attribute.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_constraints: Option<ValueConstraintsSpec>,
+
+    /// Other names of the attribute (e.g. legacy or vendor-specific names).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aliases: Option<Vec<String>>,
 }
 
 /// An unresolved attribute definition.
@@ -21,6 +21,7 @@ use weaver_semconv::stability::Stability;
 pub struct Catalog {
     /// Catalog of attributes used in the schema.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     pub attributes: Vec<Attribute>,
 }
 
@@ -38,6 +39,22 @@ pub struct Stats {
     pub stability_breakdown: HashMap<Stability, usize>,
     /// Number of deprecated attributes.
     pub deprecated_count: usize,
+    /// Enum cardinality breakdown (number of members: number of enum
+    /// attributes).
+    pub enum_card_breakdown: BTreeMap<usize, usize>,
+    /// Breakdown by namespace, i.e. the first segment of the attribute names.
+    pub namespace_breakdown: BTreeMap<String, NamespaceStats>,
+}
+
+/// Statistics on the attributes of a namespace.
+#[derive(Debug, Default, Serialize)]
+pub struct NamespaceStats {
+    /// Number of attributes in the namespace.
+    pub attribute_count: usize,
+    /// Number of deprecated attributes in the namespace.
+    pub deprecated_count: usize,
+    /// Ratio of deprecated attributes in the namespace (between 0 and 1).
+    pub deprecated_ratio: f64,
 }
 
 impl Catalog {
@@ -56,6 +73,21 @@ impl Catalog {
         self.attributes.get(attribute_ref.0 as usize)
     }
 
+    /// Statistics on the attributes of each namespace.
+    fn namespace_stats(&self) -> BTreeMap<String, NamespaceStats> {
+        let mut namespace_stats: BTreeMap<String, NamespaceStats> = BTreeMap::new();
+        for attr in self.attributes.iter() {
+            let namespace = attr.name.split('.').next().unwrap_or_default();
+            let stats = namespace_stats.entry(namespace.to_owned()).or_default();
+            stats.attribute_count += 1;
+            stats.deprecated_count += attr.deprecated.is_some() as usize;
+        }
+        for stats in namespace_stats.values_mut() {
+            stats.deprecated_ratio = stats.deprecated_count as f64 / stats.attribute_count as f64;
+        }
+        namespace_stats
+    }
+
     /// Statistics on the catalog.
     pub fn stats(&self) -> Stats {
         Stats {
@@ -109,6 +141,64 @@ impl Catalog {
                 .iter()
                 .filter(|attr| attr.deprecated.is_some())
                 .count(),
+            enum_card_breakdown: self
+                .attributes
+                .iter()
+                .filter_map(|attr| match &attr.r#type {
+                    AttributeType::Enum { members, .. } => Some(members.len()),
+                    _ => None,
+                })
+                .fold(BTreeMap::new(), |mut acc, card| {
+                    *acc.entry(card).or_insert(0) += 1;
+                    acc
+                }),
+            namespace_breakdown: self.namespace_stats(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::schema_from_yaml;
+
+    #[test]
+    fn test_catalog_stats() {
+        let schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type:
+      members:
+      - id: get
+        value: GET
+        stability: stable
+      - id: post
+        value: POST
+        stability: stable
+    brief: Method
+    requirement_level: recommended
+    stability: stable
+  - name: http.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+    deprecated: Replaced by `http.request.method`.
+  - name: url.full
+    type: string
+    brief: URL
+    requirement_level: recommended
+"#,
+        );
+        let stats = schema.catalog.stats();
+
+        assert_eq!(stats.enum_card_breakdown.get(&2), Some(&1));
+        assert_eq!(stats.namespace_breakdown.len(), 2);
+        let http = &stats.namespace_breakdown["http"];
+        assert_eq!(http.attribute_count, 2);
+        assert_eq!(http.deprecated_count, 1);
+        assert!((http.deprecated_ratio - 0.5).abs() < f64::EPSILON);
+        assert_eq!(stats.namespace_breakdown["url"].deprecated_count, 0);
+        assert!(serde_json::to_string(&schema.stats()).is_ok());
+    }
+}
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compaction of the catalog of a resolved telemetry schema.
+//!
+//! The resolution process never adds the same attribute twice to the catalog,
+//! but schemas produced by other tools, edited by hand, or rewritten after the
+//! resolution can contain structurally identical attributes. The compaction
+//! keeps the first occurrence of each attribute and rewrites the attribute
+//! references of the groups, the resource, and the instrumentation libraries.
+
+use std::collections::HashMap;
+
+use crate::attribute::AttributeRef;
+use crate::error::{handle_errors, Error};
+use crate::ResolvedTelemetrySchema;
+
+impl ResolvedTelemetrySchema {
+    /// Removes the structurally identical attributes from the catalog and
+    /// rewrites the attribute references accordingly. The order of the
+    /// remaining attributes is preserved.
+    ///
+    /// Returns the number of attributes removed from the catalog, or an
+    /// [`Error::AttributeNotFound`] for each attribute reference out of the
+    /// range of the catalog (the schema is then left unchanged).
+    pub fn compact(&mut self) -> Result<usize, Error> {
+        handle_errors(self.check_attribute_refs())?;
+
+        let mut first_refs: HashMap<String, Vec<AttributeRef>> = HashMap::new();
+        let mut attr_refs = Vec::with_capacity(self.catalog.attributes.len());
+        let mut attributes = Vec::with_capacity(self.catalog.attributes.len());
+        for attr in self.catalog.attributes.drain(..) {
+            // The content hash selects the candidates, the equality check
+            // protects against collisions.
+            let candidates = first_refs.entry(attr.content_hash()).or_default();
+            let existing = candidates
+                .iter()
+                .find(|attr_ref| attributes.get(attr_ref.0 as usize) == Some(&attr));
+            let attr_ref = if let Some(attr_ref) = existing {
+                *attr_ref
+            } else {
+                let attr_ref = AttributeRef(attributes.len() as u32);
+                candidates.push(attr_ref);
+                attributes.push(attr);
+                attr_ref
+            };
+            attr_refs.push(attr_ref);
+        }
+        let removed = attr_refs.len() - attributes.len();
+        self.catalog.attributes = attributes;
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        // All the references were checked above.
+        let remap = |attr_ref: AttributeRef| attr_refs[attr_ref.0 as usize];
+        for registry in self.registries.values_mut() {
+            for group in registry.groups.iter_mut() {
+                remap_dedup(&mut group.attributes, &remap);
+            }
+        }
+        if let Some(resource) = &mut self.resource {
+            remap_dedup(&mut resource.attributes, &remap);
+        }
+        for library in self
+            .instrumentation_library
+            .iter_mut()
+            .chain(self.dependencies.iter_mut())
+        {
+            library.remap_attribute_refs(&remap);
+        }
+        Ok(removed)
+    }
+}
+
+/// Rewrites a list of attribute references, removing the references that
+/// became duplicates.
+fn remap_dedup(attr_refs: &mut Vec<AttributeRef>, remap: &impl Fn(AttributeRef) -> AttributeRef) {
+    let mut remapped = Vec::with_capacity(attr_refs.len());
+    for attr_ref in attr_refs.drain(..).map(remap) {
+        if !remapped.contains(&attr_ref) {
+            remapped.push(attr_ref);
+        }
+    }
+    *attr_refs = remapped;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    #[test]
+    fn test_compact() {
+        let mut schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+  - name: server.address
+    type: string
+    brief: Address
+    requirement_level: recommended
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: required
+registries:
+  test:
+    groups:
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0, 2]
+    - id: span.http
+      type: span
+      brief: HTTP span
+      attributes: [2, 1, 3]
+resource:
+  attributes: [2]
+"#,
+        );
+
+        assert_eq!(schema.compact().ok(), Some(1));
+        assert_eq!(schema.catalog.attributes.len(), 3);
+        let groups = &schema.registries["test"].groups;
+        assert_eq!(groups[0].attributes, vec![AttributeRef(0)]);
+        assert_eq!(
+            groups[1].attributes,
+            vec![AttributeRef(0), AttributeRef(1), AttributeRef(2)]
+        );
+        assert_eq!(
+            schema.resource.as_ref().map(|r| r.attributes.clone()),
+            Some(vec![AttributeRef(0)])
+        );
+
+        // Already compact.
+        assert_eq!(schema.compact().ok(), Some(0));
+
+        // A dangling reference can't be rewritten.
+        schema
+            .registries
+            .get_mut("test")
+            .expect("registry not found")
+            .groups[0]
+            .attributes
+            .push(AttributeRef(3));
+        assert!(matches!(
+            schema.compact(),
+            Err(Error::CompoundError(errors)) if matches!(
+                errors.as_slice(),
+                [Error::AttributeNotFound { group_id, attr_ref: AttributeRef(3) }] if group_id == "registry.http"
+            )
+        ));
+        assert_eq!(schema.catalog.attributes.len(), 3);
+    }
+}
@@ -5,7 +5,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::attribute::AttributeRef;
-use crate::error::Error::{AttributeNotFound, CompoundError};
+use crate::error::Error::CompoundError;
 
 /// Errors emitted by this crate.
 #[derive(thiserror::Error, Debug, Clone, Deserialize, Serialize)]
@@ -19,6 +19,80 @@ pub enum Error {
         attr_ref: AttributeRef,
     },
 
+    /// A group id is declared several times in a registry.
+    #[error(
+        "The group id `{group_id}` is declared multiple times in the registry `{registry_id}`"
+    )]
+    DuplicateGroupId {
+        /// The registry id.
+        registry_id: String,
+        /// The group id.
+        group_id: String,
+    },
+
+    /// A group violates an invariant of the resolved telemetry schema.
+    #[error("Invalid group `{group_id}`: {error}")]
+    InvalidGroup {
+        /// The group id.
+        group_id: String,
+        /// The description of the violation.
+        error: String,
+    },
+
+    /// The resolved telemetry schema file can't be read or parsed.
+    #[error("Invalid resolved telemetry schema `{path}`: {error}")]
+    InvalidSchemaFile {
+        /// The path of the file (or `<str>` for in-memory content).
+        path: String,
+        /// The error message.
+        error: String,
+    },
+
+    /// The file format version of the resolved telemetry schema isn't
+    /// supported by this version of Weaver.
+    #[error("Unsupported resolved telemetry schema file format `{file_format}` (supported: {supported})")]
+    UnsupportedFileFormat {
+        /// The file format version found in the file.
+        file_format: String,
+        /// The supported file format versions.
+        supported: String,
+    },
+
+    /// A resolved telemetry schema can't be upgraded to the next file format
+    /// version.
+    #[error("Failed to upgrade the resolved telemetry schema from file format `{from}` to `{to}`: {error}")]
+    FileFormatMigrationFailed {
+        /// The file format version of the schema.
+        from: String,
+        /// The file format version targeted by the migration.
+        to: String,
+        /// The error message.
+        error: String,
+    },
+
+    /// Two resolved telemetry schemas can't be merged.
+    #[error("Conflict on `{id}` while merging the resolved telemetry schemas: {error}")]
+    MergeConflict {
+        /// The id of the conflicting attribute, registry or group.
+        id: String,
+        /// The description of the conflict.
+        error: String,
+    },
+
+    /// A JSON pointer doesn't start with `/`.
+    #[error("Invalid JSON pointer `{pointer}`: a non-empty pointer must start with `/`")]
+    InvalidJsonPointer {
+        /// The JSON pointer.
+        pointer: String,
+    },
+
+    /// No value exists at the location of a JSON pointer.
+    #[error("No value found at the JSON pointer `{pointer}`")]
+    JsonPointerNotFound {
+        /// The JSON pointer.
+        pointer: String,
+    },
+
     /// A generic container for multiple errors.
     #[error("Errors:\n{0:#?}")]
     CompoundError(Vec<Error>),
@@ -44,7 +118,7 @@ impl Error {
                 .into_iter()
                 .flat_map(|e| match e {
                     CompoundError(errors) => errors,
-                    e @ AttributeNotFound { .. } => vec![e],
+                    e => vec![e],
                 })
                 .collect(),
         )
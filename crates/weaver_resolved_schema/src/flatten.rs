@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Flattened serialization of a resolved telemetry schema.
+//!
+//! In the flattened form, the groups, the resource, and the signals of the
+//! instrumentation libraries embed their attribute definitions instead of
+//! references to the catalog, and the catalog is omitted. This form is larger, but consumers don't need to dereference the
+//! catalog (e.g. simple scripts or spreadsheet imports).
+
+use serde_json::{Map, Value};
+
+use crate::attribute::AttributeRef;
+use crate::error::{handle_errors, Error};
+use crate::ResolvedTelemetrySchema;
+
+impl ResolvedTelemetrySchema {
+    /// Returns the flattened form of the schema, ready to be serialized in
+    /// JSON or YAML: the attribute references of the groups, the resource,
+    /// and the instrumentation libraries are replaced by the attribute
+    /// definitions, and the catalog is removed.
+    ///
+    /// Returns an error listing all the attribute references not found in the
+    /// catalog.
+    pub fn to_flattened(&self) -> Result<Value, Error> {
+        let mut errors = vec![];
+        let mut inline = |owner: &str, attr_refs: &[AttributeRef]| -> Value {
+            Value::Array(
+                attr_refs
+                    .iter()
+                    .filter_map(|attr_ref| {
+                        let attr = self.catalog.attribute(attr_ref);
+                        if attr.is_none() {
+                            errors.push(Error::AttributeNotFound {
+                                group_id: owner.to_owned(),
+                                attr_ref: *attr_ref,
+                            });
+                        }
+                        attr.and_then(|attr| serde_json::to_value(attr).ok())
+                    })
+                    .collect(),
+            )
+        };
+
+        let mut registries = Map::new();
+        let mut registry_ids: Vec<_> = self.registries.keys().collect();
+        registry_ids.sort();
+        for registry_id in registry_ids {
+            let registry = &self.registries[registry_id];
+            let mut groups = vec![];
+            for group in registry.groups.iter() {
+                let mut value = to_object(group);
+                _ = value.insert(
+                    "attributes".to_owned(),
+                    inline(&group.id, &group.attributes),
+                );
+                groups.push(Value::Object(value));
+            }
+            let mut value = to_object(registry);
+            _ = value.insert("groups".to_owned(), Value::Array(groups));
+            _ = registries.insert(registry_id.clone(), Value::Object(value));
+        }
+
+        let mut schema = to_object(self);
+        _ = schema.remove("catalog");
+        _ = schema.insert("registries".to_owned(), Value::Object(registries));
+        if let Some(resource) = &self.resource {
+            let mut value = to_object(resource);
+            _ = value.insert(
+                "attributes".to_owned(),
+                inline("resource", &resource.attributes),
+            );
+            _ = schema.insert("resource".to_owned(), Value::Object(value));
+        }
+        if let Some(library) = &self.instrumentation_library {
+            let mut value = Value::Object(to_object(library));
+            inline_library(&mut value, &mut inline);
+            _ = schema.insert("instrumentation_library".to_owned(), value);
+        }
+        if !self.dependencies.is_empty() {
+            let dependencies = self
+                .dependencies
+                .iter()
+                .map(|library| {
+                    let mut value = Value::Object(to_object(library));
+                    inline_library(&mut value, &mut inline);
+                    value
+                })
+                .collect();
+            _ = schema.insert("dependencies".to_owned(), Value::Array(dependencies));
+        }
+        handle_errors(errors)?;
+        Ok(Value::Object(schema))
+    }
+}
+
+/// Replaces the attribute references of the signals of a serialized
+/// instrumentation library (metrics, events, spans, and their events and
+/// links) by the attribute definitions. All the `attributes` fields of an
+/// instrumentation library are lists of attribute references.
+fn inline_library(value: &mut Value, inline: &mut impl FnMut(&str, &[AttributeRef]) -> Value) {
+    match value {
+        Value::Object(object) => {
+            let owner = object
+                .get("name")
+                .or_else(|| object.get("link_name"))
+                .and_then(Value::as_str)
+                .unwrap_or("instrumentation_library")
+                .to_owned();
+            for (key, field) in object.iter_mut() {
+                if key == "attributes" {
+                    let attr_refs: Vec<AttributeRef> =
+                        serde_json::from_value(field.clone()).unwrap_or_default();
+                    *field = inline(&owner, &attr_refs);
+                } else {
+                    inline_library(field, inline);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values.iter_mut() {
+                inline_library(value, inline);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Serializes an item of the schema into a JSON object. The items of the
+/// schema are structs, so the serialization always produces an object.
+fn to_object<T: serde::Serialize>(item: &T) -> Map<String, Value> {
+    match serde_json::to_value(item) {
+        Ok(Value::Object(object)) => object,
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    #[test]
+    fn test_to_flattened() {
+        let schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+  - name: server.address
+    type: string
+    brief: Address
+    requirement_level: recommended
+registries:
+  test:
+    registry_url: https://example.com
+    groups:
+    - id: span.http
+      type: span
+      brief: HTTP span
+      attributes: [1, 0]
+resource:
+  attributes: [1]
+instrumentation_library:
+  name: http-client
+  spans:
+  - name: GET
+    attributes: [0]
+    events:
+    - name: retry
+      attributes: [1]
+"#,
+        );
+
+        let flattened = schema.to_flattened().expect("Failed to flatten the schema");
+        assert!(flattened.get("catalog").is_none());
+        assert_eq!(flattened["schema_url"], "https://example.com/schemas/1.0.0");
+        let group = &flattened["registries"]["test"]["groups"][0];
+        assert_eq!(group["id"], "span.http");
+        assert_eq!(group["attributes"][0]["name"], "server.address");
+        assert_eq!(group["attributes"][1]["brief"], "Method");
+        assert_eq!(
+            flattened["resource"]["attributes"][0]["name"],
+            "server.address"
+        );
+        let span = &flattened["instrumentation_library"]["spans"][0];
+        assert_eq!(span["attributes"][0]["name"], "http.request.method");
+        assert_eq!(span["events"][0]["attributes"][0]["name"], "server.address");
+
+        let mut schema = schema;
+        schema.catalog.attributes.truncate(1);
+        assert!(matches!(
+            schema.to_flattened(),
+            Err(Error::CompoundError(errors)) if errors.len() == 3
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dependency graph of the groups of a resolved registry, exportable in the
+//! DOT (Graphviz) and GraphML formats.
+//!
+//! The nodes are the groups and the edges are the relationships between them:
+//! - `extends`: the group extends another group.
+//! - `ref`: the group references an attribute defined by another group (as
+//!   recorded in the lineage of the group).
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use serde::Serialize;
+use weaver_semconv::group::{ExtendsSpec, GroupType};
+
+use crate::lineage::GroupLineage;
+use crate::registry::Registry;
+
+/// The kind of relationship between two groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    /// The source group extends the target group (one of its parents).
+    Extends,
+    /// The source group references an attribute of the target group.
+    Ref,
+}
+
+impl DependencyKind {
+    /// Returns the name of the relationship.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyKind::Extends => "extends",
+            DependencyKind::Ref => "ref",
+        }
+    }
+}
+
+/// A group of the dependency graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DependencyNode {
+    /// The id of the group.
+    pub id: String,
+    /// The type of the group.
+    pub r#type: GroupType,
+}
+
+/// A relationship between two groups of the dependency graph.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct DependencyEdge {
+    /// The id of the dependent group.
+    pub from: String,
+    /// The id of the group it depends on.
+    pub to: String,
+    /// The kind of relationship.
+    pub kind: DependencyKind,
+}
+
+/// The dependency graph of the groups of a registry.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyGraph {
+    /// The groups, in registry order.
+    pub nodes: Vec<DependencyNode>,
+    /// The relationships between the groups, sorted and deduplicated.
+    pub edges: BTreeSet<DependencyEdge>,
+}
+
+impl DependencyGraph {
+    /// Builds the dependency graph of the groups of a resolved registry.
+    #[must_use]
+    pub fn from_registry(registry: &Registry) -> Self {
+        let mut graph = Self::default();
+        for group in registry.groups.iter() {
+            graph.add_group(
+                &group.id,
+                &group.r#type,
+                group.extends.as_ref(),
+                group.lineage.as_ref(),
+            );
+        }
+        graph
+    }
+
+    /// Adds a group and its relationships to the graph.
+    ///
+    /// The attributes of the lineage whose source group is neither the group
+    /// itself nor one of the groups it extends are considered as references.
+    pub fn add_group(
+        &mut self,
+        id: &str,
+        group_type: &GroupType,
+        extends: Option<&ExtendsSpec>,
+        lineage: Option<&GroupLineage>,
+    ) {
+        self.nodes.push(DependencyNode {
+            id: id.to_owned(),
+            r#type: group_type.clone(),
+        });
+        let extends = extends
+            .map(ExtendsSpec::group_ids)
+            .or_else(|| lineage.map(GroupLineage::extends))
+            .unwrap_or_default();
+        for parent_id in extends {
+            _ = self.edges.insert(DependencyEdge {
+                from: id.to_owned(),
+                to: parent_id.clone(),
+                kind: DependencyKind::Extends,
+            });
+        }
+        for (_, attr_lineage) in lineage.into_iter().flat_map(|lineage| lineage.attributes()) {
+            let source_group = &attr_lineage.source_group;
+            if source_group != id && !extends.contains(source_group) {
+                _ = self.edges.insert(DependencyEdge {
+                    from: id.to_owned(),
+                    to: source_group.to_owned(),
+                    kind: DependencyKind::Ref,
+                });
+            }
+        }
+    }
+
+    /// Renders the graph in the DOT format (Graphviz).
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph registry {\n  rankdir=LR;\n  node [shape=box];\n");
+        for node in self.nodes.iter() {
+            _ = writeln!(
+                dot,
+                "  \"{}\" [label=\"{}\\n({})\"];",
+                escape_dot(&node.id),
+                escape_dot(&node.id),
+                group_type_name(&node.r#type)
+            );
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                DependencyKind::Extends => "solid",
+                DependencyKind::Ref => "dashed",
+            };
+            _ = writeln!(
+                dot,
+                "  \"{}\" -> \"{}\" [label=\"{}\", style={}];",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                edge.kind.as_str(),
+                style
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph in the GraphML format.
+    #[must_use]
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+             <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n  \
+             <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n  \
+             <graph id=\"registry\" edgedefault=\"directed\">\n",
+        );
+        for node in self.nodes.iter() {
+            _ = writeln!(
+                xml,
+                "    <node id=\"{}\"><data key=\"type\">{}</data></node>",
+                escape_xml(&node.id),
+                group_type_name(&node.r#type)
+            );
+        }
+        for edge in self.edges.iter() {
+            _ = writeln!(
+                xml,
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"kind\">{}</data></edge>",
+                escape_xml(&edge.from),
+                escape_xml(&edge.to),
+                edge.kind.as_str()
+            );
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+/// Returns the name of a group type as written in the semconv files.
+fn group_type_name(group_type: &GroupType) -> String {
+    serde_json::to_value(group_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+/// Escapes a string for a quoted DOT id.
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escapes a string for an XML attribute or text.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    fn graph() -> DependencyGraph {
+        let schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+  - name: server.address
+    type: string
+    brief: Address
+    requirement_level: recommended
+registries:
+  test:
+    groups:
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0]
+      lineage:
+        source_file: http.yaml
+        attributes:
+          http.request.method:
+            source_group: registry.http
+    - id: registry.server
+      type: attribute_group
+      brief: Server
+      attributes: [1]
+      lineage:
+        source_file: server.yaml
+        attributes:
+          server.address:
+            source_group: registry.server
+    - id: span.http
+      type: span
+      brief: HTTP span
+      extends: registry.http
+      attributes: [0, 1]
+      lineage:
+        source_file: http.yaml
+        extends: registry.http
+        attributes:
+          http.request.method:
+            source_group: registry.http
+          server.address:
+            source_group: registry.server
+"#,
+        );
+        DependencyGraph::from_registry(&schema.registries["test"])
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let graph = graph();
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(
+            graph.edges.iter().collect::<Vec<_>>(),
+            vec![
+                &DependencyEdge {
+                    from: "span.http".to_owned(),
+                    to: "registry.http".to_owned(),
+                    kind: DependencyKind::Extends,
+                },
+                &DependencyEdge {
+                    from: "span.http".to_owned(),
+                    to: "registry.server".to_owned(),
+                    kind: DependencyKind::Ref,
+                },
+            ]
+        );
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph registry {"));
+        assert!(dot.contains("\"span.http\" [label=\"span.http\\n(span)\"];"));
+        assert!(dot.contains("\"span.http\" -> \"registry.server\" [label=\"ref\", style=dashed];"));
+
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains(
+            "<node id=\"registry.http\"><data key=\"type\">attribute_group</data></node>"
+        ));
+        assert!(graphml.contains(
+            "<edge source=\"span.http\" target=\"registry.http\"><data key=\"kind\">extends</data></edge>"
+        ));
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escape_dot("a\"b\\c"), "a\\\"b\\\\c");
+        assert_eq!(escape_xml("<a & 'b'>"), "&lt;a &amp; &apos;b&apos;&gt;");
+    }
+}
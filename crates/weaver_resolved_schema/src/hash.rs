@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Stable content hashes of the attributes and groups of a resolved telemetry
+//! schema.
+//!
+//! A content hash is the hex-encoded SHA-256 digest of the JSON serialization
+//! of an item. Unlike the `Hash` implementations, it doesn't depend on the
+//! Rust version or the platform, so it can be stored by downstream caches
+//! and compared across Weaver runs.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::attribute::Attribute;
+use crate::catalog::Catalog;
+use crate::registry::Group;
+
+/// Returns the content hash of a serializable item.
+///
+/// The JSON serialization of the items of a resolved telemetry schema is
+/// deterministic: the fields are serialized in declaration order and the
+/// maps are sorted.
+pub(crate) fn content_hash<T: Serialize>(item: &T) -> String {
+    let mut hasher = Sha256::new();
+    // Writing into the hasher can't fail and the items of the schema are
+    // always serializable.
+    _ = serde_json::to_writer(HashWriter(&mut hasher), item);
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Adapter feeding the bytes written by the JSON serializer to a hasher.
+struct HashWriter<'a>(&'a mut Sha256);
+
+impl std::io::Write for HashWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Attribute {
+    /// Returns the content hash of the attribute, covering all its fields.
+    /// Two attributes have the same content hash if and only if they are
+    /// structurally identical.
+    #[must_use]
+    pub fn content_hash(&self) -> String {
+        content_hash(self)
+    }
+}
+
+impl Group {
+    /// Returns the content hash of the group, covering all its resolved
+    /// fields. The attributes are hashed by value (not by catalog reference)
+    /// and the lineage is ignored, so the hash only changes when the resolved
+    /// definition of the group changes, e.g. between two registry versions.
+    #[must_use]
+    pub fn content_hash(&self, catalog: &Catalog) -> String {
+        /// The hashed view of a group.
+        #[derive(Serialize)]
+        struct HashedGroup<'a> {
+            group: Group,
+            attributes: Vec<Option<&'a Attribute>>,
+        }
+
+        content_hash(&HashedGroup {
+            group: Group {
+                attributes: vec![],
+                lineage: None,
+                ..self.clone()
+            },
+            attributes: self
+                .attributes
+                .iter()
+                .map(|attr_ref| catalog.attribute(attr_ref))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use weaver_semconv::attribute::{
+        AttributeType, BasicRequirementLevelSpec, PrimitiveOrArrayTypeSpec, RequirementLevel,
+    };
+
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    fn attribute(brief: &str) -> Attribute {
+        Attribute {
+            name: "http.request.method".to_owned(),
+            r#type: AttributeType::PrimitiveOrArray(PrimitiveOrArrayTypeSpec::String),
+            brief: brief.to_owned(),
+            examples: None,
+            tag: None,
+            requirement_level: RequirementLevel::Basic(BasicRequirementLevelSpec::Recommended),
+            sampling_relevant: None,
+            note: String::new(),
+            stability: None,
+            deprecated: None,
+            prefix: false,
+            tags: None,
+            value: None,
+            value_constraints: None,
+            aliases: None,
+        }
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = attribute("Method").content_hash();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, attribute("Method").content_hash());
+        assert_ne!(hash, attribute("HTTP method").content_hash());
+    }
+
+    #[test]
+    fn test_group_content_hash() {
+        let schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+  - name: http.request.method
+    type: string
+    brief: HTTP method
+    requirement_level: recommended
+registries:
+  test:
+    groups:
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0]
+      lineage:
+        source_file: v1/http.yaml
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0]
+      lineage:
+        source_file: v2/http.yaml
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [1]
+"#,
+        );
+        let hashes: Vec<_> = schema.registries["test"]
+            .groups
+            .iter()
+            .map(|group| group.content_hash(&schema.catalog))
+            .collect();
+        // The lineage is ignored, the attributes are compared by value.
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+    }
+}
@@ -2,6 +2,7 @@
 
 //! Define an instrumentation library.
 
+use crate::attribute::AttributeRef;
 use crate::signal::{Event, MultivariateMetric, Span, UnivariateMetric};
 use crate::tags::Tags;
 use schemars::JsonSchema;
@@ -23,14 +24,51 @@ pub struct InstrumentationLibrary {
     tags: Option<Tags>,
     /// A set of univariate metrics produced by the instrumentation library.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     univariate_metrics: Vec<UnivariateMetric>,
     /// A set of multivariate metrics produced by the instrumentation library.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     multivariate_metrics: Vec<MultivariateMetric>,
     /// A set of events produced by the instrumentation library.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     events: Vec<Event>,
     /// A set of spans produced by the instrumentation library.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     spans: Vec<Span>,
 }
+
+impl InstrumentationLibrary {
+    /// Returns the attribute references of the signals of the library.
+    pub(crate) fn attribute_refs(&self) -> Vec<AttributeRef> {
+        self.univariate_metrics
+            .iter()
+            .flat_map(UnivariateMetric::attribute_refs)
+            .chain(
+                self.multivariate_metrics
+                    .iter()
+                    .flat_map(MultivariateMetric::attribute_refs),
+            )
+            .chain(self.events.iter().flat_map(Event::attribute_refs))
+            .chain(self.spans.iter().flat_map(Span::attribute_refs))
+            .collect()
+    }
+
+    /// Rewrites the attribute references of the signals of the library.
+    pub(crate) fn remap_attribute_refs(&mut self, remap: &impl Fn(AttributeRef) -> AttributeRef) {
+        for metric in self.univariate_metrics.iter_mut() {
+            metric.remap_attribute_refs(remap);
+        }
+        for metric in self.multivariate_metrics.iter_mut() {
+            metric.remap_attribute_refs(remap);
+        }
+        for event in self.events.iter_mut() {
+            event.remap_attribute_refs(remap);
+        }
+        for span in self.spans.iter_mut() {
+            span.remap_attribute_refs(remap);
+        }
+    }
+}
@@ -15,20 +15,37 @@ use weaver_version::Versions;
 
 pub mod attribute;
 pub mod catalog;
+pub mod compact;
 pub mod error;
+pub mod flatten;
+pub mod graph;
+pub mod hash;
 pub mod instrumentation_library;
 pub mod lineage;
+pub mod loader;
+pub mod merge;
 pub mod metric;
+pub mod namespace;
+pub mod pointer;
 pub mod registry;
 pub mod resource;
 pub mod signal;
 pub mod tags;
+pub mod validate;
 pub mod value;
 
+#[cfg(test)]
+pub(crate) mod test_util;
+
 /// The registry ID for the OpenTelemetry semantic conventions.
 /// This ID is reserved and should not be used by any other registry.
 pub const OTEL_REGISTRY_ID: &str = "OTEL";
 
+/// The version of the file format of the resolved telemetry schemas produced
+/// by this version of Weaver. The schemas written with an older version of
+/// the file format can be read with the [`loader`] module.
+pub const FILE_FORMAT: &str = "1.1.0";
+
 /// A Resolved Telemetry Schema.
 /// A Resolved Telemetry Schema is self-contained and doesn't contain any
 /// external references to other schemas or semantic conventions.
@@ -42,6 +59,7 @@ pub struct ResolvedTelemetrySchema {
     /// A map of named semantic convention registries that can be used in this schema
     /// and its descendants.
     #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(default)]
     pub registries: HashMap<String, Registry>,
     /// Catalog of unique items that are shared across multiple registries
     /// and signals.
@@ -55,6 +73,7 @@ pub struct ResolvedTelemetrySchema {
     pub instrumentation_library: Option<InstrumentationLibrary>,
     /// The list of dependencies of the current instrumentation application or library.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     pub dependencies: Vec<InstrumentationLibrary>,
     /// Definitions for each schema version in this family.
     /// Note: the ordering of versions is defined according to semver
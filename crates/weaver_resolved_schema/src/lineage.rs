@@ -8,6 +8,7 @@ use std::collections::{BTreeMap, BTreeSet};
 use serde::{Deserialize, Serialize};
 
 use weaver_semconv::attribute::{AttributeSpec, Examples, RequirementLevel};
+use weaver_semconv::group::ExtendsSpec;
 use weaver_semconv::stability::Stability;
 
 /// Attribute lineage (at the field level).
@@ -28,6 +29,15 @@ pub struct AttributeLineage {
     pub locally_overridden_fields: BTreeSet<String>,
 }
 
+/// A position in a source file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+pub struct SourceLocation {
+    /// The line number (1-based).
+    pub line: usize,
+    /// The column number (1-based).
+    pub column: usize,
+}
+
 /// Group lineage.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 #[must_use]
@@ -35,6 +45,25 @@ pub struct GroupLineage {
     /// The path or URL of the source file where the group is defined.
     source_file: String,
 
+    /// The location of the group definition in the source file (if recorded).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    source_location: Option<SourceLocation>,
+
+    /// The location of the attributes defined or referenced by the group in
+    /// the source file (if recorded).
+    ///
+    /// Note: Use a BTreeMap to ensure a deterministic order of attributes.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default)]
+    attribute_locations: BTreeMap<String, SourceLocation>,
+
+    /// The id of the group, or the ids of the groups, extended by this group
+    /// (if any).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    extends: Option<ExtendsSpec>,
+
     /// The lineage per attribute.
     ///
     /// Note: Use a BTreeMap to ensure a deterministic order of attributes.
@@ -420,10 +449,34 @@ impl GroupLineage {
     pub fn new(provenance: &str) -> Self {
         Self {
             source_file: provenance.replace('\\', "/"),
+            source_location: None,
+            attribute_locations: Default::default(),
+            extends: None,
             attributes: Default::default(),
         }
     }
 
+    /// Sets the source file of the group (path or URL).
+    pub fn set_source_file(&mut self, source_file: &str) {
+        self.source_file = source_file.replace('\\', "/");
+    }
+
+    /// Records the location of the group definition in the source file.
+    pub fn set_source_location(&mut self, location: SourceLocation) {
+        self.source_location = Some(location);
+    }
+
+    /// Records the location of an attribute defined or referenced by the group
+    /// in the source file.
+    pub fn add_attribute_location(&mut self, attr_id: String, location: SourceLocation) {
+        _ = self.attribute_locations.insert(attr_id, location);
+    }
+
+    /// Records the group(s) extended by this group.
+    pub fn set_extends(&mut self, extends: &ExtendsSpec) {
+        self.extends = Some(extends.clone());
+    }
+
     /// Adds an attribute lineage.
     pub fn add_attribute_lineage(&mut self, attr_id: String, attribute_lineage: AttributeLineage) {
         _ = self.attributes.insert(attr_id, attribute_lineage);
@@ -440,4 +493,38 @@ impl GroupLineage {
     pub fn source_file(&self) -> &str {
         &self.source_file
     }
+
+    /// Returns the location of the group definition in the source file (if
+    /// recorded).
+    #[must_use]
+    pub fn source_location(&self) -> Option<&SourceLocation> {
+        self.source_location.as_ref()
+    }
+
+    /// Returns the location of the given attribute in the source file of the
+    /// group (if recorded).
+    #[must_use]
+    pub fn attribute_location(&self, attr_id: &str) -> Option<&SourceLocation> {
+        self.attribute_locations.get(attr_id)
+    }
+
+    /// Returns the ids of the groups extended by this group (empty if none).
+    #[must_use]
+    pub fn extends(&self) -> &[String] {
+        self.extends
+            .as_ref()
+            .map(ExtendsSpec::group_ids)
+            .unwrap_or_default()
+    }
+
+    /// Returns the lineage of the given attribute (if any).
+    #[must_use]
+    pub fn attribute(&self, attr_id: &str) -> Option<&AttributeLineage> {
+        self.attributes.get(attr_id)
+    }
+
+    /// Returns an iterator over the attribute lineages of the group.
+    pub fn attributes(&self) -> impl Iterator<Item = (&String, &AttributeLineage)> {
+        self.attributes.iter()
+    }
 }
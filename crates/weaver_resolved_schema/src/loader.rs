@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Loading of resolved telemetry schemas written with any supported version
+//! of the file format.
+//!
+//! The `file_format` field of a schema is read first. A schema written with
+//! an older version of the file format is upgraded, one migration at a time,
+//! to the current version ([`FILE_FORMAT`]) before being deserialized. A
+//! schema written with an unknown (e.g. newer) version is rejected.
+//!
+//! Supported versions:
+//! - `1.0.0`: initial version.
+//! - `1.1.0`: the group lineage can record the location (line and column) of
+//!   the group and its attributes in the source file.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::error::Error;
+use crate::{ResolvedTelemetrySchema, FILE_FORMAT};
+
+/// A function upgrading a schema from a file format version to the next one.
+type MigrationFn = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// The migrations from each older file format version to the next one, in
+/// version order. The last migration targets [`FILE_FORMAT`].
+const MIGRATIONS: &[(&str, &str, MigrationFn)] = &[("1.0.0", "1.1.0", migrate_1_0_0)];
+
+/// Upgrades a schema from the file format `1.0.0` to `1.1.0`.
+///
+/// The source locations of the lineage are optional, so the content is
+/// unchanged.
+fn migrate_1_0_0(_schema: &mut Map<String, Value>) -> Result<(), String> {
+    Ok(())
+}
+
+impl ResolvedTelemetrySchema {
+    /// Loads a resolved telemetry schema from a JSON or YAML file (based on
+    /// its extension), upgrading it to the current file format if needed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let invalid_file = |error: String| Error::InvalidSchemaFile {
+            path: path.display().to_string(),
+            error,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| invalid_file(e.to_string()))?;
+        let value = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(|e| invalid_file(e.to_string()))?
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| invalid_file(e.to_string()))?
+        };
+        Self::from_value(value).map_err(|e| match e {
+            Error::InvalidSchemaFile { error, .. } => invalid_file(error),
+            e => e,
+        })
+    }
+
+    /// Loads a resolved telemetry schema from a JSON string, upgrading it to
+    /// the current file format if needed.
+    pub fn from_json_str(content: &str) -> Result<Self, Error> {
+        let value = serde_json::from_str(content).map_err(|e| Error::InvalidSchemaFile {
+            path: "<str>".to_owned(),
+            error: e.to_string(),
+        })?;
+        Self::from_value(value)
+    }
+
+    /// Loads a resolved telemetry schema from a YAML string, upgrading it to
+    /// the current file format if needed.
+    pub fn from_yaml_str(content: &str) -> Result<Self, Error> {
+        let value = serde_yaml::from_str(content).map_err(|e| Error::InvalidSchemaFile {
+            path: "<str>".to_owned(),
+            error: e.to_string(),
+        })?;
+        Self::from_value(value)
+    }
+
+    /// Upgrades a schema to the current file format and deserializes it.
+    fn from_value(value: Value) -> Result<Self, Error> {
+        let invalid_schema = |error: String| Error::InvalidSchemaFile {
+            path: "<str>".to_owned(),
+            error,
+        };
+        let Value::Object(mut schema) = value else {
+            return Err(invalid_schema("expected a mapping".to_owned()));
+        };
+        let mut file_format = match schema.get("file_format") {
+            Some(Value::String(file_format)) => file_format.clone(),
+            _ => return Err(invalid_schema("missing `file_format` field".to_owned())),
+        };
+
+        let Some(first_migration) = MIGRATIONS
+            .iter()
+            .position(|(from, _, _)| *from == file_format)
+            .or_else(|| (file_format == FILE_FORMAT).then_some(MIGRATIONS.len()))
+        else {
+            return Err(Error::UnsupportedFileFormat {
+                file_format,
+                supported: supported_file_formats().join(", "),
+            });
+        };
+        for (from, to, migrate) in &MIGRATIONS[first_migration..] {
+            migrate(&mut schema).map_err(|error| Error::FileFormatMigrationFailed {
+                from: (*from).to_owned(),
+                to: (*to).to_owned(),
+                error,
+            })?;
+            file_format = (*to).to_owned();
+            _ = schema.insert("file_format".to_owned(), Value::String(file_format.clone()));
+        }
+
+        serde_json::from_value(Value::Object(schema)).map_err(|e| invalid_schema(e.to_string()))
+    }
+}
+
+/// Returns the file format versions that can be loaded, oldest first.
+#[must_use]
+pub fn supported_file_formats() -> Vec<&'static str> {
+    MIGRATIONS
+        .iter()
+        .map(|(from, _, _)| *from)
+        .chain(std::iter::once(FILE_FORMAT))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+schema_url: https://example.com/schemas/1.0.0
+catalog:
+  attributes:
+  - name: test.attr
+    type: string
+    brief: Attribute
+    requirement_level: recommended
+registries:
+  test:
+    registry_url: https://example.com
+    groups:
+    - id: registry.test
+      type: attribute_group
+      brief: Registry
+      attributes: [0]
+"#;
+
+    #[test]
+    fn test_load_file_formats() {
+        for file_format in supported_file_formats() {
+            let content = format!("file_format: {}{}", file_format, SCHEMA);
+            let schema = ResolvedTelemetrySchema::from_yaml_str(&content)
+                .expect("Failed to load the schema");
+            assert_eq!(schema.file_format, FILE_FORMAT);
+            assert_eq!(schema.registries["test"].groups[0].id, "registry.test");
+
+            // JSON round trip.
+            let json = serde_json::to_string(&schema).expect("Failed to serialize the schema");
+            let schema =
+                ResolvedTelemetrySchema::from_json_str(&json).expect("Failed to load the schema");
+            assert_eq!(schema.file_format, FILE_FORMAT);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_file_formats() {
+        let content = format!("file_format: 99.0.0{}", SCHEMA);
+        assert!(matches!(
+            ResolvedTelemetrySchema::from_yaml_str(&content),
+            Err(Error::UnsupportedFileFormat { file_format, .. }) if file_format == "99.0.0"
+        ));
+        assert!(matches!(
+            ResolvedTelemetrySchema::from_yaml_str(SCHEMA),
+            Err(Error::InvalidSchemaFile { .. })
+        ));
+    }
+}
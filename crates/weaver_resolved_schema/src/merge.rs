@@ -0,0 +1,358 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Merge of resolved telemetry schemas, e.g. to compose the schema of an
+//! application from the schema of its own registry and the schemas of its
+//! library dependencies without resolving all the registries together.
+//!
+//! The attributes of the other catalog are deduplicated against the catalog
+//! of the schema (identical attributes share the same reference), and the
+//! attribute references of the merged items are rewritten accordingly. The
+//! following conflicts are reported:
+//! - an attribute defined with different types in the two catalogs,
+//! - a registry with different URLs in the two schemas,
+//! - a group defined differently in the same registry of the two schemas
+//!   (the lineage is ignored).
+
+use std::collections::HashMap;
+
+use crate::attribute::{Attribute, AttributeRef};
+use crate::catalog::Catalog;
+use crate::error::{handle_errors, Error};
+use crate::registry::{Group, Registry};
+use crate::ResolvedTelemetrySchema;
+
+impl ResolvedTelemetrySchema {
+    /// Merges another resolved telemetry schema into this one.
+    ///
+    /// The registries, catalog attributes and resource attributes of the
+    /// other schema are added to this schema. The instrumentation library
+    /// and the dependencies of the other schema are added to the dependencies
+    /// of this schema (once per name and version). The schema URL and the
+    /// versions of this schema are kept.
+    ///
+    /// All the conflicts are reported at once, and this schema is left
+    /// unchanged if there is any. The attribute references of the other
+    /// schema out of the range of its catalog are reported as
+    /// [`Error::AttributeNotFound`] errors before any other check.
+    pub fn merge(&mut self, other: ResolvedTelemetrySchema) -> Result<(), Error> {
+        handle_errors(other.check_attribute_refs())?;
+        let mut errors = vec![];
+
+        // Deduplicate the catalog and compute the new references of the
+        // attributes of the other catalog.
+        let mut known_attrs: HashMap<&Attribute, AttributeRef> = HashMap::new();
+        let mut attr_types = HashMap::new();
+        for (index, attr) in self.catalog.attributes.iter().enumerate() {
+            _ = known_attrs
+                .entry(attr)
+                .or_insert(AttributeRef(index as u32));
+            _ = attr_types.entry(attr.name.as_str()).or_insert(&attr.r#type);
+        }
+        let mut new_attrs = vec![];
+        let mut attr_refs = Vec::with_capacity(other.catalog.attributes.len());
+        for attr in other.catalog.attributes.iter() {
+            if let Some(attr_type) = attr_types.get(attr.name.as_str()) {
+                if *attr_type != &attr.r#type {
+                    errors.push(Error::MergeConflict {
+                        id: attr.name.clone(),
+                        error: format!(
+                            "the attribute is defined with the types `{}` and `{}`",
+                            attr_type, attr.r#type
+                        ),
+                    });
+                }
+            }
+            let next_ref = AttributeRef((self.catalog.attributes.len() + new_attrs.len()) as u32);
+            let attr_ref = *known_attrs.entry(attr).or_insert_with(|| {
+                new_attrs.push(attr.clone());
+                next_ref
+            });
+            attr_refs.push(attr_ref);
+        }
+        // All the references of the other schema were checked above.
+        let remap = |attr_ref: AttributeRef| attr_refs[attr_ref.0 as usize];
+        let merged_catalog = Catalog {
+            attributes: self
+                .catalog
+                .attributes
+                .iter()
+                .chain(new_attrs.iter())
+                .cloned()
+                .collect(),
+        };
+
+        // Check the registries and collect the groups to add.
+        let mut new_groups = vec![];
+        for (registry_id, registry) in other.registries.iter() {
+            let existing = self.registries.get(registry_id);
+            if let Some(existing) = existing {
+                if !existing.registry_url.is_empty()
+                    && !registry.registry_url.is_empty()
+                    && existing.registry_url != registry.registry_url
+                {
+                    errors.push(Error::MergeConflict {
+                        id: registry_id.clone(),
+                        error: format!(
+                            "the registry is published at `{}` and `{}`",
+                            existing.registry_url, registry.registry_url
+                        ),
+                    });
+                }
+            }
+            for group in registry.groups.iter() {
+                let mut group = group.clone();
+                group.attributes = group.attributes.into_iter().map(remap).collect();
+                match existing.and_then(|r| r.groups.iter().find(|g| g.id == group.id)) {
+                    Some(existing_group) => {
+                        if !same_definition(existing_group, &group, &merged_catalog) {
+                            errors.push(Error::MergeConflict {
+                                id: group.id.clone(),
+                                error: format!(
+                                    "the group is defined differently in the registry `{}`",
+                                    registry_id
+                                ),
+                            });
+                        }
+                    }
+                    None => new_groups.push((registry_id.clone(), registry, group)),
+                }
+            }
+        }
+        handle_errors(errors)?;
+
+        // No conflicts, the other schema can be merged.
+        self.catalog = merged_catalog;
+        for (registry_id, registry, group) in new_groups {
+            self.registries
+                .entry(registry_id)
+                .or_insert_with(|| Registry {
+                    registry_url: registry.registry_url.clone(),
+                    groups: vec![],
+                })
+                .groups
+                .push(group);
+        }
+        if let Some(mut resource) = other.resource {
+            resource.attributes = resource.attributes.into_iter().map(remap).collect();
+            match &mut self.resource {
+                Some(existing) => {
+                    for attr_ref in resource.attributes {
+                        if !existing.attributes.contains(&attr_ref) {
+                            existing.attributes.push(attr_ref);
+                        }
+                    }
+                }
+                None => self.resource = Some(resource),
+            }
+        }
+        for mut library in other
+            .instrumentation_library
+            .into_iter()
+            .chain(other.dependencies)
+        {
+            let already_known = self
+                .instrumentation_library
+                .iter()
+                .chain(self.dependencies.iter())
+                .any(|known| known.name == library.name && known.version == library.version);
+            if !already_known {
+                library.remap_attribute_refs(&remap);
+                self.dependencies.push(library);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if the two groups have the same definition, ignoring their
+/// lineage and comparing their attributes by value.
+fn same_definition(group: &Group, other: &Group, catalog: &Catalog) -> bool {
+    let attributes = |group: &Group| -> Vec<Option<&Attribute>> {
+        group
+            .attributes
+            .iter()
+            .map(|attr_ref| catalog.attribute(attr_ref))
+            .collect()
+    };
+    let without_refs = |group: &Group| Group {
+        attributes: vec![],
+        lineage: None,
+        ..group.clone()
+    };
+    attributes(group) == attributes(other) && without_refs(group) == without_refs(other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    #[test]
+    fn test_merge() {
+        let mut app = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: app.id
+    type: string
+    brief: Id
+    requirement_level: recommended
+  - name: server.address
+    type: string
+    brief: Address
+    requirement_level: recommended
+registries:
+  app:
+    groups:
+    - id: registry.app
+      type: attribute_group
+      brief: App
+      attributes: [0]
+  otel:
+    registry_url: https://opentelemetry.io
+    groups:
+    - id: registry.server
+      type: attribute_group
+      brief: Server
+      attributes: [1]
+      lineage:
+        source_file: app/server.yaml
+resource:
+  attributes: [0]
+"#,
+        );
+        let lib = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: server.address
+    type: string
+    brief: Address
+    requirement_level: recommended
+  - name: db.system
+    type: string
+    brief: System
+    requirement_level: recommended
+registries:
+  otel:
+    registry_url: https://opentelemetry.io
+    groups:
+    - id: registry.server
+      type: attribute_group
+      brief: Server
+      attributes: [0]
+      lineage:
+        source_file: lib/server.yaml
+    - id: registry.db
+      type: attribute_group
+      brief: Database
+      attributes: [1]
+resource:
+  attributes: [0, 1]
+instrumentation_library:
+  name: db-client
+  version: 1.0.0
+"#,
+        );
+
+        app.merge(lib).expect("Failed to merge the schemas");
+        let names: Vec<_> = app
+            .catalog
+            .attributes
+            .iter()
+            .map(|attr| attr.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["app.id", "server.address", "db.system"]);
+        let otel = &app.registries["otel"];
+        assert_eq!(otel.groups.len(), 2);
+        assert_eq!(otel.groups[1].id, "registry.db");
+        assert_eq!(otel.groups[1].attributes, vec![AttributeRef(2)]);
+        assert_eq!(
+            app.resource.as_ref().map(|r| r.attributes.clone()),
+            Some(vec![AttributeRef(0), AttributeRef(1), AttributeRef(2)])
+        );
+        assert_eq!(app.dependencies.len(), 1);
+        assert_eq!(app.dependencies[0].name.as_deref(), Some("db-client"));
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let mut app = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: server.port
+    type: int
+    brief: Port
+    requirement_level: recommended
+registries:
+  otel:
+    registry_url: https://opentelemetry.io
+    groups:
+    - id: registry.server
+      type: attribute_group
+      brief: Server
+      attributes: [0]
+"#,
+        );
+        let lib = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: server.port
+    type: string
+    brief: Port
+    requirement_level: recommended
+registries:
+  otel:
+    registry_url: https://example.com
+    groups:
+    - id: registry.server
+      type: attribute_group
+      brief: Server
+      attributes: [0]
+"#,
+        );
+
+        let Err(Error::CompoundError(errors)) = app.merge(lib) else {
+            panic!("Expected merge conflicts");
+        };
+        let ids: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                Error::MergeConflict { id, .. } => id.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(ids, vec!["server.port", "otel", "registry.server"]);
+        // The schema is unchanged.
+        assert_eq!(app.catalog.attributes.len(), 1);
+
+        // A dangling reference of the other schema can't be rewritten.
+        let lib = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: db.system
+    type: string
+    brief: System
+    requirement_level: recommended
+registries:
+  otel:
+    groups:
+    - id: registry.db
+      type: attribute_group
+      brief: Database
+      attributes: [0, 1]
+"#,
+        );
+        let Err(Error::CompoundError(errors)) = app.merge(lib) else {
+            panic!("Expected a dangling reference");
+        };
+        assert!(matches!(
+            errors.as_slice(),
+            [Error::AttributeNotFound { group_id, attr_ref: AttributeRef(1) }] if group_id == "registry.db"
+        ));
+        assert_eq!(app.catalog.attributes.len(), 1);
+    }
+}
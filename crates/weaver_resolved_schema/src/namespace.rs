@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Queries on the attributes and groups of a resolved telemetry schema by
+//! namespace.
+//!
+//! A namespace is a dot-separated prefix of an attribute name or a group id,
+//! e.g. `http` contains `http.request.method` and `http.response.status_code`,
+//! but not `https.enabled`.
+
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+
+use crate::attribute::Attribute;
+use crate::registry::Group;
+use crate::ResolvedTelemetrySchema;
+
+/// An index of the attributes and groups of a resolved telemetry schema
+/// sorted by name, answering namespace queries without scanning the whole
+/// schema.
+///
+/// Each part of the index is built on the first query using it. The index
+/// borrows the schema, so the schema can't be modified while it is in use.
+#[derive(Debug)]
+pub struct NamespaceIndex<'a> {
+    schema: &'a ResolvedTelemetrySchema,
+    attributes: OnceCell<BTreeMap<&'a str, Vec<&'a Attribute>>>,
+    groups: OnceCell<BTreeMap<&'a str, Vec<&'a Group>>>,
+}
+
+impl<'a> NamespaceIndex<'a> {
+    /// Creates an empty index of the given schema.
+    #[must_use]
+    pub fn new(schema: &'a ResolvedTelemetrySchema) -> Self {
+        Self {
+            schema,
+            attributes: OnceCell::new(),
+            groups: OnceCell::new(),
+        }
+    }
+
+    /// Returns the attributes of the catalog in the given namespace, sorted by
+    /// name. An attribute refined by several groups is returned once per
+    /// refinement.
+    pub fn attributes_in_namespace<'i>(
+        &'i self,
+        prefix: &'i str,
+    ) -> impl Iterator<Item = &'a Attribute> + 'i {
+        let index = self.attributes.get_or_init(|| {
+            let mut index: BTreeMap<&str, Vec<&Attribute>> = BTreeMap::new();
+            for attr in self.schema.catalog.attributes.iter() {
+                index.entry(attr.name.as_str()).or_default().push(attr);
+            }
+            index
+        });
+        in_namespace(index, prefix)
+    }
+
+    /// Returns the groups of all the registries whose id is in the given
+    /// namespace (e.g. `registry.http`), sorted by id.
+    pub fn groups_in_namespace<'i>(
+        &'i self,
+        prefix: &'i str,
+    ) -> impl Iterator<Item = &'a Group> + 'i {
+        let index = self.groups.get_or_init(|| {
+            let mut index: BTreeMap<&str, Vec<&Group>> = BTreeMap::new();
+            for registry in self.schema.registries.values() {
+                for group in registry.groups.iter() {
+                    index.entry(group.id.as_str()).or_default().push(group);
+                }
+            }
+            index
+        });
+        in_namespace(index, prefix)
+    }
+}
+
+impl ResolvedTelemetrySchema {
+    /// Returns an index answering namespace queries on this schema. Prefer
+    /// the index over [`Self::attributes_in_namespace`] and
+    /// [`Self::groups_in_namespace`] to run several queries.
+    #[must_use]
+    pub fn namespace_index(&self) -> NamespaceIndex<'_> {
+        NamespaceIndex::new(self)
+    }
+
+    /// Returns the attributes of the catalog in the given namespace (e.g.
+    /// `http`), sorted by name (see
+    /// [`NamespaceIndex::attributes_in_namespace`]).
+    pub fn attributes_in_namespace(&self, prefix: &str) -> impl Iterator<Item = &Attribute> {
+        let attributes: Vec<_> = self
+            .namespace_index()
+            .attributes_in_namespace(prefix)
+            .collect();
+        attributes.into_iter()
+    }
+
+    /// Returns the groups of all the registries whose id is in the given
+    /// namespace (e.g. `registry.http`), sorted by id (see
+    /// [`NamespaceIndex::groups_in_namespace`]).
+    pub fn groups_in_namespace(&self, prefix: &str) -> impl Iterator<Item = &Group> {
+        let groups: Vec<_> = self.namespace_index().groups_in_namespace(prefix).collect();
+        groups.into_iter()
+    }
+}
+
+/// Returns true if the name is the namespace itself or is in the namespace.
+/// An empty namespace contains every name.
+fn is_in_namespace(name: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('.');
+    prefix.is_empty()
+        || name
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Returns the values of the sorted index whose key is in the namespace.
+fn in_namespace<'i, 'a, T: Copy + 'a>(
+    index: &'i BTreeMap<&'a str, Vec<T>>,
+    prefix: &'i str,
+) -> impl Iterator<Item = T> + 'i {
+    let prefix = prefix.trim_end_matches('.');
+    // The names starting with the prefix are contiguous in the index.
+    index
+        .range(prefix..)
+        .take_while(move |(name, _)| name.starts_with(prefix))
+        .filter(move |(name, _)| is_in_namespace(name, prefix))
+        .flat_map(|(_, values)| values.iter().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    fn schema() -> ResolvedTelemetrySchema {
+        schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.response.status_code
+    type: int
+    brief: Status code
+    requirement_level: recommended
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+  - name: https.enabled
+    type: boolean
+    brief: HTTPS
+    requirement_level: recommended
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: required
+registries:
+  test:
+    groups:
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0, 1]
+    - id: registry.https
+      type: attribute_group
+      brief: HTTPS
+      attributes: [2]
+    - id: registry.http.client
+      type: attribute_group
+      brief: HTTP client
+      attributes: [3]
+"#,
+        )
+    }
+
+    #[test]
+    fn test_namespace_queries() {
+        let schema = schema();
+        let index = schema.namespace_index();
+
+        let attr_names: Vec<_> = index
+            .attributes_in_namespace("http")
+            .map(|attr| attr.name.as_str())
+            .collect();
+        assert_eq!(
+            attr_names,
+            vec![
+                "http.request.method",
+                "http.request.method",
+                "http.response.status_code"
+            ]
+        );
+        assert_eq!(index.attributes_in_namespace("http.request.").count(), 2);
+        assert_eq!(index.attributes_in_namespace("").count(), 4);
+        assert_eq!(index.attributes_in_namespace("url").count(), 0);
+        let schema_attr_names: Vec<_> = schema
+            .attributes_in_namespace("http")
+            .map(|attr| attr.name.as_str())
+            .collect();
+        assert_eq!(schema_attr_names, attr_names);
+
+        let group_ids: Vec<_> = index
+            .groups_in_namespace("registry.http")
+            .map(|group| group.id.as_str())
+            .collect();
+        assert_eq!(group_ids, vec!["registry.http", "registry.http.client"]);
+        let group_ids: Vec<_> = schema
+            .groups_in_namespace("registry")
+            .map(|group| group.id.as_str())
+            .collect();
+        assert_eq!(
+            group_ids,
+            vec!["registry.http", "registry.http.client", "registry.https"]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lookup of a piece of a resolved telemetry schema (or of any serializable
+//! object) with a JSON pointer ([RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)),
+//! e.g. `/registries/OTEL/groups/3/attributes`.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::ResolvedTelemetrySchema;
+
+impl ResolvedTelemetrySchema {
+    /// Returns the value located at the given JSON pointer in the serialized
+    /// form of this schema. The empty pointer designates the whole schema.
+    pub fn lookup(&self, pointer: &str) -> Result<Value, Error> {
+        lookup(self, pointer)
+    }
+}
+
+/// Returns the value located at the given JSON pointer in the serialized
+/// form of an object. The empty pointer designates the whole object.
+pub fn lookup<T: Serialize>(object: &T, pointer: &str) -> Result<Value, Error> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return Err(Error::InvalidJsonPointer {
+            pointer: pointer.to_owned(),
+        });
+    }
+    let not_found = || Error::JsonPointerNotFound {
+        pointer: pointer.to_owned(),
+    };
+    let mut value = serde_json::to_value(object).map_err(|_| not_found())?;
+    value
+        .pointer_mut(pointer)
+        .map(Value::take)
+        .ok_or_else(not_found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    #[test]
+    fn test_lookup() {
+        let schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+registries:
+  test:
+    groups:
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0]
+"#,
+        );
+
+        assert_eq!(
+            schema.lookup("/catalog/attributes/0/name").ok(),
+            Some(Value::from("http.request.method"))
+        );
+        assert_eq!(
+            schema.lookup("/registries/test/groups/0/attributes").ok(),
+            Some(serde_json::json!([0]))
+        );
+        assert!(schema.lookup("").is_ok_and(|value| value.is_object()));
+        assert!(matches!(
+            schema.lookup("/registries/test/groups/1"),
+            Err(Error::JsonPointerNotFound { .. })
+        ));
+        assert!(matches!(
+            schema.lookup("catalog"),
+            Err(Error::InvalidJsonPointer { .. })
+        ));
+    }
+}
@@ -10,7 +10,7 @@ use weaver_semconv::any_value::AnyValueSpec;
 
 use serde::{Deserialize, Serialize};
 
-use weaver_semconv::group::{GroupType, InstrumentSpec, SpanKindSpec};
+use weaver_semconv::group::{ExtendsSpec, GroupType, InstrumentSpec, SpanKindSpec};
 use weaver_semconv::stability::Stability;
 
 use crate::attribute::{Attribute, AttributeRef};
@@ -27,6 +27,7 @@ use crate::registry::GroupStats::{
 pub struct Registry {
     /// The semantic convention registry url.
     #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
     pub registry_url: String,
     /// A list of semantic convention groups.
     pub groups: Vec<Group>,
@@ -53,6 +54,7 @@ pub struct Group {
     pub r#type: GroupType,
     /// A brief description of the semantic convention.
     #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
     pub brief: String,
     /// A more elaborate description of the semantic convention.
     /// It defaults to an empty string.
@@ -64,11 +66,11 @@ pub struct Group {
     #[serde(default)]
     #[serde(skip_serializing_if = "String::is_empty")]
     pub prefix: String,
-    /// Reference another semantic convention id. It inherits the prefix,
-    /// constraints, and all attributes defined in the specified semantic
-    /// convention.
+    /// Reference another semantic convention id, or a list of semantic
+    /// convention ids. It inherits the prefix, constraints, and all attributes
+    /// defined in the specified semantic conventions.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub extends: Option<String>,
+    pub extends: Option<ExtendsSpec>,
     /// Specifies the stability of the semantic convention.
     /// Note that, if stability is missing but deprecated is present, it will
     /// automatically set the stability to deprecated. If deprecated is
@@ -127,6 +129,9 @@ pub struct Group {
     /// The readable name for attribute groups used when generating registry tables.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
+    /// The URL of the telemetry schema the group is defined under.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_url: Option<String>,
     /// The body of the event.
     /// This fields is only used for event groups.
     #[serde(skip_serializing_if = "Option::is_none")]
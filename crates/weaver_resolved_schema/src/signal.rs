@@ -16,6 +16,7 @@ use crate::tags::Tags;
 pub struct UnivariateMetric {
     /// References to attributes defined in the catalog.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     attributes: Vec<AttributeRef>,
     /// Reference to a metric defined in the catalog.
     metric: MetricRef,
@@ -32,6 +33,7 @@ pub struct MultivariateMetric {
     name: String,
     /// References to attributes defined in the catalog.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     attributes: Vec<AttributeRef>,
     /// The metrics of the multivariate metric.
     metrics: Vec<MetricRef>,
@@ -53,6 +55,7 @@ pub struct Event {
     name: String,
     /// References to attributes defined in the catalog.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     attributes: Vec<AttributeRef>,
     /// Brief description of the event.
     brief: Option<String>,
@@ -75,15 +78,18 @@ pub struct Span {
     name: String,
     /// References to attributes defined in the catalog.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     attributes: Vec<AttributeRef>,
     /// The kind of the span.
     #[serde(skip_serializing_if = "Option::is_none")]
     kind: Option<SpanKind>,
     /// The events of the span.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     events: Vec<Event>,
     /// The links of the span.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     links: Vec<SpanLink>,
     /// Brief description of the span.
     brief: Option<String>,
@@ -95,6 +101,71 @@ pub struct Span {
     tags: Option<Tags>,
 }
 
+impl UnivariateMetric {
+    /// Returns the attribute references of the metric.
+    pub(crate) fn attribute_refs(&self) -> Vec<AttributeRef> {
+        self.attributes.clone()
+    }
+
+    /// Rewrites the attribute references of the metric.
+    pub(crate) fn remap_attribute_refs(&mut self, remap: &impl Fn(AttributeRef) -> AttributeRef) {
+        self.attributes.iter_mut().for_each(|r| *r = remap(*r));
+    }
+}
+
+impl MultivariateMetric {
+    /// Returns the attribute references of the metric.
+    pub(crate) fn attribute_refs(&self) -> Vec<AttributeRef> {
+        self.attributes.clone()
+    }
+
+    /// Rewrites the attribute references of the metric.
+    pub(crate) fn remap_attribute_refs(&mut self, remap: &impl Fn(AttributeRef) -> AttributeRef) {
+        self.attributes.iter_mut().for_each(|r| *r = remap(*r));
+    }
+}
+
+impl Event {
+    /// Returns the attribute references of the event.
+    pub(crate) fn attribute_refs(&self) -> Vec<AttributeRef> {
+        self.attributes.clone()
+    }
+
+    /// Rewrites the attribute references of the event.
+    pub(crate) fn remap_attribute_refs(&mut self, remap: &impl Fn(AttributeRef) -> AttributeRef) {
+        self.attributes.iter_mut().for_each(|r| *r = remap(*r));
+    }
+}
+
+impl Span {
+    /// Returns the attribute references of the span, its events and its
+    /// links.
+    pub(crate) fn attribute_refs(&self) -> Vec<AttributeRef> {
+        self.attributes
+            .iter()
+            .copied()
+            .chain(self.events.iter().flat_map(Event::attribute_refs))
+            .chain(
+                self.links
+                    .iter()
+                    .flat_map(|link| link.attributes.iter().copied()),
+            )
+            .collect()
+    }
+
+    /// Rewrites the attribute references of the span, its events and its
+    /// links.
+    pub(crate) fn remap_attribute_refs(&mut self, remap: &impl Fn(AttributeRef) -> AttributeRef) {
+        self.attributes.iter_mut().for_each(|r| *r = remap(*r));
+        for event in self.events.iter_mut() {
+            event.remap_attribute_refs(remap);
+        }
+        for link in self.links.iter_mut() {
+            link.attributes.iter_mut().for_each(|r| *r = remap(*r));
+        }
+    }
+}
+
 /// The span kind.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub enum SpanKind {
@@ -118,6 +189,7 @@ pub struct SpanLink {
     pub link_name: String,
     /// The attributes of the span link.
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     pub attributes: Vec<AttributeRef>,
     /// Brief description of the span link.
     pub brief: Option<String>,
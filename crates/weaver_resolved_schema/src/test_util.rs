@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixtures shared by the unit tests of this crate.
+
+use crate::ResolvedTelemetrySchema;
+
+/// Loads a resolved telemetry schema from the YAML definition of its
+/// sections (`catalog`, `registries`, `resource`, ...). The `file_format` and
+/// `schema_url` fields are added by this function.
+pub(crate) fn schema_from_yaml(sections: &str) -> ResolvedTelemetrySchema {
+    ResolvedTelemetrySchema::from_yaml_str(&format!(
+        "file_format: {}\nschema_url: https://example.com/schemas/1.0.0\n{}",
+        crate::FILE_FORMAT,
+        sections
+    ))
+    .expect("Failed to load the schema")
+}
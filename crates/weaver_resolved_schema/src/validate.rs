@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validation of the internal invariants of a resolved telemetry schema.
+//!
+//! The schemas produced by the resolution process satisfy these invariants by
+//! construction. Validating is useful for schemas loaded from files produced
+//! or edited by other tools.
+
+use std::collections::{HashMap, HashSet};
+
+use weaver_semconv::group::{ExtendsSpec, GroupType};
+
+use crate::attribute::AttributeRef;
+use crate::error::{handle_errors, Error};
+use crate::lineage::GroupLineage;
+use crate::registry::Group;
+use crate::ResolvedTelemetrySchema;
+
+impl ResolvedTelemetrySchema {
+    /// Checks the internal invariants of the schema:
+    /// - all the attribute references of the groups, the resource, and the
+    ///   instrumentation libraries are in the range of the catalog,
+    /// - the group ids are unique in each registry,
+    /// - the metric groups define a metric name, an instrument, and a unit,
+    /// - the groups extended by other groups exist.
+    ///
+    /// All the violations are returned at once as a compound error.
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut errors = self.check_attribute_refs();
+
+        let group_ids: HashSet<&str> = self
+            .registries
+            .values()
+            .flat_map(|registry| registry.groups.iter())
+            .map(|group| group.id.as_str())
+            .collect();
+
+        // Sort the registries to report the errors in a deterministic order.
+        let mut registries: Vec<_> = self.registries.iter().collect();
+        registries.sort_by_key(|(registry_id, _)| *registry_id);
+        for (registry_id, registry) in registries {
+            let mut occurrences: HashMap<&str, usize> = HashMap::new();
+            for group in registry.groups.iter() {
+                let count = occurrences.entry(group.id.as_str()).or_default();
+                *count += 1;
+                if *count == 2 {
+                    errors.push(Error::DuplicateGroupId {
+                        registry_id: registry_id.clone(),
+                        group_id: group.id.clone(),
+                    });
+                }
+                // The extended groups are recorded in the lineage once the
+                // group is resolved.
+                let extends = group
+                    .extends
+                    .as_ref()
+                    .map(ExtendsSpec::group_ids)
+                    .or_else(|| group.lineage.as_ref().map(GroupLineage::extends))
+                    .unwrap_or_default();
+                for extends in extends {
+                    if !group_ids.contains(extends.as_str()) {
+                        errors.push(Error::InvalidGroup {
+                            group_id: group.id.clone(),
+                            error: format!("the extended group `{}` doesn't exist", extends),
+                        });
+                    }
+                }
+                validate_metric(group, &mut errors);
+            }
+        }
+        handle_errors(errors)
+    }
+
+    /// Returns an [`Error::AttributeNotFound`] for each attribute reference of
+    /// the groups, the resource, and the instrumentation libraries that is out
+    /// of the range of the catalog.
+    pub(crate) fn check_attribute_refs(&self) -> Vec<Error> {
+        let attr_count = self.catalog.attributes.len();
+        let mut errors = vec![];
+        let mut check = |owner: &str, attr_refs: &[AttributeRef]| {
+            for attr_ref in attr_refs {
+                if attr_ref.0 as usize >= attr_count {
+                    errors.push(Error::AttributeNotFound {
+                        group_id: owner.to_owned(),
+                        attr_ref: *attr_ref,
+                    });
+                }
+            }
+        };
+
+        // Sort the registries to report the errors in a deterministic order.
+        let mut registries: Vec<_> = self.registries.iter().collect();
+        registries.sort_by_key(|(registry_id, _)| *registry_id);
+        for (_, registry) in registries {
+            for group in registry.groups.iter() {
+                check(&group.id, &group.attributes);
+            }
+        }
+        if let Some(resource) = &self.resource {
+            check("resource", &resource.attributes);
+        }
+        for library in self
+            .instrumentation_library
+            .iter()
+            .chain(self.dependencies.iter())
+        {
+            check(
+                library.name.as_deref().unwrap_or("instrumentation_library"),
+                &library.attribute_refs(),
+            );
+        }
+        errors
+    }
+}
+
+/// Checks that a metric group defines a metric name, an instrument, and a
+/// unit.
+fn validate_metric(group: &Group, errors: &mut Vec<Error>) {
+    if group.r#type != GroupType::Metric {
+        return;
+    }
+    let missing: Vec<_> = [
+        ("metric_name", group.metric_name.is_none()),
+        ("instrument", group.instrument.is_none()),
+        ("unit", group.unit.is_none()),
+    ]
+    .into_iter()
+    .filter_map(|(field, missing)| missing.then_some(field))
+    .collect();
+    if !missing.is_empty() {
+        errors.push(Error::InvalidGroup {
+            group_id: group.id.clone(),
+            error: format!("the metric doesn't define `{}`", missing.join("`, `")),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::schema_from_yaml;
+
+    #[test]
+    fn test_validate() {
+        let schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+registries:
+  test:
+    groups:
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0]
+    - id: metric.http.duration
+      type: metric
+      brief: Duration
+      metric_name: http.duration
+      instrument: histogram
+      unit: s
+      extends: registry.http
+      attributes: [0]
+"#,
+        );
+        assert!(schema.validate().is_ok());
+
+        let schema = schema_from_yaml(
+            r#"
+catalog:
+  attributes:
+  - name: http.request.method
+    type: string
+    brief: Method
+    requirement_level: recommended
+registries:
+  test:
+    groups:
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0, 1]
+    - id: registry.http
+      type: attribute_group
+      brief: HTTP
+      attributes: [0]
+    - id: metric.http.duration
+      type: metric
+      brief: Duration
+      metric_name: http.duration
+      extends: registry.missing
+resource:
+  attributes: [2]
+"#,
+        );
+        let Err(Error::CompoundError(errors)) = schema.validate() else {
+            panic!("Expected validation errors");
+        };
+        assert_eq!(errors.len(), 5);
+        assert!(matches!(
+            &errors[0],
+            Error::AttributeNotFound { group_id, attr_ref: AttributeRef(1) } if group_id == "registry.http"
+        ));
+        assert!(matches!(
+            &errors[1],
+            Error::AttributeNotFound { group_id, .. } if group_id == "resource"
+        ));
+        assert!(matches!(
+            &errors[2],
+            Error::DuplicateGroupId { group_id, .. } if group_id == "registry.http"
+        ));
+        assert!(matches!(
+            &errors[3],
+            Error::InvalidGroup { error, .. } if error.contains("registry.missing")
+        ));
+        assert!(matches!(
+            &errors[4],
+            Error::InvalidGroup { error, .. } if error == "the metric doesn't define `instrument`, `unit`"
+        ));
+    }
+}
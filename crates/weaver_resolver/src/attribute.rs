@@ -12,6 +12,13 @@ use weaver_resolved_schema::lineage::{AttributeLineage, GroupLineage};
 use weaver_semconv::attribute::AttributeSpec;
 
 /// A catalog of deduplicated resolved attributes with their corresponding reference.
+///
+/// The references are assigned in insertion order, i.e. the first attribute
+/// added to the catalog gets the reference 0, the second distinct attribute
+/// gets the reference 1, and so on. The registry resolution adds the
+/// attributes in a deterministic order (groups sorted by provenance, then
+/// attributes in declaration order), so the same registry content always
+/// produces the same references.
 #[derive(Deserialize, Debug, Default, PartialEq)]
 pub struct AttributeCatalog {
     /// A map of deduplicated resolved attributes with their corresponding reference.
@@ -38,6 +45,11 @@ impl AttributeCatalog {
         self.root_attributes.get(name)
     }
 
+    /// Returns the names of the root attributes of the catalog.
+    pub(crate) fn root_attribute_names(&self) -> impl Iterator<Item = &str> {
+        self.root_attributes.keys().map(String::as_str)
+    }
+
     /// Returns the reference of the given attribute or creates a new reference if the attribute
     /// does not exist in the catalog.
     pub fn attribute_ref(&mut self, attr: attribute::Attribute) -> AttributeRef {
@@ -79,6 +91,19 @@ impl AttributeCatalog {
         attr: &AttributeSpec,
         lineage: Option<&mut GroupLineage>,
     ) -> Option<AttributeRef> {
+        self.resolve_attribute(group_prefix, attr)
+            .map(|resolved_attr| self.register(group_id, resolved_attr, lineage))
+    }
+
+    /// Tries to resolve the given attribute spec (ref or id) without updating
+    /// the catalog, so it can be called concurrently.
+    /// Returns `None` if the attribute spec is a ref and it does not exist yet
+    /// in the catalog.
+    pub(crate) fn resolve_attribute(
+        &self,
+        group_prefix: &str,
+        attr: &AttributeSpec,
+    ) -> Option<ResolvedAttribute> {
         match attr {
             AttributeSpec::Ref {
                 r#ref,
@@ -92,69 +117,51 @@ impl AttributeCatalog {
                 deprecated,
                 prefix,
             } => {
-                let name;
-                let root_attr = self.root_attributes.get(r#ref);
-                if let Some(root_attr) = root_attr {
-                    let mut attr_lineage = AttributeLineage::new(&root_attr.group_id);
-
-                    if *prefix {
-                        // depending on the prefix we either create embedded attribute or normal reference
-                        name = format!("{}.{}", group_prefix, r#ref);
-                    } else {
-                        name = r#ref.clone();
-                    }
-
-                    // Create a fully resolved attribute from an attribute spec
-                    // (ref) and override the root attribute with the new
-                    // values if they are present.
-                    let resolved_attr = attribute::Attribute {
-                        name: name.clone(),
-                        r#type: root_attr.attribute.r#type.clone(),
-                        brief: attr_lineage.brief(brief, &root_attr.attribute.brief),
-                        examples: attr_lineage.examples(examples, &root_attr.attribute.examples),
-                        tag: attr_lineage.tag(tag, &root_attr.attribute.tag),
-                        requirement_level: attr_lineage.requirement_level(
-                            requirement_level,
-                            &root_attr.attribute.requirement_level,
-                        ),
-                        sampling_relevant: attr_lineage.sampling_relevant(
-                            sampling_relevant,
-                            &root_attr.attribute.sampling_relevant,
-                        ),
-                        note: attr_lineage.note(note, &root_attr.attribute.note),
-                        stability: attr_lineage
-                            .stability(stability, &root_attr.attribute.stability),
-                        deprecated: attr_lineage
-                            .deprecated(deprecated, &root_attr.attribute.deprecated),
-                        tags: root_attr.attribute.tags.clone(),
-                        value: root_attr.attribute.value.clone(),
-                        prefix: *prefix,
-                    };
-
-                    let attr_ref = self.attribute_ref(resolved_attr.clone());
-
-                    // Update the lineage based on the inherited fields.
-                    // Note: the lineage is only updated if a group lineage is provided.
-                    if let Some(lineage) = lineage {
-                        lineage.add_attribute_lineage(name.clone(), attr_lineage);
-                    }
-
-                    if *prefix {
-                        // if it's a prefix with reference
-                        // we need to add it to the dictionary of resolved attributes
-                        _ = self.root_attributes.insert(
-                            name,
-                            AttributeWithGroupId {
-                                attribute: resolved_attr,
-                                group_id: group_id.to_owned(),
-                            },
-                        );
-                    }
-
-                    Some(attr_ref)
+                let root_attr = self.root_attributes.get(r#ref)?;
+                let mut attr_lineage = AttributeLineage::new(&root_attr.group_id);
+
+                // depending on the prefix we either create embedded attribute or normal reference
+                let name = if *prefix {
+                    format!("{}.{}", group_prefix, r#ref)
                 } else {
-                    None
-                }
+                    r#ref.clone()
+                };
+
+                // Create a fully resolved attribute from an attribute spec
+                // (ref) and override the root attribute with the new
+                // values if they are present.
+                let attribute = attribute::Attribute {
+                    name,
+                    r#type: root_attr.attribute.r#type.clone(),
+                    brief: attr_lineage.brief(brief, &root_attr.attribute.brief),
+                    examples: attr_lineage.examples(examples, &root_attr.attribute.examples),
+                    tag: attr_lineage.tag(tag, &root_attr.attribute.tag),
+                    requirement_level: attr_lineage.requirement_level(
+                        requirement_level,
+                        &root_attr.attribute.requirement_level,
+                    ),
+                    sampling_relevant: attr_lineage.sampling_relevant(
+                        sampling_relevant,
+                        &root_attr.attribute.sampling_relevant,
+                    ),
+                    note: attr_lineage.note(note, &root_attr.attribute.note),
+                    stability: attr_lineage.stability(stability, &root_attr.attribute.stability),
+                    deprecated: attr_lineage
+                        .deprecated(deprecated, &root_attr.attribute.deprecated),
+                    tags: root_attr.attribute.tags.clone(),
+                    value: root_attr.attribute.value.clone(),
+                    prefix: *prefix,
+                    value_constraints: root_attr.attribute.value_constraints.clone(),
+                    aliases: root_attr.attribute.aliases.clone(),
+                };
+
+                Some(ResolvedAttribute {
+                    attribute,
+                    lineage: Some(attr_lineage),
+                    // if it's a prefix with reference
+                    // we need to add it to the dictionary of resolved attributes
+                    is_root: *prefix,
+                })
             }
             AttributeSpec::Id {
                 id,
@@ -167,12 +174,11 @@ impl AttributeCatalog {
                 note,
                 stability,
                 deprecated,
+                value_constraints,
+                aliases,
             } => {
-                // Create a fully resolved attribute from an attribute spec (id),
-                // and check if it already exists in the catalog.
-                // If it does, return the reference to the existing attribute.
-                // If it does not, add it to the catalog and return a new reference.
-                let attr = attribute::Attribute {
+                // Create a fully resolved attribute from an attribute spec (id).
+                let attribute = attribute::Attribute {
                     name: id.clone(),
                     r#type: r#type.clone(),
                     brief: brief.clone().unwrap_or_default(),
@@ -186,17 +192,69 @@ impl AttributeCatalog {
                     tags: None,
                     value: None,
                     prefix: false,
+                    value_constraints: value_constraints.clone(),
+                    aliases: aliases.clone(),
                 };
 
-                _ = self.root_attributes.insert(
-                    id.to_owned(),
-                    AttributeWithGroupId {
-                        attribute: attr.clone(),
-                        group_id: group_id.to_owned(),
-                    },
-                );
-                Some(self.attribute_ref(attr))
+                Some(ResolvedAttribute {
+                    attribute,
+                    lineage: None,
+                    is_root: true,
+                })
             }
         }
     }
+
+    /// Adds a resolved attribute to the catalog and returns its reference.
+    /// If the attribute already exists in the catalog, the reference to the
+    /// existing attribute is returned.
+    pub(crate) fn register(
+        &mut self,
+        group_id: &str,
+        resolved_attr: ResolvedAttribute,
+        lineage: Option<&mut GroupLineage>,
+    ) -> AttributeRef {
+        let ResolvedAttribute {
+            attribute,
+            lineage: attr_lineage,
+            is_root,
+        } = resolved_attr;
+
+        // Update the lineage based on the inherited fields.
+        // Note: the lineage is only updated if a group lineage is provided.
+        if let (Some(lineage), Some(attr_lineage)) = (lineage, attr_lineage) {
+            lineage.add_attribute_lineage(attribute.name.clone(), attr_lineage);
+        }
+
+        if is_root {
+            _ = self.root_attributes.insert(
+                attribute.name.clone(),
+                AttributeWithGroupId {
+                    attribute: attribute.clone(),
+                    group_id: group_id.to_owned(),
+                },
+            );
+        }
+        self.attribute_ref(attribute)
+    }
+}
+
+/// An attribute resolved from an attribute spec, not yet added to the catalog.
+#[derive(Debug)]
+pub(crate) struct ResolvedAttribute {
+    /// The resolved attribute.
+    attribute: attribute::Attribute,
+    /// The lineage of the attribute (for attribute references).
+    lineage: Option<AttributeLineage>,
+    /// True if the attribute can be referenced by other attributes
+    /// (i.e. attribute definitions and prefixed references).
+    is_root: bool,
+}
+
+impl ResolvedAttribute {
+    /// Returns the name of the attribute if it can be referenced by other
+    /// attributes once added to the catalog.
+    pub(crate) fn root_name(&self) -> Option<&str> {
+        self.is_root.then_some(self.attribute.name.as_str())
+    }
 }
@@ -0,0 +1,362 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selection of the definitions to resolve.
+//!
+//! A group filter is a set of glob patterns matched against the group ids
+//! (e.g. `registry.http.*` or `*.http.*`). The groups matching one of the
+//! patterns are resolved with all the groups they depend on, i.e. the groups
+//! they extend, the groups they include, and the groups defining the attributes
+//! they reference, transitively.
+//!
+//! A stability filter removes the deprecated definitions, or the definitions
+//! below a stability level, from a resolved schema.
+
+use std::collections::{HashMap, HashSet};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use weaver_resolved_schema::ResolvedTelemetrySchema;
+use weaver_semconv::attribute::AttributeSpec;
+use weaver_semconv::group::ExtendsSpec;
+use weaver_semconv::stability::Stability;
+
+use crate::registry::UnresolvedRegistry;
+use crate::Error;
+
+/// A set of glob patterns selecting the groups to resolve.
+#[derive(Debug, Clone)]
+pub struct GroupFilter {
+    patterns: GlobSet,
+}
+
+impl GroupFilter {
+    /// Creates a group filter from a list of glob patterns matched against
+    /// the group ids.
+    pub fn try_new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let glob = Glob::new(pattern).map_err(|e| Error::InvalidGroupFilter {
+                pattern: pattern.to_owned(),
+                error: e.to_string(),
+            })?;
+            _ = builder.add(glob);
+        }
+        let patterns = builder.build().map_err(|e| Error::InvalidGroupFilter {
+            pattern: patterns
+                .iter()
+                .map(|pattern| pattern.as_ref())
+                .collect::<Vec<_>>()
+                .join(", "),
+            error: e.to_string(),
+        })?;
+        Ok(Self { patterns })
+    }
+
+    /// Returns true if the given group id matches one of the patterns.
+    #[must_use]
+    pub fn is_match(&self, group_id: &str) -> bool {
+        self.patterns.is_match(group_id)
+    }
+
+    /// Removes from the unresolved registry all the groups that are neither
+    /// selected by the filter nor needed to resolve the selected groups.
+    ///
+    /// Note: the attribute ids must be prefixed before calling this method.
+    pub(crate) fn prune(&self, ureg: &mut UnresolvedRegistry) {
+        let mut group_index: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut attribute_index: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for (index, unresolved_group) in ureg.groups.iter().enumerate() {
+            group_index
+                .entry(unresolved_group.group.id.as_str())
+                .or_default()
+                .push(index);
+            for attr in unresolved_group.attributes.iter() {
+                let name = match &attr.spec {
+                    AttributeSpec::Id { id, .. } => id.clone(),
+                    AttributeSpec::Ref {
+                        r#ref,
+                        prefix: true,
+                        ..
+                    } => format!("{}.{}", unresolved_group.group.prefix, r#ref),
+                    AttributeSpec::Ref { .. } => continue,
+                };
+                attribute_index.entry(name).or_default().push(index);
+            }
+        }
+
+        let mut selected = HashSet::new();
+        let mut to_visit: Vec<usize> = (0..ureg.groups.len())
+            .filter(|index| self.is_match(&ureg.groups[*index].group.id))
+            .collect();
+
+        while let Some(index) = to_visit.pop() {
+            if !selected.insert(index) {
+                continue;
+            }
+            let unresolved_group = &ureg.groups[index];
+            let group_deps = unresolved_group
+                .group
+                .extends
+                .iter()
+                .flat_map(ExtendsSpec::group_ids)
+                .chain(
+                    unresolved_group
+                        .group
+                        .constraints
+                        .iter()
+                        .filter_map(|constraint| constraint.include.as_ref()),
+                )
+                .filter_map(|group_id| group_index.get(group_id.as_str()));
+            let attribute_deps = unresolved_group
+                .attributes
+                .iter()
+                .filter_map(|attr| match &attr.spec {
+                    AttributeSpec::Ref { r#ref, .. } => attribute_index.get(r#ref),
+                    AttributeSpec::Id { .. } => None,
+                });
+            to_visit.extend(group_deps.chain(attribute_deps).flatten());
+        }
+
+        let mut index = 0;
+        ureg.groups.retain(|_| {
+            index += 1;
+            selected.contains(&(index - 1))
+        });
+    }
+}
+
+/// Selects the groups and attributes of a resolved schema based on their
+/// stability.
+#[derive(Debug, Clone, Default)]
+pub struct StabilityFilter {
+    /// Remove the groups and attributes that are deprecated.
+    pub strip_deprecated: bool,
+    /// Remove the groups and attributes whose stability is lower than this
+    /// level. The definitions without stability are considered in development.
+    pub min_stability: Option<Stability>,
+}
+
+impl StabilityFilter {
+    /// Returns true if the filter doesn't remove anything.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.strip_deprecated && self.min_stability.is_none()
+    }
+
+    /// Returns true if a definition with the given `deprecated` and
+    /// `stability` fields is kept by the filter.
+    #[must_use]
+    pub fn keeps(&self, deprecated: Option<&String>, stability: Option<&Stability>) -> bool {
+        if self.strip_deprecated
+            && (deprecated.is_some() || stability == Some(&Stability::Deprecated))
+        {
+            return false;
+        }
+        match &self.min_stability {
+            Some(min_stability) => {
+                stability.unwrap_or(&Stability::Development).maturity() >= min_stability.maturity()
+            }
+            None => true,
+        }
+    }
+
+    /// Removes from the registries of the resolved schema the groups and the
+    /// attribute references not kept by the filter.
+    ///
+    /// The catalog is left unchanged so the attribute references stay valid.
+    pub fn apply(&self, schema: &mut ResolvedTelemetrySchema) {
+        if self.is_empty() {
+            return;
+        }
+        let catalog = &schema.catalog;
+        for registry in schema.registries.values_mut() {
+            registry
+                .groups
+                .retain(|group| self.keeps(group.deprecated.as_ref(), group.stability.as_ref()));
+            for group in registry.groups.iter_mut() {
+                group.attributes.retain(|attr_ref| {
+                    catalog.attribute(attr_ref).map_or(true, |attr| {
+                        self.keeps(attr.deprecated.as_ref(), attr.stability.as_ref())
+                    })
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use weaver_semconv::registry::SemConvRegistry;
+
+    use crate::attribute::AttributeCatalog;
+    use crate::registry::resolve_semconv_registry_with_filter;
+
+    use super::*;
+
+    fn resolved_group_ids(patterns: &[&str]) -> Vec<String> {
+        let registry = SemConvRegistry::try_from_path_pattern(
+            "test",
+            "data/registry-test-7-spans/registry/*.yaml",
+        )
+        .into_result_failing_non_fatal()
+        .expect("Failed to load the registry");
+        let filter = GroupFilter::try_new(patterns).expect("Invalid filter");
+        let mut attr_catalog = AttributeCatalog::default();
+        let mut group_ids: Vec<_> =
+            resolve_semconv_registry_with_filter(&mut attr_catalog, "", &registry, &filter)
+                .expect("Failed to resolve the registry")
+                .groups
+                .into_iter()
+                .map(|group| group.id)
+                .collect();
+        group_ids.sort();
+        group_ids
+    }
+
+    #[test]
+    fn test_group_filter() {
+        // The selected group, the group it extends, and the groups defining
+        // the referenced attributes.
+        assert_eq!(
+            resolved_group_ids(&["db.mssql"]),
+            vec![
+                "db",
+                "db.mssql",
+                "registry.db",
+                "registry.network",
+                "server"
+            ]
+        );
+        assert_eq!(
+            resolved_group_ids(&["registry.http", "registry.u*"]),
+            vec!["registry.http", "registry.url", "registry.user_agent"]
+        );
+        assert!(resolved_group_ids(&["does.not.exist"]).is_empty());
+
+        assert!(GroupFilter::try_new(&["registry.{http"]).is_err());
+    }
+
+    /// Returns the group ids and their attribute names kept by the filter.
+    fn stability_filtered(filter: &StabilityFilter) -> Vec<(String, Vec<String>)> {
+        let mut registry = SemConvRegistry::new("test");
+        registry
+            .add_semconv_spec_from_string(
+                "<str>",
+                "
+groups:
+    - id: registry.test
+      type: attribute_group
+      brief: \"Registry\"
+      attributes:
+        - id: test.stable
+          type: string
+          brief: \"Stable attribute\"
+          examples: [\"a\"]
+          stability: stable
+        - id: test.beta
+          type: string
+          brief: \"Beta attribute\"
+          examples: [\"a\"]
+          stability: beta
+        - id: test.no_stability
+          type: string
+          brief: \"Attribute without stability\"
+          examples: [\"a\"]
+        - id: test.deprecated
+          type: string
+          brief: \"Deprecated attribute\"
+          examples: [\"a\"]
+          deprecated: \"Replaced by `test.stable`.\"
+    - id: span.stable
+      type: span
+      span_kind: client
+      stability: stable
+      brief: \"Stable span\"
+      attributes:
+        - ref: test.stable
+        - ref: test.beta
+        - ref: test.deprecated
+    - id: span.experimental
+      type: span
+      span_kind: client
+      stability: experimental
+      brief: \"Experimental span\"
+      attributes:
+        - ref: test.stable
+",
+            )
+            .into_result_failing_non_fatal()
+            .expect("Failed to load the registry");
+        let mut schema = crate::SchemaResolver::resolve_semantic_convention_registry(&mut registry)
+            .expect("Failed to resolve the registry");
+        filter.apply(&mut schema);
+
+        let catalog = schema.catalog();
+        schema.registries["test"]
+            .groups
+            .iter()
+            .map(|group| {
+                let mut attrs: Vec<_> = group
+                    .attributes
+                    .iter()
+                    .filter_map(|attr_ref| catalog.attribute_name(attr_ref))
+                    .map(|name| name.to_owned())
+                    .collect();
+                attrs.sort();
+                (group.id.clone(), attrs)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stability_filter() {
+        let group = |id: &str, attrs: &[&str]| {
+            (
+                id.to_owned(),
+                attrs
+                    .iter()
+                    .map(|attr| (*attr).to_owned())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let all = stability_filtered(&StabilityFilter::default());
+        assert_eq!(all.len(), 3);
+
+        assert_eq!(
+            stability_filtered(&StabilityFilter {
+                strip_deprecated: true,
+                min_stability: None,
+            }),
+            vec![
+                group(
+                    "registry.test",
+                    &["test.beta", "test.no_stability", "test.stable"]
+                ),
+                group("span.stable", &["test.beta", "test.stable"]),
+                group("span.experimental", &["test.stable"]),
+            ]
+        );
+
+        assert_eq!(
+            stability_filtered(&StabilityFilter {
+                strip_deprecated: false,
+                min_stability: Some(Stability::Beta),
+            }),
+            vec![
+                // The registry group has no stability, so it is considered in
+                // development.
+                group("span.stable", &["test.beta", "test.stable"]),
+            ]
+        );
+
+        assert_eq!(
+            stability_filtered(&StabilityFilter {
+                strip_deprecated: false,
+                min_stability: Some(Stability::Stable),
+            }),
+            vec![group("span.stable", &["test.stable"])]
+        );
+    }
+}
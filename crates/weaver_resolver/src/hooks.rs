@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks rewriting the groups of a registry programmatically, before or after
+//! the resolution process (e.g. to inject company-wide notes before the
+//! policies are checked).
+//!
+//! The hooks are registered on a [`SchemaResolver`](crate::SchemaResolver)
+//! and applied in their registration order.
+
+use weaver_resolved_schema::catalog::Catalog;
+use weaver_resolved_schema::registry::Group;
+use weaver_semconv::group::GroupSpec;
+
+/// A pre-resolution hook rewriting the groups of the semantic convention specs.
+pub trait SpecTransformer: Send + Sync {
+    /// Rewrites a group of a semantic convention spec, or returns an error
+    /// message if the group can't be transformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `provenance` - The provenance of the spec defining the group (path or URL).
+    /// * `group` - The group to rewrite.
+    fn transform_group_spec(&self, provenance: &str, group: &mut GroupSpec) -> Result<(), String>;
+}
+
+impl<F> SpecTransformer for F
+where
+    F: Fn(&str, &mut GroupSpec) -> Result<(), String> + Send + Sync,
+{
+    fn transform_group_spec(&self, provenance: &str, group: &mut GroupSpec) -> Result<(), String> {
+        self(provenance, group)
+    }
+}
+
+/// A post-resolution hook rewriting the resolved groups.
+pub trait GroupTransformer: Send + Sync {
+    /// Rewrites a resolved group, or returns an error message if the group
+    /// can't be transformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The resolved group to rewrite.
+    /// * `catalog` - The catalog of the attributes referenced by the group.
+    fn transform_group(&self, group: &mut Group, catalog: &Catalog) -> Result<(), String>;
+}
+
+impl<F> GroupTransformer for F
+where
+    F: Fn(&mut Group, &Catalog) -> Result<(), String> + Send + Sync,
+{
+    fn transform_group(&self, group: &mut Group, catalog: &Catalog) -> Result<(), String> {
+        self(group, catalog)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use weaver_semconv::group::GroupType;
+    use weaver_semconv::registry::SemConvRegistry;
+
+    use super::*;
+    use crate::filter::GroupFilter;
+    use crate::{Error, SchemaResolver};
+
+    fn registry() -> SemConvRegistry {
+        let mut registry = SemConvRegistry::new("test");
+        registry
+            .add_semconv_spec_from_string(
+                "<str>",
+                "
+groups:
+    - id: registry.test
+      type: attribute_group
+      brief: \"Registry\"
+      attributes:
+        - id: test.attr
+          type: string
+          brief: \"Attribute\"
+          examples: [\"a\"]
+    - id: span.test
+      type: span
+      span_kind: client
+      stability: stable
+      brief: \"Span\"
+      attributes:
+        - ref: test.attr
+",
+            )
+            .into_result_failing_non_fatal()
+            .expect("Failed to load the registry");
+        registry
+    }
+
+    #[test]
+    fn test_transformers() {
+        let resolver = SchemaResolver::default()
+            .with_spec_transformer(|_: &str, group: &mut GroupSpec| {
+                if group.r#type == GroupType::Span {
+                    group.note = "Owned by the tracing team.".to_owned();
+                }
+                Ok(())
+            })
+            .with_group_transformer(|group: &mut Group, catalog: &Catalog| {
+                let attr_count = group
+                    .attributes
+                    .iter()
+                    .filter(|attr_ref| catalog.attribute(attr_ref).is_some())
+                    .count();
+                group.brief = format!("{} ({} attributes)", group.brief, attr_count);
+                Ok(())
+            });
+
+        let schema = resolver
+            .resolve(&mut registry())
+            .into_result_failing_non_fatal()
+            .expect("Failed to resolve the registry");
+        let groups = &schema.registries["test"].groups;
+        assert_eq!(groups[0].brief, "Registry (1 attributes)");
+        assert_eq!(groups[0].note, "");
+        assert_eq!(groups[1].brief, "Span (1 attributes)");
+        assert_eq!(groups[1].note, "Owned by the tracing team.");
+    }
+
+    #[test]
+    fn test_transformers_with_filter() {
+        let resolver = SchemaResolver::default()
+            .with_spec_transformer(|_: &str, group: &mut GroupSpec| {
+                group.note = format!("Spec hook applied to `{}`.", group.id);
+                Ok(())
+            })
+            .with_group_transformer(|group: &mut Group, _: &Catalog| {
+                group.brief = format!("{} (group hook)", group.brief);
+                Ok(())
+            });
+
+        let mut registry = registry();
+        registry
+            .add_semconv_spec_from_string(
+                "<other>",
+                "
+groups:
+    - id: registry.other
+      type: attribute_group
+      brief: \"Other\"
+      attributes:
+        - id: other.attr
+          type: string
+          brief: \"Attribute\"
+          examples: [\"a\"]
+",
+            )
+            .into_result_failing_non_fatal()
+            .expect("Failed to load the registry");
+        let filter = GroupFilter::try_new(&["span.*"]).expect("Invalid filter");
+        let schema = resolver
+            .resolve_with_filter(&mut registry, &filter)
+            .into_result_failing_non_fatal()
+            .expect("Failed to resolve the registry");
+        // The selected group and the group it depends on, but not `registry.other`.
+        let groups = &schema.registries["test"].groups;
+        assert_eq!(groups.len(), 2);
+        for group in groups {
+            assert!(group.brief.ends_with(" (group hook)"));
+            assert_eq!(group.note, format!("Spec hook applied to `{}`.", group.id));
+        }
+    }
+
+    #[test]
+    fn test_transformer_errors() {
+        let resolver = SchemaResolver::default().with_spec_transformer(
+            |provenance: &str, group: &mut GroupSpec| {
+                Err(format!("`{}` rejected in {}", group.id, provenance))
+            },
+        );
+        let error = resolver
+            .resolve(&mut registry())
+            .into_result_failing_non_fatal()
+            .expect_err("The transformer should fail");
+        let Error::CompoundError(errors) = error else {
+            panic!("Expected a compound error, got {:?}", error);
+        };
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            Error::GroupTransformationFailed { group_id, provenance, error }
+                if group_id == "registry.test" && provenance == "<str>"
+                    && error == "`registry.test` rejected in <str>"
+        ));
+    }
+}
@@ -2,36 +2,57 @@
 
 #![doc = include_str!("../README.md")]
 
+use itertools::Itertools;
 use miette::Diagnostic;
 use std::collections::HashMap;
-use std::path::{PathBuf, MAIN_SEPARATOR};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
 
 use rayon::iter::ParallelIterator;
 use rayon::iter::{IntoParallelIterator, ParallelBridge};
 use serde::Serialize;
 use walkdir::DirEntry;
 
+#[cfg(feature = "registry-repo")]
 use weaver_cache::RegistryRepo;
-use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages};
-use weaver_common::error::{format_errors, WeaverError};
+use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages, SeverityOverrides};
+use weaver_common::error::{format_errors, handle_errors, WeaverError};
 use weaver_common::result::WResult;
 use weaver_common::Logger;
 use weaver_resolved_schema::catalog::Catalog;
 use weaver_resolved_schema::registry::Constraint;
 use weaver_resolved_schema::ResolvedTelemetrySchema;
+use weaver_semconv::filter::SpecFilter;
 use weaver_semconv::registry::SemConvRegistry;
 use weaver_semconv::semconv::SemConvSpec;
 
 use crate::attribute::AttributeCatalog;
-use crate::registry::resolve_semconv_registry;
+use crate::filter::GroupFilter;
+use crate::hooks::{GroupTransformer, SpecTransformer};
+use crate::registry::{
+    resolve_filtered_semconv_registry, resolve_semconv_registry,
+    resolve_semconv_registry_with_filter,
+};
 
 pub mod attribute;
 mod constraint;
+pub mod filter;
+pub mod hooks;
+pub mod location;
 pub mod registry;
+mod suggestion;
+pub mod unused;
 
 /// A resolver that can be used to resolve telemetry schemas.
 /// All references to semantic conventions will be resolved.
-pub struct SchemaResolver {}
+///
+/// Hooks rewriting the groups before or after the resolution can be
+/// registered on a resolver instance (see the [`hooks`] module).
+#[derive(Default)]
+pub struct SchemaResolver {
+    spec_transformers: Vec<Box<dyn SpecTransformer>>,
+    group_transformers: Vec<Box<dyn GroupTransformer>>,
+    severity_overrides: SeverityOverrides,
+}
 
 /// An error that can occur while resolving a telemetry schema.
 #[derive(thiserror::Error, Debug, Clone, Serialize, Diagnostic)]
@@ -91,6 +112,9 @@ pub enum Error {
         attribute_ref: String,
         /// The provenance of the reference (URL or path).
         provenance: String,
+        /// Suggestions of known attribute ids close to the reference, if any.
+        #[help]
+        help: Option<String>,
     },
 
     /// An unresolved `extends` clause reference.
@@ -102,6 +126,48 @@ pub enum Error {
         extends_ref: String,
         /// The provenance of the reference (URL or path).
         provenance: String,
+        /// Suggestions of known group ids close to the reference, if any.
+        #[help]
+        help: Option<String>,
+    },
+
+    /// An attribute defined differently by several groups extended by a group,
+    /// and not redefined by this group.
+    #[error("The attribute `{attribute_id}` is defined differently by the groups {parent_group_ids:?} extended by the group '{group_id}'.\nProvenance: {provenance}")]
+    #[diagnostic(help(
+        "Redefine the attribute in the group '{group_id}' to resolve the conflict."
+    ))]
+    ConflictingInheritedAttribute {
+        /// The id of the extending group.
+        group_id: String,
+        /// The id of the conflicting attribute.
+        attribute_id: String,
+        /// The ids of the extended groups defining the attribute differently.
+        parent_group_ids: Vec<String>,
+        /// The provenance of the extending group (URL or path).
+        provenance: String,
+    },
+
+    /// A cycle formed by the `extends` clauses of a set of groups.
+    #[error("The `extends` clauses of the following groups form a cycle: {}.\nProvenances: {provenances:?}", format_cycle(.group_ids))]
+    CircularExtendsRef {
+        /// The ids of the groups forming the cycle, in the order of the
+        /// `extends` clauses.
+        group_ids: Vec<String>,
+        /// The provenances of the groups forming the cycle (URL or path).
+        provenances: Vec<String>,
+    },
+
+    /// A cycle formed by the attribute references of a set of groups, i.e.
+    /// prefixed references (defining root attributes) depending on each other.
+    #[error("The following attribute references form a cycle: {}.\nGroups: {group_ids:?}\nProvenances: {provenances:?}", format_cycle(.attribute_refs))]
+    CircularAttributeRef {
+        /// The attribute references forming the cycle, in dependency order.
+        attribute_refs: Vec<String>,
+        /// The ids of the groups defining the attribute references.
+        group_ids: Vec<String>,
+        /// The provenances of the groups (URL or path).
+        provenances: Vec<String>,
     },
 
     /// An unresolved `include` reference.
@@ -172,6 +238,27 @@ pub enum Error {
         attribute_id: String,
     },
 
+    /// An invalid group filter.
+    #[error("Invalid group filter `{pattern}`: {error}")]
+    #[diagnostic(help("Check the glob syntax of the group filter and try again."))]
+    InvalidGroupFilter {
+        /// The invalid glob pattern.
+        pattern: String,
+        /// The error that occurred.
+        error: String,
+    },
+
+    /// A group transformation hook failed.
+    #[error("Failed to transform the group `{group_id}` (provenance: {provenance}): {error}")]
+    GroupTransformationFailed {
+        /// The id of the group.
+        group_id: String,
+        /// The provenance of the group (URL or path).
+        provenance: String,
+        /// The error returned by the hook.
+        error: String,
+    },
+
     /// A container for multiple errors.
     #[error("{:?}", format_errors(.0))]
     CompoundError(#[related] Vec<Error>),
@@ -206,6 +293,11 @@ impl From<Error> for DiagnosticMessages {
     }
 }
 
+/// Formats a cycle of ids, e.g. `a -> b -> a`.
+fn format_cycle(ids: &[String]) -> String {
+    ids.iter().chain(ids.first()).join(" -> ")
+}
+
 /// A constraint that is not satisfied and its missing attributes.
 #[derive(Debug)]
 pub struct UnsatisfiedAnyOfConstraint {
@@ -231,6 +323,129 @@ impl Error {
 }
 
 impl SchemaResolver {
+    /// Registers a pre-resolution hook rewriting the groups of the semantic
+    /// convention specs.
+    #[must_use]
+    pub fn with_spec_transformer(mut self, transformer: impl SpecTransformer + 'static) -> Self {
+        self.spec_transformers.push(Box::new(transformer));
+        self
+    }
+
+    /// Registers a post-resolution hook rewriting the resolved groups.
+    #[must_use]
+    pub fn with_group_transformer(mut self, transformer: impl GroupTransformer + 'static) -> Self {
+        self.group_transformers.push(Box::new(transformer));
+        self
+    }
+
+    /// Sets the severity overrides of the diagnostics. The registry checks
+    /// ignored by an override (e.g. `DuplicateGroupName`) are skipped by
+    /// [`SchemaResolver::resolve`] and [`SchemaResolver::resolve_with_filter`],
+    /// and the ones demoted to warnings don't fail the resolution.
+    #[must_use]
+    pub fn with_severity_overrides(mut self, severity_overrides: SeverityOverrides) -> Self {
+        self.severity_overrides = severity_overrides;
+        self
+    }
+
+    /// Applies the pre-resolution hooks to the groups of the given semantic
+    /// convention registry.
+    ///
+    /// This is done by [`SchemaResolver::resolve`], but can be called before
+    /// checking the specs against the `before_resolution` policies.
+    pub fn transform_specs(&self, registry: &mut SemConvRegistry) -> Result<(), Error> {
+        let mut errors = vec![];
+        for (provenance, group) in registry.group_spec_iter_mut() {
+            for transformer in self.spec_transformers.iter() {
+                if let Err(error) = transformer.transform_group_spec(provenance, group) {
+                    errors.push(Error::GroupTransformationFailed {
+                        group_id: group.id.clone(),
+                        provenance: provenance.to_owned(),
+                        error,
+                    });
+                }
+            }
+        }
+        handle_errors(errors)
+    }
+
+    /// Applies the post-resolution hooks to the groups of the registries of
+    /// the given resolved telemetry schema.
+    pub fn transform_groups(&self, schema: &mut ResolvedTelemetrySchema) -> Result<(), Error> {
+        let mut errors = vec![];
+        let catalog = &schema.catalog;
+        for registry in schema.registries.values_mut() {
+            for group in registry.groups.iter_mut() {
+                for transformer in self.group_transformers.iter() {
+                    if let Err(error) = transformer.transform_group(group, catalog) {
+                        errors.push(Error::GroupTransformationFailed {
+                            group_id: group.id.clone(),
+                            provenance: group
+                                .lineage
+                                .as_ref()
+                                .map(|lineage| lineage.source_file().to_owned())
+                                .unwrap_or_default(),
+                            error,
+                        });
+                    }
+                }
+            }
+        }
+        handle_errors(errors)
+    }
+
+    /// Applies the pre-resolution hooks to the given semantic convention
+    /// registry, resolves it, and applies the post-resolution hooks to the
+    /// resolved telemetry schema. The errors of the registry checks demoted to
+    /// warnings by the severity overrides are returned as non-fatal errors.
+    pub fn resolve(
+        &self,
+        registry: &mut SemConvRegistry,
+    ) -> WResult<ResolvedTelemetrySchema, Error> {
+        self.resolve_filtered(registry, None)
+    }
+
+    /// Same as [`SchemaResolver::resolve`], but only resolves the groups
+    /// selected by the filter and the groups they depend on (see
+    /// [`SchemaResolver::resolve_semantic_convention_registry_with_filter`]).
+    pub fn resolve_with_filter(
+        &self,
+        registry: &mut SemConvRegistry,
+        filter: &GroupFilter,
+    ) -> WResult<ResolvedTelemetrySchema, Error> {
+        self.resolve_filtered(registry, Some(filter))
+    }
+
+    /// Resolves the groups selected by the optional filter, applying the hooks
+    /// and the severity overrides.
+    fn resolve_filtered(
+        &self,
+        registry: &mut SemConvRegistry,
+        filter: Option<&GroupFilter>,
+    ) -> WResult<ResolvedTelemetrySchema, Error> {
+        if let Err(e) = self.transform_specs(registry) {
+            return WResult::FatalErr(e);
+        }
+        let mut attr_catalog = AttributeCatalog::default();
+        let (resolved_registry, warnings) = match resolve_filtered_semconv_registry(
+            &mut attr_catalog,
+            "",
+            registry,
+            filter,
+            &self.severity_overrides,
+        )
+        .into_result_with_non_fatal()
+        {
+            Ok(result) => result,
+            Err(e) => return WResult::FatalErr(e),
+        };
+        let mut schema = Self::resolved_schema(registry, attr_catalog, resolved_registry);
+        match self.transform_groups(&mut schema) {
+            Ok(()) => WResult::with_non_fatal_errors(schema, warnings),
+            Err(e) => WResult::FatalErr(e),
+        }
+    }
+
     /// Resolves the given semantic convention registry and returns the
     /// corresponding resolved telemetry schema.
     pub fn resolve_semantic_convention_registry(
@@ -238,7 +453,36 @@ impl SchemaResolver {
     ) -> Result<ResolvedTelemetrySchema, Error> {
         let mut attr_catalog = AttributeCatalog::default();
         let resolved_registry = resolve_semconv_registry(&mut attr_catalog, "", registry)?;
+        Ok(Self::resolved_schema(
+            registry,
+            attr_catalog,
+            resolved_registry,
+        ))
+    }
 
+    /// Resolves the groups of the given semantic convention registry selected
+    /// by the filter, and the groups they depend on, and returns the
+    /// corresponding pruned resolved telemetry schema.
+    pub fn resolve_semantic_convention_registry_with_filter(
+        registry: &mut SemConvRegistry,
+        filter: &GroupFilter,
+    ) -> Result<ResolvedTelemetrySchema, Error> {
+        let mut attr_catalog = AttributeCatalog::default();
+        let resolved_registry =
+            resolve_semconv_registry_with_filter(&mut attr_catalog, "", registry, filter)?;
+        Ok(Self::resolved_schema(
+            registry,
+            attr_catalog,
+            resolved_registry,
+        ))
+    }
+
+    /// Builds the resolved telemetry schema of a resolved registry.
+    fn resolved_schema(
+        registry: &SemConvRegistry,
+        attr_catalog: AttributeCatalog,
+        resolved_registry: weaver_resolved_schema::registry::Registry,
+    ) -> ResolvedTelemetrySchema {
         let catalog = Catalog {
             attributes: attr_catalog.drain_attributes(),
         };
@@ -246,8 +490,8 @@ impl SchemaResolver {
         let mut registries = HashMap::new();
         _ = registries.insert(registry.id().into(), resolved_registry);
 
-        let resolved_schema = ResolvedTelemetrySchema {
-            file_format: "1.0.0".to_owned(),
+        ResolvedTelemetrySchema {
+            file_format: weaver_resolved_schema::FILE_FORMAT.to_owned(),
             schema_url: "".to_owned(),
             registries,
             catalog,
@@ -255,9 +499,7 @@ impl SchemaResolver {
             instrumentation_library: None,
             dependencies: vec![],
             versions: None, // ToDo LQ: Implement this!
-        };
-
-        Ok(resolved_schema)
+        }
     }
 
     /// Loads the semantic convention specifications from the given registry path.
@@ -266,14 +508,31 @@ impl SchemaResolver {
     ///
     /// # Arguments
     /// * `registry_repo` - The registry repository containing the semantic convention files.
+    #[cfg(feature = "registry-repo")]
     pub fn load_semconv_specs(
         registry_repo: &RegistryRepo,
         follow_symlinks: bool,
     ) -> WResult<Vec<(String, SemConvSpec)>, weaver_semconv::Error> {
-        Self::load_semconv_from_local_path(
+        Self::load_semconv_specs_with_filter(registry_repo, follow_symlinks, &SpecFilter::default())
+    }
+
+    /// Loads the semantic convention files and groups selected by the filter
+    /// from the given registry path.
+    ///
+    /// # Arguments
+    /// * `registry_repo` - The registry repository containing the semantic convention files.
+    /// * `filter` - The filter selecting the files and groups to load.
+    #[cfg(feature = "registry-repo")]
+    pub fn load_semconv_specs_with_filter(
+        registry_repo: &RegistryRepo,
+        follow_symlinks: bool,
+        filter: &SpecFilter,
+    ) -> WResult<Vec<(String, SemConvSpec)>, weaver_semconv::Error> {
+        Self::load_semconv_from_local_path_with_filter(
             registry_repo.path().to_path_buf(),
             registry_repo.registry_path_repr(),
             follow_symlinks,
+            filter,
         )
     }
 
@@ -284,10 +543,32 @@ impl SchemaResolver {
     /// # Arguments
     /// * `local_path` - The local path containing the semantic convention files.
     /// * `registry_path_repr` - The representation of the registry path (URL or path).
-    fn load_semconv_from_local_path(
+    pub fn load_semconv_from_local_path(
         local_path: PathBuf,
         registry_path_repr: &str,
         follow_symlinks: bool,
+    ) -> WResult<Vec<(String, SemConvSpec)>, weaver_semconv::Error> {
+        Self::load_semconv_from_local_path_with_filter(
+            local_path,
+            registry_path_repr,
+            follow_symlinks,
+            &SpecFilter::default(),
+        )
+    }
+
+    /// Loads the semantic convention files and groups selected by the filter
+    /// from the given local path. The path patterns of the filter are matched
+    /// against the paths relative to the local path.
+    ///
+    /// # Arguments
+    /// * `local_path` - The local path containing the semantic convention files.
+    /// * `registry_path_repr` - The representation of the registry path (URL or path).
+    /// * `filter` - The filter selecting the files and groups to load.
+    pub fn load_semconv_from_local_path_with_filter(
+        local_path: PathBuf,
+        registry_path_repr: &str,
+        follow_symlinks: bool,
+        filter: &SpecFilter,
     ) -> WResult<Vec<(String, SemConvSpec)>, weaver_semconv::Error> {
         fn is_hidden(entry: &DirEntry) -> bool {
             entry
@@ -301,13 +582,16 @@ impl SchemaResolver {
             let extension = path.extension().unwrap_or_else(|| std::ffi::OsStr::new(""));
             let file_name = path.file_name().unwrap_or_else(|| std::ffi::OsStr::new(""));
             path.is_file()
-                && (extension == "yaml" || extension == "yml")
+                && (extension == "yaml"
+                    || extension == "yml"
+                    || file_name.to_string_lossy().ends_with(".semconv.json"))
                 && file_name != "schema-next.yaml"
         }
 
         // Loads the semantic convention specifications from the git repo.
-        // All yaml files are recursively loaded and parsed in parallel from
-        // the given path.
+        // All YAML files and `*.semconv.json` files are recursively loaded
+        // and parsed in parallel from the given path. The other JSON files
+        // (e.g. tooling configuration) are ignored.
         let result = walkdir::WalkDir::new(local_path.clone())
             .follow_links(follow_symlinks)
             .into_iter()
@@ -316,28 +600,38 @@ impl SchemaResolver {
             .flat_map(|entry| {
                 match entry {
                     Ok(entry) => {
-                        if !is_semantic_convention_file(&entry) {
+                        let relative_path = entry
+                            .path()
+                            .strip_prefix(&local_path)
+                            .unwrap_or(entry.path())
+                            .display()
+                            .to_string();
+                        if !is_semantic_convention_file(&entry)
+                            || !filter.is_path_selected(&relative_path)
+                        {
                             return vec![].into_par_iter();
                         }
 
-                        vec![SemConvRegistry::semconv_spec_from_file(entry.path()).map(
-                            |(path, spec)| {
-                                // Replace the local path with the git URL combined with the relative path
-                                // of the semantic convention file.
-                                let prefix = local_path
-                                    .to_str()
-                                    .map(|s| s.to_owned())
-                                    .unwrap_or_default();
-                                let path = if registry_path_repr.ends_with(MAIN_SEPARATOR) {
-                                    let relative_path = &path[prefix.len()..];
-                                    format!("{}{}", registry_path_repr, relative_path)
-                                } else {
-                                    let relative_path = &path[prefix.len() + 1..];
-                                    format!("{}/{}", registry_path_repr, relative_path)
-                                };
-                                (path, spec)
-                            },
-                        )]
+                        vec![SemConvRegistry::semconv_spec_from_file_with_filter(
+                            entry.path(),
+                            filter,
+                        )
+                        .map(|(path, spec)| {
+                            // Replace the local path with the git URL combined with the relative path
+                            // of the semantic convention file.
+                            let prefix = local_path
+                                .to_str()
+                                .map(|s| s.to_owned())
+                                .unwrap_or_default();
+                            let path = if registry_path_repr.ends_with(MAIN_SEPARATOR) {
+                                let relative_path = &path[prefix.len()..];
+                                format!("{}{}", registry_path_repr, relative_path)
+                            } else {
+                                let relative_path = &path[prefix.len() + 1..];
+                                format!("{}/{}", registry_path_repr, relative_path)
+                            };
+                            (path, spec)
+                        })]
                         .into_par_iter()
                     }
                     Err(e) => vec![WResult::FatalErr(weaver_semconv::Error::SemConvSpecError {
@@ -365,6 +659,10 @@ impl SchemaResolver {
             }
         }
 
+        // The files are loaded in parallel, sort the specs by path to return
+        // them in a deterministic order.
+        specs.sort_by(|(path1, _), (path2, _)| Path::new(path1).cmp(Path::new(path2)));
+
         WResult::OkWithNFEs(specs, non_fatal_errors)
     }
 }
@@ -0,0 +1,349 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording of the location (line and column) of the groups and attributes
+//! in their source files.
+//!
+//! The YAML parser doesn't keep track of positions, so the source files are
+//! scanned for the `id:` and `ref:` keys of the block-style group and
+//! attribute definitions. The groups are the `id:` keys with the smallest
+//! indentation, and the attributes of a group are the `id:` and `ref:` keys
+//! with the smallest indentation between the group and the next one (deeper
+//! keys, such as enum members, are ignored).
+//!
+//! The keys of the content anchored with `&name` are repeated where the
+//! content is reused with the `*name` alias (e.g. `attributes: *common` or
+//! `<<: *base`), so that the reused definitions point at their original
+//! location.
+
+use std::collections::{HashMap, HashSet};
+
+use weaver_resolved_schema::lineage::SourceLocation;
+use weaver_resolved_schema::ResolvedTelemetrySchema;
+
+/// An `id:` or `ref:` key found in a source file.
+#[derive(Debug, Clone, PartialEq)]
+struct Key {
+    /// True for an `id:` key, false for a `ref:` key.
+    is_id: bool,
+    /// The value of the key.
+    value: String,
+    /// The location of the key.
+    location: SourceLocation,
+}
+
+/// The groups of a source file, with the attributes they define or reference.
+#[derive(Debug, Default, PartialEq)]
+struct FileLocations {
+    groups: HashMap<String, (SourceLocation, Vec<(String, SourceLocation)>)>,
+}
+
+/// Records the location of the groups and attributes of the registries of
+/// the resolved schema in their group lineage.
+///
+/// The source files that can't be read (e.g. URLs) are ignored.
+pub fn record_source_locations(schema: &mut ResolvedTelemetrySchema) {
+    let mut files: HashMap<String, Option<FileLocations>> = HashMap::new();
+    let catalog = &schema.catalog;
+
+    for registry in schema.registries.values_mut() {
+        for group in registry.groups.iter_mut() {
+            let Some(lineage) = group.lineage.as_mut() else {
+                continue;
+            };
+            let file_locations = files
+                .entry(lineage.source_file().to_owned())
+                .or_insert_with_key(|path| {
+                    std::fs::read_to_string(path)
+                        .ok()
+                        .map(|content| scan_file(&content))
+                });
+            let Some((group_location, attributes)) = file_locations
+                .as_ref()
+                .and_then(|file_locations| file_locations.groups.get(&group.id))
+            else {
+                continue;
+            };
+
+            lineage.set_source_location(*group_location);
+            let attr_names: HashSet<&str> = group
+                .attributes
+                .iter()
+                .filter_map(|attr_ref| catalog.attribute_name(attr_ref))
+                .collect();
+            for (attr_id, location) in attributes {
+                let prefixed_attr_id = format!("{}.{}", group.prefix, attr_id);
+                if attr_names.contains(attr_id.as_str()) {
+                    lineage.add_attribute_location(attr_id.clone(), *location);
+                } else if attr_names.contains(prefixed_attr_id.as_str()) {
+                    lineage.add_attribute_location(prefixed_attr_id, *location);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the locations of the groups and attributes defined in the content
+/// of a source file.
+fn scan_file(content: &str) -> FileLocations {
+    let keys = scan_keys(content);
+    let Some(group_column) = keys
+        .iter()
+        .filter(|key| key.is_id)
+        .map(|key| key.location.column)
+        .min()
+    else {
+        return FileLocations::default();
+    };
+
+    let mut file_locations = FileLocations::default();
+    let mut group_keys = keys
+        .split(|key| key.is_id && key.location.column == group_column)
+        .skip(1);
+    for group in keys
+        .iter()
+        .filter(|key| key.is_id && key.location.column == group_column)
+    {
+        let attribute_keys = group_keys.next().unwrap_or_default();
+        let attribute_column = attribute_keys.iter().map(|key| key.location.column).min();
+        let attributes = attribute_keys
+            .iter()
+            .filter(|key| Some(key.location.column) == attribute_column)
+            .map(|key| (key.value.clone(), key.location))
+            .collect();
+        _ = file_locations
+            .groups
+            .entry(group.value.clone())
+            .or_insert((group.location, attributes));
+    }
+    file_locations
+}
+
+/// Returns the `id:` and `ref:` keys of the content in document order, with
+/// the keys of the anchored content repeated at each alias.
+fn scan_keys(content: &str) -> Vec<Key> {
+    /// Content anchored with `&name` whose end hasn't been reached yet.
+    struct OpenAnchor {
+        name: String,
+        indent: usize,
+        /// True for an anchored sequence item (`- &name`), false for the
+        /// anchored value of a mapping key (`key: &name`).
+        is_item: bool,
+        keys: Vec<Key>,
+    }
+
+    let mut keys = vec![];
+    let mut anchors: HashMap<String, Vec<Key>> = HashMap::new();
+    let mut open_anchors: Vec<OpenAnchor> = vec![];
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        // The anchored content ends at the first line that is not nested in
+        // it. The items of a block sequence can have the same indentation as
+        // their parent key.
+        while let Some(anchor) = open_anchors.last() {
+            let nested = indent > anchor.indent
+                || (!anchor.is_item && indent == anchor.indent && trimmed.starts_with('-'));
+            if nested {
+                break;
+            }
+            if let Some(anchor) = open_anchors.pop() {
+                _ = anchors.insert(anchor.name, anchor.keys);
+            }
+        }
+
+        let mut line_keys: Vec<Key> = scan_line((index, line)).into_iter().collect();
+        if let Some(alias) = scan_node_property(trimmed, '*') {
+            line_keys.extend(anchors.get(&alias.0).cloned().unwrap_or_default());
+        }
+        for anchor in open_anchors.iter_mut() {
+            anchor.keys.extend(line_keys.iter().cloned());
+        }
+        keys.extend(line_keys);
+
+        if let Some((name, is_item)) = scan_node_property(trimmed, '&') {
+            open_anchors.push(OpenAnchor {
+                name,
+                indent,
+                is_item,
+                keys: vec![],
+            });
+        }
+    }
+    keys
+}
+
+/// Returns the name of the anchor (`&`) or alias (`*`) introducing the value
+/// of a mapping key (`key: &name`) or of a sequence item (`- &name`), and
+/// whether it introduces a sequence item.
+fn scan_node_property(trimmed_line: &str, indicator: char) -> Option<(String, bool)> {
+    let (value, is_item) = match trimmed_line.strip_prefix("- ") {
+        Some(item) if item.trim_start().starts_with(indicator) => (item.trim_start(), true),
+        _ => (trimmed_line.split_once(": ")?.1.trim_start(), false),
+    };
+    let name = value
+        .strip_prefix(indicator)?
+        .split_whitespace()
+        .next()
+        .unwrap_or_default();
+    (!name.is_empty()).then(|| (name.to_owned(), is_item))
+}
+
+/// Returns the `id:` or `ref:` key defined on the given line (if any).
+fn scan_line((index, line): (usize, &str)) -> Option<Key> {
+    let indent = line.len() - line.trim_start().len();
+    let mut rest = line.trim_start();
+    let mut column = indent + 1;
+    if let Some(item) = rest.strip_prefix('-') {
+        let item_indent = item.len() - item.trim_start().len();
+        if item_indent == 0 {
+            return None;
+        }
+        column += 1 + item_indent;
+        rest = item.trim_start();
+    }
+
+    let (is_id, value) = if let Some(value) = rest.strip_prefix("id:") {
+        (true, value)
+    } else if let Some(value) = rest.strip_prefix("ref:") {
+        (false, value)
+    } else {
+        return None;
+    };
+    let value = value.split(" #").next().unwrap_or_default().trim();
+    let value = value.trim_matches(|c| c == '"' || c == '\'');
+    if value.is_empty() {
+        return None;
+    }
+    Some(Key {
+        is_id,
+        value: value.to_owned(),
+        location: SourceLocation {
+            line: index + 1,
+            column,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use weaver_semconv::registry::SemConvRegistry;
+
+    use super::*;
+    use crate::SchemaResolver;
+
+    fn location(line: usize, column: usize) -> SourceLocation {
+        SourceLocation { line, column }
+    }
+
+    #[test]
+    fn test_scan_file() {
+        let content = "groups:
+  - id: registry.test
+    type: attribute_group
+    brief: Test
+    attributes:
+      - id: test.enum
+        type:
+          members:
+            - id: first # a comment
+              value: first
+      - id: \"test.other\"
+        type: string
+  - type: span
+    id: span.test
+    attributes:
+      - ref: test.enum
+";
+        let file_locations = scan_file(content);
+        assert_eq!(
+            file_locations.groups["registry.test"],
+            (
+                location(2, 5),
+                vec![
+                    ("test.enum".to_owned(), location(6, 9)),
+                    ("test.other".to_owned(), location(11, 9)),
+                ]
+            )
+        );
+        assert_eq!(
+            file_locations.groups["span.test"],
+            (
+                location(14, 5),
+                vec![("test.enum".to_owned(), location(16, 9))]
+            )
+        );
+    }
+
+    #[test]
+    fn test_scan_file_with_aliases() {
+        let content = "groups:
+  - id: registry.test
+    type: attribute_group
+    brief: &brief Test
+    attributes: &common
+    - id: test.first
+      type: string
+    - ref: test.second
+  - id: span.test
+    type: span
+    brief: *brief
+    attributes: *common
+  - id: event.test
+    type: event
+    attributes:
+      - <<: *common_attr
+        ref: test.third
+";
+        let file_locations = scan_file(content);
+        let common = vec![
+            ("test.first".to_owned(), location(6, 7)),
+            ("test.second".to_owned(), location(8, 7)),
+        ];
+        assert_eq!(
+            file_locations.groups["registry.test"],
+            (location(2, 5), common.clone())
+        );
+        assert_eq!(file_locations.groups["span.test"], (location(9, 5), common));
+        assert_eq!(
+            file_locations.groups["event.test"],
+            (
+                location(13, 5),
+                vec![("test.third".to_owned(), location(17, 9))]
+            )
+        );
+    }
+
+    #[test]
+    fn test_record_source_locations() {
+        let path = "data/registry-test-7-spans/registry/registry-http.yaml";
+        let mut registry = SemConvRegistry::try_from_path_pattern(
+            "test",
+            "data/registry-test-7-spans/registry/*.yaml",
+        )
+        .into_result_failing_non_fatal()
+        .expect("Failed to load the registry");
+        let mut schema = SchemaResolver::resolve_semantic_convention_registry(&mut registry)
+            .expect("Failed to resolve the registry");
+        record_source_locations(&mut schema);
+
+        let content = std::fs::read_to_string(path).expect("Failed to read the file");
+        let group = schema.registries["test"]
+            .groups
+            .iter()
+            .find(|group| group.id == "registry.http")
+            .expect("Group not found");
+        let lineage = group.lineage.as_ref().expect("Lineage not found");
+        let group_location = lineage.source_location().expect("Location not found");
+        let group_line = content.lines().nth(group_location.line - 1).unwrap();
+        assert!(group_line[group_location.column - 1..].starts_with("id: registry.http"));
+
+        let attr_location = lineage
+            .attribute_location("http.request.method")
+            .expect("Location not found");
+        let attr_line = content.lines().nth(attr_location.line - 1).unwrap();
+        assert!(attr_line[attr_location.column - 1..].starts_with("id: http.request.method"));
+    }
+}
@@ -3,20 +3,27 @@
 //! Functions to resolve a semantic convention registry.
 
 use itertools::Itertools;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde::Deserialize;
+use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
 use std::hash::Hash;
+use std::path::Path;
+use weaver_common::diagnostic::{SeverityOverride, SeverityOverrides};
 use weaver_common::error::handle_errors;
+use weaver_common::result::WResult;
 use weaver_resolved_schema::attribute::UnresolvedAttribute;
 use weaver_resolved_schema::lineage::{AttributeLineage, GroupLineage};
 use weaver_resolved_schema::registry::{Constraint, Group, Registry};
 use weaver_semconv::attribute::AttributeSpec;
-use weaver_semconv::group::GroupSpecWithProvenance;
+use weaver_semconv::group::{ExtendsSpec, GroupSpecWithProvenance};
 use weaver_semconv::registry::SemConvRegistry;
 
 use crate::attribute::AttributeCatalog;
 use crate::constraint::resolve_constraints;
+use crate::filter::GroupFilter;
+use crate::suggestion::did_you_mean;
 use crate::Error::{DuplicateGroupId, DuplicateGroupName, DuplicateMetricName};
 use crate::{Error, UnsatisfiedAnyOfConstraint};
 
@@ -74,10 +81,96 @@ pub fn resolve_semconv_registry(
     registry_url: &str,
     registry: &SemConvRegistry,
 ) -> Result<Registry, Error> {
+    resolve_filtered_semconv_registry(
+        attr_catalog,
+        registry_url,
+        registry,
+        None,
+        &SeverityOverrides::default(),
+    )
+    // No check is demoted to a warning by the default severity overrides.
+    .into_result_failing_non_fatal()
+}
+
+/// Resolves the groups of the semantic convention registry selected by the
+/// given filter, and the groups they depend on (`extends`, `include`, and
+/// attribute references), and returns the pruned resolved registry or an
+/// error if the resolution process failed.
+///
+/// The attribute catalog only contains the attributes of the resolved groups.
+///
+/// # Arguments
+///
+/// * `attr_catalog` - The attribute catalog to use to resolve the attribute references.
+/// * `registry_url` - The URL of the registry.
+/// * `registry` - The semantic convention registry.
+/// * `filter` - The filter selecting the groups to resolve.
+pub fn resolve_semconv_registry_with_filter(
+    attr_catalog: &mut AttributeCatalog,
+    registry_url: &str,
+    registry: &SemConvRegistry,
+    filter: &GroupFilter,
+) -> Result<Registry, Error> {
+    resolve_filtered_semconv_registry(
+        attr_catalog,
+        registry_url,
+        registry,
+        Some(filter),
+        &SeverityOverrides::default(),
+    )
+    // No check is demoted to a warning by the default severity overrides.
+    .into_result_failing_non_fatal()
+}
+
+/// Resolves the groups of the semantic convention registry selected by the
+/// optional filter, skipping the registry checks ignored by the severity
+/// overrides. The errors of the checks demoted to warnings are returned as
+/// non-fatal errors alongside the resolved registry.
+pub(crate) fn resolve_filtered_semconv_registry(
+    attr_catalog: &mut AttributeCatalog,
+    registry_url: &str,
+    registry: &SemConvRegistry,
+    filter: Option<&GroupFilter>,
+    severity_overrides: &SeverityOverrides,
+) -> WResult<Registry, Error> {
+    let (registry, errors) =
+        match resolve_and_check_registry(attr_catalog, registry_url, registry, filter) {
+            Ok(result) => result,
+            Err(e) => return WResult::FatalErr(e),
+        };
+
+    let mut warnings = vec![];
+    let mut fatal_errors = vec![];
+    for error in errors {
+        match severity_overrides.get(&error) {
+            Some(SeverityOverride::Ignore) => {}
+            Some(SeverityOverride::Warning) => warnings.push(error),
+            Some(SeverityOverride::Error) | None => fatal_errors.push(error),
+        }
+    }
+    match handle_errors(fatal_errors) {
+        Ok(()) => WResult::with_non_fatal_errors(registry, warnings),
+        Err(e) => WResult::FatalErr(e),
+    }
+}
+
+/// Resolves the groups of the semantic convention registry selected by the
+/// optional filter, and returns the resolved registry along with the errors
+/// of the registry checks (e.g. duplicate group ids).
+fn resolve_and_check_registry(
+    attr_catalog: &mut AttributeCatalog,
+    registry_url: &str,
+    registry: &SemConvRegistry,
+    filter: Option<&GroupFilter>,
+) -> Result<(Registry, Vec<Error>), Error> {
     let mut ureg = unresolved_registry_from_specs(registry_url, registry);
 
     resolve_prefix_on_attributes(&mut ureg)?;
 
+    if let Some(filter) = filter {
+        filter.prune(&mut ureg);
+    }
+
     resolve_extends_references(&mut ureg)?;
 
     resolve_attribute_references(&mut ureg, attr_catalog)?;
@@ -140,9 +233,7 @@ pub fn resolve_semconv_registry(
     );
     check_root_attribute_id_duplicates(&ureg.registry, &attr_name_index, &mut errors);
 
-    handle_errors(errors)?;
-
-    Ok(ureg.registry)
+    Ok((ureg.registry, errors))
 }
 
 /// Checks the `any_of` constraints in the given registry.
@@ -171,6 +262,7 @@ pub fn check_any_of_constraints(
                     group_id: group.id.clone(),
                     attribute_ref: attr_ref.0.to_string(),
                     provenance: group.provenance().to_owned(),
+                    help: None,
                 }),
                 Some(attr_name) => {
                     _ = group_attr_names.insert(attr_name.clone());
@@ -350,15 +442,20 @@ pub fn check_root_attribute_id_duplicates(
 ///
 /// This function returns an unresolved registry containing the semantic
 /// convention specifications.
-fn unresolved_registry_from_specs(
+pub(crate) fn unresolved_registry_from_specs(
     registry_url: &str,
     registry: &SemConvRegistry,
 ) -> UnresolvedRegistry {
-    let groups = registry
+    let mut groups: Vec<_> = registry
         .unresolved_group_with_provenance_iter()
         .map(group_from_spec)
         .collect();
 
+    // The groups are resolved in the order of their provenance, and in the
+    // order of declaration for the groups of the same file, so the attribute
+    // references don't depend on the order in which the files are loaded.
+    groups.sort_by(|g1, g2| Path::new(&g1.provenance).cmp(Path::new(&g2.provenance)));
+
     UnresolvedRegistry {
         registry: Registry {
             registry_url: registry_url.to_owned(),
@@ -398,6 +495,7 @@ fn group_from_spec(group: GroupSpecWithProvenance) -> UnresolvedGroup {
             name: group.spec.name,
             lineage: Some(GroupLineage::new(&group.provenance)),
             display_name: group.spec.display_name,
+            schema_url: group.spec.schema_url,
             body: group.spec.body,
         },
         attributes: attrs,
@@ -409,7 +507,7 @@ fn group_from_spec(group: GroupSpecWithProvenance) -> UnresolvedGroup {
 /// the group prefix before continuing resolution.
 ///
 /// This should be the *only* method that updates attribute ids.
-fn resolve_prefix_on_attributes(ureg: &mut UnresolvedRegistry) -> Result<(), Error> {
+pub(crate) fn resolve_prefix_on_attributes(ureg: &mut UnresolvedRegistry) -> Result<(), Error> {
     for unresolved_group in ureg.groups.iter_mut() {
         if !unresolved_group.group.prefix.is_empty() {
             for attribute in unresolved_group.attributes.iter_mut() {
@@ -427,8 +525,12 @@ fn resolve_prefix_on_attributes(ureg: &mut UnresolvedRegistry) -> Result<(), Err
 /// attribute references are resolved or when no attribute reference could
 /// be resolved in an iteration.
 ///
-/// The resolve method of the attribute catalog is used to resolve the
-/// attribute references.
+/// In each iteration, the attributes of all the groups are first resolved in
+/// parallel against the current state of the attribute catalog. The resolved
+/// attributes are then added to the catalog sequentially, in the order of the
+/// groups and of their attributes, so the attribute references are the same
+/// as with a sequential resolution. References to root attributes updated
+/// earlier in the same iteration are resolved again during this second step.
 ///
 /// Returns true if all the attribute references could be resolved.
 fn resolve_attribute_references(
@@ -439,26 +541,53 @@ fn resolve_attribute_references(
         let mut errors = vec![];
         let mut resolved_attr_count = 0;
 
-        // Iterate over all groups and resolve the attributes.
-        for unresolved_group in ureg.groups.iter_mut() {
+        // Resolve the attributes of all the groups in parallel.
+        let catalog = &*attr_catalog;
+        let speculative_attrs: Vec<Vec<_>> = ureg
+            .groups
+            .par_iter()
+            .map(|unresolved_group| {
+                unresolved_group
+                    .attributes
+                    .iter()
+                    .map(|attr| {
+                        catalog.resolve_attribute(&unresolved_group.group.prefix, &attr.spec)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Root attributes (re)defined in this iteration.
+        let mut updated_roots = HashSet::new();
+
+        // Iterate over all groups and add the resolved attributes to the
+        // catalog.
+        for (unresolved_group, speculative_attrs) in ureg.groups.iter_mut().zip(speculative_attrs) {
             let mut resolved_attr = vec![];
 
             // Remove attributes that are resolved and keep unresolved attributes
             // in the group for the next iteration.
-            unresolved_group.attributes = unresolved_group
-                .attributes
-                .clone()
+            unresolved_group.attributes = std::mem::take(&mut unresolved_group.attributes)
                 .into_iter()
-                .filter_map(|attr| {
-                    let attr_ref = attr_catalog.resolve(
-                        &unresolved_group.group.id,
-                        &unresolved_group.group.prefix,
-                        &attr.spec,
-                        unresolved_group.group.lineage.as_mut(),
-                    );
-                    if let Some(attr_ref) = attr_ref {
+                .zip(speculative_attrs)
+                .filter_map(|(attr, speculative_attr)| {
+                    let resolved = match &attr.spec {
+                        AttributeSpec::Ref { r#ref, .. } if updated_roots.contains(r#ref) => {
+                            attr_catalog
+                                .resolve_attribute(&unresolved_group.group.prefix, &attr.spec)
+                        }
+                        _ => speculative_attr,
+                    };
+                    if let Some(resolved) = resolved {
                         // Attribute reference resolved successfully.
-                        resolved_attr.push(attr_ref);
+                        if let Some(root_name) = resolved.root_name() {
+                            _ = updated_roots.insert(root_name.to_owned());
+                        }
+                        resolved_attr.push(attr_catalog.register(
+                            &unresolved_group.group.id,
+                            resolved,
+                            unresolved_group.group.lineage.as_mut(),
+                        ));
                         resolved_attr_count += 1;
 
                         // Return None to remove this attribute from the
@@ -473,6 +602,7 @@ fn resolve_attribute_references(
                                 group_id: unresolved_group.group.id.clone(),
                                 attribute_ref: r#ref.clone(),
                                 provenance: unresolved_group.provenance.clone(),
+                                help: None,
                             });
                         }
                         Some(attr)
@@ -492,6 +622,49 @@ fn resolve_attribute_references(
         // It means that we have an issue with the semantic convention
         // specifications.
         if resolved_attr_count == 0 {
+            // Report the references forming a cycle with the complete cycle
+            // instead of an unresolved reference per attribute.
+            let pending = pending_root_attributes(ureg);
+            let cycles = find_attribute_ref_cycles(&pending);
+            let refs_in_cycles: HashSet<(&str, &str)> = cycles
+                .iter()
+                .flatten()
+                .map(|pending| (pending.group.group.id.as_str(), pending.attribute_ref))
+                .collect();
+            errors.retain(|error| match error {
+                Error::UnresolvedAttributeRef {
+                    group_id,
+                    attribute_ref,
+                    ..
+                } => !refs_in_cycles.contains(&(group_id.as_str(), attribute_ref.as_str())),
+                _ => true,
+            });
+            for error in errors.iter_mut() {
+                if let Error::UnresolvedAttributeRef {
+                    attribute_ref,
+                    help,
+                    ..
+                } = error
+                {
+                    *help = did_you_mean(attribute_ref, attr_catalog.root_attribute_names());
+                }
+            }
+            errors.extend(cycles.into_iter().map(|cycle| {
+                Error::CircularAttributeRef {
+                    attribute_refs: cycle
+                        .iter()
+                        .map(|pending| pending.attribute_ref.to_owned())
+                        .collect(),
+                    group_ids: cycle
+                        .iter()
+                        .map(|pending| pending.group.group.id.clone())
+                        .collect(),
+                    provenances: cycle
+                        .iter()
+                        .map(|pending| pending.group.provenance.clone())
+                        .collect(),
+                }
+            }));
             return Err(Error::CompoundError(errors));
         }
     }
@@ -499,6 +672,81 @@ fn resolve_attribute_references(
     Ok(())
 }
 
+/// An unresolved prefixed attribute reference, i.e. a root attribute waiting
+/// for the attribute it references.
+struct PendingRootAttribute<'a> {
+    /// The name of the root attribute defined by the reference.
+    name: String,
+    /// The referenced attribute.
+    attribute_ref: &'a str,
+    /// The group defining the reference.
+    group: &'a UnresolvedGroup,
+}
+
+/// Returns the unresolved prefixed attribute references of the registry.
+fn pending_root_attributes(ureg: &UnresolvedRegistry) -> Vec<PendingRootAttribute<'_>> {
+    ureg.groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .attributes
+                .iter()
+                .filter_map(move |attr| match &attr.spec {
+                    AttributeSpec::Ref {
+                        r#ref,
+                        prefix: true,
+                        ..
+                    } => Some(PendingRootAttribute {
+                        name: format!("{}.{}", group.group.prefix, r#ref),
+                        attribute_ref: r#ref,
+                        group,
+                    }),
+                    _ => None,
+                })
+        })
+        .collect()
+}
+
+/// Returns the cycles formed by the unresolved prefixed attribute references.
+/// Each cycle starts with the reference having the smallest name.
+fn find_attribute_ref_cycles<'a, 'b>(
+    pending: &'b [PendingRootAttribute<'a>],
+) -> Vec<Vec<&'b PendingRootAttribute<'a>>> {
+    let roots: BTreeMap<&str, &PendingRootAttribute<'_>> = pending
+        .iter()
+        .map(|pending| (pending.name.as_str(), pending))
+        .collect();
+    let mut visited = HashSet::new();
+    let mut cycles = vec![];
+
+    for start in roots.keys() {
+        // Follow the chain of references until a root attribute is seen twice.
+        let mut path: Vec<&str> = vec![];
+        let mut current = Some(*start);
+        while let Some(name) = current {
+            if let Some(pos) = path.iter().position(|n| *n == name) {
+                let mut cycle: Vec<_> = path[pos..].iter().map(|n| roots[n]).collect();
+                let first = cycle
+                    .iter()
+                    .position_min_by_key(|pending| pending.name.as_str())
+                    .unwrap_or(0);
+                cycle.rotate_left(first);
+                cycles.push(cycle);
+                break;
+            }
+            if !visited.insert(name) {
+                break;
+            }
+            path.push(name);
+            current = roots
+                .get(name)
+                .map(|pending| pending.attribute_ref)
+                .filter(|attribute_ref| roots.contains_key(attribute_ref));
+        }
+    }
+    cycles
+}
+
 /// Resolves the `extends` references in the given registry.
 /// The resolution process is iterative. The process stops when all the
 /// `extends` references are resolved or when no `extends` reference could
@@ -506,6 +754,9 @@ fn resolve_attribute_references(
 ///
 /// Returns true if all the `extends` references have been resolved.
 fn resolve_extends_references(ureg: &mut UnresolvedRegistry) -> Result<(), Error> {
+    // The attributes defined differently by several extended groups and not
+    // redefined by the extending group.
+    let mut conflicts = vec![];
     loop {
         let mut errors = vec![];
         let mut resolved_extends_count = 0;
@@ -519,17 +770,38 @@ fn resolve_extends_references(ureg: &mut UnresolvedRegistry) -> Result<(), Error
             }
         }
 
-        // Iterate over all groups and resolve the `extends` clauses.
+        // Iterate over all groups and resolve the `extends` clauses. A group
+        // is resolved once all the groups it extends are resolved.
         for unresolved_group in ureg.groups.iter_mut() {
             if let Some(extends) = unresolved_group.group.extends.as_ref() {
-                if let Some(attrs) = group_index.get(extends) {
-                    unresolved_group.attributes = resolve_inheritance_attrs(
+                let (parents, missing_parents): (Vec<_>, Vec<_>) = extends
+                    .group_ids()
+                    .iter()
+                    .map(|parent_id| (parent_id.as_str(), group_index.get(parent_id)))
+                    .partition(|(_, attrs)| attrs.is_some());
+                if missing_parents.is_empty() {
+                    if let Some(lineage) = unresolved_group.group.lineage.as_mut() {
+                        lineage.set_extends(extends);
+                    }
+                    let parents: Vec<_> = parents
+                        .into_iter()
+                        .filter_map(|(parent_id, attrs)| attrs.map(|attrs| (parent_id, attrs)))
+                        .collect();
+                    let (attributes, group_conflicts) = resolve_inheritance_attrs(
                         &unresolved_group.group.id,
                         &unresolved_group.attributes,
-                        extends,
-                        attrs,
+                        &parents,
                         unresolved_group.group.lineage.as_mut(),
                     );
+                    unresolved_group.attributes = attributes;
+                    conflicts.extend(group_conflicts.into_iter().map(
+                        |(attribute_id, parent_group_ids)| Error::ConflictingInheritedAttribute {
+                            group_id: unresolved_group.group.id.clone(),
+                            attribute_id,
+                            parent_group_ids,
+                            provenance: unresolved_group.provenance.clone(),
+                        },
+                    ));
                     _ = unresolved_group.group.extends.take();
                     _ = group_index.insert(
                         unresolved_group.group.id.clone(),
@@ -537,15 +809,14 @@ fn resolve_extends_references(ureg: &mut UnresolvedRegistry) -> Result<(), Error
                     );
                     resolved_extends_count += 1;
                 } else {
-                    errors.push(Error::UnresolvedExtendsRef {
-                        group_id: unresolved_group.group.id.clone(),
-                        extends_ref: unresolved_group
-                            .group
-                            .extends
-                            .clone()
-                            .unwrap_or("".to_owned()),
-                        provenance: unresolved_group.provenance.clone(),
-                    });
+                    for (parent_id, _) in missing_parents {
+                        errors.push(Error::UnresolvedExtendsRef {
+                            group_id: unresolved_group.group.id.clone(),
+                            extends_ref: parent_id.to_owned(),
+                            provenance: unresolved_group.provenance.clone(),
+                            help: None,
+                        });
+                    }
                 }
             }
         }
@@ -558,10 +829,90 @@ fn resolve_extends_references(ureg: &mut UnresolvedRegistry) -> Result<(), Error
         // It means that we have an issue with the semantic convention
         // specifications.
         if resolved_extends_count == 0 {
+            // Report the groups forming a cycle with the complete cycle
+            // instead of an unresolved `extends` per group.
+            let cycles = find_extends_cycles(ureg);
+            let groups_in_cycles: HashSet<&str> = cycles
+                .iter()
+                .flatten()
+                .map(|group| group.group.id.as_str())
+                .collect();
+            errors.retain(|error| match error {
+                Error::UnresolvedExtendsRef { group_id, .. } => {
+                    !groups_in_cycles.contains(group_id.as_str())
+                }
+                _ => true,
+            });
+            for error in errors.iter_mut() {
+                if let Error::UnresolvedExtendsRef {
+                    extends_ref, help, ..
+                } = error
+                {
+                    *help = did_you_mean(
+                        extends_ref,
+                        ureg.groups.iter().map(|group| group.group.id.as_str()),
+                    );
+                }
+            }
+            errors.extend(cycles.into_iter().map(|cycle| Error::CircularExtendsRef {
+                group_ids: cycle.iter().map(|group| group.group.id.clone()).collect(),
+                provenances: cycle.iter().map(|group| group.provenance.clone()).collect(),
+            }));
+            errors.extend(conflicts);
             return Err(Error::CompoundError(errors));
         }
     }
-    Ok(())
+    handle_errors(conflicts)
+}
+
+/// Returns the cycles formed by the `extends` clauses of the unresolved groups.
+/// Each cycle starts with the group having the smallest id.
+fn find_extends_cycles(ureg: &UnresolvedRegistry) -> Vec<Vec<&UnresolvedGroup>> {
+    let groups: BTreeMap<&str, &UnresolvedGroup> = ureg
+        .groups
+        .iter()
+        .filter(|group| group.group.extends.is_some())
+        .map(|group| (group.group.id.as_str(), group))
+        .collect();
+    let mut visited = HashSet::new();
+    let mut cycles = vec![];
+
+    for start in groups.keys() {
+        visit_extends(start, &groups, &mut vec![], &mut visited, &mut cycles);
+    }
+    cycles
+}
+
+/// Follows the `extends` clauses from the given group (depth-first), and
+/// records the cycles found, i.e. the groups already in the current path.
+fn visit_extends<'a>(
+    group_id: &'a str,
+    groups: &BTreeMap<&'a str, &'a UnresolvedGroup>,
+    path: &mut Vec<&'a str>,
+    visited: &mut HashSet<&'a str>,
+    cycles: &mut Vec<Vec<&'a UnresolvedGroup>>,
+) {
+    if let Some(pos) = path.iter().position(|id| *id == group_id) {
+        let mut cycle: Vec<_> = path[pos..].iter().map(|id| groups[id]).collect();
+        let first = cycle
+            .iter()
+            .position_min_by_key(|group| group.group.id.as_str())
+            .unwrap_or(0);
+        cycle.rotate_left(first);
+        cycles.push(cycle);
+        return;
+    }
+    let Some(group) = groups.get(group_id) else {
+        return;
+    };
+    if !visited.insert(group_id) {
+        return;
+    }
+    path.push(group_id);
+    for parent_id in group.group.extends.iter().flat_map(ExtendsSpec::group_ids) {
+        visit_extends(parent_id, groups, path, visited, cycles);
+    }
+    _ = path.pop();
 }
 
 /// Resolves the `include` constraints in the given registry.
@@ -655,13 +1006,19 @@ fn resolve_include_constraints(ureg: &mut UnresolvedRegistry) -> Result<(), Erro
     Ok(())
 }
 
+/// Returns the attributes of a group extending the given parent groups, and
+/// the attributes defined differently by several parent groups and not
+/// redefined by the group (with the ids of these parent groups).
+///
+/// The attributes of the first parent groups take precedence over the
+/// attributes of the next ones, and the attributes of the group complete or
+/// override the inherited attributes.
 fn resolve_inheritance_attrs(
     group_id: &str,
     attrs_group: &[UnresolvedAttribute],
-    parent_group_id: &str,
-    attrs_parent_group: &[UnresolvedAttribute],
+    parents: &[(&str, &Vec<UnresolvedAttribute>)],
     group_lineage: Option<&mut GroupLineage>,
-) -> Vec<UnresolvedAttribute> {
+) -> (Vec<UnresolvedAttribute>, Vec<(String, Vec<String>)>) {
     struct AttrWithLineage {
         spec: AttributeSpec,
         lineage: AttributeLineage,
@@ -674,16 +1031,28 @@ fn resolve_inheritance_attrs(
     // ensure that the resolved registry is easy to compare.
     let mut inherited_attrs = BTreeMap::new();
 
-    // Inherit the attributes from the parent group.
-    for parent_attr in attrs_parent_group.iter() {
-        let attr_id = parent_attr.spec.id();
-        _ = inherited_attrs.insert(
-            attr_id.clone(),
-            AttrWithLineage {
-                spec: parent_attr.spec.clone(),
-                lineage: AttributeLineage::inherit_from(parent_group_id, &parent_attr.spec),
-            },
-        );
+    // Inherit the attributes from the parent groups, and keep track of the
+    // attributes defined differently by several parent groups.
+    let mut conflicts: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (parent_group_id, attrs_parent_group) in parents.iter() {
+        for parent_attr in attrs_parent_group.iter() {
+            match inherited_attrs.entry(parent_attr.spec.id()) {
+                Entry::Vacant(entry) => {
+                    _ = entry.insert(AttrWithLineage {
+                        spec: parent_attr.spec.clone(),
+                        lineage: AttributeLineage::inherit_from(parent_group_id, &parent_attr.spec),
+                    });
+                }
+                Entry::Occupied(entry) => {
+                    if is_conflicting_inherited_attr(&entry.get().spec, &parent_attr.spec) {
+                        conflicts
+                            .entry(entry.key().clone())
+                            .or_insert_with(|| vec![entry.get().lineage.source_group.clone()])
+                            .push((*parent_group_id).to_owned());
+                    }
+                }
+            }
+        }
     }
 
     // Override the inherited attributes with the attributes from the group.
@@ -718,8 +1087,13 @@ fn resolve_inheritance_attrs(
         }
     }
 
+    // The conflicts are resolved by the attributes redefined in the group.
+    for attr in attrs_group.iter() {
+        _ = conflicts.remove(&attr.spec.id());
+    }
+
     let inherited_attrs = inherited_attrs.into_values();
-    if let Some(group_lineage) = group_lineage {
+    let attributes = if let Some(group_lineage) = group_lineage {
         inherited_attrs
             .map(|attr_with_lineage| {
                 if !attr_with_lineage.lineage.is_empty() {
@@ -739,7 +1113,33 @@ fn resolve_inheritance_attrs(
                 spec: attr_with_lineage.spec,
             })
             .collect()
+    };
+    (attributes, conflicts.into_iter().collect())
+}
+
+/// Returns true if two extended groups define the same attribute differently.
+/// A reference without any overridden field does not conflict with any other
+/// definition of the attribute.
+fn is_conflicting_inherited_attr(attr: &AttributeSpec, other_attr: &AttributeSpec) -> bool {
+    fn is_plain_ref(attr: &AttributeSpec) -> bool {
+        matches!(
+            attr,
+            AttributeSpec::Ref {
+                brief: None,
+                examples: None,
+                tag: None,
+                requirement_level: None,
+                sampling_relevant: None,
+                note: None,
+                stability: None,
+                deprecated: None,
+                prefix: false,
+                ..
+            }
+        )
     }
+
+    attr != other_attr && !is_plain_ref(attr) && !is_plain_ref(other_attr)
 }
 
 fn resolve_inheritance_attr(
@@ -801,6 +1201,8 @@ fn resolve_inheritance_attr(
                     note: parent_note,
                     stability: parent_stability,
                     deprecated: parent_deprecated,
+                    value_constraints: parent_value_constraints,
+                    aliases: parent_aliases,
                     ..
                 } => {
                     // attr is a reference and attr_parent is an id.
@@ -818,6 +1220,8 @@ fn resolve_inheritance_attr(
                         note: lineage.note(note, parent_note),
                         stability: lineage.stability(stability, parent_stability),
                         deprecated: lineage.deprecated(deprecated, parent_deprecated),
+                        value_constraints: parent_value_constraints.clone(),
+                        aliases: parent_aliases.clone(),
                     }
                 }
             }
@@ -833,7 +1237,10 @@ mod tests {
     use std::path::PathBuf;
 
     use glob::glob;
+    use miette::Diagnostic;
     use serde::Serialize;
+    use weaver_common::diagnostic::{SeverityOverride, SeverityOverrides};
+    use weaver_common::result::WResult;
     use weaver_diff::canonicalize_json_string;
     use weaver_resolved_schema::attribute;
     use weaver_resolved_schema::registry::{Constraint, Registry};
@@ -841,7 +1248,11 @@ mod tests {
     use weaver_semconv::registry::SemConvRegistry;
 
     use crate::attribute::AttributeCatalog;
-    use crate::registry::{check_group_any_of_constraints, resolve_semconv_registry};
+    use crate::registry::{
+        check_group_any_of_constraints, find_attribute_ref_cycles, pending_root_attributes,
+        resolve_filtered_semconv_registry, resolve_semconv_registry,
+        unresolved_registry_from_specs, PendingRootAttribute,
+    };
     use crate::SchemaResolver;
 
     /// Test the resolution of semantic convention registries stored in the
@@ -968,6 +1379,40 @@ mod tests {
         }
     }
 
+    /// The attribute references must not depend on the order in which the
+    /// semantic convention files are loaded.
+    #[test]
+    fn test_registry_resolution_is_deterministic() {
+        fn resolve(paths: Vec<PathBuf>) -> (String, String) {
+            let specs = paths
+                .iter()
+                .map(|path| {
+                    SemConvRegistry::semconv_spec_from_file(path)
+                        .into_result_failing_non_fatal()
+                        .expect("Failed to load semconv spec")
+                })
+                .collect();
+            let sc_specs = SemConvRegistry::from_semconv_specs("default", specs);
+            let mut attr_catalog = AttributeCatalog::default();
+            let registry =
+                resolve_semconv_registry(&mut attr_catalog, "https://127.0.0.1", &sc_specs)
+                    .expect("Failed to resolve the registry");
+            (
+                serde_json::to_string(&registry).unwrap(),
+                serde_json::to_string(&attr_catalog.drain_attributes()).unwrap(),
+            )
+        }
+
+        let paths: Vec<PathBuf> = glob("data/registry-test-7-spans/registry/*.yaml")
+            .expect("Failed to read glob pattern")
+            .map(|path| path.expect("Failed to read path"))
+            .collect();
+        let mut reversed_paths = paths.clone();
+        reversed_paths.reverse();
+
+        assert_eq!(resolve(paths), resolve(reversed_paths));
+    }
+
     fn create_registry_from_string(registry_spec: &str) -> Result<Registry, crate::Error> {
         let mut sc_specs = SemConvRegistry::new("default");
         sc_specs
@@ -980,6 +1425,97 @@ mod tests {
         resolve_semconv_registry(&mut attr_catalog, "https://127.0.0.1", &sc_specs)
     }
 
+    #[test]
+    fn test_registry_group_schema_url() {
+        let registry = create_registry_from_string(
+            "
+groups:
+    - id: registry.one
+      type: attribute_group
+      brief: \"Group one\"
+      schema_url: https://opentelemetry.io/schemas/1.27.0
+      attributes:
+        - id: one.attr
+          type: string
+          brief: \"Attribute\"
+          examples: [\"a\"]
+    - id: registry.two
+      type: attribute_group
+      brief: \"Group two\"
+      extends: registry.one",
+        )
+        .expect("Failed to resolve the registry");
+
+        assert_eq!(
+            registry.groups[0].schema_url.as_deref(),
+            Some("https://opentelemetry.io/schemas/1.27.0")
+        );
+        // The schema URL is specific to the group defining it.
+        assert_eq!(registry.groups[1].schema_url, None);
+    }
+
+    #[test]
+    fn test_registry_demoted_duplicate_group() {
+        let mut sc_specs = SemConvRegistry::new("default");
+        for name in ["one", "two"] {
+            sc_specs
+                .add_semconv_spec_from_string(
+                    &format!("<{name}>"),
+                    &format!(
+                        "
+groups:
+    - id: registry.dup
+      type: attribute_group
+      brief: \"Duplicate group\"
+      attributes:
+        - id: dup.{name}
+          type: string
+          brief: \"Attribute\"
+          examples: [\"a\"]"
+                    ),
+                )
+                .into_result_failing_non_fatal()
+                .expect("Failed to load semconv spec");
+        }
+        let resolve = |severity_overrides: &SeverityOverrides| {
+            let mut attr_catalog = AttributeCatalog::default();
+            resolve_filtered_semconv_registry(
+                &mut attr_catalog,
+                "https://127.0.0.1",
+                &sc_specs,
+                None,
+                severity_overrides,
+            )
+        };
+
+        // The duplicate group id fails the resolution by default.
+        assert!(matches!(
+            resolve(&SeverityOverrides::default()),
+            WResult::FatalErr(crate::Error::DuplicateGroupId { .. })
+        ));
+
+        // Demoted to a warning, it's returned alongside the resolved registry.
+        let overrides: SeverityOverrides =
+            [("DuplicateGroupId".to_owned(), SeverityOverride::Warning)]
+                .into_iter()
+                .collect();
+        let WResult::OkWithNFEs(registry, warnings) = resolve(&overrides) else {
+            panic!("Expected the registry with non-fatal errors");
+        };
+        assert_eq!(registry.groups.len(), 2);
+        assert!(matches!(
+            warnings.as_slice(),
+            [crate::Error::DuplicateGroupId { group_id, .. }] if group_id == "registry.dup"
+        ));
+
+        // Ignored, it's not reported at all.
+        let overrides: SeverityOverrides =
+            [("DuplicateGroupId".to_owned(), SeverityOverride::Ignore)]
+                .into_iter()
+                .collect();
+        assert!(matches!(resolve(&overrides), WResult::Ok(_)));
+    }
+
     #[test]
     fn test_registry_error_unresolved_extends() {
         let result = create_registry_from_string(
@@ -1004,6 +1540,221 @@ groups:
         }
     }
 
+    #[test]
+    fn test_find_attribute_ref_cycles() {
+        let mut sc_specs = SemConvRegistry::new("default");
+        _ = sc_specs
+            .add_semconv_spec_from_string(
+                "<str>",
+                "
+groups:
+    - id: group.a
+      type: attribute_group
+      brief: \"Group a\"
+      prefix: a
+      attributes:
+        - ref: b.x
+          prefix: true
+    - id: group.b
+      type: attribute_group
+      brief: \"Group b\"
+      prefix: b
+      attributes:
+        - ref: x
+          prefix: true",
+            )
+            .into_result_with_non_fatal()
+            .expect("Failed to load semconv spec");
+        let ureg = unresolved_registry_from_specs("https://127.0.0.1", &sc_specs);
+
+        // `b.x` is waiting for the missing `x`, so there is no cycle.
+        let pending = pending_root_attributes(&ureg);
+        assert_eq!(
+            pending.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["a.b.x", "b.x"]
+        );
+        assert!(find_attribute_ref_cycles(&pending).is_empty());
+
+        // References depending on each other, plus one waiting for the cycle.
+        let pending = vec![
+            PendingRootAttribute {
+                name: "c".to_owned(),
+                attribute_ref: "a",
+                group: &ureg.groups[0],
+            },
+            PendingRootAttribute {
+                name: "b".to_owned(),
+                attribute_ref: "c",
+                group: &ureg.groups[1],
+            },
+            PendingRootAttribute {
+                name: "a".to_owned(),
+                attribute_ref: "b",
+                group: &ureg.groups[0],
+            },
+            PendingRootAttribute {
+                name: "d".to_owned(),
+                attribute_ref: "a",
+                group: &ureg.groups[1],
+            },
+        ];
+        let cycles = find_attribute_ref_cycles(&pending);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(
+            cycles[0]
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_registry_error_circular_extends() {
+        let result = create_registry_from_string(
+            "
+groups:
+    - id: group.b
+      type: attribute_group
+      brief: \"Group b\"
+      extends: group.c
+    - id: group.c
+      type: attribute_group
+      brief: \"Group c\"
+      extends: group.a
+    - id: group.a
+      type: attribute_group
+      brief: \"Group a\"
+      extends: group.b
+    - id: group.d
+      type: attribute_group
+      brief: \"Group d\"
+      extends: group.d
+    - id: group.e
+      type: attribute_group
+      brief: \"Group e\"
+      extends: group.a",
+        );
+
+        let Err(crate::Error::CompoundError(errors)) = result else {
+            panic!("Expected a CompoundError");
+        };
+        assert_eq!(errors.len(), 3);
+        // Group `e` is not part of a cycle.
+        assert!(matches!(
+            &errors[0],
+            crate::Error::UnresolvedExtendsRef { group_id, .. } if group_id == "group.e"
+        ));
+        assert_eq!(
+            errors[1].to_string(),
+            "The `extends` clauses of the following groups form a cycle: group.a -> group.b -> group.c -> group.a.\nProvenances: [\"<str>\", \"<str>\", \"<str>\"]"
+        );
+        assert!(matches!(
+            &errors[2],
+            crate::Error::CircularExtendsRef { group_ids, .. } if group_ids == &["group.d"]
+        ));
+    }
+
+    #[test]
+    fn test_registry_multiple_extends() {
+        let registry = create_registry_from_string(
+            "
+groups:
+    - id: registry.one
+      type: attribute_group
+      brief: \"Group one\"
+      attributes:
+        - id: one.attr
+          type: string
+          brief: \"Attribute one\"
+          examples: [\"a\"]
+        - id: shared.attr
+          type: string
+          brief: \"Shared attribute\"
+          examples: [\"a\"]
+    - id: registry.two
+      type: attribute_group
+      brief: \"Group two\"
+      attributes:
+        - id: two.attr
+          type: string
+          brief: \"Attribute two\"
+          examples: [\"a\"]
+        - ref: shared.attr
+    - id: registry.three
+      type: attribute_group
+      brief: \"Group three\"
+      extends: [registry.one, registry.two]",
+        )
+        .expect("Failed to resolve the registry");
+
+        let group = &registry.groups[2];
+        assert_eq!(group.attributes.len(), 3);
+        let lineage = group.lineage.as_ref().expect("Lineage expected");
+        assert_eq!(lineage.extends(), ["registry.one", "registry.two"]);
+        let source_group = |attr_id| {
+            lineage
+                .attribute(attr_id)
+                .map(|attr| attr.source_group.as_str())
+        };
+        assert_eq!(source_group("one.attr"), Some("registry.one"));
+        assert_eq!(source_group("two.attr"), Some("registry.two"));
+        // The first extended group takes precedence.
+        assert_eq!(source_group("shared.attr"), Some("registry.one"));
+    }
+
+    #[test]
+    fn test_registry_error_conflicting_extends() {
+        let spec = |redefinition: &str| {
+            format!(
+                "
+groups:
+    - id: registry.one
+      type: attribute_group
+      brief: \"Group one\"
+      attributes:
+        - id: shared.attr
+          type: string
+          brief: \"Shared attribute\"
+          examples: [\"a\"]
+    - id: registry.two
+      type: attribute_group
+      brief: \"Group two\"
+      attributes:
+        - ref: shared.attr
+          brief: \"Another brief\"
+    - id: registry.three
+      type: attribute_group
+      brief: \"Group three\"
+      extends: [registry.one, registry.two]{}",
+                redefinition
+            )
+        };
+
+        let result = create_registry_from_string(&spec(""));
+        assert!(matches!(
+            &result,
+            Err(crate::Error::ConflictingInheritedAttribute {
+                group_id,
+                attribute_id,
+                parent_group_ids,
+                ..
+            }) if group_id == "registry.three"
+                && attribute_id == "shared.attr"
+                && parent_group_ids == &["registry.one", "registry.two"]
+        ));
+
+        // The conflict is resolved by redefining the attribute.
+        let registry = create_registry_from_string(&spec(
+            "
+      attributes:
+        - ref: shared.attr
+          brief: \"Resolved brief\"",
+        ))
+        .expect("Failed to resolve the registry");
+        assert_eq!(registry.groups[2].attributes.len(), 1);
+    }
+
     #[test]
     fn test_registry_error_unresolved_refs() {
         let result = create_registry_from_string(
@@ -1030,6 +1781,83 @@ groups:
         }
     }
 
+    #[test]
+    fn test_registry_error_suggestions() {
+        let result = create_registry_from_string(
+            "
+groups:
+    - id: registry.http
+      type: attribute_group
+      brief: 'HTTP'
+      attributes:
+        - id: http.request.method
+          type: string
+          brief: 'Method'
+          stability: stable
+          examples: ['GET']
+    - id: span.http
+      type: span
+      span_kind: client
+      stability: stable
+      brief: 'HTTP span'
+      extends: registry.htp
+    - id: span.db
+      type: span
+      span_kind: client
+      stability: stable
+      brief: 'DB span'
+      attributes:
+        - ref: http.request.methd
+        - ref: db.system",
+        );
+
+        let Err(crate::Error::CompoundError(errors)) = result else {
+            panic!("Expected a CompoundError");
+        };
+        let helps: Vec<_> = errors
+            .iter()
+            .map(|error| error.help().map(|help| help.to_string()))
+            .collect();
+        assert_eq!(
+            helps,
+            vec![Some("Did you mean `registry.http`?".to_owned())]
+        );
+
+        let result = create_registry_from_string(
+            "
+groups:
+    - id: registry.http
+      type: attribute_group
+      brief: 'HTTP'
+      attributes:
+        - id: http.request.method
+          type: string
+          brief: 'Method'
+          stability: stable
+          examples: ['GET']
+    - id: span.db
+      type: span
+      span_kind: client
+      stability: stable
+      brief: 'DB span'
+      attributes:
+        - ref: http.request.methd
+        - ref: db.system",
+        );
+
+        let Err(crate::Error::CompoundError(errors)) = result else {
+            panic!("Expected a CompoundError");
+        };
+        let helps: Vec<_> = errors
+            .iter()
+            .map(|error| error.help().map(|help| help.to_string()))
+            .collect();
+        assert_eq!(
+            helps,
+            vec![Some("Did you mean `http.request.method`?".to_owned()), None]
+        );
+    }
+
     #[test]
     fn test_registry_error_unresolved_includes() {
         let result = create_registry_from_string(
@@ -1157,6 +1985,56 @@ groups:
         Ok(())
     }
 
+    #[test]
+    fn test_validate_resolved_registry() -> Result<(), Box<dyn Error>> {
+        let registry_id = "local";
+        let mut semconv_registry = SemConvRegistry::try_from_path_pattern(
+            registry_id,
+            "data/registry-test-3-extends/registry/*.yaml",
+        )
+        .into_result_failing_non_fatal()?;
+        let mut resolved_schema =
+            SchemaResolver::resolve_semantic_convention_registry(&mut semconv_registry)?;
+        resolved_schema.validate()?;
+
+        // The `extends` clauses are resolved, the extended groups are only
+        // recorded in the lineage.
+        let groups = &mut resolved_schema
+            .registries
+            .get_mut(registry_id)
+            .expect("Registry not found")
+            .groups;
+        assert!(groups.iter().all(|group| group.extends.is_none()));
+        groups.retain(|group| group.id != "metric.messaging.attributes");
+
+        let Err(weaver_resolved_schema::error::Error::CompoundError(errors)) =
+            resolved_schema.validate()
+        else {
+            panic!("Expected validation errors");
+        };
+        assert_eq!(errors.len(), 6);
+        assert!(errors
+            .iter()
+            .all(|error| error.to_string().contains("metric.messaging.attributes")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_json_specs() {
+        // Only the `*.semconv.json` files are loaded, the other JSON files
+        // (here a `package.json`) are ignored.
+        let specs =
+            SchemaResolver::load_semconv_from_local_path("data/json-specs".into(), "test", false)
+                .into_result_failing_non_fatal()
+                .expect("Failed to load the specs");
+        let mut files: Vec<_> = specs
+            .iter()
+            .filter_map(|(path, _)| PathBuf::from(path).file_name().map(|f| f.to_owned()))
+            .collect();
+        files.sort();
+        assert_eq!(files, ["client.yaml", "registry.semconv.json"]);
+    }
+
     fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
         serde_json::to_string_pretty(value).unwrap()
     }
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! "Did you mean" suggestions for the unresolved references, based on the
+//! edit distance between the unresolved id and the known ids.
+
+/// The maximum number of suggestions.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns a help text suggesting up to three known ids close to the given
+/// unknown id, or `None` if no known id is close enough.
+///
+/// A known id is close enough if its edit distance to the unknown id is at
+/// most a third of the length of the unknown id (and at least 2).
+pub(crate) fn did_you_mean<'a>(
+    unknown: &str,
+    known_ids: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let max_distance = (unknown.chars().count() / 3).max(2);
+    let mut candidates: Vec<(usize, &str)> = known_ids
+        .into_iter()
+        .filter(|id| *id != unknown)
+        .map(|id| (edit_distance(unknown, id), id))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    let suggestions: Vec<String> = candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, id)| format!("`{}`", id))
+        .collect();
+    match suggestions.as_slice() {
+        [] => None,
+        [single] => Some(format!("Did you mean {}?", single)),
+        [init @ .., last] => Some(format!("Did you mean {} or {}?", init.join(", "), last)),
+    }
+}
+
+/// Returns the Levenshtein distance between two strings (the number of
+/// inserted, deleted, or substituted characters).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("http", ""), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("server.port", "server.prot"), 2);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let known_ids = [
+            "server.address",
+            "server.port",
+            "client.port",
+            "client.address",
+            "network.peer.port",
+        ];
+        assert_eq!(
+            did_you_mean("server.prot", known_ids),
+            Some("Did you mean `server.port`?".to_owned())
+        );
+        assert_eq!(
+            did_you_mean("client.adress", known_ids),
+            Some("Did you mean `client.address`?".to_owned())
+        );
+        assert_eq!(did_you_mean("db.system", known_ids), None);
+        assert_eq!(
+            did_you_mean("port", ["por", "pot", "prt", "ort"]),
+            Some("Did you mean `ort`, `por` or `pot`?".to_owned())
+        );
+    }
+}
@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of the definitions of a registry that are never used.
+//!
+//! An attribute is used when it is referenced by a `ref` or an `any_of`
+//! constraint, or when its group is extended or included by another group.
+//! A group that only exists to be referenced (i.e. an `attribute_group` or a
+//! `metric_group`) is used when it is extended or included by another group,
+//! or when one of its attributes is used.
+
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+use weaver_semconv::attribute::AttributeSpec;
+use weaver_semconv::group::{ExtendsSpec, GroupType};
+use weaver_semconv::registry::SemConvRegistry;
+
+use crate::registry::{resolve_prefix_on_attributes, unresolved_registry_from_specs};
+use crate::Error;
+
+/// The reason why a definition is reported as unused.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnusedReason {
+    /// The attribute is never referenced and its group is never extended or
+    /// included.
+    NeverReferenced,
+    /// The group is never extended or included, and none of its attributes
+    /// is referenced.
+    Unreachable,
+}
+
+impl Display for UnusedReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnusedReason::NeverReferenced => write!(
+                f,
+                "never referenced, and its group is never extended or included"
+            ),
+            UnusedReason::Unreachable => write!(
+                f,
+                "never extended or included, and none of its attributes is referenced"
+            ),
+        }
+    }
+}
+
+/// A definition that is never used by the other definitions of the registry.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UnusedItem {
+    /// An unused group.
+    Group {
+        /// The id of the group.
+        id: String,
+        /// The provenance of the group (URL or path).
+        provenance: String,
+        /// The reason why the group is unused.
+        reason: UnusedReason,
+    },
+    /// An unused attribute.
+    Attribute {
+        /// The id of the attribute.
+        id: String,
+        /// The id of the group defining the attribute.
+        group_id: String,
+        /// The provenance of the group defining the attribute (URL or path).
+        provenance: String,
+        /// The reason why the attribute is unused.
+        reason: UnusedReason,
+    },
+}
+
+/// The list of the unused definitions of a registry.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct UnusedReport {
+    /// The unused groups and attributes, in the order of their definition.
+    pub items: Vec<UnusedItem>,
+}
+
+impl Display for UnusedReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for item in self.items.iter() {
+            match item {
+                UnusedItem::Group {
+                    id,
+                    provenance,
+                    reason,
+                } => writeln!(f, "Unused group `{}` ({}): {}", id, provenance, reason)?,
+                UnusedItem::Attribute {
+                    id,
+                    group_id,
+                    provenance,
+                    reason,
+                } => writeln!(
+                    f,
+                    "Unused attribute `{}` in group `{}` ({}): {}",
+                    id, group_id, provenance, reason
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl UnusedReport {
+    /// Returns the unused groups and attributes of the given registry.
+    ///
+    /// The attributes of an unused group are not reported individually, and
+    /// the deprecated attributes are never reported as they are kept on
+    /// purpose.
+    pub fn try_from_registry(registry: &SemConvRegistry) -> Result<Self, Error> {
+        let mut ureg = unresolved_registry_from_specs("", registry);
+        resolve_prefix_on_attributes(&mut ureg)?;
+
+        let mut used_groups = HashSet::new();
+        let mut used_attributes = HashSet::new();
+        for unresolved_group in ureg.groups.iter() {
+            used_groups.extend(
+                unresolved_group
+                    .group
+                    .extends
+                    .iter()
+                    .flat_map(ExtendsSpec::group_ids),
+            );
+            for constraint in unresolved_group.group.constraints.iter() {
+                used_groups.extend(constraint.include.iter());
+                used_attributes.extend(constraint.any_of.iter());
+            }
+            for attr in unresolved_group.attributes.iter() {
+                if let AttributeSpec::Ref { r#ref, .. } = &attr.spec {
+                    _ = used_attributes.insert(r#ref);
+                }
+            }
+        }
+
+        let mut items = vec![];
+        for unresolved_group in ureg.groups.iter() {
+            let group = &unresolved_group.group;
+            if !matches!(
+                group.r#type,
+                GroupType::AttributeGroup | GroupType::MetricGroup
+            ) || used_groups.contains(&group.id)
+            {
+                continue;
+            }
+
+            let definitions: Vec<_> = unresolved_group
+                .attributes
+                .iter()
+                .filter_map(|attr| match &attr.spec {
+                    AttributeSpec::Id { id, deprecated, .. } => Some((id, deprecated.is_some())),
+                    AttributeSpec::Ref { .. } => None,
+                })
+                .collect();
+            let used_count = definitions
+                .iter()
+                .filter(|(id, _)| used_attributes.contains(id))
+                .count();
+
+            if used_count == 0 && definitions.iter().all(|(_, deprecated)| !deprecated) {
+                items.push(UnusedItem::Group {
+                    id: group.id.clone(),
+                    provenance: unresolved_group.provenance.clone(),
+                    reason: UnusedReason::Unreachable,
+                });
+                continue;
+            }
+            items.extend(
+                definitions
+                    .into_iter()
+                    .filter(|(id, deprecated)| !deprecated && !used_attributes.contains(id))
+                    .map(|(id, _)| UnusedItem::Attribute {
+                        id: id.clone(),
+                        group_id: group.id.clone(),
+                        provenance: unresolved_group.provenance.clone(),
+                        reason: UnusedReason::NeverReferenced,
+                    }),
+            );
+        }
+
+        Ok(Self { items })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_report() {
+        let mut registry = SemConvRegistry::new("test");
+        registry
+            .add_semconv_spec_from_string(
+                "<str>",
+                "
+groups:
+    - id: registry.used
+      type: attribute_group
+      brief: \"Used group\"
+      attributes:
+        - id: used.referenced
+          type: string
+          brief: \"Referenced attribute\"
+          examples: [\"a\"]
+        - id: used.unreferenced
+          type: string
+          brief: \"Unreferenced attribute\"
+          examples: [\"a\"]
+        - id: used.old
+          type: string
+          brief: \"Deprecated attribute\"
+          examples: [\"a\"]
+          deprecated: \"Replaced by `used.referenced`.\"
+    - id: registry.unused
+      type: attribute_group
+      brief: \"Unused group\"
+      attributes:
+        - id: unused.attr
+          type: string
+          brief: \"Unused attribute\"
+          examples: [\"a\"]
+    - id: attributes.common
+      type: attribute_group
+      brief: \"Extended group\"
+      attributes:
+        - id: common.attr
+          type: string
+          brief: \"Inherited attribute\"
+          examples: [\"a\"]
+    - id: span.one
+      type: span
+      span_kind: client
+      stability: stable
+      brief: \"Span one\"
+      extends: attributes.common
+      attributes:
+        - ref: used.referenced
+",
+            )
+            .into_result_failing_non_fatal()
+            .expect("Failed to load the registry");
+
+        let report = UnusedReport::try_from_registry(&registry).unwrap();
+        assert_eq!(
+            report.items,
+            vec![
+                UnusedItem::Attribute {
+                    id: "used.unreferenced".to_owned(),
+                    group_id: "registry.used".to_owned(),
+                    provenance: "<str>".to_owned(),
+                    reason: UnusedReason::NeverReferenced,
+                },
+                UnusedItem::Group {
+                    id: "registry.unused".to_owned(),
+                    provenance: "<str>".to_owned(),
+                    reason: UnusedReason::Unreachable,
+                },
+            ]
+        );
+        assert_eq!(
+            report.to_string(),
+            "Unused attribute `used.unreferenced` in group `registry.used` (<str>): never referenced, and its group is never extended or included\n\
+            Unused group `registry.unused` (<str>): never extended or included, and none of its attributes is referenced\n"
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["items"][1]["kind"], "group");
+        assert_eq!(json["items"][1]["reason"], "unreachable");
+    }
+}
@@ -9,7 +9,10 @@ use std::fmt::{Display, Formatter};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::attribute::{BasicRequirementLevelSpec, EnumEntriesSpec, Examples, RequirementLevel};
+use crate::attribute::{
+    map_example_errors, BasicRequirementLevelSpec, EnumEntriesSpec, Examples, RequirementLevel,
+    StructuredValue,
+};
 use crate::stability::Stability;
 
 /// The AnyValueTypeSpec is a specification of a value that can be of any type.
@@ -239,12 +242,88 @@ impl AnyValueSpec {
         let AnyValueCommonSpec { note, .. } = self.common();
         note.clone()
     }
+
+    /// Returns the fields of a `map` or `map[]` any value (empty for the
+    /// other types).
+    #[must_use]
+    pub fn fields(&self) -> &[AnyValueSpec] {
+        match self {
+            AnyValueSpec::Map { fields, .. } | AnyValueSpec::Maps { fields, .. } => fields,
+            _ => &[],
+        }
+    }
+
+    /// Returns the mismatches between a structured example value and the
+    /// structure of this any value. The path locates the value in the
+    /// example.
+    pub(crate) fn example_errors(&self, value: &StructuredValue, path: &str) -> Vec<String> {
+        let is_number =
+            |v: &StructuredValue| matches!(v, StructuredValue::Int(_) | StructuredValue::Double(_));
+        let valid = match (self, value) {
+            (AnyValueSpec::Map { fields, .. }, StructuredValue::Map(map)) => {
+                return map_example_errors(fields, map, path);
+            }
+            (AnyValueSpec::Maps { fields, .. }, StructuredValue::Array(values)) => {
+                let mut errors = vec![];
+                for (index, value) in values.iter().enumerate() {
+                    let path = format!("{}[{}]", path, index);
+                    match value {
+                        StructuredValue::Map(map) => {
+                            errors.extend(map_example_errors(fields, map, &path));
+                        }
+                        value => errors.push(format!(
+                            "The value `{}` is a {} but a map is expected",
+                            path,
+                            value.type_name()
+                        )),
+                    }
+                }
+                return errors;
+            }
+            // Enum, bytes and undefined values are open.
+            (
+                AnyValueSpec::Enum { .. }
+                | AnyValueSpec::Bytes { .. }
+                | AnyValueSpec::Undefined { .. },
+                _,
+            ) => true,
+            (AnyValueSpec::Boolean { .. }, StructuredValue::Bool(_))
+            | (AnyValueSpec::Int { .. }, StructuredValue::Int(_))
+            | (AnyValueSpec::String { .. }, StructuredValue::String(_)) => true,
+            (AnyValueSpec::Double { .. }, value) => is_number(value),
+            (AnyValueSpec::Booleans { .. }, StructuredValue::Array(values)) => {
+                values.iter().all(|v| matches!(v, StructuredValue::Bool(_)))
+            }
+            (AnyValueSpec::Ints { .. }, StructuredValue::Array(values)) => {
+                values.iter().all(|v| matches!(v, StructuredValue::Int(_)))
+            }
+            (AnyValueSpec::Doubles { .. }, StructuredValue::Array(values)) => {
+                values.iter().all(is_number)
+            }
+            (AnyValueSpec::Strings { .. }, StructuredValue::Array(values)) => values
+                .iter()
+                .all(|v| matches!(v, StructuredValue::String(_))),
+            _ => false,
+        };
+        if valid {
+            vec![]
+        } else {
+            vec![format!(
+                "The value `{}` is a {} but the field is of type `{}`",
+                path,
+                value.type_name(),
+                self
+            )]
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs;
 
+    use weaver_common::result::WResult;
+
     use crate::attribute::ValueSpec;
 
     use super::*;
@@ -824,4 +903,65 @@ mod tests {
             "enum {entry1}"
         );
     }
+
+    #[test]
+    fn test_structured_examples() {
+        let body: AnyValueSpec = serde_yaml::from_str(
+            r#"
+id: body
+type: map
+requirement_level: required
+examples:
+  - name: checkout
+    attempts: 2
+    items: [{sku: A-42, price: 9.99}]
+  - name: refund
+    attempts: "2"
+    extra: true
+    items: [{price: 1}]
+fields:
+  - id: name
+    type: string
+    requirement_level: required
+    examples: [checkout]
+  - id: attempts
+    type: int
+    requirement_level: recommended
+  - id: items
+    type: map[]
+    requirement_level: recommended
+    fields:
+      - id: sku
+        type: string
+        requirement_level: required
+        examples: [A-42]
+      - id: price
+        type: double
+        requirement_level: recommended
+"#,
+        )
+        .expect("Failed to parse the body");
+        let Some(examples @ Examples::ListOfMaps(_)) = &body.common().examples else {
+            panic!("Expected structured examples");
+        };
+        let WResult::OkWithNFEs(_, errors) = examples.validate_any_value(&body, "event", "url")
+        else {
+            panic!("Expected errors in the second example");
+        };
+        let errors: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                crate::Error::InvalidAnyValueExampleError { error, .. } => error.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "The value `body.attempts` is a string but the field is of type `int`",
+                "The field `body.extra` is not declared",
+                "The required field `body.items[0].sku` is missing",
+            ]
+        );
+    }
 }
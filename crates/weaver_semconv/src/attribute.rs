@@ -10,13 +10,14 @@ use crate::Error;
 use ordered_float::OrderedFloat;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::ops::Not;
 use weaver_common::result::WResult;
 use AttributeType::{Enum, PrimitiveOrArray, Template};
 
 /// An attribute specification.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 #[serde(deny_unknown_fields)]
 #[serde(untagged)]
 #[serde(rename_all = "snake_case")]
@@ -128,6 +129,15 @@ pub enum AttributeSpec {
         /// to use instead. See also stability.
         #[serde(skip_serializing_if = "Option::is_none")]
         deprecated: Option<String>,
+        /// Validation constraints on the values of the attribute (pattern,
+        /// range, maximum array length).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value_constraints: Option<ValueConstraintsSpec>,
+        /// Other names of the attribute (e.g. legacy or vendor-specific
+        /// names), used to map the attributes of incoming telemetry to this
+        /// attribute.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        aliases: Option<Vec<String>>,
     },
 }
 
@@ -395,6 +405,286 @@ impl From<&str> for ValueSpec {
     }
 }
 
+/// Validation constraints on the values of an attribute.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq, Hash, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValueConstraintsSpec {
+    /// Regular expression that the whole string values (or the items of the
+    /// string arrays) must match. Only valid for string attributes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Minimum value (inclusive) of the numbers (or the items of the number
+    /// arrays). Only valid for int and double attributes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<OrderedFloat<f64>>,
+    /// Maximum value (inclusive) of the numbers (or the items of the number
+    /// arrays). Only valid for int and double attributes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<OrderedFloat<f64>>,
+    /// Maximum number of items of the arrays. Only valid for array
+    /// attributes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+}
+
+/// The kind of the values (or of the array items) of an attribute type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueKind {
+    Boolean,
+    Number,
+    String,
+}
+
+/// Returns the kind of the values of an attribute type and whether the
+/// values are arrays, or None for enums.
+fn value_kind(attr_type: &AttributeType) -> Option<(ValueKind, bool)> {
+    match attr_type {
+        PrimitiveOrArray(t) => Some(match t {
+            PrimitiveOrArrayTypeSpec::Boolean => (ValueKind::Boolean, false),
+            PrimitiveOrArrayTypeSpec::Int | PrimitiveOrArrayTypeSpec::Double => {
+                (ValueKind::Number, false)
+            }
+            PrimitiveOrArrayTypeSpec::String => (ValueKind::String, false),
+            PrimitiveOrArrayTypeSpec::Booleans => (ValueKind::Boolean, true),
+            PrimitiveOrArrayTypeSpec::Ints | PrimitiveOrArrayTypeSpec::Doubles => {
+                (ValueKind::Number, true)
+            }
+            PrimitiveOrArrayTypeSpec::Strings => (ValueKind::String, true),
+        }),
+        Template(t) => Some(match t {
+            TemplateTypeSpec::Boolean => (ValueKind::Boolean, false),
+            TemplateTypeSpec::Int | TemplateTypeSpec::Double => (ValueKind::Number, false),
+            TemplateTypeSpec::String => (ValueKind::String, false),
+            TemplateTypeSpec::Booleans => (ValueKind::Boolean, true),
+            TemplateTypeSpec::Ints | TemplateTypeSpec::Doubles => (ValueKind::Number, true),
+            TemplateTypeSpec::Strings => (ValueKind::String, true),
+        }),
+        Enum { .. } => None,
+    }
+}
+
+impl ValueConstraintsSpec {
+    /// Compiles the `pattern` constraint into a regex matching the whole
+    /// values, or returns None if there is no pattern. The regex is compiled
+    /// once and reused to check all the values.
+    pub fn pattern_regex(&self) -> Result<Option<regex::Regex>, String> {
+        self.pattern
+            .as_ref()
+            .map(|pattern| {
+                regex::Regex::new(&format!("^(?:{})$", pattern))
+                    .map_err(|e| format!("The pattern `{}` is invalid: {}", pattern, e))
+            })
+            .transpose()
+    }
+
+    /// Checks that a string value (or a string array item) matches the
+    /// pattern compiled by [`ValueConstraintsSpec::pattern_regex`].
+    pub fn check_string(&self, regex: &regex::Regex, value: &str) -> Result<(), String> {
+        if regex.is_match(value) {
+            Ok(())
+        } else {
+            Err(format!(
+                "The value `{}` doesn't match the pattern `{}`.",
+                value,
+                self.pattern.as_deref().unwrap_or_default()
+            ))
+        }
+    }
+
+    /// Checks that a number (or a number array item) is in the range.
+    pub fn check_number(&self, value: f64) -> Result<(), String> {
+        if self.min.is_some_and(|min| value < min.0) || self.max.is_some_and(|max| value > max.0) {
+            Err(format!(
+                "The value `{}` is out of the range [{}, {}].",
+                value,
+                self.min.map_or("-inf".to_owned(), |min| min.to_string()),
+                self.max.map_or("+inf".to_owned(), |max| max.to_string())
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that an array doesn't exceed the maximum length.
+    pub fn check_length(&self, length: usize) -> Result<(), String> {
+        match self.max_length {
+            Some(max_length) if length > max_length => Err(format!(
+                "The array has {} items but the maximum length is {}.",
+                length, max_length
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the violations of the constraints by the definition of an
+    /// attribute: constraints not applicable to the attribute type, invalid
+    /// pattern, empty range, and examples not satisfying the constraints.
+    pub(crate) fn validate(
+        &self,
+        attr_type: &AttributeType,
+        examples: Option<&Examples>,
+    ) -> Vec<String> {
+        let mut errors = vec![];
+        let kind = value_kind(attr_type);
+        let is_kind = |expected: ValueKind| kind.is_some_and(|(kind, _)| kind == expected);
+        if self.pattern.is_some() && !is_kind(ValueKind::String) {
+            errors.push(format!(
+                "The `pattern` constraint is only valid for string attributes, not `{}`.",
+                attr_type
+            ));
+        }
+        if (self.min.is_some() || self.max.is_some()) && !is_kind(ValueKind::Number) {
+            errors.push(format!(
+                "The `min` and `max` constraints are only valid for int and double attributes, not `{}`.",
+                attr_type
+            ));
+        }
+        if self.max_length.is_some() && !kind.is_some_and(|(_, is_array)| is_array) {
+            errors.push(format!(
+                "The `max_length` constraint is only valid for array attributes, not `{}`.",
+                attr_type
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                errors.push(format!("The range [{}, {}] is empty.", min, max));
+            }
+        }
+        let regex = match self.pattern_regex() {
+            Ok(regex) => regex,
+            Err(e) => {
+                errors.push(e);
+                return errors;
+            }
+        };
+        if !errors.is_empty() {
+            return errors;
+        }
+
+        // Check the examples: the length of the arrays, and the numbers and
+        // strings of the values or of the array items.
+        let is_array = kind.is_some_and(|(_, is_array)| is_array);
+        let mut lengths = vec![];
+        let mut numbers = vec![];
+        let mut strings = vec![];
+        match examples {
+            Some(Examples::Int(value)) => numbers.push(*value as f64),
+            Some(Examples::Double(value)) => numbers.push(value.0),
+            Some(Examples::String(value)) => strings.push(value.as_str()),
+            Some(Examples::Ints(values)) => {
+                if is_array {
+                    lengths.push(values.len());
+                }
+                numbers.extend(values.iter().map(|v| *v as f64));
+            }
+            Some(Examples::Doubles(values)) => {
+                if is_array {
+                    lengths.push(values.len());
+                }
+                numbers.extend(values.iter().map(|v| v.0));
+            }
+            Some(Examples::Bools(values)) => {
+                if is_array {
+                    lengths.push(values.len());
+                }
+            }
+            Some(Examples::Strings(values)) => {
+                if is_array {
+                    lengths.push(values.len());
+                }
+                strings.extend(values.iter().map(String::as_str));
+            }
+            Some(Examples::ListOfInts(arrays)) => {
+                lengths.extend(arrays.iter().map(Vec::len));
+                numbers.extend(arrays.iter().flatten().map(|v| *v as f64));
+            }
+            Some(Examples::ListOfDoubles(arrays)) => {
+                lengths.extend(arrays.iter().map(Vec::len));
+                numbers.extend(arrays.iter().flatten().map(|v| v.0));
+            }
+            Some(Examples::ListOfBools(arrays)) => lengths.extend(arrays.iter().map(Vec::len)),
+            Some(Examples::ListOfStrings(arrays)) => {
+                lengths.extend(arrays.iter().map(Vec::len));
+                strings.extend(arrays.iter().flatten().map(String::as_str));
+            }
+            Some(Examples::Bool(_) | Examples::Map(_) | Examples::ListOfMaps(_)) | None => {}
+        }
+        let results =
+            lengths
+                .into_iter()
+                .map(|length| self.check_length(length))
+                .chain(numbers.into_iter().map(|value| self.check_number(value)))
+                .chain(strings.into_iter().filter_map(|value| {
+                    regex.as_ref().map(|regex| self.check_string(regex, value))
+                }));
+        for result in results {
+            if let Err(e) = result {
+                errors.push(format!("Invalid example: {}", e));
+            }
+        }
+        errors
+    }
+}
+
+/// A structured example value, i.e. a primitive value, an array or a map of
+/// structured values.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash, JsonSchema)]
+#[serde(untagged)]
+pub enum StructuredValue {
+    /// A boolean value.
+    Bool(bool),
+    /// An integer value.
+    Int(i64),
+    /// A double value.
+    Double(OrderedFloat<f64>),
+    /// A string value.
+    String(String),
+    /// An array of values.
+    Array(Vec<StructuredValue>),
+    /// A map of values.
+    Map(BTreeMap<String, StructuredValue>),
+}
+
+impl StructuredValue {
+    /// Returns the name of the type of the value.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            StructuredValue::Bool(_) => "boolean",
+            StructuredValue::Int(_) => "int",
+            StructuredValue::Double(_) => "double",
+            StructuredValue::String(_) => "string",
+            StructuredValue::Array(_) => "array",
+            StructuredValue::Map(_) => "map",
+        }
+    }
+
+    /// Returns true if the value is of the given template type.
+    fn is_of_template_type(&self, template_type: &TemplateTypeSpec) -> bool {
+        let all =
+            |values: &[StructuredValue], f: fn(&StructuredValue) -> bool| values.iter().all(f);
+        match (self, template_type) {
+            (StructuredValue::Bool(_), TemplateTypeSpec::Boolean)
+            | (StructuredValue::Int(_), TemplateTypeSpec::Int)
+            | (StructuredValue::Int(_) | StructuredValue::Double(_), TemplateTypeSpec::Double)
+            | (StructuredValue::String(_), TemplateTypeSpec::String) => true,
+            (StructuredValue::Array(values), TemplateTypeSpec::Booleans) => {
+                all(values, |v| matches!(v, StructuredValue::Bool(_)))
+            }
+            (StructuredValue::Array(values), TemplateTypeSpec::Ints) => {
+                all(values, |v| matches!(v, StructuredValue::Int(_)))
+            }
+            (StructuredValue::Array(values), TemplateTypeSpec::Doubles) => all(values, |v| {
+                matches!(v, StructuredValue::Int(_) | StructuredValue::Double(_))
+            }),
+            (StructuredValue::Array(values), TemplateTypeSpec::Strings) => {
+                all(values, |v| matches!(v, StructuredValue::String(_)))
+            }
+            _ => false,
+        }
+    }
+}
+
 /// The different types of examples.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -424,6 +714,12 @@ pub enum Examples {
     ListOfBools(Vec<Vec<bool>>),
     /// List of arrays of strings example.
     ListOfStrings(Vec<Vec<String>>),
+    /// A structured map example, e.g. the body of an event or the keys and
+    /// values of a template attribute.
+    Map(BTreeMap<String, StructuredValue>),
+    /// List of structured map examples (or a single example of an array of
+    /// maps).
+    ListOfMaps(Vec<BTreeMap<String, StructuredValue>>),
 }
 
 impl Examples {
@@ -435,57 +731,104 @@ impl Examples {
         attr_id: &str,
         path_or_url: &str,
     ) -> WResult<(), Error> {
-        match (self, attr_type) {
-            (Examples::Bool(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Boolean))
-            | (Examples::Int(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Int))
-            | (Examples::Double(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Double))
-            | (Examples::String(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::String))
-            | (Examples::Ints(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Int))
-            | (Examples::Doubles(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Double))
-            | (Examples::Bools(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Boolean))
-            | (Examples::Strings(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::String))
-            | (Examples::ListOfInts(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Ints))
-            | (Examples::ListOfDoubles(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Doubles))
-            | (Examples::ListOfBools(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Booleans))
-            | (Examples::ListOfStrings(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Strings)) => {
-                WResult::Ok(())
-            }
-            (_, Enum { .. }) => {
-                // enum types are open so it's not possible to validate the examples
-                WResult::Ok(())
-            }
+        let (type_check, expected_type) = match attr_type {
+            PrimitiveOrArray(value_type) => (self.check_type(value_type), attr_type.to_string()),
+            Template(template_type) => match self {
+                // A structured example of a template attribute maps the keys
+                // of the template to values of the template type.
+                Examples::Map(map) => {
+                    let errors = template_example_errors(map, template_type);
+                    return invalid_examples(errors, group_id, attr_id, path_or_url);
+                }
+                Examples::ListOfMaps(maps) => {
+                    let errors = maps
+                        .iter()
+                        .flat_map(|map| template_example_errors(map, template_type))
+                        .collect();
+                    return invalid_examples(errors, group_id, attr_id, path_or_url);
+                }
+                Examples::String(_) | Examples::Strings(_)
+                    if matches!(
+                        template_type,
+                        TemplateTypeSpec::String | TemplateTypeSpec::Strings
+                    ) =>
+                {
+                    (TypeCheck::Valid, attr_type.to_string())
+                }
+                _ => (
+                    self.check_type(&template_value_type(template_type)),
+                    attr_type.to_string(),
+                ),
+            },
+            // Enum types are open, so the examples are not required to be
+            // members, but they must be of the type of the member values.
+            // These mismatches are only reported as warnings (errors with the
+            // `--future` flag).
+            Enum { members, .. } => match enum_value_type(members) {
+                Some(value_type) => {
+                    let type_check = match self.check_type(&value_type) {
+                        TypeCheck::Invalid => TypeCheck::Legacy,
+                        type_check => type_check,
+                    };
+                    (type_check, value_type.to_string())
+                }
+                None => (TypeCheck::Valid, attr_type.to_string()),
+            },
+        };
+
+        match type_check {
+            TypeCheck::Valid => WResult::Ok(()),
             // Only if future mode is disabled, we allow to have examples following
             // the conventions used in semconv 1.27.0 and earlier.
-            (Examples::Ints(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Ints))
-            | (Examples::Doubles(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Doubles))
-            | (Examples::Bools(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Booleans))
-            | (Examples::Strings(_), PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Strings)) => {
-                WResult::OkWithNFEs(
-                    (),
-                    vec![Error::InvalidExampleWarning {
-                        path_or_url: path_or_url.to_owned(),
-                        group_id: group_id.to_owned(),
-                        attribute_id: attr_id.to_owned(),
-                        error: format!("All examples SHOULD be of type `{}`", attr_type),
-                    }],
-                )
-            }
-            (Examples::String(_), Template(TemplateTypeSpec::String))
-            | (Examples::Strings(_), Template(TemplateTypeSpec::String))
-            | (Examples::String(_), Template(TemplateTypeSpec::Strings))
-            | (Examples::Strings(_), Template(TemplateTypeSpec::Strings)) => WResult::Ok(()),
-            _ => WResult::OkWithNFEs(
+            TypeCheck::Legacy => WResult::with_non_fatal_errors(
+                (),
+                vec![Error::InvalidExampleWarning {
+                    path_or_url: path_or_url.to_owned(),
+                    group_id: group_id.to_owned(),
+                    attribute_id: attr_id.to_owned(),
+                    error: format!("All examples SHOULD be of type `{}`", expected_type),
+                }],
+            ),
+            TypeCheck::Invalid => WResult::OkWithNFEs(
                 (),
                 vec![Error::InvalidExampleError {
                     path_or_url: path_or_url.to_owned(),
                     group_id: group_id.to_owned(),
                     attribute_id: attr_id.to_owned(),
-                    error: format!("All examples MUST be of type `{}`", attr_type),
+                    error: format!("All examples MUST be of type `{}`", expected_type),
                 }],
             ),
         }
     }
 
+    /// Checks the type of the examples against the type of a value.
+    fn check_type(&self, value_type: &PrimitiveOrArrayTypeSpec) -> TypeCheck {
+        match (self, value_type) {
+            (Examples::Bool(_), PrimitiveOrArrayTypeSpec::Boolean)
+            | (Examples::Int(_), PrimitiveOrArrayTypeSpec::Int)
+            | (Examples::Double(_), PrimitiveOrArrayTypeSpec::Double)
+            | (Examples::String(_), PrimitiveOrArrayTypeSpec::String)
+            | (Examples::Ints(_), PrimitiveOrArrayTypeSpec::Int)
+            | (Examples::Doubles(_), PrimitiveOrArrayTypeSpec::Double)
+            | (Examples::Bools(_), PrimitiveOrArrayTypeSpec::Boolean)
+            | (Examples::Strings(_), PrimitiveOrArrayTypeSpec::String)
+            | (Examples::ListOfInts(_), PrimitiveOrArrayTypeSpec::Ints)
+            | (Examples::ListOfDoubles(_), PrimitiveOrArrayTypeSpec::Doubles)
+            | (Examples::ListOfBools(_), PrimitiveOrArrayTypeSpec::Booleans)
+            | (Examples::ListOfStrings(_), PrimitiveOrArrayTypeSpec::Strings) => TypeCheck::Valid,
+            // An integer is also a valid double.
+            (Examples::Int(_) | Examples::Ints(_), PrimitiveOrArrayTypeSpec::Double)
+            | (Examples::ListOfInts(_), PrimitiveOrArrayTypeSpec::Doubles) => TypeCheck::Valid,
+            // A single array (instead of a list of arrays) follows the
+            // conventions used in semconv 1.27.0 and earlier.
+            (Examples::Ints(_), PrimitiveOrArrayTypeSpec::Ints)
+            | (Examples::Ints(_) | Examples::Doubles(_), PrimitiveOrArrayTypeSpec::Doubles)
+            | (Examples::Bools(_), PrimitiveOrArrayTypeSpec::Booleans)
+            | (Examples::Strings(_), PrimitiveOrArrayTypeSpec::Strings) => TypeCheck::Legacy,
+            _ => TypeCheck::Invalid,
+        }
+    }
+
     /// Validation logic for the any_value.
     pub(crate) fn validate_any_value(
         &self,
@@ -494,6 +837,19 @@ impl Examples {
         path_or_url: &str,
     ) -> WResult<(), Error> {
         match (self, any_value) {
+            // Structured examples are validated against the declared structure.
+            (Examples::Map(map), AnyValueSpec::Map { fields, .. }) => {
+                let errors = map_example_errors(fields, map, &any_value.id());
+                invalid_any_value_examples(errors, any_value, group_id, path_or_url)
+            }
+            (Examples::ListOfMaps(maps), AnyValueSpec::Map { fields, .. })
+            | (Examples::ListOfMaps(maps), AnyValueSpec::Maps { fields, .. }) => {
+                let errors = maps
+                    .iter()
+                    .flat_map(|map| map_example_errors(fields, map, &any_value.id()))
+                    .collect();
+                invalid_any_value_examples(errors, any_value, group_id, path_or_url)
+            }
             (Examples::Bool(_), AnyValueSpec::Boolean { .. })
             | (Examples::Int(_), AnyValueSpec::Int { .. })
             | (Examples::Double(_), AnyValueSpec::Double { .. })
@@ -557,6 +913,146 @@ impl Examples {
     }
 }
 
+/// Returns the values of a structured template example that are not of the
+/// template type.
+fn template_example_errors(
+    map: &BTreeMap<String, StructuredValue>,
+    template_type: &TemplateTypeSpec,
+) -> Vec<String> {
+    map.iter()
+        .filter(|(_, value)| !value.is_of_template_type(template_type))
+        .map(|(key, value)| {
+            format!(
+                "The value of the key `{}` is a {} but the template type is `{}`",
+                key,
+                value.type_name(),
+                template_type
+            )
+        })
+        .collect()
+}
+
+/// Returns the mismatches between a structured map example and the fields of
+/// a map any value. The path locates the map in the example.
+pub(crate) fn map_example_errors(
+    fields: &[AnyValueSpec],
+    map: &BTreeMap<String, StructuredValue>,
+    path: &str,
+) -> Vec<String> {
+    let mut errors = vec![];
+    for (key, value) in map {
+        match fields.iter().find(|field| field.id() == *key) {
+            Some(field) => errors.extend(field.example_errors(value, &format!("{}.{}", path, key))),
+            None => errors.push(format!("The field `{}.{}` is not declared", path, key)),
+        }
+    }
+    for field in fields.iter().filter(|field| field.is_required()) {
+        if !map.contains_key(&field.id()) {
+            errors.push(format!(
+                "The required field `{}.{}` is missing",
+                path,
+                field.id()
+            ));
+        }
+    }
+    errors
+}
+
+/// Converts the errors of structured examples of an attribute into a result.
+fn invalid_examples(
+    errors: Vec<String>,
+    group_id: &str,
+    attr_id: &str,
+    path_or_url: &str,
+) -> WResult<(), Error> {
+    if errors.is_empty() {
+        return WResult::Ok(());
+    }
+    WResult::OkWithNFEs(
+        (),
+        errors
+            .into_iter()
+            .map(|error| Error::InvalidExampleError {
+                path_or_url: path_or_url.to_owned(),
+                group_id: group_id.to_owned(),
+                attribute_id: attr_id.to_owned(),
+                error,
+            })
+            .collect(),
+    )
+}
+
+/// The result of the type check of examples.
+enum TypeCheck {
+    /// The examples are of the expected type.
+    Valid,
+    /// The examples follow a convention that will be considered invalid in
+    /// the future.
+    Legacy,
+    /// The examples are not of the expected type.
+    Invalid,
+}
+
+/// Returns the type of the values of a template type.
+fn template_value_type(template_type: &TemplateTypeSpec) -> PrimitiveOrArrayTypeSpec {
+    match template_type {
+        TemplateTypeSpec::Boolean => PrimitiveOrArrayTypeSpec::Boolean,
+        TemplateTypeSpec::Int => PrimitiveOrArrayTypeSpec::Int,
+        TemplateTypeSpec::Double => PrimitiveOrArrayTypeSpec::Double,
+        TemplateTypeSpec::String => PrimitiveOrArrayTypeSpec::String,
+        TemplateTypeSpec::Strings => PrimitiveOrArrayTypeSpec::Strings,
+        TemplateTypeSpec::Ints => PrimitiveOrArrayTypeSpec::Ints,
+        TemplateTypeSpec::Doubles => PrimitiveOrArrayTypeSpec::Doubles,
+        TemplateTypeSpec::Booleans => PrimitiveOrArrayTypeSpec::Booleans,
+    }
+}
+
+/// Returns the type of the values of the members of an enum, or `None` if
+/// the members have values of different types (the integers are also
+/// doubles).
+fn enum_value_type(members: &[EnumEntriesSpec]) -> Option<PrimitiveOrArrayTypeSpec> {
+    let mut value_types = members.iter().map(|member| match member.value {
+        ValueSpec::Int(_) => PrimitiveOrArrayTypeSpec::Int,
+        ValueSpec::Double(_) => PrimitiveOrArrayTypeSpec::Double,
+        ValueSpec::String(_) => PrimitiveOrArrayTypeSpec::String,
+    });
+    let first = value_types.next()?;
+    value_types.try_fold(first, |value_type, member_type| {
+        match (value_type, member_type) {
+            (a, b) if a == b => Some(a),
+            (PrimitiveOrArrayTypeSpec::Int, PrimitiveOrArrayTypeSpec::Double)
+            | (PrimitiveOrArrayTypeSpec::Double, PrimitiveOrArrayTypeSpec::Int) => {
+                Some(PrimitiveOrArrayTypeSpec::Double)
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Converts the errors of structured examples of an any value into a result.
+fn invalid_any_value_examples(
+    errors: Vec<String>,
+    any_value: &AnyValueSpec,
+    group_id: &str,
+    path_or_url: &str,
+) -> WResult<(), Error> {
+    if errors.is_empty() {
+        return WResult::Ok(());
+    }
+    WResult::OkWithNFEs(
+        (),
+        errors
+            .into_iter()
+            .map(|error| Error::InvalidAnyValueExampleError {
+                path_or_url: path_or_url.to_owned(),
+                group_id: group_id.to_owned(),
+                value_id: any_value.id(),
+                error,
+            })
+            .collect(),
+    )
+}
+
 /// The different requirement level specifications.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -888,6 +1384,8 @@ mod tests {
             note: "note".to_owned(),
             stability: Some(Stability::Stable),
             deprecated: Some("deprecated".to_owned()),
+            value_constraints: None,
+            aliases: None,
         };
         assert_eq!(attr.id(), "id");
         assert_eq!(attr.brief(), "brief");
@@ -1236,6 +1734,174 @@ mod tests {
             .validate(&attr_double, "grp", "attr", "url")
             .into_result_failing_non_fatal()
             .is_err());
+
+        // Integers are valid doubles, but not the other way around.
+        let examples = Examples::Ints(vec![42, 43]);
+        assert!(examples
+            .validate(&attr_double, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_ok());
+        let examples = Examples::ListOfInts(vec![vec![42, 43], vec![44, 45]]);
+        assert!(examples
+            .validate(&attr_doubles, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_ok());
+        let examples = Examples::Double(OrderedFloat(42.5));
+        assert!(examples
+            .validate(&attr_ints, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_err());
+
+        // === Test template examples ===
+        let template_int = Template(TemplateTypeSpec::Int);
+        let template_doubles = Template(TemplateTypeSpec::Doubles);
+        let examples = Examples::Ints(vec![42, 43]);
+        assert!(examples
+            .validate(&template_int, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_ok());
+        let examples = Examples::ListOfInts(vec![vec![42, 43], vec![44, 45]]);
+        assert!(examples
+            .validate(&template_doubles, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_ok());
+        let examples = Examples::String("foo".to_owned());
+        assert!(examples
+            .validate(&template_int, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_err());
+
+        // === Test enum examples ===
+        let member = |id: &str, value: ValueSpec| EnumEntriesSpec {
+            id: id.to_owned(),
+            value,
+            brief: None,
+            note: None,
+            stability: None,
+            deprecated: None,
+        };
+        let string_enum = Enum {
+            allow_custom_values: None,
+            members: vec![
+                member("get", ValueSpec::String("GET".to_owned())),
+                member("post", ValueSpec::String("POST".to_owned())),
+            ],
+        };
+        let double_enum = Enum {
+            allow_custom_values: None,
+            members: vec![
+                member("one", ValueSpec::Int(1)),
+                member("half", ValueSpec::Double(OrderedFloat(0.5))),
+            ],
+        };
+        // The values of an enum are open, but not their type.
+        let examples = Examples::Strings(vec!["GET".to_owned(), "PATCH".to_owned()]);
+        assert!(examples
+            .validate(&string_enum, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_ok());
+        let examples = Examples::Ints(vec![1, 2]);
+        assert!(examples
+            .validate(&double_enum, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_ok());
+        let examples = Examples::ListOfStrings(vec![vec!["GET".to_owned(), "POST".to_owned()]]);
+        assert_eq!(
+            examples
+                .validate(&string_enum, "grp", "attr", "url")
+                .into_result_failing_non_fatal(),
+            Err(Error::InvalidExampleWarning {
+                path_or_url: "url".to_owned(),
+                group_id: "grp".to_owned(),
+                attribute_id: "attr".to_owned(),
+                error: "All examples SHOULD be of type `string`".to_owned(),
+            })
+        );
+        let examples = Examples::String("one".to_owned());
+        assert!(examples
+            .validate(&double_enum, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_err());
+    }
+
+    #[test]
+    fn test_value_constraints() {
+        let string = PrimitiveOrArray(PrimitiveOrArrayTypeSpec::String);
+        let ints = PrimitiveOrArray(PrimitiveOrArrayTypeSpec::Ints);
+        let pattern = ValueConstraintsSpec {
+            pattern: Some("[a-z]+".to_owned()),
+            ..Default::default()
+        };
+        let regex = pattern
+            .pattern_regex()
+            .expect("Invalid pattern")
+            .expect("Missing pattern");
+        assert!(pattern.check_string(&regex, "abc").is_ok());
+        assert!(pattern.check_string(&regex, "abc1").is_err());
+        assert!(pattern
+            .validate(&string, Some(&Examples::String("get".to_owned())))
+            .is_empty());
+        assert_eq!(
+            pattern
+                .validate(
+                    &string,
+                    Some(&Examples::Strings(vec!["get".to_owned(), "GET".to_owned()]))
+                )
+                .len(),
+            1
+        );
+        // Not applicable to int arrays.
+        assert_eq!(pattern.validate(&ints, None).len(), 1);
+
+        let range = ValueConstraintsSpec {
+            min: Some(OrderedFloat(0.0)),
+            max: Some(OrderedFloat(65535.0)),
+            max_length: Some(2),
+            ..Default::default()
+        };
+        assert!(range.check_number(8080.0).is_ok());
+        assert!(range.check_number(-1.0).is_err());
+        assert!(range.check_length(3).is_err());
+        assert!(range
+            .validate(&ints, Some(&Examples::Ints(vec![80, 443])))
+            .is_empty());
+        assert_eq!(
+            range
+                .validate(&ints, Some(&Examples::Ints(vec![80, 443, 70000])))
+                .len(),
+            2
+        );
+
+        let invalid = ValueConstraintsSpec {
+            pattern: Some("[".to_owned()),
+            min: Some(OrderedFloat(1.0)),
+            max: Some(OrderedFloat(0.0)),
+            ..Default::default()
+        };
+        // Range not applicable, empty range and invalid pattern.
+        assert_eq!(invalid.validate(&string, None).len(), 3);
+    }
+
+    #[test]
+    fn test_structured_template_examples() {
+        let headers = Template(TemplateTypeSpec::Strings);
+        let examples: Examples =
+            serde_yaml::from_str("{content-type: [application/json], accept: ['*/*']}")
+                .expect("Failed to parse the examples");
+        assert!(matches!(examples, Examples::Map(_)));
+        assert!(examples
+            .validate(&headers, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_ok());
+
+        let examples: Examples =
+            serde_yaml::from_str("[{content-type: [application/json]}, {content-length: 42}]")
+                .expect("Failed to parse the examples");
+        assert!(matches!(examples, Examples::ListOfMaps(_)));
+        assert!(examples
+            .validate(&headers, "grp", "attr", "url")
+            .into_result_failing_non_fatal()
+            .is_err());
     }
 }
 
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selection of the semantic convention files and groups to load.
+//!
+//! A spec filter is a set of include and exclude glob patterns. The patterns
+//! containing a `/` are matched against the paths of the semantic convention
+//! files (relative to the root of the registry, e.g. `http/**`), the other
+//! patterns are matched against the group ids (e.g. `registry.http.*`).
+//!
+//! A file is loaded if it matches one of the included paths (or if there is
+//! none) and none of the excluded paths. A group of a loaded file is kept if
+//! it matches one of the included group ids (or if there is none) and none of
+//! the excluded group ids. The files not selected are not parsed, and the
+//! groups not selected are not validated.
+
+use glob::Pattern;
+
+use crate::Error;
+
+/// Include and exclude glob patterns selecting the semantic convention files
+/// and groups to load.
+#[derive(Debug, Clone, Default)]
+pub struct SpecFilter {
+    include_paths: Vec<Pattern>,
+    exclude_paths: Vec<Pattern>,
+    include_groups: Vec<Pattern>,
+    exclude_groups: Vec<Pattern>,
+}
+
+impl SpecFilter {
+    /// Creates a spec filter from lists of include and exclude glob patterns
+    /// matched against the file paths (patterns containing a `/`) or the
+    /// group ids (other patterns).
+    pub fn try_new<S: AsRef<str>>(include: &[S], exclude: &[S]) -> Result<Self, Error> {
+        let mut filter = Self::default();
+        for (patterns, is_include) in [(include, true), (exclude, false)] {
+            for pattern in patterns {
+                let pattern = pattern.as_ref();
+                let glob = Pattern::new(pattern).map_err(|e| Error::InvalidSpecFilter {
+                    pattern: pattern.to_owned(),
+                    error: e.to_string(),
+                })?;
+                match (pattern.contains('/'), is_include) {
+                    (true, true) => filter.include_paths.push(glob),
+                    (true, false) => filter.exclude_paths.push(glob),
+                    (false, true) => filter.include_groups.push(glob),
+                    (false, false) => filter.exclude_groups.push(glob),
+                }
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Returns true if the filter selects all the files and groups.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.include_paths.is_empty()
+            && self.exclude_paths.is_empty()
+            && self.include_groups.is_empty()
+            && self.exclude_groups.is_empty()
+    }
+
+    /// Returns true if the semantic convention file with the given path
+    /// (relative to the root of the registry) must be loaded.
+    #[must_use]
+    pub fn is_path_selected(&self, path: &str) -> bool {
+        // The paths are matched with `/` separators on all platforms.
+        let path = path.replace('\\', "/");
+        is_selected(&self.include_paths, &self.exclude_paths, &path)
+    }
+
+    /// Returns true if the group with the given id must be loaded.
+    #[must_use]
+    pub fn is_group_selected(&self, group_id: &str) -> bool {
+        is_selected(&self.include_groups, &self.exclude_groups, group_id)
+    }
+}
+
+/// Returns true if the value matches one of the included patterns (or if
+/// there is none) and none of the excluded patterns.
+fn is_selected(include: &[Pattern], exclude: &[Pattern], value: &str) -> bool {
+    (include.is_empty() || include.iter().any(|pattern| pattern.matches(value)))
+        && !exclude.iter().any(|pattern| pattern.matches(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_filter() {
+        let filter = SpecFilter::try_new(
+            &["http/**", "registry.*"],
+            &["http/deprecated/*", "*.client"],
+        )
+        .expect("Failed to create the filter");
+        assert!(!filter.is_empty());
+        assert!(filter.is_path_selected("http/registry.yaml"));
+        assert!(filter.is_path_selected("http/spans/client.yaml"));
+        assert!(!filter.is_path_selected("http/deprecated/registry.yaml"));
+        assert!(!filter.is_path_selected("db/registry.yaml"));
+        assert!(filter.is_group_selected("registry.http"));
+        assert!(!filter.is_group_selected("registry.http.client"));
+        assert!(!filter.is_group_selected("span.http.server"));
+
+        let filter = SpecFilter::try_new::<&str>(&[], &[]).expect("Failed to create the filter");
+        assert!(filter.is_empty());
+        assert!(filter.is_path_selected("db/registry.yaml"));
+        assert!(filter.is_group_selected("span.http.server"));
+
+        assert!(matches!(
+            SpecFilter::try_new(&["registry.[http"], &[]),
+            Err(Error::InvalidSpecFilter { .. })
+        ));
+    }
+}
@@ -5,6 +5,7 @@
 //! A group specification.
 
 use schemars::JsonSchema;
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 
 use serde::{Deserialize, Serialize};
@@ -18,7 +19,7 @@ use weaver_common::result::WResult;
 
 /// Group Spec contain the list of semantic conventions for attributes,
 /// metrics, events, spans, etc.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct GroupSpec {
     /// The id that uniquely identifies the semantic convention.
@@ -36,10 +37,10 @@ pub struct GroupSpec {
     /// It defaults to an empty string.
     #[serde(default)]
     pub prefix: String,
-    /// Reference another semantic convention id. It inherits the prefix,
-    /// constraints, and all attributes defined in the specified semantic
-    /// convention.
-    pub extends: Option<String>,
+    /// Reference another semantic convention id, or a list of semantic
+    /// convention ids. It inherits the prefix, constraints, and all attributes
+    /// defined in the specified semantic conventions.
+    pub extends: Option<ExtendsSpec>,
     /// Specifies the stability of the semantic convention.
     /// Note that, if stability is missing but deprecated is present, it will
     /// automatically set the stability to deprecated. If deprecated is
@@ -87,6 +88,10 @@ pub struct GroupSpec {
     pub name: Option<String>,
     /// The readable name for attribute groups used when generating registry tables.
     pub display_name: Option<String>,
+    /// The URL of the telemetry schema the group is defined under (e.g.
+    /// `https://opentelemetry.io/schemas/1.27.0`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_url: Option<String>,
     /// The event body definition
     /// Note: only valid if type is event
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -129,6 +134,27 @@ impl GroupSpec {
             });
         }
 
+        // A list of extended groups must be non-empty and without duplicates.
+        if let Some(extends) = &self.extends {
+            let group_ids = extends.group_ids();
+            let mut seen = HashSet::new();
+            let error = if group_ids.is_empty() {
+                Some("The `extends` list is empty.".to_owned())
+            } else {
+                group_ids
+                    .iter()
+                    .find(|group_id| !seen.insert(group_id.as_str()))
+                    .map(|group_id| format!("The group `{}` is extended twice.", group_id))
+            };
+            if let Some(error) = error {
+                errors.push(Error::InvalidGroup {
+                    path_or_url: path_or_url.to_owned(),
+                    group_id: self.id.clone(),
+                    error,
+                });
+            }
+        }
+
         // Fields span_kind and events are only valid if type is span (the default).
         if self.r#type != GroupType::Span {
             if self.span_kind.is_some() {
@@ -178,6 +204,9 @@ impl GroupSpec {
                 });
             }
 
+            if let Some(body) = &self.body {
+                validate_any_value_fields(&mut errors, body, &self.id, path_or_url);
+            }
             match validate_any_value_examples(
                 &mut errors,
                 self.body.as_ref(),
@@ -291,6 +320,50 @@ impl GroupSpec {
                 }
             }
 
+            // The value constraints must be applicable to the attribute type
+            // and satisfied by the examples.
+            if let AttributeSpec::Id {
+                r#type,
+                examples,
+                value_constraints: Some(value_constraints),
+                ..
+            } = attribute
+            {
+                for error in value_constraints.validate(r#type, examples.as_ref()) {
+                    errors.push(Error::InvalidAttribute {
+                        path_or_url: path_or_url.to_owned(),
+                        group_id: self.id.clone(),
+                        attribute_id: attribute.id(),
+                        error,
+                    });
+                }
+            }
+
+            // The aliases must differ from the attribute id and from each other.
+            if let AttributeSpec::Id {
+                id,
+                aliases: Some(aliases),
+                ..
+            } = attribute
+            {
+                let mut seen = HashSet::new();
+                for alias in aliases {
+                    let error = if alias == id {
+                        format!("The alias `{}` is the id of the attribute.", alias)
+                    } else if !seen.insert(alias.as_str()) {
+                        format!("The alias `{}` is duplicated.", alias)
+                    } else {
+                        continue;
+                    };
+                    errors.push(Error::InvalidAttribute {
+                        path_or_url: path_or_url.to_owned(),
+                        group_id: self.id.clone(),
+                        attribute_id: attribute.id(),
+                        error,
+                    });
+                }
+            }
+
             // Produce a warning if `allow_custom_values` is Some.
             if let AttributeSpec::Id {
                 r#type:
@@ -314,6 +387,44 @@ impl GroupSpec {
     }
 }
 
+/// Validates the fields of a `map` or `map[]` any value and of its nested
+/// maps: a map must define at least one field, and the ids of its fields must
+/// be unique.
+fn validate_any_value_fields(
+    errors: &mut Vec<Error>,
+    any_value: &AnyValueSpec,
+    group_id: &str,
+    path_or_url: &str,
+) {
+    let fields = any_value.fields();
+    let mut field_ids = HashSet::new();
+    let error = if matches!(
+        any_value,
+        AnyValueSpec::Map { .. } | AnyValueSpec::Maps { .. }
+    ) && fields.is_empty()
+    {
+        Some(format!("This value is a {} without any fields.", any_value))
+    } else {
+        fields
+            .iter()
+            .map(|field| field.common().id.as_str())
+            .find(|field_id| !field_ids.insert(*field_id))
+            .map(|field_id| format!("The field `{}` is defined twice.", field_id))
+    };
+    if let Some(error) = error {
+        errors.push(Error::InvalidAnyValue {
+            path_or_url: path_or_url.to_owned(),
+            group_id: group_id.to_owned(),
+            value_id: any_value.id(),
+            error,
+        });
+    }
+
+    for field in fields {
+        validate_any_value_fields(errors, field, group_id, path_or_url);
+    }
+}
+
 fn validate_any_value_examples(
     errors: &mut Vec<Error>,
     any_value: Option<&AnyValueSpec>,
@@ -394,6 +505,36 @@ impl Default for GroupType {
     }
 }
 
+/// The group(s) extended by a group.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(untagged)]
+pub enum ExtendsSpec {
+    /// A single extended group id.
+    Group(String),
+    /// A list of extended group ids. An attribute defined differently by
+    /// several of these groups must be redefined by the extending group, and
+    /// this definition completes the one of the first group of the list.
+    Groups(Vec<String>),
+}
+
+impl ExtendsSpec {
+    /// Returns the ids of the extended groups.
+    #[must_use]
+    pub fn group_ids(&self) -> &[String] {
+        match self {
+            ExtendsSpec::Group(group_id) => std::slice::from_ref(group_id),
+            ExtendsSpec::Groups(group_ids) => group_ids,
+        }
+    }
+}
+
+/// Implements a human readable display for the extended groups.
+impl Display for ExtendsSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.group_ids().join(", "))
+    }
+}
+
 /// The span kind.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -411,7 +552,7 @@ pub enum SpanKindSpec {
 }
 
 /// Allow to define additional requirements on the semantic convention.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ConstraintSpec {
     /// any_of accepts a list of sequences. Each sequence contains a list of
@@ -457,9 +598,9 @@ mod tests {
     use crate::any_value::AnyValueCommonSpec;
     use crate::attribute::{BasicRequirementLevelSpec, Examples, RequirementLevel};
     use crate::Error::{
-        CompoundError, InvalidAttributeAllowCustomValues, InvalidExampleWarning, InvalidGroup,
-        InvalidGroupMissingExtendsOrAttributes, InvalidGroupStability, InvalidGroupUsesPrefix,
-        InvalidMetric, InvalidSpanMissingSpanKind,
+        CompoundError, InvalidAttribute, InvalidAttributeAllowCustomValues, InvalidExampleWarning,
+        InvalidGroup, InvalidGroupMissingExtendsOrAttributes, InvalidGroupStability,
+        InvalidGroupUsesPrefix, InvalidMetric, InvalidSpanMissingSpanKind,
     };
 
     use super::*;
@@ -486,6 +627,8 @@ mod tests {
                 requirement_level: Default::default(),
                 sampling_relevant: None,
                 note: "".to_owned(),
+                value_constraints: None,
+                aliases: None,
             }],
             constraints: vec![],
             span_kind: Some(SpanKindSpec::Client),
@@ -495,6 +638,7 @@ mod tests {
             unit: None,
             name: None,
             display_name: None,
+            schema_url: None,
             body: None,
         };
         assert!(group
@@ -615,6 +759,8 @@ mod tests {
                 requirement_level: Default::default(),
                 sampling_relevant: None,
                 note: "".to_owned(),
+                value_constraints: None,
+                aliases: None,
             }],
             constraints: vec![],
             span_kind: Some(SpanKindSpec::Client),
@@ -624,6 +770,7 @@ mod tests {
             unit: None,
             name: None,
             display_name: None,
+            schema_url: None,
             body: None,
         };
         assert!(group
@@ -643,6 +790,8 @@ mod tests {
             requirement_level: Default::default(),
             sampling_relevant: None,
             note: "".to_owned(),
+            value_constraints: None,
+            aliases: None,
         }];
         let result = group.validate("<test>").into_result_failing_non_fatal();
         assert_eq!(
@@ -668,6 +817,8 @@ mod tests {
             requirement_level: Default::default(),
             sampling_relevant: None,
             note: "".to_owned(),
+            value_constraints: None,
+            aliases: None,
         }];
         let result = group.validate("<test>").into_result_failing_non_fatal();
         assert_eq!(
@@ -680,6 +831,44 @@ mod tests {
             },),
             result
         );
+
+        // Aliases must differ from the attribute id and from each other.
+        group.attributes = vec![AttributeSpec::Id {
+            id: "test".to_owned(),
+            r#type: AttributeType::PrimitiveOrArray(PrimitiveOrArrayTypeSpec::String),
+            brief: None,
+            stability: Some(Stability::Deprecated),
+            deprecated: Some("true".to_owned()),
+            examples: Some(Examples::String("test".to_owned())),
+            tag: None,
+            requirement_level: Default::default(),
+            sampling_relevant: None,
+            note: "".to_owned(),
+            value_constraints: None,
+            aliases: Some(vec![
+                "legacy.test".to_owned(),
+                "test".to_owned(),
+                "legacy.test".to_owned(),
+            ]),
+        }];
+        let result = group.validate("<test>").into_result_failing_non_fatal();
+        assert_eq!(
+            Err(CompoundError(vec![
+                InvalidAttribute {
+                    path_or_url: "<test>".to_owned(),
+                    group_id: "test".to_owned(),
+                    attribute_id: "test".to_owned(),
+                    error: "The alias `test` is the id of the attribute.".to_owned(),
+                },
+                InvalidAttribute {
+                    path_or_url: "<test>".to_owned(),
+                    group_id: "test".to_owned(),
+                    attribute_id: "test".to_owned(),
+                    error: "The alias `legacy.test` is duplicated.".to_owned(),
+                },
+            ])),
+            result
+        );
     }
 
     #[test]
@@ -707,6 +896,8 @@ mod tests {
                 requirement_level: Default::default(),
                 sampling_relevant: None,
                 note: "".to_owned(),
+                value_constraints: None,
+                aliases: None,
             }],
             constraints: vec![],
             span_kind: Some(SpanKindSpec::Client),
@@ -716,6 +907,7 @@ mod tests {
             unit: None,
             name: None,
             display_name: None,
+            schema_url: None,
             body: None,
         };
         let result = group.validate("<test>").into_result_failing_non_fatal();
@@ -745,6 +937,8 @@ mod tests {
             requirement_level: Default::default(),
             sampling_relevant: None,
             note: "".to_owned(),
+            value_constraints: None,
+            aliases: None,
         }];
         let result = group.validate("<test>").into_result_failing_non_fatal();
         assert!(result.is_ok());
@@ -769,6 +963,7 @@ mod tests {
             instrument: None,
             unit: None,
             display_name: None,
+            schema_url: None,
             attributes: vec![],
             body: Some(AnyValueSpec::String {
                 common: AnyValueCommonSpec {
@@ -952,6 +1147,49 @@ mod tests {
             },),
             result
         );
+
+        // A nested map must define fields with unique ids.
+        let common = |id: &str| AnyValueCommonSpec {
+            id: id.to_owned(),
+            brief: "brief".to_owned(),
+            note: "note".to_owned(),
+            stability: None,
+            examples: None,
+            requirement_level: RequirementLevel::Basic(BasicRequirementLevelSpec::Optional),
+        };
+        group.body = Some(AnyValueSpec::Map {
+            common: common("map_id"),
+            fields: vec![
+                AnyValueSpec::Maps {
+                    common: common("nested_maps_id"),
+                    fields: vec![],
+                },
+                AnyValueSpec::Int {
+                    common: common("int_id"),
+                },
+                AnyValueSpec::Double {
+                    common: common("int_id"),
+                },
+            ],
+        });
+        let result = group.validate("<test>").into_result_failing_non_fatal();
+        assert_eq!(
+            Err(CompoundError(vec![
+                Error::InvalidAnyValue {
+                    path_or_url: "<test>".to_owned(),
+                    group_id: "test".to_owned(),
+                    value_id: "map_id".to_owned(),
+                    error: "The field `int_id` is defined twice.".to_owned(),
+                },
+                Error::InvalidAnyValue {
+                    path_or_url: "<test>".to_owned(),
+                    group_id: "test".to_owned(),
+                    value_id: "nested_maps_id".to_owned(),
+                    error: "This value is a map[] without any fields.".to_owned(),
+                },
+            ])),
+            result
+        );
     }
 
     #[test]
@@ -976,6 +1214,8 @@ mod tests {
                 requirement_level: Default::default(),
                 sampling_relevant: None,
                 note: "".to_owned(),
+                value_constraints: None,
+                aliases: None,
             }],
             constraints: vec![],
             span_kind: None,
@@ -985,6 +1225,7 @@ mod tests {
             unit: None,
             name: None,
             display_name: None,
+            schema_url: None,
             body: None,
         };
         assert!(group
@@ -1118,6 +1359,8 @@ mod tests {
             requirement_level: Default::default(),
             sampling_relevant: None,
             note: "".to_owned(),
+            value_constraints: None,
+            aliases: None,
         }];
         let mut group = GroupSpec {
             id: "test".to_owned(),
@@ -1137,6 +1380,7 @@ mod tests {
             unit: None,
             name: None,
             display_name: None,
+            schema_url: None,
             body: None,
         };
 
@@ -1158,11 +1402,49 @@ mod tests {
             .is_ok());
 
         group.attributes = vec![];
-        group.extends = Some("test".to_owned());
+        group.extends = Some(ExtendsSpec::Group("test".to_owned()));
         assert!(group
             .validate("<test>")
             .into_result_failing_non_fatal()
             .is_ok());
+
+        // A list of extended groups.
+        group.extends = Some(ExtendsSpec::Groups(vec![
+            "test".to_owned(),
+            "other".to_owned(),
+        ]));
+        assert!(group
+            .validate("<test>")
+            .into_result_failing_non_fatal()
+            .is_ok());
+
+        // The list of extended groups must not be empty.
+        group.extends = Some(ExtendsSpec::Groups(vec![]));
+        let result = group.validate("<test>").into_result_failing_non_fatal();
+        assert_eq!(
+            Err(InvalidGroup {
+                path_or_url: "<test>".to_owned(),
+                group_id: "test".to_owned(),
+                error: "The `extends` list is empty.".to_owned(),
+            }),
+            result
+        );
+
+        // A group can't be extended twice.
+        group.extends = Some(ExtendsSpec::Groups(vec![
+            "test".to_owned(),
+            "other".to_owned(),
+            "test".to_owned(),
+        ]));
+        let result = group.validate("<test>").into_result_failing_non_fatal();
+        assert_eq!(
+            Err(InvalidGroup {
+                path_or_url: "<test>".to_owned(),
+                group_id: "test".to_owned(),
+                error: "The group `test` is extended twice.".to_owned(),
+            }),
+            result
+        );
         group.extends = None;
 
         // Span must have extends or attributes.
@@ -1185,7 +1467,7 @@ mod tests {
             .is_ok());
 
         group.attributes = vec![];
-        group.extends = Some("test".to_owned());
+        group.extends = Some(ExtendsSpec::Group("test".to_owned()));
         assert!(group
             .validate("<test>")
             .into_result_failing_non_fatal()
@@ -1212,7 +1494,7 @@ mod tests {
             .is_ok());
 
         group.attributes = vec![];
-        group.extends = Some("test".to_owned());
+        group.extends = Some(ExtendsSpec::Group("test".to_owned()));
         assert!(group
             .validate("<test>")
             .into_result_failing_non_fatal()
@@ -10,8 +10,10 @@ use weaver_common::error::{format_errors, WeaverError};
 
 pub mod any_value;
 pub mod attribute;
+pub mod filter;
 pub mod group;
 pub mod metric;
+pub mod prose;
 pub mod registry;
 pub mod semconv;
 pub mod stability;
@@ -30,6 +32,16 @@ pub enum Error {
         error: String,
     },
 
+    /// A pattern of the filter selecting the semantic convention files and
+    /// groups to load is invalid.
+    #[error("Invalid spec filter pattern `{pattern}`: {error}")]
+    InvalidSpecFilter {
+        /// The invalid glob pattern.
+        pattern: String,
+        /// The reason of the error.
+        error: String,
+    },
+
     /// The semantic convention registry is not found.
     #[error(
         "The semantic convention registry is not found (path_or_url: {path_or_url:?}). {error}"
@@ -179,6 +191,23 @@ pub enum Error {
         group_id: String,
     },
 
+    /// This warning indicates that a brief or a note doesn't follow one of the
+    /// enabled prose lint rules.
+    #[error("The {field} of `{id}` doesn't follow the prose lint rule `{rule}`: {error}.\nProvenance: {path_or_url:?}")]
+    #[diagnostic(severity(Warning))]
+    ProseLintWarning {
+        /// The path or URL of the semantic convention asset.
+        path_or_url: String,
+        /// The id of the group or of the attribute.
+        id: String,
+        /// The linted field (`brief` or `note`).
+        field: String,
+        /// The violated rule.
+        rule: String,
+        /// The reason of the warning.
+        error: String,
+    },
+
     /// The semantic convention asset contains an invalid metric definition.
     #[error("Invalid metric definition in {path_or_url:?}.\ngroup_id=`{group_id}`. {error}")]
     InvalidMetric {
@@ -204,6 +233,21 @@ pub enum Error {
         error: String,
     },
 
+    /// This indicates that a semantic convention asset contains an invalid
+    /// any value definition.
+    #[error("The value `{value_id}` in the group `{group_id}` is invalid. {error}\nProvenance: {path_or_url:?}")]
+    #[diagnostic(severity(Error))]
+    InvalidAnyValue {
+        /// The path or URL of the semantic convention asset.
+        path_or_url: String,
+        /// The group id of the any_value.
+        group_id: String,
+        /// The id of the any_value.
+        value_id: String,
+        /// The reason of the error.
+        error: String,
+    },
+
     /// A container for multiple errors.
     #[error("{:?}", format_errors(.0))]
     CompoundError(#[related] Vec<Error>),
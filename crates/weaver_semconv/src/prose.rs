@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint rules for the prose (`brief` and `note` fields) of the semantic
+//! conventions.
+//!
+//! The rules are disabled by default. The enabled rules are applied to the
+//! briefs and notes of the groups and attributes of the loaded semantic
+//! convention specs (see [`lint_semconv_spec`]), and the violations are
+//! reported as warnings.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::semconv::SemConvSpec;
+use crate::Error;
+
+/// A lint rule for the briefs and notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProseLintRule {
+    /// The text starts with an uppercase letter (texts starting with a
+    /// non-letter, e.g. a code span, are accepted).
+    SentenceCase,
+    /// The text ends with a period.
+    TrailingPeriod,
+    /// The text doesn't contain markdown headings.
+    NoHeadings,
+    /// The brief has at most the given number of characters.
+    MaxLength(usize),
+}
+
+impl ProseLintRule {
+    /// Checks a brief or a note (as designated by `field`) against this rule,
+    /// and returns the reason of the violation if any.
+    #[must_use]
+    pub fn check(&self, field: &str, text: &str) -> Option<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        match self {
+            ProseLintRule::SentenceCase => text
+                .chars()
+                .next()
+                .filter(|c| c.is_lowercase())
+                .map(|_| "it should start with an uppercase letter".to_owned()),
+            ProseLintRule::TrailingPeriod => {
+                (!text.ends_with('.')).then(|| "it should end with a period".to_owned())
+            }
+            ProseLintRule::NoHeadings => text
+                .lines()
+                .find(|line| is_heading(line))
+                .map(|line| format!("it should not contain the heading `{}`", line.trim())),
+            ProseLintRule::MaxLength(max_length) => {
+                let length = text.chars().count();
+                (field == "brief" && length > *max_length).then(|| {
+                    format!(
+                        "it has {} characters, more than the maximum of {}",
+                        length, max_length
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Returns true if the line is a markdown (ATX) heading.
+fn is_heading(line: &str) -> bool {
+    let line = line.trim_start();
+    let level = line.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&level)
+        && line[level..]
+            .chars()
+            .next()
+            .map_or(true, char::is_whitespace)
+}
+
+impl Display for ProseLintRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProseLintRule::SentenceCase => write!(f, "sentence_case"),
+            ProseLintRule::TrailingPeriod => write!(f, "trailing_period"),
+            ProseLintRule::NoHeadings => write!(f, "no_headings"),
+            ProseLintRule::MaxLength(max_length) => write!(f, "max_length={}", max_length),
+        }
+    }
+}
+
+impl FromStr for ProseLintRule {
+    type Err = String;
+
+    /// Parses a rule: `sentence_case`, `trailing_period`, `no_headings` or
+    /// `max_length=<N>`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "sentence_case" => Ok(ProseLintRule::SentenceCase),
+            "trailing_period" => Ok(ProseLintRule::TrailingPeriod),
+            "no_headings" => Ok(ProseLintRule::NoHeadings),
+            other => other
+                .strip_prefix("max_length=")
+                .and_then(|max_length| max_length.trim().parse().ok())
+                .map(ProseLintRule::MaxLength)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid prose lint rule `{}`, expected `sentence_case`, `trailing_period`, `no_headings` or `max_length=<N>`",
+                        other
+                    )
+                }),
+        }
+    }
+}
+
+/// Checks the briefs and notes of the groups and attributes of a semantic
+/// convention spec against the given prose lint rules, and returns a
+/// [`Error::ProseLintWarning`] for each violation.
+#[must_use]
+pub fn lint_semconv_spec(
+    rules: &[ProseLintRule],
+    path_or_url: &str,
+    spec: &SemConvSpec,
+) -> Vec<Error> {
+    if rules.is_empty() {
+        return vec![];
+    }
+    let mut errors = vec![];
+    for group in spec.groups.iter() {
+        let mut texts = vec![
+            (group.id.clone(), "brief", group.brief.clone()),
+            (group.id.clone(), "note", group.note.clone()),
+        ];
+        for attribute in &group.attributes {
+            texts.push((attribute.id(), "brief", attribute.brief()));
+            texts.push((attribute.id(), "note", attribute.note()));
+        }
+        for (id, field, text) in texts {
+            for rule in rules {
+                if let Some(error) = rule.check(field, &text) {
+                    errors.push(Error::ProseLintWarning {
+                        path_or_url: path_or_url.to_owned(),
+                        id: id.clone(),
+                        field: field.to_owned(),
+                        rule: rule.to_string(),
+                        error,
+                    });
+                }
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prose_lint_rules() {
+        let rules: Vec<ProseLintRule> = [
+            "sentence_case",
+            "trailing_period",
+            "no_headings",
+            "max_length=20",
+        ]
+        .iter()
+        .map(|rule| rule.parse().expect("Failed to parse the rule"))
+        .collect();
+        assert_eq!(rules[3], ProseLintRule::MaxLength(20));
+        assert_eq!(rules[3].to_string(), "max_length=20");
+        assert!("max_length=abc".parse::<ProseLintRule>().is_err());
+
+        let violations = |field: &str, text: &str| -> Vec<String> {
+            rules
+                .iter()
+                .filter(|rule| rule.check(field, text).is_some())
+                .map(ToString::to_string)
+                .collect()
+        };
+        assert!(violations("brief", "The HTTP method.").is_empty());
+        assert!(violations("brief", "`http.route` value.").is_empty());
+        assert!(violations("note", "").is_empty());
+        assert_eq!(
+            violations("brief", "the HTTP request method"),
+            vec!["sentence_case", "trailing_period", "max_length=20"]
+        );
+        assert_eq!(
+            violations(
+                "note",
+                "Notes.\n\n## Details\n\nThe longest note of the registry."
+            ),
+            vec!["no_headings"]
+        );
+        assert!(violations("note", "See #1234 and #issue.").is_empty());
+    }
+
+    #[test]
+    fn test_lint_semconv_spec() {
+        let spec = SemConvSpec::from_string(
+            r#"
+groups:
+  - id: registry.http
+    type: attribute_group
+    brief: HTTP attributes.
+    attributes:
+      - id: http.request.method
+        type: string
+        brief: the HTTP request method
+        examples: [GET]
+"#,
+        )
+        .into_result_failing_non_fatal()
+        .expect("Failed to load the spec");
+
+        assert!(lint_semconv_spec(&[], "test.yaml", &spec).is_empty());
+        let errors = lint_semconv_spec(
+            &[ProseLintRule::SentenceCase, ProseLintRule::TrailingPeriod],
+            "test.yaml",
+            &spec,
+        );
+        let rules: Vec<_> = errors
+            .iter()
+            .map(|error| match error {
+                Error::ProseLintWarning { id, rule, .. } => format!("{id}:{rule}"),
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                "http.request.method:sentence_case",
+                "http.request.method:trailing_period"
+            ]
+        );
+    }
+}
@@ -3,7 +3,8 @@
 //! Semantic Convention Registry.
 
 use crate::attribute::AttributeSpecWithProvenance;
-use crate::group::GroupSpecWithProvenance;
+use crate::filter::SpecFilter;
+use crate::group::{GroupSpec, GroupSpecWithProvenance};
 use crate::metric::MetricSpecWithProvenance;
 use crate::semconv::{SemConvSpec, SemConvSpecWithProvenance};
 use crate::stats::Stats;
@@ -66,9 +67,36 @@ impl SemConvRegistry {
     ///
     /// If the registry path pattern is invalid.
     pub fn try_from_path_pattern(registry_id: &str, path_pattern: &str) -> WResult<Self, Error> {
+        Self::try_from_path_pattern_with_filter(registry_id, path_pattern, &SpecFilter::default())
+    }
+
+    /// Create a new semantic convention registry from the files matching the
+    /// path pattern and selected by the filter, keeping only the groups
+    /// selected by the filter. The path patterns of the filter are matched
+    /// against the paths produced by the path pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry_id` - The id of the semantic convention registry.
+    /// * `path_pattern` - A glob pattern to load semantic convention registry from files.
+    /// * `filter` - The filter selecting the files and groups to load.
+    ///
+    /// # Returns
+    ///
+    /// A new semantic convention registry.
+    ///
+    /// # Errors
+    ///
+    /// If the registry path pattern is invalid.
+    pub fn try_from_path_pattern_with_filter(
+        registry_id: &str,
+        path_pattern: &str,
+        filter: &SpecFilter,
+    ) -> WResult<Self, Error> {
         fn create_registry_or_fatal(
             registry_id: &str,
             path_pattern: &str,
+            filter: &SpecFilter,
             non_fatal_errors: &mut Vec<Error>,
         ) -> Result<SemConvRegistry, Error> {
             let mut registry = SemConvRegistry::new(registry_id);
@@ -82,9 +110,13 @@ impl SemConvRegistry {
                     path_pattern: path_pattern.to_owned(),
                     error: e.to_string(),
                 })?;
-                let (semconv_spec, nfes) = SemConvSpecWithProvenance::from_file(path_buf.as_path())
+                if !filter.is_path_selected(&path_buf.display().to_string()) {
+                    continue;
+                }
+                let provenance = path_buf.display().to_string();
+                let (spec, nfes) = SemConvSpec::from_file_with_filter(path_buf.as_path(), filter)
                     .into_result_with_non_fatal()?;
-                registry.add_semconv_spec(semconv_spec);
+                registry.add_semconv_spec(SemConvSpecWithProvenance { spec, provenance });
                 non_fatal_errors.extend(nfes);
             }
             Ok(registry)
@@ -92,7 +124,7 @@ impl SemConvRegistry {
 
         let mut non_fatal_errors = vec![];
 
-        match create_registry_or_fatal(registry_id, path_pattern, &mut non_fatal_errors) {
+        match create_registry_or_fatal(registry_id, path_pattern, filter, &mut non_fatal_errors) {
             Ok(registry) => WResult::with_non_fatal_errors(registry, non_fatal_errors),
             Err(e) => WResult::FatalErr(e),
         }
@@ -161,7 +193,18 @@ impl SemConvRegistry {
         SemConvSpec::from_file(semconv_path).map(|spec| (provenance, spec))
     }
 
+    /// Loads and returns the semantic convention spec from a file, keeping
+    /// only the groups selected by the filter.
+    pub fn semconv_spec_from_file_with_filter<P: AsRef<Path>>(
+        semconv_path: P,
+        filter: &SpecFilter,
+    ) -> WResult<(String, SemConvSpec), Error> {
+        let provenance = semconv_path.as_ref().display().to_string();
+        SemConvSpec::from_file_with_filter(semconv_path, filter).map(|spec| (provenance, spec))
+    }
+
     /// Downloads and returns the semantic convention spec from an URL.
+    #[cfg(feature = "network")]
     pub fn semconv_spec_from_url(sem_conv_url: &str) -> WResult<(String, SemConvSpec), Error> {
         SemConvSpec::from_url(sem_conv_url).map(|spec| (sem_conv_url.to_owned(), spec))
     }
@@ -173,6 +216,28 @@ impl SemConvRegistry {
         self.semconv_spec_count
     }
 
+    /// Returns an iterator over the semantic convention specs of the registry,
+    /// in the order they were added. Each spec is associated with its
+    /// provenance (path or URL).
+    pub fn semconv_spec_iter(&self) -> impl Iterator<Item = (&str, &SemConvSpec)> {
+        self.specs
+            .iter()
+            .map(|SemConvSpecWithProvenance { spec, provenance }| (provenance.as_str(), spec))
+    }
+
+    /// Returns an iterator over the mutable unresolved groups of the semantic
+    /// convention specs, in the order they were added. Each group is
+    /// associated with its provenance (path or URL).
+    pub fn group_spec_iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut GroupSpec)> {
+        self.specs
+            .iter_mut()
+            .flat_map(|SemConvSpecWithProvenance { spec, provenance }| {
+                spec.groups
+                    .iter_mut()
+                    .map(|group| (provenance.as_str(), group))
+            })
+    }
+
     /// Returns an iterator over all the unresolved groups defined in the semantic convention
     /// registry. Each group is associated with its provenance (path or URL).
     ///
@@ -267,6 +332,8 @@ mod tests {
                             note: "note".to_owned(),
                             stability: None,
                             deprecated: None,
+                            value_constraints: None,
+                            aliases: None,
                         }],
                         constraints: vec![],
                         span_kind: None,
@@ -282,6 +349,7 @@ mod tests {
                         events: vec![],
                         name: None,
                         display_name: Some("Group 1".to_owned()),
+                        schema_url: None,
                         body: None,
                     }],
                 },
@@ -307,6 +375,7 @@ mod tests {
                         events: vec![],
                         name: None,
                         display_name: Some("Group 2".to_owned()),
+                        schema_url: None,
                         body: None,
                     }],
                 },
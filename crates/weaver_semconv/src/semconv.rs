@@ -2,17 +2,18 @@
 
 //! Semantic convention specification.
 
+use crate::filter::SpecFilter;
 use crate::group::GroupSpec;
 use crate::Error;
+use schemars::schema::{RootSchema, Schema, SchemaObject};
+use schemars::{schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 use weaver_common::result::WResult;
 
 /// A semantic convention file as defined [here](https://github.com/open-telemetry/build-tools/blob/main/semantic-conventions/syntax.md)
 /// A semconv file is a collection of semantic convention groups (i.e. [`GroupSpec`]).
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct SemConvSpec {
     /// A collection of semantic convention groups or [`GroupSpec`].
@@ -39,26 +40,42 @@ impl SemConvSpec {
     ///
     /// The [`SemConvSpec`] or an [`Error`] if the semantic convention spec is invalid.
     pub fn from_file<P: AsRef<Path>>(path: P) -> WResult<SemConvSpec, Error> {
+        Self::from_file_with_filter(path, &SpecFilter::default())
+    }
+
+    /// Create a new semantic convention spec from a file, keeping only the
+    /// groups selected by the filter. The groups not selected are removed
+    /// before the validation. The path of the file is not checked against
+    /// the filter.
+    ///
+    /// # Arguments:
+    ///
+    /// * `path` - The path to the [`SemConvSpec`].
+    /// * `filter` - The filter selecting the groups to keep.
+    ///
+    /// # Returns
+    ///
+    /// The [`SemConvSpec`] or an [`Error`] if the semantic convention spec is invalid.
+    pub fn from_file_with_filter<P: AsRef<Path>>(
+        path: P,
+        filter: &SpecFilter,
+    ) -> WResult<SemConvSpec, Error> {
         fn from_file_or_fatal(path: &Path, provenance: &str) -> Result<SemConvSpec, Error> {
             // Load and deserialize the semantic convention registry
-            let semconv_file = File::open(path).map_err(|e| Error::RegistryNotFound {
+            let content = std::fs::read_to_string(path).map_err(|e| Error::RegistryNotFound {
                 path_or_url: provenance.to_owned(),
                 error: e.to_string(),
             })?;
-            serde_yaml::from_reader(BufReader::new(semconv_file)).map_err(|e| {
-                Error::InvalidSemConvSpec {
-                    path_or_url: provenance.to_owned(),
-                    line: e.location().map(|loc| loc.line()),
-                    column: e.location().map(|loc| loc.column()),
-                    error: e.to_string(),
-                }
-            })
+            parse_spec(&content, provenance)
         }
 
         let provenance = path.as_ref().display().to_string();
 
         match from_file_or_fatal(path.as_ref(), &provenance) {
-            Ok(semconv_spec) => {
+            Ok(mut semconv_spec) => {
+                semconv_spec
+                    .groups
+                    .retain(|group| filter.is_group_selected(&group.id));
                 // Important note: the resolution process expects this step of validation to be done for
                 // each semantic convention spec.
                 semconv_spec.validate(&provenance)
@@ -77,12 +94,7 @@ impl SemConvSpec {
     ///
     /// The [`SemConvSpec`] or an [`Error`] if the semantic convention spec is invalid.
     pub fn from_string(spec: &str) -> WResult<SemConvSpec, Error> {
-        match serde_yaml::from_str::<SemConvSpec>(spec).map_err(|e| Error::InvalidSemConvSpec {
-            path_or_url: "<str>".to_owned(),
-            line: None,
-            column: None,
-            error: e.to_string(),
-        }) {
+        match parse_spec(spec, "<str>") {
             Ok(semconv_spec) => {
                 // Important note: the resolution process expects this step of validation to be done for
                 // each semantic convention spec.
@@ -101,10 +113,13 @@ impl SemConvSpec {
     /// # Returns
     ///
     /// The [`SemConvSpec`] or an [`Error`] if the semantic convention spec is invalid.
+    #[cfg(feature = "network")]
     pub fn from_url(semconv_url: &str) -> WResult<SemConvSpec, Error> {
         fn from_url_or_fatal(semconv_url: &str) -> Result<SemConvSpec, Error> {
             // Create a content reader from the semantic convention URL
-            let reader = ureq::get(semconv_url)
+            use std::io::Read;
+
+            let mut reader = ureq::get(semconv_url)
                 .call()
                 .map_err(|e| Error::RegistryNotFound {
                     path_or_url: semconv_url.to_owned(),
@@ -113,12 +128,14 @@ impl SemConvSpec {
                 .into_reader();
 
             // Deserialize the telemetry schema from the content reader
-            serde_yaml::from_reader(reader).map_err(|e| Error::InvalidSemConvSpec {
-                path_or_url: semconv_url.to_owned(),
-                line: e.location().map(|loc| loc.line()),
-                column: e.location().map(|loc| loc.column()),
-                error: e.to_string(),
-            })
+            let mut content = String::new();
+            _ = reader
+                .read_to_string(&mut content)
+                .map_err(|e| Error::RegistryNotFound {
+                    path_or_url: semconv_url.to_owned(),
+                    error: e.to_string(),
+                })?;
+            parse_spec(&content, semconv_url)
         }
 
         match from_url_or_fatal(semconv_url) {
@@ -144,6 +161,99 @@ impl SemConvSpec {
 
         WResult::with_non_fatal_errors(self, errors)
     }
+
+    /// Returns the JSON schema of the semantic convention files.
+    ///
+    /// The schema is generated from the spec structs and completed with the
+    /// legacy values still accepted by the parser (e.g. the `experimental`
+    /// stability).
+    #[must_use]
+    pub fn json_schema() -> RootSchema {
+        let mut schema = schema_for!(SemConvSpec);
+        if let Some(Schema::Object(stability)) = schema.definitions.get_mut("Stability") {
+            let development = serde_json::Value::from("development");
+            for variant in stability.subschemas().one_of.iter_mut().flatten() {
+                if let Schema::Object(SchemaObject {
+                    enum_values: Some(values),
+                    ..
+                }) = variant
+                {
+                    if values.contains(&development) {
+                        values.push("experimental".into());
+                    }
+                }
+            }
+        }
+        schema
+    }
+}
+
+/// Deserializes a semantic convention spec from its YAML content, or from its
+/// JSON content if the path or URL has a `.json` extension.
+///
+/// Anchors and aliases are supported by the YAML parser. Merge keys (`<<`)
+/// are expanded before the deserialization. The errors found in expanded
+/// content have no location in the YAML document, so they are reported at
+/// the location of the `id` of the group containing them.
+fn parse_spec(content: &str, path_or_url: &str) -> Result<SemConvSpec, Error> {
+    if path_or_url.ends_with(".json") {
+        return serde_json::from_str(content).map_err(|e| Error::InvalidSemConvSpec {
+            path_or_url: path_or_url.to_owned(),
+            line: Some(e.line()),
+            column: Some(e.column()),
+            error: e.to_string(),
+        });
+    }
+
+    let invalid_spec = |e: serde_yaml::Error| Error::InvalidSemConvSpec {
+        path_or_url: path_or_url.to_owned(),
+        line: e.location().map(|loc| loc.line()),
+        column: e.location().map(|loc| loc.column()),
+        error: e.to_string(),
+    };
+    if !content.contains("<<") {
+        return serde_yaml::from_str(content).map_err(invalid_spec);
+    }
+
+    let mut value: serde_yaml::Value = serde_yaml::from_str(content).map_err(invalid_spec)?;
+    value.apply_merge().map_err(invalid_spec)?;
+    SemConvSpec::deserialize(&value).map_err(|e| {
+        // Find the first group that can't be deserialized on its own.
+        let invalid_group = value
+            .get("groups")
+            .and_then(serde_yaml::Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .find(|group| GroupSpec::deserialize(*group).is_err())
+            .and_then(|group| group.get("id"))
+            .and_then(serde_yaml::Value::as_str);
+        match invalid_group {
+            Some(group_id) => {
+                let location = id_location(content, group_id);
+                Error::InvalidSemConvSpec {
+                    path_or_url: path_or_url.to_owned(),
+                    line: location.map(|(line, _)| line),
+                    column: location.map(|(_, column)| column),
+                    error: format!(
+                        "{} (in the group `{}`, after the expansion of the YAML merge keys)",
+                        e, group_id
+                    ),
+                }
+            }
+            None => invalid_spec(e),
+        }
+    })
+}
+
+/// Returns the location (line and column, starting at 1) of the first
+/// `id: <id>` key of the YAML content.
+fn id_location(content: &str, id: &str) -> Option<(usize, usize)> {
+    content.lines().enumerate().find_map(|(index, line)| {
+        let rest = line.trim_start().trim_start_matches("- ").trim_start();
+        let value = rest.strip_prefix("id:")?.split(" #").next()?.trim();
+        (value.trim_matches(|c| c == '"' || c == '\'') == id)
+            .then(|| (index + 1, line.len() - rest.len() + 1))
+    })
 }
 
 impl SemConvSpecWithProvenance {
@@ -216,6 +326,28 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_semconv_spec_from_json_file() {
+        let json_spec = SemConvSpec::from_file("data/server.semconv.json")
+            .into_result_failing_non_fatal()
+            .unwrap();
+        let yaml_spec = SemConvSpec::from_file("data/server.yaml")
+            .into_result_failing_non_fatal()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&json_spec).unwrap(),
+            serde_json::to_value(&yaml_spec).unwrap()
+        );
+
+        // Invalid JSON content
+        let err = parse_spec(
+            "{\n  \"groups\": [\n    {\"id\": \"server\",}\n  ]\n}",
+            "spec.json",
+        )
+        .unwrap_err();
+        assert!(matches!(err, InvalidSemConvSpec { line: Some(3), .. }));
+    }
+
     #[test]
     fn test_semconv_spec_from_string() {
         // Valid spec
@@ -320,6 +452,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "network")]
     fn test_semconv_spec_from_url() {
         let server = ServeStaticFiles::from("tests/test_data").unwrap();
         // Existing URL. The URL is a raw file from the semantic conventions repository.
@@ -346,6 +479,72 @@ mod tests {
         assert!(matches!(semconv_spec.unwrap_err(), RegistryNotFound { .. }));
     }
 
+    #[test]
+    fn test_semconv_spec_anchors_and_merge_keys() {
+        let spec = r#"
+groups:
+  - id: registry.http
+    type: attribute_group
+    brief: HTTP
+    stability: stable
+    attributes: &http_attributes
+      - id: http.request.method
+        type: string
+        brief: &method_brief Method
+        stability: stable
+        examples: [GET]
+  - id: registry.http.old
+    type: attribute_group
+    brief: *method_brief
+    stability: stable
+    attributes:
+      - <<: &base
+          type: string
+          brief: Old method
+          stability: experimental
+          examples: [GET]
+        id: http.method
+  - id: span.http
+    type: span
+    brief: HTTP span
+    stability: stable
+    span_kind: client
+    attributes: *http_attributes
+"#;
+        let semconv_spec = SemConvSpec::from_string(spec)
+            .into_result_failing_non_fatal()
+            .expect("Failed to parse the spec");
+        assert_eq!(semconv_spec.groups.len(), 3);
+        assert_eq!(semconv_spec.groups[1].brief, "Method");
+        assert_eq!(
+            semconv_spec.groups[1].attributes[0].id(),
+            "http.method".to_owned()
+        );
+        assert_eq!(semconv_spec.groups[2].attributes.len(), 1);
+
+        // Error in merged content: reported at the location of the group.
+        let spec = r#"
+groups:
+  - id: registry.http
+    type: attribute_group
+    brief: HTTP
+    attributes:
+      - <<: &base
+          type: string
+          unknown_field: true
+        id: http.method
+        brief: Method
+"#;
+        let error = SemConvSpec::from_string(spec)
+            .into_result_failing_non_fatal()
+            .expect_err("The spec should be invalid");
+        assert!(matches!(
+            error,
+            InvalidSemConvSpec { line: Some(3), column: Some(5), ref error, .. }
+                if error.contains("registry.http")
+        ));
+    }
+
     #[test]
     fn test_semconv_spec_with_provenance_from_file() {
         let path = PathBuf::from("data/database.yaml");
@@ -386,4 +585,18 @@ mod tests {
         assert_eq!(semconv_spec.spec.groups.len(), 2);
         assert_eq!(semconv_spec.provenance, provenance);
     }
+    #[test]
+    fn test_semconv_json_schema() {
+        let schema = serde_json::to_value(SemConvSpec::json_schema())
+            .expect("Failed to serialize the JSON schema");
+        assert_eq!(schema["required"], serde_json::json!(["groups"]));
+        let stability_values: Vec<_> = schema["definitions"]["Stability"]["oneOf"]
+            .as_array()
+            .expect("Expected the stability variants")
+            .iter()
+            .flat_map(|variant| variant["enum"].as_array().cloned().unwrap_or_default())
+            .collect();
+        assert!(stability_values.contains(&serde_json::json!("development")));
+        assert!(stability_values.contains(&serde_json::json!("experimental")));
+    }
 }
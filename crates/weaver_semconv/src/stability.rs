@@ -25,6 +25,22 @@ pub enum Stability {
     ReleaseCandidate,
 }
 
+impl Stability {
+    /// Returns the maturity level of the stability, from the least mature
+    /// (deprecated) to the most mature (stable).
+    #[must_use]
+    pub fn maturity(&self) -> u8 {
+        match self {
+            Stability::Deprecated => 0,
+            Stability::Development => 1,
+            Stability::Alpha => 2,
+            Stability::Beta => 3,
+            Stability::ReleaseCandidate => 4,
+            Stability::Stable => 5,
+        }
+    }
+}
+
 /// Implements a human readable display for the stability.
 impl Display for Stability {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -77,4 +93,13 @@ mod tests {
         assert_eq!(Stability::Beta.to_string(), "beta");
         assert_eq!(Stability::ReleaseCandidate.to_string(), "release_candidate");
     }
+
+    #[test]
+    fn test_maturity() {
+        assert!(Stability::Deprecated.maturity() < Stability::Development.maturity());
+        assert!(Stability::Development.maturity() < Stability::Alpha.maturity());
+        assert!(Stability::Alpha.maturity() < Stability::Beta.maturity());
+        assert!(Stability::Beta.maturity() < Stability::ReleaseCandidate.maturity());
+        assert!(Stability::ReleaseCandidate.maturity() < Stability::Stable.maturity());
+    }
 }
@@ -3,9 +3,11 @@
 //! Statistics about the semantic convention registry.
 
 use crate::group::GroupType;
+use serde::Serialize;
 use std::collections::HashMap;
 
 /// Statistics about the semantic convention registry.
+#[derive(Debug, Serialize)]
 #[must_use]
 pub struct Stats {
     /// Number of semconv files.
@@ -309,8 +309,24 @@ impl SnippetGenerator {
         diag_msgs: &mut DiagnosticMessages,
         follow_symlinks: bool,
     ) -> Result<SnippetGenerator, Error> {
-        let registry = ResolvedSemconvRegistry::try_from_registry_repo(
-            registry_repo,
+        Self::try_from_registry_repos(
+            std::slice::from_ref(registry_repo),
+            template_engine,
+            diag_msgs,
+            follow_symlinks,
+        )
+    }
+
+    /// Resolve semconv registries (possibly from git) merged together, and
+    /// make them available for rendering.
+    pub fn try_from_registry_repos(
+        registry_repos: &[RegistryRepo],
+        template_engine: TemplateEngine,
+        diag_msgs: &mut DiagnosticMessages,
+        follow_symlinks: bool,
+    ) -> Result<SnippetGenerator, Error> {
+        let registry = ResolvedSemconvRegistry::try_from_registry_repos(
+            registry_repos,
             diag_msgs,
             follow_symlinks,
         )?;
@@ -328,15 +344,20 @@ struct ResolvedSemconvRegistry {
 }
 
 impl ResolvedSemconvRegistry {
-    /// Resolve semconv registry (possibly from git), and make it available for rendering.
-    fn try_from_registry_repo(
-        registry_repo: &RegistryRepo,
+    /// Resolve semconv registries (possibly from git), and make them available for rendering.
+    fn try_from_registry_repos(
+        registry_repos: &[RegistryRepo],
         diag_msgs: &mut DiagnosticMessages,
         follow_symlinks: bool,
     ) -> Result<ResolvedSemconvRegistry, Error> {
         let registry_id = "semantic_conventions";
-        let semconv_specs = SchemaResolver::load_semconv_specs(registry_repo, follow_symlinks)
-            .capture_non_fatal_errors(diag_msgs)?;
+        let mut semconv_specs = vec![];
+        for registry_repo in registry_repos {
+            semconv_specs.extend(
+                SchemaResolver::load_semconv_specs(registry_repo, follow_symlinks)
+                    .capture_non_fatal_errors(diag_msgs)?,
+            );
+        }
         let mut registry = SemConvRegistry::from_semconv_specs(registry_id, semconv_specs);
         let schema = SchemaResolver::resolve_semantic_convention_registry(&mut registry)?;
         let lookup = ResolvedSemconvRegistry {
@@ -363,7 +384,7 @@ impl ResolvedSemconvRegistry {
 #[cfg(test)]
 mod tests {
     use weaver_cache::registry_path::RegistryPath;
-    use weaver_cache::RegistryRepo;
+    use weaver_cache::{RegistryCache, RegistryRepo};
     use weaver_common::diagnostic::DiagnosticMessages;
     use weaver_forge::config::{Params, WeaverConfig};
     use weaver_forge::file_loader::FileSystemFileLoader;
@@ -387,7 +408,8 @@ mod tests {
             path: "data".to_owned(),
         };
         let mut diag_msgs = DiagnosticMessages::empty();
-        let registry_repo = RegistryRepo::try_new("main", &registry_path)?;
+        let registry_repo =
+            RegistryRepo::try_new("main", &registry_path, &RegistryCache::try_default()?)?;
         let generator = SnippetGenerator::try_from_registry_repo(
             &registry_repo,
             template,
@@ -0,0 +1,17 @@
+package before_resolution
+
+# An attribute whose stability is not `deprecated` but has the deprecated field
+# set is invalid.
+deny[violation] {
+    group := input.groups[_]
+    attr := group.attributes[_]
+    attr.stability != "deprecated"
+    attr.deprecated
+    violation := {
+        "id": "attr_stability_deprecated",
+        "type": "semconv_attribute",
+        "category": "attribute",
+        "group": group.id,
+        "attr": attr.id,
+    }
+}
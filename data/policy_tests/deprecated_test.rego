@@ -0,0 +1,5 @@
+package before_resolution
+
+test_deprecated_attr_denied {
+    count(deny) == 1 with input as {"groups": [{"id": "registry.a", "attributes": [{"id": "a.b", "stability": "stable", "deprecated": "Use `a.c`."}]}]}
+}
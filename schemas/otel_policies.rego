@@ -45,6 +45,16 @@ deny[schema_evolution_violation("attr_removed", old_group.id, old_attr.id)] {
     not attr_exists_in_new_group(old_group.id, old_attr.id)
 }
 
+# An enum member whose stability is not `deprecated` but has the deprecated
+# field set is invalid.
+deny[enum_member_violation("enum_member_stability_deprecated", group.id, attr.id, member.id)] {
+    group := input.groups[_]
+    attr := group.attributes[_]
+    member := attr.type.members[_]
+    member.stability != "deprecated"
+    is_string(member.deprecated)
+}
+
 
 # ========= Helper functions =========
 
@@ -88,4 +98,16 @@ schema_evolution_violation(violation_id, group_id, attr_id) = violation {
         "group": group_id,
         "attr": attr_id,
     }
-}
\ No newline at end of file
+}
+
+# Build an enum member violation, the attribute being reported as
+# `<attr_id>.<member_id>`
+enum_member_violation(violation_id, group_id, attr_id, member_id) = violation {
+    violation := {
+        "id": violation_id,
+        "type": "semconv_attribute",
+        "category": "enum_member",
+        "group": group_id,
+        "attr": concat(".", [attr_id, member_id]),
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Remove all the cached registries.
+
+use clap::Args;
+
+use weaver_cache::cached_registry::clear;
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::Logger;
+
+use crate::cache::{format_size, resolution_cache_error, CacheDirArgs};
+use crate::{DiagnosticArgs, ExitDirectives};
+
+/// Parameters for the `cache clear` sub-command
+#[derive(Debug, Args)]
+pub struct CacheClearArgs {
+    /// Directory of the registry cache.
+    #[command(flatten)]
+    pub cache_dir: CacheDirArgs,
+
+    /// Parameters to specify the diagnostic format.
+    #[command(flatten)]
+    pub diagnostic: DiagnosticArgs,
+}
+
+/// Remove all the cached registries and resolved registries not in use.
+pub(crate) fn command(
+    logger: impl Logger + Sync + Clone,
+    args: &CacheClearArgs,
+) -> Result<ExitDirectives, DiagnosticMessages> {
+    let cache_dir = args.cache_dir.cache_dir()?;
+    let removed = clear(&cache_dir)?;
+
+    logger.success(&format!(
+        "Removed {} cached registries ({}) from {:?}",
+        removed.len(),
+        format_size(removed.iter().map(|registry| registry.size).sum()),
+        cache_dir
+    ));
+    if let Some(resolution_cache) = args.cache_dir.resolution_cache() {
+        let (count, size) = resolution_cache
+            .clear()
+            .map_err(|e| resolution_cache_error(&resolution_cache, e))?;
+        logger.success(&format!(
+            "Removed {} resolved registries ({}) from {:?}",
+            count,
+            format_size(size),
+            resolution_cache.dir()
+        ));
+    }
+    Ok(ExitDirectives {
+        exit_code: 0,
+        quiet_mode: false,
+    })
+}
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! List the cached registries.
+
+use clap::Args;
+
+use weaver_cache::cached_registry::cached_registries;
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::Logger;
+
+use crate::cache::{describe, format_size, resolution_cache_error, CacheDirArgs};
+use crate::{DiagnosticArgs, ExitDirectives};
+
+/// Parameters for the `cache list` sub-command
+#[derive(Debug, Args)]
+pub struct CacheListArgs {
+    /// Directory of the registry cache.
+    #[command(flatten)]
+    pub cache_dir: CacheDirArgs,
+
+    /// Parameters to specify the diagnostic format.
+    #[command(flatten)]
+    pub diagnostic: DiagnosticArgs,
+}
+
+/// List the cached registries, the oldest first, and the size of the resolution cache.
+pub(crate) fn command(
+    logger: impl Logger + Sync + Clone,
+    args: &CacheListArgs,
+) -> Result<ExitDirectives, DiagnosticMessages> {
+    let cache_dir = args.cache_dir.cache_dir()?;
+    let registries = cached_registries(&cache_dir)?;
+
+    for registry in registries.iter() {
+        logger.log(&describe(registry));
+    }
+    logger.log(&format!(
+        "{} cached registries ({}) in {:?}",
+        registries.len(),
+        format_size(registries.iter().map(|registry| registry.size).sum()),
+        cache_dir
+    ));
+    if let Some(resolution_cache) = args.cache_dir.resolution_cache() {
+        let (count, size) = resolution_cache
+            .usage()
+            .map_err(|e| resolution_cache_error(&resolution_cache, e))?;
+        logger.log(&format!(
+            "{} resolved registries ({}) in {:?}",
+            count,
+            format_size(size),
+            resolution_cache.dir()
+        ));
+    }
+
+    Ok(ExitDirectives {
+        exit_code: 0,
+        quiet_mode: false,
+    })
+}
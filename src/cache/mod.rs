@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands to manage the cache of the remote registries.
+//!
+//! The Git repositories and the remote archives used as registries or template packages are
+//! cached in `~/.weaver/semconv_registry_cache` (see the `--offline` and `--registry-cache-ttl`
+//! options), and the resolved registries in `~/.weaver/resolution_cache`. These commands report
+//! the content of these caches and remove their entries. The entries in use by another Weaver
+//! process are never removed.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Subcommand};
+
+use weaver_cache::cached_registry::{default_cache_dir, CachedRegistry};
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::Logger;
+
+use crate::cache::clear::CacheClearArgs;
+use crate::cache::list::CacheListArgs;
+use crate::cache::prune::CachePruneArgs;
+use crate::resolution_cache::ResolutionCache;
+use crate::CmdResult;
+
+mod clear;
+mod list;
+mod prune;
+
+/// Parameters for the `cache` command
+#[derive(Debug, Args)]
+pub struct CacheCommand {
+    /// Define the sub-commands for the `cache` command
+    #[clap(subcommand)]
+    pub command: CacheSubCommand,
+}
+
+/// Sub-commands to manage the cache of the remote registries.
+#[derive(Debug, Subcommand)]
+#[clap(verbatim_doc_comment)]
+pub enum CacheSubCommand {
+    /// Lists the cached registries with their size and age.
+    List(CacheListArgs),
+    /// Removes the cached registries older than a duration, then the oldest cached registries
+    /// until the cache fits in a maximum size.
+    Prune(CachePruneArgs),
+    /// Removes all the cached registries.
+    Clear(CacheClearArgs),
+}
+
+/// Set of parameters used to specify the directories of the registry and resolution caches.
+#[derive(Args, Debug, Clone, Default)]
+pub struct CacheDirArgs {
+    /// Directory of the registry cache.
+    /// Default is `$HOME/.weaver/semconv_registry_cache`.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Directory of the resolution cache.
+    /// Default is `$HOME/.weaver/resolution_cache`.
+    #[arg(long)]
+    pub resolution_cache_dir: Option<PathBuf>,
+}
+
+impl CacheDirArgs {
+    /// Returns the directory of the registry cache.
+    pub(crate) fn cache_dir(&self) -> Result<PathBuf, weaver_cache::Error> {
+        match &self.cache_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => default_cache_dir(),
+        }
+    }
+
+    /// Returns the resolution cache, or `None` if the home directory can't be determined.
+    pub(crate) fn resolution_cache(&self) -> Option<ResolutionCache> {
+        match &self.resolution_cache_dir {
+            Some(dir) => Some(ResolutionCache::new(dir.clone())),
+            None => ResolutionCache::try_default(),
+        }
+    }
+}
+
+/// Manage the cache of the remote registries.
+pub fn cache(log: impl Logger + Sync + Clone, command: &CacheCommand) -> CmdResult {
+    match &command.command {
+        CacheSubCommand::List(args) => {
+            CmdResult::new(list::command(log, args), Some(args.diagnostic.clone()))
+        }
+        CacheSubCommand::Prune(args) => {
+            CmdResult::new(prune::command(log, args), Some(args.diagnostic.clone()))
+        }
+        CacheSubCommand::Clear(args) => {
+            CmdResult::new(clear::command(log, args), Some(args.diagnostic.clone()))
+        }
+    }
+}
+
+/// Returns a one-line description of a cache entry.
+fn describe(registry: &CachedRegistry) -> String {
+    format!(
+        "{} - {}, {} old{} - {}",
+        registry
+            .registry_path
+            .as_deref()
+            .unwrap_or("<temporary directory>"),
+        format_size(registry.size),
+        format_duration(registry.age()),
+        if registry.in_use { ", in use" } else { "" },
+        registry.path.display()
+    )
+}
+
+/// Converts an I/O error on the resolution cache into a diagnostic message.
+fn resolution_cache_error(cache: &ResolutionCache, error: std::io::Error) -> DiagnosticMessages {
+    DiagnosticMessages::from(weaver_cache::Error::CacheDirNotAccessible {
+        path: cache.dir().display().to_string(),
+        error: error.to_string(),
+    })
+}
+
+/// Formats a size in bytes with a decimal unit, e.g. `12.3 MB`.
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if size < 1000 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64;
+    let mut unit = "B";
+    for next_unit in UNITS {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        unit = next_unit;
+    }
+    format!("{:.1} {}", value, unit)
+}
+
+/// Formats a duration with its largest unit, e.g. `3d` or `5h`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+/// Parses a duration made of a number and an optional unit (`s`, `m`, `h`, or `d`, seconds by
+/// default), e.g. `30d`.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let trimmed = value.trim();
+    let (number, unit_secs) = match trimmed.char_indices().last() {
+        Some((index, 's')) => (&trimmed[..index], 1),
+        Some((index, 'm')) => (&trimmed[..index], 60),
+        Some((index, 'h')) => (&trimmed[..index], 3600),
+        Some((index, 'd')) => (&trimmed[..index], 86400),
+        _ => (trimmed, 1),
+    };
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(unit_secs))
+        .map(Duration::from_secs)
+        .ok_or_else(|| {
+            format!(
+                "Invalid duration `{}`, expected a number of seconds, minutes, hours, or days (e.g. `3600`, `90m`, `12h`, `30d`)",
+                value
+            )
+        })
+}
+
+/// Parses a size made of a number and an optional decimal unit (`B`, `KB`, `MB`, `GB`, or `TB`,
+/// bytes by default), e.g. `500MB`.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value_upper = value.trim().to_uppercase();
+    let (number, unit) = [
+        ("TB", 1_000_000_000_000),
+        ("GB", 1_000_000_000),
+        ("MB", 1_000_000),
+        ("KB", 1_000),
+        ("B", 1),
+    ]
+    .into_iter()
+    .find_map(|(suffix, unit)| {
+        value_upper
+            .strip_suffix(suffix)
+            .map(|number| (number.to_owned(), unit))
+    })
+    .unwrap_or((value_upper.clone(), 1));
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(unit))
+        .ok_or_else(|| {
+            format!(
+                "Invalid size `{}`, expected a number of bytes with an optional unit (e.g. `500MB`, `2GB`)",
+                value
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("3600"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("12h"), Ok(Duration::from_secs(43200)));
+        assert_eq!(parse_duration("30d"), Ok(Duration::from_secs(2_592_000)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3w").is_err());
+        assert!(parse_duration("-1d").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("10B"), Ok(10));
+        assert_eq!(parse_size("500MB"), Ok(500_000_000));
+        assert_eq!(parse_size("2gb"), Ok(2_000_000_000));
+        assert_eq!(parse_size("3 KB"), Ok(3000));
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("1.5GB").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_size(999), "999 B");
+        assert_eq!(format_size(12_345_678), "12.3 MB");
+        assert_eq!(format_size(2_000_000_000), "2.0 GB");
+        assert_eq!(format_duration(Duration::from_secs(59)), "59s");
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2h");
+        assert_eq!(format_duration(Duration::from_secs(3 * 86400 + 1)), "3d");
+    }
+}
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Remove the old cached registries.
+
+use std::time::Duration;
+
+use clap::{ArgGroup, Args};
+
+use weaver_cache::cached_registry::prune;
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::Logger;
+
+use crate::cache::{
+    describe, format_size, parse_duration, parse_size, resolution_cache_error, CacheDirArgs,
+};
+use crate::{DiagnosticArgs, ExitDirectives};
+
+/// Parameters for the `cache prune` sub-command
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("limit").required(true).multiple(true).args(["older_than", "max_size"])))]
+pub struct CachePruneArgs {
+    /// Remove the cached registries older than this duration, e.g. `3600`, `90m`, `12h`, or
+    /// `30d` (the unit is the second by default).
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub older_than: Option<Duration>,
+
+    /// Remove the oldest cached registries until the total size of the cache is at most this
+    /// size, e.g. `500MB` or `2GB` (the unit is the byte by default).
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_size: Option<u64>,
+
+    /// Directory of the registry cache.
+    #[command(flatten)]
+    pub cache_dir: CacheDirArgs,
+
+    /// Parameters to specify the diagnostic format.
+    #[command(flatten)]
+    pub diagnostic: DiagnosticArgs,
+}
+
+/// Remove the cached registries older than the given duration, then the oldest cached
+/// registries until the cache fits in the given size. The resolution cache is pruned the same
+/// way, its size limit being applied separately.
+pub(crate) fn command(
+    logger: impl Logger + Sync + Clone,
+    args: &CachePruneArgs,
+) -> Result<ExitDirectives, DiagnosticMessages> {
+    let cache_dir = args.cache_dir.cache_dir()?;
+    let removed = prune(&cache_dir, args.older_than, args.max_size)?;
+
+    for registry in removed.iter() {
+        logger.log(&format!("Removed {}", describe(registry)));
+    }
+    logger.success(&format!(
+        "Removed {} cached registries ({}) from {:?}",
+        removed.len(),
+        format_size(removed.iter().map(|registry| registry.size).sum()),
+        cache_dir
+    ));
+    if let Some(resolution_cache) = args.cache_dir.resolution_cache() {
+        let (count, size) = resolution_cache
+            .prune(args.older_than, args.max_size)
+            .map_err(|e| resolution_cache_error(&resolution_cache, e))?;
+        logger.success(&format!(
+            "Removed {} resolved registries ({}) from {:?}",
+            count,
+            format_size(size),
+            resolution_cache.dir()
+        ));
+    }
+    Ok(ExitDirectives {
+        exit_code: 0,
+        quiet_mode: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+
+    use tempdir::TempDir;
+
+    use weaver_cache::cached_registry::cached_registries;
+    use weaver_cache::test::write_cache_entry;
+    use weaver_common::TestLogger;
+
+    use crate::cache::clear::CacheClearArgs;
+    use crate::cache::list::CacheListArgs;
+    use crate::cache::prune::CachePruneArgs;
+    use crate::cache::{CacheCommand, CacheDirArgs, CacheSubCommand};
+    use crate::cli::{Cli, Commands};
+    use crate::run_command;
+
+    fn cache_cli(command: CacheSubCommand) -> Cli {
+        Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Cache(CacheCommand { command })),
+        }
+    }
+
+    #[test]
+    fn test_cache_commands() {
+        let logger = TestLogger::new();
+        let cache_dir = TempDir::new("cache").unwrap();
+        let registry_cache_dir = cache_dir.path().join("registries");
+        let resolution_cache_dir = cache_dir.path().join("resolution");
+        let cache_dir_args = CacheDirArgs {
+            cache_dir: Some(registry_cache_dir.clone()),
+            resolution_cache_dir: Some(resolution_cache_dir.clone()),
+        };
+        write_cache_entry(
+            &registry_cache_dir,
+            "recent",
+            "https://example.com/recent.zip",
+            60,
+            10,
+        );
+        write_cache_entry(
+            &registry_cache_dir,
+            "old",
+            "https://example.com/old.zip",
+            10 * 86400,
+            10,
+        );
+        fs::create_dir_all(&resolution_cache_dir).unwrap();
+        for (key, age) in [("recent", 60), ("old", 10 * 86400)] {
+            let path = resolution_cache_dir.join(format!("{}.json", key));
+            fs::write(&path, "{}").unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now() - Duration::from_secs(age)))
+                .unwrap();
+        }
+
+        let cli = cache_cli(CacheSubCommand::List(CacheListArgs {
+            cache_dir: cache_dir_args.clone(),
+            diagnostic: Default::default(),
+        }));
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 0);
+
+        let cli = cache_cli(CacheSubCommand::Prune(CachePruneArgs {
+            older_than: Some(Duration::from_secs(7 * 86400)),
+            max_size: None,
+            cache_dir: cache_dir_args.clone(),
+            diagnostic: Default::default(),
+        }));
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 0);
+        let registries = cached_registries(&registry_cache_dir).unwrap();
+        assert_eq!(registries.len(), 1);
+        assert_eq!(
+            registries[0].registry_path.as_deref(),
+            Some("https://example.com/recent.zip")
+        );
+        assert!(resolution_cache_dir.join("recent.json").exists());
+        assert!(!resolution_cache_dir.join("old.json").exists());
+
+        let cli = cache_cli(CacheSubCommand::Clear(CacheClearArgs {
+            cache_dir: cache_dir_args,
+            diagnostic: Default::default(),
+        }));
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 0);
+        assert!(cached_registries(&registry_cache_dir).unwrap().is_empty());
+        assert_eq!(fs::read_dir(&resolution_cache_dir).unwrap().count(), 0);
+    }
+}
@@ -2,8 +2,11 @@
 
 //! Manage command line arguments
 
+use crate::cache::CacheCommand;
 use crate::diagnostic::DiagnosticCommand;
+use crate::policy::PolicyCommand;
 use crate::registry::RegistryCommand;
+use crate::template::TemplateCommand;
 use clap::{Parser, Subcommand};
 
 /// Command line arguments.
@@ -31,6 +34,33 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub future: bool,
 
+    /// Only use the cached copies of the remote registries (Git repositories
+    /// and archives) without accessing the network. The command fails if a
+    /// registry is not in the cache.
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// The number of seconds during which a cached remote registry is used
+    /// without being downloaded again (1 day by default).
+    #[arg(long, global = true, value_name = "SECONDS")]
+    pub registry_cache_ttl: Option<u64>,
+
+    /// Record the revisions of the remote registries in the `weaver.lock`
+    /// lockfile of the current directory, creating it if needed. An existing
+    /// lockfile is always updated.
+    #[arg(long, global = true)]
+    pub write_lockfile: bool,
+
+    /// Fail if a remote registry is not recorded in the `weaver.lock` lockfile
+    /// or doesn't match its recorded revision, instead of updating the
+    /// lockfile.
+    #[arg(long, global = true)]
+    pub locked: bool,
+
+    /// Equivalent to `--locked` and `--offline`.
+    #[arg(long, global = true)]
+    pub frozen: bool,
+
     /// List of supported commands
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -44,4 +74,10 @@ pub enum Commands {
     Registry(RegistryCommand),
     /// Manage Diagnostic Messages
     Diagnostic(DiagnosticCommand),
+    /// Manage Template Packages
+    Template(TemplateCommand),
+    /// Manage the Cache of the Remote Registries
+    Cache(CacheCommand),
+    /// Manage Policies
+    Policy(PolicyCommand),
 }
@@ -98,6 +98,11 @@ mod tests {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Diagnostic(DiagnosticCommand {
                 command: DiagnosticSubCommand::Init(DiagnosticInitArgs {
                     target: "".to_owned(),
@@ -123,6 +128,11 @@ mod tests {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Diagnostic(DiagnosticCommand {
                 command: DiagnosticSubCommand::Init(DiagnosticInitArgs {
                     target: "json".to_owned(),
@@ -3,11 +3,16 @@
 #![allow(clippy::print_stdout)]
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::{Args, Parser};
 
 use registry::semconv_registry;
-use weaver_common::diagnostic::{enable_future_mode, DiagnosticMessages};
+use weaver_cache::lockfile::{LockMode, DEFAULT_LOCKFILE};
+use weaver_cache::RegistryCache;
+use weaver_common::diagnostic::{
+    enable_future_mode, DiagnosticMessages, SeverityOverride, SeverityOverrides,
+};
 use weaver_common::quiet::QuietLogger;
 use weaver_common::{ConsoleLogger, Logger};
 use weaver_forge::config::{Params, WeaverConfig};
@@ -17,10 +22,14 @@ use weaver_forge::{OutputDirective, TemplateEngine};
 use crate::cli::{Cli, Commands};
 use crate::diagnostic::DEFAULT_DIAGNOSTIC_TEMPLATES;
 
+mod cache;
 mod cli;
 mod diagnostic;
 mod format;
+mod policy;
 mod registry;
+mod resolution_cache;
+mod template;
 mod util;
 
 /// Set of parameters used to specify the diagnostic format.
@@ -34,6 +43,18 @@ pub(crate) struct DiagnosticArgs {
     /// Path to the directory where the diagnostic templates are located.
     #[arg(long, default_value = "diagnostic_templates")]
     pub(crate) diagnostic_template: PathBuf,
+
+    /// Maximum number of occurrences displayed for each violated policy rule by the ansi
+    /// format, the violations being grouped by rule id (0 displays all of them). The json
+    /// format always contains all the violations.
+    #[arg(long, default_value_t = 10)]
+    pub(crate) max_violations_per_rule: usize,
+
+    /// Override the severity of a diagnostic, e.g. `duplicate_group_name=error`
+    /// or `invalid_example_warning=ignore`. The severity is one of `error`,
+    /// `warning` or `ignore`. This option can be repeated.
+    #[arg(long, value_name = "ID=SEVERITY", value_parser = parse_severity_override)]
+    pub(crate) diagnostic_severity: Vec<(String, SeverityOverride)>,
 }
 
 impl Default for DiagnosticArgs {
@@ -41,10 +62,38 @@ impl Default for DiagnosticArgs {
         Self {
             diagnostic_format: "ansi".to_owned(),
             diagnostic_template: PathBuf::from("diagnostic_templates"),
+            max_violations_per_rule: 10,
+            diagnostic_severity: vec![],
         }
     }
 }
 
+impl DiagnosticArgs {
+    /// Returns the severity overrides of the diagnostics.
+    pub(crate) fn severity_overrides(&self) -> SeverityOverrides {
+        self.diagnostic_severity.iter().cloned().collect()
+    }
+}
+
+/// Parses a severity override of the form `<diagnostic id>=<severity>`.
+fn parse_severity_override(value: &str) -> Result<(String, SeverityOverride), String> {
+    let (id, severity) = value
+        .split_once('=')
+        .ok_or_else(|| format!("Expected `<ID>=<SEVERITY>`, got `{}`", value))?;
+    let severity = match severity.trim() {
+        "error" => SeverityOverride::Error,
+        "warning" => SeverityOverride::Warning,
+        "ignore" => SeverityOverride::Ignore,
+        other => {
+            return Err(format!(
+                "Invalid severity `{}`, expected `error`, `warning` or `ignore`",
+                other
+            ))
+        }
+    };
+    Ok((id.trim().to_owned(), severity))
+}
+
 /// Result of a command execution.
 #[derive(Debug)]
 pub(crate) struct CmdResult {
@@ -101,9 +150,31 @@ fn run_command(cli: &Cli, log: impl Logger + Sync + Clone) -> ExitDirectives {
     if cli.future {
         enable_future_mode();
     }
+    let registry_cache = match RegistryCache::try_default() {
+        Ok(registry_cache) => registry_cache.with_offline_mode(cli.offline || cli.frozen),
+        Err(e) => return process_diagnostics(CmdResult::new(Err(e.into()), None), log),
+    };
+    let registry_cache = match cli.registry_cache_ttl {
+        Some(ttl) => registry_cache.with_ttl(Duration::from_secs(ttl)),
+        None => registry_cache,
+    };
+    // The lockfile is only written when requested or when it already exists.
+    let lockfile = PathBuf::from(DEFAULT_LOCKFILE);
+    let registry_cache = if cli.locked || cli.frozen {
+        registry_cache.with_lockfile(lockfile, LockMode::Locked)
+    } else if cli.write_lockfile || lockfile.exists() {
+        registry_cache.with_lockfile(lockfile, LockMode::Update)
+    } else {
+        registry_cache
+    };
     let cmd_result = match &cli.command {
-        Some(Commands::Registry(params)) => semconv_registry(log.clone(), params),
+        Some(Commands::Registry(params)) => semconv_registry(log.clone(), params, &registry_cache),
         Some(Commands::Diagnostic(params)) => diagnostic::diagnostic(log.clone(), params),
+        Some(Commands::Template(params)) => {
+            template::template(log.clone(), params, &registry_cache)
+        }
+        Some(Commands::Cache(params)) => cache::cache(log.clone(), params),
+        Some(Commands::Policy(params)) => policy::policy(log.clone(), params),
         None => {
             return ExitDirectives {
                 exit_code: 0,
@@ -132,6 +203,9 @@ fn process_diagnostics(
     };
 
     if let Err(diagnostic_messages) = cmd_result.command_result {
+        let diagnostic_messages = diagnostic_args
+            .severity_overrides()
+            .apply(diagnostic_messages);
         let loader = EmbeddedFileLoader::try_new(
             &DEFAULT_DIAGNOSTIC_TEMPLATES,
             diagnostic_args.diagnostic_template,
@@ -140,7 +214,13 @@ fn process_diagnostics(
         .expect("Failed to create the embedded file loader for the diagnostic templates");
         let config = WeaverConfig::try_from_loader(&loader)
             .expect("Failed to load `defaults/diagnostic_templates/weaver.yaml`");
-        let engine = TemplateEngine::new(config, loader, Params::default());
+        // The violations are grouped by rule id, and the number of occurrences
+        // displayed for each rule is capped by the templates supporting it.
+        let params = Params::from_key_value_pairs(&[(
+            "max_violations_per_rule",
+            serde_yaml::Value::from(diagnostic_args.max_violations_per_rule),
+        )]);
+        let engine = TemplateEngine::new(config, loader, params);
         match engine.generate(
             logger.clone(),
             &diagnostic_messages,
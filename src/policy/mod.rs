@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands to manage the policies.
+
+use clap::{Args, Subcommand};
+
+use weaver_common::Logger;
+
+use crate::policy::test::PolicyTestArgs;
+use crate::CmdResult;
+
+mod test;
+
+/// Parameters for the `policy` command
+#[derive(Debug, Args)]
+pub struct PolicyCommand {
+    /// Define the sub-commands for the `policy` command
+    #[clap(subcommand)]
+    pub command: PolicySubCommand,
+}
+
+/// Sub-commands to manage the policies.
+#[derive(Debug, Subcommand)]
+#[clap(verbatim_doc_comment)]
+pub enum PolicySubCommand {
+    /// Runs the tests of the policies: the Rego test rules (`test_*`) and the test fixtures
+    /// (`*.test.yaml`) comparing the violations reported for an input to the expected ones.
+    Test(PolicyTestArgs),
+}
+
+/// Manage the policies.
+pub fn policy(log: impl Logger + Sync + Clone, command: &PolicyCommand) -> CmdResult {
+    match &command.command {
+        PolicySubCommand::Test(args) => {
+            CmdResult::new(test::command(log, args), Some(args.diagnostic.clone()))
+        }
+    }
+}
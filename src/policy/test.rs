@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Run the tests of the policies.
+
+use std::path::PathBuf;
+
+use clap::Args;
+
+use weaver_checker::policy_test::load_policy_tests;
+use weaver_checker::{Engine, SEMCONV_REGO};
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::error::WeaverError;
+use weaver_common::Logger;
+
+use crate::{DiagnosticArgs, ExitDirectives};
+
+/// Parameters for the `policy test` sub-command
+#[derive(Debug, Args)]
+pub struct PolicyTestArgs {
+    /// The policy files or directories to test. The policies (`.rego`, `.cel.yaml`,
+    /// `.lint.yaml`, and `.wasm` files) and the test fixtures (`.test.yaml` files) of the
+    /// directories are loaded recursively.
+    #[arg(default_value = ".")]
+    pub paths: Vec<PathBuf>,
+
+    /// Parameters to specify the diagnostic format.
+    #[command(flatten)]
+    pub diagnostic: DiagnosticArgs,
+}
+
+/// Run the tests of the policies.
+pub(crate) fn command(
+    logger: impl Logger + Sync + Clone,
+    args: &PolicyTestArgs,
+) -> Result<ExitDirectives, DiagnosticMessages> {
+    // The tests are evaluated with the same engine as `registry check`.
+    let mut engine = Engine::new();
+    _ = engine.add_policy("defaults/rego/semconv.rego", SEMCONV_REGO)?;
+    let mut fixtures = vec![];
+    for path in &args.paths {
+        engine.add_policy_from_file_or_dir(path)?;
+        fixtures.extend(load_policy_tests(path)?);
+    }
+
+    let tests: Vec<_> = engine.policy_tests().iter().chain(&fixtures).collect();
+    let mut failures = vec![];
+    for test in &tests {
+        match test.run(&engine) {
+            Ok(()) => logger.success(&format!("{} ({})", test.name, test.file)),
+            Err(e) => {
+                logger.error(&format!("{} ({})", test.name, test.file));
+                failures.push(e);
+            }
+        }
+    }
+    logger.log(&format!(
+        "{} policy tests, {} passed, {} failed",
+        tests.len(),
+        tests.len() - failures.len(),
+        failures.len()
+    ));
+
+    if !failures.is_empty() {
+        return Err(weaver_checker::Error::compound(failures).into());
+    }
+    Ok(ExitDirectives {
+        exit_code: 0,
+        quiet_mode: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use weaver_common::TestLogger;
+
+    use crate::cli::{Cli, Commands};
+    use crate::policy::test::PolicyTestArgs;
+    use crate::policy::{PolicyCommand, PolicySubCommand};
+    use crate::run_command;
+
+    #[test]
+    fn test_policy_test() {
+        let policy_test = |path: &str| {
+            let cli = Cli {
+                debug: 0,
+                quiet: false,
+                future: false,
+                offline: false,
+                registry_cache_ttl: None,
+                write_lockfile: false,
+                locked: false,
+                frozen: false,
+                command: Some(Commands::Policy(PolicyCommand {
+                    command: PolicySubCommand::Test(PolicyTestArgs {
+                        paths: vec![path.into()],
+                        diagnostic: Default::default(),
+                    }),
+                })),
+            };
+            run_command(&cli, TestLogger::new()).exit_code
+        };
+
+        // The fixtures of the policies of the repository pass.
+        assert_eq!(policy_test("data/policy_tests/"), 0);
+        // Some tests of the weaver_checker test data fail on purpose.
+        assert_eq!(policy_test("crates/weaver_checker/data/policy_tests/"), 1);
+    }
+}
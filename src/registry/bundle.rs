@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bundle semantic convention registries into a self-contained archive.
+
+use crate::registry::RegistryArgs;
+use crate::{DiagnosticArgs, ExitDirectives};
+use clap::Args;
+use std::path::PathBuf;
+use weaver_cache::bundle::write_bundle;
+use weaver_cache::RegistryCache;
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::Logger;
+
+/// Parameters for the `registry bundle` sub-command
+#[derive(Debug, Args)]
+pub struct RegistryBundleArgs {
+    /// Parameters to specify the semantic convention registry
+    #[command(flatten)]
+    registry: RegistryArgs,
+
+    /// Path of the bundle to write. The format is selected by the extension:
+    /// `.tar`, `.tar.gz` (or `.tgz`), or `.tar.zst`.
+    #[arg(short = 'o', long, default_value = "registry-bundle.tar.gz")]
+    output: PathBuf,
+
+    /// Templates directory to include in the bundle.
+    #[arg(short = 't', long)]
+    templates: Option<PathBuf>,
+
+    /// Policy files or directories to include in the bundle. This option can
+    /// be repeated.
+    #[arg(short = 'p', long = "policy")]
+    policies: Vec<PathBuf>,
+
+    /// Parameters to specify the diagnostic format.
+    #[command(flatten)]
+    pub diagnostic: DiagnosticArgs,
+}
+
+/// Bundle the registries, templates, and policies into a single archive.
+pub(crate) fn command(
+    logger: impl Logger + Sync + Clone,
+    args: &RegistryBundleArgs,
+    registry_cache: &RegistryCache,
+) -> Result<ExitDirectives, DiagnosticMessages> {
+    logger.loading(&format!(
+        "Bundling the registry `{}`",
+        args.registry.registry_paths()
+    ));
+
+    let registry_repos = args
+        .registry
+        .registry_repos("main", registry_cache, &logger)?;
+    let manifest = write_bundle(
+        &args.output,
+        &registry_repos,
+        args.templates.as_deref(),
+        &args.policies,
+    )?;
+
+    for registry in &manifest.registries {
+        logger.log(&format!(
+            "Bundled `{}`{} into `{}`",
+            registry.registry_path,
+            registry
+                .revision
+                .as_ref()
+                .map(|revision| format!(" (revision {})", revision))
+                .unwrap_or_default(),
+            registry.dir
+        ));
+    }
+    logger.success(&format!(
+        "Registry bundle written to `{}`",
+        args.output.display()
+    ));
+
+    Ok(ExitDirectives {
+        exit_code: 0,
+        quiet_mode: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+    use weaver_cache::registry_path::RegistryPath;
+    use weaver_cache::{RegistryCache, RegistryRepo};
+    use weaver_common::in_memory;
+
+    use crate::cli::{Cli, Commands};
+    use crate::registry::bundle::RegistryBundleArgs;
+    use crate::registry::{RegistryArgs, RegistryCommand, RegistrySubCommand};
+    use crate::run_command;
+
+    #[test]
+    fn test_registry_bundle() {
+        let logger = in_memory::Logger::new(0);
+        let dir = TempDir::new("bundle").unwrap();
+        let output = dir.path().join("bundle.tar.gz");
+        let cli = Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Bundle(RegistryBundleArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(dir.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
+                    },
+                    output: output.clone(),
+                    templates: Some("crates/weaver_codegen_test/templates/".into()),
+                    policies: vec!["test_data/registry.rego".into()],
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+
+        let exit_directive = run_command(&cli, logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
+
+        // The bundle can be used as a registry path.
+        let repo = RegistryRepo::try_new(
+            "main",
+            &RegistryPath::LocalArchive {
+                path: output.display().to_string(),
+                sub_folder: None,
+            },
+            &RegistryCache::try_default().unwrap(),
+        )
+        .unwrap();
+        assert!(repo.path().join("http-common.yaml").exists());
+        let root = repo.path().parent().unwrap().parent().unwrap();
+        assert!(root.join("templates/registry").is_dir());
+        assert!(root.join("policies/registry.rego").is_file());
+    }
+}
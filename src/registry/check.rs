@@ -2,19 +2,23 @@
 
 //! Check a semantic convention registry.
 
+use std::path::PathBuf;
+
 use clap::Args;
 use miette::Diagnostic;
 use weaver_cache::registry_path::RegistryPath;
-use weaver_cache::RegistryRepo;
-use weaver_checker::PolicyStage;
+use weaver_cache::{RegistryCache, RegistryRepo};
+use weaver_checker::fix::apply_fixes;
+use weaver_checker::{Engine, PolicyStage};
 use weaver_common::diagnostic::{DiagnosticMessages, ResultExt};
 use weaver_common::Logger;
 use weaver_forge::registry::ResolvedRegistry;
 use weaver_semconv::registry::SemConvRegistry;
 
-use crate::registry::{PolicyArgs, RegistryArgs};
+use crate::registry::{Error, PolicyArgs, RegistryArgs, ResolutionArgs};
 use crate::util::{
-    check_policy_stage, load_semconv_specs, prepare_main_registry, resolve_semconv_specs,
+    check_policy_stage, load_semconv_specs, prepare_main_registry, report_policy_coverage,
+    resolve_semconv_specs, update_policy_baseline,
 };
 use crate::{DiagnosticArgs, ExitDirectives};
 
@@ -29,10 +33,28 @@ pub struct RegistryCheckArgs {
     #[arg(long)]
     baseline_registry: Option<RegistryPath>,
 
+    /// Optional YAML or JSON file with the aggregated statistics of the observed telemetry
+    /// (e.g. collected by a live check in staging), checked with the registry by the
+    /// `observation_after_resolution` policies.
+    #[arg(long, value_name = "FILE")]
+    observations: Option<PathBuf>,
+
     /// Policy parameters
     #[command(flatten)]
     policy: PolicyArgs,
 
+    /// Check all the groups with the `after_resolution` policies. By default, when a
+    /// baseline registry is provided, only the groups added or changed since the
+    /// baseline are checked.
+    #[arg(long, default_value = "false")]
+    all_groups: bool,
+
+    /// Apply the safe fixes suggested by the policies (e.g. adding a missing stability
+    /// field) to the spec files of the local registries. The violations are still
+    /// reported, run the check again to verify the fixed registry.
+    #[arg(long, default_value = "false")]
+    fix: bool,
+
     /// Parameters to specify the diagnostic format.
     #[command(flatten)]
     pub diagnostic: DiagnosticArgs,
@@ -42,37 +64,51 @@ pub struct RegistryCheckArgs {
 pub(crate) fn command(
     logger: impl Logger + Sync + Clone,
     args: &RegistryCheckArgs,
+    registry_cache: &RegistryCache,
 ) -> Result<ExitDirectives, DiagnosticMessages> {
     let mut diag_msgs = DiagnosticMessages::empty();
     logger.log("Weaver Registry Check");
-    logger.loading(&format!("Checking registry `{}`", args.registry.registry));
-
-    // Initialize the main registry.
-    let registry_path = &args.registry.registry;
-
-    let (main_resolved_registry, mut policy_engine) =
-        prepare_main_registry(&args.registry, &args.policy, logger.clone(), &mut diag_msgs)?;
+    logger.loading(&format!(
+        "Checking registry `{}`",
+        args.registry.registry_paths()
+    ));
 
     // Initialize the baseline registry if provided.
     let baseline_registry_repo = if let Some(baseline_registry) = &args.baseline_registry {
-        Some(RegistryRepo::try_new("baseline", baseline_registry)?)
+        let baseline_registry_repo =
+            RegistryRepo::try_new("baseline", baseline_registry, registry_cache)?;
+        for warning in baseline_registry_repo.warnings() {
+            logger.warn(warning);
+        }
+        Some(baseline_registry_repo)
     } else {
         None
     };
 
+    // The baseline registry is filtered like the main registry to compare
+    // the same subset of definitions.
+    let spec_filter = args.registry.spec_filter()?;
     let baseline_semconv_specs = baseline_registry_repo
         .as_ref()
+        .filter(|_| !args.policy.skip_policies)
         .map(|repo| {
             // Baseline registry resolution should allow non-future features
             // and warnings against it should be suppressed when evaluating
             // against it as a "baseline".
-            load_semconv_specs(repo, logger.clone(), args.registry.follow_symlinks)
-                .ignore(|e| matches!(e.severity(), Some(miette::Severity::Warning)))
-                .capture_non_fatal_errors(&mut diag_msgs)
+            load_semconv_specs(
+                repo,
+                logger.clone(),
+                args.registry.follow_symlinks,
+                &spec_filter,
+            )
+            .ignore(|e| matches!(e.severity(), Some(miette::Severity::Warning)))
+            .capture_non_fatal_errors(&mut diag_msgs)
         })
         .transpose()?;
 
-    if let Some(policy_engine) = policy_engine.as_mut() {
+    // The baseline registry is resolved first to only check the changed
+    // groups with the `after_resolution` policies.
+    let baseline_resolved_registry =
         if let (Some(baseline_registry_repo), Some(baseline_semconv_specs)) =
             (baseline_registry_repo, baseline_semconv_specs)
         {
@@ -80,37 +116,113 @@ pub(crate) fn command(
                 baseline_registry_repo.id(),
                 baseline_semconv_specs,
             );
-            let baseline_resolved_schema =
-                resolve_semconv_specs(&mut baseline_registry, logger.clone())
-                    .combine_diag_msgs_with(&diag_msgs)?;
-            let baseline_resolved_registry = ResolvedRegistry::try_from_resolved_registry(
-                baseline_resolved_schema
-                    .registry(baseline_registry_repo.id())
-                    .expect("Failed to get the registry from the baseline resolved schema"),
-                baseline_resolved_schema.catalog(),
+            let baseline_resolved_schema = resolve_semconv_specs(
+                &mut baseline_registry,
+                std::slice::from_ref(&baseline_registry_repo),
+                &ResolutionArgs {
+                    severity_overrides: args.diagnostic.severity_overrides(),
+                    ..ResolutionArgs::default()
+                },
+                args.registry.resolution_cache(),
+                logger.clone(),
+                &mut diag_msgs,
             )
             .combine_diag_msgs_with(&diag_msgs)?;
-
-            // Check the policies against the resolved registry (`PolicyState::ComparisonAfterResolution`).
-            check_policy_stage(
-                policy_engine,
-                PolicyStage::ComparisonAfterResolution,
-                &registry_path.to_string(),
-                &main_resolved_registry,
-                &[baseline_resolved_registry],
+            Some(
+                ResolvedRegistry::try_from_resolved_registry(
+                    baseline_resolved_schema
+                        .registry(baseline_registry_repo.id())
+                        .expect("Failed to get the registry from the baseline resolved schema"),
+                    baseline_resolved_schema.catalog(),
+                )
+                .combine_diag_msgs_with(&diag_msgs)?,
             )
-            .inspect(|_, violations| {
-                if let Some(violations) = violations {
-                    logger.success(&format!(
-                        "All `comparison_after_resolution` policies checked ({} violations found)",
-                        violations.len()
-                    ));
-                } else {
-                    logger.success("No `comparison_after_resolution` policy violation");
-                }
-            })
-            .capture_non_fatal_errors(&mut diag_msgs)?;
-        }
+        } else {
+            None
+        };
+
+    // Initialize the main registry.
+    let (main_resolved_registry, mut policy_engine) = prepare_main_registry(
+        &args.registry,
+        &args.policy,
+        &ResolutionArgs {
+            severity_overrides: args.diagnostic.severity_overrides(),
+            ..ResolutionArgs::default()
+        },
+        baseline_resolved_registry
+            .as_ref()
+            .filter(|_| !args.all_groups),
+        None,
+        registry_cache,
+        logger.clone(),
+        &mut diag_msgs,
+    )?;
+
+    if let (Some(policy_engine), Some(baseline_resolved_registry)) =
+        (policy_engine.as_mut(), baseline_resolved_registry)
+    {
+        // Check the policies against the resolved registry (`PolicyState::ComparisonAfterResolution`).
+        check_policy_stage(
+            policy_engine,
+            PolicyStage::ComparisonAfterResolution,
+            &args.registry.registry_paths(),
+            &main_resolved_registry,
+            &[baseline_resolved_registry],
+        )
+        .inspect(|_, violations| {
+            if let Some(violations) = violations {
+                logger.success(&format!(
+                    "All `comparison_after_resolution` policies checked ({} violations found)",
+                    violations.len()
+                ));
+            } else {
+                logger.success("No `comparison_after_resolution` policy violation");
+            }
+        })
+        .capture_non_fatal_errors(&mut diag_msgs)?;
+    }
+
+    if let (Some(policy_engine), Some(observations)) =
+        (policy_engine.as_mut(), args.observations.as_ref())
+    {
+        let invalid_observations = |error: String| Error::InvalidObservations {
+            file: observations.clone(),
+            error,
+        };
+        let content = std::fs::read_to_string(observations)
+            .map_err(|e| invalid_observations(e.to_string()))?;
+        let observed: serde_json::Value =
+            serde_yaml::from_str(&content).map_err(|e| invalid_observations(e.to_string()))?;
+        // Check the policies against the resolved registry and the observed
+        // telemetry (`PolicyState::ObservationAfterResolution`).
+        check_policy_stage::<_, ()>(
+            policy_engine,
+            PolicyStage::ObservationAfterResolution,
+            &observations.display().to_string(),
+            &serde_json::json!({
+                "registry": main_resolved_registry,
+                "observations": observed,
+            }),
+            &[],
+        )
+        .inspect(|_, violations| {
+            if let Some(violations) = violations {
+                logger.success(&format!(
+                    "All `observation_after_resolution` policies checked ({} violations found)",
+                    violations.len()
+                ));
+            } else {
+                logger.success("No `observation_after_resolution` policy violation");
+            }
+        })
+        .capture_non_fatal_errors(&mut diag_msgs)?;
+    }
+
+    update_policy_baseline(&args.policy, policy_engine.as_ref(), logger.clone())?;
+    report_policy_coverage(&args.policy, policy_engine.as_ref(), logger.clone())?;
+
+    if let (true, Some(policy_engine)) = (args.fix, policy_engine.as_ref()) {
+        apply_policy_fixes(&args.registry, policy_engine, logger.clone())?;
     }
 
     if !diag_msgs.is_empty() {
@@ -123,38 +235,252 @@ pub(crate) fn command(
     })
 }
 
+/// Applies the safe fixes suggested by the policies to the YAML files of the
+/// local registries (the remote registries are left untouched).
+fn apply_policy_fixes(
+    registry_args: &RegistryArgs,
+    policy_engine: &Engine,
+    logger: impl Logger + Sync + Clone,
+) -> Result<(), Error> {
+    let violations = policy_engine.fixes();
+    if violations.is_empty() {
+        logger.log("No policy fix to apply");
+        return Ok(());
+    }
+
+    let mut applied = vec![false; violations.len()];
+    for registry_path in &registry_args.registry {
+        let RegistryPath::LocalFolder { path } = registry_path else {
+            logger.warn(&format!(
+                "The policy fixes are not applied to `{}`, which is not a local folder",
+                registry_path
+            ));
+            continue;
+        };
+        for entry in walkdir::WalkDir::new(path)
+            .sort_by_file_name()
+            .into_iter()
+            .flatten()
+        {
+            let is_yaml = entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml");
+            if !entry.file_type().is_file() || !is_yaml {
+                continue;
+            }
+            let fix_failed = |error: String| Error::PolicyFixFailed {
+                file: entry.path().to_path_buf(),
+                error,
+            };
+            let content =
+                std::fs::read_to_string(entry.path()).map_err(|e| fix_failed(e.to_string()))?;
+            let (fixed_content, fixed) = apply_fixes(&content, &violations);
+            if fixed_content != content {
+                std::fs::write(entry.path(), fixed_content)
+                    .map_err(|e| fix_failed(e.to_string()))?;
+                logger.log(&format!(
+                    "{} policy fixes applied to `{}`",
+                    fixed.len(),
+                    entry.path().display()
+                ));
+            }
+            for index in fixed {
+                applied[index] = true;
+            }
+        }
+    }
+
+    let applied_count = applied.iter().filter(|applied| **applied).count();
+    logger.success(&format!("{} policy fixes applied", applied_count));
+    if applied_count < violations.len() {
+        logger.warn(&format!(
+            "{} policy fixes can't be applied safely and must be applied manually",
+            violations.len() - applied_count
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
+    use tempdir::TempDir;
+    use weaver_cache::bundle::write_bundle;
+    use weaver_cache::{RegistryCache, RegistryRepo};
+    use weaver_checker::violation::Severity;
+    use weaver_checker::{Engine, PolicyStage};
     use weaver_common::TestLogger;
 
     use crate::cli::{Cli, Commands};
     use crate::registry::check::RegistryCheckArgs;
     use crate::registry::{
-        semconv_registry, PolicyArgs, RegistryArgs, RegistryCommand, RegistryPath,
-        RegistrySubCommand,
+        semconv_registry, PolicyArgs, PolicyCoverageFormat, RegistryArgs, RegistryCommand,
+        RegistryPath, RegistrySubCommand,
     };
     use crate::run_command;
 
+    #[test]
+    fn test_registry_semver_range_with_comma() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from([
+            "weaver",
+            "registry",
+            "check",
+            "-r",
+            "https://github.com/open-telemetry/semantic-conventions.git@>=1.26, <2[model]",
+            "-r",
+            "local/registry",
+        ])
+        .expect("Failed to parse the command line");
+        let Some(Commands::Registry(RegistryCommand {
+            command: RegistrySubCommand::Check(args),
+        })) = cli.command
+        else {
+            panic!("Expected a registry check command");
+        };
+        assert_eq!(args.registry.registry.len(), 2);
+    }
+
+    #[test]
+    fn test_registry_check_diagnostic_severity() {
+        use clap::Parser;
+
+        let registry_dir = TempDir::new("registry").expect("Failed to create temp dir");
+        let group = |brief: &str| {
+            format!(
+                "
+groups:
+  - id: registry.test
+    type: attribute_group
+    brief: \"{brief}\"
+    attributes:
+      - id: test.{brief}
+        type: string
+        brief: \"Attribute.\"
+        examples: [\"a\"]
+"
+            )
+        };
+        std::fs::write(registry_dir.path().join("a.yaml"), group("a"))
+            .expect("Failed to write the spec");
+        std::fs::write(registry_dir.path().join("b.yaml"), group("b"))
+            .expect("Failed to write the spec");
+
+        let parse = |options: &[&str]| {
+            let mut args = vec![
+                "weaver",
+                "--quiet",
+                "registry",
+                "check",
+                "-r",
+                registry_dir.path().to_str().expect("Invalid temp dir"),
+                "--skip-policies",
+                "--no-resolution-cache",
+            ];
+            args.extend(options);
+            Cli::try_parse_from(args).expect("Failed to parse the command line")
+        };
+        let messages = |options: &[&str]| {
+            let Some(Commands::Registry(registry_cmd)) = parse(options).command else {
+                panic!("Expected a registry command");
+            };
+            semconv_registry(
+                TestLogger::new(),
+                &registry_cmd,
+                &RegistryCache::try_default().unwrap(),
+            )
+            .command_result
+            .err()
+            .map_or(0, |diag_msgs| diag_msgs.len())
+        };
+
+        // The duplicate group id is reported as a warning, unless the check
+        // is skipped or promoted to an error.
+        assert_eq!(messages(&[]), 1);
+        assert_eq!(run_command(&parse(&[]), TestLogger::new()).exit_code, 0);
+        assert_eq!(
+            messages(&["--diagnostic-severity", "duplicate_group_id=ignore"]),
+            0
+        );
+        assert_eq!(
+            run_command(
+                &parse(&["--diagnostic-severity", "DuplicateGroupId=error"]),
+                TestLogger::new()
+            )
+            .exit_code,
+            1
+        );
+
+        // The briefs of the groups don't end with a period.
+        let prose_lint = [
+            "--diagnostic-severity",
+            "duplicate_group_id=ignore",
+            "--prose-lint",
+            "trailing_period",
+        ];
+        assert_eq!(messages(&prose_lint), 2);
+        assert_eq!(
+            run_command(
+                &parse(
+                    &[
+                        &prose_lint[..],
+                        &["--diagnostic-severity", "prose_lint_warning=error"]
+                    ]
+                    .concat()
+                ),
+                TestLogger::new()
+            )
+            .exit_code,
+            1
+        );
+    }
+
     #[test]
     fn test_registry_check_exit_code() {
         let logger = TestLogger::new();
+        let cache_dir = TempDir::new("cache").expect("Failed to create temp dir");
         let cli = Cli {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::Check(RegistryCheckArgs {
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(cache_dir.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     baseline_registry: None,
+                    observations: None,
+                    all_groups: false,
+                    fix: false,
                     policy: PolicyArgs {
                         policies: vec![],
                         skip_policies: true,
                         display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Severity::Violation,
                     },
                     diagnostic: Default::default(),
                 }),
@@ -170,19 +496,41 @@ mod tests {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::Check(RegistryCheckArgs {
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(cache_dir.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     baseline_registry: None,
+                    observations: None,
+                    all_groups: false,
+                    fix: false,
                     policy: PolicyArgs {
                         policies: vec![],
                         skip_policies: false,
                         display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Severity::Violation,
                     },
                     diagnostic: Default::default(),
                 }),
@@ -194,40 +542,597 @@ mod tests {
         assert_eq!(exit_directive.exit_code, 1);
     }
 
+    #[test]
+    fn test_registry_check_enum_member_evolution() {
+        let cli = |baseline: &str, policy: RegistryPath| Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Check(RegistryCheckArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "data/enum_evolution/current/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: true,
+                        resolution_cache_dir: None,
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
+                    },
+                    baseline_registry: Some(RegistryPath::LocalFolder {
+                        path: baseline.to_owned(),
+                    }),
+                    observations: None,
+                    all_groups: false,
+                    fix: false,
+                    policy: PolicyArgs {
+                        policies: vec![policy],
+                        skip_policies: false,
+                        display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Severity::Violation,
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+        let check = |baseline: &str, policy: RegistryPath| {
+            run_command(&cli(baseline, policy), TestLogger::new()).exit_code
+        };
+
+        let policy = RegistryPath::LocalFolder {
+            path: "test_data/compatibility_check.rego".to_owned(),
+        };
+        // Compared to itself, the registry doesn't rename or remove any member.
+        assert_eq!(check("data/enum_evolution/current/", policy.clone()), 0);
+        // A member of the baseline is renamed and another one is removed.
+        assert_eq!(check("data/enum_evolution/baseline/", policy.clone()), 1);
+        let Some(Commands::Registry(registry_cmd)) =
+            cli("data/enum_evolution/baseline/", policy).command
+        else {
+            panic!("Expected a registry command");
+        };
+        let diag_msgs = semconv_registry(
+            TestLogger::new(),
+            &registry_cmd,
+            &RegistryCache::try_default().unwrap(),
+        )
+        .command_result
+        .expect_err("Expected policy violations");
+        let diag_msgs = serde_json::to_value(&diag_msgs).unwrap();
+        let mut violations: Vec<_> = diag_msgs
+            .as_array()
+            .expect("Expected a list of diagnostics")
+            .iter()
+            .map(|diag_msg| {
+                let violation = &diag_msg["error"]["violation"];
+                (
+                    violation["id"].as_str().unwrap_or_default(),
+                    violation["attr"].as_str().unwrap_or_default(),
+                )
+            })
+            .collect();
+        violations.sort();
+        assert_eq!(
+            violations,
+            vec![
+                (
+                    "Enum member 'beta' of attribute 'test.kind' has been renamed to 'beta2'",
+                    "test.kind"
+                ),
+                (
+                    "Enum member 'gamma' of attribute 'test.kind' no longer exists",
+                    "test.kind"
+                ),
+            ]
+        );
+
+        // The deprecated member of the fixture follows the OTel policies.
+        let otel_policies = std::fs::read_to_string("schemas/otel_policies.rego")
+            .unwrap()
+            .replacen("package otel", "package before_resolution", 1);
+        let spec = |path: &str| -> serde_json::Value {
+            serde_yaml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+        };
+        let mut engine = Engine::new();
+        _ = engine
+            .add_policy("otel_policies.rego", &otel_policies)
+            .unwrap();
+        engine
+            .add_data(&spec("data/enum_evolution/baseline/registry.test.yaml"))
+            .unwrap();
+        engine
+            .set_input(&spec("data/enum_evolution/current/registry.test.yaml"))
+            .unwrap();
+        assert_eq!(engine.check(PolicyStage::BeforeResolution).unwrap(), vec![]);
+
+        // The same policy loaded from the `policies` directory of a bundle.
+        let dir = TempDir::new("policy_bundle").unwrap();
+        let bundle = dir.path().join("bundle.tar.gz");
+        let registry = RegistryRepo::try_new(
+            "main",
+            &RegistryPath::LocalFolder {
+                path: "data/enum_evolution/current/".to_owned(),
+            },
+            &RegistryCache::try_default().unwrap(),
+        )
+        .unwrap();
+        _ = write_bundle(
+            &bundle,
+            &[registry],
+            None,
+            &["test_data/compatibility_check.rego".into()],
+        )
+        .unwrap();
+        let bundled_policy = RegistryPath::LocalArchive {
+            path: bundle.display().to_string(),
+            sub_folder: Some("policies".to_owned()),
+        };
+        assert_eq!(check("data/enum_evolution/baseline/", bundled_policy), 1);
+    }
+
+    #[test]
+    fn test_registry_check_fail_on() {
+        let check = |fail_on: Severity| {
+            let cli = Cli {
+                debug: 0,
+                quiet: false,
+                future: false,
+                offline: false,
+                registry_cache_ttl: None,
+                write_lockfile: false,
+                locked: false,
+                frozen: false,
+                command: Some(Commands::Registry(RegistryCommand {
+                    command: RegistrySubCommand::Check(RegistryCheckArgs {
+                        registry: RegistryArgs {
+                            registry: vec![RegistryPath::LocalFolder {
+                                path: "data/enum_evolution/current/".to_owned(),
+                            }],
+                            follow_symlinks: false,
+                            no_resolution_cache: true,
+                            resolution_cache_dir: None,
+                            include_spec: vec![],
+                            exclude_spec: vec![],
+                            prose_lint: vec![],
+                        },
+                        baseline_registry: None,
+                        observations: None,
+                        all_groups: false,
+                        fix: false,
+                        policy: PolicyArgs {
+                            policies: vec![RegistryPath::LocalFolder {
+                                path: "test_data/brief_period.lint.yaml".to_owned(),
+                            }],
+                            skip_policies: false,
+                            display_policy_coverage: false,
+                            policy_coverage: None,
+                            policy_coverage_format: Default::default(),
+                            policy_baseline: None,
+                            update_baseline: false,
+                            per_group_policies: false,
+                            policy_sandbox: false,
+                            policy_timeout: None,
+                            policy_max_memory: None,
+                            fail_on,
+                        },
+                        diagnostic: Default::default(),
+                    }),
+                })),
+            };
+            run_command(&cli, TestLogger::new()).exit_code
+        };
+
+        // The brief without a trailing period is only reported as a warning.
+        assert_eq!(check(Severity::Violation), 0);
+        // The warnings fail the check when promoted.
+        assert_eq!(check(Severity::Warning), 1);
+        assert_eq!(check(Severity::Info), 1);
+    }
+
+    #[test]
+    fn test_registry_check_changed_groups() {
+        let check = |all_groups: bool| {
+            let cli = Cli {
+                debug: 0,
+                quiet: false,
+                future: false,
+                offline: false,
+                registry_cache_ttl: None,
+                write_lockfile: false,
+                locked: false,
+                frozen: false,
+                command: Some(Commands::Registry(RegistryCommand {
+                    command: RegistrySubCommand::Check(RegistryCheckArgs {
+                        registry: RegistryArgs {
+                            registry: vec![RegistryPath::LocalFolder {
+                                path: "data/enum_evolution/current/".to_owned(),
+                            }],
+                            follow_symlinks: false,
+                            no_resolution_cache: true,
+                            resolution_cache_dir: None,
+                            include_spec: vec![],
+                            exclude_spec: vec![],
+                            prose_lint: vec![],
+                        },
+                        baseline_registry: Some(RegistryPath::LocalFolder {
+                            path: "data/enum_evolution/current/".to_owned(),
+                        }),
+                        observations: None,
+                        all_groups,
+                        fix: false,
+                        policy: PolicyArgs {
+                            policies: vec![RegistryPath::LocalFolder {
+                                path: "test_data/group_brief_period.lint.yaml".to_owned(),
+                            }],
+                            skip_policies: false,
+                            display_policy_coverage: false,
+                            policy_coverage: None,
+                            policy_coverage_format: Default::default(),
+                            policy_baseline: None,
+                            update_baseline: false,
+                            per_group_policies: false,
+                            policy_sandbox: false,
+                            policy_timeout: None,
+                            policy_max_memory: None,
+                            fail_on: Severity::Violation,
+                        },
+                        diagnostic: Default::default(),
+                    }),
+                })),
+            };
+            run_command(&cli, TestLogger::new()).exit_code
+        };
+
+        // The group violating the policy is unchanged since the baseline.
+        assert_eq!(check(false), 0);
+        assert_eq!(check(true), 1);
+    }
+
+    #[test]
+    fn test_registry_check_policy_coverage() {
+        let temp_dir = TempDir::new("weaver").expect("Failed to create a temp dir");
+        let coverage = temp_dir.path().join("policy_coverage.lcov");
+        let cli = Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Check(RegistryCheckArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "data/enum_evolution/current/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: true,
+                        resolution_cache_dir: None,
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
+                    },
+                    baseline_registry: Some(RegistryPath::LocalFolder {
+                        path: "data/enum_evolution/baseline/".to_owned(),
+                    }),
+                    observations: None,
+                    all_groups: false,
+                    fix: false,
+                    policy: PolicyArgs {
+                        policies: vec![RegistryPath::LocalFolder {
+                            path: "test_data/compatibility_check.rego".to_owned(),
+                        }],
+                        skip_policies: false,
+                        display_policy_coverage: false,
+                        policy_coverage: Some(coverage.clone()),
+                        policy_coverage_format: PolicyCoverageFormat::Lcov,
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Severity::Violation,
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+        assert_eq!(run_command(&cli, TestLogger::new()).exit_code, 1);
+
+        // The report covers the policies of all the stages.
+        let lcov = std::fs::read_to_string(&coverage).expect("Failed to read the report");
+        assert!(lcov.contains("SF:defaults/rego/semconv.rego\n"), "{}", lcov);
+        assert!(lcov.contains("compatibility_check.rego\n"), "{}", lcov);
+        assert!(lcov.contains(",deny:"), "{}", lcov);
+        assert!(lcov.ends_with("end_of_record\n"));
+    }
+
+    #[test]
+    fn test_registry_check_fix() {
+        let temp_dir = TempDir::new("weaver").expect("Failed to create a temp dir");
+        let spec = temp_dir.path().join("registry.yaml");
+        std::fs::write(
+            &spec,
+            "groups:
+  - id: registry.fix
+    type: attribute_group
+    brief: Attributes to fix.
+    attributes:
+      # A comment preserved by the fix.
+      - id: fix.name
+        type: string
+        stability: development
+        brief: A name
+        examples: [\"a\"]
+",
+        )
+        .expect("Failed to write the spec file");
+        let check = |fix: bool| {
+            let cli = Cli {
+                debug: 0,
+                quiet: false,
+                future: false,
+                offline: false,
+                registry_cache_ttl: None,
+                write_lockfile: false,
+                locked: false,
+                frozen: false,
+                command: Some(Commands::Registry(RegistryCommand {
+                    command: RegistrySubCommand::Check(RegistryCheckArgs {
+                        registry: RegistryArgs {
+                            registry: vec![RegistryPath::LocalFolder {
+                                path: temp_dir.path().display().to_string(),
+                            }],
+                            follow_symlinks: false,
+                            no_resolution_cache: true,
+                            resolution_cache_dir: None,
+                            include_spec: vec![],
+                            exclude_spec: vec![],
+                            prose_lint: vec![],
+                        },
+                        baseline_registry: None,
+                        observations: None,
+                        all_groups: false,
+                        fix,
+                        policy: PolicyArgs {
+                            policies: vec![RegistryPath::LocalFolder {
+                                path: "test_data/brief_fix.rego".to_owned(),
+                            }],
+                            skip_policies: false,
+                            display_policy_coverage: false,
+                            policy_coverage: None,
+                            policy_coverage_format: Default::default(),
+                            policy_baseline: None,
+                            update_baseline: false,
+                            per_group_policies: false,
+                            policy_sandbox: false,
+                            policy_timeout: None,
+                            policy_max_memory: None,
+                            fail_on: Severity::Violation,
+                        },
+                        diagnostic: Default::default(),
+                    }),
+                })),
+            };
+            run_command(&cli, TestLogger::new()).exit_code
+        };
+
+        // The violation is reported, but fixed in the spec file.
+        assert_eq!(check(true), 1);
+        let fixed_spec = std::fs::read_to_string(&spec).expect("Failed to read the spec file");
+        assert!(fixed_spec.contains("        brief: A name.\n"));
+        assert!(fixed_spec.contains("# A comment preserved by the fix."));
+        assert_eq!(check(false), 0);
+    }
+
+    #[test]
+    fn test_registry_check_observations() {
+        let temp_dir = TempDir::new("weaver").expect("Failed to create a temp dir");
+        let observed_all = temp_dir.path().join("observations.yaml");
+        std::fs::write(
+            &observed_all,
+            "attributes:\n  - name: test.kind\n    count: 12\n",
+        )
+        .expect("Failed to write the observations file");
+        let check = |observations: PathBuf| {
+            let cli = Cli {
+                debug: 0,
+                quiet: false,
+                future: false,
+                offline: false,
+                registry_cache_ttl: None,
+                write_lockfile: false,
+                locked: false,
+                frozen: false,
+                command: Some(Commands::Registry(RegistryCommand {
+                    command: RegistrySubCommand::Check(RegistryCheckArgs {
+                        registry: RegistryArgs {
+                            registry: vec![RegistryPath::LocalFolder {
+                                path: "data/enum_evolution/current/".to_owned(),
+                            }],
+                            follow_symlinks: false,
+                            no_resolution_cache: true,
+                            resolution_cache_dir: None,
+                            include_spec: vec![],
+                            exclude_spec: vec![],
+                            prose_lint: vec![],
+                        },
+                        baseline_registry: None,
+                        observations: Some(observations),
+                        all_groups: false,
+                        fix: false,
+                        policy: PolicyArgs {
+                            policies: vec![RegistryPath::LocalFolder {
+                                path: "test_data/observation_check.rego".to_owned(),
+                            }],
+                            skip_policies: false,
+                            display_policy_coverage: false,
+                            policy_coverage: None,
+                            policy_coverage_format: Default::default(),
+                            policy_baseline: None,
+                            update_baseline: false,
+                            per_group_policies: false,
+                            policy_sandbox: false,
+                            policy_timeout: None,
+                            policy_max_memory: None,
+                            fail_on: Severity::Violation,
+                        },
+                        diagnostic: Default::default(),
+                    }),
+                })),
+            };
+            run_command(&cli, TestLogger::new()).exit_code
+        };
+
+        // The stable attribute is observed.
+        assert_eq!(check(observed_all), 0);
+        // The stable attribute isn't observed, and an unknown attribute is.
+        assert_eq!(check(PathBuf::from("test_data/observations.yaml")), 1);
+        // Missing observations file.
+        assert_eq!(check(PathBuf::from("test_data/missing.yaml")), 1);
+    }
+
+    #[test]
+    fn test_registry_check_baseline() {
+        let temp_dir = TempDir::new("weaver").expect("Failed to create a temp dir");
+        let baseline = temp_dir.path().join("baseline.yaml");
+        let check = |policy_baseline: Option<PathBuf>, update_baseline: bool| {
+            let cli = Cli {
+                debug: 0,
+                quiet: false,
+                future: false,
+                offline: false,
+                registry_cache_ttl: None,
+                write_lockfile: false,
+                locked: false,
+                frozen: false,
+                command: Some(Commands::Registry(RegistryCommand {
+                    command: RegistrySubCommand::Check(RegistryCheckArgs {
+                        registry: RegistryArgs {
+                            registry: vec![RegistryPath::LocalFolder {
+                                path: "data/enum_evolution/current/".to_owned(),
+                            }],
+                            follow_symlinks: false,
+                            no_resolution_cache: true,
+                            resolution_cache_dir: None,
+                            include_spec: vec![],
+                            exclude_spec: vec![],
+                            prose_lint: vec![],
+                        },
+                        baseline_registry: None,
+                        observations: None,
+                        all_groups: false,
+                        fix: false,
+                        policy: PolicyArgs {
+                            policies: vec![RegistryPath::LocalFolder {
+                                path: "test_data/brief_period.lint.yaml".to_owned(),
+                            }],
+                            skip_policies: false,
+                            display_policy_coverage: false,
+                            policy_coverage: None,
+                            policy_coverage_format: Default::default(),
+                            policy_baseline,
+                            update_baseline,
+                            per_group_policies: false,
+                            policy_sandbox: false,
+                            policy_timeout: None,
+                            policy_max_memory: None,
+                            fail_on: Severity::Warning,
+                        },
+                        diagnostic: Default::default(),
+                    }),
+                })),
+            };
+            run_command(&cli, TestLogger::new()).exit_code
+        };
+
+        // The baseline file must exist.
+        assert_eq!(check(Some(baseline.clone()), false), 1);
+        assert_eq!(check(None, false), 1);
+        // The known violations are recorded and not reported.
+        assert_eq!(check(Some(baseline.clone()), true), 0);
+        assert!(baseline.exists());
+        assert_eq!(check(Some(baseline), false), 0);
+    }
+
     #[test]
     fn test_semconv_registry() {
         let logger = TestLogger::new();
+        let cache_dir = TempDir::new("cache").expect("Failed to create temp dir");
 
-        let registry_cmd = RegistryCommand {
-            command: RegistrySubCommand::Check(RegistryCheckArgs {
-                registry: RegistryArgs {
-                    registry: RegistryPath::LocalFolder {
-                        path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
+        // The same violations are observed when the `after_resolution`
+        // policies are evaluated on each group in parallel.
+        for per_group_policies in [false, true] {
+            let registry_cmd = RegistryCommand {
+                command: RegistrySubCommand::Check(RegistryCheckArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(cache_dir.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
-                    follow_symlinks: false,
-                },
-                baseline_registry: None,
-                policy: PolicyArgs {
-                    policies: vec![],
-                    skip_policies: false,
-                    display_policy_coverage: false,
-                },
-                diagnostic: Default::default(),
-            }),
-        };
+                    baseline_registry: None,
+                    observations: None,
+                    all_groups: false,
+                    fix: false,
+                    policy: PolicyArgs {
+                        policies: vec![],
+                        skip_policies: false,
+                        display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Severity::Violation,
+                    },
+                    diagnostic: Default::default(),
+                }),
+            };
 
-        let cmd_result = semconv_registry(logger.clone(), &registry_cmd);
-        // Violations should be observed.
-        assert!(cmd_result.command_result.is_err());
-        if let Err(diag_msgs) = cmd_result.command_result {
-            assert!(!diag_msgs.is_empty());
-            assert_eq!(
-                diag_msgs.len(),
-                12 /* allow_custom_values */
-                + 13 /* before resolution */
-                    + 3 /* metric after resolution */
-                    + 9 /* http after resolution */
+            let cmd_result = semconv_registry(
+                logger.clone(),
+                &registry_cmd,
+                &RegistryCache::try_default().unwrap(),
             );
+            // Violations should be observed.
+            assert!(cmd_result.command_result.is_err());
+            if let Err(diag_msgs) = cmd_result.command_result {
+                assert!(!diag_msgs.is_empty());
+                assert_eq!(
+                    diag_msgs.len(),
+                    12 /* allow_custom_values */
+                    + 13 /* before resolution */
+                        + 3 /* metric after resolution */
+                        + 9 /* http after resolution */
+                );
+            }
         }
     }
 }
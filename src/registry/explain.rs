@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Explain the provenance of a resolved group or attribute, i.e. which spec
+//! files and which `extends`/`ref`/overrides produced the resolved values.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+use weaver_forge::registry::{ResolvedGroup, ResolvedRegistry};
+
+/// Returns a human-readable provenance report for the group or the attribute
+/// identified by `id`, or `None` if no such group or attribute exists.
+pub(crate) fn explain(registry: &ResolvedRegistry, id: &str) -> Option<String> {
+    let groups: HashMap<&str, &ResolvedGroup> = registry
+        .groups
+        .iter()
+        .map(|group| (group.id.as_str(), group))
+        .collect();
+    let mut report = String::new();
+
+    if let Some(group) = groups.get(id) {
+        explain_group(&mut report, &groups, group);
+    }
+
+    for group in registry.groups.iter() {
+        if group.attributes.iter().any(|attr| attr.name == id) {
+            _ = writeln!(report, "Attribute `{}` in group `{}`", id, group.id);
+            explain_attribute(&mut report, &groups, group, id);
+            _ = writeln!(report);
+        }
+    }
+
+    if report.is_empty() {
+        None
+    } else {
+        Some(report)
+    }
+}
+
+/// Explains the provenance of a group and of all its attributes.
+fn explain_group(
+    report: &mut String,
+    groups: &HashMap<&str, &ResolvedGroup>,
+    group: &ResolvedGroup,
+) {
+    _ = writeln!(report, "Group `{}`", group.id);
+    _ = writeln!(report, "  - defined in {}", source_file(group));
+
+    // Follow the `extends` clauses (depth-first, in declaration order).
+    let mut visited = HashSet::from([group.id.as_str()]);
+    let mut to_visit: Vec<&str> = extended_groups(group).rev().collect();
+    while let Some(parent_id) = to_visit.pop() {
+        match groups.get(parent_id) {
+            Some(parent) => {
+                if visited.insert(parent_id) {
+                    _ = writeln!(
+                        report,
+                        "  - extends `{}` defined in {}",
+                        parent_id,
+                        source_file(parent)
+                    );
+                    to_visit.extend(extended_groups(parent).rev());
+                }
+            }
+            None => {
+                _ = writeln!(report, "  - extends `{}`", parent_id);
+            }
+        }
+    }
+    _ = writeln!(report);
+
+    for attr in group.attributes.iter() {
+        _ = writeln!(report, "Attribute `{}`", attr.name);
+        explain_attribute(report, groups, group, &attr.name);
+        _ = writeln!(report);
+    }
+}
+
+/// Explains the provenance chain of an attribute of a group, from the group
+/// down to the group where the attribute is defined.
+fn explain_attribute(
+    report: &mut String,
+    groups: &HashMap<&str, &ResolvedGroup>,
+    group: &ResolvedGroup,
+    attr_name: &str,
+) {
+    let mut visited = HashSet::new();
+    let mut current = group;
+
+    while visited.insert(current.id.as_str()) {
+        let Some(attr_lineage) = current
+            .lineage
+            .as_ref()
+            .and_then(|lineage| lineage.attribute(attr_name))
+        else {
+            _ = writeln!(
+                report,
+                "  - defined in group `{}` ({})",
+                current.id,
+                source_file(current)
+            );
+            return;
+        };
+
+        // Group the overridden fields by the group that actually set them.
+        let mut overrides: BTreeMap<&str, (&ResolvedGroup, BTreeSet<String>)> = BTreeMap::new();
+        for field in attr_lineage.locally_overridden_fields.iter() {
+            let origin = override_origin(groups, current, attr_name, field);
+            _ = overrides
+                .entry(origin.id.as_str())
+                .or_insert_with(|| (origin, BTreeSet::new()))
+                .1
+                .insert(field.clone());
+        }
+        for (origin, fields_set) in overrides.values() {
+            let via = if origin.id == current.id {
+                ""
+            } else {
+                " via `extends`"
+            };
+            _ = writeln!(
+                report,
+                "  - {} set in group `{}` ({}){}",
+                fields(fields_set),
+                origin.id,
+                source_file(origin),
+                via
+            );
+        }
+
+        let source_group = attr_lineage.source_group.as_str();
+        let relation = if extended_groups(current).any(|parent_id| parent_id == source_group) {
+            "extends"
+        } else {
+            "ref"
+        };
+        let inherited = if attr_lineage.inherited_fields.is_empty() {
+            "no field".to_owned()
+        } else {
+            fields(&attr_lineage.inherited_fields)
+        };
+        _ = writeln!(
+            report,
+            "  - {} inherited from group `{}` via `{}`",
+            inherited, source_group, relation
+        );
+
+        match groups.get(source_group) {
+            Some(source) => current = source,
+            None => return,
+        }
+    }
+}
+
+/// Returns the ids of the groups extended by the given group.
+fn extended_groups(group: &ResolvedGroup) -> impl DoubleEndedIterator<Item = &str> {
+    group
+        .lineage
+        .iter()
+        .flat_map(|lineage| lineage.extends())
+        .map(String::as_str)
+}
+
+/// Returns the group that set the given field of an attribute.
+///
+/// The fields overridden in a parent group are reported as locally overridden
+/// in the groups extending it, so the `extends` chain is followed as long as
+/// the parent group overrides the same field with the same value.
+fn override_origin<'a>(
+    groups: &HashMap<&str, &'a ResolvedGroup>,
+    group: &'a ResolvedGroup,
+    attr_name: &str,
+    field: &str,
+) -> &'a ResolvedGroup {
+    let value = field_value(group, attr_name, field);
+    let mut visited = HashSet::from([group.id.as_str()]);
+    let mut origin = group;
+
+    // The attribute is inherited from the first extended group defining it.
+    while let Some(parent) = extended_groups(origin)
+        .filter_map(|parent_id| groups.get(parent_id))
+        .find(|parent| parent.attributes.iter().any(|attr| attr.name == attr_name))
+    {
+        let overridden = parent
+            .lineage
+            .as_ref()
+            .and_then(|lineage| lineage.attribute(attr_name))
+            .is_some_and(|attr_lineage| attr_lineage.locally_overridden_fields.contains(field));
+        if !visited.insert(parent.id.as_str())
+            || !overridden
+            || field_value(parent, attr_name, field) != value
+        {
+            break;
+        }
+        origin = parent;
+    }
+    origin
+}
+
+/// Returns the resolved value of a field of an attribute of a group.
+fn field_value(group: &ResolvedGroup, attr_name: &str, field: &str) -> Option<serde_json::Value> {
+    group
+        .attributes
+        .iter()
+        .find(|attr| attr.name == attr_name)
+        .and_then(|attr| serde_json::to_value(attr).ok())
+        .and_then(|attr| attr.get(field).cloned())
+}
+
+/// Returns the source file of a group.
+fn source_file(group: &ResolvedGroup) -> &str {
+    group
+        .lineage
+        .as_ref()
+        .map_or("an unknown file", |lineage| lineage.source_file())
+}
+
+/// Formats a set of field names.
+fn fields(fields: &BTreeSet<String>) -> String {
+    fields
+        .iter()
+        .map(|field| format!("`{}`", field))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use weaver_forge::registry::ResolvedRegistry;
+    use weaver_resolver::SchemaResolver;
+    use weaver_semconv::registry::SemConvRegistry;
+
+    use super::explain;
+
+    fn registry() -> ResolvedRegistry {
+        let mut registry = SemConvRegistry::try_from_path_pattern(
+            "default",
+            "crates/weaver_codegen_test/semconv_registry/**/*.yaml",
+        )
+        .into_result_with_non_fatal()
+        .expect("Failed to load registry")
+        .0;
+        let schema = SchemaResolver::resolve_semantic_convention_registry(&mut registry)
+            .expect("Failed to resolve registry");
+        ResolvedRegistry::try_from_resolved_registry(
+            schema.registry("default").expect("registry not found"),
+            schema.catalog(),
+        )
+        .expect("Failed to create the resolved registry")
+    }
+
+    #[test]
+    fn test_explain() {
+        let registry = registry();
+
+        let report = explain(&registry, "metric.http.server.request.duration").unwrap();
+        assert!(report.contains("- extends `metric_attributes.http.server` defined in"));
+        assert!(report.contains(
+            "- `note`, `requirement_level` set in group `metric_attributes.http.server`"
+        ));
+        assert!(report.contains("- defined in group `registry.server`"));
+
+        let report = explain(&registry, "server.address").unwrap();
+        assert!(report.contains("Attribute `server.address` in group `registry.server`"));
+        assert!(report.contains("inherited from group `registry.server` via `ref`"));
+
+        assert!(explain(&registry, "does.not.exist").is_none());
+    }
+}
@@ -7,16 +7,20 @@ use std::path::PathBuf;
 use clap::Args;
 use serde_yaml::Value;
 
+use weaver_cache::RegistryCache;
 use weaver_common::diagnostic::DiagnosticMessages;
 use weaver_common::Logger;
 use weaver_forge::config::{Params, WeaverConfig};
 use weaver_forge::file_loader::{FileLoader, FileSystemFileLoader};
 use weaver_forge::{OutputDirective, TemplateEngine};
 
-use crate::registry::{Error, PolicyArgs, RegistryArgs};
-use crate::util::prepare_main_registry;
+use crate::registry::{Error, PolicyArgs, RegistryArgs, ResolutionArgs};
+use crate::util::{prepare_main_registry, report_policy_coverage, update_policy_baseline};
 use crate::{DiagnosticArgs, ExitDirectives};
 
+/// Number of entries displayed in the profiling report.
+const PROFILE_REPORT_SIZE: usize = 20;
+
 /// Parameters for the `registry generate` sub-command
 #[derive(Debug, Args)]
 pub struct RegistryGenerateArgs {
@@ -51,6 +55,10 @@ pub struct RegistryGenerateArgs {
     #[command(flatten)]
     registry: RegistryArgs,
 
+    /// Parameters to select the definitions of the resolved registry
+    #[command(flatten)]
+    resolution: ResolutionArgs,
+
     /// Policy parameters
     #[command(flatten)]
     policy: PolicyArgs,
@@ -60,6 +68,11 @@ pub struct RegistryGenerateArgs {
     #[arg(long, default_value = "false")]
     pub future: bool,
 
+    /// Record the time spent in each Jinja filter and each JQ filter, and
+    /// display the slowest ones once the generation is done.
+    #[arg(long, default_value = "false")]
+    pub profile_templates: bool,
+
     /// Parameters to specify the diagnostic format.
     #[command(flatten)]
     pub diagnostic: DiagnosticArgs,
@@ -85,17 +98,15 @@ fn parse_key_val(s: &str) -> Result<(String, Value), Error> {
 pub(crate) fn command(
     logger: impl Logger + Sync + Clone,
     args: &RegistryGenerateArgs,
+    registry_cache: &RegistryCache,
 ) -> Result<ExitDirectives, DiagnosticMessages> {
     logger.loading(&format!(
         "Generating artifacts for the registry `{}`",
-        args.registry.registry
+        args.registry.registry_paths()
     ));
 
     let mut diag_msgs = DiagnosticMessages::empty();
 
-    let (template_registry, _) =
-        prepare_main_registry(&args.registry, &args.policy, logger.clone(), &mut diag_msgs)?;
-
     let params = generate_params(args)?;
     let loader = FileSystemFileLoader::try_new(args.templates.join("registry"), &args.target)?;
     let config = if let Some(paths) = &args.config {
@@ -103,7 +114,34 @@ pub(crate) fn command(
     } else {
         WeaverConfig::try_from_path(loader.root())
     }?;
-    let engine = TemplateEngine::new(config, loader, params);
+    let mut engine = TemplateEngine::new(config, loader, params);
+    if args.profile_templates {
+        engine.enable_profiling();
+    }
+
+    // The params are available to all the policy stages as `data.weaver.params`.
+    let (template_registry, policy_engine) = prepare_main_registry(
+        &args.registry,
+        &args.policy,
+        &args
+            .resolution
+            .with_severity_overrides(args.diagnostic.severity_overrides()),
+        None,
+        Some(engine.policy_params()),
+        registry_cache,
+        logger.clone(),
+        &mut diag_msgs,
+    )?;
+
+    // The baseline and the coverage of the registry policies are reported
+    // before the generation, so a failing template doesn't discard them.
+    update_policy_baseline(&args.policy, policy_engine.as_ref(), logger.clone())?;
+    report_policy_coverage(&args.policy, policy_engine.as_ref(), logger.clone())?;
+
+    // The `before_generation` policies are evaluated by the template engine.
+    if let Some(policy_engine) = &policy_engine {
+        engine.set_policy_engine(policy_engine.clone());
+    }
 
     engine.generate(
         logger.clone(),
@@ -112,6 +150,13 @@ pub(crate) fn command(
         &OutputDirective::File,
     )?;
 
+    if let Some(profiler) = engine.profiler() {
+        logger.info(&format!(
+            "Slowest filters:\n{}",
+            profiler.report(PROFILE_REPORT_SIZE)
+        ));
+    }
+
     if !diag_msgs.is_empty() {
         return Err(diag_msgs);
     }
@@ -163,7 +208,7 @@ mod tests {
     use crate::cli::{Cli, Commands};
     use crate::registry::generate::RegistryGenerateArgs;
     use crate::registry::{
-        PolicyArgs, RegistryArgs, RegistryCommand, RegistryPath, RegistrySubCommand,
+        PolicyArgs, RegistryArgs, RegistryCommand, RegistryPath, RegistrySubCommand, ResolutionArgs,
     };
     use crate::run_command;
 
@@ -177,6 +222,11 @@ mod tests {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::Generate(RegistryGenerateArgs {
                     target: "rust".to_owned(),
@@ -186,17 +236,33 @@ mod tests {
                     param: None,
                     params: None,
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     policy: PolicyArgs {
                         policies: vec![],
                         skip_policies: true,
                         display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
                     },
                     future: false,
+                    profile_templates: false,
+                    resolution: ResolutionArgs::default(),
                     diagnostic: Default::default(),
                 }),
             })),
@@ -252,6 +318,11 @@ mod tests {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::Generate(RegistryGenerateArgs {
                     target: "rust".to_owned(),
@@ -261,17 +332,33 @@ mod tests {
                     param: None,
                     params: None,
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     policy: PolicyArgs {
                         policies: vec![],
                         skip_policies: false,
                         display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
                     },
                     future: false,
+                    profile_templates: false,
+                    resolution: ResolutionArgs::default(),
                     diagnostic: Default::default(),
                 }),
             })),
@@ -292,6 +379,11 @@ mod tests {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::Generate(RegistryGenerateArgs {
                     target: "rust".to_owned(),
@@ -308,17 +400,33 @@ mod tests {
                     param: None,
                     params: None,
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     policy: PolicyArgs {
                         policies: vec![],
                         skip_policies: true,
                         display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
                     },
                     future: false,
+                    profile_templates: false,
+                    resolution: ResolutionArgs::default(),
                     diagnostic: Default::default(),
                 }),
             })),
@@ -404,6 +512,11 @@ mod tests {
                 debug: 0,
                 quiet: false,
                 future: false,
+                offline: false,
+                registry_cache_ttl: None,
+                write_lockfile: false,
+                locked: false,
+                frozen: false,
                 command: Some(Commands::Registry(RegistryCommand {
                     command: RegistrySubCommand::Generate(RegistryGenerateArgs {
                         target: "rust".to_owned(),
@@ -413,17 +526,33 @@ mod tests {
                         param: None,
                         params: None,
                         registry: RegistryArgs {
-                            registry: RegistryPath::LocalFolder {
+                            registry: vec![RegistryPath::LocalFolder {
                                 path: "data/symbolic_test/".to_owned(),
-                            },
+                            }],
                             follow_symlinks,
+                            no_resolution_cache: false,
+                            resolution_cache_dir: Some(temp_output.join("resolution_cache")),
+                            include_spec: vec![],
+                            exclude_spec: vec![],
+                            prose_lint: vec![],
                         },
                         policy: PolicyArgs {
                             policies: vec![],
                             skip_policies: true,
                             display_policy_coverage: false,
+                            policy_coverage: None,
+                            policy_coverage_format: Default::default(),
+                            policy_baseline: None,
+                            update_baseline: false,
+                            per_group_policies: false,
+                            policy_sandbox: false,
+                            policy_timeout: None,
+                            policy_max_memory: None,
+                            fail_on: Default::default(),
                         },
                         future: false,
+                        profile_templates: false,
+                        resolution: ResolutionArgs::default(),
                         diagnostic: Default::default(),
                     }),
                 })),
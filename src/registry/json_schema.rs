@@ -1,10 +1,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Generate the JSON Schema of the resolved registry documents consumed by the template generator
-//! and the policy engine.
+//! and the policy engine, or of the semantic convention files.
 
 use crate::{DiagnosticArgs, ExitDirectives};
-use clap::Args;
+use clap::{Args, ValueEnum};
 use miette::Diagnostic;
 use schemars::schema_for;
 use serde::Serialize;
@@ -13,10 +13,27 @@ use std::path::PathBuf;
 use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages};
 use weaver_common::Logger;
 use weaver_forge::registry::ResolvedRegistry;
+use weaver_semconv::semconv::SemConvSpec;
+
+/// The documents described by the generated JSON Schema.
+#[derive(Debug, Clone, Default, ValueEnum)]
+pub(crate) enum JsonSchemaType {
+    /// The resolved registry consumed by the template generator and the
+    /// policy engine.
+    #[default]
+    ResolvedRegistry,
+    /// The semantic convention files (e.g. for the validation of the files
+    /// in an editor).
+    Semconv,
+}
 
 /// Parameters for the `registry json-schema` sub-command
 #[derive(Debug, Args)]
 pub struct RegistryJsonSchemaArgs {
+    /// The documents described by the JSON schema
+    #[arg(short, long, value_enum, default_value = "resolved-registry")]
+    json_schema: JsonSchemaType,
+
     /// Output file to write the JSON schema to
     /// If not specified, the JSON schema is printed to stdout
     #[arg(short, long)]
@@ -54,13 +71,16 @@ impl From<Error> for DiagnosticMessages {
     }
 }
 
-/// Generate the JSON Schema of a ResolvedRegistry and write the JSON schema to a
-/// file or print it to stdout.
+/// Generate the JSON Schema of a ResolvedRegistry or of a semantic convention file and
+/// write the JSON schema to a file or print it to stdout.
 pub(crate) fn command(
     logger: impl Logger + Sync + Clone,
     args: &RegistryJsonSchemaArgs,
 ) -> Result<ExitDirectives, DiagnosticMessages> {
-    let json_schema = schema_for!(ResolvedRegistry);
+    let json_schema = match args.json_schema {
+        JsonSchemaType::ResolvedRegistry => schema_for!(ResolvedRegistry),
+        JsonSchemaType::Semconv => SemConvSpec::json_schema(),
+    };
 
     let json_schema_str =
         to_string_pretty(&json_schema).map_err(|e| Error::SerializationError {
@@ -89,9 +109,10 @@ mod tests {
     use weaver_common::in_memory::LogMessage;
 
     use crate::cli::{Cli, Commands};
-    use crate::registry::json_schema::RegistryJsonSchemaArgs;
+    use crate::registry::json_schema::{JsonSchemaType, RegistryJsonSchemaArgs};
     use crate::registry::{RegistryCommand, RegistrySubCommand};
     use crate::run_command;
+    use weaver_semconv::semconv::SemConvSpec;
 
     #[test]
     fn test_registry_json_schema() {
@@ -100,8 +121,14 @@ mod tests {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::JsonSchema(RegistryJsonSchemaArgs {
+                    json_schema: JsonSchemaType::ResolvedRegistry,
                     output: None,
                     diagnostic: Default::default(),
                 }),
@@ -132,4 +159,65 @@ mod tests {
             panic!("Expected a log message, but got: {:?}", message);
         }
     }
+    #[test]
+    fn test_semconv_json_schema() {
+        let logger = in_memory::Logger::new(0);
+        let cli = Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::JsonSchema(RegistryJsonSchemaArgs {
+                    json_schema: JsonSchemaType::Semconv,
+                    output: None,
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+
+        let exit_directive = run_command(&cli, logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
+
+        let messages = logger.messages();
+        assert_eq!(messages.len(), 1);
+        let LogMessage::Log(log) = &messages[0] else {
+            panic!("Expected a log message, but got: {:?}", messages[0]);
+        };
+        let value = serde_json::from_str::<serde_json::Value>(log).expect("Failed to parse JSON");
+        assert_eq!(value["title"], "SemConvSpec");
+        assert_eq!(value["required"], serde_json::json!(["groups"]));
+        for definition in [
+            "GroupSpec",
+            "AttributeSpec",
+            "AnyValueSpec",
+            "EnumEntriesSpec",
+        ] {
+            assert!(
+                value["definitions"].get(definition).is_some(),
+                "Expected the definition `{}` in the JSON schema",
+                definition
+            );
+        }
+    }
+
+    #[test]
+    fn test_semconv_json_schema_in_sync() {
+        let generated = serde_json::to_value(SemConvSpec::json_schema())
+            .expect("Failed to serialize the JSON schema");
+        let committed: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string("schemas/semconv.schema.json")
+                .expect("Failed to read the JSON schema"),
+        )
+        .expect("Failed to parse the JSON schema");
+        assert!(
+            generated == committed,
+            "`schemas/semconv.schema.json` is out of date, regenerate it with \
+            `weaver registry json-schema -j semconv -o schemas/semconv.schema.json`"
+        );
+    }
 }
@@ -3,24 +3,36 @@
 //! Commands to manage a semantic convention registry.
 
 use std::path::PathBuf;
+use std::time::Duration;
 
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
+use itertools::Itertools;
 use miette::Diagnostic;
 use serde::Serialize;
 
+use crate::registry::bundle::RegistryBundleArgs;
 use crate::registry::generate::RegistryGenerateArgs;
 use crate::registry::json_schema::RegistryJsonSchemaArgs;
 use crate::registry::resolve::RegistryResolveArgs;
 use crate::registry::search::RegistrySearchArgs;
 use crate::registry::stats::RegistryStatsArgs;
 use crate::registry::update_markdown::RegistryUpdateMarkdownArgs;
+use crate::resolution_cache::ResolutionCache;
 use crate::CmdResult;
 use check::RegistryCheckArgs;
 use weaver_cache::registry_path::RegistryPath;
-use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages};
+use weaver_cache::{RegistryCache, RegistryRepo};
+use weaver_checker::sandbox::{EvalLimits, Sandbox};
+use weaver_checker::violation::Severity;
+use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages, SeverityOverrides};
 use weaver_common::Logger;
+use weaver_semconv::filter::SpecFilter;
+use weaver_semconv::prose::ProseLintRule;
+use weaver_semconv::stability::Stability;
 
+mod bundle;
 mod check;
+mod explain;
 mod generate;
 mod json_schema;
 mod resolve;
@@ -39,6 +51,42 @@ pub enum Error {
     /// Invalid params file passed to the command line
     #[error("The params file `{params_file}` is invalid. {error}")]
     InvalidParams { params_file: PathBuf, error: String },
+
+    /// The group or attribute to explain doesn't exist in the resolved registry
+    #[error("No group or attribute `{id}` found in the resolved registry.")]
+    ExplainTargetNotFound { id: String },
+
+    /// The report of the unused definitions can't be written
+    #[error("Failed to write the unused definitions report to `{file}`. {error}")]
+    UnusedReportWriteFailed { file: PathBuf, error: String },
+
+    /// The statistics can't be serialized
+    #[error("Failed to serialize the statistics. {error}")]
+    StatsWriteFailed { error: String },
+
+    /// The `--query` JSON pointer is invalid or doesn't match any value
+    #[error("Invalid `--query`. {error}")]
+    InvalidQuery { error: String },
+
+    /// The resolved schema can't be flattened
+    #[error("Failed to flatten the resolved schema. {error}")]
+    FlattenFailed { error: String },
+
+    /// The dependency graph can't be written
+    #[error("Failed to write the dependency graph to `{file}`. {error}")]
+    DependencyGraphWriteFailed { file: PathBuf, error: String },
+
+    /// The policy coverage report can't be written
+    #[error("Failed to write the policy coverage report to `{file}`. {error}")]
+    PolicyCoverageWriteFailed { file: PathBuf, error: String },
+
+    /// The fixes suggested by the policies can't be applied to a spec file
+    #[error("Failed to apply the policy fixes to `{file}`. {error}")]
+    PolicyFixFailed { file: PathBuf, error: String },
+
+    /// The statistics of the observed telemetry can't be loaded
+    #[error("Invalid observations file `{file}`. {error}")]
+    InvalidObservations { file: PathBuf, error: String },
 }
 
 impl From<Error> for DiagnosticMessages {
@@ -99,37 +147,126 @@ pub enum RegistrySubCommand {
     /// Generate the JSON Schema of the resolved registry documents consumed by the template generator and the policy engine.
     ///
     /// The produced JSON Schema can be used to generate documentation of the resolved registry format or to generate code in your language of choice if you need to interact with the resolved registry format for any reason.
+    /// With `--json-schema semconv`, the JSON Schema of the semantic convention files is generated instead, e.g. to validate the files in an editor.
     #[clap(verbatim_doc_comment)]
     JsonSchema(RegistryJsonSchemaArgs),
+    /// Bundles registries, and optionally templates and policies, into a single self-contained archive.
+    ///
+    /// The registries are fetched at their exact revision. The bundle can be used as a registry path without any network access: it resolves to the first bundled registry, and the next ones are selected with the `[registries/main.1]` sub-folder syntax.
+    #[clap(verbatim_doc_comment)]
+    Bundle(RegistryBundleArgs),
 }
 
 /// Set of parameters used to specify a semantic convention registry.
 #[derive(Args, Debug)]
 pub struct RegistryArgs {
-    /// Local folder, Git repo URL, or Git archive URL of the semantic
-    /// convention registry. For Git URLs, a sub-folder can be specified
-    /// using the `[sub-folder]` syntax after the URL.
+    /// Local folder, Git repo URL, Git archive URL, or OCI artifact reference
+    /// (e.g. `oci://ghcr.io/acme/semconv:1.2.0`) of the semantic convention
+    /// registry. For Git URLs and OCI artifacts, a sub-folder can be specified
+    /// using the `[sub-folder]` syntax after the URL. For Git repo URLs, a
+    /// tag, a branch, or a semver range selecting the highest matching version
+    /// tag (e.g. `@^1.26`) can be specified using the `@refspec` syntax. For
+    /// archive URLs, the expected SHA-256 digest of the archive can be
+    /// specified using the `@sha256:<digest>` syntax. For OCI artifacts, the
+    /// manifest can be pinned using the `@sha256:<digest>` syntax. A local
+    /// overlay directory can be layered over any registry using the
+    /// `+overlay=<dir>` suffix.
+    /// This option can be repeated to load and resolve several registries
+    /// together (a comma-separated list is not supported as semver ranges
+    /// can contain commas, e.g. `@>=1.26, <2`).
     #[arg(
         short = 'r',
         long,
         default_value = "https://github.com/open-telemetry/semantic-conventions.git[model]"
     )]
-    pub registry: RegistryPath,
+    pub registry: Vec<RegistryPath>,
 
     /// Boolean flag to specify whether to follow symlinks when loading the registry.
     /// Default is false.
     #[arg(short = 's', long)]
     pub(crate) follow_symlinks: bool,
+
+    /// Always resolve the registry from scratch instead of reusing a resolved
+    /// registry from the resolution cache (`~/.weaver/resolution_cache`).
+    #[arg(long, default_value = "false")]
+    pub(crate) no_resolution_cache: bool,
+
+    /// Directory of the resolution cache.
+    /// Default is `$HOME/.weaver/resolution_cache`.
+    #[arg(long)]
+    pub(crate) resolution_cache_dir: Option<PathBuf>,
+
+    /// Only load the semantic convention files or groups matching this glob
+    /// pattern. Patterns containing a `/` are matched against the file paths
+    /// relative to the registry root (e.g. `http/**`), other patterns against
+    /// the group ids (e.g. `registry.http*`). This option can be repeated.
+    #[arg(long, value_name = "GLOB")]
+    pub(crate) include_spec: Vec<String>,
+
+    /// Don't load the semantic convention files or groups matching this glob
+    /// pattern (same syntax as `--include-spec`). This option can be repeated.
+    #[arg(long, value_name = "GLOB")]
+    pub(crate) exclude_spec: Vec<String>,
+
+    /// Enable a lint rule for the briefs and notes of the semconv registry, one
+    /// of `sentence_case`, `trailing_period`, `no_headings` or `max_length=<N>`.
+    /// The violations are reported as warnings. This option can be repeated.
+    #[arg(long, value_name = "RULE")]
+    pub(crate) prose_lint: Vec<ProseLintRule>,
+}
+
+impl RegistryArgs {
+    /// Returns a human-readable representation of the registry paths.
+    pub(crate) fn registry_paths(&self) -> String {
+        self.registry.iter().join(", ")
+    }
+
+    /// Returns the filter selecting the semantic convention files and groups
+    /// to load.
+    pub(crate) fn spec_filter(&self) -> Result<SpecFilter, weaver_semconv::Error> {
+        SpecFilter::try_new(&self.include_spec, &self.exclude_spec)
+    }
+
+    /// Creates the repositories of the registries. The first registry is
+    /// identified by `id`, the next ones by `id` followed by their index
+    /// (e.g. `main`, `main.1`, `main.2`). The warnings raised while fetching
+    /// the registries are logged.
+    pub(crate) fn registry_repos(
+        &self,
+        id: &str,
+        registry_cache: &RegistryCache,
+        logger: &impl Logger,
+    ) -> Result<Vec<RegistryRepo>, weaver_cache::Error> {
+        self.registry
+            .iter()
+            .enumerate()
+            .map(|(index, registry_path)| {
+                let repo_id = if index == 0 {
+                    id.to_owned()
+                } else {
+                    format!("{id}.{index}")
+                };
+                let repo = RegistryRepo::try_new(&repo_id, registry_path, registry_cache)?;
+                for warning in repo.warnings() {
+                    logger.warn(warning);
+                }
+                Ok(repo)
+            })
+            .collect()
+    }
 }
 
 /// Set of common parameters used for policy checks.
 #[derive(Args, Debug)]
 pub struct PolicyArgs {
     /// Optional list of policy files or directories to check against the files of the semantic
-    /// convention registry.  If a directory is provided all `.rego` files in the directory will be
-    /// loaded.
+    /// convention registry.  If a directory is provided all `.rego`, CEL policy (`.cel.yaml`
+    /// or `.cel.yml`), lint policy (`.lint.yaml` or `.lint.yml`) and WASM policy (`.wasm`)
+    /// files in the directory will be loaded. Policy bundles can also be loaded from an archive (local or `http(s)` URL),
+    /// a Git repository, or an OCI artifact (`oci://...`), with the same syntax as `--registry`
+    /// (e.g. `https://example.com/policies.tar.gz@sha256:<digest>[policies]`).
     #[arg(short = 'p', long = "policy")]
-    pub policies: Vec<PathBuf>,
+    pub policies: Vec<RegistryPath>,
 
     /// Skip the policy checks.
     #[arg(long, default_value = "false")]
@@ -138,38 +275,188 @@ pub struct PolicyArgs {
     /// Display the policy coverage report (useful for debugging).
     #[arg(long, default_value = "false")]
     pub display_policy_coverage: bool,
+
+    /// Optional file where the coverage report of the Rego policies is written, with the hit
+    /// counts of each line and rule (e.g. to track the policy coverage in CI dashboards).
+    #[arg(long, value_name = "FILE")]
+    pub policy_coverage: Option<PathBuf>,
+
+    /// Format of the policy coverage report.
+    #[arg(long, value_name = "FORMAT", default_value = "json")]
+    pub policy_coverage_format: PolicyCoverageFormat,
+
+    /// Optional baseline file of the known policy violations. The violations of the baseline
+    /// are not reported, so that strict policies can be adopted incrementally on existing
+    /// registries.
+    #[arg(long, value_name = "FILE")]
+    pub policy_baseline: Option<PathBuf>,
+
+    /// Regenerate the baseline file with all the current policy violations, which are not
+    /// reported.
+    #[arg(long, default_value = "false", requires = "policy_baseline")]
+    pub update_baseline: bool,
+
+    /// Evaluate the `after_resolution` policies on each group in parallel, with a registry
+    /// containing only this group as input. This speeds up the check of large registries, but
+    /// is only suited to the policies checking each group independently of the others.
+    #[arg(long, default_value = "false")]
+    pub per_group_policies: bool,
+
+    /// Deny the Rego built-in functions accessing the network or returning non-deterministic
+    /// values (`http.send`, `rand.intn`, `time.now_ns`, `uuid.rfc4122`), to safely run
+    /// third-party policy bundles.
+    #[arg(long, default_value = "false")]
+    pub policy_sandbox: bool,
+
+    /// Optional time limit in seconds of the evaluation of each Rego policy package.
+    #[arg(long, value_name = "SECONDS")]
+    pub policy_timeout: Option<u64>,
+
+    /// Optional memory limit in MiB of the evaluation of each Rego policy package, measured as
+    /// the growth of the resident memory of the process (Linux only).
+    #[arg(long, value_name = "MIB")]
+    pub policy_max_memory: Option<u64>,
+
+    /// The minimum severity of the policy violations failing the command, one of
+    /// `info`, `warning` or `violation`. The violations with a lower severity
+    /// are reported without failing the command.
+    #[arg(long, value_name = "SEVERITY", default_value = "violation")]
+    pub fail_on: Severity,
+}
+
+impl PolicyArgs {
+    /// Returns the sandbox of the Rego policies.
+    pub(crate) fn sandbox(&self) -> Sandbox {
+        let sandbox = if self.policy_sandbox {
+            Sandbox::restricted()
+        } else {
+            Sandbox::default()
+        };
+        Sandbox {
+            limits: EvalLimits {
+                timeout: self.policy_timeout.map(Duration::from_secs),
+                max_memory: self
+                    .policy_max_memory
+                    .map(|mib| mib.saturating_mul(1024 * 1024)),
+            },
+            ..sandbox
+        }
+    }
+}
+
+/// Supported formats for the policy coverage report.
+#[derive(Debug, Clone, Default, ValueEnum)]
+pub enum PolicyCoverageFormat {
+    /// JSON format
+    #[default]
+    Json,
+    /// LCOV format
+    Lcov,
+}
+
+/// Set of parameters used to select the definitions of the resolved registry.
+#[derive(Args, Debug, Default, Clone)]
+pub struct ResolutionArgs {
+    /// Only resolve the groups whose id matches one of these glob patterns
+    /// (e.g. `registry.http.*`), and the groups they depend on through
+    /// `extends`, `include`, and attribute references.
+    /// This option can be repeated. By default, all the groups are resolved.
+    #[arg(long = "include-group", value_name = "GLOB")]
+    pub include_groups: Vec<String>,
+
+    /// Remove the deprecated groups and attributes from the resolved registry.
+    #[arg(long, default_value = "false")]
+    pub strip_deprecated: bool,
+
+    /// Remove the groups and attributes whose stability is lower than the
+    /// given level from the resolved registry. Definitions without stability
+    /// are considered in development.
+    /// Levels: deprecated, development, alpha, beta, release_candidate, stable
+    #[arg(long, value_parser = parse_stability)]
+    pub min_stability: Option<Stability>,
+
+    /// Record the line and column of each group and attribute definition in
+    /// the lineage of the resolved registry, so that tools can point at the
+    /// exact location in the YAML files.
+    #[arg(long, default_value = "false")]
+    pub source_locations: bool,
+
+    /// The severity overrides of the diagnostics (set from the diagnostic
+    /// args of the command). The registry checks ignored by an override are
+    /// skipped by the resolution.
+    #[arg(skip)]
+    pub severity_overrides: SeverityOverrides,
+}
+
+impl RegistryArgs {
+    /// Returns the resolution cache, or `None` if the resolution cache is
+    /// disabled or if the home directory can't be determined.
+    pub(crate) fn resolution_cache(&self) -> Option<ResolutionCache> {
+        if self.no_resolution_cache {
+            return None;
+        }
+        match &self.resolution_cache_dir {
+            Some(dir) => Some(ResolutionCache::new(dir.clone())),
+            None => ResolutionCache::try_default(),
+        }
+    }
+}
+
+impl ResolutionArgs {
+    /// Returns a copy of these args with the given severity overrides.
+    #[must_use]
+    pub(crate) fn with_severity_overrides(&self, severity_overrides: SeverityOverrides) -> Self {
+        Self {
+            severity_overrides,
+            ..self.clone()
+        }
+    }
+}
+
+/// Utility function to parse a stability level from the command line.
+fn parse_stability(s: &str) -> Result<Stability, String> {
+    serde_json::from_value(serde_json::Value::String(s.to_owned()))
+        .map_err(|_| format!("`{s}` is not a valid stability level"))
 }
 
 /// Manage a semantic convention registry and return the exit code.
-pub fn semconv_registry(log: impl Logger + Sync + Clone, command: &RegistryCommand) -> CmdResult {
+pub fn semconv_registry(
+    log: impl Logger + Sync + Clone,
+    command: &RegistryCommand,
+    registry_cache: &RegistryCache,
+) -> CmdResult {
     match &command.command {
         RegistrySubCommand::Check(args) => CmdResult::new(
-            check::command(log.clone(), args),
+            check::command(log.clone(), args, registry_cache),
             Some(args.diagnostic.clone()),
         ),
         RegistrySubCommand::Generate(args) => CmdResult::new(
-            generate::command(log.clone(), args),
+            generate::command(log.clone(), args, registry_cache),
             Some(args.diagnostic.clone()),
         ),
         RegistrySubCommand::Stats(args) => CmdResult::new(
-            stats::command(log.clone(), args),
+            stats::command(log.clone(), args, registry_cache),
             Some(args.diagnostic.clone()),
         ),
         RegistrySubCommand::Resolve(args) => CmdResult::new(
-            resolve::command(log.clone(), args),
+            resolve::command(log.clone(), args, registry_cache),
             Some(args.diagnostic.clone()),
         ),
         RegistrySubCommand::Search(args) => CmdResult::new(
-            search::command(log.clone(), args),
+            search::command(log.clone(), args, registry_cache),
             Some(args.diagnostic.clone()),
         ),
         RegistrySubCommand::UpdateMarkdown(args) => CmdResult::new(
-            update_markdown::command(log.clone(), args),
+            update_markdown::command(log.clone(), args, registry_cache),
             Some(args.diagnostic.clone()),
         ),
         RegistrySubCommand::JsonSchema(args) => CmdResult::new(
             json_schema::command(log.clone(), args),
             Some(args.diagnostic.clone()),
         ),
+        RegistrySubCommand::Bundle(args) => CmdResult::new(
+            bundle::command(log.clone(), args, registry_cache),
+            Some(args.diagnostic.clone()),
+        ),
     }
 }
@@ -2,16 +2,21 @@
 
 //! Resolve a semantic convention registry.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 
+use weaver_cache::RegistryCache;
 use weaver_common::diagnostic::DiagnosticMessages;
 use weaver_common::Logger;
+use weaver_forge::registry::ResolvedRegistry;
+use weaver_resolved_schema::graph::DependencyGraph;
+use weaver_resolved_schema::pointer::lookup;
 
 use crate::format::{apply_format, Format};
-use crate::registry::{PolicyArgs, RegistryArgs};
-use crate::util::prepare_main_registry;
+use crate::registry::explain::explain;
+use crate::registry::{Error, PolicyArgs, RegistryArgs, ResolutionArgs};
+use crate::util::{prepare_main_schema, report_policy_coverage, update_policy_baseline};
 use crate::{DiagnosticArgs, ExitDirectives};
 
 /// Parameters for the `registry resolve` sub-command
@@ -39,6 +44,33 @@ pub struct RegistryResolveArgs {
     #[arg(short, long, default_value = "yaml")]
     format: Format,
 
+    /// Explain the provenance of a group or an attribute (by id) instead of
+    /// printing the resolved schema, i.e. which spec files and which
+    /// `extends`/`ref`/overrides produced the resolved values.
+    #[arg(long)]
+    explain: Option<String>,
+
+    /// Print the flattened resolved telemetry schema instead of the resolved
+    /// registry: the groups, the resource, and the instrumentation libraries
+    /// embed their attribute definitions, and the catalog is omitted.
+    #[arg(long, conflicts_with = "explain")]
+    flattened: bool,
+
+    /// Only print the part of the resolved registry located at this JSON
+    /// pointer (RFC 6901), e.g. `/groups/3/attributes`.
+    #[arg(long, value_name = "JSON_POINTER", conflicts_with = "explain")]
+    query: Option<String>,
+
+    /// Write the dependency graph of the groups (`extends` and `ref`
+    /// relationships) to the given file. The GraphML format is used if the
+    /// file extension is `.graphml`, the DOT format (Graphviz) otherwise.
+    #[arg(long, value_name = "PATH")]
+    dependency_graph: Option<PathBuf>,
+
+    /// Parameters to select the definitions of the resolved registry
+    #[command(flatten)]
+    resolution: ResolutionArgs,
+
     /// Policy parameters
     #[command(flatten)]
     policy: PolicyArgs,
@@ -53,19 +85,67 @@ pub struct RegistryResolveArgs {
 pub(crate) fn command(
     logger: impl Logger + Sync + Clone,
     args: &RegistryResolveArgs,
+    registry_cache: &RegistryCache,
 ) -> Result<ExitDirectives, DiagnosticMessages> {
     if args.output.is_none() {
         logger.mute();
     }
-    logger.loading(&format!("Resolving registry `{}`", args.registry.registry));
+    logger.loading(&format!(
+        "Resolving registry `{}`",
+        args.registry.registry_paths()
+    ));
 
     let mut diag_msgs = DiagnosticMessages::empty();
 
-    let (registry, _) =
-        prepare_main_registry(&args.registry, &args.policy, logger.clone(), &mut diag_msgs)?;
+    let (schema, registry, policy_engine) = prepare_main_schema(
+        &args.registry,
+        &args.policy,
+        &args
+            .resolution
+            .with_severity_overrides(args.diagnostic.severity_overrides()),
+        None,
+        None,
+        registry_cache,
+        logger.clone(),
+        &mut diag_msgs,
+    )?;
+
+    update_policy_baseline(&args.policy, policy_engine.as_ref(), logger.clone())?;
+    report_policy_coverage(&args.policy, policy_engine.as_ref(), logger.clone())?;
+
+    if let Some(path) = &args.dependency_graph {
+        write_dependency_graph(&registry, path)?;
+    }
+
+    let flattened = if args.flattened {
+        Some(schema.to_flattened().map_err(|e| Error::FlattenFailed {
+            error: e.to_string(),
+        })?)
+    } else {
+        None
+    };
 
-    apply_format(&args.format, &registry)
-        .map_err(|e| format!("Failed to serialize the registry: {e:?}"))
+    let output = match (&args.explain, &args.query, &flattened) {
+        (Some(id), _, _) => Ok(explain(&registry, id)
+            .ok_or_else(|| Error::ExplainTargetNotFound { id: id.clone() })?),
+        (None, Some(query), flattened) => {
+            let value = match flattened {
+                Some(flattened) => lookup(flattened, query),
+                None => lookup(&registry, query),
+            }
+            .map_err(|e| Error::InvalidQuery {
+                error: e.to_string(),
+            })?;
+            apply_format(&args.format, &value)
+                .map_err(|e| format!("Failed to serialize the query result: {e:?}"))
+        }
+        (None, None, Some(flattened)) => apply_format(&args.format, flattened)
+            .map_err(|e| format!("Failed to serialize the flattened schema: {e:?}")),
+        (None, None, None) => apply_format(&args.format, &registry)
+            .map_err(|e| format!("Failed to serialize the registry: {e:?}")),
+    };
+
+    output
         .and_then(|s| {
             if let Some(ref path) = args.output {
                 // Write the resolved registry to a file.
@@ -92,40 +172,270 @@ pub(crate) fn command(
     })
 }
 
+/// Writes the dependency graph of the groups of the resolved registry in the
+/// DOT or GraphML format, based on the file extension.
+fn write_dependency_graph(registry: &ResolvedRegistry, path: &Path) -> Result<(), Error> {
+    let mut graph = DependencyGraph::default();
+    for group in registry.groups.iter() {
+        graph.add_group(
+            &group.id,
+            &group.r#type,
+            group.extends.as_ref(),
+            group.lineage.as_ref(),
+        );
+    }
+    let content = if path.extension().is_some_and(|ext| ext == "graphml") {
+        graph.to_graphml()
+    } else {
+        graph.to_dot()
+    };
+    std::fs::write(path, content).map_err(|e| Error::DependencyGraphWriteFailed {
+        file: path.to_path_buf(),
+        error: e.to_string(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use tempdir::TempDir;
     use weaver_common::TestLogger;
+    use weaver_semconv::stability::Stability;
 
     use crate::cli::{Cli, Commands};
     use crate::format::Format;
     use crate::registry::resolve::RegistryResolveArgs;
     use crate::registry::{
-        PolicyArgs, RegistryArgs, RegistryCommand, RegistryPath, RegistrySubCommand,
+        PolicyArgs, RegistryArgs, RegistryCommand, RegistryPath, RegistrySubCommand, ResolutionArgs,
     };
     use crate::run_command;
 
+    #[test]
+    fn test_registry_resolve_query() {
+        let logger = TestLogger::new();
+        let temp_output = TempDir::new("output").expect("Failed to create temporary directory");
+        let output = temp_output.path().join("resolved.json");
+        let cli = |query: Option<&str>, flattened: bool| Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Resolve(RegistryResolveArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
+                    },
+                    lineage: false,
+                    output: Some(output.clone()),
+                    format: Format::Json,
+                    explain: None,
+                    flattened,
+                    dependency_graph: None,
+                    query: query.map(ToOwned::to_owned),
+                    resolution: ResolutionArgs::default(),
+                    policy: PolicyArgs {
+                        policies: vec![],
+                        skip_policies: true,
+                        display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+
+        let read_output = || -> serde_json::Value {
+            serde_json::from_str(
+                &std::fs::read_to_string(&output).expect("Failed to read the output"),
+            )
+            .expect("Failed to parse the output")
+        };
+        let exit_directive = run_command(&cli(None, false), logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
+        let registry = read_output();
+
+        // Only the value at the JSON pointer is printed.
+        let exit_directive = run_command(&cli(Some("/groups/0/attributes"), false), logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
+        let attributes = read_output();
+        assert!(attributes.as_array().is_some_and(|attrs| !attrs.is_empty()));
+        assert_eq!(attributes, registry["groups"][0]["attributes"]);
+
+        // No value at this location.
+        let exit_directive = run_command(&cli(Some("/groups/100000"), false), logger.clone());
+        assert_eq!(exit_directive.exit_code, 1);
+
+        // Invalid JSON pointer.
+        let exit_directive = run_command(&cli(Some("groups"), false), logger.clone());
+        assert_eq!(exit_directive.exit_code, 1);
+
+        // The flattened schema embeds the attribute definitions of the groups
+        // and omits the catalog.
+        let exit_directive = run_command(&cli(None, true), logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
+        let flattened = read_output();
+        assert!(flattened.get("catalog").is_none());
+        let groups = flattened["registries"]["main"]["groups"]
+            .as_array()
+            .expect("Expected groups");
+        assert_eq!(
+            groups.len(),
+            registry["groups"].as_array().map_or(0, Vec::len)
+        );
+        let exit_directive = run_command(
+            &cli(Some("/registries/main/groups/0/attributes/0/name"), true),
+            logger,
+        );
+        assert_eq!(exit_directive.exit_code, 0);
+        assert!(read_output().is_string());
+    }
+
+    #[test]
+    fn test_registry_resolve_spec_filter() {
+        let logger = TestLogger::new();
+        let temp_output = TempDir::new("output").expect("Failed to create temporary directory");
+        let output = temp_output.path().join("resolved.json");
+        let cli = |include_spec: &[&str], exclude_spec: &[&str]| Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Resolve(RegistryResolveArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: true,
+                        resolution_cache_dir: None,
+                        include_spec: include_spec.iter().map(|s| (*s).to_owned()).collect(),
+                        exclude_spec: exclude_spec.iter().map(|s| (*s).to_owned()).collect(),
+                        prose_lint: vec![],
+                    },
+                    lineage: false,
+                    output: Some(output.clone()),
+                    format: Format::Json,
+                    explain: None,
+                    flattened: false,
+                    dependency_graph: None,
+                    query: None,
+                    resolution: ResolutionArgs::default(),
+                    policy: PolicyArgs {
+                        policies: vec![],
+                        skip_policies: true,
+                        display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+
+        // Only the attribute registry, without the exception attributes.
+        let exit_directive = run_command(
+            &cli(&["registry/**"], &["registry.exception"]),
+            logger.clone(),
+        );
+        assert_eq!(exit_directive.exit_code, 0);
+        let resolved: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(&output).expect("Failed to read the resolved registry"),
+        )
+        .expect("Failed to parse the resolved registry");
+        let group_ids: Vec<_> = resolved["groups"]
+            .as_array()
+            .expect("Expected groups")
+            .iter()
+            .filter_map(|group| group["id"].as_str())
+            .collect();
+        assert!(group_ids.contains(&"registry.http"));
+        assert!(!group_ids.contains(&"registry.exception"));
+        assert!(group_ids.iter().all(|id| id.starts_with("registry.")));
+
+        // Invalid pattern.
+        let exit_directive = run_command(&cli(&["registry.[http"], &[]), logger);
+        assert_eq!(exit_directive.exit_code, 1);
+    }
+
     #[test]
     fn test_registry_resolve() {
         let logger = TestLogger::new();
+        let temp_output = TempDir::new("output").expect("Failed to create temporary directory");
+        let graph_path = temp_output.path().join("dependencies.dot");
         let cli = Cli {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::Resolve(RegistryResolveArgs {
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     lineage: true,
                     output: None,
                     format: Format::Yaml,
+                    explain: None,
+                    flattened: false,
+                    dependency_graph: Some(graph_path.clone()),
+                    query: None,
+                    resolution: ResolutionArgs::default(),
                     policy: PolicyArgs {
                         policies: vec![],
                         skip_policies: true,
                         display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
                     },
                     diagnostic: Default::default(),
                 }),
@@ -135,27 +445,231 @@ mod tests {
         let exit_directive = run_command(&cli, logger.clone());
         // The command should succeed.
         assert_eq!(exit_directive.exit_code, 0);
+        let graph = std::fs::read_to_string(&graph_path).expect("Failed to read the graph");
+        assert!(graph.starts_with("digraph registry {"));
+        assert!(graph.contains("[label=\"extends\", style=solid];"));
+
+        // Explain the provenance of a group of a pruned registry.
+        let cli = Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Resolve(RegistryResolveArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
+                    },
+                    lineage: false,
+                    output: None,
+                    format: Format::Yaml,
+                    explain: Some("metric.http.server.request.duration".to_owned()),
+                    flattened: false,
+                    dependency_graph: None,
+                    query: None,
+                    resolution: ResolutionArgs {
+                        include_groups: vec!["metric.http.*".to_owned()],
+                        ..ResolutionArgs::default()
+                    },
+                    policy: PolicyArgs {
+                        policies: vec![],
+                        skip_policies: true,
+                        display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+
+        let exit_directive = run_command(&cli, logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
+
+        // Resolve the stable and non-deprecated definitions only.
+        let cli = Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Resolve(RegistryResolveArgs {
+                    registry: RegistryArgs {
+                        registry: vec![RegistryPath::LocalFolder {
+                            path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
+                        }],
+                        follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
+                    },
+                    lineage: false,
+                    output: None,
+                    format: Format::Yaml,
+                    explain: None,
+                    flattened: false,
+                    dependency_graph: None,
+                    query: None,
+                    resolution: ResolutionArgs {
+                        strip_deprecated: true,
+                        min_stability: Some(Stability::Stable),
+                        ..ResolutionArgs::default()
+                    },
+                    policy: PolicyArgs {
+                        policies: vec![],
+                        skip_policies: true,
+                        display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+
+        let exit_directive = run_command(&cli, logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
+
+        // Resolve two registries together, the metrics referencing the
+        // attributes of the other registry.
+        let cli = Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Registry(RegistryCommand {
+                command: RegistrySubCommand::Resolve(RegistryResolveArgs {
+                    registry: RegistryArgs {
+                        registry: vec![
+                            RegistryPath::LocalFolder {
+                                path: "crates/weaver_codegen_test/semconv_registry/registry/"
+                                    .to_owned(),
+                            },
+                            RegistryPath::LocalFolder {
+                                path: "crates/weaver_codegen_test/semconv_registry/metrics/"
+                                    .to_owned(),
+                            },
+                        ],
+                        follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
+                    },
+                    lineage: false,
+                    output: None,
+                    format: Format::Yaml,
+                    explain: None,
+                    flattened: false,
+                    dependency_graph: None,
+                    query: None,
+                    resolution: ResolutionArgs {
+                        include_groups: vec!["metric.system.*".to_owned()],
+                        ..ResolutionArgs::default()
+                    },
+                    policy: PolicyArgs {
+                        policies: vec![],
+                        skip_policies: true,
+                        display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+
+        let exit_directive = run_command(&cli, logger.clone());
+        assert_eq!(exit_directive.exit_code, 0);
 
         // Now, let's run the command again with the policy checks enabled.
         let cli = Cli {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::Resolve(RegistryResolveArgs {
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "crates/weaver_codegen_test/semconv_registry/".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(temp_output.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     lineage: true,
                     output: None,
                     format: Format::Json,
+                    explain: None,
+                    flattened: false,
+                    dependency_graph: None,
+                    query: None,
+                    resolution: ResolutionArgs::default(),
                     policy: PolicyArgs {
                         policies: vec![],
                         skip_policies: false,
                         display_policy_coverage: false,
+                        policy_coverage: None,
+                        policy_coverage_format: Default::default(),
+                        policy_baseline: None,
+                        update_baseline: false,
+                        per_group_policies: false,
+                        policy_sandbox: false,
+                        policy_timeout: None,
+                        policy_max_memory: None,
+                        fail_on: Default::default(),
                     },
                     diagnostic: Default::default(),
                 }),
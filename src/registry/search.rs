@@ -5,15 +5,15 @@
 use clap::Args;
 use itertools::Itertools;
 use miette::Diagnostic;
-use weaver_cache::RegistryRepo;
+use weaver_cache::RegistryCache;
 use weaver_common::diagnostic::DiagnosticMessages;
 use weaver_common::Logger;
 use weaver_resolved_schema::{attribute::Attribute, ResolvedTelemetrySchema};
 use weaver_semconv::registry::SemConvRegistry;
 
 use crate::{
-    registry::RegistryArgs,
-    util::{load_semconv_specs, resolve_semconv_specs},
+    registry::{RegistryArgs, ResolutionArgs},
+    util::{load_registries_semconv_specs, resolve_semconv_specs},
     DiagnosticArgs, ExitDirectives,
 };
 use crossterm::{
@@ -373,24 +373,47 @@ fn run_command_line_search(schema: &ResolvedTelemetrySchema, pattern: &str) {
 pub(crate) fn command(
     logger: impl Logger + Sync + Clone,
     args: &RegistrySearchArgs,
+    registry_cache: &RegistryCache,
 ) -> Result<ExitDirectives, DiagnosticMessages> {
-    logger.loading(&format!("Resolving registry `{}`", args.registry.registry));
+    logger.loading(&format!(
+        "Resolving registry `{}`",
+        args.registry.registry_paths()
+    ));
 
     let registry_id = "default";
-    let registry_path = &args.registry.registry;
-
-    let registry_repo = RegistryRepo::try_new("main", registry_path)?;
+    let registry_repos = args
+        .registry
+        .registry_repos("main", registry_cache, &logger)?;
 
     // Load the semantic convention registry into a local cache.
-    let semconv_specs = load_semconv_specs(
-        &registry_repo,
+    let severity_overrides = args.diagnostic.severity_overrides();
+    let semconv_specs = load_registries_semconv_specs(
+        &registry_repos,
         logger.clone(),
         args.registry.follow_symlinks,
+        &args.registry.spec_filter()?,
+        &args.registry.prose_lint,
     )
-    .ignore(|e| matches!(e.severity(), Some(miette::Severity::Warning)))
+    .ignore(|e| {
+        matches!(
+            severity_overrides.severity(e),
+            None | Some(miette::Severity::Warning)
+        )
+    })
     .into_result_failing_non_fatal()?;
     let mut registry = SemConvRegistry::from_semconv_specs(registry_id, semconv_specs);
-    let schema = resolve_semconv_specs(&mut registry, logger.clone())?;
+    let schema = resolve_semconv_specs(
+        &mut registry,
+        &registry_repos,
+        &ResolutionArgs {
+            severity_overrides,
+            ..ResolutionArgs::default()
+        },
+        args.registry.resolution_cache(),
+        logger.clone(),
+        // As for the loading, the warnings are not reported.
+        &mut DiagnosticMessages::empty(),
+    )?;
 
     // We should have two modes:
     // 1. a single input we take in and directly output some rendered result.
@@ -2,18 +2,41 @@
 
 //! Compute stats on a semantic convention registry.
 
-use crate::registry::RegistryArgs;
-use crate::util::{load_semconv_specs, resolve_semconv_specs};
+use crate::registry::{Error, RegistryArgs, ResolutionArgs};
+use crate::util::{load_registries_semconv_specs, resolve_semconv_specs};
 use crate::{DiagnosticArgs, ExitDirectives};
-use clap::Args;
-use miette::Diagnostic;
-use weaver_cache::RegistryRepo;
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use weaver_cache::RegistryCache;
 use weaver_common::diagnostic::DiagnosticMessages;
 use weaver_common::Logger;
 use weaver_resolved_schema::registry::{CommonGroupStats, GroupStats};
 use weaver_resolved_schema::ResolvedTelemetrySchema;
+use weaver_resolved_schema::Stats as SchemaStats;
+use weaver_resolver::unused::UnusedReport;
 use weaver_semconv::group::GroupType;
 use weaver_semconv::registry::SemConvRegistry;
+use weaver_semconv::stats::Stats as SemConvStats;
+
+/// Output formats of the statistics.
+#[derive(Debug, Clone, Default, ValueEnum)]
+pub(crate) enum StatsFormat {
+    /// Human-readable format.
+    #[default]
+    Text,
+    /// JSON format.
+    Json,
+}
+
+/// The statistics printed in JSON.
+#[derive(Serialize)]
+struct StatsReport {
+    /// Statistics on the semantic convention registry (before resolution).
+    semconv_registry: SemConvStats,
+    /// Statistics on the resolved telemetry schema.
+    resolved_schema: SchemaStats,
+}
 
 /// Parameters for the `registry stats` sub-command
 #[derive(Debug, Args)]
@@ -22,6 +45,17 @@ pub struct RegistryStatsArgs {
     #[command(flatten)]
     registry: RegistryArgs,
 
+    /// Write the report of the groups and attributes that are never
+    /// referenced, extended, or included to the given file. The report is
+    /// written in JSON if the file has a `.json` extension, and in a
+    /// human-readable format otherwise.
+    #[arg(long)]
+    unused_report: Option<PathBuf>,
+
+    /// Output format of the statistics (text or json).
+    #[arg(long, default_value = "text")]
+    format: StatsFormat,
+
     /// Parameters to specify the diagnostic format.
     #[command(flatten)]
     pub diagnostic: DiagnosticArgs,
@@ -31,41 +65,112 @@ pub struct RegistryStatsArgs {
 pub(crate) fn command(
     logger: impl Logger + Sync + Clone,
     args: &RegistryStatsArgs,
+    registry_cache: &RegistryCache,
 ) -> Result<ExitDirectives, DiagnosticMessages> {
+    let json_output = matches!(args.format, StatsFormat::Json);
+    if json_output {
+        logger.mute();
+    }
     logger.loading(&format!(
         "Compute statistics on the registry `{}`",
-        args.registry.registry
+        args.registry.registry_paths()
     ));
 
     let registry_id = "default";
-    let registry_path = &args.registry.registry;
-
-    let registry_repo = RegistryRepo::try_new("main", registry_path)?;
+    let registry_repos = args
+        .registry
+        .registry_repos("main", registry_cache, &logger)?;
 
     // Load the semantic convention registry into a local cache.
-    let semconv_specs = load_semconv_specs(
-        &registry_repo,
+    let severity_overrides = args.diagnostic.severity_overrides();
+    let semconv_specs = load_registries_semconv_specs(
+        &registry_repos,
         logger.clone(),
         args.registry.follow_symlinks,
+        &args.registry.spec_filter()?,
+        &args.registry.prose_lint,
     )
-    .ignore(|e| matches!(e.severity(), Some(miette::Severity::Warning)))
+    .ignore(|e| {
+        matches!(
+            severity_overrides.severity(e),
+            None | Some(miette::Severity::Warning)
+        )
+    })
     .into_result_failing_non_fatal()?;
     let mut registry = SemConvRegistry::from_semconv_specs(registry_id, semconv_specs);
 
-    display_semconv_registry_stats(&registry);
+    let semconv_stats = registry.stats();
+    if !json_output {
+        display_semconv_registry_stats(&semconv_stats);
+    }
+
+    if let Some(path) = &args.unused_report {
+        let unused_count = write_unused_report(&registry, path)?;
+        if !json_output {
+            println!(
+                "Unused definitions: {} (see `{}`)",
+                unused_count,
+                path.display()
+            );
+        }
+    }
 
     // Resolve the semantic convention registry.
-    let resolved_schema = resolve_semconv_specs(&mut registry, logger)?;
+    let resolved_schema = resolve_semconv_specs(
+        &mut registry,
+        &registry_repos,
+        &ResolutionArgs {
+            severity_overrides,
+            ..ResolutionArgs::default()
+        },
+        args.registry.resolution_cache(),
+        logger,
+        // As for the loading, the warnings are not reported.
+        &mut DiagnosticMessages::empty(),
+    )?;
 
-    display_schema_stats(&resolved_schema);
+    if json_output {
+        let report = StatsReport {
+            semconv_registry: semconv_stats,
+            resolved_schema: resolved_schema.stats(),
+        };
+        let json = serde_json::to_string_pretty(&report).map_err(|e| Error::StatsWriteFailed {
+            error: e.to_string(),
+        })?;
+        println!("{}", json);
+    } else {
+        display_schema_stats(&resolved_schema);
+    }
     Ok(ExitDirectives {
         exit_code: 0,
-        quiet_mode: false,
+        quiet_mode: json_output,
     })
 }
 
-fn display_semconv_registry_stats(semconv_registry: &SemConvRegistry) {
-    let stats = semconv_registry.stats();
+/// Writes the report of the unused definitions of the registry to the given
+/// file, in JSON or in a human-readable format depending on its extension,
+/// and returns the number of unused definitions.
+fn write_unused_report(
+    registry: &SemConvRegistry,
+    path: &Path,
+) -> Result<usize, DiagnosticMessages> {
+    let report = UnusedReport::try_from_registry(registry)?;
+    let content = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::to_string_pretty(&report).map_err(|e| Error::UnusedReportWriteFailed {
+            file: path.to_path_buf(),
+            error: e.to_string(),
+        })?
+    } else {
+        report.to_string()
+    };
+    std::fs::write(path, content).map_err(|e| Error::UnusedReportWriteFailed {
+        file: path.to_path_buf(),
+        error: e.to_string(),
+    })?;
+    Ok(report.items.len())
+}
+
+fn display_semconv_registry_stats(stats: &SemConvStats) {
     println!("Semantic Convention Registry Stats:");
     println!("  - Total number of files: {}", stats.file_count);
 }
@@ -162,12 +267,35 @@ fn display_schema_stats(schema: &ResolvedTelemetrySchema) {
             println!("      - {}: {}", stability, count);
         }
     }
+    if !catalog_stats.enum_card_breakdown.is_empty() {
+        println!(
+            "    - [(enum card: frequency), ...]: [{}]",
+            catalog_stats
+                .enum_card_breakdown
+                .iter()
+                .map(|(card, count)| format!("{}: {}", *card, *count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
     if catalog_stats.deprecated_count > 0 {
         println!(
             "    - Total number of deprecated attributes: {} ({}%)",
             catalog_stats.deprecated_count,
             catalog_stats.deprecated_count * 100 / catalog_stats.attribute_count
         );
+        println!("    - Deprecated attributes by namespace:");
+        for (namespace, namespace_stats) in catalog_stats.namespace_breakdown.iter() {
+            if namespace_stats.deprecated_count > 0 {
+                println!(
+                    "      - {}: {}/{} ({:.0}%)",
+                    namespace,
+                    namespace_stats.deprecated_count,
+                    namespace_stats.attribute_count,
+                    namespace_stats.deprecated_ratio * 100.0
+                );
+            }
+        }
     }
 }
 
@@ -6,7 +6,7 @@
 use crate::registry::RegistryArgs;
 use crate::{DiagnosticArgs, ExitDirectives};
 use clap::Args;
-use weaver_cache::RegistryRepo;
+use weaver_cache::RegistryCache;
 use weaver_common::diagnostic::{is_future_mode_enabled, DiagnosticMessages};
 use weaver_common::Logger;
 use weaver_forge::config::{Params, WeaverConfig};
@@ -55,6 +55,7 @@ pub struct RegistryUpdateMarkdownArgs {
 pub(crate) fn command(
     log: impl Logger + Sync + Clone,
     args: &RegistryUpdateMarkdownArgs,
+    registry_cache: &RegistryCache,
 ) -> Result<ExitDirectives, DiagnosticMessages> {
     fn is_markdown(entry: &walkdir::DirEntry) -> bool {
         let path = entry.path();
@@ -74,11 +75,9 @@ pub(crate) fn command(
         TemplateEngine::new(config, loader, Params::default())
     };
 
-    let registry_path = &args.registry.registry;
-
-    let registry_repo = RegistryRepo::try_new("main", registry_path)?;
-    let generator = SnippetGenerator::try_from_registry_repo(
-        &registry_repo,
+    let registry_repos = args.registry.registry_repos("main", registry_cache, &log)?;
+    let generator = SnippetGenerator::try_from_registry_repos(
+        &registry_repos,
         generator,
         &mut diag_msgs,
         args.registry.follow_symlinks,
@@ -131,6 +130,7 @@ pub(crate) fn command(
 
 #[cfg(test)]
 mod tests {
+    use tempdir::TempDir;
     use weaver_common::TestLogger;
 
     use crate::cli::{Cli, Commands};
@@ -141,18 +141,29 @@ mod tests {
     #[test]
     fn test_registry_update_markdown() {
         let logger = TestLogger::new();
+        let cache_dir = TempDir::new("cache").expect("Failed to create temp dir");
         let cli = Cli {
             debug: 0,
             quiet: false,
             future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
             command: Some(Commands::Registry(RegistryCommand {
                 command: RegistrySubCommand::UpdateMarkdown(RegistryUpdateMarkdownArgs {
                     markdown_dir: "data/update_markdown/markdown".to_owned(),
                     registry: RegistryArgs {
-                        registry: RegistryPath::LocalFolder {
+                        registry: vec![RegistryPath::LocalFolder {
                             path: "data/update_markdown/registry".to_owned(),
-                        },
+                        }],
                         follow_symlinks: false,
+                        no_resolution_cache: false,
+                        resolution_cache_dir: Some(cache_dir.path().join("resolution_cache")),
+                        include_spec: vec![],
+                        exclude_spec: vec![],
+                        prose_lint: vec![],
                     },
                     dry_run: true,
                     attribute_registry_base_url: Some("/docs/attributes-registry".to_owned()),
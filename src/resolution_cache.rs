@@ -0,0 +1,593 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-disk cache of the resolved telemetry schemas.
+//!
+//! A resolved schema is stored in `~/.weaver/resolution_cache` under a key
+//! derived from the Weaver version, the registry id, the resolution options
+//! (including the diagnostic severity overrides), and the provenance and
+//! content of every spec file of the registry (the raw bytes of the files when
+//! the source locations are recorded). Any change to one of these inputs
+//! produces a different key, so stale entries are never reused. The prose
+//! lint rules are not part of the key: they are checked on the loaded specs,
+//! before the cache is looked up, and don't change the resolved schema.
+//!
+//! The provenance of the spec files is hashed relative to the registry they
+//! were loaded from, so the registries extracted or assembled in a temporary
+//! directory (archives, overlays) produce the same key on every run. For the
+//! same reason, the entries record these relative provenances, and the
+//! provenances of the current run are restored when an entry is loaded.
+//!
+//! The entries not used for [`MAX_AGE`] are evicted, then the least recently
+//! used entries until the cache is smaller than [`MAX_SIZE`]. The `cache`
+//! commands also list, prune, and clear the entries of this cache.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use sha2::{Digest, Sha256};
+use weaver_cache::RegistryRepo;
+use weaver_resolved_schema::ResolvedTelemetrySchema;
+use weaver_semconv::registry::SemConvRegistry;
+
+use crate::registry::ResolutionArgs;
+
+/// Directory of the resolution cache, relative to the home directory.
+const CACHE_DIR: &str = ".weaver/resolution_cache";
+
+/// Duration after which an unused entry is evicted (30 days).
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Total size of the entries above which the least recently used entries are
+/// evicted (512 MiB).
+const MAX_SIZE: u64 = 512 * 1024 * 1024;
+
+/// A directory of resolved telemetry schemas indexed by the hash of their
+/// inputs.
+pub(crate) struct ResolutionCache {
+    dir: PathBuf,
+    max_age: Duration,
+    max_size: u64,
+}
+
+impl ResolutionCache {
+    /// Creates a resolution cache stored in the given directory.
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_age: MAX_AGE,
+            max_size: MAX_SIZE,
+        }
+    }
+
+    /// Creates the resolution cache stored in the home directory of the user,
+    /// or returns `None` if the home directory can't be determined.
+    pub(crate) fn try_default() -> Option<Self> {
+        dirs::home_dir().map(|home| Self::new(home.join(CACHE_DIR)))
+    }
+
+    /// Returns the cache key of the resolution of the given registry, loaded
+    /// from the given registry repositories, with the given options.
+    pub(crate) fn key(
+        registry: &SemConvRegistry,
+        registry_repos: &[RegistryRepo],
+        resolution_args: &ResolutionArgs,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        let mut update = |value: &[u8]| {
+            // Length-prefixed to avoid collisions between adjacent values.
+            hasher.update((value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        };
+
+        update(env!("CARGO_PKG_VERSION").as_bytes());
+        update(registry.id().as_bytes());
+        update(&(resolution_args.include_groups.len() as u64).to_le_bytes());
+        for pattern in resolution_args.include_groups.iter() {
+            update(pattern.as_bytes());
+        }
+        update(&[
+            u8::from(resolution_args.strip_deprecated),
+            u8::from(resolution_args.source_locations),
+        ]);
+        update(
+            serde_json::to_string(&resolution_args.min_stability)
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        // The severity overrides select the resolution errors reported (or
+        // ignored), hence whether the resolution succeeds.
+        update(
+            serde_json::to_string(&resolution_args.severity_overrides)
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        for (provenance, spec) in registry.semconv_spec_iter() {
+            update(logical_provenance(provenance, registry_repos).as_bytes());
+            update(&serde_json::to_vec(spec).unwrap_or_default());
+            // The source locations are computed from the spec files, so
+            // moving a definition (e.g. adding a comment before it) must
+            // produce a different key.
+            if resolution_args.source_locations {
+                update(&fs::read(provenance).unwrap_or_default());
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Returns the resolved schema stored under the given key, or `None` if
+    /// there is no such entry or if it can't be read. The provenances of the
+    /// groups are those of the spec files of the given registry.
+    pub(crate) fn load(
+        &self,
+        key: &str,
+        registry: &SemConvRegistry,
+        registry_repos: &[RegistryRepo],
+    ) -> Option<ResolvedTelemetrySchema> {
+        let file = fs::File::open(self.entry_path(key)).ok()?;
+        // The modification time of an entry is its last use (see `evict`).
+        _ = file.set_modified(SystemTime::now());
+        let mut schema = serde_json::from_reader(std::io::BufReader::new(file)).ok()?;
+        let provenances = logical_provenances(registry, registry_repos)
+            .into_iter()
+            .map(|(provenance, logical)| (logical, provenance))
+            .collect();
+        replace_provenances(&mut schema, &provenances);
+        Some(schema)
+    }
+
+    /// Stores the resolved schema of the given registry under the given key.
+    ///
+    /// The entry is written to a temporary file first and then renamed, so
+    /// concurrent invocations never read a partially written entry. The
+    /// unused entries are evicted once the entry is stored.
+    pub(crate) fn store(
+        &self,
+        key: &str,
+        schema: &mut ResolvedTelemetrySchema,
+        registry: &SemConvRegistry,
+        registry_repos: &[RegistryRepo],
+    ) -> Result<(), std::io::Error> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp_path = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        // The entry records the logical provenances, and the schema is
+        // restored once serialized.
+        let provenances = logical_provenances(registry, registry_repos);
+        replace_provenances(schema, &provenances);
+        let content = serde_json::to_vec(schema);
+        replace_provenances(
+            schema,
+            &provenances
+                .into_iter()
+                .map(|(provenance, logical)| (logical, provenance))
+                .collect(),
+        );
+        fs::write(&tmp_path, content?)?;
+        fs::rename(&tmp_path, self.entry_path(key)).inspect_err(|_| {
+            _ = fs::remove_file(&tmp_path);
+        })?;
+        self.evict().map(|_| ())
+    }
+
+    /// Removes the entries (and the temporary files left by interrupted
+    /// invocations) not used for the maximum age, then the least recently
+    /// used entries until the total size of the cache is below the maximum
+    /// size. Returns the number of removed files.
+    pub(crate) fn evict(&self) -> Result<usize, std::io::Error> {
+        self.prune(Some(self.max_age), Some(self.max_size))
+            .map(|(removed, _)| removed)
+    }
+
+    /// Returns the directory of the cache.
+    pub(crate) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the number of entries of the cache and their total size, in
+    /// bytes. A missing cache directory is an empty cache.
+    pub(crate) fn usage(&self) -> Result<(usize, u64), std::io::Error> {
+        let entries: Vec<_> = self
+            .files()?
+            .into_iter()
+            .filter(|(path, _, _)| is_entry(path))
+            .collect();
+        Ok((entries.len(), entries.iter().map(|(_, _, len)| len).sum()))
+    }
+
+    /// Removes the entries not used for `older_than`, then the least recently
+    /// used entries until the total size of the cache is at most `max_size`
+    /// bytes. The temporary files of the entries being stored are kept, unless
+    /// they are older than the maximum age. Returns the number of removed
+    /// files and their total size, in bytes.
+    pub(crate) fn prune(
+        &self,
+        older_than: Option<Duration>,
+        max_size: Option<u64>,
+    ) -> Result<(usize, u64), std::io::Error> {
+        let now = SystemTime::now();
+        let mut files = self.files()?;
+        // Most recently used first.
+        files.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+
+        let mut removed = 0;
+        let mut removed_size = 0;
+        let mut size = 0;
+        for (path, modified, len) in files {
+            let age = now.duration_since(modified).unwrap_or_default();
+            let remove = if is_entry(&path) {
+                older_than.is_some_and(|older_than| age > older_than)
+                    || max_size.is_some_and(|max_size| size + len > max_size)
+            } else {
+                age > self.max_age
+            };
+            if remove {
+                // Another invocation may have removed it already.
+                if fs::remove_file(&path).is_ok() {
+                    removed += 1;
+                    removed_size += len;
+                }
+            } else if is_entry(&path) {
+                size += len;
+            }
+        }
+        Ok((removed, removed_size))
+    }
+
+    /// Removes all the entries of the cache. Returns the number of removed
+    /// entries and their total size, in bytes.
+    pub(crate) fn clear(&self) -> Result<(usize, u64), std::io::Error> {
+        self.prune(None, Some(0))
+    }
+
+    /// Returns the files of the cache with their modification time and size.
+    fn files(&self) -> Result<Vec<(PathBuf, SystemTime, u64)>, std::io::Error> {
+        let dir_entries = match fs::read_dir(&self.dir) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            dir_entries => dir_entries?,
+        };
+        let mut files = vec![];
+        for entry in dir_entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                files.push((entry.path(), metadata.modified()?, metadata.len()));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Returns the path of the entry stored under the given key.
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+/// Returns true if the file is an entry of the cache, not a temporary file.
+fn is_entry(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Returns the provenance of a spec file relative to the registry it was
+/// loaded from (e.g. `<registry_path>/http/spans.yaml`), or the provenance
+/// itself if it's not in one of the registries.
+fn logical_provenance(provenance: &str, registry_repos: &[RegistryRepo]) -> String {
+    registry_repos
+        .iter()
+        .find_map(|repo| {
+            let relative = Path::new(provenance).strip_prefix(repo.path()).ok()?;
+            Some(format!(
+                "{}/{}",
+                repo.registry_path_repr(),
+                relative.to_string_lossy().replace('\\', "/")
+            ))
+        })
+        .unwrap_or_else(|| provenance.to_owned())
+}
+
+/// Returns the logical provenance of the spec files of the registry indexed
+/// by their provenance, as recorded in the lineage of the resolved groups.
+fn logical_provenances(
+    registry: &SemConvRegistry,
+    registry_repos: &[RegistryRepo],
+) -> HashMap<String, String> {
+    registry
+        .semconv_spec_iter()
+        .map(|(provenance, _)| {
+            (
+                provenance.replace('\\', "/"),
+                logical_provenance(provenance, registry_repos),
+            )
+        })
+        .collect()
+}
+
+/// Replaces the provenance of the resolved groups found in the given map.
+fn replace_provenances(
+    schema: &mut ResolvedTelemetrySchema,
+    provenances: &HashMap<String, String>,
+) {
+    for registry in schema.registries.values_mut() {
+        for lineage in registry
+            .groups
+            .iter_mut()
+            .filter_map(|group| group.lineage.as_mut())
+        {
+            if let Some(provenance) = provenances.get(lineage.source_file()) {
+                lineage.set_source_file(provenance);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use weaver_cache::registry_path::RegistryPath;
+    use weaver_cache::RegistryCache;
+    use weaver_common::diagnostic::{SeverityOverride, SeverityOverrides};
+    use weaver_resolver::SchemaResolver;
+    use weaver_semconv::stability::Stability;
+
+    use super::*;
+
+    const SPEC: &str = "
+groups:
+    - id: registry.test
+      type: attribute_group
+      brief: \"Registry\"
+      attributes:
+        - id: test.attr
+          type: string
+          brief: \"Attribute\"
+          examples: [\"a\"]
+";
+
+    fn registry(brief: &str) -> SemConvRegistry {
+        let mut registry = SemConvRegistry::new("test");
+        registry
+            .add_semconv_spec_from_string("<str>", &SPEC.replace("Registry", brief))
+            .into_result_failing_non_fatal()
+            .expect("Failed to load the registry");
+        registry
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let default_args = ResolutionArgs::default();
+        let key = ResolutionCache::key(&registry("Registry"), &[], &default_args);
+        assert_eq!(
+            key,
+            ResolutionCache::key(&registry("Registry"), &[], &default_args)
+        );
+        assert_ne!(
+            key,
+            ResolutionCache::key(&registry("Other registry"), &[], &default_args)
+        );
+        assert_ne!(
+            key,
+            ResolutionCache::key(
+                &registry("Registry"),
+                &[],
+                &ResolutionArgs {
+                    min_stability: Some(Stability::Stable),
+                    ..ResolutionArgs::default()
+                }
+            )
+        );
+        assert_ne!(
+            key,
+            ResolutionCache::key(
+                &registry("Registry"),
+                &[],
+                &ResolutionArgs {
+                    severity_overrides: SeverityOverrides::from_iter([(
+                        "duplicate_group_id".to_owned(),
+                        SeverityOverride::Ignore
+                    )]),
+                    ..ResolutionArgs::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_cache_key_logical_provenance() {
+        let temp_dir = tempdir::TempDir::new("weaver").expect("Failed to create temp dir");
+        let base = temp_dir.path().join("base");
+        let overlay = temp_dir.path().join("overlay");
+        fs::create_dir_all(&base).expect("Failed to create the registry");
+        fs::create_dir_all(&overlay).expect("Failed to create the overlay");
+        fs::write(base.join("registry.yaml"), SPEC).expect("Failed to write the spec file");
+        let registry_path = RegistryPath::Overlay {
+            base: Box::new(RegistryPath::LocalFolder {
+                path: base.to_string_lossy().into_owned(),
+            }),
+            overlay: overlay.to_string_lossy().into_owned(),
+        };
+
+        // Each run assembles the overlaid registry in its own temporary
+        // directory.
+        let load = || {
+            let registry_cache = RegistryCache::try_default()
+                .expect("Failed to create the cache")
+                .with_dir(temp_dir.path().join("registries"));
+            let repo = RegistryRepo::try_new("main", &registry_path, &registry_cache)
+                .expect("Failed to create the registry");
+            let mut registry = SemConvRegistry::new("test");
+            registry
+                .add_semconv_spec_from_string(
+                    &repo.path().join("registry.yaml").to_string_lossy(),
+                    SPEC,
+                )
+                .into_result_failing_non_fatal()
+                .expect("Failed to load the registry");
+            let repos = [repo];
+            (
+                ResolutionCache::key(&registry, &repos, &ResolutionArgs::default()),
+                registry,
+                repos,
+            )
+        };
+        let (key, mut registry, repos) = load();
+        let (other_key, other_registry, other_repos) = load();
+        assert_ne!(repos[0].path(), other_repos[0].path());
+        assert_eq!(key, other_key);
+
+        // The entry loaded by another run records the provenance of its own
+        // spec files.
+        let cache = ResolutionCache::new(temp_dir.path().join("resolution_cache"));
+        let mut schema = SchemaResolver::resolve_semantic_convention_registry(&mut registry)
+            .expect("Failed to resolve the registry");
+        cache
+            .store(&key, &mut schema, &registry, &repos)
+            .expect("Failed to store the schema");
+        let provenance = |schema: &ResolvedTelemetrySchema| {
+            schema.registry("test").expect("Registry not found").groups[0]
+                .provenance()
+                .to_owned()
+        };
+        let other_provenance = other_repos[0]
+            .path()
+            .join("registry.yaml")
+            .to_string_lossy()
+            .replace('\\', "/");
+        assert_ne!(provenance(&schema), other_provenance);
+        let cached_schema = cache
+            .load(&key, &other_registry, &other_repos)
+            .expect("Schema not found in the cache");
+        assert_eq!(provenance(&cached_schema), other_provenance);
+    }
+
+    #[test]
+    fn test_cache_key_source_locations() {
+        let temp_dir = tempdir::TempDir::new("weaver").expect("Failed to create temp dir");
+        let path = temp_dir.path().join("registry.yaml");
+        let registry = |content: &str| {
+            fs::write(&path, content).expect("Failed to write the spec file");
+            let mut registry = SemConvRegistry::new("test");
+            registry
+                .add_semconv_spec_from_string(&path.to_string_lossy(), content)
+                .into_result_failing_non_fatal()
+                .expect("Failed to load the registry");
+            (
+                ResolutionCache::key(&registry, &[], &ResolutionArgs::default()),
+                ResolutionCache::key(
+                    &registry,
+                    &[],
+                    &ResolutionArgs {
+                        source_locations: true,
+                        ..ResolutionArgs::default()
+                    },
+                ),
+            )
+        };
+
+        // A comment moves the group without changing its definition.
+        let (key, key_with_locations) = registry(SPEC);
+        let (moved_key, moved_key_with_locations) = registry(&format!("# Comment\n{SPEC}"));
+        assert_eq!(key, moved_key);
+        assert_ne!(key_with_locations, moved_key_with_locations);
+    }
+
+    #[test]
+    fn test_cache_store_and_load() {
+        let temp_dir = tempdir::TempDir::new("weaver").expect("Failed to create temp dir");
+        let cache = ResolutionCache::new(temp_dir.path().join("resolution_cache"));
+        let mut registry = registry("Registry");
+        let key = ResolutionCache::key(&registry, &[], &ResolutionArgs::default());
+        assert!(cache.load(&key, &registry, &[]).is_none());
+
+        let mut schema = SchemaResolver::resolve_semantic_convention_registry(&mut registry)
+            .expect("Failed to resolve the registry");
+        cache
+            .store(&key, &mut schema, &registry, &[])
+            .expect("Failed to store the schema");
+
+        let cached_schema = cache
+            .load(&key, &registry, &[])
+            .expect("Schema not found in the cache");
+        assert_eq!(
+            serde_json::to_value(&cached_schema).unwrap(),
+            serde_json::to_value(&schema).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cache_eviction() {
+        let temp_dir = tempdir::TempDir::new("weaver").expect("Failed to create temp dir");
+        let dir = temp_dir.path().join("resolution_cache");
+        let mut registry = registry("Registry");
+        let mut schema = SchemaResolver::resolve_semantic_convention_registry(&mut registry)
+            .expect("Failed to resolve the registry");
+        let entry_size = serde_json::to_vec(&schema).unwrap().len() as u64;
+        let cache = ResolutionCache {
+            dir: dir.clone(),
+            max_age: MAX_AGE,
+            max_size: 2 * entry_size,
+        };
+        let set_age = |key: &str, age: Duration| {
+            fs::File::options()
+                .write(true)
+                .open(cache.entry_path(key))
+                .and_then(|file| file.set_modified(SystemTime::now() - age))
+                .expect("Failed to set the modification time");
+        };
+
+        // The least recently used entry is evicted once the cache is full.
+        cache
+            .store("a", &mut schema, &registry, &[])
+            .expect("Failed to store the schema");
+        set_age("a", Duration::from_secs(20));
+        cache
+            .store("b", &mut schema, &registry, &[])
+            .expect("Failed to store the schema");
+        set_age("b", Duration::from_secs(10));
+        assert!(cache.load("a", &registry, &[]).is_some());
+        cache
+            .store("c", &mut schema, &registry, &[])
+            .expect("Failed to store the schema");
+        assert!(cache.load("a", &registry, &[]).is_some());
+        assert!(cache.load("b", &registry, &[]).is_none());
+        assert!(cache.load("c", &registry, &[]).is_some());
+
+        // The entries not used for the maximum age are evicted.
+        set_age("a", MAX_AGE + Duration::from_secs(1));
+        fs::write(dir.join("d.1234.tmp"), "").expect("Failed to write a temporary file");
+        assert_eq!(cache.evict().ok(), Some(1));
+        assert!(cache.load("a", &registry, &[]).is_none());
+        assert!(cache.load("c", &registry, &[]).is_some());
+        assert!(dir.join("d.1234.tmp").exists());
+    }
+
+    #[test]
+    fn test_cache_prune_and_clear() {
+        let temp_dir = tempdir::TempDir::new("weaver").expect("Failed to create temp dir");
+        let dir = temp_dir.path().join("resolution_cache");
+        let cache = ResolutionCache::new(dir.clone());
+        assert_eq!(cache.usage().ok(), Some((0, 0)));
+        assert_eq!(cache.clear().ok(), Some((0, 0)));
+
+        fs::create_dir_all(&dir).expect("Failed to create the cache");
+        for (key, age) in [("a", 7200), ("b", 3600), ("c", 10)] {
+            fs::write(cache.entry_path(key), "{}").expect("Failed to write an entry");
+            fs::File::options()
+                .write(true)
+                .open(cache.entry_path(key))
+                .and_then(|file| file.set_modified(SystemTime::now() - Duration::from_secs(age)))
+                .expect("Failed to set the modification time");
+        }
+        // An entry being stored by another invocation.
+        fs::write(dir.join("d.1234.tmp"), "{}").expect("Failed to write a temporary file");
+        assert_eq!(cache.usage().ok(), Some((3, 6)));
+
+        assert_eq!(
+            cache.prune(Some(Duration::from_secs(5000)), None).ok(),
+            Some((1, 2))
+        );
+        assert_eq!(cache.prune(None, Some(2)).ok(), Some((1, 2)));
+        assert!(cache.entry_path("c").exists());
+        assert_eq!(cache.clear().ok(), Some((1, 2)));
+        assert_eq!(cache.usage().ok(), Some((0, 0)));
+        assert!(dir.join("d.1234.tmp").exists());
+    }
+}
@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Install a template package.
+
+use std::fs;
+use std::path::Path;
+
+use clap::Args;
+use walkdir::WalkDir;
+
+use weaver_cache::registry_path::RegistryPath;
+use weaver_cache::{RegistryCache, RegistryRepo};
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::Logger;
+
+use crate::template::package::{weaver_version, PackageManifest};
+use crate::template::{Error, PackagesDirArgs};
+use crate::{DiagnosticArgs, ExitDirectives};
+
+/// Parameters for the `template install` sub-command
+#[derive(Debug, Args)]
+pub struct TemplateInstallArgs {
+    /// Local folder, local archive, remote archive URL, or Git repo URL of the template package.
+    /// A sub-folder can be specified using the `[sub-folder]` syntax after the path or URL.
+    pub package: RegistryPath,
+
+    /// Install the package even if it is already installed or not compatible with the current
+    /// version of Weaver.
+    #[arg(long, default_value = "false")]
+    pub force: bool,
+
+    /// Directory where the template packages are installed.
+    #[command(flatten)]
+    pub packages_dir: PackagesDirArgs,
+
+    /// Parameters to specify the diagnostic format.
+    #[command(flatten)]
+    pub diagnostic: DiagnosticArgs,
+}
+
+/// Install a template package.
+pub(crate) fn command(
+    logger: impl Logger + Sync + Clone,
+    args: &TemplateInstallArgs,
+    registry_cache: &RegistryCache,
+) -> Result<ExitDirectives, DiagnosticMessages> {
+    logger.loading(&format!(
+        "Installing the template package `{}`",
+        args.package
+    ));
+
+    let package_repo = RegistryRepo::try_new("template_package", &args.package, registry_cache)
+        .map_err(|e| Error::PackageFetchFailed {
+            package: args.package.to_string(),
+            error: e.to_string(),
+        })?;
+    let manifest = PackageManifest::try_from_dir(package_repo.path())?;
+
+    let weaver_version = weaver_version();
+    if !args.force && !manifest.is_compatible_with(&weaver_version) {
+        return Err(Error::IncompatiblePackage {
+            package: manifest.id(),
+            requirement: manifest
+                .weaver_version
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            weaver_version: weaver_version.to_string(),
+        }
+        .into());
+    }
+
+    let install_dir = manifest.install_dir(&args.packages_dir.packages_dir()?);
+    if install_dir.exists() {
+        if !args.force {
+            return Err(Error::PackageAlreadyInstalled {
+                package: manifest.id(),
+                path: install_dir,
+            }
+            .into());
+        }
+        fs::remove_dir_all(&install_dir).map_err(|e| Error::PackageInstallFailed {
+            package: manifest.id(),
+            path: install_dir.clone(),
+            error: e.to_string(),
+        })?;
+    }
+
+    copy_dir(package_repo.path(), &install_dir).map_err(|e| Error::PackageInstallFailed {
+        package: manifest.id(),
+        path: install_dir.clone(),
+        error: e.to_string(),
+    })?;
+
+    logger.success(&format!(
+        "Template package `{}` installed in {:?}",
+        manifest.id(),
+        install_dir
+    ));
+    Ok(ExitDirectives {
+        exit_code: 0,
+        quiet_mode: false,
+    })
+}
+
+/// Recursively copies the content of a directory, the `.git` directory is ignored.
+/// The files are written as new files, so they are writable even if the cached
+/// files are read-only.
+fn copy_dir(src: &Path, dst: &Path) -> std::io::Result<()> {
+    for entry in WalkDir::new(src)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+    {
+        let entry = entry.map_err(std::io::Error::other)?;
+        let relative_path = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(std::io::Error::other)?;
+        let target = dst.join(relative_path);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::write(&target, fs::read(entry.path())?)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use tempdir::TempDir;
+
+    use weaver_common::TestLogger;
+
+    use crate::cli::{Cli, Commands};
+    use crate::run_command;
+    use crate::template::install::TemplateInstallArgs;
+    use crate::template::list::TemplateListArgs;
+    use crate::template::{PackagesDirArgs, TemplateCommand, TemplateSubCommand};
+
+    fn write_package(dir: &Path, manifest: &str) {
+        fs::create_dir_all(dir.join("registry/markdown")).unwrap();
+        fs::write(dir.join("weaver_package.yaml"), manifest).unwrap();
+        fs::write(dir.join("registry/markdown/weaver.yaml"), "templates: []").unwrap();
+    }
+
+    fn install_cli(package: &Path, packages_dir: &Path, force: bool) -> Cli {
+        Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Template(TemplateCommand {
+                command: TemplateSubCommand::Install(TemplateInstallArgs {
+                    package: package.to_str().unwrap().parse().unwrap(),
+                    force,
+                    packages_dir: PackagesDirArgs {
+                        packages_dir: Some(packages_dir.to_path_buf()),
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_template_install() {
+        let logger = TestLogger::new();
+        let package_dir = TempDir::new("package").unwrap();
+        let packages_dir = TempDir::new("packages").unwrap();
+        write_package(
+            package_dir.path(),
+            "name: markdown\nversion: 1.2.0\nweaver_version: '>=0.1'\n",
+        );
+
+        // First installation.
+        let cli = install_cli(package_dir.path(), packages_dir.path(), false);
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 0);
+        let install_dir = packages_dir.path().join("markdown/1.2.0");
+        assert!(install_dir.join("weaver_package.yaml").exists());
+        assert!(install_dir.join("registry/markdown/weaver.yaml").exists());
+
+        // The package is already installed.
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 1);
+        let cli = install_cli(package_dir.path(), packages_dir.path(), true);
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 0);
+
+        // The installed package is listed.
+        let cli = Cli {
+            debug: 0,
+            quiet: false,
+            future: false,
+            offline: false,
+            registry_cache_ttl: None,
+            write_lockfile: false,
+            locked: false,
+            frozen: false,
+            command: Some(Commands::Template(TemplateCommand {
+                command: TemplateSubCommand::List(TemplateListArgs {
+                    packages_dir: PackagesDirArgs {
+                        packages_dir: Some(packages_dir.path().to_path_buf()),
+                    },
+                    diagnostic: Default::default(),
+                }),
+            })),
+        };
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 0);
+    }
+
+    #[test]
+    fn test_template_install_errors() {
+        let logger = TestLogger::new();
+        let packages_dir = TempDir::new("packages").unwrap();
+
+        // Incompatible package.
+        let package_dir = TempDir::new("package").unwrap();
+        write_package(
+            package_dir.path(),
+            "name: markdown\nversion: 1.0.0\nweaver_version: '<0.1'\n",
+        );
+        let cli = install_cli(package_dir.path(), packages_dir.path(), false);
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 1);
+        let cli = install_cli(package_dir.path(), packages_dir.path(), true);
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 0);
+
+        // Invalid package name.
+        let package_dir = TempDir::new("package").unwrap();
+        write_package(package_dir.path(), "name: ../markdown\nversion: 1.0.0\n");
+        let cli = install_cli(package_dir.path(), packages_dir.path(), false);
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 1);
+
+        // Missing manifest.
+        let package_dir = TempDir::new("package").unwrap();
+        let cli = install_cli(package_dir.path(), packages_dir.path(), false);
+        assert_eq!(run_command(&cli, logger.clone()).exit_code, 1);
+    }
+}
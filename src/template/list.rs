@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! List the installed template packages.
+
+use clap::Args;
+
+use weaver_common::diagnostic::DiagnosticMessages;
+use weaver_common::Logger;
+
+use crate::template::package::{installed_packages, weaver_version};
+use crate::template::PackagesDirArgs;
+use crate::{DiagnosticArgs, ExitDirectives};
+
+/// Parameters for the `template list` sub-command
+#[derive(Debug, Args)]
+pub struct TemplateListArgs {
+    /// Directory where the template packages are installed.
+    #[command(flatten)]
+    pub packages_dir: PackagesDirArgs,
+
+    /// Parameters to specify the diagnostic format.
+    #[command(flatten)]
+    pub diagnostic: DiagnosticArgs,
+}
+
+/// List the installed template packages.
+pub(crate) fn command(
+    logger: impl Logger + Sync + Clone,
+    args: &TemplateListArgs,
+) -> Result<ExitDirectives, DiagnosticMessages> {
+    let packages_dir = args.packages_dir.packages_dir()?;
+    let packages = installed_packages(&packages_dir)?;
+
+    if packages.is_empty() {
+        logger.log(&format!(
+            "No template packages installed in {:?}",
+            packages_dir
+        ));
+    }
+
+    let weaver_version = weaver_version();
+    for package in packages {
+        let manifest = &package.manifest;
+        let compatibility = if manifest.is_compatible_with(&weaver_version) {
+            ""
+        } else {
+            " (incompatible with this version of Weaver)"
+        };
+        logger.log(&format!(
+            "{} {}{} - {}",
+            manifest.name,
+            manifest.version,
+            compatibility,
+            package.path.display()
+        ));
+        if let Some(description) = &manifest.description {
+            logger.log(&format!("  {}", description));
+        }
+    }
+
+    Ok(ExitDirectives {
+        exit_code: 0,
+        quiet_mode: false,
+    })
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Commands to manage template packages.
+//!
+//! A template package is a directory or an archive (`.tar`, `.tar.gz`, `.tgz`, `.tar.zst`, or
+//! `.zip`) containing a `weaver_package.yaml` manifest and a set of targets organized as a
+//! templates directory (i.e. `registry/<target>/...`). Installed packages are stored by name and version so they
+//! can be pinned and shared across repositories instead of being vendored.
+
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use miette::Diagnostic;
+use serde::Serialize;
+
+use weaver_cache::RegistryCache;
+use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages};
+use weaver_common::Logger;
+
+use crate::template::install::TemplateInstallArgs;
+use crate::template::list::TemplateListArgs;
+use crate::CmdResult;
+
+mod install;
+mod list;
+mod package;
+
+/// Errors emitted by the `template` sub-commands
+#[derive(thiserror::Error, Debug, Serialize, Diagnostic)]
+#[non_exhaustive]
+pub enum Error {
+    /// The template package can't be fetched.
+    #[error("The template package `{package}` can't be fetched: {error}")]
+    PackageFetchFailed { package: String, error: String },
+
+    /// Invalid template package manifest.
+    #[error("Invalid template package manifest `{manifest}`: {error}")]
+    InvalidPackageManifest { manifest: PathBuf, error: String },
+
+    /// The template package is not compatible with the current version of Weaver.
+    #[error("The template package `{package}` requires Weaver `{requirement}` (current version: {weaver_version}). Use `--force` to install it anyway.")]
+    IncompatiblePackage {
+        package: String,
+        requirement: String,
+        weaver_version: String,
+    },
+
+    /// The template package is already installed.
+    #[error("The template package `{package}` is already installed in {path}. Use `--force` to reinstall it.")]
+    PackageAlreadyInstalled { package: String, path: PathBuf },
+
+    /// The template package can't be installed.
+    #[error("The template package `{package}` can't be installed in {path}: {error}")]
+    PackageInstallFailed {
+        package: String,
+        path: PathBuf,
+        error: String,
+    },
+
+    /// The template packages directory can't be determined or read.
+    #[error("The template packages directory can't be accessed: {error}")]
+    PackagesDirNotAccessible { error: String },
+}
+
+impl From<Error> for DiagnosticMessages {
+    fn from(error: Error) -> Self {
+        DiagnosticMessages::new(vec![DiagnosticMessage::new(error)])
+    }
+}
+
+/// Parameters for the `template` command
+#[derive(Debug, Args)]
+pub struct TemplateCommand {
+    /// Define the sub-commands for the `template` command
+    #[clap(subcommand)]
+    pub command: TemplateSubCommand,
+}
+
+/// Sub-commands to manage template packages.
+#[derive(Debug, Subcommand)]
+#[clap(verbatim_doc_comment)]
+pub enum TemplateSubCommand {
+    /// Installs a template package from a local folder, a local archive, a remote archive, or a
+    /// Git repository.
+    ///
+    /// The package must contain a `weaver_package.yaml` manifest defining its name, version, and
+    /// the compatible Weaver versions. The package is installed in `<packages-dir>/<name>/<version>`
+    /// and this directory can be used as the `--templates` directory of `weaver registry generate`.
+    #[clap(verbatim_doc_comment)]
+    Install(TemplateInstallArgs),
+    /// Lists the installed template packages.
+    List(TemplateListArgs),
+}
+
+/// Set of parameters used to specify the directory where the template packages are installed.
+#[derive(Args, Debug, Clone, Default)]
+pub struct PackagesDirArgs {
+    /// Directory where the template packages are installed.
+    /// Default is `$HOME/.weaver/templates`.
+    #[arg(long)]
+    pub packages_dir: Option<PathBuf>,
+}
+
+impl PackagesDirArgs {
+    /// Returns the directory where the template packages are installed.
+    pub(crate) fn packages_dir(&self) -> Result<PathBuf, Error> {
+        match &self.packages_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => dirs::home_dir()
+                .map(|home| home.join(".weaver/templates"))
+                .ok_or(Error::PackagesDirNotAccessible {
+                    error: "Home directory not found".to_owned(),
+                }),
+        }
+    }
+}
+
+/// Manage template packages.
+pub fn template(
+    log: impl Logger + Sync + Clone,
+    command: &TemplateCommand,
+    registry_cache: &RegistryCache,
+) -> CmdResult {
+    match &command.command {
+        TemplateSubCommand::Install(args) => CmdResult::new(
+            install::command(log, args, registry_cache),
+            Some(args.diagnostic.clone()),
+        ),
+        TemplateSubCommand::List(args) => {
+            CmdResult::new(list::command(log, args), Some(args.diagnostic.clone()))
+        }
+    }
+}
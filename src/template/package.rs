@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Template package manifest and installed packages.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::template::Error;
+
+/// Name of the manifest file of a template package.
+pub(crate) const PACKAGE_MANIFEST: &str = "weaver_package.yaml";
+
+/// Manifest of a template package (`weaver_package.yaml`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PackageManifest {
+    /// Name of the package (e.g. `java`).
+    pub(crate) name: String,
+    /// Version of the package (semver).
+    pub(crate) version: Version,
+    /// Optional description of the package.
+    pub(crate) description: Option<String>,
+    /// Weaver versions compatible with this package (semver requirement, e.g. `>=0.12, <0.14`).
+    /// All the versions are compatible if not defined.
+    pub(crate) weaver_version: Option<VersionReq>,
+}
+
+impl PackageManifest {
+    /// Loads the manifest of the package located in the given directory.
+    pub(crate) fn try_from_dir(package_dir: &Path) -> Result<Self, Error> {
+        let manifest = package_dir.join(PACKAGE_MANIFEST);
+        let content = fs::read_to_string(&manifest).map_err(|e| Error::InvalidPackageManifest {
+            manifest: manifest.clone(),
+            error: e.to_string(),
+        })?;
+        let package: PackageManifest =
+            serde_yaml::from_str(&content).map_err(|e| Error::InvalidPackageManifest {
+                manifest: manifest.clone(),
+                error: e.to_string(),
+            })?;
+        if package.name.is_empty()
+            || !package
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+            || package.name.starts_with('.')
+        {
+            return Err(Error::InvalidPackageManifest {
+                manifest,
+                error: format!(
+                    "The package name `{}` must only contain alphanumeric characters, `_`, `-`, or `.`",
+                    package.name
+                ),
+            });
+        }
+        Ok(package)
+    }
+
+    /// Returns the `name@version` identifier of the package.
+    pub(crate) fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    /// Returns true if the package is compatible with the given Weaver version.
+    pub(crate) fn is_compatible_with(&self, weaver_version: &Version) -> bool {
+        self.weaver_version
+            .as_ref()
+            .map_or(true, |requirement| requirement.matches(weaver_version))
+    }
+
+    /// Returns the directory where the package is installed.
+    pub(crate) fn install_dir(&self, packages_dir: &Path) -> PathBuf {
+        packages_dir.join(&self.name).join(self.version.to_string())
+    }
+}
+
+/// An installed template package.
+#[derive(Debug)]
+pub(crate) struct InstalledPackage {
+    /// The manifest of the package.
+    pub(crate) manifest: PackageManifest,
+    /// The directory where the package is installed.
+    pub(crate) path: PathBuf,
+}
+
+/// Returns the template packages installed in the given directory sorted by name and version.
+/// Directories without a valid manifest are ignored.
+pub(crate) fn installed_packages(packages_dir: &Path) -> Result<Vec<InstalledPackage>, Error> {
+    let mut packages = Vec::new();
+    if !packages_dir.exists() {
+        return Ok(packages);
+    }
+    let read_dir = |dir: &Path| {
+        fs::read_dir(dir).map_err(|e| Error::PackagesDirNotAccessible {
+            error: format!("{}: {}", dir.display(), e),
+        })
+    };
+    for name_entry in read_dir(packages_dir)?.flatten() {
+        if !name_entry.path().is_dir() {
+            continue;
+        }
+        for version_entry in read_dir(&name_entry.path())?.flatten() {
+            let path = version_entry.path();
+            if let Ok(manifest) = PackageManifest::try_from_dir(&path) {
+                packages.push(InstalledPackage { manifest, path });
+            }
+        }
+    }
+    packages.sort_by(|a, b| {
+        (&a.manifest.name, &a.manifest.version).cmp(&(&b.manifest.name, &b.manifest.version))
+    });
+    Ok(packages)
+}
+
+/// Returns the version of the current Weaver binary.
+pub(crate) fn weaver_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("The Weaver version must be a valid semver")
+}
@@ -3,22 +3,35 @@
 //! Utility functions for resolving a semantic convention registry and checking policies.
 //! This module supports the `schema` and `registry` commands.
 
+use std::collections::BTreeMap;
+
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde::Serialize;
-use std::path::PathBuf;
-use weaver_cache::RegistryRepo;
+use weaver_cache::registry_path::RegistryPath;
+use weaver_cache::{RegistryCache, RegistryRepo};
+use weaver_checker::baseline::Baseline;
+use weaver_checker::sandbox::Sandbox;
+use weaver_checker::violation::ReportedViolation;
 use weaver_checker::Error::{InvalidPolicyFile, PolicyViolation};
-use weaver_checker::{Engine, Error, PolicyStage, SEMCONV_REGO};
-use weaver_common::diagnostic::{DiagnosticMessages, ResultExt};
+use weaver_checker::{
+    Engine, Error, PolicyStage, CEL_POLICY_GLOB, LINT_POLICY_GLOB, SEMCONV_REGO, WASM_POLICY_GLOB,
+};
+use weaver_common::diagnostic::{DiagnosticMessage, DiagnosticMessages, ResultExt};
 use weaver_common::result::WResult;
 use weaver_common::Logger;
 use weaver_forge::registry::ResolvedRegistry;
 use weaver_resolved_schema::ResolvedTelemetrySchema;
+use weaver_resolver::filter::{GroupFilter, StabilityFilter};
+use weaver_resolver::location::record_source_locations;
 use weaver_resolver::SchemaResolver;
+use weaver_semconv::filter::SpecFilter;
+use weaver_semconv::prose::{lint_semconv_spec, ProseLintRule};
 use weaver_semconv::registry::SemConvRegistry;
 use weaver_semconv::semconv::SemConvSpec;
 
-use crate::registry::{PolicyArgs, RegistryArgs};
+use crate::registry::Error::PolicyCoverageWriteFailed;
+use crate::registry::{PolicyArgs, PolicyCoverageFormat, RegistryArgs, ResolutionArgs};
+use crate::resolution_cache::ResolutionCache;
 
 /// Loads the semantic convention specifications from a registry path.
 ///
@@ -26,6 +39,7 @@ use crate::registry::{PolicyArgs, RegistryArgs};
 ///
 /// * `registry_repo` - The registry repository.
 /// * `log` - The logger for logging messages.
+/// * `filter` - The filter selecting the files and groups to load.
 ///
 /// # Returns
 ///
@@ -35,8 +49,9 @@ pub(crate) fn load_semconv_specs(
     registry_repo: &RegistryRepo,
     log: impl Logger + Sync + Clone,
     follow_symlinks: bool,
+    filter: &SpecFilter,
 ) -> WResult<Vec<(String, SemConvSpec)>, weaver_semconv::Error> {
-    SchemaResolver::load_semconv_specs(registry_repo, follow_symlinks).inspect(
+    SchemaResolver::load_semconv_specs_with_filter(registry_repo, follow_symlinks, filter).inspect(
         |semconv_specs, _| {
             log.success(&format!(
                 "`{}` semconv registry `{}` loaded ({} files)",
@@ -48,25 +63,68 @@ pub(crate) fn load_semconv_specs(
     )
 }
 
-/// Initializes the policy engine with policies from the registry and command line.
+/// Loads the semantic convention specifications from several registries.
 ///
 /// # Arguments
 ///
-/// * `registry_path` - The path to the semantic convention registry.
-/// * `cache` - The cache for loading the registry.
-/// * `policies` - A list of paths to policy files.
+/// * `registry_repos` - The registry repositories.
+/// * `log` - The logger for logging messages.
+/// * `filter` - The filter selecting the files and groups to load.
+/// * `prose_lint` - The lint rules checked on the briefs and notes of the
+///   loaded specs (reported as non-fatal errors).
+///
+/// # Returns
+///
+/// A `Result` containing a vector of tuples with file names and `SemConvSpec` on success,
+/// or a `weaver_resolver::Error` on failure.
+pub(crate) fn load_registries_semconv_specs(
+    registry_repos: &[RegistryRepo],
+    log: impl Logger + Sync + Clone,
+    follow_symlinks: bool,
+    filter: &SpecFilter,
+    prose_lint: &[ProseLintRule],
+) -> WResult<Vec<(String, SemConvSpec)>, weaver_semconv::Error> {
+    let mut semconv_specs = vec![];
+    let mut nfes = vec![];
+    for registry_repo in registry_repos {
+        match load_semconv_specs(registry_repo, log.clone(), follow_symlinks, filter) {
+            WResult::Ok(specs) => semconv_specs.extend(specs),
+            WResult::OkWithNFEs(specs, errors) => {
+                semconv_specs.extend(specs);
+                nfes.extend(errors);
+            }
+            WResult::FatalErr(e) => return WResult::FatalErr(e),
+        }
+    }
+    for (path, spec) in semconv_specs.iter() {
+        nfes.extend(lint_semconv_spec(prose_lint, path, spec));
+    }
+    WResult::with_non_fatal_errors(semconv_specs, nfes)
+}
+
+/// Initializes the policy engine with policies from the registries and command line.
+///
+/// # Arguments
+///
+/// * `registry_repos` - The semantic convention registry repositories.
+/// * `policies` - A list of local or remote policy files, directories, or bundles.
+/// * `registry_cache` - The cache of the remote policy bundles.
 /// * `policy_coverage` - A flag to enable policy coverage.
+/// * `sandbox` - The sandbox of the Rego policies.
 ///
 /// # Returns
 ///
 /// A `Result` containing the initialized `Engine` on success, or `DiagnosticMessages`
 /// on failure.
 pub(crate) fn init_policy_engine(
-    registry_repo: &RegistryRepo,
-    policies: &[PathBuf],
+    registry_repos: &[RegistryRepo],
+    policies: &[RegistryPath],
+    registry_cache: &RegistryCache,
     policy_coverage: bool,
+    sandbox: Sandbox,
 ) -> Result<Engine, DiagnosticMessages> {
     let mut engine = Engine::new();
+    engine.set_sandbox(sandbox);
 
     if policy_coverage {
         engine.enable_coverage();
@@ -78,12 +136,19 @@ pub(crate) fn init_policy_engine(
         .add_policy("defaults/rego/semconv.rego", SEMCONV_REGO)
         .map_err(DiagnosticMessages::from_error)?;
 
-    // Add policies from the registry
-    _ = engine.add_policies(registry_repo.path(), "*.rego")?;
+    // Add policies from the registries
+    for registry_repo in registry_repos {
+        _ = engine.add_policies(registry_repo.path(), "*.rego")?;
+        _ = engine.add_policies(registry_repo.path(), CEL_POLICY_GLOB)?;
+        _ = engine.add_policies(registry_repo.path(), LINT_POLICY_GLOB)?;
+        _ = engine.add_policies(registry_repo.path(), WASM_POLICY_GLOB)?;
+    }
 
-    // Add policies from the command line
+    // Add policies from the command line. The remote policy bundles are
+    // fetched and cached like the registries.
     for policy in policies {
-        engine.add_policy_from_file_or_dir(policy)?;
+        let policy_repo = RegistryRepo::try_new("policy", policy, registry_cache)?;
+        engine.add_policy_from_file_or_dir(policy_repo.path())?;
     }
     Ok(engine)
 }
@@ -118,10 +183,11 @@ pub(crate) fn check_policy_stage<T: Serialize, U: Serialize>(
     match policy_engine.set_input(input) {
         Ok(_) => match policy_engine.check(policy_stage) {
             Ok(violations) => {
+                let fail_on = policy_engine.fail_on();
                 for violation in violations {
                     errors.push(PolicyViolation {
                         provenance: policy_file.to_owned(),
-                        violation,
+                        violation: Box::new(ReportedViolation::new(violation, fail_on)),
                     });
                 }
             }
@@ -169,7 +235,48 @@ pub(crate) fn check_policy(
             )
         })
         .collect::<Vec<WResult<(), Error>>>();
+    merge_policy_results(results)
+}
 
+/// Checks the `after_resolution` policies on each group of a resolved registry
+/// in parallel. Each evaluation gets a registry containing only the group as
+/// input, so the policies checking several groups at once are not supported.
+///
+/// # Arguments
+///
+/// * `policy_engine` - The pre-configured policy engine for checking policies.
+/// * `policy_file` - The policy file to check.
+/// * `registry` - The resolved registry to check.
+pub(crate) fn check_policy_per_group(
+    policy_engine: &Engine,
+    policy_file: &str,
+    registry: &ResolvedRegistry,
+) -> WResult<(), Error> {
+    let results = registry
+        .groups
+        .par_iter()
+        .map(|group| {
+            // Create a local policy engine inheriting the policies
+            // from the global policy engine
+            let mut policy_engine = policy_engine.clone();
+            let group_registry = ResolvedRegistry {
+                registry_url: registry.registry_url.clone(),
+                groups: vec![group.clone()],
+            };
+            check_policy_stage::<ResolvedRegistry, ()>(
+                &mut policy_engine,
+                PolicyStage::AfterResolution,
+                policy_file,
+                &group_registry,
+                &[],
+            )
+        })
+        .collect::<Vec<WResult<(), Error>>>();
+    merge_policy_results(results)
+}
+
+/// Merges the results of policy checks evaluated in parallel.
+fn merge_policy_results(results: Vec<WResult<(), Error>>) -> WResult<(), Error> {
     let mut nfes = vec![];
     for result in results {
         match result {
@@ -185,10 +292,19 @@ pub(crate) fn check_policy(
 
 /// Resolves the semantic convention specifications and returns the resolved schema.
 ///
+/// When a resolution cache is given, a schema previously resolved from the
+/// same specs and options is reused from it, and a newly resolved schema is
+/// stored in it.
+///
 /// # Arguments
 ///
 /// * `registry` - The semantic convention registry to resolve.
+/// * `registry_repos` - The registry repositories the specs were loaded from.
+/// * `resolution_args` - The common CLI args selecting the resolved definitions.
+/// * `resolution_cache` - The resolution cache, if enabled.
 /// * `logger` - The logger for logging messages.
+/// * `diag_msgs` - The diagnostic messages collecting the resolution errors
+///   demoted to warnings.
 ///
 /// # Returns
 ///
@@ -196,12 +312,59 @@ pub(crate) fn check_policy(
 /// `DiagnosticMessages` on failure.
 pub(crate) fn resolve_semconv_specs(
     registry: &mut SemConvRegistry,
+    registry_repos: &[RegistryRepo],
+    resolution_args: &ResolutionArgs,
+    resolution_cache: Option<ResolutionCache>,
     logger: impl Logger + Sync + Clone,
+    diag_msgs: &mut DiagnosticMessages,
 ) -> Result<ResolvedTelemetrySchema, DiagnosticMessages> {
     let registry_id = registry.id().to_owned();
-    let resolved_schema = SchemaResolver::resolve_semantic_convention_registry(registry)?;
+    let cache = resolution_cache.map(|cache| {
+        let key = ResolutionCache::key(registry, registry_repos, resolution_args);
+        (cache, key)
+    });
+
+    if let Some(resolved_schema) = cache
+        .as_ref()
+        .and_then(|(cache, key)| cache.load(key, registry, registry_repos))
+    {
+        logger.success(&format!(
+            "`{}` semconv registry loaded from the resolution cache",
+            registry_id
+        ));
+        return Ok(resolved_schema);
+    }
+
+    let resolver = SchemaResolver::default()
+        .with_severity_overrides(resolution_args.severity_overrides.clone());
+    let (mut resolved_schema, warnings) = if resolution_args.include_groups.is_empty() {
+        resolver.resolve(registry)
+    } else {
+        let filter = GroupFilter::try_new(&resolution_args.include_groups)?;
+        resolver.resolve_with_filter(registry, &filter)
+    }
+    .into_result_with_non_fatal()?;
+    StabilityFilter {
+        strip_deprecated: resolution_args.strip_deprecated,
+        min_stability: resolution_args.min_stability.clone(),
+    }
+    .apply(&mut resolved_schema);
+    if resolution_args.source_locations {
+        record_source_locations(&mut resolved_schema);
+    }
+
+    // A schema resolved with warnings is not cached, so that the warnings are
+    // reported on every run.
+    if let Some((cache, key)) = cache.filter(|_| warnings.is_empty()) {
+        if let Err(e) = cache.store(&key, &mut resolved_schema, registry, registry_repos) {
+            logger.warn(&format!(
+                "Failed to store the resolved registry in the cache: {e}"
+            ));
+        }
+    }
 
     logger.success(&format!("`{}` semconv registry resolved", registry_id));
+    diag_msgs.extend_from_vec(warnings.into_iter().map(DiagnosticMessage::new).collect());
     Ok(resolved_schema)
 }
 
@@ -213,6 +376,10 @@ pub(crate) fn resolve_semconv_specs(
 ///
 /// * `registry_args` - The common CLI args for the main registry.
 /// * `policy_args` - The common CLI args for policies.
+/// * `resolution_args` - The common CLI args selecting the resolved definitions.
+/// * `baseline_registry` - The resolved baseline registry, if only the groups added or
+///   changed since the baseline must be checked by the `after_resolution` policies.
+/// * `policy_params` - The params made available to the policies as `data.weaver.params`.
 /// * `logger` - The logger for logging messages.
 /// * `diag_msgs` - The DiagnosticMessages to append to.
 ///
@@ -223,28 +390,72 @@ pub(crate) fn resolve_semconv_specs(
 pub(crate) fn prepare_main_registry(
     registry_args: &RegistryArgs,
     policy_args: &PolicyArgs,
+    resolution_args: &ResolutionArgs,
+    baseline_registry: Option<&ResolvedRegistry>,
+    policy_params: Option<&BTreeMap<String, serde_yaml::Value>>,
+    registry_cache: &RegistryCache,
     logger: impl Logger + Sync + Clone,
     diag_msgs: &mut DiagnosticMessages,
 ) -> Result<(ResolvedRegistry, Option<Engine>), DiagnosticMessages> {
-    let registry_path = &registry_args.registry;
+    let (_, main_resolved_registry, policy_engine) = prepare_main_schema(
+        registry_args,
+        policy_args,
+        resolution_args,
+        baseline_registry,
+        policy_params,
+        registry_cache,
+        logger,
+        diag_msgs,
+    )?;
+    Ok((main_resolved_registry, policy_engine))
+}
 
-    let main_registry_repo = RegistryRepo::try_new("main", registry_path)?;
+/// Same as [`prepare_main_registry`], but also returns the resolved telemetry
+/// schema the `ResolvedRegistry` is built from.
+pub(crate) fn prepare_main_schema(
+    registry_args: &RegistryArgs,
+    policy_args: &PolicyArgs,
+    resolution_args: &ResolutionArgs,
+    baseline_registry: Option<&ResolvedRegistry>,
+    policy_params: Option<&BTreeMap<String, serde_yaml::Value>>,
+    registry_cache: &RegistryCache,
+    logger: impl Logger + Sync + Clone,
+    diag_msgs: &mut DiagnosticMessages,
+) -> Result<(ResolvedTelemetrySchema, ResolvedRegistry, Option<Engine>), DiagnosticMessages> {
+    let main_registry_id = "main";
+    let main_registry_repos =
+        registry_args.registry_repos(main_registry_id, registry_cache, &logger)?;
+    let main_registry_paths = registry_args.registry_paths();
 
     // Load the semantic convention specs
-    let main_semconv_specs = load_semconv_specs(
-        &main_registry_repo,
+    let main_semconv_specs = load_registries_semconv_specs(
+        &main_registry_repos,
         logger.clone(),
         registry_args.follow_symlinks,
+        &registry_args.spec_filter()?,
+        &registry_args.prose_lint,
     )
     .capture_non_fatal_errors(diag_msgs)?;
 
     // Optionally init policy engine
     let mut policy_engine = if !policy_args.skip_policies {
-        Some(init_policy_engine(
-            &main_registry_repo,
+        let mut engine = init_policy_engine(
+            &main_registry_repos,
             &policy_args.policies,
-            policy_args.display_policy_coverage,
-        )?)
+            registry_cache,
+            policy_args.display_policy_coverage || policy_args.policy_coverage.is_some(),
+            policy_args.sandbox(),
+        )?;
+        engine.set_fail_on(policy_args.fail_on);
+        if policy_args.update_baseline {
+            engine.set_baseline(Baseline::recording());
+        } else if let Some(path) = &policy_args.policy_baseline {
+            engine.set_baseline(Baseline::load(path)?);
+        }
+        if let Some(policy_params) = policy_params {
+            engine.add_params(policy_params)?;
+        }
+        Some(engine)
     } else {
         None
     };
@@ -267,13 +478,20 @@ pub(crate) fn prepare_main_registry(
 
     // Resolve the main registry
     let mut main_registry =
-        SemConvRegistry::from_semconv_specs(main_registry_repo.id(), main_semconv_specs);
-    let main_resolved_schema = resolve_semconv_specs(&mut main_registry, logger.clone())
-        .combine_diag_msgs_with(diag_msgs)?;
+        SemConvRegistry::from_semconv_specs(main_registry_id, main_semconv_specs);
+    let main_resolved_schema = resolve_semconv_specs(
+        &mut main_registry,
+        &main_registry_repos,
+        resolution_args,
+        registry_args.resolution_cache(),
+        logger.clone(),
+        diag_msgs,
+    )
+    .combine_diag_msgs_with(diag_msgs)?;
 
     let main_resolved_registry = ResolvedRegistry::try_from_resolved_registry(
         main_resolved_schema
-            .registry(main_registry_repo.id())
+            .registry(main_registry_id)
             .expect("Failed to get the registry from the resolved schema"),
         main_resolved_schema.catalog(),
     )
@@ -281,25 +499,120 @@ pub(crate) fn prepare_main_registry(
 
     // Check post-resolution policies
     if let Some(engine) = policy_engine.as_mut() {
-        check_policy_stage::<ResolvedRegistry, ()>(
-            engine,
-            PolicyStage::AfterResolution,
-            main_registry_repo.registry_path_repr(),
-            &main_resolved_registry,
-            &[],
-        )
-        .inspect(|_, violations| {
-            if let Some(violations) = violations {
-                logger.success(&format!(
-                    "All `after_resolution` policies checked ({} violations found)",
-                    violations.len()
-                ));
-            } else {
-                logger.success("No `after_resolution` policy violation");
-            }
-        })
-        .capture_non_fatal_errors(diag_msgs)?;
+        let changed_registry = baseline_registry.map(|baseline_registry| {
+            let changed_registry = main_resolved_registry.changed_groups(baseline_registry);
+            logger.log(&format!(
+                "Checking the `after_resolution` policies on the {} groups added or changed since the baseline registry (out of {})",
+                changed_registry.groups.len(),
+                main_resolved_registry.groups.len()
+            ));
+            changed_registry
+        });
+        let registry = changed_registry.as_ref().unwrap_or(&main_resolved_registry);
+        let result = if policy_args.per_group_policies {
+            check_policy_per_group(engine, &main_registry_paths, registry)
+        } else {
+            check_policy_stage::<ResolvedRegistry, ()>(
+                engine,
+                PolicyStage::AfterResolution,
+                &main_registry_paths,
+                registry,
+                &[],
+            )
+        };
+        result
+            .inspect(|_, violations| {
+                if let Some(violations) = violations {
+                    logger.success(&format!(
+                        "All `after_resolution` policies checked ({} violations found)",
+                        violations.len()
+                    ));
+                } else {
+                    logger.success("No `after_resolution` policy violation");
+                }
+            })
+            .capture_non_fatal_errors(diag_msgs)?;
     }
 
-    Ok((main_resolved_registry, policy_engine))
+    Ok((main_resolved_schema, main_resolved_registry, policy_engine))
+}
+
+/// Regenerates the baseline file of the known policy violations if
+/// `--update-baseline` is set, or reports the violations suppressed by the
+/// baseline otherwise. This must be called once all the policy stages have
+/// been checked.
+///
+/// # Arguments
+///
+/// * `policy_args` - The common CLI args for policies.
+/// * `policy_engine` - The policy engine, if the policies are checked.
+/// * `logger` - The logger for logging messages.
+pub(crate) fn update_policy_baseline(
+    policy_args: &PolicyArgs,
+    policy_engine: Option<&Engine>,
+    logger: impl Logger,
+) -> Result<(), DiagnosticMessages> {
+    let (Some(baseline), Some(path)) = (
+        policy_engine.and_then(Engine::baseline),
+        &policy_args.policy_baseline,
+    ) else {
+        return Ok(());
+    };
+    if policy_args.update_baseline {
+        let count = baseline.save(path)?;
+        logger.success(&format!(
+            "Baseline `{}` updated ({} known violations)",
+            path.display(),
+            count
+        ));
+    } else {
+        logger.log(&format!(
+            "{} known violations suppressed by the baseline `{}`, {} fixed",
+            baseline.suppressed_count(),
+            path.display(),
+            baseline.stale_count()
+        ));
+    }
+    Ok(())
+}
+
+/// Displays the coverage report of the Rego policies if
+/// `--display-policy-coverage` is set, and writes it to the `--policy-coverage`
+/// file if any. This must be called once all the policy stages have been
+/// checked.
+///
+/// # Arguments
+///
+/// * `policy_args` - The common CLI args for policies.
+/// * `policy_engine` - The policy engine, if the policies are checked.
+/// * `logger` - The logger for logging messages.
+pub(crate) fn report_policy_coverage(
+    policy_args: &PolicyArgs,
+    policy_engine: Option<&Engine>,
+    logger: impl Logger,
+) -> Result<(), DiagnosticMessages> {
+    let Some(report) = policy_engine.and_then(Engine::coverage_report) else {
+        return Ok(());
+    };
+    if policy_args.display_policy_coverage {
+        logger.log(&report.to_string());
+    }
+    if let Some(path) = &policy_args.policy_coverage {
+        let write_failed = |error: String| PolicyCoverageWriteFailed {
+            file: path.clone(),
+            error,
+        };
+        let content = match policy_args.policy_coverage_format {
+            PolicyCoverageFormat::Json => {
+                report.to_json().map_err(|e| write_failed(e.to_string()))?
+            }
+            PolicyCoverageFormat::Lcov => report.to_lcov(),
+        };
+        std::fs::write(path, content).map_err(|e| write_failed(e.to_string()))?;
+        logger.success(&format!(
+            "Policy coverage report written to `{}`",
+            path.display()
+        ));
+    }
+    Ok(())
 }
@@ -0,0 +1,18 @@
+package before_resolution
+
+import rego.v1
+
+# A period is appended to the attribute briefs that don't end with one.
+deny contains violation if {
+    group := input.groups[_]
+    attr := group.attributes[_]
+    not endswith(attr.brief, ".")
+    violation := {
+        "id": "attr_brief_period",
+        "type": "semconv_attribute",
+        "category": "attribute",
+        "group": group.id,
+        "attr": attr.id,
+        "fix": {"path": "brief", "value": concat("", [attr.brief, "."])},
+    }
+}
@@ -34,6 +34,56 @@ deny contains back_comp_violation(description, group_id, attr_name) if {
     description := sprintf("Attribute '%s' no longer exists in the attribute registry", [attr_name])
 }
 
+# Rule: Detect Renamed Enum Members
+#
+# This rule checks for enum members of the baseline registry whose id
+# changed in the current registry while their value is unchanged.
+deny contains back_comp_violation(description, group_id, attr.name) if {
+    some group in data.groups
+    group_id := group.id
+    some attr in group.attributes
+    some member in attr.type.members
+    not registry_enum_member_ids[attr.name][member.id]
+    some new_id in registry_enum_member_ids_by_value[attr.name][member.value]
+
+    description := sprintf("Enum member '%s' of attribute '%s' has been renamed to '%s'", [member.id, attr.name, new_id])
+}
+
+# Rule: Detect Removed Enum Members
+#
+# This rule checks for enum members of the baseline registry that are no
+# longer present (neither by id nor by value) in an attribute of the current
+# registry. Deprecated members must be kept.
+deny contains back_comp_violation(description, group_id, attr.name) if {
+    some group in data.groups
+    group_id := group.id
+    some attr in group.attributes
+    registry_enum_member_ids[attr.name]
+    some member in attr.type.members
+    not registry_enum_member_ids[attr.name][member.id]
+    not registry_enum_member_ids_by_value[attr.name][member.value]
+
+    description := sprintf("Enum member '%s' of attribute '%s' no longer exists", [member.id, attr.name])
+}
+
+# The ids of the enum members of the current registry by attribute name.
+registry_enum_member_ids[attr_name] contains member.id if {
+    some group in input.groups
+    some attr in group.attributes
+    attr_name := attr.name
+    some member in attr.type.members
+}
+
+# The ids of the enum members of the current registry by attribute name and
+# member value.
+registry_enum_member_ids_by_value[attr_name][value] contains member.id if {
+    some group in input.groups
+    some attr in group.attributes
+    attr_name := attr.name
+    some member in attr.type.members
+    value := member.value
+}
+
 # Helper Function: Create Backward Compatibility Violation Object
 #
 # This function generates a structured violation object for each
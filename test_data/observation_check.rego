@@ -0,0 +1,33 @@
+package observation_after_resolution
+
+import rego.v1
+
+# Every stable attribute of the registry must have been observed.
+deny contains observation_violation("stable_attr_not_observed", group.id, attr.name) if {
+    group := input.registry.groups[_]
+    attr := group.attributes[_]
+    attr.stability == "stable"
+    not attr.name in observed_attributes
+}
+
+# No attribute must be observed outside the registry.
+deny contains observation_violation("attr_not_in_registry", "", name) if {
+    name := observed_attributes[_]
+    not name in registry_attributes
+}
+
+observed_attributes contains attr.name if {
+    attr := input.observations.attributes[_]
+}
+
+registry_attributes contains attr.name if {
+    attr := input.registry.groups[_].attributes[_]
+}
+
+observation_violation(violation_id, group_id, attr_id) := {
+    "id": violation_id,
+    "type": "semconv_attribute",
+    "category": "observation",
+    "group": group_id,
+    "attr": attr_id,
+}
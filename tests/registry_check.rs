@@ -3,6 +3,7 @@
 //! Test the registry check command.
 
 use assert_cmd::Command;
+use tempdir::TempDir;
 
 /// This test checks the CLI interface for the registry generate command.
 /// This test doesn't count for the coverage report as it runs a separate process.
@@ -24,8 +25,11 @@ fn test_cli_interface() {
     // Test a local semantic convention registry.
     // There are policy violations in this registry.
     // This test should fail with a non-zero exit code and display the policy violations.
+    // The resolution cache is stored in a temporary home directory.
+    let home = TempDir::new("home").expect("Failed to create a temporary home");
     let mut cmd = Command::cargo_bin("weaver").unwrap();
     let output = cmd
+        .env("HOME", home.path())
         .arg("--quiet")
         .arg("registry")
         .arg("check")
@@ -49,3 +53,31 @@ fn test_cli_interface() {
     // - 9 violations for http after resolution
     assert_eq!(json_value.len(), 37);
 }
+
+/// This test checks that the violations are grouped by rule id in the ansi output.
+#[test]
+fn test_cli_grouped_violations() {
+    // The resolution cache is stored in a temporary home directory.
+    let home = TempDir::new("home").expect("Failed to create a temporary home");
+    let mut cmd = Command::cargo_bin("weaver").unwrap();
+    let output = cmd
+        .env("HOME", home.path())
+        .arg("--quiet")
+        .arg("registry")
+        .arg("check")
+        .arg("-r")
+        .arg("crates/weaver_codegen_test/semconv_registry/")
+        .arg("--max-violations-per-rule")
+        .arg("1")
+        .timeout(std::time::Duration::from_secs(60))
+        .output()
+        .expect("failed to execute process");
+
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    // Each rule is displayed once with its number of occurrences.
+    assert!(stdout.contains("occurrences)"));
+    // Only the first occurrence of each rule is displayed.
+    assert!(stdout.contains("more (use `--diagnostic-format json` for the full details)"));
+}
@@ -3,6 +3,7 @@
 //! Test the registry generate command.
 
 use assert_cmd::Command;
+use tempdir::TempDir;
 
 /// This test checks the CLI interface for the registry generate command.
 /// This test doesn't count for the coverage report as it runs a separate process.
@@ -22,8 +23,11 @@ fn test_cli_interface() {
     // Test a local semantic convention registry.
     // There are policy violations in this registry.
     // This test should fail with a non-zero exit code and display the policy violations.
+    // The resolution cache is stored in a temporary home directory.
+    let home = TempDir::new("home").expect("Failed to create a temporary home");
     let mut cmd = Command::cargo_bin("weaver").unwrap();
     let output = cmd
+        .env("HOME", home.path())
         .arg("--quiet")
         .arg("registry")
         .arg("generate")
@@ -5,7 +5,7 @@
 use miette::Diagnostic;
 
 use weaver_cache::registry_path::RegistryPath;
-use weaver_cache::RegistryRepo;
+use weaver_cache::{RegistryCache, RegistryRepo};
 use weaver_common::TestLogger;
 use weaver_resolver::attribute::AttributeCatalog;
 use weaver_resolver::registry::resolve_semconv_registry;
@@ -38,9 +38,13 @@ fn test_cli_interface() {
         sub_folder: Some(SEMCONV_REGISTRY_MODEL.to_owned()),
         refspec: None,
     };
-    let registry_repo = RegistryRepo::try_new("main", &registry_path).unwrap_or_else(|e| {
-        panic!("Failed to create the registry repo, error: {e}");
+    let registry_cache = RegistryCache::try_default().unwrap_or_else(|e| {
+        panic!("Failed to create the registry cache, error: {e}");
     });
+    let registry_repo = RegistryRepo::try_new("main", &registry_path, &registry_cache)
+        .unwrap_or_else(|e| {
+            panic!("Failed to create the registry repo, error: {e}");
+        });
     let semconv_specs = SchemaResolver::load_semconv_specs(&registry_repo, false)
         .ignore(|e| matches!(e.severity(), Some(miette::Severity::Warning)))
         .into_result_failing_non_fatal()